@@ -0,0 +1,354 @@
+//! A language-neutral description of the components a host exports, for `wasvy wit --abi`.
+//!
+//! Languages without WIT bindings for resources (or authors who don't want them) can call the
+//! methods of components through `component.invoke` with JSON instead. The ABI lists every
+//! component with its type path and methods, and every record, enum and variant with the names
+//! their fields and cases have in JSON. See `docs/guest-abi.md` for the conventions.
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+use wit_parser::{FunctionKind, Handle, InterfaceId, Resolve, Type, TypeDef, TypeDefKind, TypeId};
+
+/// The ABI of the components of a host
+#[derive(Serialize, Debug)]
+pub struct Abi {
+    /// The WIT package of the components
+    pub package: String,
+
+    pub components: Vec<AbiComponent>,
+    pub types: Vec<AbiType>,
+}
+
+/// A component, which is a WIT resource
+#[derive(Serialize, Debug)]
+pub struct AbiComponent {
+    /// The type path, used to query the component
+    pub type_path: String,
+
+    /// The name of the WIT resource
+    pub name: String,
+    pub interface: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub docs: Option<String>,
+    pub methods: Vec<AbiMethod>,
+}
+
+/// A method of a component, invoked by name with a JSON array of its params
+#[derive(Serialize, Debug)]
+pub struct AbiMethod {
+    pub name: String,
+
+    /// Static methods are invoked with `component.invoke-static` and the type path of the component
+    #[serde(rename = "static")]
+    pub is_static: bool,
+    pub params: Vec<AbiField>,
+
+    /// The WIT type of the result, none if the method returns nothing
+    pub result: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub docs: Option<String>,
+}
+
+/// A named value, such as a param or the field of a record
+#[derive(Serialize, Debug)]
+pub struct AbiField {
+    pub name: String,
+
+    /// The name in JSON, if this is a field or case
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub json: Option<String>,
+
+    /// The WIT type, none for cases without a value
+    #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
+    pub ty: Option<String>,
+}
+
+/// A record, enum or variant used by the methods of components
+#[derive(Serialize, Debug)]
+pub struct AbiType {
+    pub name: String,
+    pub kind: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub type_path: Option<String>,
+
+    /// The fields of records, or the cases of enums and variants
+    pub fields: Vec<AbiField>,
+}
+
+/// Generates the ABI of the components in `wit`, which may use the types of `wasvy:ecs`
+pub fn generate_abi(wit: &str) -> Result<String> {
+    let mut resolve = Resolve::default();
+    resolve
+        .push_str("wasvy-ecs.wit", include_str!("../wit/wasvy-ecs.wit"))
+        .context("parsing wasvy:ecs")?;
+    let package = resolve
+        .push_str("components.wit", wit)
+        .context("parsing the WIT")?;
+
+    let mut abi = Abi {
+        package: resolve.packages[package].name.to_string(),
+        components: Vec::new(),
+        types: Vec::new(),
+    };
+    for (interface_id, interface) in resolve.interfaces.iter() {
+        if interface.package != Some(package) {
+            continue;
+        }
+        let interface_name = interface.name.clone().unwrap_or_default();
+
+        for type_id in interface.types.values() {
+            let ty = &resolve.types[*type_id];
+            match &ty.kind {
+                TypeDefKind::Resource => abi.components.push(AbiComponent {
+                    type_path: type_path(ty).unwrap_or_default(),
+                    name: ty.name.clone().unwrap_or_default(),
+                    interface: interface_name.clone(),
+                    docs: docs(ty.docs.contents.as_deref()),
+                    methods: methods(&resolve, interface_id, *type_id),
+                }),
+                TypeDefKind::Record(record) => abi.types.push(AbiType {
+                    name: ty.name.clone().unwrap_or_default(),
+                    kind: "record",
+                    type_path: type_path(ty),
+                    fields: record
+                        .fields
+                        .iter()
+                        .map(|field| AbiField {
+                            name: field.name.clone(),
+                            json: Some(field.name.replace('-', "_")),
+                            ty: Some(type_name(&resolve, &field.ty)),
+                        })
+                        .collect(),
+                }),
+                TypeDefKind::Enum(enum_) => abi.types.push(AbiType {
+                    name: ty.name.clone().unwrap_or_default(),
+                    kind: "enum",
+                    type_path: type_path(ty),
+                    fields: enum_
+                        .cases
+                        .iter()
+                        .map(|case| AbiField {
+                            name: case.name.clone(),
+                            json: Some(upper_camel(&case.name)),
+                            ty: None,
+                        })
+                        .collect(),
+                }),
+                TypeDefKind::Variant(variant) => abi.types.push(AbiType {
+                    name: ty.name.clone().unwrap_or_default(),
+                    kind: "variant",
+                    type_path: type_path(ty),
+                    fields: variant
+                        .cases
+                        .iter()
+                        .map(|case| AbiField {
+                            name: case.name.clone(),
+                            json: Some(upper_camel(&case.name)),
+                            ty: case.ty.as_ref().map(|ty| type_name(&resolve, ty)),
+                        })
+                        .collect(),
+                }),
+                _ => {}
+            }
+        }
+    }
+
+    let mut json = serde_json::to_string_pretty(&abi)?;
+    json.push('\n');
+    Ok(json)
+}
+
+/// The methods of a resource, without its constructor which takes the component
+fn methods(resolve: &Resolve, interface: InterfaceId, resource: TypeId) -> Vec<AbiMethod> {
+    resolve.interfaces[interface]
+        .functions
+        .values()
+        .filter_map(|function| {
+            let is_static = match function.kind {
+                FunctionKind::Method(id) if id == resource => false,
+                FunctionKind::Static(id) if id == resource => true,
+                _ => return None,
+            };
+            let params = function
+                .params
+                .iter()
+                .skip(if is_static { 0 } else { 1 })
+                .map(|param| AbiField {
+                    name: param.name.clone(),
+                    json: None,
+                    ty: Some(type_name(resolve, &param.ty)),
+                })
+                .collect();
+            Some(AbiMethod {
+                name: function.item_name().to_string(),
+                is_static,
+                params,
+                result: function.result.as_ref().map(|ty| type_name(resolve, ty)),
+                docs: docs(function.docs.contents.as_deref()),
+            })
+        })
+        .collect()
+}
+
+/// The type path of a type, from its `wasvy:type-path` doc comment
+fn type_path(ty: &TypeDef) -> Option<String> {
+    ty.docs
+        .contents
+        .as_deref()?
+        .lines()
+        .find_map(|line| line.trim().strip_prefix("wasvy:type-path="))
+        .map(|type_path| type_path.trim().to_string())
+}
+
+/// Docs without the `wasvy:type-path` line
+fn docs(docs: Option<&str>) -> Option<String> {
+    let docs = docs?
+        .lines()
+        .filter(|line| !line.trim().starts_with("wasvy:type-path="))
+        .collect::<Vec<_>>()
+        .join("\n");
+    let docs = docs.trim();
+    (!docs.is_empty()).then(|| docs.to_string())
+}
+
+/// The type as written in WIT
+fn type_name(resolve: &Resolve, ty: &Type) -> String {
+    let id = match ty {
+        Type::Bool => return "bool".into(),
+        Type::U8 => return "u8".into(),
+        Type::U16 => return "u16".into(),
+        Type::U32 => return "u32".into(),
+        Type::U64 => return "u64".into(),
+        Type::S8 => return "s8".into(),
+        Type::S16 => return "s16".into(),
+        Type::S32 => return "s32".into(),
+        Type::S64 => return "s64".into(),
+        Type::F32 => return "f32".into(),
+        Type::F64 => return "f64".into(),
+        Type::Char => return "char".into(),
+        Type::String => return "string".into(),
+        Type::ErrorContext => return "error-context".into(),
+        Type::Id(id) => *id,
+    };
+
+    let ty = &resolve.types[id];
+    if let Some(name) = &ty.name {
+        return name.clone();
+    }
+    match &ty.kind {
+        TypeDefKind::Option(ty) => format!("option<{}>", type_name(resolve, ty)),
+        TypeDefKind::List(ty) => format!("list<{}>", type_name(resolve, ty)),
+        TypeDefKind::Tuple(tuple) => format!(
+            "tuple<{}>",
+            tuple
+                .types
+                .iter()
+                .map(|ty| type_name(resolve, ty))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+        TypeDefKind::Result(result) => {
+            let ok = result.ok.as_ref().map(|ty| type_name(resolve, ty));
+            let err = result.err.as_ref().map(|ty| type_name(resolve, ty));
+            match (ok, err) {
+                (Some(ok), Some(err)) => format!("result<{ok}, {err}>"),
+                (None, Some(err)) => format!("result<_, {err}>"),
+                (Some(ok), None) => format!("result<{ok}>"),
+                (None, None) => "result".into(),
+            }
+        }
+        TypeDefKind::Handle(Handle::Own(id) | Handle::Borrow(id)) => {
+            type_name(resolve, &Type::Id(*id))
+        }
+        TypeDefKind::Type(ty) => type_name(resolve, ty),
+        _ => "unknown".into(),
+    }
+}
+
+/// The UpperCamelCase of a kebab-case WIT name, which is how Rust names enum variants
+fn upper_camel(name: &str) -> String {
+    name.split('-')
+        .map(|word| {
+            let mut chars = word.chars();
+            match chars.next() {
+                Some(first) => first.to_ascii_uppercase().to_string() + chars.as_str(),
+                None => String::new(),
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn generates_abi() {
+        let wit = r#"
+package my-namespace:my-game;
+
+interface components {
+  use wasvy:ecs/app@0.0.9.{component};
+
+  /// wasvy:type-path=my_game::Order
+  variant order {
+    hold,
+    move-to(tuple<f32, f32>),
+  }
+  /// A health component
+  ///
+  /// wasvy:type-path=my_game::Health
+  resource health {
+    constructor(component: component);
+    /// Heals by `amount`
+    heal: func(amount: f32);
+    command: func(order: order) -> result<u32, string>;
+    full: static func(max: f32) -> list<option<s64>>;
+  }
+}
+"#;
+        let abi: serde_json::Value = serde_json::from_str(&generate_abi(wit).unwrap()).unwrap();
+        assert_eq!(
+            abi,
+            serde_json::json!({
+                "package": "my-namespace:my-game",
+                "components": [{
+                    "type_path": "my_game::Health",
+                    "name": "health",
+                    "interface": "components",
+                    "docs": "A health component",
+                    "methods": [
+                        {
+                            "name": "heal",
+                            "static": false,
+                            "params": [{ "name": "amount", "type": "f32" }],
+                            "result": null,
+                            "docs": "Heals by `amount`",
+                        },
+                        {
+                            "name": "command",
+                            "static": false,
+                            "params": [{ "name": "order", "type": "order" }],
+                            "result": "result<u32, string>",
+                        },
+                        {
+                            "name": "full",
+                            "static": true,
+                            "params": [{ "name": "max", "type": "f32" }],
+                            "result": "list<option<s64>>",
+                        },
+                    ],
+                }],
+                "types": [{
+                    "name": "order",
+                    "kind": "variant",
+                    "type_path": "my_game::Order",
+                    "fields": [
+                        { "name": "hold", "json": "Hold" },
+                        { "name": "move-to", "json": "MoveTo", "type": "tuple<f32, f32>" },
+                    ],
+                }],
+            })
+        );
+    }
+}
@@ -8,7 +8,9 @@ use std::{
 };
 
 use crate::{
+    abi::generate_abi,
     command::Logging,
+    host_wit::generate_host_wit,
     named::Named,
     remote::{Remote, RemoteUri},
     runtime::Runtime,
@@ -52,6 +54,9 @@ impl From<Command> for Args {
 #[derive(clap::Subcommand, Debug, Eq, PartialEq)]
 pub enum Command {
     /// Creates a new mod source
+    ///
+    /// The mod imports the components the remote app exports
+    #[command(visible_alias = "new-mod")]
     New(NewArgs),
 
     /// Load mod sources and then watch for changes
@@ -65,6 +70,9 @@ pub enum Command {
 
     /// Unloads one or more mods from the remote app
     Unload(ModArgs),
+
+    /// Generates the WIT of the components the host crate at `--path` exports to mods
+    Wit(WitArgs),
 }
 
 impl Default for Command {
@@ -91,6 +99,25 @@ pub struct ModArgs {
     pub mods: Vec<String>,
 }
 
+#[derive(clap::Args, Debug, Eq, PartialEq)]
+pub struct WitArgs {
+    /// Where the WIT is written, relative to the crate
+    #[arg(short, long, default_value = "wit/components.wit")]
+    pub output: PathBuf,
+
+    /// The WIT package of the components
+    #[arg(long)]
+    pub package: Option<String>,
+
+    /// Also writes the JSON ABI of the components there, relative to the crate
+    #[arg(long)]
+    pub abi: Option<PathBuf>,
+
+    /// Fails if the WIT is stale instead of writing it
+    #[arg(long)]
+    pub check: bool,
+}
+
 #[derive(clap::Args, Debug, Eq, PartialEq, Default)]
 pub struct DevArgs {
     #[command(flatten)]
@@ -114,6 +141,13 @@ pub fn cli(args: Args, logging: Logging) -> Result<Vec<Source>> {
     } = &args;
     let default = Command::default();
     let command = command.as_ref().unwrap_or(&default);
+
+    // Generating WIT doesn't need a remote app
+    if let Command::Wit(args) = command {
+        wit(path, args, logging)?;
+        return Ok(Vec::new());
+    }
+
     let uri: RemoteUri = uri
         .as_ref()
         .and_then(|a| a.parse().ok())
@@ -166,7 +200,30 @@ pub fn cli(args: Args, logging: Logging) -> Result<Vec<Source>> {
             remote.watch(&sources, timeout, args.count, logging)?;
             Ok(sources)
         }
+        Command::Wit(_) => unreachable!("handled before connecting"),
+    }
+}
+
+fn wit(path: &Path, args: &WitArgs, logging: Logging) -> Result<()> {
+    let wit = generate_host_wit(path, args.package.as_deref(), logging)?;
+    let mut outputs = vec![(path.join(&args.output), wit)];
+    if let Some(abi) = &args.abi {
+        let contents = generate_abi(&outputs[0].1)?;
+        outputs.push((path.join(abi), contents));
+    }
+
+    for (output, contents) in outputs {
+        if args.check {
+            if fs::read_to_string(&output).ok().as_deref() != Some(contents.as_str()) {
+                bail!("{output:?} is stale, run `wasvy wit` to update it");
+            }
+            println!("{output:?} is up to date");
+        } else {
+            crate::fs::write(&output, contents)?;
+            println!("Wrote {output:?}");
+        }
     }
+    Ok(())
 }
 
 fn get_sources(
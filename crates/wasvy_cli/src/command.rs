@@ -72,7 +72,7 @@ impl Logging {
         }
     }
 
-    fn stdio(&self) -> Stdio {
+    pub(crate) fn stdio(&self) -> Stdio {
         match self {
             Logging::Inherit => Stdio::inherit(),
             Logging::Capture => Stdio::piped(),
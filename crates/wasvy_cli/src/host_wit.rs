@@ -0,0 +1,235 @@
+//! Generates the WIT of the components a host crate exports to mods, for `wasvy wit`.
+//!
+//! The CLI can't load the host crate, so it builds a small generator for it instead, in
+//! `target/wasvy-wit`. Like a build script, the generator includes the modules of the crate that
+//! use Wasvy's macros with `include_wasvy_components!`, registers them and prints the WIT.
+//!
+//! The generator is named after the crate so type paths match, and uses the dependencies and
+//! lockfile of the crate.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    process,
+};
+
+use anyhow::{Context, Result, bail};
+use toml_edit::{DocumentMut, InlineTable, Item, Table, Value, value};
+
+use crate::{command::Logging, fs::WriteTo, languages::cargo_metadata};
+
+/// Generates the WIT of the components of the host crate at `crate_path`.
+///
+/// The WIT package defaults to the one of `WitGeneratorSettings`.
+pub fn generate_host_wit(
+    crate_path: &Path,
+    package: Option<&str>,
+    logging: Logging,
+) -> Result<String> {
+    let crate_path = fs::canonicalize(crate_path)
+        .with_context(|| format!("invalid crate path {crate_path:?}"))?;
+    let manifest = read_manifest(&crate_path.join("Cargo.toml"))?;
+    let name = manifest
+        .get("package")
+        .and_then(|package| package.get("name"))
+        .and_then(Item::as_str)
+        .with_context(|| format!("{crate_path:?} is not a package"))?;
+
+    let metadata = cargo_metadata(&crate_path)?;
+    let workspace_manifest = metadata.workspace_root.join("Cargo.toml");
+    let workspace = match workspace_manifest == crate_path.join("Cargo.toml") {
+        true => None,
+        false => Some(read_manifest(&workspace_manifest)?),
+    };
+
+    let dependencies = dependencies(
+        &manifest,
+        &crate_path,
+        workspace.as_ref(),
+        &metadata.workspace_root,
+    )?;
+    if !dependencies.contains_key("wasvy") {
+        bail!("{name} does not depend on wasvy");
+    }
+
+    let directory = metadata.target_directory.join("wasvy-wit");
+    let generator = generator_manifest(&manifest, name, dependencies);
+    crate::fs::write(directory.join("Cargo.toml"), generator.to_string())?;
+
+    #[derive(askama::Template)]
+    #[template(path = "./host/main.rs")]
+    struct Main<'a> {
+        name: &'a str,
+        src: String,
+        package: Option<&'a str>,
+    }
+    let main = Main {
+        name,
+        src: crate_path.join("src").to_string_lossy().replace('\\', "/"),
+        package,
+    };
+    main.write(&directory)?;
+
+    let lockfile = metadata.workspace_root.join("Cargo.lock");
+    if lockfile.is_file() {
+        fs::copy(&lockfile, directory.join("Cargo.lock"))
+            .with_context(|| format!("copying {lockfile:?}"))?;
+    }
+
+    // The generator has the name of the crate, so it has its own target directory to not
+    // overwrite the crate's binaries
+    logging.println(format!("Generating the WIT of {name}"));
+    let output = process::Command::new("cargo")
+        .arg("run")
+        .arg("--quiet")
+        .arg("--manifest-path")
+        .arg(directory.join("Cargo.toml"))
+        .arg("--target-dir")
+        .arg(directory.join("target"))
+        .stderr(logging.stdio())
+        .output()
+        .context("could not run `cargo run`")?;
+    if !output.status.success() {
+        bail!("generating the WIT of {name} failed with {}", output.status);
+    }
+
+    String::from_utf8(output.stdout).context("the generated WIT was not valid UTF-8")
+}
+
+fn read_manifest(path: &Path) -> Result<DocumentMut> {
+    fs::read_to_string(path)
+        .with_context(|| format!("reading {path:?}"))?
+        .parse()
+        .with_context(|| format!("parsing {path:?}"))
+}
+
+/// The manifest of the generator of the crate called `name`
+fn generator_manifest(manifest: &DocumentMut, name: &str, dependencies: Table) -> DocumentMut {
+    let edition = manifest
+        .get("package")
+        .and_then(|package| package.get("edition"))
+        .and_then(Item::as_str)
+        .unwrap_or("2024");
+
+    let mut package = Table::new();
+    package.insert("name", value(name));
+    package.insert("version", value("0.0.0"));
+    package.insert("edition", value(edition));
+    package.insert("publish", value(false));
+
+    let mut generator = DocumentMut::new();
+    generator.insert("package", Item::Table(package));
+    // The generator is not a member of the crate's workspace
+    generator.insert("workspace", Item::Table(Table::new()));
+    generator.insert("dependencies", Item::Table(dependencies));
+    generator
+}
+
+/// The dependencies of a crate, with inherited dependencies resolved and absolute paths
+fn dependencies(
+    manifest: &DocumentMut,
+    crate_path: &Path,
+    workspace: Option<&DocumentMut>,
+    workspace_root: &Path,
+) -> Result<Table> {
+    let workspace_dependencies = workspace
+        .and_then(|workspace| workspace.get("workspace"))
+        .and_then(|workspace| workspace.get("dependencies"))
+        .and_then(Item::as_table_like);
+
+    let mut out = Table::new();
+    let Some(dependencies) = manifest.get("dependencies").and_then(Item::as_table_like) else {
+        return Ok(out);
+    };
+    for (name, item) in dependencies.iter() {
+        let mut dependency =
+            dependency_table(item).with_context(|| format!("invalid dependency {name}"))?;
+        let mut base = crate_path;
+
+        if dependency.get("workspace").and_then(Value::as_bool) == Some(true) {
+            let mut inherited = workspace_dependencies
+                .and_then(|dependencies| dependencies.get(name))
+                .and_then(dependency_table)
+                .with_context(|| format!("dependency {name} is not in the workspace"))?;
+            if let Some(features) = dependency.get("features").and_then(Value::as_array) {
+                let inherited_features = inherited
+                    .get_or_insert("features", Value::Array(Default::default()))
+                    .as_array_mut()
+                    .with_context(|| format!("invalid features of dependency {name}"))?;
+                inherited_features.extend(features.iter().cloned());
+                inherited_features.fmt();
+            }
+            dependency = inherited;
+            base = workspace_root;
+        }
+
+        // The generator has no features to enable optional dependencies with
+        dependency.remove("optional");
+        if let Some(path) = dependency.get("path").and_then(Value::as_str) {
+            let path: PathBuf = base.join(path).components().collect();
+            dependency.insert("path", path.to_string_lossy().as_ref().into());
+        }
+        out.insert(name, value(dependency));
+    }
+    Ok(out)
+}
+
+/// A dependency as an inline table, like `{ version = "0.1" }`
+fn dependency_table(item: &Item) -> Option<InlineTable> {
+    match item {
+        Item::Value(Value::String(version)) => {
+            let mut table = InlineTable::new();
+            table.insert("version", version.value().as_str().into());
+            Some(table)
+        }
+        Item::Value(Value::InlineTable(table)) => Some(table.clone()),
+        Item::Table(table) => Some(table.clone().into_inline_table()),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resolves_dependencies() {
+        let manifest: DocumentMut = r#"
+[package]
+name = "my-game"
+
+[dependencies]
+serde = "1.0"
+wasvy = { workspace = true, features = ["inspector"] }
+assets = { path = "../assets", optional = true }
+"#
+        .parse()
+        .unwrap();
+        let workspace: DocumentMut = r#"
+[workspace.dependencies]
+wasvy = { path = "crates/wasvy", features = ["serde_json"] }
+"#
+        .parse()
+        .unwrap();
+
+        let dependencies = dependencies(
+            &manifest,
+            Path::new("/game/crates/my-game"),
+            Some(&workspace),
+            Path::new("/game"),
+        )
+        .unwrap();
+        let dependencies: Vec<String> = dependencies
+            .iter()
+            .map(|(name, dependency)| format!("{name} = {}", dependency.to_string().trim()))
+            .collect();
+        assert_eq!(
+            dependencies,
+            [
+                r#"serde = { version = "1.0" }"#,
+                r#"wasvy = { path = "/game/crates/wasvy", features = ["serde_json", "inspector"] }"#,
+                r#"assets = { path = "/game/crates/my-game/../assets" }"#,
+            ]
+        );
+    }
+}
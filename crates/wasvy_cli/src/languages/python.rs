@@ -55,6 +55,11 @@ impl Language for Python {
         }
         errors.collect(App { name }.write(path));
 
+        #[derive(askama::Template)]
+        #[template(path = "./python/src/wasvy_helpers.py")]
+        pub struct Helpers;
+        errors.collect(Helpers.write(path));
+
         // Remove outdated codegen
         let src = path.join("src");
         let _ = fs::remove_dir_all(src.join("componentize_py_async_support"));
@@ -88,14 +88,21 @@ impl Language for Rust {
             name: &'a str,
             namespace: &'a str,
             rust_version: &'a str,
+            wasvy_version: &'a str,
         }
         let file = CargoToml {
             name,
             namespace,
             rust_version,
+            wasvy_version: env!("CARGO_PKG_VERSION"),
         };
         errors.collect(file.write(path));
 
+        #[derive(askama::Template)]
+        #[template(path = "./rust/.cargo/config.toml.tmpl")]
+        struct CargoConfig;
+        errors.collect(CargoConfig.write(path));
+
         #[derive(askama::Template)]
         #[template(path = "./rust/src/lib.rs")]
         struct Lib<'a> {
@@ -1,3 +1,4 @@
+pub mod abi;
 #[cfg(feature = "cli")]
 pub mod cli;
 pub mod command;
@@ -5,6 +6,7 @@ pub mod dependency;
 pub mod editor;
 pub mod editors;
 pub mod fs;
+pub mod host_wit;
 pub mod id;
 pub mod language;
 pub mod languages;
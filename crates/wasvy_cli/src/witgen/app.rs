@@ -19,6 +19,31 @@ impl bindings::HostApp for Host {
         Ok(())
     }
 
+    fn export_function(&mut self, _: Resource<WasmApp>, _: String) -> Result<(), wasmtime::Error> {
+        Ok(())
+    }
+
+    fn add_callback(&mut self, _: Resource<WasmApp>, _: String) -> Result<(), wasmtime::Error> {
+        Ok(())
+    }
+
+    fn register_component_schema(
+        &mut self,
+        _: Resource<WasmApp>,
+        _: String,
+        _: String,
+    ) -> Result<(), wasmtime::Error> {
+        Ok(())
+    }
+
+    fn use_codec(
+        &mut self,
+        _: Resource<WasmApp>,
+        _: bindings::Codec,
+    ) -> Result<(), wasmtime::Error> {
+        Ok(())
+    }
+
     // Note: this is never guaranteed to be called by the wasi binary
     fn drop(&mut self, _: Resource<WasmApp>) -> Result<(), wasmtime::Error> {
         Ok(())
@@ -24,7 +24,52 @@ impl Default for Host {
     }
 }
 
-impl bindings::Host for Host {}
+impl bindings::Host for Host {
+    /// Mods may read their info during setup, so return a placeholder
+    fn mod_info(&mut self) -> Result<bindings::ModMetadata, wasmtime::Error> {
+        Ok(bindings::ModMetadata {
+            name: "witgen".into(),
+            entity_bits: 0,
+            accesses: Vec::new(),
+            capabilities_granted: Vec::new(),
+            version: None,
+        })
+    }
+
+    fn get_data(&mut self, _: String) -> Result<Option<Vec<u8>>, wasmtime::Error> {
+        Err(wasmtime::Error::msg("Unexpected call to get_data"))
+    }
+
+    fn set_data(&mut self, _: String, _: Vec<u8>) -> Result<(), wasmtime::Error> {
+        Err(wasmtime::Error::msg("Unexpected call to set_data"))
+    }
+
+    fn remove_data(&mut self, _: String) -> Result<(), wasmtime::Error> {
+        Err(wasmtime::Error::msg("Unexpected call to remove_data"))
+    }
+
+    fn call_mod(
+        &mut self,
+        _: String,
+        _: String,
+        _: bindings::SerializedComponent,
+    ) -> Result<Result<bindings::SerializedComponent, String>, wasmtime::Error> {
+        Err(wasmtime::Error::msg("Unexpected call to call_mod"))
+    }
+
+    fn log(&mut self, _: bindings::LogLevel, _: String) -> Result<(), wasmtime::Error> {
+        Err(wasmtime::Error::msg("Unexpected call to log"))
+    }
+
+    fn get_time(&mut self) -> Result<bindings::TimeInfo, wasmtime::Error> {
+        Err(wasmtime::Error::msg("Unexpected call to get_time"))
+    }
+
+    /// Mods may list components during setup, and the CLI has none
+    fn list_components(&mut self) -> Result<Vec<bindings::ComponentInfo>, wasmtime::Error> {
+        Ok(Vec::new())
+    }
+}
 
 impl WasiView for Host {
     fn ctx(&mut self) -> WasiCtxView<'_> {
@@ -63,6 +108,28 @@ impl bindings::HostComponent for Host {
     ) -> Result<bindings::SerializedComponent, wasmtime::Error> {
         Err(wasmtime::Error::msg("Unexpected call to Component::invoke"))
     }
+
+    fn try_invoke(
+        &mut self,
+        _: Resource<bindings::Component>,
+        _: String,
+        _: String,
+    ) -> Result<Result<String, String>, wasmtime::Error> {
+        Err(wasmtime::Error::msg(
+            "Unexpected call to Component::try_invoke",
+        ))
+    }
+
+    fn invoke_static(
+        &mut self,
+        _: bindings::TypePath,
+        _: String,
+        _: bindings::SerializedComponent,
+    ) -> Result<bindings::SerializedComponent, wasmtime::Error> {
+        Err(wasmtime::Error::msg(
+            "Unexpected call to Component::invoke_static",
+        ))
+    }
 }
 
 impl bindings::HostCommands for Host {
@@ -74,6 +141,16 @@ impl bindings::HostCommands for Host {
         Err(wasmtime::Error::msg("Unexpected call to Commands::entity"))
     }
 
+    fn find_by_name(
+        &mut self,
+        _: Resource<bindings::Commands>,
+        _: String,
+    ) -> Result<Option<Resource<bindings::Entity>>, wasmtime::Error> {
+        Err(wasmtime::Error::msg(
+            "Unexpected call to Commands::find_by_name",
+        ))
+    }
+
     fn drop(&mut self, _: Resource<bindings::Commands>) -> Result<(), wasmtime::Error> {
         Err(wasmtime::Error::msg("Unexpected call to Commands::drop"))
     }
@@ -94,9 +171,71 @@ impl bindings::HostCommands for Host {
             "Unexpected call to Commands::spawn_empty",
         ))
     }
+
+    fn send_event(
+        &mut self,
+        _: Resource<bindings::Commands>,
+        _: String,
+        _: bindings::SerializedComponent,
+    ) -> Result<(), wasmtime::Error> {
+        Err(wasmtime::Error::msg(
+            "Unexpected call to Commands::send_event",
+        ))
+    }
+
+    fn insert_resource(
+        &mut self,
+        _: Resource<bindings::Commands>,
+        _: String,
+        _: bindings::SerializedComponent,
+    ) -> Result<(), wasmtime::Error> {
+        Err(wasmtime::Error::msg(
+            "Unexpected call to Commands::insert_resource",
+        ))
+    }
+
+    fn remove_resource(
+        &mut self,
+        _: Resource<bindings::Commands>,
+        _: String,
+    ) -> Result<(), wasmtime::Error> {
+        Err(wasmtime::Error::msg(
+            "Unexpected call to Commands::remove_resource",
+        ))
+    }
+
+    fn spawn_scene(
+        &mut self,
+        _: Resource<bindings::Commands>,
+        _: String,
+    ) -> Result<Resource<bindings::EntityCommands>, wasmtime::Error> {
+        Err(wasmtime::Error::msg(
+            "Unexpected call to Commands::spawn_scene",
+        ))
+    }
+
+    fn take_errors(
+        &mut self,
+        _: Resource<bindings::Commands>,
+    ) -> Result<Vec<bindings::CommandError>, wasmtime::Error> {
+        Err(wasmtime::Error::msg(
+            "Unexpected call to Commands::take_errors",
+        ))
+    }
 }
 
 impl bindings::HostEntityCommands for Host {
+    fn insert_asset(
+        &mut self,
+        _: Resource<bindings::EntityCommands>,
+        _: String,
+        _: Resource<bindings::AssetHandle>,
+    ) -> Result<(), wasmtime::Error> {
+        Err(wasmtime::Error::msg(
+            "Unexpected call to EntityCommands::insert_asset",
+        ))
+    }
+
     fn despawn(&mut self, _: Resource<bindings::EntityCommands>) -> Result<(), wasmtime::Error> {
         Err(wasmtime::Error::msg(
             "Unexpected call to EntityCommands::despawn",
@@ -146,14 +285,124 @@ impl bindings::HostEntityCommands for Host {
             "Unexpected call to EntityCommands::try_despawn",
         ))
     }
+
+    fn set_parent(
+        &mut self,
+        _: Resource<bindings::EntityCommands>,
+        _: Resource<bindings::Entity>,
+    ) -> Result<(), wasmtime::Error> {
+        Err(wasmtime::Error::msg(
+            "Unexpected call to EntityCommands::set_parent",
+        ))
+    }
+
+    fn remove_parent(
+        &mut self,
+        _: Resource<bindings::EntityCommands>,
+    ) -> Result<(), wasmtime::Error> {
+        Err(wasmtime::Error::msg(
+            "Unexpected call to EntityCommands::remove_parent",
+        ))
+    }
+
+    fn add_child(
+        &mut self,
+        _: Resource<bindings::EntityCommands>,
+        _: Resource<bindings::Entity>,
+    ) -> Result<(), wasmtime::Error> {
+        Err(wasmtime::Error::msg(
+            "Unexpected call to EntityCommands::add_child",
+        ))
+    }
+
+    fn set_despawn_behaviour(
+        &mut self,
+        _: Resource<bindings::EntityCommands>,
+        _: bindings::DespawnBehaviour,
+    ) -> Result<(), wasmtime::Error> {
+        Err(wasmtime::Error::msg(
+            "Unexpected call to EntityCommands::set_despawn_behaviour",
+        ))
+    }
 }
 
 impl bindings::HostEntity for Host {
+    fn to_bits(&mut self, _: Resource<bindings::Entity>) -> Result<u64, wasmtime::Error> {
+        Err(wasmtime::Error::msg("Unexpected call to Entity::to_bits"))
+    }
+
+    fn from_bits(&mut self, _: u64) -> Result<Resource<bindings::Entity>, wasmtime::Error> {
+        Err(wasmtime::Error::msg("Unexpected call to Entity::from_bits"))
+    }
+
     fn drop(&mut self, _: Resource<bindings::Entity>) -> Result<(), wasmtime::Error> {
         Err(wasmtime::Error::msg("Unexpected call to Entity::drop"))
     }
 }
 
+impl bindings::HostEventReader for Host {
+    fn drop(&mut self, _: Resource<bindings::EventReader>) -> Result<(), wasmtime::Error> {
+        Err(wasmtime::Error::msg("Unexpected call to EventReader::drop"))
+    }
+
+    fn read(
+        &mut self,
+        _: Resource<bindings::EventReader>,
+    ) -> Result<Vec<bindings::SerializedComponent>, wasmtime::Error> {
+        Err(wasmtime::Error::msg("Unexpected call to EventReader::read"))
+    }
+}
+
+impl bindings::HostRes for Host {
+    fn get(
+        &mut self,
+        _: Resource<bindings::Res>,
+    ) -> Result<bindings::SerializedComponent, wasmtime::Error> {
+        Err(wasmtime::Error::msg("Unexpected call to Res::get"))
+    }
+
+    fn set(
+        &mut self,
+        _: Resource<bindings::Res>,
+        _: bindings::SerializedComponent,
+    ) -> Result<(), wasmtime::Error> {
+        Err(wasmtime::Error::msg("Unexpected call to Res::set"))
+    }
+
+    fn invoke(
+        &mut self,
+        _: Resource<bindings::Res>,
+        _: String,
+        _: bindings::SerializedComponent,
+    ) -> Result<bindings::SerializedComponent, wasmtime::Error> {
+        Err(wasmtime::Error::msg("Unexpected call to Res::invoke"))
+    }
+
+    fn drop(&mut self, _: Resource<bindings::Res>) -> Result<(), wasmtime::Error> {
+        Err(wasmtime::Error::msg("Unexpected call to Res::drop"))
+    }
+}
+
+impl bindings::HostAssets for Host {
+    fn load(&mut self, _: String) -> Result<Resource<bindings::AssetHandle>, wasmtime::Error> {
+        Err(wasmtime::Error::msg("Unexpected call to Assets::load"))
+    }
+
+    fn drop(&mut self, _: Resource<bindings::Assets>) -> Result<(), wasmtime::Error> {
+        Err(wasmtime::Error::msg("Unexpected call to Assets::drop"))
+    }
+}
+
+impl bindings::HostAssetHandle for Host {
+    fn path(&mut self, _: Resource<bindings::AssetHandle>) -> Result<String, wasmtime::Error> {
+        Err(wasmtime::Error::msg("Unexpected call to AssetHandle::path"))
+    }
+
+    fn drop(&mut self, _: Resource<bindings::AssetHandle>) -> Result<(), wasmtime::Error> {
+        Err(wasmtime::Error::msg("Unexpected call to AssetHandle::drop"))
+    }
+}
+
 impl bindings::HostQueryResult for Host {
     fn component(
         &mut self,
@@ -177,6 +426,15 @@ impl bindings::HostQueryResult for Host {
             "Unexpected call to QueryResult::entity",
         ))
     }
+
+    fn children(
+        &mut self,
+        _: Resource<bindings::QueryResult>,
+    ) -> Result<Vec<Resource<bindings::Entity>>, wasmtime::Error> {
+        Err(wasmtime::Error::msg(
+            "Unexpected call to QueryResult::children",
+        ))
+    }
 }
 
 impl bindings::HostQuery for Host {
@@ -190,6 +448,66 @@ impl bindings::HostQuery for Host {
     ) -> Result<Option<Resource<bindings::QueryResult>>, wasmtime::Error> {
         Err(wasmtime::Error::msg("Unexpected call to Query::iter"))
     }
+
+    fn next_batch(
+        &mut self,
+        _: Resource<bindings::Query>,
+        _: u32,
+    ) -> Result<Vec<Resource<bindings::QueryResult>>, wasmtime::Error> {
+        Err(wasmtime::Error::msg("Unexpected call to Query::next_batch"))
+    }
+
+    fn get(
+        &mut self,
+        _: Resource<bindings::Query>,
+        _: Resource<bindings::Entity>,
+    ) -> Result<Option<Resource<bindings::QueryResult>>, wasmtime::Error> {
+        Err(wasmtime::Error::msg("Unexpected call to Query::get"))
+    }
+
+    fn count(&mut self, _: Resource<bindings::Query>) -> Result<u64, wasmtime::Error> {
+        Err(wasmtime::Error::msg("Unexpected call to Query::count"))
+    }
+
+    fn is_empty(&mut self, _: Resource<bindings::Query>) -> Result<bool, wasmtime::Error> {
+        Err(wasmtime::Error::msg("Unexpected call to Query::is_empty"))
+    }
+
+    fn fetch_all(
+        &mut self,
+        _: Resource<bindings::Query>,
+    ) -> Result<Vec<bindings::EntityRow>, wasmtime::Error> {
+        Err(wasmtime::Error::msg("Unexpected call to Query::fetch_all"))
+    }
+
+    fn write_all(
+        &mut self,
+        _: Resource<bindings::Query>,
+        _: Vec<bindings::EntityRow>,
+    ) -> Result<(), wasmtime::Error> {
+        Err(wasmtime::Error::msg("Unexpected call to Query::write_all"))
+    }
+
+    fn get_resource(
+        &mut self,
+        _: Resource<bindings::Query>,
+        _: bindings::ComponentIndex,
+    ) -> Result<bindings::SerializedComponent, wasmtime::Error> {
+        Err(wasmtime::Error::msg(
+            "Unexpected call to Query::get_resource",
+        ))
+    }
+
+    fn set_resource(
+        &mut self,
+        _: Resource<bindings::Query>,
+        _: bindings::ComponentIndex,
+        _: bindings::SerializedComponent,
+    ) -> Result<(), wasmtime::Error> {
+        Err(wasmtime::Error::msg(
+            "Unexpected call to Query::set_resource",
+        ))
+    }
 }
 
 impl bindings::HostSerialize for Host {
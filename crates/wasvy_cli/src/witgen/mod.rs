@@ -14,6 +14,7 @@ use error_collection::Errors;
 use semver::Version;
 pub(super) use wasmtime::component::Resource;
 use wasmtime::component::Val;
+use wit_parser::{Resolve, TypeDefKind};
 
 pub mod bindings;
 
@@ -35,6 +36,7 @@ pub struct Wit {
     pub namespace: String,
     pub name: String,
     pub wasvy_wit_version: Version,
+    pub imports: Vec<String>,
     pub params: Vec<SystemParam>,
     pub systems: Vec<WasmSystem>,
 }
@@ -48,6 +50,7 @@ impl Wit {
             namespace,
             systems,
             wasvy_wit_version,
+            imports,
         } = config.try_into().map_err(Into::into)?;
 
         let mut errors = Errors::new();
@@ -73,6 +76,7 @@ impl Wit {
         errors.as_result().map(|_| Self {
             name,
             namespace,
+            imports,
             params,
             systems,
             wasvy_wit_version,
@@ -100,6 +104,9 @@ pub struct WitConfig {
     pub name: String,
     pub wasvy_wit_version: Version,
     pub systems: Vec<WasmSystem>,
+
+    /// Interfaces of the runtime with components exported by the host, imported by the world
+    pub imports: Vec<String>,
 }
 
 impl Default for WitConfig {
@@ -109,6 +116,7 @@ impl Default for WitConfig {
             namespace: Default::default(),
             wasvy_wit_version: Version::parse("0.0.9").unwrap(),
             systems: Default::default(),
+            imports: Default::default(),
         }
     }
 }
@@ -122,10 +130,35 @@ impl From<&Runtime> for WitConfig {
         if let Some(dependency) = runtime.find_dependency("wasvy", "ecs") {
             config.wasvy_wit_version = dependency.version.clone();
         }
+        config.imports = component_interfaces(runtime.resolve());
         config
     }
 }
 
+/// The interfaces with resources of components, which the host marks with a
+/// `wasvy:type-path` doc comment
+fn component_interfaces(resolve: &Resolve) -> Vec<String> {
+    let mut interfaces: Vec<String> = resolve
+        .interfaces
+        .iter()
+        .filter(|(_, interface)| {
+            interface.types.values().any(|id| {
+                let ty = &resolve.types[*id];
+                matches!(ty.kind, TypeDefKind::Resource)
+                    && ty
+                        .docs
+                        .contents
+                        .as_deref()
+                        .is_some_and(|docs| docs.contains("wasvy:type-path="))
+            })
+        })
+        .filter_map(|(id, _)| resolve.id_of(id))
+        .collect();
+    // Predictable order is important so we avoid overwriting files that havn't changed
+    interfaces.sort();
+    interfaces
+}
+
 impl TryFrom<&Source> for WitConfig {
     type Error = anyhow::Error;
 
@@ -259,4 +292,36 @@ mod tests {
         assert!(output.contains("export start: func(commands: commands);"));
         assert!(output.contains("export update: func(query: query);"));
     }
+
+    #[test]
+    fn scaffold_imports_components() {
+        let mut config = Config {
+            namespace: "test".into(),
+            ..Default::default()
+        };
+        config
+            .add_dependency(
+                "package game:components@0.1.0;
+
+interface components {
+  /// wasvy:type-path=game::Health
+  resource health {
+    pct: func() -> f32;
+  }
+}
+
+interface math {
+  record point { x: f32, y: f32 }
+}
+",
+            )
+            .unwrap();
+        let runtime = Runtime::new(config).unwrap();
+        let wit = Wit::new(ScaffoldWit::new("game", &runtime)).unwrap();
+
+        let output: String = wit.try_into().unwrap();
+
+        assert!(output.contains("import game:components/components@0.1.0;"));
+        assert!(!output.contains("math"));
+    }
 }
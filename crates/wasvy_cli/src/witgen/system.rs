@@ -19,6 +19,9 @@ pub struct Arg {
 pub enum SystemParam {
     Commands,
     Query,
+    EventReader,
+    Trigger,
+    Resource,
 }
 
 impl fmt::Display for SystemParam {
@@ -26,6 +29,9 @@ impl fmt::Display for SystemParam {
         f.write_str(match self {
             Self::Commands => "commands",
             Self::Query => "query",
+            Self::EventReader => "event-reader",
+            Self::Trigger => "entity",
+            Self::Resource => "res",
         })
     }
 }
@@ -53,6 +59,53 @@ impl bindings::HostSystem for Host {
         add_param(self, system, SystemParam::Query)
     }
 
+    fn add_event_reader(
+        &mut self,
+        system: Resource<WasmSystem>,
+        _: String,
+    ) -> Result<(), wasmtime::Error> {
+        add_param(self, system, SystemParam::EventReader)
+    }
+
+    fn add_trigger(&mut self, system: Resource<WasmSystem>) -> Result<(), wasmtime::Error> {
+        add_param(self, system, SystemParam::Trigger)
+    }
+
+    fn add_resource(
+        &mut self,
+        system: Resource<WasmSystem>,
+        _: String,
+        _: bindings::Mutability,
+    ) -> Result<(), wasmtime::Error> {
+        add_param(self, system, SystemParam::Resource)
+    }
+
+    fn observe(
+        &mut self,
+        _: Resource<WasmSystem>,
+        _: String,
+        _: bindings::LifecycleEvent,
+    ) -> Result<(), wasmtime::Error> {
+        Ok(())
+    }
+
+    fn run_if_resource_equals(
+        &mut self,
+        _: Resource<WasmSystem>,
+        _: String,
+        _: Vec<u8>,
+    ) -> Result<(), wasmtime::Error> {
+        Ok(())
+    }
+
+    fn run_every_n_frames(
+        &mut self,
+        _: Resource<WasmSystem>,
+        _: u32,
+    ) -> Result<(), wasmtime::Error> {
+        Ok(())
+    }
+
     fn after(
         &mut self,
         _: Resource<WasmSystem>,
@@ -69,6 +122,14 @@ impl bindings::HostSystem for Host {
         Ok(())
     }
 
+    fn after_set(&mut self, _: Resource<WasmSystem>, _: String) -> Result<(), wasmtime::Error> {
+        Ok(())
+    }
+
+    fn before_set(&mut self, _: Resource<WasmSystem>, _: String) -> Result<(), wasmtime::Error> {
+        Ok(())
+    }
+
     fn drop(&mut self, _: Resource<WasmSystem>) -> std::result::Result<(), wasmtime::Error> {
         Ok(())
     }
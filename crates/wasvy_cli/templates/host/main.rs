@@ -0,0 +1,22 @@
+// filename: src/main.rs
+//! Generated by `wasvy wit`, prints the WIT of the components of {{ name }}.
+#![allow(dead_code, unused_imports)]
+
+wasvy::include_wasvy_components!("{{ src }}");
+
+fn main() {
+    use wasvy::prelude::*;
+
+    let mut app = wasvy::runtime::authoring::App::new();
+    app.add_plugins(AutoRegistrationPlugin);
+
+    let settings = WitGeneratorSettings {
+        {%- if let Some(package) = package %}
+        package: "{{ package }}".to_string(),
+        {%- endif %}
+        ..Default::default()
+    };
+    let world = app.world();
+    let wit = wasvy::runtime::witgen::generate_wit(&settings, world.resource(), world.resource());
+    print!("{wit}");
+}
@@ -0,0 +1,77 @@
+# filename: src/wasvy_helpers.py
+"""Typed wrappers for the components a host exports, see docs/guest-abi.md in the wasvy repo."""
+import json
+
+
+class ComponentWrapper:
+    """Wraps a component of a query result, calling the methods of the host by name"""
+
+    TYPE_PATH = ""
+
+    def __init__(self, component):
+        self.component = component
+
+    def get(self):
+        """Gets the value of the component"""
+        return json.loads(self.component.get())
+
+    def set(self, value):
+        """Sets the value of the component, which must be queried as mutable"""
+        self.component.set(json.dumps(value).encode())
+
+    def invoke(self, method, *params):
+        """Invokes a method of the component, returning its result"""
+        return _loads(self.component.invoke(method, json.dumps(list(params)).encode()))
+
+    @classmethod
+    def invoke_static(cls, component_type, method, *params):
+        """Invokes a static method, passing the `Component` class of the bindings"""
+        params = json.dumps(list(params)).encode()
+        return _loads(component_type.invoke_static(cls.TYPE_PATH, method, params))
+
+
+def component_wrapper(type_path, methods=(), static_methods=()):
+    """Creates a wrapper class for a component, with a Python method for each of its methods"""
+    namespace = {"TYPE_PATH": type_path}
+    for method in methods:
+        namespace[_python_name(method)] = _method(method)
+    for method in static_methods:
+        namespace[_python_name(method)] = _static_method(method)
+    name = type_path.rsplit("::", 1)[-1]
+    return type(name, (ComponentWrapper,), namespace)
+
+
+def wrappers_from_abi(abi):
+    """Creates the wrappers of every component of an ABI, by the name of their WIT resource"""
+    if isinstance(abi, (str, bytes)):
+        abi = json.loads(abi)
+    wrappers = {}
+    for component in abi["components"]:
+        methods = [method["name"] for method in component["methods"] if not method["static"]]
+        static_methods = [method["name"] for method in component["methods"] if method["static"]]
+        wrappers[component["name"]] = component_wrapper(
+            component["type_path"], methods, static_methods
+        )
+    return wrappers
+
+
+def _method(method):
+    def call(self, *params):
+        return self.invoke(method, *params)
+
+    return call
+
+
+def _static_method(method):
+    def call(cls, component_type, *params):
+        return cls.invoke_static(component_type, method, *params)
+
+    return classmethod(call)
+
+
+def _python_name(name):
+    return name.replace("-", "_")
+
+
+def _loads(result):
+    return json.loads(result) if result else None
@@ -1,5 +1,7 @@
 // filename: src/bindings.rs
-wit_bindgen::generate!({
+// Generates bindings for the wit of the mod, along with `TYPE_PATH` constants for the components
+// the host exports
+wasvy_macros::guest_bindings!({
     path: ["./wit"],
     world: "{{ world_name }}",
     with: {
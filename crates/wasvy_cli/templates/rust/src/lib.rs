@@ -1,27 +1,33 @@
 // filename: src/lib.rs
 use bevy_math::Quat;
 use bevy_transform::components::Transform;
-use serde::{Deserialize, Serialize};
 
 mod bindings;
 use bindings::*;
 
+// A typed query, which deserializes its components and writes them back with `set`
+wasvy_macros::guest_query! {
+    struct Spinning {
+        transform: mut Transform = "bevy_transform::components::transform::Transform",
+        without "bevy_camera::camera::Camera",
+        without "bevy_ecs::hierarchy::ChildOf",
+    }
+}
+
 struct GuestComponent;
 
+// Generates the `systems` module, with a constructor for each exported system
+#[wasvy_macros::guest_systems]
 impl Guest for GuestComponent {
     fn setup(app: App) {
         // Define an example system with commands that run on startup
-        let start = System::new("start");
-        start.add_commands();
+        let start = systems::start();
         app.add_systems(&Schedule::ModStartup, &[&start]);
 
         // Define another system that runs every update
-        let update = System::new("update");
-        update.add_query(&[
-            QueryFor::Mut("bevy_transform::components::transform::Transform".to_string()),
-            QueryFor::Without("bevy_camera::camera::Camera".to_string()),
-            QueryFor::Without("bevy_ecs::hierarchy::ChildOf".to_string()),
-        ]);
+        // Components the host exports are generated in `bindings` with their type paths, so they
+        // can be queried with `QueryFor::Mut(Health::type_path())`
+        let update = systems::update(&Spinning::query_for());
         app.add_systems(&Schedule::Update, &[&update]);
     }
 
@@ -35,36 +41,14 @@ impl Guest for GuestComponent {
     }
 
     fn update(query: Query) {
-        while let Some(results) = query.iter() {
-            // Get the first component
-            let component = results.component(0);
-
-            // Deserialize the first component
-            let mut transform: Transform = from_json(&component.get());
-
+        for mut entity in Spinning::iter(&query) {
             // Spin the entity
-            transform.rotate(Quat::from_rotation_y(0.025));
+            entity.transform.rotate(Quat::from_rotation_y(0.025));
 
             // Set the new component value
-            component.set(&to_json(&transform));
+            entity.set();
         }
     }
 }
 
 export!(GuestComponent);
-
-fn from_json<'a, T>(component: &'a [u8]) -> T
-where
-    T: Deserialize<'a>,
-{
-    serde_json::from_slice(component).expect("serializable component")
-}
-
-fn to_json<T>(component: &T) -> Vec<u8>
-where
-    T: Serialize,
-{
-    serde_json::to_string(&component)
-        .expect("serializable component")
-        .into_bytes()
-}
@@ -4,7 +4,7 @@ use proc_macro::TokenStream;
 use proc_macro_crate::{FoundCrate, crate_name};
 use quote::{format_ident, quote};
 use std::path::{Path, PathBuf};
-use syn::{Attribute, DeriveInput, Ident, ImplItem, Item, ItemImpl, ItemStruct, Type, TypePath};
+use syn::{Attribute, DeriveInput, Ident, ImplItem, Item, ItemFn, ItemImpl, Type, TypePath};
 use wit_parser::{FunctionKind, Resolve, TypeDefKind, WorldItem};
 
 /// Attribute used to skip exporting a method in a `#[wasvy::methods]` impl.
@@ -17,33 +17,183 @@ pub fn skip(_attr: TokenStream, item: TokenStream) -> TokenStream {
 ///
 /// This is required even for components without methods so they can be exported
 /// to mods and appear in generated WIT.
-#[proc_macro_derive(WasvyComponent)]
+///
+/// Use `#[wasvy(level = "internal")]` to change the component's export level
+/// (`public`, `modding-api` or `internal`).
+///
+/// The WIT resource is named after the type and lands in the components interface. Use
+/// `#[wasvy(rename = "hit-points")]` to pick another name, `#[wasvy(interface = "combat")]` to put
+/// it in its own interface, and `#[wasvy(skip_export)]` to leave the component out of the
+/// automatic registration, for example only in some builds:
+///
+/// ```ignore
+/// #[derive(Component, Reflect, Default, WasvyComponent)]
+/// #[reflect(Component)]
+/// #[wasvy(rename = "hit-points", interface = "combat")]
+/// #[cfg_attr(not(feature = "modding"), wasvy(skip_export))]
+/// struct Health {
+///     current: f32,
+/// }
+/// ```
+#[proc_macro_derive(WasvyComponent, attributes(wasvy))]
 pub fn derive_wasvy_component(input: TokenStream) -> TokenStream {
     let input = syn::parse_macro_input!(input as DeriveInput);
     let wasvy_path = wasvy_runtime_path();
-    let ident = &input.ident;
-    let register_ident = format_ident!("__wasvy_register_component_{}", ident);
 
-    let expanded = quote! {
-        impl #wasvy_path::authoring::WasvyComponent for #ident {}
+    let attrs = match ComponentAttrs::parse(&input.attrs) {
+        Ok(attrs) => attrs,
+        Err(err) => return err.to_compile_error().into(),
+    };
 
-        #[allow(non_snake_case)]
-        fn #register_ident(app: &mut #wasvy_path::authoring::App) {
-            <#ident as #wasvy_path::authoring::WasvyComponent>::register(app);
-        }
+    expand_component(&input.ident, &attrs, &wasvy_path).into()
+}
 
-        #wasvy_path::__wasvy_submit_component_registration!(
-            #wasvy_path::authoring::WasvyComponentRegistration { register: #register_ident }
-        );
+/// Derive macro to mark a Bevy resource as exported to Wasvy, so mods can invoke its methods.
+///
+/// Accepts the same `#[wasvy(...)]` options as [`WasvyComponent`](derive@WasvyComponent). Mods
+/// construct the WIT resource from a `res` system param:
+///
+/// ```ignore
+/// #[derive(Resource, Reflect, Default, WasvyResource)]
+/// #[reflect(Resource)]
+/// struct Score(u32);
+///
+/// #[wasvy::methods]
+/// impl Score {
+///     fn add(&mut self, points: u32) {
+///         self.0 += points;
+///     }
+/// }
+/// ```
+#[proc_macro_derive(WasvyResource, attributes(wasvy))]
+pub fn derive_wasvy_resource(input: TokenStream) -> TokenStream {
+    let input = syn::parse_macro_input!(input as DeriveInput);
+    let wasvy_path = wasvy_runtime_path();
+
+    let attrs = match ComponentAttrs::parse(&input.attrs) {
+        Ok(attrs) => attrs,
+        Err(err) => return err.to_compile_error().into(),
     };
 
-    expanded.into()
+    expand_export(
+        &input.ident,
+        &attrs,
+        &wasvy_path,
+        &format_ident!("WasvyResource"),
+    )
+    .into()
+}
+
+/// Options parsed from `#[wasvy(...)]` attributes on a `WasvyComponent`, or from the arguments of
+/// `#[wasvy::component(...)]`.
+#[derive(Default)]
+struct ComponentAttrs {
+    /// The `ExportLevel` variant
+    level: Option<Ident>,
+    /// The name of the WIT resource
+    rename: Option<syn::LitStr>,
+    /// The WIT interface of the resource
+    interface: Option<syn::LitStr>,
+    /// Whether the component is left out of the automatic registration
+    skip_export: bool,
+    /// The doc comment of the component, shown in the generated WIT
+    docs: Option<String>,
+}
+
+impl ComponentAttrs {
+    fn parse(attrs: &[Attribute]) -> syn::Result<Self> {
+        let mut out = Self::default();
+        for attr in attrs.iter().filter(|attr| attr.path().is_ident("wasvy")) {
+            attr.parse_nested_meta(|meta| out.parse_meta(meta))?;
+        }
+        out.set_docs(attrs);
+        Ok(out)
+    }
+
+    fn set_docs(&mut self, attrs: &[Attribute]) {
+        self.docs = Some(doc_comment(attrs)).filter(|docs| !docs.is_empty());
+    }
+
+    fn parse_meta(&mut self, meta: syn::meta::ParseNestedMeta) -> syn::Result<()> {
+        if meta.path.is_ident("level") {
+            let value: syn::LitStr = meta.value()?.parse()?;
+            let variant = match value.value().as_str() {
+                "public" => "Public",
+                "modding-api" => "ModdingApi",
+                "internal" => "Internal",
+                _ => {
+                    return Err(syn::Error::new_spanned(
+                        value,
+                        "expected \"public\", \"modding-api\" or \"internal\"",
+                    ));
+                }
+            };
+            self.level = Some(Ident::new(variant, value.span()));
+            Ok(())
+        } else if meta.path.is_ident("rename") {
+            self.rename = Some(parse_wit_name(&meta)?);
+            Ok(())
+        } else if meta.path.is_ident("interface") {
+            self.interface = Some(parse_wit_name(&meta)?);
+            Ok(())
+        } else if meta.path.is_ident("skip_export") {
+            self.skip_export = true;
+            Ok(())
+        } else {
+            Err(meta.error("unsupported wasvy attribute"))
+        }
+    }
+}
+
+/// The text of the `///` comments in `attrs`
+fn doc_comment(attrs: &[Attribute]) -> String {
+    let lines: Vec<String> = attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("doc"))
+        .filter_map(|attr| match &attr.meta {
+            syn::Meta::NameValue(syn::MetaNameValue {
+                value:
+                    syn::Expr::Lit(syn::ExprLit {
+                        lit: syn::Lit::Str(line),
+                        ..
+                    }),
+                ..
+            }) => Some(line.value()),
+            _ => None,
+        })
+        .map(|line| {
+            let line = line.trim_end();
+            line.strip_prefix(' ').unwrap_or(line).to_string()
+        })
+        .collect();
+    lines.join("\n").trim_matches('\n').to_string()
+}
+
+/// Parses a WIT name, which is kebab-case
+fn parse_wit_name(meta: &syn::meta::ParseNestedMeta) -> syn::Result<syn::LitStr> {
+    let value: syn::LitStr = meta.value()?.parse()?;
+    let name = value.value();
+    let valid = name.split('-').all(|word| {
+        word.chars().next().is_some_and(|c| c.is_ascii_lowercase())
+            && word
+                .chars()
+                .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit())
+    });
+    if !valid {
+        return Err(syn::Error::new_spanned(
+            value,
+            "expected a kebab-case WIT name, like \"hit-points\"",
+        ));
+    }
+    Ok(value)
 }
 
-/// Generate host-side bindings for the WIT components interface.
+/// Generate host-side bindings for the WIT components interfaces.
 ///
 /// This expands to `wasmtime::component::bindgen!`, implements host traits
-/// for `WasmHost`, and exposes an `add_components_to_linker` helper.
+/// for `WasmHost`, and exposes an `add_components_to_linker` helper. Every
+/// interface the world imports from its own package is bound, including the
+/// ones components were moved to with `#[wasvy(interface = "...")]`.
 ///
 /// # Example
 /// ```ignore
@@ -62,6 +212,48 @@ pub fn auto_host_components(input: TokenStream) -> TokenStream {
     }
 }
 
+/// Generate host-side bindings for a WIT interface of game APIs, implemented with closures.
+///
+/// Where `auto_host_components!` binds component resources, this binds the free functions of any
+/// interface the world imports, such as `give-gold: func(amount: u32)`. It expands to
+/// `wasmtime::component::bindgen!` for the interface's types, and a `functions` struct with a
+/// builder method per WIT function. Each closure gets a `HostContext` to access the calling mod,
+/// and may fail with a `wasmtime::Error`, which traps the mod. Calling a function that was not
+/// given a closure traps as well.
+///
+/// # Example
+/// ```ignore
+/// wasvy::host_interface! {
+///     path = "wit",
+///     world = "game:api/host",
+///     interface = "economy",
+///     module = economy_bindings,
+///     functions = EconomyFunctions,
+/// }
+///
+/// let economy = EconomyFunctions::default()
+///     .give_gold(|ctx, amount| {
+///         let mut commands = ctx.commands().ok_or_else(|| wasmtime::Error::msg("not in a system"))?;
+///         commands.trigger(GoldGiven { amount });
+///         Ok(())
+///     })
+///     .balance(|_ctx| Ok(100));
+///
+/// app.add_plugins(
+///     ModLoaderPlugin::default().add_functionality(move |linker| {
+///         economy.add_to_linker(linker).expect("link economy interface")
+///     }),
+/// );
+/// ```
+#[proc_macro]
+pub fn host_interface(input: TokenStream) -> TokenStream {
+    let args = syn::parse_macro_input!(input as HostInterfaceArgs);
+    match expand_host_interface(args) {
+        Ok(tokens) => tokens.into(),
+        Err(err) => err.to_compile_error().into(),
+    }
+}
+
 /// Generate `type_path` helpers for guest bindings.
 ///
 /// This reads `wasvy:type-path=` doc tags from resources and adds
@@ -89,6 +281,10 @@ pub fn guest_type_paths(input: TokenStream) -> TokenStream {
 ///
 /// This is intended for mods so they only need to call this macro.
 ///
+/// The records of built-in math types (`vec2`, `vec3`, `vec4`, `quat`) convert from and to
+/// `[f32; N]`. When the mod depends on `glam`, `bevy_math` or `bevy`, they also convert from and to
+/// the `glam` types, as do `mat4` and `transform`.
+///
 /// # Example
 /// ```ignore
 /// wasvy::guest_bindings!({
@@ -106,6 +302,92 @@ pub fn guest_bindings(input: TokenStream) -> TokenStream {
     }
 }
 
+/// Generates constructors for the systems a mod exports from its `Guest` impl.
+///
+/// Each exported function besides `setup` gets a function of the same name in a `systems` module
+/// (or the one given with `module = ...`), which creates the `System` with the exported name and
+/// adds its params in order. Queries take their components, event readers their event type path
+/// and resources their type path and `Mutability` as arguments. This way, the name and params of a
+/// system can't get out of sync with its export.
+///
+/// Supported params are `Commands`, `Query`, `EventReader`, `Res` and `Entity` (for observers).
+/// `System`, `QueryFor` and `Mutability` must be in scope along with them.
+///
+/// # Example
+/// ```ignore
+/// #[wasvy::guest_systems]
+/// impl Guest for GuestComponent {
+///     fn setup(app: App) {
+///         let heal_system = systems::heal_system(&[QueryFor::Mut(Health::type_path())]);
+///         app.add_systems(&Schedule::Update, &[&heal_system]);
+///     }
+///
+///     fn heal_system(query: Query) {
+///         // ...
+///     }
+/// }
+/// ```
+#[proc_macro_attribute]
+pub fn guest_systems(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let mut module = format_ident!("systems");
+    let parser = syn::meta::parser(|meta| {
+        if meta.path.is_ident("module") {
+            module = meta.value()?.parse()?;
+            Ok(())
+        } else {
+            Err(meta.error("unsupported guest_systems option"))
+        }
+    });
+    syn::parse_macro_input!(attr with parser);
+    let input = syn::parse_macro_input!(item as ItemImpl);
+
+    match expand_guest_systems(&input, &module) {
+        Ok(tokens) => quote! {
+            #input
+            #tokens
+        }
+        .into(),
+        Err(err) => err.to_compile_error().into(),
+    }
+}
+
+/// Declares a typed query for a mod, so systems don't need to index and (de)serialize components
+/// by hand.
+///
+/// Each field is a component of the query, deserialized from JSON with `serde_json`. Fields are
+/// read-only unless marked `mut`, and `with`/`without` add filters. Component type paths default to
+/// the `TYPE_PATH` of the type, such as the ones `guest_bindings!` generates, or can be given as a
+/// string. Filters may be given as just a type path.
+///
+/// The struct gets a `query_for` function returning the params to pass to `System::add_query`,
+/// an `iter` function returning the results of a `Query`, and a `set` method writing its mutable
+/// components back. `Query`, `QueryFor` and `Component` must be in scope.
+///
+/// # Example
+/// ```ignore
+/// wasvy::guest_query! {
+///     struct SpinCube {
+///         transform: mut Transform = "bevy_transform::components::transform::Transform",
+///         with MyMarker,
+///         without "bevy_camera::camera::Camera",
+///     }
+/// }
+///
+/// // In setup
+/// spin_cube.add_query(&SpinCube::query_for());
+///
+/// // In the system
+/// for mut cube in SpinCube::iter(&query) {
+///     cube.transform.rotate(Quat::from_rotation_y(0.025));
+///     cube.set();
+/// }
+/// ```
+#[proc_macro]
+pub fn guest_query(input: TokenStream) -> TokenStream {
+    let query = syn::parse_macro_input!(input as GuestQuery);
+    expand_guest_query(query).into()
+}
+
 /// Include all Rust modules under a path that contain Wasvy macros.
 ///
 /// This is primarily used in `build.rs` to ensure `inventory` sees all
@@ -139,31 +421,30 @@ pub fn include_wasvy_components(input: TokenStream) -> TokenStream {
 ///     pub max: f32,
 /// }
 /// ```
+///
+/// Accepts the same options as `#[wasvy(...)]` on the derive macro, such as
+/// `#[wasvy::component(rename = "hit-points", interface = "combat")]` or
+/// `#[wasvy::component(skip_export)]`.
 #[deprecated(note = "Use #[derive(WasvyComponent)] instead")]
 #[proc_macro_attribute]
-pub fn component(_attr: TokenStream, item: TokenStream) -> TokenStream {
+pub fn component(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let mut attrs = ComponentAttrs::default();
+    let parser = syn::meta::parser(|meta| attrs.parse_meta(meta));
+    syn::parse_macro_input!(attr with parser);
+
     let input = syn::parse_macro_input!(item as Item);
     let runtime_path = wasvy_runtime_path();
 
     let expanded = match input {
-        Item::Struct(item) => expand_component_struct(item, &runtime_path),
+        Item::Struct(item) => {
+            attrs.set_docs(&item.attrs);
+            let component = expand_component(&item.ident, &attrs, &runtime_path);
+            quote!(#item #component)
+        }
         Item::Enum(item) => {
-            let ident = &item.ident;
-            let register_ident = format_ident!("__wasvy_register_component_{}", ident);
-            quote! {
-                #item
-
-                impl #runtime_path::authoring::WasvyComponent for #ident {}
-
-                #[allow(non_snake_case)]
-                fn #register_ident(app: &mut #runtime_path::authoring::App) {
-                    <#ident as #runtime_path::authoring::WasvyComponent>::register(app);
-                }
-
-                #runtime_path::__wasvy_submit_component_registration!(
-                    #runtime_path::authoring::WasvyComponentRegistration { register: #register_ident }
-                );
-            }
+            attrs.set_docs(&item.attrs);
+            let component = expand_component(&item.ident, &attrs, &runtime_path);
+            quote!(#item #component)
         }
         other => {
             return syn::Error::new_spanned(
@@ -184,10 +465,17 @@ pub fn component(_attr: TokenStream, item: TokenStream) -> TokenStream {
 /// argument names are captured for WIT generation. Use `#[wasvy::skip]` to
 /// exclude a method from export.
 ///
+/// Associated functions without a receiver, such as constructors, are exported as
+/// static functions of the WIT resource, which guests call without a component.
+///
 /// # Example
 /// ```ignore
 /// #[wasvy::methods]
 /// impl Health {
+///     pub fn full(max: f32) -> Health {
+///         Health { current: max, max }
+///     }
+///
 ///     pub fn heal(&mut self, amount: f32) {
 ///         self.current = (self.current + amount).min(self.max);
 ///     }
@@ -229,30 +517,18 @@ pub fn methods(_attr: TokenStream, item: TokenStream) -> TokenStream {
                     continue;
                 }
 
-                match func.sig.receiver() {
-                    Some(receiver) => {
-                        if receiver.reference.is_none() {
-                            let err = syn::Error::new_spanned(
-                                &func.sig,
-                                "#[wasvy::methods] only supports &self or &mut self receivers; add #[wasvy::skip] or move this method to another impl",
-                            );
-                            if let Some(errors) = errors.as_mut() {
-                                errors.combine(err);
-                            } else {
-                                errors = Some(err);
-                            }
-                        }
-                    }
-                    None => {
-                        let err = syn::Error::new_spanned(
-                            &func.sig,
-                            "#[wasvy::methods] requires a self receiver; add #[wasvy::skip] or move this method to another impl",
-                        );
-                        if let Some(errors) = errors.as_mut() {
-                            errors.combine(err);
-                        } else {
-                            errors = Some(err);
-                        }
+                let is_static = func.sig.receiver().is_none();
+                if let Some(receiver) = func.sig.receiver()
+                    && receiver.reference.is_none()
+                {
+                    let err = syn::Error::new_spanned(
+                        &func.sig,
+                        "#[wasvy::methods] only supports &self or &mut self receivers; add #[wasvy::skip] or move this method to another impl",
+                    );
+                    if let Some(errors) = errors.as_mut() {
+                        errors.combine(err);
+                    } else {
+                        errors = Some(err);
                     }
                 }
 
@@ -273,7 +549,8 @@ pub fn methods(_attr: TokenStream, item: TokenStream) -> TokenStream {
                 let method_lit =
                     syn::LitStr::new(&method_ident.to_string(), proc_macro2::Span::call_site());
                 let mut arg_names = Vec::new();
-                for (idx, arg) in func.sig.inputs.iter().skip(1).enumerate() {
+                let receivers = if is_static { 0 } else { 1 };
+                for (idx, arg) in func.sig.inputs.iter().skip(receivers).enumerate() {
                     if let syn::FnArg::Typed(pat) = arg {
                         match pat.pat.as_ref() {
                             syn::Pat::Ident(ident) => {
@@ -301,6 +578,7 @@ pub fn methods(_attr: TokenStream, item: TokenStream) -> TokenStream {
                     .map(|name| syn::LitStr::new(name, proc_macro2::Span::call_site()))
                     .collect();
                 let metadata_ident = format_ident!("__wasvy_args_{}_{}", type_ident, method_ident);
+                let docs = doc_comment(&func.attrs);
 
                 metadata_submits.push(quote! {
                     #[allow(non_upper_case_globals)]
@@ -310,6 +588,8 @@ pub fn methods(_attr: TokenStream, item: TokenStream) -> TokenStream {
                             type_path: #type_path_expr,
                             method: #method_lit,
                             arg_names: #metadata_ident,
+                            is_static: #is_static,
+                            docs: #docs,
                         }
                     );
                 });
@@ -355,20 +635,265 @@ pub fn methods(_attr: TokenStream, item: TokenStream) -> TokenStream {
     }
 }
 
-fn expand_component_struct(
-    item: ItemStruct,
+/// Export a free function taking a component as its first argument for Wasvy.
+///
+/// The function is exported as a method of that component, alongside those from
+/// `#[wasvy::methods]`, so existing game logic can be exported without moving it into an
+/// `impl` block. The first argument must be `&Component` or `&mut Component`.
+///
+/// The component must be named by its identifier (when it's defined in the same module) or
+/// by a `crate::` path.
+///
+/// # Example
+/// ```ignore
+/// #[wasvy::export_fn(Health)]
+/// fn heal(health: &mut Health, amount: f32) {
+///     health.current = (health.current + amount).min(health.max);
+/// }
+/// ```
+#[proc_macro_attribute]
+pub fn export_fn(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let component = syn::parse_macro_input!(attr as syn::Path);
+    let func = syn::parse_macro_input!(item as ItemFn);
+    match expand_export_fn(component, func) {
+        Ok(tokens) => tokens.into(),
+        Err(err) => err.to_compile_error().into(),
+    }
+}
+
+fn expand_export_fn(component: syn::Path, func: ItemFn) -> syn::Result<proc_macro2::TokenStream> {
+    let wasvy_path = wasvy_runtime_path();
+
+    // Type paths are submitted as constants, so they are built from the module path
+    let segments: Vec<String> = component
+        .segments
+        .iter()
+        .map(|segment| segment.ident.to_string())
+        .collect();
+    let type_path_expr = match segments.as_slice() {
+        [ident] => quote!(concat!(module_path!(), "::", #ident)),
+        [first, rest @ ..] if first == "crate" => {
+            let rest = rest.join("::");
+            quote!(concat!(env!("CARGO_CRATE_NAME"), "::", #rest))
+        }
+        [first, ..] if first == "self" || first == "super" => {
+            return Err(syn::Error::new_spanned(
+                &component,
+                "#[wasvy::export_fn] requires the component's name or a crate:: path",
+            ));
+        }
+        _ => {
+            let path = segments.join("::");
+            quote!(#path)
+        }
+    };
+
+    if !func.sig.generics.params.is_empty() {
+        return Err(syn::Error::new_spanned(
+            &func.sig.generics,
+            "#[wasvy::export_fn] does not support generic functions",
+        ));
+    }
+
+    let mut inputs = func.sig.inputs.iter();
+    let receiver_is_ref = match inputs.next() {
+        Some(syn::FnArg::Typed(pat)) => matches!(pat.ty.as_ref(), Type::Reference(_)),
+        _ => false,
+    };
+    if !receiver_is_ref {
+        return Err(syn::Error::new_spanned(
+            &func.sig,
+            "#[wasvy::export_fn] requires the first argument to be &Component or &mut Component",
+        ));
+    }
+
+    let mut arg_names = Vec::new();
+    for arg in inputs {
+        let syn::FnArg::Typed(pat) = arg else {
+            unreachable!("free functions have no receiver");
+        };
+        let syn::Pat::Ident(ident) = pat.pat.as_ref() else {
+            return Err(syn::Error::new_spanned(
+                pat.pat.as_ref(),
+                "#[wasvy::export_fn] only supports identifier arguments",
+            ));
+        };
+        arg_names.push(syn::LitStr::new(
+            &ident.ident.to_string(),
+            proc_macro2::Span::call_site(),
+        ));
+    }
+
+    let fn_ident = &func.sig.ident;
+    let method_lit = syn::LitStr::new(&fn_ident.to_string(), proc_macro2::Span::call_site());
+    let metadata_ident = format_ident!("__wasvy_args_fn_{}", fn_ident);
+    let docs = doc_comment(&func.attrs);
+    let register_ident = format_ident!("__wasvy_register_fn_{}", fn_ident);
+
+    Ok(quote! {
+        #func
+
+        #[allow(non_upper_case_globals)]
+        const #metadata_ident: &[&str] = &[#(#arg_names),*];
+        #wasvy_path::__wasvy_submit_method_metadata!(
+            #wasvy_path::authoring::WasvyMethodMetadata {
+                type_path: #type_path_expr,
+                method: #method_lit,
+                arg_names: #metadata_ident,
+                is_static: false,
+                docs: #docs,
+            }
+        );
+
+        #[allow(non_snake_case)]
+        fn #register_ident(app: &mut #wasvy_path::authoring::App) {
+            app.register_function(#fn_ident);
+        }
+
+        #wasvy_path::__wasvy_submit_methods_registration!(
+            #wasvy_path::authoring::WasvyMethodsRegistration { register: #register_ident }
+        );
+    })
+}
+
+/// Export a free function without a receiver to mods.
+///
+/// The function is a WIT function of the components interface, which mods call like any other
+/// imported function, without a component. Arguments and the result are passed like those of
+/// methods.
+///
+/// # Example
+/// ```ignore
+/// /// Gives the player gold
+/// #[wasvy::host_fn]
+/// fn give_gold(amount: u32) {
+///     GOLD.fetch_add(amount, Ordering::Relaxed);
+/// }
+/// ```
+#[proc_macro_attribute]
+pub fn host_fn(_attr: TokenStream, item: TokenStream) -> TokenStream {
+    let func = syn::parse_macro_input!(item as ItemFn);
+    match expand_host_fn(func) {
+        Ok(tokens) => tokens.into(),
+        Err(err) => err.to_compile_error().into(),
+    }
+}
+
+fn expand_host_fn(func: ItemFn) -> syn::Result<proc_macro2::TokenStream> {
+    let wasvy_path = wasvy_runtime_path();
+
+    if !func.sig.generics.params.is_empty() {
+        return Err(syn::Error::new_spanned(
+            &func.sig.generics,
+            "#[wasvy::host_fn] does not support generic functions",
+        ));
+    }
+
+    let mut arg_names = Vec::new();
+    for arg in &func.sig.inputs {
+        let syn::FnArg::Typed(pat) = arg else {
+            return Err(syn::Error::new_spanned(
+                arg,
+                "#[wasvy::host_fn] requires a free function; use #[wasvy::methods] for methods",
+            ));
+        };
+        let syn::Pat::Ident(ident) = pat.pat.as_ref() else {
+            return Err(syn::Error::new_spanned(
+                pat.pat.as_ref(),
+                "#[wasvy::host_fn] only supports identifier arguments",
+            ));
+        };
+        arg_names.push(syn::LitStr::new(
+            &ident.ident.to_string(),
+            proc_macro2::Span::call_site(),
+        ));
+    }
+
+    let fn_ident = &func.sig.ident;
+    let name_lit = syn::LitStr::new(&fn_ident.to_string(), proc_macro2::Span::call_site());
+    let metadata_ident = format_ident!("__wasvy_args_host_fn_{}", fn_ident);
+    let docs = doc_comment(&func.attrs);
+    let register_ident = format_ident!("__wasvy_register_host_fn_{}", fn_ident);
+
+    Ok(quote! {
+        #func
+
+        #[allow(non_upper_case_globals)]
+        const #metadata_ident: &[&str] = &[#(#arg_names),*];
+        #wasvy_path::__wasvy_submit_host_fn_metadata!(
+            #wasvy_path::authoring::WasvyHostFnMetadata {
+                function_name: concat!(module_path!(), "::", #name_lit),
+                name: #name_lit,
+                arg_names: #metadata_ident,
+                docs: #docs,
+            }
+        );
+
+        #[allow(non_snake_case)]
+        fn #register_ident(app: &mut #wasvy_path::authoring::App) {
+            app.register_function(#fn_ident);
+        }
+
+        #wasvy_path::__wasvy_submit_methods_registration!(
+            #wasvy_path::authoring::WasvyMethodsRegistration { register: #register_ident }
+        );
+    })
+}
+
+/// Implements `WasvyComponent` and submits its registration, unless `skip_export` is set
+fn expand_component(
+    ident: &Ident,
+    attrs: &ComponentAttrs,
+    wasvy_path: &proc_macro2::TokenStream,
+) -> proc_macro2::TokenStream {
+    expand_export(ident, attrs, wasvy_path, &format_ident!("WasvyComponent"))
+}
+
+/// Implements `export_trait` (`WasvyComponent` or `WasvyResource`) for `ident`, and submits its
+/// registration unless it skips the export
+fn expand_export(
+    ident: &Ident,
+    attrs: &ComponentAttrs,
     wasvy_path: &proc_macro2::TokenStream,
+    export_trait: &Ident,
 ) -> proc_macro2::TokenStream {
-    let ident = &item.ident;
+    let level = attrs.level.as_ref().map(|level| {
+        quote! {
+            const LEVEL: #wasvy_path::authoring::ExportLevel = #wasvy_path::authoring::ExportLevel::#level;
+        }
+    });
+    let docs = attrs
+        .docs
+        .as_ref()
+        .map(|docs| quote!(const DOCS: Option<&'static str> = Some(#docs);));
+    let name = attrs
+        .rename
+        .as_ref()
+        .map(|name| quote!(const NAME: Option<&'static str> = Some(#name);));
+    let interface = attrs
+        .interface
+        .as_ref()
+        .map(|interface| quote!(const INTERFACE: Option<&'static str> = Some(#interface);));
+
+    let component = quote! {
+        impl #wasvy_path::authoring::#export_trait for #ident {
+            #level
+            #name
+            #interface
+            #docs
+        }
+    };
+    if attrs.skip_export {
+        return component;
+    }
+
     let register_ident = format_ident!("__wasvy_register_component_{}", ident);
     quote! {
-        #item
-
-        impl #wasvy_path::authoring::WasvyComponent for #ident {}
+        #component
 
         #[allow(non_snake_case)]
         fn #register_ident(app: &mut #wasvy_path::authoring::App) {
-            <#ident as #wasvy_path::authoring::WasvyComponent>::register(app);
+            <#ident as #wasvy_path::authoring::#export_trait>::register(app);
         }
 
         #wasvy_path::__wasvy_submit_component_registration!(
@@ -430,6 +955,14 @@ struct AutoHostArgs {
     module: Ident,
 }
 
+struct HostInterfaceArgs {
+    path: syn::LitStr,
+    world: syn::LitStr,
+    interface: syn::LitStr,
+    module: Ident,
+    functions: Ident,
+}
+
 struct GuestTypePathsArgs {
     path: syn::LitStr,
     package: syn::LitStr,
@@ -437,6 +970,26 @@ struct GuestTypePathsArgs {
     module: syn::Path,
 }
 
+struct GuestQuery {
+    attrs: Vec<Attribute>,
+    vis: syn::Visibility,
+    ident: Ident,
+    params: Vec<QueryParam>,
+}
+
+struct QueryParam {
+    kind: QueryParamKind,
+    ty: Option<Type>,
+    type_path: Option<syn::LitStr>,
+}
+
+enum QueryParamKind {
+    Ref(Ident),
+    Mut(Ident),
+    With,
+    Without,
+}
+
 struct GuestBindingsArgs {
     paths: Vec<syn::LitStr>,
 }
@@ -481,21 +1034,23 @@ impl syn::parse::Parse for AutoHostArgs {
     }
 }
 
-impl syn::parse::Parse for GuestTypePathsArgs {
-    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+impl syn::parse::Parse for HostInterfaceArgs {
+    fn parse(input: syn::parse::ParseStream<'_>) -> syn::Result<Self> {
         let mut path = None;
-        let mut package = None;
+        let mut world = None;
         let mut interface = None;
         let mut module = None;
+        let mut functions = None;
 
         while !input.is_empty() {
             let key: Ident = input.parse()?;
             let _: syn::Token![=] = input.parse()?;
             match key.to_string().as_str() {
                 "path" => path = Some(input.parse()?),
-                "package" => package = Some(input.parse()?),
+                "world" => world = Some(input.parse()?),
                 "interface" => interface = Some(input.parse()?),
                 "module" => module = Some(input.parse()?),
+                "functions" => functions = Some(input.parse()?),
                 _ => return Err(input.error("unsupported key")),
             }
 
@@ -506,20 +1061,108 @@ impl syn::parse::Parse for GuestTypePathsArgs {
 
         Ok(Self {
             path: path.ok_or_else(|| input.error("missing `path`"))?,
-            package: package.ok_or_else(|| input.error("missing `package`"))?,
+            world: world.ok_or_else(|| input.error("missing `world`"))?,
             interface: interface.ok_or_else(|| input.error("missing `interface`"))?,
             module: module.ok_or_else(|| input.error("missing `module`"))?,
+            functions: functions.ok_or_else(|| input.error("missing `functions`"))?,
         })
     }
 }
 
-impl syn::parse::Parse for GuestBindingsArgs {
+impl syn::parse::Parse for GuestTypePathsArgs {
     fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
-        let tokens: proc_macro2::TokenStream = input.parse()?;
-        let paths = extract_paths_from_stream(tokens)?;
-        Ok(Self { paths })
-    }
-}
+        let mut path = None;
+        let mut package = None;
+        let mut interface = None;
+        let mut module = None;
+
+        while !input.is_empty() {
+            let key: Ident = input.parse()?;
+            let _: syn::Token![=] = input.parse()?;
+            match key.to_string().as_str() {
+                "path" => path = Some(input.parse()?),
+                "package" => package = Some(input.parse()?),
+                "interface" => interface = Some(input.parse()?),
+                "module" => module = Some(input.parse()?),
+                _ => return Err(input.error("unsupported key")),
+            }
+
+            if input.peek(syn::Token![,]) {
+                let _: syn::Token![,] = input.parse()?;
+            }
+        }
+
+        Ok(Self {
+            path: path.ok_or_else(|| input.error("missing `path`"))?,
+            package: package.ok_or_else(|| input.error("missing `package`"))?,
+            interface: interface.ok_or_else(|| input.error("missing `interface`"))?,
+            module: module.ok_or_else(|| input.error("missing `module`"))?,
+        })
+    }
+}
+
+impl syn::parse::Parse for GuestQuery {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let attrs = input.call(Attribute::parse_outer)?;
+        let vis = input.parse()?;
+        let _: syn::Token![struct] = input.parse()?;
+        let ident = input.parse()?;
+
+        let content;
+        syn::braced!(content in input);
+        let mut params = Vec::new();
+        while !content.is_empty() {
+            let name: Ident = content.parse()?;
+            let kind = match name.to_string().as_str() {
+                "with" => QueryParamKind::With,
+                "without" => QueryParamKind::Without,
+                _ => {
+                    let _: syn::Token![:] = content.parse()?;
+                    if content.parse::<Option<syn::Token![mut]>>()?.is_some() {
+                        QueryParamKind::Mut(name)
+                    } else {
+                        QueryParamKind::Ref(name)
+                    }
+                }
+            };
+            // Filters may only give a type path
+            let is_filter = matches!(kind, QueryParamKind::With | QueryParamKind::Without);
+            let (ty, type_path) = if is_filter && content.peek(syn::LitStr) {
+                (None, Some(content.parse()?))
+            } else {
+                let ty = content.parse()?;
+                match content.parse::<Option<syn::Token![=]>>()? {
+                    Some(_) => (Some(ty), Some(content.parse()?)),
+                    None => (Some(ty), None),
+                }
+            };
+            params.push(QueryParam {
+                kind,
+                ty,
+                type_path,
+            });
+
+            if !content.is_empty() {
+                let _: syn::Token![,] = content.parse()?;
+            }
+        }
+
+        Ok(Self {
+            attrs,
+            vis,
+            ident,
+            params,
+        })
+    }
+}
+
+impl syn::parse::Parse for GuestBindingsArgs {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let tokens: proc_macro2::TokenStream = input.parse()?;
+        let paths = extract_paths_from_stream(tokens)?;
+        Ok(Self { paths })
+    }
+}
 
 impl syn::parse::Parse for IncludeComponentsArgs {
     fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
@@ -543,125 +1186,192 @@ fn expand_auto_host_components(args: AutoHostArgs) -> syn::Result<proc_macro2::T
         .map_err(|err| syn::Error::new(args.world.span(), err.to_string()))?;
     let world = &resolve.worlds[world_id];
 
-    let interface_id = world
+    // Every interface the world imports from its own package holds component resources
+    let interface_ids: Vec<_> = world
         .imports
-        .iter()
-        .find_map(|(name, item)| match (name, item) {
-            (wit_parser::WorldKey::Name(name), WorldItem::Interface { id, .. })
-                if name == "components" =>
-            {
+        .values()
+        .filter_map(|item| match item {
+            WorldItem::Interface { id, .. } if resolve.interfaces[*id].package == Some(pkg_id) => {
                 Some(*id)
             }
-            (wit_parser::WorldKey::Interface(id), WorldItem::Interface { .. }) => {
-                let iface = &resolve.interfaces[*id];
-                if iface.name.as_deref() == Some("components") {
-                    Some(*id)
-                } else {
-                    None
-                }
-            }
             _ => None,
         })
-        .ok_or_else(|| {
-            syn::Error::new(args.world.span(), "missing `components` interface import")
-        })?;
-    let interface = &resolve.interfaces[interface_id];
-    let package_id = interface
-        .package
-        .ok_or_else(|| syn::Error::new(args.world.span(), "interface has no package"))?;
-    let package = &resolve.packages[package_id];
+        .collect();
+    if interface_ids.is_empty() {
+        return Err(syn::Error::new(
+            args.world.span(),
+            "missing `components` interface import",
+        ));
+    }
+    let package = &resolve.packages[pkg_id];
 
     let pkg_namespace = rust_ident(&package.name.namespace.to_string());
     let pkg_name = rust_ident(&package.name.name.to_string());
-    let interface_name = rust_ident(interface.name.as_deref().unwrap_or("components"));
 
     let module_ident = args.module;
 
     let mut with_entries = Vec::new();
-    for (name, type_id) in interface.types.iter() {
-        let type_def = &resolve.types[*type_id];
-        if !matches!(type_def.kind, TypeDefKind::Resource) {
-            continue;
-        }
-        let path = format!(
-            "{}:{}/{}.{}",
-            package.name.namespace,
-            package.name.name,
-            interface.name.as_deref().unwrap_or("components"),
-            name
-        );
-        let lit = syn::LitStr::new(&path, proc_macro2::Span::call_site());
-        with_entries.push(quote!(#lit: #wasm_path::host::WasmComponent));
-    }
-
-    let wasvy_component =
-        syn::LitStr::new("wasvy:ecs/app.component", proc_macro2::Span::call_site());
-    with_entries.push(quote!(#wasvy_component: #wasm_path::host::WasmComponent));
-
     let mut impls = Vec::new();
+    let mut add_to_linker = Vec::new();
+    for interface_id in interface_ids {
+        let interface = &resolve.interfaces[interface_id];
+        let interface_name = rust_ident(interface.name.as_deref().unwrap_or("components"));
 
-    for (name, type_id) in interface.types.iter() {
-        let type_def = &resolve.types[*type_id];
-        if !matches!(type_def.kind, TypeDefKind::Resource) {
-            continue;
+        for (name, type_id) in interface.types.iter() {
+            let type_def = &resolve.types[*type_id];
+            if !matches!(type_def.kind, TypeDefKind::Resource) {
+                continue;
+            }
+            let path = format!(
+                "{}:{}/{}.{}",
+                package.name.namespace,
+                package.name.name,
+                interface.name.as_deref().unwrap_or("components"),
+                name
+            );
+            let lit = syn::LitStr::new(&path, proc_macro2::Span::call_site());
+            let host_path = HostHandle::of(&resolve, *type_id).host_path(&wasm_path);
+            with_entries.push(quote!(#lit: #host_path));
         }
-        let trait_ident = format_ident!("Host{}", upper_camel(name));
 
-        let mut methods = Vec::new();
-        for function in interface.functions.values() {
-            match function.kind {
-                FunctionKind::Constructor(id) if id == *type_id => {
-                    let params = render_params(&resolve, &function.params, &wasm_path, true);
-                    let ret_tokens =
-                        quote!(::wasmtime::component::Resource<#wasm_path::host::WasmComponent>);
-                    let body = quote!(component);
-                    methods.push(quote! {
-                        fn new(&mut self, #params) -> #ret_tokens {
-                            #body
-                        }
-                    });
-                }
-                FunctionKind::Method(id) if id == *type_id => {
-                    let method_name = method_name(&function.name);
-                    let method_ident = rust_ident(&method_name);
-                    let params = render_params(&resolve, &function.params, &wasm_path, false);
-                    let ret = render_return(&resolve, function.result.as_ref(), &wasm_path);
-                    let invoke = render_invoke_body(
-                        &method_name,
-                        &function.params,
-                        function.result.as_ref(),
-                        &runtime_path,
-                        &wasm_path,
-                    );
-                    methods.push(quote! {
-                        fn #method_ident(&mut self, #params) #ret {
-                            #invoke
-                        }
-                    });
+        for (name, type_id) in interface.types.iter() {
+            let type_def = &resolve.types[*type_id];
+            if !matches!(type_def.kind, TypeDefKind::Resource) {
+                continue;
+            }
+            let trait_ident = format_ident!("Host{}", upper_camel(name));
+            let handle = HostHandle::of(&resolve, *type_id);
+            let handle_ident = handle.ident();
+            let host_type = handle.host_type(&wasm_path);
+
+            let mut methods = Vec::new();
+            for function in interface.functions.values() {
+                match function.kind {
+                    FunctionKind::Constructor(id) if id == *type_id => {
+                        let params = render_params(&resolve, &function.params, &wasm_path, None);
+                        let body = function
+                            .params
+                            .first()
+                            .map(|param| rust_ident(&param.name))
+                            .unwrap_or(handle_ident.clone());
+                        methods.push(quote! {
+                            fn new(&mut self, #params) -> #host_type {
+                                #body
+                            }
+                        });
+                    }
+                    FunctionKind::Method(id) if id == *type_id => {
+                        let method_name = method_name(&function.name);
+                        let method_ident = rust_ident(&method_name);
+                        let params =
+                            render_params(&resolve, &function.params, &wasm_path, Some(handle));
+                        let ret = render_return(&resolve, function.result.as_ref(), &wasm_path);
+                        let invoke = render_invoke_body(
+                            &method_name,
+                            InvokeTarget::Handle(handle),
+                            &function.params,
+                            function.result.as_ref(),
+                            &runtime_path,
+                            &wasm_path,
+                        );
+                        methods.push(quote! {
+                            fn #method_ident(&mut self, #params) #ret {
+                                #invoke
+                            }
+                        });
+                    }
+                    FunctionKind::Static(id) if id == *type_id => {
+                        let type_path = extract_wit_type_path(&type_def.docs).ok_or_else(|| {
+                            syn::Error::new(
+                                args.world.span(),
+                                format!("resource `{name}` missing wasvy:type-path doc"),
+                            )
+                        })?;
+                        let method_name = method_name(&function.name);
+                        let method_ident = rust_ident(&method_name);
+                        let params = render_params(&resolve, &function.params, &wasm_path, None);
+                        let ret = render_return(&resolve, function.result.as_ref(), &wasm_path);
+                        let invoke = render_invoke_body(
+                            &method_name,
+                            InvokeTarget::Static(&type_path),
+                            &function.params,
+                            function.result.as_ref(),
+                            &runtime_path,
+                            &wasm_path,
+                        );
+                        methods.push(quote! {
+                            fn #method_ident(&mut self, #params) #ret {
+                                #invoke
+                            }
+                        });
+                    }
+                    _ => {}
                 }
-                _ => {}
             }
+
+            methods.push(quote! {
+                fn drop(&mut self, #handle_ident: #host_type) -> Result<(), ::wasmtime::Error> {
+                    let _ = #handle_ident;
+                    Ok(())
+                }
+            });
+
+            let trait_path =
+                quote!(#module_ident::#pkg_namespace::#pkg_name::#interface_name::#trait_ident);
+            impls.push(quote! {
+                impl #trait_path for #wasm_path::host::WasmHost {
+                    #(#methods)*
+                }
+            });
         }
 
-        methods.push(quote! {
-            fn drop(&mut self, component: ::wasmtime::component::Resource<#wasm_path::host::WasmComponent>) -> Result<(), ::wasmtime::Error> {
-                let _ = component;
-                Ok(())
+        // Free functions of the interface are host functions, see `#[wasvy::host_fn]`
+        let mut functions = Vec::new();
+        for function in interface.functions.values() {
+            if !matches!(function.kind, FunctionKind::Freestanding) {
+                continue;
             }
-        });
+            let function_ident = rust_ident(&function.name);
+            let params = render_params(&resolve, &function.params, &wasm_path, None);
+            let ret = render_return(&resolve, function.result.as_ref(), &wasm_path);
+            let invoke = render_invoke_body(
+                &function.name,
+                InvokeTarget::HostFn,
+                &function.params,
+                function.result.as_ref(),
+                &runtime_path,
+                &wasm_path,
+            );
+            functions.push(quote! {
+                fn #function_ident(&mut self, #params) #ret {
+                    #invoke
+                }
+            });
+        }
 
-        let trait_path =
-            quote!(#module_ident::#pkg_namespace::#pkg_name::#interface_name::#trait_ident);
+        let interface_path = quote!(#module_ident::#pkg_namespace::#pkg_name::#interface_name);
         impls.push(quote! {
-            impl #trait_path for #wasm_path::host::WasmHost {
-                #(#methods)*
+            impl #interface_path::Host for #wasm_path::host::WasmHost {
+                #(#functions)*
             }
         });
+        add_to_linker.push(quote! {
+            #interface_path::add_to_linker::<_, Data>(linker, |state| state)
+                .expect("implement components interface");
+        });
     }
 
-    let trait_host_path = quote!(#module_ident::#pkg_namespace::#pkg_name::#interface_name::Host);
-    let add_to_linker_path =
-        quote!(#module_ident::#pkg_namespace::#pkg_name::#interface_name::add_to_linker);
+    let wasvy_component =
+        syn::LitStr::new("wasvy:ecs/app.component", proc_macro2::Span::call_site());
+    with_entries.push(quote!(#wasvy_component: #wasm_path::host::WasmComponent));
+    if resolve
+        .types
+        .iter()
+        .any(|(id, _)| HostHandle::is_wasvy_res(&resolve, id))
+    {
+        let wasvy_res = syn::LitStr::new("wasvy:ecs/app.res", proc_macro2::Span::call_site());
+        with_entries.push(quote!(#wasvy_res: #wasm_path::host::WasmRes));
+    }
 
     let expanded = quote! {
         mod #module_ident {
@@ -674,18 +1384,253 @@ fn expand_auto_host_components(args: AutoHostArgs) -> syn::Result<proc_macro2::T
 
         pub fn add_components_to_linker(linker: &mut #wasm_path::engine::Linker) {
             type Data = ::wasmtime::component::HasSelf<#wasm_path::host::WasmHost>;
-            #add_to_linker_path::<_, Data>(linker, |state| state)
-                .expect("implement components interface");
+            #(#add_to_linker)*
         }
 
-        impl #trait_host_path for #wasm_path::host::WasmHost {}
-
         #(#impls)*
     };
 
     Ok(expanded)
 }
 
+fn expand_host_interface(args: HostInterfaceArgs) -> syn::Result<proc_macro2::TokenStream> {
+    let wasm_path = wasvy_wasm_path();
+    let path_value = resolve_wit_path(&args.path);
+    let world_value = args.world.value();
+    let interface_value = args.interface.value();
+
+    let mut resolve = Resolve::default();
+    let (pkg_id, _sources) = resolve
+        .push_path(&path_value)
+        .map_err(|err| syn::Error::new(args.path.span(), err.to_string()))?;
+    let world_id = resolve
+        .select_world(&[pkg_id], Some(&world_value))
+        .map_err(|err| syn::Error::new(args.world.span(), err.to_string()))?;
+    let interface_id = resolve.worlds[world_id]
+        .imports
+        .values()
+        .find_map(|item| match item {
+            WorldItem::Interface { id, .. }
+                if resolve.interfaces[*id].name.as_deref() == Some(&interface_value) =>
+            {
+                Some(*id)
+            }
+            _ => None,
+        })
+        .ok_or_else(|| {
+            syn::Error::new(
+                args.interface.span(),
+                format!("world `{world_value}` does not import interface `{interface_value}`"),
+            )
+        })?;
+    let instance_name = resolve
+        .id_of(interface_id)
+        .ok_or_else(|| syn::Error::new(args.interface.span(), "interface must be named"))?;
+    let instance_lit = syn::LitStr::new(&instance_name, proc_macro2::Span::call_site());
+
+    let module_ident = args.module;
+    let functions_ident = args.functions;
+
+    let mut fields = Vec::new();
+    let mut builders = Vec::new();
+    let mut wraps = Vec::new();
+    for function in resolve.interfaces[interface_id].functions.values() {
+        if !matches!(function.kind, FunctionKind::Freestanding) {
+            return Err(syn::Error::new(
+                args.interface.span(),
+                format!(
+                    "`{}` belongs to a resource, bind resources with `auto_host_components!`",
+                    function.name
+                ),
+            ));
+        }
+
+        let ident = rust_ident(&function.name);
+        let name_lit = syn::LitStr::new(&function.name, proc_macro2::Span::call_site());
+        let param_idents: Vec<_> = function
+            .params
+            .iter()
+            .map(|param| rust_ident(&param.name))
+            .collect();
+        let param_types = function
+            .params
+            .iter()
+            .map(|param| host_interface_ty(&resolve, &param.ty, &module_ident, &args.interface))
+            .collect::<syn::Result<Vec<_>>>()?;
+        let (ret, output) = match &function.result {
+            Some(ty) => {
+                let ty = host_interface_ty(&resolve, ty, &module_ident, &args.interface)?;
+                (quote!(#ty), quote!(Ok((output,))))
+            }
+            None => (quote!(()), quote!(Ok(output))),
+        };
+        let signature = quote! {
+            Fn(&mut #wasm_path::host::HostContext<'_>, #(#param_types),*)
+                -> ::core::result::Result<#ret, ::wasmtime::Error> + Send + Sync
+        };
+
+        let doc = format!(
+            "Implements `{}` of the `{interface_value}` interface",
+            function.name
+        );
+        fields.push(quote! {
+            #ident: Option<::std::sync::Arc<dyn #signature>>
+        });
+        builders.push(quote! {
+            #[doc = #doc]
+            pub fn #ident(mut self, f: impl #signature + 'static) -> Self {
+                self.#ident = Some(::std::sync::Arc::new(f));
+                self
+            }
+        });
+        let missing = format!(
+            "Function `{}` of interface `{interface_value}` is not implemented by the host",
+            function.name
+        );
+        wraps.push(quote! {
+            let f = self.#ident.clone();
+            instance.func_wrap(
+                #name_lit,
+                move |mut store: ::wasmtime::StoreContextMut<'_, #wasm_path::host::WasmHost>,
+                      (#(#param_idents,)*): (#(#param_types,)*)| {
+                    let Some(f) = &f else {
+                        return Err(::wasmtime::Error::msg(#missing));
+                    };
+                    let mut ctx = #wasm_path::host::HostContext::new(store);
+                    let output = f(&mut ctx, #(#param_idents),*)?;
+                    #output
+                },
+            )?;
+        });
+    }
+
+    let struct_doc = format!(
+        "The host implementation of the `{instance_name}` interface, see `wasvy::host_interface!`"
+    );
+    let expanded = quote! {
+        mod #module_ident {
+            ::wasmtime::component::bindgen!({
+                path: #path_value,
+                world: #world_value,
+            });
+        }
+
+        #[doc = #struct_doc]
+        #[derive(Clone, Default)]
+        pub struct #functions_ident {
+            #(#fields,)*
+        }
+
+        impl #functions_ident {
+            #(#builders)*
+
+            /// Adds the functions to a mod's linker, see `ModLoaderPlugin::add_functionality`
+            pub fn add_to_linker(
+                &self,
+                linker: &mut #wasm_path::engine::Linker,
+            ) -> ::core::result::Result<(), ::wasmtime::Error> {
+                let mut instance = linker.instance(#instance_lit)?;
+                #(#wraps)*
+                Ok(())
+            }
+        }
+    };
+
+    Ok(expanded)
+}
+
+/// The Rust type of a WIT type in the bindings generated for `host_interface!`
+fn host_interface_ty(
+    resolve: &Resolve,
+    ty: &wit_parser::Type,
+    module: &Ident,
+    interface: &syn::LitStr,
+) -> syn::Result<proc_macro2::TokenStream> {
+    let id = match ty {
+        wit_parser::Type::Id(id) => *id,
+        wit_parser::Type::ErrorContext => {
+            return Err(syn::Error::new(
+                interface.span(),
+                "`error-context` is not supported by `host_interface!`",
+            ));
+        }
+        ty => return Ok(ty_to_tokens(resolve, ty, &quote!())),
+    };
+    let type_def = &resolve.types[id];
+
+    // Named types are generated by bindgen, in the module of the interface or world defining them
+    if let Some(name) = &type_def.name {
+        if matches!(
+            type_def.kind,
+            TypeDefKind::Resource | TypeDefKind::Handle(_)
+        ) {
+            return Err(syn::Error::new(
+                interface.span(),
+                format!(
+                    "resource `{name}` is not supported, bind resources with `auto_host_components!`"
+                ),
+            ));
+        }
+        let type_ident = format_ident!("{}", upper_camel(name));
+        return Ok(match type_def.owner {
+            wit_parser::TypeOwner::Interface(owner) => {
+                let owner = &resolve.interfaces[owner];
+                let package = &resolve.packages[owner.package.expect("named interface")];
+                let namespace = rust_ident(&package.name.namespace);
+                let package = rust_ident(&package.name.name);
+                let owner = rust_ident(owner.name.as_deref().expect("named interface"));
+                quote!(#module::#namespace::#package::#owner::#type_ident)
+            }
+            _ => quote!(#module::#type_ident),
+        });
+    }
+
+    let ty = |ty| host_interface_ty(resolve, ty, module, interface);
+    Ok(match &type_def.kind {
+        TypeDefKind::Option(inner) => {
+            let inner = ty(inner)?;
+            quote!(Option<#inner>)
+        }
+        TypeDefKind::List(inner) => {
+            let inner = ty(inner)?;
+            quote!(Vec<#inner>)
+        }
+        TypeDefKind::Tuple(tuple) => {
+            let types = tuple
+                .types
+                .iter()
+                .map(ty)
+                .collect::<syn::Result<Vec<_>>>()?;
+            quote!((#(#types,)*))
+        }
+        TypeDefKind::Result(result) => {
+            let ok = result
+                .ok
+                .as_ref()
+                .map(ty)
+                .transpose()?
+                .unwrap_or(quote!(()));
+            let err = result
+                .err
+                .as_ref()
+                .map(ty)
+                .transpose()?
+                .unwrap_or(quote!(()));
+            quote!(::core::result::Result<#ok, #err>)
+        }
+        TypeDefKind::Type(inner) => ty(inner)?,
+        kind => {
+            return Err(syn::Error::new(
+                interface.span(),
+                format!(
+                    "`{}` types are not supported by `host_interface!`",
+                    kind.as_str()
+                ),
+            ));
+        }
+    })
+}
+
 fn expand_guest_type_paths(args: GuestTypePathsArgs) -> syn::Result<proc_macro2::TokenStream> {
     let path_value = resolve_wit_path(&args.path);
     let package_value = args.package.value();
@@ -776,6 +1721,7 @@ fn expand_guest_bindings(
     }
 
     let mut impls = Vec::new();
+    let math_paths = MathPaths::find();
 
     for (_id, interface) in resolve.interfaces.iter() {
         let Some(package_id) = interface.package else {
@@ -787,6 +1733,19 @@ fn expand_guest_bindings(
         let interface_name = rust_ident(interface.name.as_deref().unwrap_or("components"));
         let module = quote!(self::#namespace::#name::#interface_name);
 
+        for (record_name, type_id) in interface.types.iter() {
+            let type_def = &resolve.types[*type_id];
+            if !matches!(type_def.kind, TypeDefKind::Record(_)) {
+                continue;
+            }
+            let Some(type_path) = extract_wit_type_path(&type_def.docs) else {
+                continue;
+            };
+            let record_ident = format_ident!("{}", upper_camel(record_name));
+            let record = quote!(#module::#record_ident);
+            impls.extend(math_conversions(&type_path, &record, &math_paths));
+        }
+
         for (resource_name, type_id) in interface.types.iter() {
             let type_def = &resolve.types[*type_id];
             if !matches!(type_def.kind, TypeDefKind::Resource) {
@@ -819,6 +1778,327 @@ fn expand_guest_bindings(
     })
 }
 
+/// Paths to the math types of the crate using `guest_bindings!`, if it depends on them
+struct MathPaths {
+    /// `glam`, or a crate re-exporting its types such as `bevy_math`
+    glam: Option<proc_macro2::TokenStream>,
+    /// The module of `Transform`
+    transform: Option<proc_macro2::TokenStream>,
+}
+
+impl MathPaths {
+    fn find() -> Self {
+        let found = |name: &str| match crate_name(name) {
+            Ok(FoundCrate::Itself) => Some(quote!(crate)),
+            Ok(FoundCrate::Name(name)) => Some(crate_ident(&name)),
+            Err(_) => None,
+        };
+        let bevy = found("bevy");
+        Self {
+            glam: found("glam")
+                .or_else(|| found("bevy_math"))
+                .or_else(|| bevy.as_ref().map(|bevy| quote!(#bevy::math))),
+            transform: found("bevy_transform")
+                .map(|path| quote!(#path::components))
+                .or_else(|| bevy.map(|bevy| quote!(#bevy::transform::components))),
+        }
+    }
+}
+
+/// Conversions between the WIT record of a built-in math type and the type itself, see
+/// `BUILTIN_RECORDS` in `wasvy_runtime::witgen`
+///
+/// Vectors and quaternions always convert from and to arrays, and to the `glam` types when the
+/// crate depends on `glam`, `bevy_math` or `bevy`.
+fn math_conversions(
+    type_path: &str,
+    record: &proc_macro2::TokenStream,
+    paths: &MathPaths,
+) -> Option<proc_macro2::TokenStream> {
+    let (fields, target): (&[&str], _) = match type_path {
+        "glam::Vec2" => (
+            &["x", "y"],
+            paths.glam.as_ref().map(|glam| quote!(#glam::Vec2)),
+        ),
+        "glam::Vec3" => (
+            &["x", "y", "z"],
+            paths.glam.as_ref().map(|glam| quote!(#glam::Vec3)),
+        ),
+        "glam::Vec3A" => (
+            &["x", "y", "z"],
+            paths.glam.as_ref().map(|glam| quote!(#glam::Vec3A)),
+        ),
+        "glam::Vec4" => (
+            &["x", "y", "z", "w"],
+            paths.glam.as_ref().map(|glam| quote!(#glam::Vec4)),
+        ),
+        "glam::Quat" => (
+            &["x", "y", "z", "w"],
+            paths.glam.as_ref().map(|glam| quote!(#glam::Quat)),
+        ),
+        "glam::Mat4" => {
+            let glam = paths.glam.as_ref()?;
+            return Some(quote! {
+                impl From<#record> for #glam::Mat4 {
+                    fn from(value: #record) -> Self {
+                        Self::from_cols(
+                            value.x_axis.into(),
+                            value.y_axis.into(),
+                            value.z_axis.into(),
+                            value.w_axis.into(),
+                        )
+                    }
+                }
+
+                impl From<#glam::Mat4> for #record {
+                    fn from(value: #glam::Mat4) -> Self {
+                        Self {
+                            x_axis: value.x_axis.into(),
+                            y_axis: value.y_axis.into(),
+                            z_axis: value.z_axis.into(),
+                            w_axis: value.w_axis.into(),
+                        }
+                    }
+                }
+            });
+        }
+        "bevy_transform::components::transform::Transform" => {
+            let transform = paths.transform.as_ref()?;
+            return Some(quote! {
+                impl From<#record> for #transform::Transform {
+                    fn from(value: #record) -> Self {
+                        Self {
+                            translation: value.translation.into(),
+                            rotation: value.rotation.into(),
+                            scale: value.scale.into(),
+                        }
+                    }
+                }
+
+                impl From<#transform::Transform> for #record {
+                    fn from(value: #transform::Transform) -> Self {
+                        Self {
+                            translation: value.translation.into(),
+                            rotation: value.rotation.into(),
+                            scale: value.scale.into(),
+                        }
+                    }
+                }
+            });
+        }
+        _ => return None,
+    };
+
+    let len = fields.len();
+    let fields: Vec<Ident> = fields
+        .iter()
+        .map(|field| format_ident!("{field}"))
+        .collect();
+    let indices = 0..len;
+    let arrays = quote! {
+        impl From<[f32; #len]> for #record {
+            fn from(value: [f32; #len]) -> Self {
+                Self { #(#fields: value[#indices]),* }
+            }
+        }
+
+        impl From<#record> for [f32; #len] {
+            fn from(value: #record) -> Self {
+                [#(value.#fields),*]
+            }
+        }
+    };
+    let glam = target.map(|target| {
+        quote! {
+            impl From<#record> for #target {
+                fn from(value: #record) -> Self {
+                    Self::from_array(value.into())
+                }
+            }
+
+            impl From<#target> for #record {
+                fn from(value: #target) -> Self {
+                    value.to_array().into()
+                }
+            }
+        }
+    });
+
+    Some(quote! {
+        #arrays
+        #glam
+    })
+}
+
+fn expand_guest_systems(input: &ItemImpl, module: &Ident) -> syn::Result<proc_macro2::TokenStream> {
+    if input.trait_.is_none() {
+        return Err(syn::Error::new_spanned(
+            &input.self_ty,
+            "#[wasvy::guest_systems] must be used on the `Guest` impl of a mod",
+        ));
+    }
+
+    let mut constructors = Vec::new();
+    for item in &input.items {
+        let ImplItem::Fn(function) = item else {
+            continue;
+        };
+        let ident = &function.sig.ident;
+        if ident == "setup" {
+            continue;
+        }
+
+        let name = ident.to_string().replace('_', "-");
+        let mut args = Vec::new();
+        let mut params = Vec::new();
+        for (index, input) in function.sig.inputs.iter().enumerate() {
+            let syn::FnArg::Typed(pat_type) = input else {
+                return Err(syn::Error::new_spanned(
+                    input,
+                    "exported systems can't have a receiver",
+                ));
+            };
+            let arg = match pat_type.pat.as_ref() {
+                syn::Pat::Ident(pat) => pat.ident.clone(),
+                _ => format_ident!("arg{index}"),
+            };
+            let param = match &*pat_type.ty {
+                Type::Path(type_path) => type_path.path.segments.last().map(|s| &s.ident),
+                _ => None,
+            };
+            match param.map(Ident::to_string).as_deref() {
+                Some("Commands") => params.push(quote!(system.add_commands();)),
+                Some("Query") => {
+                    args.push(quote!(#arg: &[QueryFor]));
+                    params.push(quote!(system.add_query(#arg);));
+                }
+                Some("EventReader") => {
+                    args.push(quote!(#arg: &str));
+                    params.push(quote!(system.add_event_reader(#arg);));
+                }
+                Some("Res") => {
+                    let mutability = format_ident!("{arg}_mutability");
+                    args.push(quote!(#arg: &str, #mutability: Mutability));
+                    params.push(quote!(system.add_resource(#arg, #mutability);));
+                }
+                Some("Entity") => params.push(quote!(system.add_trigger();)),
+                _ => {
+                    return Err(syn::Error::new_spanned(
+                        &pat_type.ty,
+                        "unsupported system param, expected `Commands`, `Query`, `EventReader`, `Res` or `Entity`",
+                    ));
+                }
+            }
+        }
+
+        let doc = format!("Creates the `{name}` system with the params of its export");
+        constructors.push(quote! {
+            #[doc = #doc]
+            pub fn #ident(#(#args),*) -> System {
+                let system = System::new(#name);
+                #(#params)*
+                system
+            }
+        });
+    }
+
+    Ok(quote! {
+        /// Constructors for the exported systems, generated by `#[wasvy::guest_systems]`
+        #[allow(dead_code)]
+        mod #module {
+            use super::*;
+
+            #(#constructors)*
+        }
+    })
+}
+
+fn expand_guest_query(query: GuestQuery) -> proc_macro2::TokenStream {
+    let GuestQuery {
+        attrs,
+        vis,
+        ident,
+        params,
+    } = query;
+
+    let mut query_for = Vec::new();
+    let mut fields = Vec::new();
+    let mut values = Vec::new();
+    let mut writes = Vec::new();
+    for param in &params {
+        let ty = &param.ty;
+        let type_path = match (&param.type_path, ty) {
+            (Some(type_path), _) => quote!(#type_path),
+            (None, ty) => quote!(<#ty>::TYPE_PATH),
+        };
+        // Filters don't count as components
+        let index = fields.len() as u8;
+        let field = match &param.kind {
+            QueryParamKind::Ref(field) => {
+                query_for.push(quote!(QueryFor::Ref(#type_path.to_string())));
+                field
+            }
+            QueryParamKind::Mut(field) => {
+                query_for.push(quote!(QueryFor::Mut(#type_path.to_string())));
+                writes.push(quote! {
+                    self.wasvy_components[#index as usize].set(
+                        &::serde_json::to_vec(&self.#field).expect("serializable component"),
+                    );
+                });
+                field
+            }
+            QueryParamKind::With => {
+                query_for.push(quote!(QueryFor::With(#type_path.to_string())));
+                continue;
+            }
+            QueryParamKind::Without => {
+                query_for.push(quote!(QueryFor::Without(#type_path.to_string())));
+                continue;
+            }
+        };
+        fields.push(quote!(pub #field: #ty));
+        values.push(quote! {
+            #field: ::serde_json::from_slice(&wasvy_components[#index as usize].get())
+                .expect("deserializable component")
+        });
+    }
+    let count = fields.len() as u8;
+
+    quote! {
+        #(#attrs)*
+        #vis struct #ident {
+            #(#fields,)*
+            wasvy_components: Vec<Component>,
+        }
+
+        #[allow(dead_code)]
+        impl #ident {
+            /// The params of the query, for `System::add_query`
+            pub fn query_for() -> Vec<QueryFor> {
+                vec![#(#query_for),*]
+            }
+
+            /// Returns the results of the query, with their components deserialized
+            pub fn iter(query: &Query) -> impl Iterator<Item = Self> + '_ {
+                ::std::iter::from_fn(move || {
+                    let result = query.iter()?;
+                    let wasvy_components: Vec<Component> =
+                        (0..#count).map(|index| result.component(index)).collect();
+                    Some(Self {
+                        #(#values,)*
+                        wasvy_components,
+                    })
+                })
+            }
+
+            /// Writes the mutable components back to the entity
+            pub fn set(&self) {
+                #(#writes)*
+            }
+        }
+    }
+}
+
 fn expand_include_components(args: IncludeComponentsArgs) -> syn::Result<proc_macro2::TokenStream> {
     let base = resolve_wit_path(&args.path);
     let base = PathBuf::from(base);
@@ -843,17 +2123,116 @@ fn expand_include_components(args: IncludeComponentsArgs) -> syn::Result<proc_ma
     Ok(rendered)
 }
 
+/// Renders the params of a resource function, after the component handle unless `without_handle`
+/// The host type behind a generated WIT resource
+#[derive(Clone, Copy)]
+enum HostHandle {
+    /// A component, constructed from a `wasvy:ecs/app.component`
+    Component,
+    /// A Bevy resource, constructed from a `wasvy:ecs/app.res`
+    Res,
+}
+
+impl HostHandle {
+    /// The handle behind the resource `id`, which is a `res` if it is `wasvy:ecs/app.res` or
+    /// constructed from one
+    fn of(resolve: &Resolve, id: wit_parser::TypeId) -> Self {
+        let id = resolve_resource(resolve, id);
+        if Self::is_wasvy_res(resolve, id) {
+            return Self::Res;
+        }
+
+        let wit_parser::TypeOwner::Interface(interface) = resolve.types[id].owner else {
+            return Self::Component;
+        };
+        let constructed_from_res =
+            resolve.interfaces[interface]
+                .functions
+                .values()
+                .any(|function| {
+                    matches!(function.kind, FunctionKind::Constructor(owner) if owner == id)
+                        && function.params.first().is_some_and(|param| match param.ty {
+                            wit_parser::Type::Id(param) => {
+                                Self::is_wasvy_res(resolve, resolve_resource(resolve, param))
+                            }
+                            _ => false,
+                        })
+                });
+        if constructed_from_res {
+            Self::Res
+        } else {
+            Self::Component
+        }
+    }
+
+    /// Whether `id` is the `res` resource of `wasvy:ecs/app`
+    fn is_wasvy_res(resolve: &Resolve, id: wit_parser::TypeId) -> bool {
+        let type_def = &resolve.types[id];
+        let wit_parser::TypeOwner::Interface(interface) = type_def.owner else {
+            return false;
+        };
+        matches!(type_def.kind, TypeDefKind::Resource)
+            && type_def.name.as_deref() == Some("res")
+            && resolve.interfaces[interface].name.as_deref() == Some("app")
+    }
+
+    fn ident(self) -> Ident {
+        match self {
+            Self::Component => format_ident!("component"),
+            Self::Res => format_ident!("res"),
+        }
+    }
+
+    /// The host type of the resource, as mapped in `bindgen!`
+    fn host_path(self, wasm_path: &proc_macro2::TokenStream) -> proc_macro2::TokenStream {
+        match self {
+            Self::Component => quote!(#wasm_path::host::WasmComponent),
+            Self::Res => quote!(#wasm_path::host::WasmRes),
+        }
+    }
+
+    /// The type of the handle passed to host functions
+    fn host_type(self, wasm_path: &proc_macro2::TokenStream) -> proc_macro2::TokenStream {
+        let host_path = self.host_path(wasm_path);
+        quote!(::wasmtime::component::Resource<#host_path>)
+    }
+}
+
+/// Follows handles and `use`d aliases of a WIT type to the resource they refer to
+fn resolve_resource(resolve: &Resolve, mut id: wit_parser::TypeId) -> wit_parser::TypeId {
+    loop {
+        match &resolve.types[id].kind {
+            TypeDefKind::Type(wit_parser::Type::Id(inner))
+            | TypeDefKind::Handle(wit_parser::Handle::Own(inner))
+            | TypeDefKind::Handle(wit_parser::Handle::Borrow(inner)) => id = *inner,
+            _ => return id,
+        }
+    }
+}
+
+/// The target of a generated method call
+enum InvokeTarget<'a> {
+    /// A method of the component or resource behind the handle
+    Handle(HostHandle),
+    /// An associated function of the type with this type path
+    Static(&'a str),
+    /// A free function exported with `#[wasvy::host_fn]`
+    HostFn,
+}
+
+/// Renders the params of a generated host function, starting with the `handle` of the resource
+/// for methods
 fn render_params(
     resolve: &Resolve,
     params: &[wit_parser::Param],
     wasm_path: &proc_macro2::TokenStream,
-    is_constructor: bool,
+    handle: Option<HostHandle>,
 ) -> proc_macro2::TokenStream {
     let mut out = Vec::new();
-    if !is_constructor {
-        out.push(
-            quote!(component: ::wasmtime::component::Resource<#wasm_path::host::WasmComponent>),
-        );
+    if let Some(handle) = handle {
+        let ident = handle.ident();
+        let host_type = handle.host_type(wasm_path);
+        out.push(quote!(#ident: #host_type));
     }
     for param in params.iter().filter(|param| param.name != "self") {
         let ident = rust_ident(&param.name);
@@ -877,8 +2256,10 @@ fn render_return(
     }
 }
 
+/// Renders a call to a method of a component or resource, or to an associated function
 fn render_invoke_body(
     method: &str,
+    target: InvokeTarget,
     params: &[wit_parser::Param],
     result: Option<&wit_parser::Type>,
     runtime_path: &proc_macro2::TokenStream,
@@ -895,22 +2276,35 @@ fn render_invoke_body(
         quote!((#(#arg_idents),*,))
     };
     let method_lit = syn::LitStr::new(method, proc_macro2::Span::call_site());
+    let call = match target {
+        InvokeTarget::Static(type_path) => {
+            let type_path_lit = syn::LitStr::new(type_path, proc_macro2::Span::call_site());
+            quote!(#wasm_path::host::invoke_component_static(self, #type_path_lit, #method_lit, &params))
+        }
+        InvokeTarget::Handle(HostHandle::Component) => {
+            quote!(#wasm_path::host::invoke_component_method(self, component, #method_lit, &params))
+        }
+        InvokeTarget::HostFn => {
+            quote!(#wasm_path::host::invoke_host_fn(self, #method_lit, &params))
+        }
+        InvokeTarget::Handle(HostHandle::Res) => {
+            quote!(#wasm_path::host::invoke_resource_method(self, res, #method_lit, &params))
+        }
+    };
     match result {
         None => quote! {
             #[allow(unused_imports)]
             use #runtime_path::serialize::*;
             // Note: when implementing a custom codec, a wasvy_encode method is expected to be in scope
             let params = wasvy_encode(&#args_expr).expect("serialize params");
-            let _ = #wasm_path::host::invoke_component_method(self, component, #method_lit, &params)
-                .expect("invoke method");
+            let _ = #call.expect("invoke method");
         },
         Some(_) => quote! {
             #[allow(unused_imports)]
             use #runtime_path::serialize::*;
             // Note: when implementing a custom codec, a wasvy_encode and wasvy_decode method is expected to be in scope
             let params = wasvy_encode(&#args_expr).expect("serialize params");
-            let output = #wasm_path::host::invoke_component_method(self, component, #method_lit, &params)
-                .expect("invoke method");
+            let output = #call.expect("invoke method");
             wasvy_decode(&output).expect("deserialize")
         },
     }
@@ -936,14 +2330,9 @@ fn ty_to_tokens(
         wit_parser::Type::Char => quote!(char),
         wit_parser::Type::String => quote!(String),
         wit_parser::Type::Id(id) => match &resolve.types[*id].kind {
-            TypeDefKind::Resource => {
-                quote!(::wasmtime::component::Resource<#wasm_path::host::WasmComponent>)
+            TypeDefKind::Resource | TypeDefKind::Handle(_) => {
+                HostHandle::of(resolve, *id).host_type(wasm_path)
             }
-            TypeDefKind::Handle(handle) => match handle {
-                wit_parser::Handle::Borrow(_) | wit_parser::Handle::Own(_) => {
-                    quote!(::wasmtime::component::Resource<#wasm_path::host::WasmComponent>)
-                }
-            },
             TypeDefKind::Option(inner) => {
                 let inner = ty_to_tokens(resolve, inner, wasm_path);
                 quote!(Option<#inner>)
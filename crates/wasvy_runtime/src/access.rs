@@ -1,8 +1,11 @@
-use bevy_ecs::{prelude::*, query::FilteredAccess};
+use bevy_ecs::{prelude::*, query::FilteredAccess, relationship::Relationship};
 use bevy_reflect::Reflect;
 use serde::Deserialize;
 
-use crate::prelude::{ModSchedules, Sandbox};
+use crate::{
+    prelude::{ModSchedules, Sandbox},
+    sandbox::Sandboxed,
+};
 
 /// Represents the access a mod can be given to run in.
 ///
@@ -46,6 +49,19 @@ impl ModAccess {
         }
     }
 
+    /// Whether an entity with the given [Sandboxed] component (or none) is within this access.
+    ///
+    /// Matches the entities of [Self::filtered_access]: mods running in the world can't reach
+    /// sandboxed entities, and sandboxed mods can only reach the entities of their own sandbox.
+    pub fn contains(&self, sandboxed: Option<&Sandboxed>) -> bool {
+        match self {
+            Self::Sandbox(sandbox) => {
+                sandboxed.is_some_and(|sandboxed| sandboxed.get() == *sandbox)
+            }
+            Self::World => sandboxed.is_none(),
+        }
+    }
+
     /// Validates a ModAccess is valid before invoking it
     pub fn validate(&self, world: &World) -> Result<(), String> {
         if let ModAccess::Sandbox(entity) = self
@@ -1,17 +1,33 @@
-use std::{fmt, sync::Arc};
+use std::{fmt, path::PathBuf, sync::Arc};
 
-use anyhow::Result;
-use bevy_asset::{Asset, AssetId, Assets};
+use anyhow::{Result, bail};
+use bevy_app::App;
+use bevy_asset::{
+    Asset, AssetApp, AssetEvent, AssetId, AssetLoadFailedEvent, AssetPath, AssetServer, Assets,
+};
 use bevy_ecs::{change_detection::Tick, prelude::*};
+use bevy_log::prelude::*;
 use bevy_reflect::TypePath;
+use serde::{Deserialize, Serialize};
 
-use crate::{access::ModAccess, cleanup::DespawnModEntities, mods::ModDespawnBehaviour};
+use crate::{
+    access::ModAccess,
+    budget::clear_frame_budgets,
+    cleanup::{DespawnModEntities, TransferModEntities, transfer_to_host},
+    entry_points::ModEntryPoints,
+    manifest::{ModDetails, ModManifest},
+    package::PackageAssets,
+    permissions::ComponentPermissions,
+    verify::ModVerifier,
+};
 
 /// Backend implementation driving a [ModAsset]
 ///
 /// For example, one backend may execute wasm files, and another might execute native systems
 pub trait ModBackend: Send + Sync + 'static {
     /// Responsible for registering the systems exported by the mod.
+    ///
+    /// This calls the [setup entry point](ModEntryPoints::setup).
     fn setup(
         &self,
         world: &mut World,
@@ -19,12 +35,147 @@ pub trait ModBackend: Send + Sync + 'static {
         mod_name: &str,
         accesses: &[ModAccess],
     ) -> Result<()>;
+
+    /// Reads the [ModDetails] the mod declares about itself, called right before [setup](Self::setup).
+    ///
+    /// Backends may fail if the mod requires an unsupported interface version. Returns [None] by
+    /// default.
+    fn info(
+        &self,
+        _world: &mut World,
+        _mod_id: Entity,
+        _mod_name: &str,
+    ) -> Result<Option<ModDetails>> {
+        Ok(None)
+    }
+
+    /// Whether the mod has an export, used to check its [required entry points](ModEntryPoints::required).
+    ///
+    /// Backends that can't tell assume it does.
+    fn has_export(&self, _name: &str) -> bool {
+        true
+    }
+
+    /// Calls an entry point of the mod, see [Mods::run_entry_point](crate::mods::Mods::run_entry_point).
+    ///
+    /// Backends should do nothing if the mod lacks the export. Does nothing by default.
+    fn run_entry_point(
+        &self,
+        _world: &mut World,
+        _mod_id: Entity,
+        _mod_name: &str,
+        _accesses: &[ModAccess],
+        _name: &str,
+    ) -> Result<()> {
+        Ok(())
+    }
+
+    /// Invokes a callback the mod registered, see [ModCallbacks](crate::callbacks::ModCallbacks).
+    ///
+    /// Fails by default, since callbacks are backend specific.
+    fn run_callback(
+        &self,
+        _world: &mut World,
+        _mod_id: Entity,
+        mod_name: &str,
+        _accesses: &[ModAccess],
+        name: &str,
+        _args: &[u8],
+    ) -> Result<()> {
+        bail!("Mod \"{mod_name}\" has no callback \"{name}\"")
+    }
+
+    /// Calls an optional export of the mod taking `commands`, such as the `on-save` and `on-load`
+    /// hooks of [ModSaveData](crate::save::ModSaveData).
+    ///
+    /// Backends should do nothing if the mod lacks the export. Does nothing by default.
+    fn run_hook(
+        &self,
+        _world: &mut World,
+        _mod_id: Entity,
+        _mod_name: &str,
+        _accesses: &[ModAccess],
+        _name: &str,
+    ) -> Result<()> {
+        Ok(())
+    }
+
+    /// Called when components were exported after the mod was setup, such as when a DLC plugin is added late.
+    ///
+    /// Backends may use this to let mods register new systems against them. Does nothing by default.
+    fn registry_changed(
+        &self,
+        _world: &mut World,
+        _mod_id: Entity,
+        _mod_name: &str,
+        _accesses: &[ModAccess],
+        _type_paths: &[String],
+    ) -> Result<()> {
+        Ok(())
+    }
+
+    /// Called when the mod is despawned, before its systems are disabled.
+    ///
+    /// Backends may use this to let mods clean up. Since the mod's entity no longer exists, its
+    /// `permissions` are passed along. Does nothing by default.
+    fn teardown(
+        &self,
+        _world: &mut World,
+        _mod_id: Entity,
+        _mod_name: &str,
+        _accesses: &[ModAccess],
+        _permissions: &ComponentPermissions,
+    ) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// Registers the [ModAsset] asset type.
+///
+/// Headless apps (the `headless` feature) may run without an asset server, in which case mods can
+/// only be loaded with [ModAsset::from_bytes].
+pub fn init_mod_assets(app: &mut App) {
+    if cfg!(feature = "headless") && !app.world().contains_resource::<AssetServer>() {
+        app.init_resource::<Assets<ModAsset>>()
+            .add_message::<AssetEvent<ModAsset>>()
+            .add_message::<AssetLoadFailedEvent<ModAsset>>();
+    } else {
+        app.init_asset::<ModAsset>();
+    }
+}
+
+/// Compiles the bytes of a mod into a [ModAsset], see [ModAsset::from_bytes].
+///
+/// Backends insert this resource, such as the wasm backend compiling wasm files.
+#[derive(Resource, Clone)]
+pub struct ModCompiler(Arc<CompileFn>);
+
+type CompileFn = dyn Fn(&[u8]) -> Result<ModAsset> + Send + Sync;
+
+impl ModCompiler {
+    pub fn new(compile: impl Fn(&[u8]) -> Result<ModAsset> + Send + Sync + 'static) -> Self {
+        Self(Arc::new(compile))
+    }
+
+    pub fn compile(&self, bytes: &[u8]) -> Result<ModAsset> {
+        (self.0)(bytes)
+    }
+}
+
+/// The settings of the loader of [ModAsset]s
+#[derive(Default, Clone, Serialize, Deserialize)]
+pub struct ModLoaderSettings {
+    /// Components linked with the mod host-side, whose exports satisfy imports of the mod or of the
+    /// plugs listed before them. See [Mods::load_composed](crate::mods::Mods::load_composed).
+    pub plugs: Vec<AssetPath<'static>>,
 }
 
 /// An asset representing a loaded wasvy mod.
 #[derive(Asset, TypePath)]
 pub struct ModAsset {
     version: Option<Tick>,
+    manifest: Option<ModManifest>,
+    package_assets: Option<Arc<PackageAssets>>,
     backend: Arc<dyn ModBackend>,
 }
 
@@ -33,14 +184,80 @@ impl ModAsset {
     pub fn new(backend: impl ModBackend) -> Self {
         Self {
             version: None,
+            manifest: None,
+            package_assets: None,
             backend: Arc::new(backend),
         }
     }
 
+    /// Compiles a mod from the bytes of a wasm file, without going through the asset server.
+    ///
+    /// Use this to load mods embedded in the binary, downloaded at runtime or read from custom
+    /// storage. The manifest embedded in the wasm file is read, if any. Requires a backend
+    /// providing a [ModCompiler], such as the wasm backend.
+    ///
+    /// Fails with a [ModVerificationError](crate::verify::ModVerificationError) if the app has a [ModVerifier] that rejects the mod.
+    ///
+    /// ```no_run
+    /// # use bevy_asset::Assets;
+    /// # use bevy_ecs::prelude::*;
+    /// # use wasvy_runtime::prelude::*;
+    /// fn load_downloaded(world: &mut World, bytes: &[u8]) -> anyhow::Result<()> {
+    ///     let asset = ModAsset::from_bytes(world, bytes)?;
+    ///     let handle = world.resource_mut::<Assets<ModAsset>>().add(asset);
+    ///     world.spawn((Mod::new(handle), Name::new("downloaded")));
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn from_bytes(world: &World, bytes: &[u8]) -> Result<Self> {
+        let Some(compiler) = world.get_resource::<ModCompiler>() else {
+            bail!("Compiling mods requires a backend, such as the WasmBackendPlugin");
+        };
+        let manifest = ModManifest::from_wasm(bytes)?;
+        if let Some(verifier) = world.get_resource::<ModVerifier>() {
+            verifier.verify(bytes, manifest.as_ref(), &[])?;
+        }
+        Ok(compiler.compile(bytes)?.with_manifest(manifest))
+    }
+
+    /// Attaches the manifest the mod shipped with
+    pub fn with_manifest(mut self, manifest: Option<ModManifest>) -> Self {
+        self.manifest = manifest;
+        self
+    }
+
+    /// The manifest the mod shipped with, if any. See [ModManifest]
+    pub fn manifest(&self) -> Option<&ModManifest> {
+        self.manifest.as_ref()
+    }
+
+    /// Attaches the assets of the [ModPackage](crate::package::ModPackage) the mod shipped in,
+    /// which are mounted under `name` when the mod is set up.
+    ///
+    /// Fails if `name` is not a single folder name, such as `a/b` or `..`.
+    pub fn with_package_assets(
+        mut self,
+        name: impl Into<String>,
+        files: Vec<(PathBuf, Vec<u8>)>,
+    ) -> Result<Self> {
+        self.package_assets = Some(Arc::new(PackageAssets::new(name.into(), files)?));
+        Ok(self)
+    }
+
+    /// The folder the assets of the mod's package are mounted in, if it shipped in one
+    pub fn package_asset_root(&self) -> Option<AssetPath<'static>> {
+        self.package_assets.as_ref().map(|assets| assets.root())
+    }
+
     pub fn version(&self) -> Option<Tick> {
         self.version
     }
 
+    /// Marks the asset as changed at `tick`, such as when its mods are [reloaded](crate::mods::Mods::reload)
+    pub(crate) fn bump_version(&mut self, tick: Tick) {
+        self.version = Some(tick);
+    }
+
     /// Initiates mods by asking the asset backend to run setup and register systems.
     pub(crate) fn initiate(
         world: &mut World,
@@ -54,23 +271,148 @@ impl ModAsset {
             .expect("ModAssets be registered");
         let asset = assets.get(*asset_id).ok_or(AssetNotFound)?;
         let backend = Arc::clone(&asset.backend);
+        let package_assets = asset.package_assets.clone();
+        let manifest = asset.manifest.clone();
 
-        if ModDespawnBehaviour::should_despawn_entities(world) {
-            let (entities, mut commands) = world.entities_and_commands();
-            let despawn = entities
-                .get(mod_id)
-                .expect("Mod entity exists")
-                .get::<DespawnModEntities>()
-                .expect(
-                    "DespawnModEntities should have been registered as a required componet for Mod",
-                );
-            for source_entity in despawn.iter() {
-                commands.entity(source_entity).try_despawn();
+        if let Some(package_assets) = package_assets {
+            package_assets.mount(world, *asset_id)?;
+        }
+
+        // The manifest may have changed if the mod was hot reloaded
+        match manifest {
+            Some(manifest) => world.entity_mut(mod_id).insert(manifest),
+            None => world.entity_mut(mod_id).remove::<ModManifest>(),
+        };
+
+        match backend.info(world, mod_id, mod_name)? {
+            Some(details) => {
+                debug!("Mod \"{mod_name}\" is {details}");
+                world.entity_mut(mod_id).insert(details)
             }
+            None => world.entity_mut(mod_id).remove::<ModDetails>(),
+        };
+
+        if let Some(entry_points) = world.get_resource::<ModEntryPoints>() {
+            entry_points.check(|name| backend.has_export(name))?;
         }
 
+        // Entities are only tied to the mod if their ModDespawnBehaviour is DespawnWithMod
+        let (entities, mut commands) = world.entities_and_commands();
+        let despawn = entities
+            .get(mod_id)
+            .expect("Mod entity exists")
+            .get::<DespawnModEntities>()
+            .expect(
+                "DespawnModEntities should have been registered as a required componet for Mod",
+            );
+        for source_entity in despawn.iter() {
+            commands.entity(source_entity).try_despawn();
+        }
+        let transfer: Vec<_> = world
+            .get::<TransferModEntities>(mod_id)
+            .map(|transfer| transfer.iter().collect())
+            .unwrap_or_default();
+        transfer_to_host(world, transfer);
+
+        // The systems of these accesses are added again, and numbered from the start
+        clear_frame_budgets(world, mod_id, accesses);
+
         backend.setup(world, mod_id, mod_name, accesses)
     }
+
+    /// Calls an entry point of a mod. See [ModBackend::run_entry_point].
+    pub(crate) fn run_entry_point(
+        world: &mut World,
+        asset_id: &AssetId<ModAsset>,
+        mod_id: Entity,
+        mod_name: &str,
+        accesses: &[ModAccess],
+        name: &str,
+    ) -> Result<()> {
+        let assets = world
+            .get_resource::<Assets<Self>>()
+            .expect("ModAssets be registered");
+        let asset = assets.get(*asset_id).ok_or(AssetNotFound)?;
+        let backend = Arc::clone(&asset.backend);
+
+        backend.run_entry_point(world, mod_id, mod_name, accesses, name)
+    }
+
+    /// Invokes a callback of a mod. See [ModBackend::run_callback].
+    pub(crate) fn run_callback(
+        world: &mut World,
+        asset_id: &AssetId<ModAsset>,
+        mod_id: Entity,
+        mod_name: &str,
+        accesses: &[ModAccess],
+        name: &str,
+        args: &[u8],
+    ) -> Result<()> {
+        let assets = world
+            .get_resource::<Assets<Self>>()
+            .expect("ModAssets be registered");
+        let asset = assets.get(*asset_id).ok_or(AssetNotFound)?;
+        let backend = Arc::clone(&asset.backend);
+
+        backend.run_callback(world, mod_id, mod_name, accesses, name, args)
+    }
+
+    /// Calls an optional export of a mod. See [ModBackend::run_hook].
+    pub(crate) fn run_hook(
+        world: &mut World,
+        asset_id: &AssetId<ModAsset>,
+        mod_id: Entity,
+        mod_name: &str,
+        accesses: &[ModAccess],
+        name: &str,
+    ) -> Result<()> {
+        let assets = world
+            .get_resource::<Assets<Self>>()
+            .expect("ModAssets be registered");
+        let asset = assets.get(*asset_id).ok_or(AssetNotFound)?;
+        let backend = Arc::clone(&asset.backend);
+
+        backend.run_hook(world, mod_id, mod_name, accesses, name)
+    }
+
+    /// Notifies a mod about newly exported components. See [ModBackend::registry_changed].
+    pub(crate) fn registry_changed(
+        world: &mut World,
+        asset_id: &AssetId<ModAsset>,
+        mod_id: Entity,
+        mod_name: &str,
+        accesses: &[ModAccess],
+        type_paths: &[String],
+    ) -> Result<()> {
+        let assets = world
+            .get_resource::<Assets<Self>>()
+            .expect("ModAssets be registered");
+        let asset = assets.get(*asset_id).ok_or(AssetNotFound)?;
+        let backend = Arc::clone(&asset.backend);
+
+        backend.registry_changed(world, mod_id, mod_name, accesses, type_paths)
+    }
+
+    /// Lets a despawned mod clean up. See [ModBackend::teardown].
+    pub(crate) fn teardown(
+        world: &mut World,
+        asset_id: &AssetId<ModAsset>,
+        mod_id: Entity,
+        mod_name: &str,
+        accesses: &[ModAccess],
+        permissions: &ComponentPermissions,
+    ) -> Result<()> {
+        let assets = world
+            .get_resource::<Assets<Self>>()
+            .expect("ModAssets be registered");
+        // A mod whose asset never loaded was never setup, so there's nothing to tear down
+        let Some(asset) = assets.get(*asset_id) else {
+            return Ok(());
+        };
+        let backend = Arc::clone(&asset.backend);
+
+        backend.teardown(world, mod_id, mod_name, accesses, permissions)
+    }
 }
 
 #[derive(Debug)]
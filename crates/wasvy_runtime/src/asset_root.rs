@@ -0,0 +1,170 @@
+use std::path::Component as PathComponent;
+
+use anyhow::{Result, anyhow, bail};
+use bevy_asset::{AssetPath, AssetServer, Assets, ReflectHandle, io::AssetSourceId};
+use bevy_ecs::{error::warn, prelude::*, reflect::AppTypeRegistry};
+use bevy_log::prelude::*;
+use bevy_reflect::{ReflectFromReflect, TypeInfo, tuple_struct::DynamicTupleStruct};
+
+use crate::{
+    asset::ModAsset,
+    mods::Mod,
+    permissions::{ComponentAccess, ComponentPermissions},
+};
+
+/// The folder a [Mod] loads its assets from, such as textures, sounds and scenes.
+///
+/// Mods load assets with `assets.load`, by a path relative to their root which may not leave it.
+/// Mods without this component load assets from their [package](crate::package::ModPackage), or
+/// from the folder of their wasm file, so assets can be shipped alongside it.
+///
+/// ```
+/// # use bevy_ecs::prelude::*;
+/// # use wasvy_runtime::prelude::*;
+/// fn setup(mut mods: Mods, mut commands: Commands) {
+///     let mod_id = mods.spawn("mods/dungeon.wasm", None);
+///     commands
+///         .entity(mod_id)
+///         .insert(ModAssetRoot::new("mods/dungeon-assets"));
+///     mods.enable_access(mod_id, ModAccess::World);
+/// }
+/// ```
+#[derive(Component, Debug, Clone, PartialEq, Eq)]
+pub struct ModAssetRoot(pub AssetPath<'static>);
+
+impl ModAssetRoot {
+    pub fn new(root: impl Into<AssetPath<'static>>) -> Self {
+        Self(root.into())
+    }
+
+    /// Returns the asset root of a mod, or [None] if it has none and its wasm file has no path
+    pub fn of(mod_id: Entity, world: &World) -> Option<Self> {
+        let entity = world.get_entity(mod_id).ok()?;
+        if let Some(root) = entity.get::<ModAssetRoot>() {
+            return Some(root.clone());
+        }
+
+        let asset = entity.get::<Mod>()?.asset();
+        let package_root = world
+            .get_resource::<Assets<ModAsset>>()
+            .and_then(|assets| assets.get(&asset))
+            .and_then(ModAsset::package_asset_root);
+        if let Some(root) = package_root {
+            return Some(Self(root));
+        }
+
+        let path = asset.path()?;
+        Some(Self(path.parent().unwrap_or_default().into_owned()))
+    }
+
+    /// Resolves the path of an asset of the mod, see [check_asset_path]
+    pub fn resolve(&self, path: &str) -> Result<AssetPath<'static>> {
+        let path = check_asset_path(path)?;
+        Ok(self.0.resolve(&path))
+    }
+}
+
+/// Checks that a path passed by a mod stays within its [ModAssetRoot].
+///
+/// Paths are relative to the root, so they may not name an asset source, start with `/` or contain `..`.
+pub fn check_asset_path(path: &str) -> Result<AssetPath<'_>> {
+    let asset_path = AssetPath::try_parse(path)?;
+    let relative = asset_path
+        .path()
+        .components()
+        .all(|component| matches!(component, PathComponent::Normal(_) | PathComponent::CurDir));
+    if !relative || !matches!(asset_path.source(), AssetSourceId::Default) {
+        bail!("Asset path \"{path}\" must be relative to the mod's asset root");
+    }
+
+    Ok(asset_path)
+}
+
+/// Inserts a component holding a handle to an asset of a mod, given its path relative to the mod's
+/// [ModAssetRoot].
+///
+/// The component must be a tuple struct whose only field is a [Handle](bevy_asset::Handle), such as
+/// `AudioPlayer`. The asset is loaded as the asset type of that handle.
+///
+/// Like [insert_component](crate::component::insert_component), this is skipped with an error log
+/// if `permissions` deny inserting the component.
+pub fn insert_asset(
+    commands: &mut Commands,
+    type_registry: &AppTypeRegistry,
+    permissions: &ComponentPermissions,
+    mod_id: Entity,
+    entity: Entity,
+    type_path: String,
+    path: String,
+) -> Result<()> {
+    if let Err(denied) = permissions.check(ComponentAccess::Insert, &type_path) {
+        error!("{denied}, skipping insert into {entity}");
+        return Ok(());
+    }
+    check_asset_path(&path)?;
+
+    let type_registry = type_registry.read();
+    let type_registration = type_registry
+        .get_with_type_path(&type_path)
+        .ok_or_else(|| anyhow!("Component {type_path} is not registered"))?;
+    if type_registration.data::<ReflectComponent>().is_none() {
+        bail!("{type_path} is not a component");
+    }
+    let from_reflect = type_registration
+        .data::<ReflectFromReflect>()
+        .ok_or_else(|| anyhow!("Component {type_path} does not reflect FromReflect"))?
+        .clone();
+    let reflect_handle = match type_registration.type_info() {
+        TypeInfo::TupleStruct(info) if info.field_len() == 1 => info
+            .field_at(0)
+            .and_then(|field| type_registry.get_type_data::<ReflectHandle>(field.type_id())),
+        _ => None,
+    }
+    .ok_or_else(|| anyhow!("Component {type_path} does not hold a single asset handle"))?
+    .clone();
+
+    let command = move |world: &mut World| -> Result<(), BevyError> {
+        let root = ModAssetRoot::of(mod_id, world)
+            .ok_or_else(|| format!("Mod ({mod_id}) has no asset root, could not load {path}"))?;
+        let asset_path = root.resolve(&path).map_err(|err| err.to_string())?;
+        let handle = world
+            .get_resource::<AssetServer>()
+            .ok_or("Loading assets requires the AssetPlugin")?
+            .load_builder()
+            .load_erased(reflect_handle.asset_type_id(), asset_path);
+
+        let mut value = DynamicTupleStruct::default();
+        value.insert_boxed(reflect_handle.typed(handle).into_partial_reflect());
+        let component = from_reflect
+            .from_reflect(&value)
+            .ok_or_else(|| format!("Could not create {type_path} from the handle of {path}"))?;
+        world
+            .get_entity_mut(entity)?
+            .insert_reflect(component.into_partial_reflect());
+        Ok(())
+    };
+    commands.queue_handled(command, warn);
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resolves_paths_within_the_root() {
+        let root = ModAssetRoot::new("mods/dungeon");
+        assert_eq!(
+            root.resolve("sounds/door.ogg").unwrap(),
+            AssetPath::parse("mods/dungeon/sounds/door.ogg")
+        );
+        assert_eq!(
+            root.resolve("./scene.gltf#Scene0").unwrap(),
+            AssetPath::parse("mods/dungeon/scene.gltf#Scene0")
+        );
+        assert!(root.resolve("../other/secret.png").is_err());
+        assert!(root.resolve("/secret.png").is_err());
+        assert!(root.resolve("embedded://secret.png").is_err());
+    }
+}
@@ -4,8 +4,15 @@
 //! - [`WasvyComponent`] marks a component as exportable to mods, even if it has
 //!   **no methods**. This is important for components that are queried or
 //!   serialized but never invoked.
+//! - [`WasvyResource`] does the same for resources, so mods can invoke their methods.
 //! - [`WasvyMethods`] registers exported methods (typically via
 //!   [`#[wasvy::methods]`](\[wasvy::methods\])).
+//! - [`ExportComponentExt`] exports types from other crates without the derive, such as
+//!   Bevy's own components.
+//! - [`ExportMethodsExt`] exports methods of types from other crates, such as Bevy's
+//!   `Transform`, which `#[wasvy::methods]` can't be used on.
+//! - [`WasvyHostFnMetadata`] describes free functions exported with `#[wasvy::host_fn]`, which
+//!   mods call without a component.
 //! - [`AutoRegistrationPlugin`] apply all submitted
 //!   registrations to a Bevy `App`.
 //!
@@ -38,13 +45,21 @@
 //! }
 //! ```
 
-use std::marker::PhantomData;
+use std::{any::TypeId, marker::PhantomData};
 
 pub use bevy_app::App;
 use bevy_app::Plugin;
 use bevy_ecs::component::Component;
-use bevy_ecs::reflect::{AppFunctionRegistry, AppTypeRegistry};
-use bevy_reflect::{FromType, GetTypeRegistration, Reflect, TypePath};
+use bevy_ecs::reflect::{AppFunctionRegistry, AppTypeRegistry, ReflectResource};
+use bevy_ecs::resource::Resource;
+use bevy_ecs::world::World;
+use bevy_log::prelude::*;
+use bevy_reflect::{
+    FromType, GetTypeRegistration, PartialReflect, Reflect, ReflectRef, TypePath, TypeRegistry,
+    func::IntoFunction, std_traits::ReflectDefault,
+};
+
+use crate::serialize::CodecResource;
 
 /// Inventory entry that registers a component with a Bevy app.
 #[derive(Clone, Copy)]
@@ -64,6 +79,26 @@ pub struct WasvyMethodMetadata {
     pub method: &'static str,
     /// Ordered argument names (excluding `self`).
     pub arg_names: &'static [&'static str],
+    /// Whether this is an associated function without a `self` receiver, like a constructor.
+    pub is_static: bool,
+    /// The doc comment of the method, empty when it has none.
+    pub docs: &'static str,
+}
+
+/// Inventory entry for a free function exported with `#[wasvy::host_fn]`.
+///
+/// Host functions have no receiver. They are WIT functions of the components interface, which
+/// mods call without a component.
+#[derive(Clone, Copy)]
+pub struct WasvyHostFnMetadata {
+    /// Fully-qualified path of the function, as registered in the `FunctionRegistry`.
+    pub function_name: &'static str,
+    /// Function name as exposed to mods.
+    pub name: &'static str,
+    /// Ordered argument names.
+    pub arg_names: &'static [&'static str],
+    /// The doc comment of the function, empty when it has none.
+    pub docs: &'static str,
 }
 
 /// Inventory entry that registers exported methods for a component.
@@ -75,6 +110,7 @@ pub struct WasvyMethodsRegistration {
 
 inventory::collect!(WasvyComponentRegistration);
 inventory::collect!(WasvyMethodMetadata);
+inventory::collect!(WasvyHostFnMetadata);
 inventory::collect!(WasvyMethodsRegistration);
 
 #[doc(hidden)]
@@ -101,25 +137,92 @@ macro_rules! __wasvy_submit_method_metadata {
     };
 }
 
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __wasvy_submit_host_fn_metadata {
+    ($info:expr) => {
+        $crate::authoring::inventory::submit! { $info }
+    };
+}
+
 /// Re-exported inventory crate for proc-macro submissions.
 pub use inventory;
 
-/// Marker type data for components exported to Wasvy.
+/// Type data for components exported to Wasvy.
 ///
-/// This data is used to identify which components should appear in WIT.
-#[derive(Clone)]
-pub struct WasvyExport;
+/// This data is used to identify which components should appear in WIT, at which
+/// [`ExportLevel`], and under which name and interface.
+#[derive(Clone, Default)]
+pub struct WasvyExport {
+    pub level: ExportLevel,
+    /// The name of the WIT resource, defaulting to the kebab-case of the type name
+    pub name: Option<String>,
+    /// The WIT interface the resource lands in, defaulting to
+    /// [`WitGeneratorSettings::component_interface`](crate::witgen::WitGeneratorSettings::component_interface)
+    pub interface: Option<String>,
+    /// The doc comment of the component, shown on its WIT resource
+    pub docs: Option<String>,
+    /// Whether the type is a [`WasvyResource`] rather than a component
+    pub resource: bool,
+}
+
+impl WasvyExport {
+    pub fn new(level: ExportLevel) -> Self {
+        Self {
+            level,
+            ..Default::default()
+        }
+    }
+
+    /// Sets the name of the WIT resource
+    pub fn with_name(mut self, name: impl Into<String>) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+
+    /// Sets the WIT interface the resource lands in
+    pub fn with_interface(mut self, interface: impl Into<String>) -> Self {
+        self.interface = Some(interface.into());
+        self
+    }
+}
 
 impl<T> FromType<T> for WasvyExport {
     fn from_type() -> Self {
-        WasvyExport
+        WasvyExport::default()
     }
 }
 
+/// How widely an exported component is visible to mods.
+///
+/// Levels are ordered from most to least visible. By default, WIT generation and
+/// the [`FunctionIndex`](crate::methods::FunctionIndex) used by mods only include
+/// [`Public`](Self::Public) and [`ModdingApi`](Self::ModdingApi) components, see
+/// [`ModRuntimePlugin::export_level`](crate::plugin::ModRuntimePlugin::export_level).
+///
+/// Set the level with `#[wasvy(level = "internal")]` next to `#[derive(WasvyComponent)]`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ExportLevel {
+    /// Part of the public mod API.
+    #[default]
+    Public,
+    /// Part of the dedicated modding API of the game.
+    ModdingApi,
+    /// Only meant for first-party tooling mods.
+    Internal,
+}
+
 /// Trait implemented by components that are exported to mods.
 ///
 /// This exists so components without methods can still be exported.
 ///
+/// The derive macro accepts `#[wasvy(...)]` options:
+/// - `level = "internal"` sets the [`ExportLevel`].
+/// - `rename = "hit-points"` sets the name of the WIT resource.
+/// - `interface = "combat"` puts the resource in its own WIT interface.
+/// - `skip_export` leaves the component out of [`AutoRegistrationPlugin`], for example with
+///   `#[cfg_attr(not(feature = "modding"), wasvy(skip_export))]`.
+///
 /// # Example
 /// ```ignore
 /// use bevy_ecs::prelude::*;
@@ -131,17 +234,173 @@ impl<T> FromType<T> for WasvyExport {
 /// struct Tag;
 /// ```
 pub trait WasvyComponent: Component + Reflect + TypePath + GetTypeRegistration {
+    /// How widely this component is exported to mods.
+    const LEVEL: ExportLevel = ExportLevel::Public;
+
+    /// The name of the WIT resource, see [`WasvyExport::name`].
+    const NAME: Option<&'static str> = None;
+
+    /// The WIT interface of the resource, see [`WasvyExport::interface`].
+    const INTERFACE: Option<&'static str> = None;
+
+    /// The doc comment of the component, see [`WasvyExport::docs`].
+    const DOCS: Option<&'static str> = None;
+
     /// Register the component's reflect data with the app.
     fn register(app: &mut App)
     where
         Self: Sized,
     {
         app.register_type::<Self>();
-        app.register_type_data::<Self, WasvyExport>();
+        app.world()
+            .resource::<AppTypeRegistry>()
+            .write()
+            .get_mut(TypeId::of::<Self>())
+            .expect("type to be registered")
+            .insert(WasvyExport {
+                level: Self::LEVEL,
+                name: Self::NAME.map(Into::into),
+                interface: Self::INTERFACE.map(Into::into),
+                docs: Self::DOCS.map(Into::into),
+                resource: false,
+            });
+
+        check_round_trip(app.world(), TypeId::of::<Self>());
     }
 }
 
-/// Trait implemented by components that export methods to mods.
+/// Trait implemented by resources that are exported to mods, so mods can invoke their methods
+/// like those of components.
+///
+/// Mods construct the WIT resource of a resource from a `res` system param, see
+/// `system.add-resource`. The derive macro accepts the same `#[wasvy(...)]` options as
+/// [`WasvyComponent`].
+///
+/// # Example
+/// ```ignore
+/// use bevy_ecs::prelude::*;
+/// use bevy_reflect::Reflect;
+/// use wasvy::WasvyResource;
+///
+/// #[derive(Resource, Reflect, Default, WasvyResource)]
+/// #[reflect(Resource)]
+/// struct Score(u32);
+///
+/// #[wasvy::methods]
+/// impl Score {
+///     fn add(&mut self, points: u32) {
+///         self.0 += points;
+///     }
+/// }
+/// ```
+pub trait WasvyResource: Resource + Reflect + TypePath + GetTypeRegistration {
+    /// How widely this resource is exported to mods.
+    const LEVEL: ExportLevel = ExportLevel::Public;
+
+    /// The name of the WIT resource, see [`WasvyExport::name`].
+    const NAME: Option<&'static str> = None;
+
+    /// The WIT interface of the resource, see [`WasvyExport::interface`].
+    const INTERFACE: Option<&'static str> = None;
+
+    /// The doc comment of the resource, see [`WasvyExport::docs`].
+    const DOCS: Option<&'static str> = None;
+
+    /// Register the resource's reflect data with the app.
+    fn register(app: &mut App)
+    where
+        Self: Sized,
+    {
+        app.register_type::<Self>();
+        app.world()
+            .resource::<AppTypeRegistry>()
+            .write()
+            .get_mut(TypeId::of::<Self>())
+            .expect("type to be registered")
+            .insert(WasvyExport {
+                level: Self::LEVEL,
+                name: Self::NAME.map(Into::into),
+                interface: Self::INTERFACE.map(Into::into),
+                docs: Self::DOCS.map(Into::into),
+                resource: true,
+            });
+    }
+}
+
+/// Warns when a component's reflected value can't round-trip through the app's codec.
+///
+/// Opaque types and handles often can't be serialized, which otherwise only shows up as
+/// confusing runtime errors once a mod touches the component. This needs the component to
+/// reflect [`Default`] (via `#[reflect(Default)]`), otherwise the check is skipped.
+fn check_round_trip(world: &World, type_id: TypeId) {
+    let Some((type_path, err, fields)) = round_trip_failure(world, type_id) else {
+        return;
+    };
+
+    if fields.is_empty() {
+        warn!("Component {type_path} can't be serialized for mods: {err}");
+    } else {
+        warn!(
+            "Component {type_path} can't be serialized for mods, these fields don't round-trip: {}",
+            fields.join(", ")
+        );
+    }
+}
+
+/// Why a type can't round-trip, see [check_round_trip]: its path, the error and the fields that
+/// fail on their own
+fn round_trip_failure(
+    world: &World,
+    type_id: TypeId,
+) -> Option<(&'static str, anyhow::Error, Vec<String>)> {
+    let codec = world.get_resource::<CodecResource>()?;
+    let registry = world.resource::<AppTypeRegistry>().read();
+    let registration = registry.get(type_id)?;
+    let default = registration.data::<ReflectDefault>()?;
+
+    let value = default.default();
+    let value = value.as_partial_reflect();
+    let err = round_trip(value, codec, &registry).err()?;
+
+    let type_path = registration.type_info().type_path();
+    Some((type_path, err, failing_fields(value, codec, &registry)))
+}
+
+fn round_trip(
+    value: &dyn PartialReflect,
+    codec: &CodecResource,
+    registry: &TypeRegistry,
+) -> anyhow::Result<()> {
+    let registration = value
+        .get_represented_type_info()
+        .and_then(|info| registry.get(info.type_id()))
+        .ok_or_else(|| anyhow::anyhow!("type is not registered"))?;
+
+    let bytes = codec.encode_reflect(value, registry)?;
+    codec.decode_reflect(&bytes, registration, registry)?;
+    Ok(())
+}
+
+fn failing_fields(
+    value: &dyn PartialReflect,
+    codec: &CodecResource,
+    registry: &TypeRegistry,
+) -> Vec<String> {
+    let fails = |field: &dyn PartialReflect| round_trip(field, codec, registry).is_err();
+    match value.reflect_ref() {
+        ReflectRef::Struct(value) => (0..value.field_len())
+            .filter(|&i| value.field_at(i).is_some_and(fails))
+            .filter_map(|i| value.name_at(i).map(ToString::to_string))
+            .collect(),
+        ReflectRef::TupleStruct(value) => (0..value.field_len())
+            .filter(|&i| value.field(i).is_some_and(fails))
+            .map(|i| i.to_string())
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Trait implemented by components and resources that export methods to mods.
 ///
 /// Prefer using `#[wasvy::methods]` which implements this trait and registers
 /// methods automatically.
@@ -175,6 +434,21 @@ impl<T: WasvyComponent> Plugin for WasvyComponentPlugin<T> {
     }
 }
 
+/// Plugin that registers a single resource type.
+pub struct WasvyResourcePlugin<T>(PhantomData<T>);
+
+impl<T> Default for WasvyResourcePlugin<T> {
+    fn default() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<T: WasvyResource> Plugin for WasvyResourcePlugin<T> {
+    fn build(&self, app: &mut App) {
+        T::register(app);
+    }
+}
+
 /// Plugin that registers a single component's exported methods.
 pub struct WasvyMethodsPlugin<T>(PhantomData<T>);
 
@@ -211,3 +485,254 @@ impl Plugin for AutoRegistrationPlugin {
         }
     }
 }
+
+/// Exports types that can't derive [`WasvyComponent`] or [`WasvyResource`], because they are
+/// defined in other crates, such as Bevy's `Transform`.
+///
+/// This registers the [`WasvyExport`] type data at runtime, so witgen and mods see the type like
+/// those with the derive. Use [`ExportMethodsExt`] to export their methods as well.
+///
+/// # Example
+/// ```ignore
+/// app.export_component::<Transform>()
+///     .export_component_with::<GlobalTransform>(WasvyExport::new(ExportLevel::ModdingApi));
+///
+/// // Types only known by their type path, such as from a config file
+/// app.export_component_by_path("bevy_sprite::sprite::Sprite", WasvyExport::default())?;
+/// ```
+pub trait ExportComponentExt {
+    /// Exports a component or resource with the default [`WasvyExport`], unless it already has one
+    fn export_component<T: Reflect + TypePath + GetTypeRegistration>(&mut self) -> &mut Self;
+
+    /// Exports a component or resource with `export`, replacing its [`WasvyExport`]
+    fn export_component_with<T: Reflect + TypePath + GetTypeRegistration>(
+        &mut self,
+        export: WasvyExport,
+    ) -> &mut Self;
+
+    /// Exports a registered type by its type path, replacing its [`WasvyExport`].
+    ///
+    /// Fails if no type with this path is registered.
+    fn export_component_by_path(
+        &mut self,
+        type_path: &str,
+        export: WasvyExport,
+    ) -> anyhow::Result<&mut Self>;
+}
+
+impl ExportComponentExt for App {
+    fn export_component<T: Reflect + TypePath + GetTypeRegistration>(&mut self) -> &mut Self {
+        self.register_type::<T>();
+        let exported = self
+            .world()
+            .resource::<AppTypeRegistry>()
+            .read()
+            .get_type_data::<WasvyExport>(TypeId::of::<T>())
+            .is_some();
+        if exported {
+            return self;
+        }
+
+        let export = WasvyExport::default();
+        self.export_component_by_path(T::type_path(), export)
+            .expect("type to be registered")
+    }
+
+    fn export_component_with<T: Reflect + TypePath + GetTypeRegistration>(
+        &mut self,
+        export: WasvyExport,
+    ) -> &mut Self {
+        self.register_type::<T>();
+        self.export_component_by_path(T::type_path(), export)
+            .expect("type to be registered")
+    }
+
+    fn export_component_by_path(
+        &mut self,
+        type_path: &str,
+        mut export: WasvyExport,
+    ) -> anyhow::Result<&mut Self> {
+        let type_id = {
+            let type_registry = self.world().resource::<AppTypeRegistry>();
+            let mut type_registry = type_registry.write();
+            let registration = type_registry
+                .get_with_type_path_mut(type_path)
+                .ok_or_else(|| anyhow::anyhow!("{type_path} is not a registered type"))?;
+            export.resource |= registration.data::<ReflectResource>().is_some();
+            registration.insert(export);
+            registration.type_id()
+        };
+
+        check_round_trip(self.world(), type_id);
+        Ok(self)
+    }
+}
+
+/// Exports methods of types that can't use `#[wasvy::methods]`, because they are defined in
+/// other crates, such as Bevy's `Transform`.
+///
+/// Methods are registered as functions named after the type, so the
+/// [`FunctionIndex`](crate::methods::FunctionIndex) finds them like those of `#[wasvy::methods]`.
+/// The type is exported with the default [`WasvyExport`], unless it already has one.
+///
+/// # Example
+/// ```ignore
+/// app.export_methods::<Transform>(|methods| {
+///     methods
+///         .method("translation", |transform: &Transform| transform.translation)
+///         .method("translate", |transform: &mut Transform, offset: Vec3| {
+///             transform.translation += offset;
+///         })
+///         .args(&["offset"])
+///         .static_method("from_xyz", Transform::from_xyz)
+///         .args(&["x", "y", "z"]);
+/// });
+/// ```
+pub trait ExportMethodsExt {
+    fn export_methods<T: Reflect + TypePath + GetTypeRegistration>(
+        &mut self,
+        f: impl FnOnce(&mut ExportMethods<T>),
+    ) -> &mut Self;
+}
+
+impl ExportMethodsExt for App {
+    fn export_methods<T: Reflect + TypePath + GetTypeRegistration>(
+        &mut self,
+        f: impl FnOnce(&mut ExportMethods<T>),
+    ) -> &mut Self {
+        self.init_resource::<AppFunctionRegistry>();
+        self.register_type::<T>();
+
+        let mut methods = ExportMethods {
+            app: self,
+            methods: Vec::new(),
+            _marker: PhantomData,
+        };
+        f(&mut methods);
+        let methods = methods.methods;
+
+        self.export_component::<T>();
+        let type_registry = self.world().resource::<AppTypeRegistry>();
+        let mut type_registry = type_registry.write();
+        let registration = type_registry
+            .get_mut(TypeId::of::<T>())
+            .expect("type to be registered");
+        match registration.data_mut::<ExternalMethods>() {
+            Some(external) => external.0.extend(methods),
+            None => registration.insert(ExternalMethods(methods)),
+        }
+        drop(type_registry);
+
+        self
+    }
+}
+
+/// Registers the methods of a type, see [`ExportMethodsExt`]
+pub struct ExportMethods<'a, T> {
+    app: &'a mut App,
+    methods: Vec<ExternalMethod>,
+    _marker: PhantomData<T>,
+}
+
+impl<T: TypePath> ExportMethods<'_, T> {
+    /// Exports a method, taking `&T` or `&mut T` as its first argument
+    pub fn method<F, Marker>(&mut self, name: &str, function: F) -> &mut Self
+    where
+        F: IntoFunction<'static, Marker> + 'static,
+    {
+        self.register(name, function, false)
+    }
+
+    /// Exports an associated function without a receiver, such as a constructor
+    pub fn static_method<F, Marker>(&mut self, name: &str, function: F) -> &mut Self
+    where
+        F: IntoFunction<'static, Marker> + 'static,
+    {
+        self.register(name, function, true)
+    }
+
+    /// Names the arguments of the last method, without its receiver. They default to `argN`.
+    pub fn args(&mut self, names: &[&str]) -> &mut Self {
+        if let Some(method) = self.methods.last_mut() {
+            method.arg_names = names.iter().map(|name| name.to_string()).collect();
+        }
+        self
+    }
+
+    /// Sets the doc comment of the last method, shown in the generated WIT
+    pub fn docs(&mut self, docs: &str) -> &mut Self {
+        if let Some(method) = self.methods.last_mut() {
+            method.docs = Some(docs.to_string());
+        }
+        self
+    }
+
+    fn register<F, Marker>(&mut self, name: &str, function: F, is_static: bool) -> &mut Self
+    where
+        F: IntoFunction<'static, Marker> + 'static,
+    {
+        self.app
+            .register_function_with_name(format!("{}::{name}", T::type_path()), function);
+        self.methods.push(ExternalMethod {
+            method: name.to_string(),
+            arg_names: Vec::new(),
+            is_static,
+            docs: None,
+        });
+        self
+    }
+}
+
+/// Type data with the methods exported with [`ExportMethodsExt`], the counterpart of
+/// [`WasvyMethodMetadata`] for types of other crates
+#[derive(Clone, Default)]
+pub struct ExternalMethods(pub Vec<ExternalMethod>);
+
+/// A method exported with [`ExportMethodsExt`]
+#[derive(Clone, Default)]
+pub struct ExternalMethod {
+    /// Method name as exposed to mods.
+    pub method: String,
+    /// Ordered argument names (excluding `self`), empty to name them `argN`.
+    pub arg_names: Vec<String>,
+    /// Whether this is an associated function without a `self` receiver.
+    pub is_static: bool,
+    pub docs: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy_ecs::reflect::ReflectComponent;
+
+    use super::*;
+
+    /// A type mods can't serialize, since it doesn't reflect `Serialize`
+    #[derive(Reflect, Default, Clone)]
+    #[reflect(opaque, Default)]
+    struct Opaque;
+
+    #[derive(Component, Reflect, Default)]
+    #[reflect(Component, Default)]
+    struct Broken {
+        health: f32,
+        opaque: Opaque,
+    }
+
+    impl WasvyComponent for Broken {}
+
+    #[test]
+    fn finds_fields_that_dont_round_trip() {
+        let mut app = App::new();
+        app.insert_resource(CodecResource::default());
+        app.register_type::<Opaque>();
+        Broken::register(&mut app);
+
+        let (type_path, _, fields) =
+            round_trip_failure(app.world(), TypeId::of::<Broken>()).unwrap();
+        assert_eq!(type_path, Broken::type_path());
+        assert_eq!(fields, ["opaque"]);
+
+        app.register_type::<f32>();
+        assert!(round_trip_failure(app.world(), TypeId::of::<f32>()).is_none());
+    }
+}
@@ -0,0 +1,287 @@
+use std::{
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicU64, AtomicUsize, Ordering},
+    },
+    time::Duration,
+};
+
+use bevy_ecs::prelude::*;
+use bevy_platform::collections::HashMap;
+
+use crate::access::ModAccess;
+
+/// Spreads the systems of mods across frames, so mods with many heavy systems don't stall a frame.
+///
+/// A mod with a budget runs its systems until they used up the budget for the frame, and defers
+/// the rest to the next frame. The next frame starts with the first deferred system, and wraps
+/// around to the first system if there is budget left, so every system gets its turn
+/// (round-robin). Which systems fit in a frame is estimated by how long they took the last time
+/// they ran. The first system of a frame always runs, even if it alone exceeds the budget.
+///
+/// Budgets are configured per [ModAccess], and each mod has its own budget for each access. Only
+/// systems scheduled with `app.add-systems` are deferred, observers always run. By default mods
+/// have no budget.
+///
+/// The budgets may be changed at runtime, and apply from the next frame on.
+///
+/// ```
+/// # use std::time::Duration;
+/// # use bevy_ecs::prelude::*;
+/// # use wasvy_runtime::prelude::*;
+/// let budgets = ModFrameBudgets::default().with_default(Duration::from_millis(4));
+///
+/// // Give mods running in a sandbox less time
+/// fn limit_sandbox(mut budgets: ResMut<ModFrameBudgets>, sandbox: Single<Entity, With<Sandbox>>) {
+///     budgets.set(ModAccess::Sandbox(*sandbox), Duration::from_millis(1));
+/// }
+/// # let _ = budgets;
+/// ```
+#[derive(Resource, Default, Clone, Debug)]
+pub struct ModFrameBudgets {
+    default: Option<Duration>,
+    accesses: HashMap<ModAccess, Duration>,
+}
+
+impl ModFrameBudgets {
+    /// Sets the budget of accesses without their own
+    pub fn with_default(mut self, budget: Duration) -> Self {
+        self.default = Some(budget);
+        self
+    }
+
+    /// Sets the budget of an access
+    pub fn with_access(mut self, access: ModAccess, budget: Duration) -> Self {
+        self.set(access, budget);
+        self
+    }
+
+    /// Sets the budget of an access
+    pub fn set(&mut self, access: ModAccess, budget: Duration) {
+        self.accesses.insert(access, budget);
+    }
+
+    /// Removes the budget of an access, so it falls back to the default
+    pub fn remove(&mut self, access: &ModAccess) {
+        self.accesses.remove(access);
+    }
+
+    /// The budget of an access, or [None] if its systems all run every frame
+    pub fn get(&self, access: &ModAccess) -> Option<Duration> {
+        self.accesses.get(access).copied().or(self.default)
+    }
+}
+
+/// The frame budget of a mod for one of its accesses, shared by its systems. See [ModFrameBudgets]
+#[derive(Clone)]
+pub struct FrameBudget(Arc<BudgetState>);
+
+struct BudgetState {
+    /// The budget in nanoseconds, or 0 if there is none
+    budget: AtomicU64,
+
+    /// The time the systems spent this frame, in nanoseconds
+    spent: AtomicU64,
+
+    /// The number of systems registered, used to number them
+    systems: AtomicUsize,
+
+    /// The first system that runs this frame
+    cursor: AtomicUsize,
+
+    /// The number of systems, from the cursor on, expected to fit in the budget this frame
+    window: AtomicUsize,
+
+    /// The turn (systems after the cursor) of the first system that was deferred this frame, or
+    /// [usize::MAX] if none were
+    deferred: AtomicUsize,
+
+    /// The time each system took when it last ran, in nanoseconds, or [None] if it never ran
+    durations: Mutex<Vec<Option<u64>>>,
+}
+
+/// The frame budgets of a mod, see [FrameBudget::of]
+#[derive(Component, Default)]
+pub(crate) struct FrameBudgets(HashMap<ModAccess, FrameBudget>);
+
+impl FrameBudget {
+    /// Returns the budget of a mod for an access, creating it if it has none yet
+    pub fn of(mod_id: Entity, access: &ModAccess, world: &mut World) -> Self {
+        let budget = world
+            .get_resource::<ModFrameBudgets>()
+            .and_then(|budgets| budgets.get(access));
+        let mut entity = world.entity_mut(mod_id);
+        let mut budgets = entity.entry::<FrameBudgets>().or_default().into_mut();
+        budgets
+            .0
+            .entry(*access)
+            .or_insert_with(|| {
+                FrameBudget(Arc::new(BudgetState {
+                    budget: AtomicU64::new(nanos(budget)),
+                    spent: AtomicU64::new(0),
+                    systems: AtomicUsize::new(0),
+                    cursor: AtomicUsize::new(0),
+                    window: AtomicUsize::new(1),
+                    deferred: AtomicUsize::new(usize::MAX),
+                    durations: Mutex::new(Vec::new()),
+                }))
+            })
+            .clone()
+    }
+
+    /// Numbers a system of the mod, in the order they were added
+    pub fn register_system(&self) -> usize {
+        let mut durations = self.0.durations.lock().unwrap();
+        durations.push(None);
+        self.0.systems.fetch_add(1, Ordering::Relaxed)
+    }
+
+    /// Whether a system may run this frame, deferring it otherwise
+    pub fn should_run(&self, system: usize) -> bool {
+        let state = &self.0;
+        let budget = state.budget.load(Ordering::Relaxed);
+        if budget == 0 {
+            return true;
+        }
+
+        // Turns start at the cursor, and wrap around to the first system
+        let systems = state.systems.load(Ordering::Relaxed);
+        let cursor = state.cursor.load(Ordering::Relaxed);
+        let turn = (system + systems - cursor) % systems;
+        if turn == 0
+            || (turn < state.window.load(Ordering::Relaxed)
+                && state.spent.load(Ordering::Relaxed) < budget)
+        {
+            return true;
+        }
+
+        state.deferred.fetch_min(turn, Ordering::Relaxed);
+        false
+    }
+
+    /// Records the time a system ran for
+    pub fn spend(&self, system: usize, elapsed: Duration) {
+        let elapsed = nanos(Some(elapsed));
+        self.0.spent.fetch_add(elapsed, Ordering::Relaxed);
+        self.0.durations.lock().unwrap()[system] = Some(elapsed);
+    }
+
+    fn reset(&self, budget: Option<Duration>) {
+        let state = &self.0;
+        let systems = state.systems.load(Ordering::Relaxed);
+        let deferred = state.deferred.swap(usize::MAX, Ordering::Relaxed);
+        let cursor = match deferred {
+            usize::MAX => 0,
+            turn => (state.cursor.load(Ordering::Relaxed) + turn) % systems,
+        };
+        let budget = nanos(budget);
+
+        // Plan the systems that fit, up to the first that never ran, since its time is unknown
+        let durations = state.durations.lock().unwrap();
+        let mut planned = 0;
+        let mut window = 0;
+        while window < systems {
+            let Some(duration) = durations[(cursor + window) % systems] else {
+                window += 1;
+                break;
+            };
+            if window > 0 && planned + duration > budget {
+                break;
+            }
+            planned += duration;
+            window += 1;
+        }
+
+        state.cursor.store(cursor, Ordering::Relaxed);
+        state.window.store(window, Ordering::Relaxed);
+        state.spent.store(0, Ordering::Relaxed);
+        state.budget.store(budget, Ordering::Relaxed);
+    }
+}
+
+fn nanos(duration: Option<Duration>) -> u64 {
+    duration.map_or(0, |duration| {
+        u64::try_from(duration.as_nanos())
+            .unwrap_or(u64::MAX)
+            .max(1)
+    })
+}
+
+/// Forgets the budgets of a mod for `accesses`, whose systems are about to be added again, so they
+/// are numbered from the start
+pub(crate) fn clear_frame_budgets(world: &mut World, mod_id: Entity, accesses: &[ModAccess]) {
+    if let Some(mut budgets) = world.get_mut::<FrameBudgets>(mod_id) {
+        budgets.0.retain(|access, _| !accesses.contains(access));
+    }
+}
+
+/// Starts a new frame for the budget of every mod, see [ModFrameBudgets]
+pub(crate) fn reset_frame_budgets(budgets: Res<ModFrameBudgets>, mods: Query<&FrameBudgets>) {
+    for mod_budgets in mods.iter() {
+        for (access, budget) in mod_budgets.0.iter() {
+            budget.reset(budgets.get(access));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Runs a frame of systems taking 1ms each, returning the ones that ran
+    fn frame(budget: &FrameBudget, systems: &[usize]) -> Vec<usize> {
+        let mut ran = Vec::new();
+        for &system in systems {
+            if budget.should_run(system) {
+                budget.spend(system, Duration::from_millis(1));
+                ran.push(system);
+            }
+        }
+        budget.reset(Some(Duration::from_millis(2)));
+        ran
+    }
+
+    #[test]
+    fn defers_systems_round_robin() {
+        let mut world = World::new();
+        world.insert_resource(
+            ModFrameBudgets::default().with_access(ModAccess::World, Duration::from_millis(2)),
+        );
+        let mod_id = world.spawn_empty().id();
+        let budget = FrameBudget::of(mod_id, &ModAccess::World, &mut world);
+        let systems: Vec<_> = (0..5).map(|_| budget.register_system()).collect();
+
+        // Systems that never ran take their turn alone, since their time is unknown
+        assert_eq!(frame(&budget, &systems), [0]);
+        assert_eq!(frame(&budget, &systems), [1]);
+        assert_eq!(frame(&budget, &systems), [2]);
+        assert_eq!(frame(&budget, &systems), [3]);
+        assert_eq!(frame(&budget, &systems), [4]);
+
+        // Turns wrap around to the first system within a frame
+        assert_eq!(frame(&budget, &systems), [0, 1]);
+        assert_eq!(frame(&budget, &systems), [2, 3]);
+        assert_eq!(frame(&budget, &systems), [0, 4]);
+        assert_eq!(frame(&budget, &systems), [1, 2]);
+
+        // Without a budget every system runs
+        budget.reset(None);
+        assert_eq!(frame(&budget, &systems), [0, 1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn numbers_systems_again_when_set_up_again() {
+        let mut world = World::new();
+        world.insert_resource(ModFrameBudgets::default().with_default(Duration::from_millis(2)));
+        let mod_id = world.spawn_empty().id();
+        let budget = FrameBudget::of(mod_id, &ModAccess::World, &mut world);
+        assert_eq!(budget.register_system(), 0);
+        assert_eq!(budget.register_system(), 1);
+        frame(&budget, &[0, 1]);
+
+        clear_frame_budgets(&mut world, mod_id, &[ModAccess::World]);
+        let budget = FrameBudget::of(mod_id, &ModAccess::World, &mut world);
+        assert_eq!(budget.register_system(), 0);
+        assert_eq!(frame(&budget, &[0]), [0]);
+    }
+}
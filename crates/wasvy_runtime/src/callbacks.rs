@@ -0,0 +1,74 @@
+use bevy_asset::Assets;
+use bevy_ecs::{change_detection::MaybeLocation, error::warn, prelude::*, system::SystemParam};
+use bevy_log::prelude::*;
+
+use crate::{asset::ModAsset, mods::Mod};
+
+/// This system param invokes the callbacks mods registered during setup.
+///
+/// Unlike systems, callbacks are not bound to a schedule. They run when the host asks for them,
+/// for example when the player picks a dialogue choice. Mods register them with
+/// `app.add-callback`, and they receive commands along with the args passed by the host.
+///
+/// ```
+/// # use bevy_ecs::prelude::*;
+/// # use wasvy_runtime::callbacks::ModCallbacks;
+/// #[derive(Resource)]
+/// struct Dialogue {
+///     mod_id: Entity,
+/// }
+///
+/// fn on_choice(dialogue: Res<Dialogue>, mut callbacks: ModCallbacks) {
+///     callbacks.run(dialogue.mod_id, "on-dialogue-choice", br#"{"choice":2}"#);
+/// }
+/// ```
+#[derive(SystemParam)]
+pub struct ModCallbacks<'w, 's> {
+    commands: Commands<'w, 's>,
+}
+
+impl ModCallbacks<'_, '_> {
+    /// Invokes a callback of a mod, passing it `args`. How args are encoded is up to the mod,
+    /// JSON is a common choice.
+    ///
+    /// Logs an error if the mod hasn't registered the callback, or if the callback fails.
+    ///
+    /// Note: The effect of this change is not immediate. The callback runs when commands are applied.
+    #[track_caller]
+    pub fn run(&mut self, mod_id: Entity, name: impl Into<String>, args: impl Into<Vec<u8>>) {
+        let caller = MaybeLocation::caller();
+        let name = name.into();
+        let args = args.into();
+        let command = move |world: &mut World| -> Result<(), BevyError> {
+            let entity = world
+                .get_entity(mod_id)
+                .map_err(|error| format!("{error}, could not run callback\n{caller}"))?;
+            let mod_component = entity
+                .get::<Mod>()
+                .ok_or_else(|| format!("Entity ({mod_id}) is not a Mod\n{caller}"))?;
+            let asset_id = mod_component.asset().id();
+            let accesses: Vec<_> = mod_component.accesses().copied().collect();
+            let mod_name = entity
+                .get::<Name>()
+                .map(|name| name.as_str())
+                .unwrap_or("unknown")
+                .to_string();
+
+            if !world.resource::<Assets<ModAsset>>().contains(asset_id) {
+                return Err(format!(
+                    "Mod \"{mod_name}\" has not loaded yet, could not run callback \"{name}\"\n{caller}"
+                )
+                .into());
+            }
+
+            if let Err(err) =
+                ModAsset::run_callback(world, &asset_id, mod_id, &mod_name, &accesses, &name, &args)
+            {
+                error!("Failed to run callback \"{name}\" of mod \"{mod_name}\": {err:?}");
+            }
+
+            Ok(())
+        };
+        self.commands.queue_handled(command, warn);
+    }
+}
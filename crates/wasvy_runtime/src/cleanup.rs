@@ -7,7 +7,7 @@ use bevy_ecs::{
 use bevy_log::prelude::*;
 use bevy_platform::collections::{HashMap, HashSet};
 
-use crate::{mods::ModSystemSet, prelude::ModSchedules};
+use crate::{mods::ModSystemSet, prelude::ModSchedules, sandbox::Sandbox};
 
 /// A [Message] that triggers disabling of scheduled [ModSystemSets](ModSystemSet).
 ///
@@ -96,3 +96,35 @@ impl DespawnModEntity {
         Self(entity)
     }
 }
+
+/// A component that tracks the entities a mod hands over to the host when it is despawned, see
+/// [TransferToHost](crate::mods::ModDespawnBehaviour::TransferToHost).
+#[derive(Component, Default)]
+#[relationship_target(relationship = TransferModEntity)]
+pub struct TransferModEntities(EntityHashSet);
+
+/// A component that tracks the mod an entity is handed over from.
+#[derive(Component)]
+#[relationship(relationship_target = TransferModEntities)]
+pub struct TransferModEntity(pub(crate) Entity);
+
+impl TransferModEntity {
+    pub fn new(entity: Entity) -> Self {
+        Self(entity)
+    }
+}
+
+/// Hands the entities of a mod over to the host, moving them out of their sandbox
+pub(crate) fn transfer_to_host(world: &mut World, entities: Vec<Entity>) {
+    for entity in entities {
+        let Ok(mut entity_mut) = world.get_entity_mut(entity) else {
+            continue;
+        };
+        entity_mut.remove::<TransferModEntity>();
+
+        let parent = entity_mut.get::<ChildOf>().map(ChildOf::parent);
+        if parent.is_some_and(|parent| world.get::<Sandbox>(parent).is_some()) {
+            world.entity_mut(entity).remove::<ChildOf>();
+        }
+    }
+}
@@ -10,13 +10,20 @@ use bevy_derive::{Deref, DerefMut};
 use bevy_ecs::{
     component::{ComponentDescriptor, ComponentId},
     prelude::*,
-    reflect::ReflectCommandExt,
-    world::{FilteredEntityMut, FilteredEntityRef},
+    reflect::{ReflectCommandExt, ReflectComponent, ReflectResource},
+    world::{FilteredEntityMut, FilteredEntityRef, FilteredResources, FilteredResourcesMut},
 };
+use bevy_log::prelude::*;
 use bevy_platform::collections::HashMap;
-use bevy_reflect::{Reflect, ReflectFromPtr};
+use bevy_reflect::{
+    PartialReflect, Reflect, ReflectFromPtr, ReflectRef, TypeRegistration, TypeRegistry,
+};
 
-use crate::serialize::CodecResource;
+use crate::{
+    permissions::{ComponentAccess, ComponentPermissions},
+    sandbox::check_relation,
+    serialize::CodecResource,
+};
 
 /// Fully-qualified type path used to identify a component type.
 pub type TypePath = String;
@@ -27,6 +34,46 @@ pub type TypePath = String;
 #[derive(Default, Clone, Debug, Resource, Deref, DerefMut)]
 pub struct WasmComponentRegistry(HashMap<TypePath, ComponentId>);
 
+/// Short names mods may use in place of the full type path of a component or resource.
+///
+/// This lets games curate a stable set of names for scripting, such as `Transform` for
+/// `bevy_transform::components::transform::Transform`, regardless of where the types live in
+/// Rust. Names without an alias are used as type paths as is. Permissions apply to the type path
+/// an alias resolves to.
+///
+/// Declare aliases with [ModRuntimePlugin::alias](crate::plugin::ModRuntimePlugin::alias).
+#[derive(Default, Clone, Debug, Resource)]
+pub struct TypeAliasRegistry(HashMap<String, TypePath>);
+
+impl TypeAliasRegistry {
+    /// Declares `alias` as a name for the type at `type_path`, replacing any previous alias
+    pub fn insert(&mut self, alias: impl Into<String>, type_path: impl Into<TypePath>) {
+        self.0.insert(alias.into(), type_path.into());
+    }
+
+    /// The type path of a name, which is the name itself unless it is an alias
+    pub fn resolve<'a>(&'a self, name: &'a str) -> &'a str {
+        self.0.get(name).map_or(name, String::as_str)
+    }
+
+    /// Like [Self::resolve], with the aliases of the world if it has any.
+    ///
+    /// Check [ComponentPermissions] against the result, since they apply to type paths.
+    pub fn resolve_in(world: &World, name: &str) -> TypePath {
+        world
+            .get_resource::<Self>()
+            .map_or(name, |aliases| aliases.resolve(name))
+            .to_string()
+    }
+
+    /// Iterates over the aliases and the type paths they resolve to
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.0
+            .iter()
+            .map(|(alias, type_path)| (alias.as_str(), type_path.as_str()))
+    }
+}
+
 /// This component is the wrapper component for all the Bevy components that are registered in a
 /// WASM.
 ///
@@ -66,20 +113,74 @@ impl Command for InsertWasmComponent {
     }
 }
 
+/// Inserts a guest defined component into an entity, registering it if it hasn't been yet
+pub(crate) fn insert_wasm_component(
+    world: &mut World,
+    entity: Entity,
+    type_path: &str,
+    serialized_value: Vec<u8>,
+) {
+    InsertWasmComponent {
+        component: WasmComponent { serialized_value },
+        entity,
+        type_path: type_path.to_string(),
+    }
+    .apply(world);
+}
+
+/// Inserts a component given its serialized value.
+///
+/// Aliases in the [TypeAliasRegistry] must be [resolved](TypeAliasRegistry::resolve) first.
+/// Components denied by the mod's [ComponentPermissions] are skipped, logging an error, as are
+/// relationships denied by a [RelationPolicy](crate::sandbox::RelationPolicy). Since relationships
+/// are only checked once the commands are applied, `relation_denied` is then called with the
+/// reason, so it can be reported to the mod.
+#[allow(clippy::too_many_arguments)]
 pub fn insert_component(
     commands: &mut Commands,
     type_registry: &AppTypeRegistry,
     codec: &CodecResource,
+    permissions: &ComponentPermissions,
     entity: Entity,
     type_path: String,
     serialized_value: Vec<u8>,
+    relation_denied: impl FnOnce(String) + Send + 'static,
 ) -> Result<()> {
+    if let Err(denied) = permissions.check(ComponentAccess::Insert, &type_path) {
+        error!("{denied}, skipping insert into {entity}");
+        return Ok(());
+    }
+
     let type_registry = type_registry.read();
 
     // Insert types that are known by bevy (inserted as concrete types)
     if let Some(type_registration) = type_registry.get_with_type_path(&type_path) {
         let output = codec.decode_reflect(&serialized_value, type_registration, &type_registry)?;
-        commands.entity(entity).insert_reflect(output);
+        let reflect_component = type_registration
+            .data::<ReflectComponent>()
+            .ok_or_else(|| anyhow!("{type_path} is not a component"))?
+            .clone();
+        commands.queue(move |world: &mut World| {
+            // Relationships may not cross sandboxes, see RelationPolicy
+            let component_id = reflect_component.register_component(world);
+            let is_relationship = world
+                .components()
+                .get_info(component_id)
+                .is_some_and(|info| info.relationship_accessor().is_some());
+            if is_relationship
+                && let Some(target) = reflected_entity(output.as_ref())
+                && !check_relation(world, entity, target, &type_path)
+            {
+                relation_denied(format!(
+                    "{type_path} would relate {entity} to {target} in another sandbox"
+                ));
+                return;
+            }
+
+            if let Ok(mut entity) = world.get_entity_mut(entity) {
+                entity.insert_reflect(output);
+            }
+        });
     }
     // Handle guest types (inserted as json strings)
     else {
@@ -93,6 +194,16 @@ pub fn insert_component(
     Ok(())
 }
 
+/// Finds the first entity among the fields of a reflected value, such as the target of a relationship
+fn reflected_entity(value: &dyn PartialReflect) -> Option<Entity> {
+    let find = |field: &dyn PartialReflect| field.try_downcast_ref::<Entity>().copied();
+    match value.reflect_ref() {
+        ReflectRef::TupleStruct(value) => value.iter_fields().find_map(find),
+        ReflectRef::Struct(value) => value.iter_fields().find_map(|(_, field)| find(field)),
+        _ => None,
+    }
+}
+
 pub fn remove_component(
     commands: &mut Commands,
     wasm_registry: &WasmComponentRegistry,
@@ -109,6 +220,75 @@ pub fn remove_component(
     }
 }
 
+/// Inserts a resource, or replaces its value, given its serialized value
+///
+/// Like [insert_component], this is skipped with an error log if `permissions` deny inserting it.
+pub fn insert_resource(
+    commands: &mut Commands,
+    type_registry: &AppTypeRegistry,
+    codec: &CodecResource,
+    permissions: &ComponentPermissions,
+    type_path: String,
+    serialized_value: Vec<u8>,
+) -> Result<()> {
+    if let Err(denied) = permissions.check(ComponentAccess::Insert, &type_path) {
+        error!("{denied}, skipping inserting the resource");
+        return Ok(());
+    }
+
+    let type_registry = type_registry.read();
+    let (type_registration, reflect_component) = resource_registration(&type_registry, &type_path)?;
+    let output = codec.decode_reflect(&serialized_value, type_registration, &type_registry)?;
+    commands.queue(move |world: &mut World| {
+        let resource_id = reflect_component.register_component(world);
+        world.insert_reflect_resource(resource_id, output);
+    });
+
+    Ok(())
+}
+
+/// Removes a resource if it exists
+///
+/// This requires the same permission as [insert_resource].
+pub fn remove_resource(
+    commands: &mut Commands,
+    type_registry: &AppTypeRegistry,
+    permissions: &ComponentPermissions,
+    type_path: String,
+) -> Result<()> {
+    if let Err(denied) = permissions.check(ComponentAccess::Insert, &type_path) {
+        error!("{denied}, skipping removing the resource");
+        return Ok(());
+    }
+
+    let type_registry = type_registry.read();
+    let (_, reflect_component) = resource_registration(&type_registry, &type_path)?;
+    commands.queue(move |world: &mut World| {
+        let resource_id = reflect_component.register_component(world);
+        world.remove_resource_by_id(resource_id);
+    });
+
+    Ok(())
+}
+
+/// The registration of a reflected resource, and the [ReflectComponent] resources also have
+fn resource_registration<'a>(
+    type_registry: &'a TypeRegistry,
+    type_path: &str,
+) -> Result<(&'a TypeRegistration, ReflectComponent)> {
+    let type_registration = type_registry
+        .get_with_type_path(type_path)
+        .ok_or(anyhow!("{type_path} is not a registered type"))?;
+    if type_registration.data::<ReflectResource>().is_none() {
+        return Err(anyhow!("{type_path} is not a resource"));
+    }
+    let reflect_component = type_registration
+        .data::<ReflectComponent>()
+        .ok_or(anyhow!("{type_path} is not a resource"))?
+        .clone();
+    Ok((type_registration, reflect_component))
+}
+
 /// A collection containing a [ComponentId], and a [TypeId]
 ///
 /// The type id is [None] for guest components, and [Some] for concrete host types
@@ -126,7 +306,19 @@ pub struct ComponentRef {
 
 impl ComponentRef {
     /// See [ComponentRef]
-    pub fn new(type_path: &str, world: &mut World) -> Result<Self> {
+    ///
+    /// The `type_path` may be an alias from the [TypeAliasRegistry]. Fails with [PermissionDenied](crate::permissions::PermissionDenied) if `permissions`
+    /// don't allow this access to the component.
+    pub fn new(
+        type_path: &str,
+        access: ComponentAccess,
+        permissions: &ComponentPermissions,
+        world: &mut World,
+    ) -> Result<Self> {
+        let type_path = TypeAliasRegistry::resolve_in(world, type_path);
+        let type_path = type_path.as_str();
+        permissions.check(access, type_path)?;
+
         let type_registry = world
             .get_resource::<AppTypeRegistry>()
             .expect("there to be an AppTypeRegistry")
@@ -169,7 +361,11 @@ impl ComponentRef {
     }
 }
 
+/// The component id of a `With` or `Without` filter. The `type_path` may be an alias from the
+/// [TypeAliasRegistry].
 pub fn get_component_id_for_filter(type_path: &str, world: &mut World) -> Option<ComponentId> {
+    let type_path = TypeAliasRegistry::resolve_in(world, type_path);
+    let type_path = type_path.as_str();
     let type_registry = world
         .get_resource::<AppTypeRegistry>()
         .expect("there to be an AppTypeRegistry")
@@ -185,7 +381,7 @@ pub fn get_component_id_for_filter(type_path: &str, world: &mut World) -> Option
 }
 
 /// Gets the component id given a type path, or registers a new component id for a [WasmComponent]
-fn get_wasm_component_id(type_path: &str, world: &mut World) -> ComponentId {
+pub(crate) fn get_wasm_component_id(type_path: &str, world: &mut World) -> ComponentId {
     let component_registry = world.get_resource_or_init::<WasmComponentRegistry>();
 
     // Get an existing id if it exists
@@ -362,3 +558,303 @@ pub fn with_component_mut<R>(
 
     f(reflect)
 }
+
+/// A reference to a host resource requested by a mod.
+///
+/// Unlike components, resources can only be defined by the host, so the type must be registered
+/// in the [AppTypeRegistry].
+#[derive(Clone)]
+pub struct ResourceRef {
+    component_id: ComponentId,
+    type_id: TypeId,
+    type_path: TypePath,
+}
+
+impl ResourceRef {
+    /// See [ResourceRef]
+    ///
+    /// The `type_path` may be an alias from the [TypeAliasRegistry].
+    pub fn new(type_path: &str, world: &mut World) -> Result<Self> {
+        let type_path = TypeAliasRegistry::resolve_in(world, type_path);
+        let type_path = type_path.as_str();
+        let type_registry = world
+            .get_resource::<AppTypeRegistry>()
+            .expect("there to be an AppTypeRegistry")
+            .read();
+
+        let type_registration = type_registry
+            .get_with_type_path(type_path)
+            .ok_or(anyhow!("{type_path} is not a registered type"))?;
+        if type_registration.data::<ReflectResource>().is_none() {
+            return Err(anyhow!("{type_path} is not a resource"));
+        }
+        let type_id = type_registration.type_id();
+        let component_id = world
+            .components()
+            .get_id(type_id)
+            .ok_or(anyhow!("{type_path} has not been inserted into the world"))?;
+
+        Ok(Self {
+            component_id,
+            type_id,
+            type_path: type_path.to_string(),
+        })
+    }
+
+    pub fn component_id(&self) -> ComponentId {
+        self.component_id
+    }
+
+    pub fn type_path(&self) -> &str {
+        &self.type_path
+    }
+}
+
+/// Retrieves the serialized value of a resource
+pub fn get_resource(
+    resources: &FilteredResources,
+    resource_ref: &ResourceRef,
+    type_registry: &AppTypeRegistry,
+    codec: &CodecResource,
+) -> Result<Vec<u8>> {
+    let val = resources
+        .get_by_id(resource_ref.component_id)
+        .map_err(|err| anyhow!("Cannot read resource {}: {err}", resource_ref.type_path))?;
+
+    let type_registry = type_registry.read();
+    let type_registration = type_registry
+        .get(resource_ref.type_id)
+        .expect("ResourceRef type_id be registered");
+    let reflect_from_ptr = type_registration
+        .data::<ReflectFromPtr>()
+        .expect("ReflectFromPtr to be registered");
+
+    // SAFETY: val is of the same type that reflect_from_ptr was constructed for
+    let reflect = unsafe { reflect_from_ptr.as_reflect(val) };
+    codec.encode_reflect(reflect, &type_registry)
+}
+
+/// Whether a resource equals `expected`, or false if it's missing or can't be compared
+pub fn resource_equals(
+    resources: &FilteredResources,
+    resource_ref: &ResourceRef,
+    expected: &dyn PartialReflect,
+    type_registry: &AppTypeRegistry,
+) -> bool {
+    let Ok(val) = resources.get_by_id(resource_ref.component_id) else {
+        return false;
+    };
+
+    let type_registry = type_registry.read();
+    let type_registration = type_registry
+        .get(resource_ref.type_id)
+        .expect("ResourceRef type_id be registered");
+    let reflect_from_ptr = type_registration
+        .data::<ReflectFromPtr>()
+        .expect("ReflectFromPtr to be registered");
+
+    // SAFETY: val is of the same type that reflect_from_ptr was constructed for
+    let reflect = unsafe { reflect_from_ptr.as_reflect(val) };
+    reflect
+        .as_partial_reflect()
+        .reflect_partial_eq(expected)
+        .unwrap_or(false)
+}
+
+/// Sets the value of a resource given its serialized value
+pub fn set_resource(
+    resources: &mut FilteredResourcesMut,
+    resource_ref: &ResourceRef,
+    serialized_value: Vec<u8>,
+    type_registry: &AppTypeRegistry,
+    codec: &CodecResource,
+) -> Result<()> {
+    let mut val = resources
+        .get_mut_by_id(resource_ref.component_id)
+        .map_err(|err| anyhow!("Cannot write resource {}: {err}", resource_ref.type_path))?;
+
+    let type_registry = type_registry.read();
+    let type_registration = type_registry
+        .get(resource_ref.type_id)
+        .expect("ResourceRef type_id be registered");
+    let reflect_from_ptr = type_registration
+        .data::<ReflectFromPtr>()
+        .expect("ReflectFromPtr to be registered");
+
+    let boxed_dyn_reflect =
+        codec.decode_reflect(&serialized_value, type_registration, &type_registry)?;
+
+    // SAFETY: val is of the same type that ReflectFromPtr was constructed for
+    let reflect = unsafe { reflect_from_ptr.as_reflect_mut(val.as_mut()) };
+    reflect.apply(boxed_dyn_reflect.as_partial_reflect());
+
+    Ok(())
+}
+
+/// Calls `f` with the reflected value of a resource, such as to invoke its methods
+pub fn with_resource_ref<R>(
+    resources: &FilteredResources,
+    resource_ref: &ResourceRef,
+    type_registry: &AppTypeRegistry,
+    f: impl FnOnce(&dyn Reflect) -> Result<R>,
+) -> Result<R> {
+    let val = resources
+        .get_by_id(resource_ref.component_id)
+        .map_err(|err| anyhow!("Cannot read resource {}: {err}", resource_ref.type_path))?;
+
+    let type_registry = type_registry.read();
+    let type_registration = type_registry
+        .get(resource_ref.type_id)
+        .expect("ResourceRef type_id be registered");
+    let reflect_from_ptr = type_registration
+        .data::<ReflectFromPtr>()
+        .expect("ReflectFromPtr to be registered");
+
+    // SAFETY: val is of the same type that reflect_from_ptr was constructed for
+    let reflect = unsafe { reflect_from_ptr.as_reflect(val) };
+
+    f(reflect)
+}
+
+/// Calls `f` with the mutable reflected value of a resource, such as to invoke its methods
+pub fn with_resource_mut<R>(
+    resources: &mut FilteredResourcesMut,
+    resource_ref: &ResourceRef,
+    type_registry: &AppTypeRegistry,
+    f: impl FnOnce(&mut dyn Reflect) -> Result<R>,
+) -> Result<R> {
+    let mut val = resources
+        .get_mut_by_id(resource_ref.component_id)
+        .map_err(|err| anyhow!("Cannot write resource {}: {err}", resource_ref.type_path))?;
+
+    let type_registry = type_registry.read();
+    let type_registration = type_registry
+        .get(resource_ref.type_id)
+        .expect("ResourceRef type_id be registered");
+    let reflect_from_ptr = type_registration
+        .data::<ReflectFromPtr>()
+        .expect("ReflectFromPtr to be registered");
+
+    // SAFETY: val is of the same type that reflect_from_ptr was constructed for
+    let reflect = unsafe { reflect_from_ptr.as_reflect_mut(val.as_mut()) };
+
+    f(reflect)
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy_reflect::TypePath;
+
+    use super::*;
+
+    #[derive(Resource, Reflect, Debug, PartialEq)]
+    #[reflect(Resource)]
+    struct Score(u32);
+
+    #[derive(Component, Reflect, Debug, PartialEq)]
+    #[reflect(Component)]
+    struct Health(u32);
+
+    #[cfg(feature = "serde_json")]
+    #[test]
+    fn inserts_and_removes_resources() {
+        let mut world = World::new();
+        let type_registry = AppTypeRegistry::default();
+        type_registry.write().register::<Score>();
+        world.insert_resource(type_registry.clone());
+        let codec = CodecResource::default();
+        let permissions = ComponentPermissions::default();
+
+        let mut commands = world.commands();
+        let type_path = Score::type_path().to_string();
+        insert_resource(
+            &mut commands,
+            &type_registry,
+            &codec,
+            &permissions,
+            type_path.clone(),
+            b"3".to_vec(),
+        )
+        .unwrap();
+        world.flush();
+        assert_eq!(world.get_resource::<Score>(), Some(&Score(3)));
+
+        let mut commands = world.commands();
+        insert_resource(
+            &mut commands,
+            &type_registry,
+            &codec,
+            &permissions,
+            type_path.clone(),
+            b"5".to_vec(),
+        )
+        .unwrap();
+        world.flush();
+        assert_eq!(world.get_resource::<Score>(), Some(&Score(5)));
+
+        let denied = permissions
+            .clone()
+            .deny(ComponentAccess::Insert, [type_path.clone()]);
+        let mut commands = world.commands();
+        remove_resource(&mut commands, &type_registry, &denied, type_path.clone()).unwrap();
+        world.flush();
+        assert!(world.contains_resource::<Score>());
+
+        let mut commands = world.commands();
+        remove_resource(&mut commands, &type_registry, &permissions, type_path).unwrap();
+        world.flush();
+        assert!(!world.contains_resource::<Score>());
+
+        let mut commands = world.commands();
+        assert!(
+            remove_resource(
+                &mut commands,
+                &type_registry,
+                &permissions,
+                "alloc::string::String".to_string(),
+            )
+            .is_err()
+        );
+    }
+
+    #[cfg(feature = "serde_json")]
+    #[test]
+    fn resolves_aliases() {
+        let mut world = World::new();
+        let type_registry = AppTypeRegistry::default();
+        type_registry.write().register::<Health>();
+        world.insert_resource(type_registry.clone());
+        world.register_component::<Health>();
+        let mut aliases = TypeAliasRegistry::default();
+        aliases.insert("Health", Health::type_path());
+        world.insert_resource(aliases.clone());
+        let codec = CodecResource::default();
+        let permissions = ComponentPermissions::default();
+
+        let component =
+            ComponentRef::new("Health", ComponentAccess::Query, &permissions, &mut world).unwrap();
+        assert_eq!(component.type_path(), Health::type_path());
+
+        // Permissions apply to the type an alias resolves to
+        let denied = permissions
+            .clone()
+            .deny(ComponentAccess::Query, [Health::type_path()]);
+        assert!(ComponentRef::new("Health", ComponentAccess::Query, &denied, &mut world).is_err());
+
+        let entity = world.spawn_empty().id();
+        let mut commands = world.commands();
+        insert_component(
+            &mut commands,
+            &type_registry,
+            &codec,
+            &permissions,
+            entity,
+            aliases.resolve("Health").to_string(),
+            b"3".to_vec(),
+            |_| {},
+        )
+        .unwrap();
+        world.flush();
+        assert_eq!(world.get::<Health>(entity), Some(&Health(3)));
+    }
+}
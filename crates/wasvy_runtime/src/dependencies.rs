@@ -0,0 +1,359 @@
+use bevy_asset::Assets;
+use bevy_ecs::{entity::EntityHashSet, prelude::*};
+use bevy_platform::collections::{HashMap, HashSet};
+
+use crate::{
+    asset::ModAsset,
+    manifest::{ModDetails, ModManifest},
+    mods::Mod,
+};
+
+/// The outcome of checking the dependencies of a mod, see [ModManifest::dependencies]
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) enum Resolution {
+    /// All dependencies are initialized, so the mod can be set up after them
+    Ready(Vec<Entity>),
+
+    /// Some dependencies are still loading or being set up
+    Wait,
+
+    /// The dependencies can never be satisfied
+    Fail(String),
+}
+
+/// The manifests of all loaded mods, used to resolve their dependencies
+///
+/// Mods without a manifest are known by their [ModDetails] instead, once they were set up.
+pub(crate) struct Dependencies {
+    manifests: HashMap<Entity, ModManifest>,
+    ids: HashMap<String, Entity>,
+
+    /// The ids shared by several mods, which can't be depended on
+    duplicates: HashSet<String>,
+
+    /// Whether the assets of all mods have loaded, so no new manifests can show up
+    all_loaded: bool,
+
+    /// Mods without a manifest that haven't declared their [ModDetails] yet
+    undeclared: EntityHashSet,
+}
+
+impl Dependencies {
+    pub(crate) fn new(world: &mut World) -> Self {
+        let mut mods = world.query::<(Entity, &Mod, Option<&ModDetails>)>();
+        let assets = world.resource::<Assets<ModAsset>>();
+
+        let mut all_loaded = true;
+        let mut manifests = Vec::new();
+        let mut undeclared = EntityHashSet::default();
+        for (mod_id, mod_component, details) in mods.iter(world) {
+            let Some(asset) = assets.get(mod_component.asset().id()) else {
+                all_loaded = false;
+                continue;
+            };
+            match (asset.manifest(), details) {
+                (Some(manifest), _) => manifests.push((mod_id, manifest.clone())),
+                (None, Some(details)) => manifests.push((mod_id, details.to_manifest())),
+                (None, None) => {
+                    undeclared.insert(mod_id);
+                }
+            }
+        }
+
+        let mut dependencies = Self::from_manifests(manifests, all_loaded);
+        dependencies.undeclared = undeclared;
+        dependencies
+    }
+
+    /// Takes note of the details a mod without a manifest declared during its setup
+    pub(crate) fn declare(&mut self, mod_id: Entity, details: Option<&ModDetails>) {
+        if !self.undeclared.remove(&mod_id) {
+            return;
+        }
+        if let Some(details) = details {
+            self.insert_id(&details.name, mod_id);
+            self.manifests.insert(mod_id, details.to_manifest());
+        }
+    }
+
+    fn from_manifests(manifests: Vec<(Entity, ModManifest)>, all_loaded: bool) -> Self {
+        let mut dependencies = Self {
+            manifests: HashMap::new(),
+            ids: HashMap::new(),
+            duplicates: HashSet::new(),
+            all_loaded,
+            undeclared: EntityHashSet::default(),
+        };
+        for (mod_id, manifest) in manifests {
+            dependencies.insert_id(&manifest.id, mod_id);
+            dependencies.manifests.insert(mod_id, manifest);
+        }
+        dependencies
+    }
+
+    /// Takes note of the id of a mod, and of whether another mod has it too
+    fn insert_id(&mut self, id: &str, mod_id: Entity) {
+        match self.ids.get(id) {
+            Some(other) if *other != mod_id => {
+                self.duplicates.insert(id.to_string());
+            }
+            Some(_) => {}
+            None => {
+                self.ids.insert(id.to_string(), mod_id);
+            }
+        }
+    }
+
+    /// Checks whether a mod can be set up, given which mods are already `initialized` and which are
+    /// still `pending` setup
+    pub(crate) fn resolve(
+        &self,
+        mod_id: Entity,
+        initialized: &EntityHashSet,
+        pending: &EntityHashSet,
+    ) -> Resolution {
+        let Some(manifest) = self.manifests.get(&mod_id) else {
+            return Resolution::Ready(Vec::new());
+        };
+
+        if let Some(cycle) = self.find_cycle(mod_id) {
+            return Resolution::Fail(format!("Cyclic dependency {}", cycle.join(" -> ")));
+        }
+
+        let mut ready = Vec::new();
+        let mut wait = false;
+        for (id, requirement) in manifest.dependencies.iter() {
+            if self.duplicates.contains(id) {
+                return Resolution::Fail(format!(
+                    "Depends on mod \"{id}\", but several loaded mods have that id"
+                ));
+            }
+            let Some(dependency) = self.ids.get(id) else {
+                // Mods without a manifest may still turn out to be the dependency once set up
+                let may_declare = self
+                    .undeclared
+                    .iter()
+                    .any(|undeclared| *undeclared != mod_id && pending.contains(undeclared));
+                if self.all_loaded && !may_declare {
+                    return Resolution::Fail(format!(
+                        "Depends on mod \"{id}\", which is not loaded"
+                    ));
+                }
+                wait = true;
+                continue;
+            };
+
+            let loaded = &self.manifests[dependency];
+            if !loaded.satisfies(requirement) {
+                return Resolution::Fail(format!(
+                    "Depends on mod \"{id}\" {requirement}, but version {} is loaded",
+                    loaded.version
+                ));
+            }
+
+            if initialized.contains(dependency) {
+                ready.push(*dependency);
+            } else if pending.contains(dependency) {
+                wait = true;
+            } else {
+                return Resolution::Fail(format!(
+                    "Depends on mod \"{id}\", which failed to initialize"
+                ));
+            }
+        }
+
+        if wait {
+            Resolution::Wait
+        } else {
+            Resolution::Ready(ready)
+        }
+    }
+
+    /// Returns the ids of the mods in a dependency cycle through `mod_id`, if there is one
+    fn find_cycle(&self, mod_id: Entity) -> Option<Vec<String>> {
+        let mut path = vec![mod_id];
+        let mut visited = EntityHashSet::default();
+        self.visit(mod_id, &mut path, &mut visited).then(|| {
+            path.iter()
+                .map(|id| self.manifests[id].id.clone())
+                .collect()
+        })
+    }
+
+    fn visit(&self, current: Entity, path: &mut Vec<Entity>, visited: &mut EntityHashSet) -> bool {
+        let Some(manifest) = self.manifests.get(&current) else {
+            return false;
+        };
+
+        for id in manifest.dependencies.keys() {
+            let Some(&dependency) = self.ids.get(id) else {
+                continue;
+            };
+            if dependency == path[0] {
+                path.push(dependency);
+                return true;
+            }
+            if !visited.insert(dependency) {
+                continue;
+            }
+
+            path.push(dependency);
+            if self.visit(dependency, path, visited) {
+                return true;
+            }
+            path.pop();
+        }
+
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn manifest(id: &str, version: &str, dependencies: &[(&str, &str)]) -> ModManifest {
+        ModManifest {
+            id: id.to_string(),
+            version: version.parse().unwrap(),
+            author: None,
+            dependencies: dependencies
+                .iter()
+                .map(|(id, requirement)| (id.to_string(), requirement.parse().unwrap()))
+                .collect(),
+            signature: None,
+        }
+    }
+
+    #[test]
+    fn resolves_dependencies() {
+        let [a, b, c, d] = [0, 1, 2, 3].map(Entity::from_raw_u32).map(Option::unwrap);
+        let dependencies = Dependencies::from_manifests(
+            vec![
+                (a, manifest("a", "1.2.0", &[])),
+                (b, manifest("b", "0.1.0", &[("a", "^1.0")])),
+                (c, manifest("c", "0.1.0", &[("a", "^2.0")])),
+                (d, manifest("d", "0.1.0", &[("missing", "*")])),
+            ],
+            true,
+        );
+
+        let mut initialized = EntityHashSet::default();
+        let mut pending = EntityHashSet::from_iter([a, b, c, d]);
+        assert_eq!(
+            dependencies.resolve(b, &initialized, &pending),
+            Resolution::Wait
+        );
+        assert_eq!(
+            dependencies.resolve(a, &initialized, &pending),
+            Resolution::Ready(vec![])
+        );
+        initialized.insert(a);
+        pending.remove(&a);
+        assert_eq!(
+            dependencies.resolve(b, &initialized, &pending),
+            Resolution::Ready(vec![a])
+        );
+        assert!(matches!(
+            dependencies.resolve(c, &initialized, &pending),
+            Resolution::Fail(_)
+        ));
+        assert!(matches!(
+            dependencies.resolve(d, &initialized, &pending),
+            Resolution::Fail(_)
+        ));
+
+        // A dependency that failed to initialize is neither initialized nor pending
+        initialized.remove(&a);
+        assert!(matches!(
+            dependencies.resolve(b, &initialized, &pending),
+            Resolution::Fail(_)
+        ));
+    }
+
+    #[test]
+    fn resolves_dependencies_on_declared_details() {
+        let [a, b] = [0, 1].map(Entity::from_raw_u32).map(Option::unwrap);
+        let mut dependencies =
+            Dependencies::from_manifests(vec![(b, manifest("b", "0.1.0", &[("a", "^1.0")]))], true);
+        dependencies.undeclared.insert(a);
+
+        let mut initialized = EntityHashSet::default();
+        let mut pending = EntityHashSet::from_iter([a, b]);
+        assert_eq!(
+            dependencies.resolve(b, &initialized, &pending),
+            Resolution::Wait
+        );
+
+        let details = ModDetails {
+            name: "a".to_string(),
+            version: "1.1.0".parse().unwrap(),
+            wasvy_version: None,
+            description: None,
+        };
+        dependencies.declare(a, Some(&details));
+        initialized.insert(a);
+        pending.remove(&a);
+        assert_eq!(
+            dependencies.resolve(b, &initialized, &pending),
+            Resolution::Ready(vec![a])
+        );
+    }
+
+    #[test]
+    fn fails_on_duplicate_ids() {
+        let [a, b, c, d] = [0, 1, 2, 3].map(Entity::from_raw_u32).map(Option::unwrap);
+        let mut dependencies = Dependencies::from_manifests(
+            vec![
+                (a, manifest("a", "1.0.0", &[])),
+                (b, manifest("a", "1.1.0", &[])),
+                (c, manifest("c", "0.1.0", &[("a", "^1.0")])),
+                (d, manifest("d", "0.1.0", &[("e", "^1.0")])),
+            ],
+            true,
+        );
+
+        let initialized = EntityHashSet::from_iter([a, b]);
+        let pending = EntityHashSet::from_iter([c]);
+        assert_eq!(
+            dependencies.resolve(c, &initialized, &pending),
+            Resolution::Fail(
+                "Depends on mod \"a\", but several loaded mods have that id".to_string()
+            )
+        );
+
+        // Declared details count too
+        let [e, f] = [4, 5].map(Entity::from_raw_u32).map(Option::unwrap);
+        dependencies.undeclared.extend([e, f]);
+        let details = ModDetails {
+            name: "e".to_string(),
+            version: "1.0.0".parse().unwrap(),
+            wasvy_version: None,
+            description: None,
+        };
+        dependencies.declare(e, Some(&details));
+        dependencies.declare(f, Some(&details));
+        assert!(matches!(
+            dependencies.resolve(d, &EntityHashSet::from_iter([e, f]), &pending),
+            Resolution::Fail(_)
+        ));
+    }
+
+    #[test]
+    fn detects_cycles() {
+        let [a, b, c] = [0, 1, 2].map(Entity::from_raw_u32).map(Option::unwrap);
+        let dependencies = Dependencies::from_manifests(
+            vec![
+                (a, manifest("a", "1.0.0", &[("b", "*")])),
+                (b, manifest("b", "1.0.0", &[("c", "*")])),
+                (c, manifest("c", "1.0.0", &[("a", "*")])),
+            ],
+            true,
+        );
+
+        assert_eq!(
+            dependencies.resolve(a, &EntityHashSet::default(), &EntityHashSet::default()),
+            Resolution::Fail("Cyclic dependency a -> b -> c -> a".to_string())
+        );
+    }
+}
@@ -26,13 +26,13 @@ pub fn spawn(mut params: In<Option<Value>>, mut mods: Mods) -> Result<Value> {
     let mut errors = Errors::new();
     let mod_ids: Vec<Value> = values
         .into_iter()
-        .filter_map(|value| errors.collect(serde_json::from_value(value)))
-        .map(|Instance { path, name, access }| {
-            let mod_id = mods.spawn(path, name);
+        .filter_map(|value| {
+            let Instance { path, name, access } = errors.collect(serde_json::from_value(value))?;
+            let mod_id = errors.collect(mods.try_spawn(path, name))?;
             for access in access {
                 mods.enable_access(mod_id, access);
             }
-            mod_id
+            Some(mod_id)
         })
         .map(|entity| entity.to_bits().into())
         .collect();
@@ -0,0 +1,102 @@
+use std::path::PathBuf;
+
+use bevy_asset::{AssetPath, AssetServer};
+use bevy_ecs::prelude::*;
+use bevy_log::prelude::*;
+use bevy_tasks::{IoTaskPool, Task, futures::check_ready, futures_lite::StreamExt};
+
+use crate::{access::ModAccess, mods::Mods, package::PACKAGE_EXTENSION};
+
+/// Sent for every mod found by [Mods::load_dir]
+#[derive(Message, Debug, Clone, PartialEq, Eq)]
+pub struct ModDiscovered {
+    /// The path of the mod's wasm file or package
+    pub path: AssetPath<'static>,
+
+    /// The spawned mod, or [None] if the filter passed to [Mods::load_dir_filtered] skipped it
+    pub mod_id: Option<Entity>,
+}
+
+/// Decides which discovered mods are loaded, see [Mods::load_dir_filtered]
+pub type ModFilter = Box<dyn Fn(&AssetPath) -> bool + Send + Sync>;
+
+/// A directory that is being scanned for mods
+#[derive(Component)]
+pub(crate) struct ModScan {
+    dir: AssetPath<'static>,
+    task: Task<Result<Vec<PathBuf>, BevyError>>,
+    filter: ModFilter,
+}
+
+impl ModScan {
+    pub(crate) fn new(
+        asset_server: AssetServer,
+        dir: AssetPath<'static>,
+        filter: ModFilter,
+    ) -> Self {
+        let task = IoTaskPool::get().spawn({
+            let dir = dir.clone();
+            async move { scan(&asset_server, &dir).await }
+        });
+        Self { dir, task, filter }
+    }
+}
+
+/// Lists the wasm files and [packages](crate::package::ModPackage) in a directory, in a stable order
+async fn scan(asset_server: &AssetServer, dir: &AssetPath<'_>) -> Result<Vec<PathBuf>, BevyError> {
+    let reader = asset_server.get_source(dir.source())?.reader();
+
+    let mut paths = Vec::new();
+    let mut entries = reader.read_directory(dir.path()).await?;
+    while let Some(path) = entries.next().await {
+        let is_mod = path
+            .extension()
+            .is_some_and(|ext| ext == "wasm" || ext == PACKAGE_EXTENSION);
+        if is_mod && !reader.is_directory(&path).await? {
+            paths.push(path);
+        }
+    }
+    paths.sort();
+
+    Ok(paths)
+}
+
+/// Loads the mods of finished directory scans
+pub(crate) fn load_scanned_mods(
+    mut commands: Commands,
+    mut mods: Mods,
+    mut scans: Query<(Entity, &mut ModScan)>,
+    mut discovered: MessageWriter<ModDiscovered>,
+) {
+    for (entity, mut scan) in scans.iter_mut() {
+        let Some(result) = check_ready(&mut scan.task) else {
+            continue;
+        };
+        commands.entity(entity).despawn();
+
+        let paths = match result {
+            Ok(paths) => paths,
+            Err(err) => {
+                warn!("Could not load mods from \"{}\": {err}", scan.dir);
+                continue;
+            }
+        };
+
+        info!("Found {} mods in \"{}\"", paths.len(), scan.dir);
+        for path in paths {
+            let path = AssetPath::from(path).with_source(scan.dir.source().clone_owned());
+            let mod_id = match (scan.filter)(&path).then(|| mods.try_spawn(path.clone(), None)) {
+                Some(Ok(mod_id)) => {
+                    mods.enable_access(mod_id, ModAccess::World);
+                    Some(mod_id)
+                }
+                Some(Err(err)) => {
+                    warn!("Could not load mod \"{path}\": {err}");
+                    continue;
+                }
+                None => None,
+            };
+            discovered.write(ModDiscovered { path, mod_id });
+        }
+    }
+}
@@ -0,0 +1,104 @@
+use anyhow::{Result, bail};
+use bevy_ecs::prelude::*;
+
+/// The exports the host calls on mods, defining their lifecycle.
+///
+/// Every mod is set up by calling its [setup](Self::setup) export. Hosts can define more entry
+/// points, such as `on-save` and `on-load`, and call them on all mods with
+/// [Mods::run_entry_point](crate::mods::Mods::run_entry_point). Mods lacking a [required](Self::required)
+/// export fail to load, while mods lacking an [optional](Self::optional) one are skipped when it
+/// is run. Entry points take the same params as setup.
+///
+/// Configure these via [ModRuntimePlugin::entry_points](crate::plugin::ModRuntimePlugin::entry_points).
+///
+/// ```
+/// # use wasvy_runtime::entry_points::ModEntryPoints;
+/// let entry_points = ModEntryPoints::default()
+///     .require("on-load")
+///     .optional("on-save");
+/// # let _ = entry_points;
+/// ```
+#[derive(Resource, Debug, Clone, PartialEq, Eq)]
+pub struct ModEntryPoints {
+    /// The export called to set up mods. Defaults to `setup`
+    pub setup: String,
+
+    /// The exports every mod must have, besides setup
+    pub required: Vec<String>,
+
+    /// The exports mods may have
+    pub optional: Vec<String>,
+}
+
+impl Default for ModEntryPoints {
+    fn default() -> Self {
+        Self {
+            setup: "setup".to_string(),
+            required: Vec::new(),
+            optional: Vec::new(),
+        }
+    }
+}
+
+impl ModEntryPoints {
+    /// Adds an export every mod must have
+    pub fn require(mut self, name: impl Into<String>) -> Self {
+        self.required.push(name.into());
+        self
+    }
+
+    /// Adds an export mods may have
+    pub fn optional(mut self, name: impl Into<String>) -> Self {
+        self.optional.push(name.into());
+        self
+    }
+
+    /// Whether `name` is one of the entry points, other than setup
+    pub fn contains(&self, name: &str) -> bool {
+        self.required
+            .iter()
+            .chain(self.optional.iter())
+            .any(|n| n == name)
+    }
+
+    /// The name of the setup export of the world's entry points
+    pub fn setup_of(world: &World) -> String {
+        world
+            .get_resource::<Self>()
+            .map(|entry_points| entry_points.setup.clone())
+            .unwrap_or_else(|| Self::default().setup)
+    }
+
+    /// Checks that a mod has the setup export and all required exports
+    pub fn check(&self, has_export: impl Fn(&str) -> bool) -> Result<()> {
+        for name in std::iter::once(&self.setup).chain(self.required.iter()) {
+            if !has_export(name) {
+                bail!("Mod is missing the required export \"{name}\"");
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn checks_required_exports() {
+        let entry_points = ModEntryPoints::default()
+            .require("on-load")
+            .optional("on-save");
+        assert!(entry_points.contains("on-save"));
+        assert!(!entry_points.contains("setup"));
+
+        assert!(entry_points.check(|name| name != "on-save").is_ok());
+        assert_eq!(
+            entry_points
+                .check(|name| name != "on-load")
+                .unwrap_err()
+                .to_string(),
+            "Mod is missing the required export \"on-load\""
+        );
+    }
+}
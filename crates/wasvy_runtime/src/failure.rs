@@ -0,0 +1,170 @@
+use std::sync::{
+    Arc,
+    atomic::{AtomicU32, Ordering},
+};
+
+use bevy_ecs::prelude::*;
+use bevy_log::prelude::*;
+
+use crate::{
+    cleanup::DisableSystemSet,
+    mods::{Mod, ModState, ModSystemSet},
+};
+
+/// Decides when a [Mod] that keeps failing is quarantined.
+///
+/// A mod whose systems trap several runs in a row is likely broken, so rather than letting it fail
+/// (and log) every frame, its systems are disabled and it is marked [ModState::Quarantined].
+/// A [ModQuarantined] message is sent, and [Mods::reload](crate::mods::Mods::reload) gives
+/// the mod another chance.
+///
+/// Configure this via [ModRuntimePlugin::failure_policy](crate::plugin::ModRuntimePlugin::failure_policy).
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ModFailurePolicy {
+    /// How many consecutive system runs of a mod may trap before it is quarantined.
+    ///
+    /// Set to [None] to never quarantine mods.
+    pub max_consecutive_traps: Option<u32>,
+}
+
+impl Default for ModFailurePolicy {
+    fn default() -> Self {
+        Self {
+            max_consecutive_traps: Some(3),
+        }
+    }
+}
+
+impl ModFailurePolicy {
+    /// Whether a mod that trapped `traps` times in a row should be quarantined
+    pub fn should_quarantine(&self, traps: u32) -> bool {
+        self.max_consecutive_traps.is_some_and(|max| traps >= max)
+    }
+}
+
+/// Sent when a mod is quarantined, see [ModFailurePolicy]
+#[derive(Message, Debug, Clone, PartialEq, Eq)]
+pub struct ModQuarantined {
+    /// The mod's entity
+    pub mod_id: Entity,
+
+    /// The name of the mod
+    pub mod_name: String,
+
+    /// The error of the last trap
+    pub error: String,
+}
+
+/// Counts the consecutive traps of a mod, shared by all of its systems.
+///
+/// Backends record each run of a mod system here, and queue [ModTrapped] when one fails.
+#[derive(Component, Clone, Default)]
+pub struct ModTraps(Arc<AtomicU32>);
+
+impl ModTraps {
+    /// Returns the trap counter of a mod, inserting one if it has none yet
+    pub fn of(mod_id: Entity, world: &mut World) -> Self {
+        let mut entity = world.entity_mut(mod_id);
+        if let Some(traps) = entity.get::<ModTraps>() {
+            return traps.clone();
+        }
+
+        let traps = ModTraps::default();
+        entity.insert(traps.clone());
+        traps
+    }
+
+    /// Counts a trap, returning the number of consecutive traps
+    pub fn record(&self) -> u32 {
+        self.0.fetch_add(1, Ordering::Relaxed).saturating_add(1)
+    }
+
+    /// Resets the count, after a successful run
+    pub fn reset(&self) {
+        self.0.store(0, Ordering::Relaxed);
+    }
+}
+
+/// A command that reports a trapped system run, quarantining the mod if [ModFailurePolicy] says so
+pub struct ModTrapped {
+    pub mod_id: Entity,
+
+    /// See [ModTraps::record]
+    pub consecutive: u32,
+    pub error: String,
+}
+
+impl Command for ModTrapped {
+    type Out = ();
+
+    fn apply(self, world: &mut World) {
+        let policy = world
+            .get_resource::<ModFailurePolicy>()
+            .copied()
+            .unwrap_or_default();
+        if !policy.should_quarantine(self.consecutive) {
+            return;
+        }
+
+        let Ok(entity) = world.get_entity(self.mod_id) else {
+            return;
+        };
+        // Other systems of the mod might trap before its systems are disabled
+        if entity.get::<ModState>() == Some(&ModState::Quarantined) {
+            return;
+        }
+        let Some(mod_component) = entity.get::<Mod>() else {
+            return;
+        };
+        let accesses: Vec<_> = mod_component.accesses().copied().collect();
+        let mod_name = entity
+            .get::<Name>()
+            .map(|name| name.as_str())
+            .unwrap_or("unknown")
+            .to_string();
+
+        error!(
+            "Mod \"{mod_name}\" trapped {} times in a row and was quarantined, its systems won't run until it is reloaded",
+            self.consecutive
+        );
+
+        for access in accesses {
+            let schedules = access.schedules(world);
+            DisableSystemSet {
+                set: ModSystemSet::Mod(self.mod_id),
+                schedules,
+            }
+            .apply(world);
+        }
+
+        ModState::Quarantined.set(self.mod_id, world);
+        world.write_message(ModQuarantined {
+            mod_id: self.mod_id,
+            mod_name,
+            error: self.error,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quarantines_after_consecutive_traps() {
+        let policy = ModFailurePolicy::default();
+        let traps = ModTraps::default();
+
+        assert!(!policy.should_quarantine(traps.record()));
+        assert!(!policy.should_quarantine(traps.record()));
+        traps.reset();
+        assert!(!policy.should_quarantine(traps.record()));
+        assert!(!policy.should_quarantine(traps.record()));
+        assert!(policy.should_quarantine(traps.record()));
+
+        let never = ModFailurePolicy {
+            max_consecutive_traps: None,
+        };
+        assert!(!never.should_quarantine(u32::MAX));
+    }
+}
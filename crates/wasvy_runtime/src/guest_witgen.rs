@@ -0,0 +1,247 @@
+//! WIT generation for guest-defined components, the reverse of [`crate::witgen`].
+//!
+//! Mods declare the shape of their components with `app.register-component-schema`, see
+//! [`crate::schema`]. The [`GuestWitPlugin`] writes these schemas to a WIT file (and optionally a
+//! JSON file) whenever they change, so other mods and host tooling can generate typed bindings
+//! for components of other mods instead of passing around untyped JSON.
+//!
+//! Each object becomes a WIT record, whose fields use the kebab-case of the JSON fields. Bindings
+//! generators turn them back into the casing of their language, such as snake_case in Rust.
+//!
+//! ```
+//! # use bevy_app::App;
+//! # use wasvy_runtime::guest_witgen::*;
+//! # let mut app = App::new();
+//! app.add_plugins(GuestWitPlugin::new(GuestWitSettings {
+//!     json_path: Some("wit/guest-components.json".into()),
+//!     ..Default::default()
+//! }));
+//! ```
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs,
+    path::{Path, PathBuf},
+};
+
+use bevy_app::{App, Last, Plugin};
+use bevy_ecs::prelude::*;
+use bevy_log::prelude::*;
+
+use crate::{
+    schema::{ComponentSchema, WasmComponentSchemas},
+    witgen::{to_wit_ident, type_path_to_name},
+};
+
+/// Settings controlling how the schemas of guest components are written.
+#[derive(Resource, Clone, Debug)]
+pub struct GuestWitSettings {
+    /// WIT package namespace
+    ///
+    /// Defaults to "my-namespace:mods"
+    pub package: String,
+
+    /// Interface name that contains the records of the components.
+    ///
+    /// Defaults to "guest-components"
+    pub interface: String,
+
+    /// File path where the generated WIT is written.
+    ///
+    /// Defaults to "wit/guest-components.wit"
+    pub wit_path: PathBuf,
+
+    /// File path where the schemas are written as JSON, keyed by type path. Not written by default.
+    pub json_path: Option<PathBuf>,
+}
+
+impl Default for GuestWitSettings {
+    fn default() -> Self {
+        Self {
+            package: "my-namespace:mods".to_string(),
+            interface: "guest-components".to_string(),
+            wit_path: PathBuf::from("wit/guest-components.wit"),
+            json_path: None,
+        }
+    }
+}
+
+/// Plugin that writes the schemas of guest components to disk whenever mods register them.
+#[derive(Default)]
+pub struct GuestWitPlugin {
+    settings: GuestWitSettings,
+}
+
+impl GuestWitPlugin {
+    /// Create a plugin with the provided settings.
+    pub fn new(settings: GuestWitSettings) -> Self {
+        Self { settings }
+    }
+}
+
+impl Plugin for GuestWitPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(self.settings.clone())
+            .init_resource::<WasmComponentSchemas>()
+            .add_systems(
+                Last,
+                write_guest_wit.run_if(resource_changed::<WasmComponentSchemas>),
+            );
+    }
+}
+
+fn write_guest_wit(settings: Res<GuestWitSettings>, schemas: Res<WasmComponentSchemas>) {
+    let schemas: BTreeMap<&str, &ComponentSchema> = schemas.iter().collect();
+    if schemas.is_empty() {
+        return;
+    }
+
+    write(&settings.wit_path, generate_guest_wit(&settings, &schemas));
+    if let Some(json_path) = &settings.json_path {
+        match serde_json::to_string_pretty(&schemas) {
+            Ok(json) => write(json_path, json),
+            Err(err) => error!("Failed to serialize the schemas of guest components: {err}"),
+        }
+    }
+}
+
+fn write(path: &Path, contents: String) {
+    if let Some(parent) = path.parent()
+        && let Err(err) = fs::create_dir_all(parent)
+    {
+        error!("Failed to create the directory of {path:?}: {err}");
+        return;
+    }
+    match fs::write(path, contents) {
+        Ok(()) => info!("Wrote the schemas of guest components to {path:?}"),
+        Err(err) => error!("Failed to write {path:?}: {err}"),
+    }
+}
+
+/// Build a WIT document with a type for each guest component, by type path.
+pub fn generate_guest_wit(
+    settings: &GuestWitSettings,
+    schemas: &BTreeMap<&str, &ComponentSchema>,
+) -> String {
+    let mut used_names = BTreeSet::new();
+    let mut types = Vec::new();
+    for (type_path, schema) in schemas {
+        let name = to_wit_ident(&type_path_to_name(type_path), &mut used_names);
+        let mut docs = format!("  /// wasvy:type-path={type_path}\n");
+        render_type(&name, schema, &mut docs, &mut types, &mut used_names);
+    }
+
+    let mut out = String::new();
+    out.push_str("/// These bindings are automatically generated by wasvy from the schemas of guest components\n");
+    out.push_str(&format!("package {};\n\n", settings.package));
+    out.push_str(&format!("interface {} {{\n", settings.interface));
+    out.push_str(&types.join("\n"));
+    out.push_str("}\n");
+    out
+}
+
+/// Renders a named type for `schema`, after the records it refers to
+///
+/// `docs` are prepended to the type, and `types` collects every rendered type.
+fn render_type(
+    name: &str,
+    schema: &ComponentSchema,
+    docs: &mut String,
+    types: &mut Vec<String>,
+    used_names: &mut BTreeSet<String>,
+) {
+    let ComponentSchema::Object(fields) = schema else {
+        let ty = map_schema(name, schema, types, used_names);
+        types.push(format!("{docs}  type {name} = {ty};\n"));
+        return;
+    };
+    // WIT records need at least one field
+    if fields.is_empty() {
+        docs.push_str("  /// A JSON object\n");
+        types.push(format!("{docs}  type {name} = string;\n"));
+        return;
+    }
+
+    let mut field_names = BTreeSet::new();
+    let mut out = format!("{docs}  record {name} {{\n");
+    for (field, schema) in fields {
+        let field_name = to_wit_ident(field, &mut field_names);
+        let ty = map_schema(&format!("{name}-{field_name}"), schema, types, used_names);
+        out.push_str(&format!("    {field_name}: {ty},\n"));
+    }
+    out.push_str("  }\n");
+    types.push(out);
+}
+
+/// The WIT type of a schema, rendering a record named after `name` for nested objects
+fn map_schema(
+    name: &str,
+    schema: &ComponentSchema,
+    types: &mut Vec<String>,
+    used_names: &mut BTreeSet<String>,
+) -> String {
+    match schema {
+        ComponentSchema::Bool => "bool".to_string(),
+        ComponentSchema::Number => "f64".to_string(),
+        // Any values are passed as JSON
+        ComponentSchema::String | ComponentSchema::Any => "string".to_string(),
+        ComponentSchema::Optional(inner) => {
+            format!("option<{}>", map_schema(name, inner, types, used_names))
+        }
+        ComponentSchema::List(inner) => {
+            format!("list<{}>", map_schema(name, inner, types, used_names))
+        }
+        ComponentSchema::Object(_) => {
+            let name = to_wit_ident(name, used_names);
+            render_type(&name, schema, &mut String::new(), types, used_names);
+            name
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn generates_guest_wit() {
+        let inventory = ComponentSchema::from_json(
+            r#"{"object": [
+                ["gold", "number"],
+                ["items", {"list": {"object": [["name", "string"], ["count", "number"]]}}],
+                ["equipped", {"optional": "string"}]
+            ]}"#,
+        )
+        .unwrap();
+        let level = ComponentSchema::Number;
+        let schemas = BTreeMap::from([
+            ("my_mod::Inventory", &inventory),
+            ("my_mod::PlayerLevel", &level),
+        ]);
+
+        let output = generate_guest_wit(&GuestWitSettings::default(), &schemas);
+        assert_eq!(
+            output,
+            "/// These bindings are automatically generated by wasvy from the schemas of guest components
+package my-namespace:mods;
+
+interface guest-components {
+  record inventory-items {
+    name: string,
+    count: f64,
+  }
+
+  /// wasvy:type-path=my_mod::Inventory
+  record inventory {
+    gold: f64,
+    items: list<inventory-items>,
+    equipped: option<string>,
+  }
+
+  /// wasvy:type-path=my_mod::PlayerLevel
+  type player-level = f64;
+}
+"
+        );
+    }
+}
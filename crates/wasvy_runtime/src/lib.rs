@@ -1,16 +1,37 @@
 pub mod access;
 pub mod app_extend;
 pub mod asset;
+pub mod asset_root;
 pub mod authoring;
+pub mod budget;
+pub mod callbacks;
 pub mod cleanup;
 pub mod component;
+pub(crate) mod dependencies;
 pub mod devtools;
+pub mod discovery;
+pub mod entry_points;
+pub mod failure;
+#[cfg(feature = "serde_json")]
+pub mod guest_witgen;
+pub mod limits;
+pub mod manifest;
+pub mod messages;
 pub mod methods;
 pub mod mods;
+pub mod package;
+pub mod permissions;
 pub mod plugin;
 pub mod prelude;
+pub mod registry;
 pub mod sandbox;
+pub mod save;
 pub mod schedule;
+#[cfg(feature = "serde_json")]
+pub mod schema;
 pub mod serialize;
 pub(crate) mod setup;
+pub mod storage;
+pub mod sync;
+pub mod verify;
 pub mod witgen;
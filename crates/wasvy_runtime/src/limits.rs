@@ -0,0 +1,192 @@
+use std::fmt;
+
+use bevy_ecs::prelude::*;
+
+/// Bounds what a single mod may register during setup.
+///
+/// Without limits, a hostile (or buggy) mod could register millions of systems or queries and
+/// explode schedule build times. The defaults are generous enough that well-behaved mods never
+/// reach them.
+///
+/// Configure these via [ModRuntimePlugin::limits](crate::plugin::ModRuntimePlugin::limits).
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ModLimits {
+    /// The most systems a mod may add during a single setup
+    pub max_systems_per_mod: usize,
+
+    /// The most query params a single mod system may have
+    pub max_queries_per_system: usize,
+
+    /// The most items (components and filters) a single query may have
+    pub max_query_items: usize,
+}
+
+impl Default for ModLimits {
+    fn default() -> Self {
+        Self {
+            max_systems_per_mod: 1024,
+            max_queries_per_system: 32,
+            // Components are indexed by a u8 in the wit interface
+            max_query_items: u8::MAX as usize,
+        }
+    }
+}
+
+impl ModLimits {
+    /// Checks that a mod adding `count` systems stays within [Self::max_systems_per_mod]
+    pub fn check_systems(&self, count: usize) -> Result<(), LimitExceeded> {
+        if count > self.max_systems_per_mod {
+            return Err(LimitExceeded::Systems {
+                limit: self.max_systems_per_mod,
+            });
+        }
+        Ok(())
+    }
+
+    /// Checks that a system with `queries` query params, the last of which has `items` items, stays within limits
+    pub fn check_query(
+        &self,
+        system: &str,
+        queries: usize,
+        items: usize,
+    ) -> Result<(), LimitExceeded> {
+        if queries > self.max_queries_per_system {
+            return Err(LimitExceeded::Queries {
+                system: system.to_string(),
+                limit: self.max_queries_per_system,
+            });
+        }
+        if items > self.max_query_items {
+            return Err(LimitExceeded::QueryItems {
+                system: system.to_string(),
+                limit: self.max_query_items,
+            });
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for ModLimits {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} systems per mod, {} queries per system, {} items per query",
+            self.max_systems_per_mod, self.max_queries_per_system, self.max_query_items
+        )
+    }
+}
+
+/// Bounds the resources a single mod instance may allocate, so one mod can't exhaust host memory.
+///
+/// Set the defaults for all mods via `ModLoaderPlugin::memory_limits`, and override them for a
+/// single mod by inserting this component on its entity. Limits left as `None` are not enforced
+/// beyond the runtime's own defaults.
+///
+/// When a mod reaches a limit, growing its memory fails as it would when the system is out of
+/// memory, which usually makes the mod trap.
+///
+/// Note: Limits are read when the mod is set up, so changes apply the next time the mod is
+/// reloaded.
+#[derive(Component, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ModMemoryLimits {
+    /// The most bytes a single linear memory may grow to
+    pub max_memory: Option<usize>,
+
+    /// The most tables an instance may create
+    pub max_tables: Option<usize>,
+
+    /// The most instances a mod may create. A single mod is made of several core instances.
+    pub max_instances: Option<usize>,
+}
+
+impl ModMemoryLimits {
+    /// Sets [Self::max_memory], in bytes
+    pub fn max_memory(mut self, bytes: usize) -> Self {
+        self.max_memory = Some(bytes);
+        self
+    }
+
+    /// Sets [Self::max_tables]
+    pub fn max_tables(mut self, tables: usize) -> Self {
+        self.max_tables = Some(tables);
+        self
+    }
+
+    /// Sets [Self::max_instances]
+    pub fn max_instances(mut self, instances: usize) -> Self {
+        self.max_instances = Some(instances);
+        self
+    }
+}
+
+/// The error returned to a mod when it exceeds one of the [ModLimits]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LimitExceeded {
+    /// The mod added more than [ModLimits::max_systems_per_mod] systems
+    Systems { limit: usize },
+
+    /// A system has more than [ModLimits::max_queries_per_system] queries
+    Queries { system: String, limit: usize },
+
+    /// A query has more than [ModLimits::max_query_items] items
+    QueryItems { system: String, limit: usize },
+}
+
+impl std::error::Error for LimitExceeded {}
+
+impl fmt::Display for LimitExceeded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Systems { limit } => {
+                write!(f, "Mod exceeded the limit of {limit} systems")
+            }
+            Self::Queries { system, limit } => {
+                write!(
+                    f,
+                    "System \"{system}\" exceeded the limit of {limit} queries"
+                )
+            }
+            Self::QueryItems { system, limit } => {
+                write!(
+                    f,
+                    "A query of system \"{system}\" exceeded the limit of {limit} items"
+                )
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reports_exceeded_limit() {
+        let limits = ModLimits {
+            max_systems_per_mod: 2,
+            max_queries_per_system: 1,
+            max_query_items: 3,
+        };
+
+        assert_eq!(limits.check_systems(2), Ok(()));
+        assert_eq!(
+            limits.check_systems(3),
+            Err(LimitExceeded::Systems { limit: 2 })
+        );
+        assert_eq!(limits.check_query("spin", 1, 3), Ok(()));
+        assert_eq!(
+            limits.check_query("spin", 2, 1),
+            Err(LimitExceeded::Queries {
+                system: "spin".into(),
+                limit: 1
+            })
+        );
+        assert_eq!(
+            limits.check_query("spin", 1, 4),
+            Err(LimitExceeded::QueryItems {
+                system: "spin".into(),
+                limit: 3
+            })
+        );
+    }
+}
@@ -0,0 +1,359 @@
+use std::{collections::BTreeMap, fmt};
+
+use anyhow::{Context, Result, bail};
+use bevy_ecs::prelude::*;
+use semver::{Version, VersionReq};
+use serde::Deserialize;
+
+/// The name of the wasm custom section a mod may embed its [ModManifest] in, as TOML
+pub const MANIFEST_SECTION: &str = "wasvy-manifest";
+
+/// Metadata a mod declares about itself.
+///
+/// Mods ship a manifest either embedded in their wasm file (see [MANIFEST_SECTION]) or as a
+/// side-car TOML file next to it, such as `my_mod.toml` for `my_mod.wasm`. The side-car takes
+/// priority, so a manifest can be tweaked without rebuilding the mod. Packages hold theirs in
+/// `mod.toml`, see [ModPackage](crate::package::ModPackage).
+///
+/// ```toml
+/// id = "my-mod"
+/// version = "1.2.0"
+/// author = "Jane Doe"
+///
+/// [dependencies]
+/// core-mod = "^1.0"
+/// ```
+///
+/// The manifest is inserted on the mod's entity once its asset has loaded.
+#[derive(Component, Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct ModManifest {
+    /// A unique id, which other mods refer to in their dependencies
+    pub id: String,
+
+    /// The version of the mod
+    pub version: Version,
+
+    /// Who made the mod
+    #[serde(default)]
+    pub author: Option<String>,
+
+    /// The ids of the mods this mod depends on, and which of their versions it supports.
+    ///
+    /// A mod is only set up once its dependencies are, and its systems run after theirs.
+    /// If a dependency is missing, has an unsupported version or is cyclic, the mod fails to load.
+    #[serde(default)]
+    pub dependencies: BTreeMap<String, VersionReq>,
+
+    /// The publisher's signature of the mod, checked by the [ModVerifier](crate::verify::ModVerifier)
+    /// if the host set one
+    #[serde(default)]
+    pub signature: Option<String>,
+}
+
+impl ModManifest {
+    /// Parses a manifest from TOML
+    pub fn from_toml(source: &str) -> Result<Self> {
+        toml::from_str(source).context("Invalid mod manifest")
+    }
+
+    /// Reads the manifest embedded in a wasm module or component, if it has one
+    pub fn from_wasm(bytes: &[u8]) -> Result<Option<Self>> {
+        let Some(section) = custom_section(bytes, MANIFEST_SECTION, 0)? else {
+            return Ok(None);
+        };
+        let source = std::str::from_utf8(section).context("Mod manifest is not valid UTF-8")?;
+        Self::from_toml(source).map(Some)
+    }
+
+    /// Whether this mod's version satisfies `requirement`
+    pub fn satisfies(&self, requirement: &VersionReq) -> bool {
+        requirement.matches(&self.version)
+    }
+}
+
+/// Metadata a mod declares about itself in code, by exporting an `info` function.
+///
+/// Unlike a [ModManifest] this needs no side-car file or custom section. The backend reads it right
+/// before the mod is set up, and inserts it on the mod's entity. Mods without a manifest are then
+/// known by this name instead of their file name, in logs and when other mods depend on them.
+#[derive(Component, Debug, Clone, PartialEq, Eq)]
+pub struct ModDetails {
+    /// The name of the mod, which other mods refer to in their dependencies
+    pub name: String,
+
+    /// The version of the mod
+    pub version: Version,
+
+    /// The version of the `wasvy:ecs` interface the mod requires, if it declared one
+    pub wasvy_version: Option<String>,
+
+    /// What the mod does
+    pub description: Option<String>,
+}
+
+impl ModDetails {
+    /// A manifest of a mod that only declared its details, so it has no dependencies
+    pub fn to_manifest(&self) -> ModManifest {
+        ModManifest {
+            id: self.name.clone(),
+            version: self.version.clone(),
+            author: None,
+            dependencies: BTreeMap::new(),
+            signature: None,
+        }
+    }
+}
+
+impl fmt::Display for ModDetails {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.name, self.version)
+    }
+}
+
+/// The id of sections holding a nested core module in a component
+const CORE_MODULE_SECTION: u8 = 1;
+
+/// The id of sections holding a nested component in a component
+const COMPONENT_SECTION: u8 = 4;
+
+/// How deep components may nest, so crafted files can't overflow the stack
+const MAX_NESTING: usize = 32;
+
+/// Finds a custom section of a wasm module or component.
+///
+/// Composed components (such as those made with `wac`) nest the components they were built from,
+/// so nested components and modules are searched too, after the top-level sections.
+fn custom_section<'a>(bytes: &'a [u8], name: &str, depth: usize) -> Result<Option<&'a [u8]>> {
+    if depth > MAX_NESTING {
+        bail!("Wasm file nests more than {MAX_NESTING} components");
+    }
+    let sections = sections(bytes)?;
+    if let Some(section) = sections
+        .iter()
+        .find(|section| section.custom_name == Some(name.as_bytes()))
+    {
+        return Ok(Some(section.contents));
+    }
+
+    // In core modules these ids are other sections
+    if !is_component(bytes) {
+        return Ok(None);
+    }
+    for section in sections {
+        if matches!(section.id, CORE_MODULE_SECTION | COMPONENT_SECTION)
+            && let Some(contents) = custom_section(section.contents, name, depth + 1)?
+        {
+            return Ok(Some(contents));
+        }
+    }
+
+    Ok(None)
+}
+
+/// Whether a wasm file is a component rather than a core module, from its layer
+fn is_component(bytes: &[u8]) -> bool {
+    bytes.get(6..8) == Some(&[1, 0])
+}
+
+/// Copies a wasm module or component without its [embedded manifest](MANIFEST_SECTION).
+///
+/// This is what mods are signed over, since their manifest holds the signature. See
+/// [ModVerifier](crate::verify::ModVerifier). Manifests are stripped from nested components and
+/// modules too, the same ones [ModManifest::from_wasm] searches.
+pub fn strip_manifest(bytes: &[u8]) -> Result<Vec<u8>> {
+    strip_manifest_at(bytes, 0)
+}
+
+fn strip_manifest_at(bytes: &[u8], depth: usize) -> Result<Vec<u8>> {
+    if depth > MAX_NESTING {
+        bail!("Wasm file nests more than {MAX_NESTING} components");
+    }
+    let sections = sections(bytes)?;
+    let nested = is_component(bytes);
+    let Some(preamble) = bytes.get(..8) else {
+        bail!("Not a wasm file");
+    };
+    let mut stripped = preamble.to_vec();
+    for section in sections {
+        if section.custom_name == Some(MANIFEST_SECTION.as_bytes()) {
+            continue;
+        }
+        if !nested || !matches!(section.id, CORE_MODULE_SECTION | COMPONENT_SECTION) {
+            stripped.extend_from_slice(section.raw);
+            continue;
+        }
+
+        // Sections without a manifest are kept as they are, even if their size could be shorter
+        let contents = strip_manifest_at(section.contents, depth + 1)?;
+        if contents == section.contents {
+            stripped.extend_from_slice(section.raw);
+            continue;
+        }
+        stripped.push(section.id);
+        write_u32(contents.len() as u32, &mut stripped);
+        stripped.extend_from_slice(&contents);
+    }
+    Ok(stripped)
+}
+
+struct Section<'a> {
+    id: u8,
+    /// The whole section, including its id and size
+    raw: &'a [u8],
+    /// The name of custom sections
+    custom_name: Option<&'a [u8]>,
+    /// The contents of the section, after the name of custom sections
+    contents: &'a [u8],
+}
+
+/// Splits a wasm module or component into its top-level sections
+fn sections(bytes: &[u8]) -> Result<Vec<Section<'_>>> {
+    // Modules and components share the same preamble length and section layout
+    let Some(mut rest) = bytes.strip_prefix(b"\0asm").and_then(|rest| rest.get(4..)) else {
+        bail!("Not a wasm file");
+    };
+
+    let mut sections = Vec::new();
+    while let Some((&id, after_id)) = rest.split_first() {
+        let (size, after_size) = read_u32(after_id)?;
+        let size = size as usize;
+        if after_size.len() < size {
+            bail!("Wasm section is out of bounds");
+        }
+        let (contents, after_section) = after_size.split_at(size);
+        let raw = &rest[..rest.len() - after_section.len()];
+        rest = after_section;
+
+        if id != 0 {
+            sections.push(Section {
+                id,
+                raw,
+                custom_name: None,
+                contents,
+            });
+            continue;
+        }
+        let (name_len, after_len) = read_u32(contents)?;
+        let Some(custom_name) = after_len.get(..name_len as usize) else {
+            bail!("Wasm custom section name is out of bounds");
+        };
+        sections.push(Section {
+            id,
+            raw,
+            custom_name: Some(custom_name),
+            contents: &after_len[name_len as usize..],
+        });
+    }
+
+    Ok(sections)
+}
+
+/// Reads an unsigned LEB128 integer
+fn read_u32(bytes: &[u8]) -> Result<(u32, &[u8])> {
+    let mut result = 0u32;
+    for (i, byte) in bytes.iter().enumerate().take(5) {
+        result |= u32::from(byte & 0x7f) << (i * 7);
+        if byte & 0x80 == 0 {
+            return Ok((result, &bytes[i + 1..]));
+        }
+    }
+    bail!("Invalid LEB128 integer in wasm file")
+}
+
+/// Writes an unsigned LEB128 integer
+fn write_u32(mut value: u32, out: &mut Vec<u8>) {
+    loop {
+        let byte = (value & 0x7f) as u8;
+        value >>= 7;
+        if value == 0 {
+            out.push(byte);
+            return;
+        }
+        out.push(byte | 0x80);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MANIFEST: &str = r#"
+        id = "my-mod"
+        version = "1.2.0"
+
+        [dependencies]
+        core-mod = "^1.0"
+    "#;
+
+    /// Appends a section to a wasm file
+    fn push_section(wasm: &mut Vec<u8>, id: u8, contents: &[u8]) {
+        wasm.push(id);
+        write_u32(contents.len() as u32, wasm);
+        wasm.extend_from_slice(contents);
+    }
+
+    /// An empty module with a custom section holding the manifest
+    fn module_with_manifest() -> Vec<u8> {
+        let mut section = Vec::new();
+        write_u32(MANIFEST_SECTION.len() as u32, &mut section);
+        section.extend_from_slice(MANIFEST_SECTION.as_bytes());
+        section.extend_from_slice(MANIFEST.as_bytes());
+        let mut wasm = b"\0asm\x01\0\0\0".to_vec();
+        push_section(&mut wasm, 0, &section);
+        wasm
+    }
+
+    #[test]
+    fn reads_embedded_manifest() {
+        let wasm = module_with_manifest();
+
+        let manifest = ModManifest::from_wasm(&wasm).unwrap().unwrap();
+        assert_eq!(manifest.id, "my-mod");
+        assert_eq!(manifest.version, Version::new(1, 2, 0));
+        assert_eq!(manifest.author, None);
+        assert!(manifest.dependencies["core-mod"].matches(&Version::new(1, 4, 0)));
+        assert_eq!(ModManifest::from_wasm(b"\0asm\x01\0\0\0").unwrap(), None);
+        assert_eq!(strip_manifest(&wasm).unwrap(), b"\0asm\x01\0\0\0");
+        assert!(ModManifest::from_wasm(b"not wasm").is_err());
+    }
+
+    #[test]
+    fn reads_manifest_of_composed_components() {
+        // A component nesting a component that nests the module
+        let mut inner = b"\0asm\x0d\0\x01\0".to_vec();
+        push_section(&mut inner, CORE_MODULE_SECTION, &module_with_manifest());
+        let mut composed = b"\0asm\x0d\0\x01\0".to_vec();
+        push_section(&mut composed, COMPONENT_SECTION, &inner);
+
+        let manifest = ModManifest::from_wasm(&composed).unwrap().unwrap();
+        assert_eq!(manifest.id, "my-mod");
+
+        // Stripping it leaves the nesting, with an empty module
+        let mut stripped_inner = b"\0asm\x0d\0\x01\0".to_vec();
+        push_section(&mut stripped_inner, CORE_MODULE_SECTION, b"\0asm\x01\0\0\0");
+        let mut stripped = b"\0asm\x0d\0\x01\0".to_vec();
+        push_section(&mut stripped, COMPONENT_SECTION, &stripped_inner);
+        assert_eq!(strip_manifest(&composed).unwrap(), stripped);
+        assert_eq!(ModManifest::from_wasm(&stripped).unwrap(), None);
+    }
+
+    #[test]
+    fn rejects_truncated_and_deeply_nested_files() {
+        assert!(strip_manifest(b"\0asm").is_err());
+        assert!(ModManifest::from_wasm(b"\0asm").is_err());
+
+        let mut truncated = b"\0asm\x0d\0\x01\0".to_vec();
+        push_section(&mut truncated, COMPONENT_SECTION, b"\0asm");
+        assert!(strip_manifest(&truncated).is_err());
+        assert!(ModManifest::from_wasm(&truncated).is_err());
+
+        let mut nested = module_with_manifest();
+        for _ in 0..=MAX_NESTING {
+            let mut component = b"\0asm\x0d\0\x01\0".to_vec();
+            push_section(&mut component, COMPONENT_SECTION, &nested);
+            nested = component;
+        }
+        assert!(strip_manifest(&nested).is_err());
+        assert!(ModManifest::from_wasm(&nested).is_err());
+    }
+}
@@ -0,0 +1,231 @@
+//! Reflection of [Message]s so that mods can read and send them.
+//!
+//! Bevy's [ReflectMessage] can only write messages, so mods rely on [ReflectModMessage] to read
+//! them. It also serves as the opt-in: mods may only read and send messages that have it.
+//! Register it for any message mods should see:
+//!
+//! ```
+//! # use bevy_app::prelude::*;
+//! # use bevy_ecs::prelude::*;
+//! # use bevy_reflect::Reflect;
+//! # use wasvy_runtime::messages::AppModMessageExt;
+//! #[derive(Message, Reflect)]
+//! struct ScoreChanged {
+//!     points: u32,
+//! }
+//!
+//! App::new().add_mod_message::<ScoreChanged>();
+//! ```
+
+use std::any::Any;
+
+use anyhow::{Result, anyhow};
+use bevy_app::App;
+use bevy_ecs::{
+    component::ComponentId,
+    message::{Message, MessageCursor, Messages},
+    prelude::*,
+    ptr::Ptr,
+    reflect::ReflectMessage,
+};
+use bevy_reflect::{FromType, GetTypeRegistration, Reflect, TypePath};
+
+use crate::serialize::CodecResource;
+
+/// Type data that allows mods to read a [Message] whose type is only known at runtime.
+///
+/// Add it with `#[reflect(ModMessage)]` or via [AppModMessageExt::add_mod_message].
+#[derive(Clone)]
+pub struct ReflectModMessage {
+    messages_id: fn(&World) -> Option<ComponentId>,
+    new_cursor: fn() -> ModMessageCursor,
+    read: ReadMessages,
+}
+
+type ReadMessages = unsafe fn(Ptr<'_>, &mut ModMessageCursor, &mut dyn FnMut(&dyn Reflect));
+
+impl ReflectModMessage {
+    /// The id of the [Messages] resource holding these messages, if it exists
+    pub fn messages_id(&self, world: &World) -> Option<ComponentId> {
+        (self.messages_id)(world)
+    }
+
+    /// Creates a cursor that has not read any messages yet
+    pub fn new_cursor(&self) -> ModMessageCursor {
+        (self.new_cursor)()
+    }
+
+    /// Calls `f` with every message that `cursor` has not seen yet, and advances it.
+    ///
+    /// # Safety
+    ///
+    /// - `messages` must point to the resource identified by [Self::messages_id]
+    /// - `cursor` must have been created by [Self::new_cursor] of this same type data
+    pub unsafe fn read(
+        &self,
+        messages: Ptr<'_>,
+        cursor: &mut ModMessageCursor,
+        f: &mut dyn FnMut(&dyn Reflect),
+    ) {
+        // SAFETY: guaranteed by the caller
+        unsafe { (self.read)(messages, cursor, f) }
+    }
+}
+
+impl<M: Message + Reflect> FromType<M> for ReflectModMessage {
+    fn from_type() -> Self {
+        Self {
+            messages_id: |world| world.component_id::<Messages<M>>(),
+            new_cursor: || ModMessageCursor(Box::new(MessageCursor::<M>::default())),
+            read: |messages, cursor, f| {
+                // SAFETY: guaranteed by the caller of ReflectModMessage::read
+                let messages = unsafe { messages.deref::<Messages<M>>() };
+                let cursor = cursor
+                    .0
+                    .downcast_mut::<MessageCursor<M>>()
+                    .expect("cursor to be created for the same message type");
+                for message in cursor.read(messages) {
+                    f(message);
+                }
+            },
+        }
+    }
+}
+
+/// A type-erased [MessageCursor], tracking which messages a mod system has already read.
+pub struct ModMessageCursor(Box<dyn Any + Send + Sync>);
+
+/// Sends a message from its serialized value, as requested by a mod.
+///
+/// The message is written when commands are applied.
+pub fn send_message(
+    commands: &mut Commands,
+    type_registry: &AppTypeRegistry,
+    codec: &CodecResource,
+    type_path: &str,
+    serialized_value: &[u8],
+) -> Result<()> {
+    let registry = type_registry.read();
+    let type_registration = registry
+        .get_with_type_path(type_path)
+        .filter(|registration| registration.contains::<ReflectModMessage>())
+        .ok_or_else(|| {
+            anyhow!("{type_path} is not an event that mods can send. See AppModMessageExt::add_mod_message")
+        })?;
+    let reflect_message = type_registration
+        .data::<ReflectMessage>()
+        .ok_or_else(|| anyhow!("{type_path} does not reflect Message"))?
+        .clone();
+    let value = codec.decode_reflect(serialized_value, type_registration, &registry)?;
+
+    let type_registry = type_registry.clone();
+    commands.queue(move |world: &mut World| {
+        reflect_message.write_message(world, value.as_partial_reflect(), &type_registry.read());
+    });
+
+    Ok(())
+}
+
+pub trait AppModMessageExt {
+    /// Adds the message `M` to the app, and allows mods to read and send it
+    fn add_mod_message<M>(&mut self) -> &mut Self
+    where
+        M: Message + Reflect + TypePath + GetTypeRegistration;
+}
+
+impl AppModMessageExt for App {
+    fn add_mod_message<M>(&mut self) -> &mut Self
+    where
+        M: Message + Reflect + TypePath + GetTypeRegistration,
+    {
+        self.add_message::<M>()
+            .register_type::<M>()
+            .register_type_data::<M, ReflectModMessage>()
+            .register_type_data::<M, ReflectMessage>()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy_ecs::reflect::AppTypeRegistry;
+    use bevy_reflect::TypePath;
+
+    use super::*;
+
+    #[derive(Message, Reflect, Debug, PartialEq)]
+    struct Scored {
+        points: u32,
+    }
+
+    #[test]
+    fn reads_each_message_once() {
+        let mut app = App::new();
+        app.add_mod_message::<Scored>();
+
+        let reflect = app
+            .world()
+            .resource::<AppTypeRegistry>()
+            .read()
+            .get_type_data::<ReflectModMessage>(std::any::TypeId::of::<Scored>())
+            .expect("type data to be registered")
+            .clone();
+        let messages_id = reflect.messages_id(app.world()).unwrap();
+        let mut cursor = reflect.new_cursor();
+
+        let read = |world: &World, cursor: &mut ModMessageCursor| {
+            let messages = world.get_resource_by_id(messages_id).unwrap();
+            let mut read = Vec::new();
+            // SAFETY: messages_id and the cursor come from the same type data
+            unsafe {
+                reflect.read(messages, cursor, &mut |message| {
+                    read.push(message.downcast_ref::<Scored>().unwrap().points);
+                });
+            }
+            read
+        };
+
+        app.world_mut().write_message(Scored { points: 1 });
+        app.world_mut().write_message(Scored { points: 2 });
+        assert_eq!(read(app.world(), &mut cursor), vec![1, 2]);
+
+        app.world_mut().write_message(Scored { points: 3 });
+        assert_eq!(read(app.world(), &mut cursor), vec![3]);
+        assert_eq!(read(app.world(), &mut cursor), Vec::<u32>::new());
+    }
+
+    #[cfg(feature = "serde_json")]
+    #[test]
+    fn sends_serialized_messages() {
+        let mut app = App::new();
+        app.add_mod_message::<Scored>();
+
+        let world = app.world_mut();
+        let type_registry = world.resource::<AppTypeRegistry>().clone();
+        let codec = CodecResource::default();
+        let mut commands = world.commands();
+        send_message(
+            &mut commands,
+            &type_registry,
+            &codec,
+            Scored::type_path(),
+            br#"{"points":7}"#,
+        )
+        .unwrap();
+        assert!(
+            send_message(
+                &mut commands,
+                &type_registry,
+                &codec,
+                "alloc::string::String",
+                br#""hi""#,
+            )
+            .is_err()
+        );
+        world.flush();
+
+        let messages = world.resource::<Messages<Scored>>();
+        let mut cursor = messages.get_cursor();
+        let sent: Vec<_> = cursor.read(messages).collect();
+        assert_eq!(sent, vec![&Scored { points: 7 }]);
+    }
+}
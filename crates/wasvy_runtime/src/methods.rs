@@ -6,20 +6,46 @@
 //!
 //! Argument names are sourced from `#[wasvy::methods]` metadata when available,
 //! and fall back to `argN` otherwise.
+//!
+//! Reflected structs in signatures are WIT records, which the JSON codec passes as objects keyed
+//! by field name, whatever their serde representation is (`Vec3` is `{"x":0,"y":0,"z":0}`).
+//! Reflected enums are WIT enums or variants: unit variants are passed as their name (`"Idle"`),
+//! others as an object with the variant as the only key (`{"Running":{"speed":1.0}}`).
+//!
+//! Free functions exported with `#[wasvy::host_fn]` are indexed by name, see
+//! [`FunctionIndex::invoke_host_fn`].
+//!
+//! Methods returning a `Result` return WIT results, `{"Ok":..}` or `{"Err":..}`, so errors reach
+//! the guest as values instead of trapping it.
 
 use std::collections::{BTreeMap, BTreeSet};
 
+#[cfg(feature = "serde_json")]
+use anyhow::Context;
+#[cfg(feature = "serde_json")]
+use anyhow::anyhow;
 use anyhow::{Result, bail};
 use bevy_ecs::prelude::Resource;
+#[cfg(feature = "serde_json")]
+use bevy_ecs::prelude::{Entity, Mut, ReflectComponent, World};
 use bevy_ecs::reflect::{AppFunctionRegistry, AppTypeRegistry};
 use bevy_platform::collections::HashMap;
+#[cfg(feature = "serde_json")]
 use bevy_reflect::{
-    Reflect,
+    NamedField, TypeInfo,
+    enums::{DynamicEnum, DynamicVariant, VariantInfo, VariantType},
+    structs::DynamicStruct,
+    tuple::DynamicTuple,
+};
+use bevy_reflect::{
+    PartialReflect, Reflect, ReflectFromReflect, ReflectMut, ReflectRef, TypeRegistry,
     func::args::Ownership,
     func::{ArgList, DynamicFunction},
 };
 
-use crate::authoring::{WasvyExport, WasvyMethodMetadata, inventory};
+use crate::authoring::{
+    ExportLevel, ExternalMethods, WasvyExport, WasvyHostFnMetadata, WasvyMethodMetadata, inventory,
+};
 use crate::serialize::CodecResource;
 
 /// Required access for a registered function.
@@ -27,6 +53,11 @@ use crate::serialize::CodecResource;
 pub enum FunctionAccess {
     Read,
     Write,
+    /// An associated function without a `self` receiver, invoked without a component.
+    ///
+    /// See [`FunctionIndex::invoke_static`]. Host functions are static as well, see
+    /// [`FunctionIndex::invoke_host_fn`].
+    Static,
 }
 
 /// One argument in a reflected function signature.
@@ -46,6 +77,8 @@ pub struct FunctionEntry {
     pub access: FunctionAccess,
     pub args: Vec<FunctionArg>,
     pub ret: String,
+    /// The doc comment of the method, if it was exported with `#[wasvy::methods]`
+    pub docs: Option<String>,
     pub function: DynamicFunction<'static>,
 }
 
@@ -64,6 +97,13 @@ pub struct FunctionIndex {
     entries: HashMap<FunctionKey, FunctionEntry>,
     methods_by_component: BTreeMap<String, Vec<FunctionKey>>,
     components: BTreeSet<String>,
+    /// The exported types that are [`WasvyResource`](crate::authoring::WasvyResource)s
+    resources: BTreeSet<String>,
+    /// Free functions exported with `#[wasvy::host_fn]`, by name
+    host_fns: BTreeMap<String, FunctionEntry>,
+    /// The least visible [`ExportLevel`] in the index
+    export_level: ExportLevel,
+    strict_params: bool,
 }
 
 /// Target used when invoking a method.
@@ -82,12 +122,33 @@ impl FunctionIndex {
     /// let index = FunctionIndex::build(&type_registry, &function_registry);
     /// ```
     pub fn build(type_registry: &AppTypeRegistry, function_registry: &AppFunctionRegistry) -> Self {
+        Self::build_with_level(type_registry, function_registry, ExportLevel::ModdingApi)
+    }
+
+    /// Build an index that includes components exported up to (and including) `level`.
+    ///
+    /// [`FunctionIndex::build`] only includes [`ExportLevel::Public`] and
+    /// [`ExportLevel::ModdingApi`] components. Pass [`ExportLevel::Internal`] to also
+    /// include internal components, for example for first-party tooling mods.
+    pub fn build_with_level(
+        type_registry: &AppTypeRegistry,
+        function_registry: &AppFunctionRegistry,
+        level: ExportLevel,
+    ) -> Self {
         let mut arg_name_overrides: HashMap<(String, String), Vec<String>> = HashMap::default();
+        let mut static_methods = BTreeSet::new();
+        let mut method_docs: HashMap<(String, String), String> = HashMap::default();
         for entry in inventory::iter::<WasvyMethodMetadata> {
             let key = (
                 normalize_type_path(entry.type_path),
                 entry.method.to_string(),
             );
+            if entry.is_static {
+                static_methods.insert(key.clone());
+            }
+            if !entry.docs.is_empty() {
+                method_docs.insert(key.clone(), entry.docs.to_string());
+            }
             let names = entry
                 .arg_names
                 .iter()
@@ -96,10 +157,43 @@ impl FunctionIndex {
             arg_name_overrides.insert(key, names);
         }
 
+        // Host functions by the path they are registered with
+        let host_fns: HashMap<String, &WasvyHostFnMetadata> =
+            inventory::iter::<WasvyHostFnMetadata>
+                .into_iter()
+                .map(|entry| (normalize_type_path(entry.function_name), entry))
+                .collect();
+
         let registry = type_registry.read();
+
+        // Methods of types from other crates, see `ExportMethodsExt`
+        for (registration, external) in registry.iter_with_data::<ExternalMethods>() {
+            let type_path = normalize_type_path(registration.type_info().type_path());
+            for method in &external.0 {
+                let key = (type_path.clone(), method.method.clone());
+                if method.is_static {
+                    static_methods.insert(key.clone());
+                }
+                if let Some(docs) = &method.docs {
+                    method_docs.insert(key.clone(), docs.clone());
+                }
+                if !method.arg_names.is_empty() {
+                    arg_name_overrides.insert(key, method.arg_names.clone());
+                }
+            }
+        }
+
         let mut components = BTreeSet::new();
-        for (registration, _) in registry.iter_with_data::<WasvyExport>() {
-            components.insert(normalize_type_path(registration.type_info().type_path()));
+        let mut resources = BTreeSet::new();
+        for (registration, export) in registry.iter_with_data::<WasvyExport>() {
+            if export.level > level {
+                continue;
+            }
+            let type_path = normalize_type_path(registration.type_info().type_path());
+            if export.resource {
+                resources.insert(type_path.clone());
+            }
+            components.insert(type_path);
         }
 
         let functions = function_registry.read();
@@ -107,6 +201,10 @@ impl FunctionIndex {
             entries: HashMap::new(),
             methods_by_component: BTreeMap::new(),
             components,
+            resources,
+            host_fns: BTreeMap::new(),
+            export_level: level,
+            strict_params: cfg!(debug_assertions),
         };
 
         for function in functions.iter() {
@@ -121,32 +219,48 @@ impl FunctionIndex {
 
             let signature = info.base();
             let args = signature.args();
-            if args.is_empty() {
+            let name = info
+                .name()
+                .map(|n| n.as_ref())
+                .or_else(|| signature.name().map(|n| n.as_ref()));
+
+            if let Some(host_fn) = name.and_then(|name| host_fns.get(&normalize_type_path(name))) {
+                index.insert_host_fn(host_fn, function);
                 continue;
             }
 
-            let receiver = &args[0];
-            let access = match receiver.ownership() {
-                Ownership::Ref => FunctionAccess::Read,
-                Ownership::Mut => FunctionAccess::Write,
-                Ownership::Owned => {
-                    bevy_log::warn!(
-                        "Skipping function {:?}; first argument must be &self or &mut self",
-                        info.name()
-                    );
-                    continue;
+            // Associated functions are named after the type they belong to
+            let static_owner = name.and_then(|name| {
+                let (owner, method) = name.rsplit_once("::")?;
+                let key = (normalize_type_path(owner), method.to_string());
+                static_methods.contains(&key).then_some(key.0)
+            });
+
+            let (access, receiver_type_path, receivers) = match static_owner {
+                Some(owner) => (FunctionAccess::Static, owner, 0),
+                None => {
+                    let Some(receiver) = args.first() else {
+                        continue;
+                    };
+                    let access = match receiver.ownership() {
+                        Ownership::Ref => FunctionAccess::Read,
+                        Ownership::Mut => FunctionAccess::Write,
+                        Ownership::Owned => {
+                            bevy_log::warn!(
+                                "Skipping function {:?}; first argument must be &self or &mut self",
+                                info.name()
+                            );
+                            continue;
+                        }
+                    };
+                    (access, normalize_type_path(receiver.ty().path()), 1)
                 }
             };
 
-            let receiver_type_path = normalize_type_path(receiver.ty().path());
             if !index.components.contains(&receiver_type_path) {
                 continue;
             }
 
-            let name = info
-                .name()
-                .map(|n| n.as_ref())
-                .or_else(|| signature.name().map(|n| n.as_ref()));
             let Some(name) = name else {
                 bevy_log::warn!("Skipping unnamed function; register with a name");
                 continue;
@@ -174,13 +288,13 @@ impl FunctionIndex {
 
             let override_key = (receiver_type_path.clone(), method.to_string());
             let override_names = arg_name_overrides.get(&override_key);
-            let mut arg_specs = Vec::with_capacity(args.len().saturating_sub(1));
-            for (idx, arg) in args.iter().enumerate().skip(1) {
+            let mut arg_specs = Vec::with_capacity(args.len().saturating_sub(receivers));
+            for (idx, arg) in args.iter().enumerate().skip(receivers) {
                 let name = override_names
-                    .and_then(|names| names.get(idx - 1))
+                    .and_then(|names| names.get(idx - receivers))
                     .cloned()
                     .or_else(|| arg.name().map(|n| n.to_string()))
-                    .unwrap_or_else(|| format!("arg{}", idx - 1));
+                    .unwrap_or_else(|| format!("arg{}", idx - receivers));
                 let type_path = normalize_type_path(arg.ty().path());
                 arg_specs.push(FunctionArg {
                     name,
@@ -197,6 +311,7 @@ impl FunctionIndex {
                 access,
                 args: arg_specs,
                 ret,
+                docs: method_docs.get(&override_key).cloned(),
                 function: function.clone(),
             };
 
@@ -211,11 +326,27 @@ impl FunctionIndex {
         index
     }
 
-    /// Iterate over all exported component type paths.
+    /// Iterate over all exported component type paths, including those of resources.
     pub fn components(&self) -> impl Iterator<Item = &str> {
         self.components.iter().map(|s| s.as_str())
     }
 
+    /// Iterate over all free functions exported with `#[wasvy::host_fn]`.
+    pub fn host_fns(&self) -> impl Iterator<Item = &FunctionEntry> {
+        self.host_fns.values()
+    }
+
+    /// Lookup a host function by name, accepting both the WIT name (`give-gold`) and the Rust
+    /// name (`give_gold`).
+    pub fn get_host_fn(&self, name: &str) -> Option<&FunctionEntry> {
+        self.host_fns.get(&name.replace('-', "_"))
+    }
+
+    /// Whether an exported type is a resource rather than a component.
+    pub fn is_resource(&self, type_path: &str) -> bool {
+        self.resources.contains(type_path)
+    }
+
     /// Iterate over all methods for a component type path.
     pub fn methods_for<'a>(&'a self, type_path: &str) -> impl Iterator<Item = &'a FunctionEntry> {
         self.methods_by_component
@@ -225,14 +356,39 @@ impl FunctionIndex {
             .filter_map(|key| self.entries.get(key))
     }
 
-    /// Lookup a specific method entry.
+    /// Lookup a specific method entry, accepting both the WIT name (`is-dead`) and the Rust name
+    /// (`is_dead`).
     pub fn get(&self, type_path: &str, method: &str) -> Option<&FunctionEntry> {
         self.entries.get(&FunctionKey {
             type_path: type_path.to_string(),
-            method: method.to_string(),
+            method: method.replace('-', "_"),
         })
     }
 
+    /// Whether params are validated against the method signature before invoking it.
+    ///
+    /// The least visible [`ExportLevel`] this index was built with, see
+    /// [`FunctionIndex::build_with_level`].
+    pub fn export_level(&self) -> ExportLevel {
+        self.export_level
+    }
+
+    /// See [`FunctionIndex::set_strict_params`].
+    pub fn strict_params(&self) -> bool {
+        self.strict_params
+    }
+
+    /// Validates JSON params against the method's argument types before invoking it.
+    ///
+    /// Without this, params of the wrong shape fail deep inside reflect deserialization. With it,
+    /// [`FunctionIndex::invoke`] returns errors naming the argument index, the expected type path
+    /// and the JSON kind that was received instead. Only applies to the JSON codec.
+    ///
+    /// Defaults to enabled in debug builds.
+    pub fn set_strict_params(&mut self, strict: bool) {
+        self.strict_params = strict;
+    }
+
     /// Invoke a reflected method using JSON-encoded arguments.
     ///
     /// `params_json` must be a JSON array string. The return value is JSON.
@@ -255,14 +411,200 @@ impl FunctionIndex {
         params: &[u8],
         type_registry: &AppTypeRegistry,
         codec: &CodecResource,
+    ) -> Result<Vec<u8>> {
+        let Some(entry) = self.get(type_path, method) else {
+            return self.access_field(type_path, method, target, params, type_registry, codec);
+        };
+
+        match (entry.access, &target) {
+            (FunctionAccess::Static, _) => {
+                bail!(
+                    "{type_path}::{method} is an associated function, invoke it without a component"
+                )
+            }
+            (FunctionAccess::Write, MethodTarget::Read(_)) => {
+                bail!("Method {type_path}::{method} requires mutable access")
+            }
+            _ => {}
+        }
+
+        self.call(entry, Some(target), params, type_registry, codec)
+    }
+
+    /// Invoke a reflected associated function, such as a constructor, using JSON-encoded
+    /// arguments.
+    ///
+    /// These are exported by `#[wasvy::methods]` for functions without a `self` receiver.
+    ///
+    /// # Example
+    /// ```ignore
+    /// let health = index.invoke_static(
+    ///     Health::type_path(),
+    ///     "full",
+    ///     "[100.0]",
+    ///     &type_registry,
+    ///     &codec,
+    /// )?;
+    /// ```
+    pub fn invoke_static(
+        &self,
+        type_path: &str,
+        method: &str,
+        params: &[u8],
+        type_registry: &AppTypeRegistry,
+        codec: &CodecResource,
     ) -> Result<Vec<u8>> {
         let entry = self
             .get(type_path, method)
             .ok_or_else(|| anyhow::anyhow!("Unknown method {type_path}::{method}"))?;
+        if entry.access != FunctionAccess::Static {
+            bail!("Method {type_path}::{method} must be invoked on a component")
+        }
+
+        self.call(entry, None, params, type_registry, codec)
+    }
 
-        if let (FunctionAccess::Write, MethodTarget::Read(_)) = (entry.access, &target) {
+    /// Invoke a free function exported with `#[wasvy::host_fn]`, using JSON-encoded arguments.
+    ///
+    /// # Example
+    /// ```ignore
+    /// #[wasvy::host_fn]
+    /// fn give_gold(amount: u32) { /* ... */ }
+    ///
+    /// index.invoke_host_fn("give-gold", b"[5]", &type_registry, &codec)?;
+    /// ```
+    pub fn invoke_host_fn(
+        &self,
+        name: &str,
+        params: &[u8],
+        type_registry: &AppTypeRegistry,
+        codec: &CodecResource,
+    ) -> Result<Vec<u8>> {
+        let entry = self
+            .get_host_fn(name)
+            .ok_or_else(|| anyhow::anyhow!("Unknown host function {name}"))?;
+
+        self.call(entry, None, params, type_registry, codec)
+    }
+
+    fn insert_host_fn(
+        &mut self,
+        host_fn: &WasvyHostFnMetadata,
+        function: &DynamicFunction<'static>,
+    ) {
+        let info = function.info();
+        let signature = info.base();
+        let args = signature
+            .args()
+            .iter()
+            .enumerate()
+            .map(|(idx, arg)| FunctionArg {
+                name: host_fn
+                    .arg_names
+                    .get(idx)
+                    .map(|name| name.to_string())
+                    .unwrap_or_else(|| format!("arg{idx}")),
+                type_path: normalize_type_path(arg.ty().path()),
+                ownership: arg.ownership(),
+            })
+            .collect();
+
+        let function_name = normalize_type_path(host_fn.function_name);
+        let owner = function_name
+            .rsplit_once("::")
+            .map_or("", |(owner, _)| owner)
+            .to_string();
+        if self.host_fns.contains_key(host_fn.name) {
+            bevy_log::warn!(
+                "Skipping host function {function_name}; another host function is named {}",
+                host_fn.name
+            );
+            return;
+        }
+
+        self.host_fns.insert(
+            host_fn.name.to_string(),
+            FunctionEntry {
+                type_path: owner,
+                method: host_fn.name.to_string(),
+                function_name,
+                access: FunctionAccess::Static,
+                args,
+                ret: normalize_type_path(signature.return_info().ty().path()),
+                docs: (!host_fn.docs.is_empty()).then(|| host_fn.docs.to_string()),
+                function: function.clone(),
+            },
+        );
+    }
+
+    /// Reads or writes a field of a component for its `get-<field>` and `set-<field>` functions,
+    /// see [`WitGeneratorSettings::field_accessors`](crate::witgen::WitGeneratorSettings::field_accessors)
+    fn access_field(
+        &self,
+        type_path: &str,
+        method: &str,
+        target: MethodTarget<'_>,
+        params: &[u8],
+        type_registry: &AppTypeRegistry,
+        codec: &CodecResource,
+    ) -> Result<Vec<u8>> {
+        let unknown = || anyhow::anyhow!("Unknown method {type_path}::{method}");
+        // Accept both the WIT name (`get-current`) and the Rust name (`get_current`)
+        let (is_setter, field) = match method.split_at_checked(4) {
+            Some(("get-" | "get_", field)) => (false, field),
+            Some(("set-" | "set_", field)) => (true, field),
+            _ => return Err(unknown()),
+        };
+        if !self.components.contains(type_path) {
+            return Err(unknown());
+        }
+        let field = field.replace('-', "_");
+        let registry = type_registry.read();
+
+        if !is_setter {
+            let target: &dyn Reflect = match &target {
+                MethodTarget::Read(target) => *target,
+                MethodTarget::Write(target) => &**target,
+            };
+            let ReflectRef::Struct(component) = target.reflect_ref() else {
+                return Err(unknown());
+            };
+            let value = component.field(&field).ok_or_else(unknown)?;
+            return encode_value(value, &registry, codec);
+        }
+
+        let MethodTarget::Write(target) = target else {
             bail!("Method {type_path}::{method} requires mutable access")
+        };
+        let ReflectMut::Struct(component) = target.reflect_mut() else {
+            return Err(unknown());
+        };
+        let value = component.field_mut(&field).ok_or_else(unknown)?;
+        let field_type_path = value.reflect_type_path().to_string();
+
+        let mut args = decode_args(params, &[&field_type_path], &registry, codec)?;
+        if args.len() != 1 {
+            bail!(
+                "Method {type_path}::{method} expects 1 args but received {}",
+                args.len()
+            );
         }
+        let arg = args.pop().flatten().expect("arg to exist");
+        value.try_apply(arg.as_ref())?;
+        Ok(b"null".to_vec())
+    }
+
+    fn call(
+        &self,
+        entry: &FunctionEntry,
+        target: Option<MethodTarget<'_>>,
+        params: &[u8],
+        type_registry: &AppTypeRegistry,
+        codec: &CodecResource,
+    ) -> Result<Vec<u8>> {
+        let FunctionEntry {
+            type_path, method, ..
+        } = entry;
 
         let type_paths = entry
             .args
@@ -272,7 +614,12 @@ impl FunctionIndex {
 
         let registry = type_registry.read();
 
-        let mut owned_args = codec.decode_reflect_args(params, &type_paths, &registry)?;
+        #[cfg(feature = "serde_json")]
+        if self.strict_params && codec.get_type() == "json" {
+            validate_params(entry, params, &registry)?;
+        }
+
+        let mut owned_args = decode_args(params, &type_paths, &registry, codec)?;
 
         if owned_args.len() != entry.args.len() {
             bail!(
@@ -284,8 +631,9 @@ impl FunctionIndex {
 
         let mut arg_list = ArgList::new();
         match target {
-            MethodTarget::Read(target) => arg_list.push_ref(target),
-            MethodTarget::Write(target) => arg_list.push_mut(target),
+            Some(MethodTarget::Read(target)) => arg_list.push_ref(target),
+            Some(MethodTarget::Write(target)) => arg_list.push_mut(target),
+            None => {}
         }
         for (spec, slot) in entry.args.iter().zip(owned_args.iter_mut()) {
             match spec.ownership {
@@ -310,21 +658,522 @@ impl FunctionIndex {
     }
 }
 
+/// Invokes methods of components from host code, such as a scripting console, like mods do with
+/// `component.invoke`
+#[cfg(feature = "serde_json")]
+pub trait WorldMethodExt {
+    /// Invokes a method of the component `type_path` of an entity.
+    ///
+    /// `params` must be a JSON array string. The return value is JSON. Methods are looked up in
+    /// the [`FunctionIndex`] resource, which [`ModRuntimePlugin`](crate::plugin::ModRuntimePlugin)
+    /// adds.
+    ///
+    /// # Example
+    /// ```ignore
+    /// let pct = world.invoke_component_method(player, Health::type_path(), "pct", "[]")?;
+    /// ```
+    fn invoke_component_method(
+        &mut self,
+        entity: Entity,
+        type_path: &str,
+        method: &str,
+        params: &str,
+    ) -> Result<String>;
+}
+
+#[cfg(feature = "serde_json")]
+impl WorldMethodExt for World {
+    fn invoke_component_method(
+        &mut self,
+        entity: Entity,
+        type_path: &str,
+        method: &str,
+        params: &str,
+    ) -> Result<String> {
+        let type_registry = self
+            .get_resource::<AppTypeRegistry>()
+            .context("Missing AppTypeRegistry")?
+            .clone();
+        let reflect_component = type_registry
+            .read()
+            .get_with_type_path(type_path)
+            .and_then(|registration| registration.data::<ReflectComponent>())
+            .cloned()
+            .ok_or_else(|| anyhow!("{type_path} is not a reflected component"))?;
+        let codec = CodecResource::default();
+
+        self.try_resource_scope(|world, index: Mut<FunctionIndex>| {
+            let missing = || anyhow!("Entity {entity} has no component {type_path}");
+            let writes = match index.get(type_path, method) {
+                Some(entry) => entry.access == FunctionAccess::Write,
+                // Field accessors, see FunctionIndex::access_field
+                None => matches!(method.split_at_checked(4), Some(("set-" | "set_", _))),
+            };
+
+            let output = if writes {
+                let entity = world.get_entity_mut(entity)?;
+                let mut component = reflect_component.reflect_mut(entity).ok_or_else(missing)?;
+                index.invoke(
+                    type_path,
+                    method,
+                    MethodTarget::Write(&mut *component),
+                    params.as_bytes(),
+                    &type_registry,
+                    &codec,
+                )?
+            } else {
+                let entity = world.get_entity(entity)?;
+                let component = reflect_component.reflect(entity).ok_or_else(missing)?;
+                index.invoke(
+                    type_path,
+                    method,
+                    MethodTarget::Read(component),
+                    params.as_bytes(),
+                    &type_registry,
+                    &codec,
+                )?
+            };
+            Ok(String::from_utf8(output)?)
+        })
+        .context("Missing FunctionIndex, add the ModRuntimePlugin")?
+    }
+}
+
+/// Decodes params into the concrete types of `type_paths`
+fn decode_args(
+    params: &[u8],
+    type_paths: &[&str],
+    registry: &TypeRegistry,
+    codec: &CodecResource,
+) -> Result<Vec<Option<Box<dyn PartialReflect>>>> {
+    #[cfg(feature = "serde_json")]
+    let decoded = if codec.get_type() == "json" {
+        decode_json_args(params, type_paths, registry)?
+    } else {
+        codec.decode_reflect_args(params, type_paths, registry)?
+    };
+    #[cfg(not(feature = "serde_json"))]
+    let decoded = codec.decode_reflect_args(params, type_paths, registry)?;
+
+    decoded
+        .into_iter()
+        .zip(type_paths)
+        .map(|(arg, type_path)| {
+            arg.map(|arg| from_reflect_arg(arg, type_path, registry))
+                .transpose()
+        })
+        .collect()
+}
+
+/// Decodes JSON params, reading reflected structs and enums as in WIT, see [`crate::methods`]
+#[cfg(feature = "serde_json")]
+fn decode_json_args(
+    params: &[u8],
+    type_paths: &[&str],
+    registry: &TypeRegistry,
+) -> Result<Vec<Option<Box<dyn PartialReflect>>>> {
+    use serde_json::Value;
+
+    if params.iter().all(|b| b.is_ascii_whitespace()) {
+        return Ok(Vec::new());
+    }
+    let args = match serde_json::from_slice(params).context("Invalid JSON params")? {
+        Value::Null => Vec::new(),
+        Value::Array(args) => args,
+        other => bail!("Expected JSON array for params, got {other}"),
+    };
+
+    type_paths
+        .iter()
+        .zip(&args)
+        .map(|(type_path, value)| value_from_json(type_path, value, registry).map(Some))
+        .collect()
+}
+
+/// Reads a value, with reflected structs and enums in the shape of their WIT types
+#[cfg(feature = "serde_json")]
+fn value_from_json(
+    type_path: &str,
+    value: &serde_json::Value,
+    registry: &TypeRegistry,
+) -> Result<Box<dyn PartialReflect>> {
+    use serde::de::DeserializeSeed;
+    use serde_json::Value;
+
+    // Such as the payload of `Ok(())`, which guests leave out
+    if type_path == "()" {
+        return Ok(Box::new(()));
+    }
+
+    let registration = registry
+        .get_with_type_path(type_path)
+        .ok_or_else(|| anyhow::anyhow!("Type {type_path} is not registered"))?;
+    let type_info = registration.type_info();
+
+    let mut variant = match (type_info, value) {
+        (TypeInfo::Struct(info), Value::Object(object)) => {
+            let mut record = fields_from_json(type_path, info.iter(), object, registry)?;
+            record.set_represented_type(Some(type_info));
+            return Ok(Box::new(record));
+        }
+        (TypeInfo::Enum(info), value) if info.type_path_table().ident() == Some("Option") => {
+            match (value, info.variant("Some")) {
+                (Value::Null, _) => DynamicEnum::new("None", DynamicVariant::Unit),
+                (value, Some(VariantInfo::Tuple(some))) => {
+                    let field = some.field_at(0).expect("Some has one field");
+                    let mut tuple = DynamicTuple::default();
+                    tuple.insert_boxed(value_from_json(field.type_path(), value, registry)?);
+                    DynamicEnum::new("Some", DynamicVariant::Tuple(tuple))
+                }
+                _ => bail!("Option {type_path} has no Some variant"),
+            }
+        }
+        (TypeInfo::Enum(info), Value::String(name)) => match info.variant(name) {
+            Some(VariantInfo::Unit(_)) => DynamicEnum::new(name.clone(), DynamicVariant::Unit),
+            Some(_) => bail!("Variant {type_path}::{name} expects a payload"),
+            None => bail!("Enum {type_path} has no variant {name}"),
+        },
+        (TypeInfo::Enum(info), Value::Object(object)) if object.len() == 1 => {
+            let (name, payload) = object.iter().next().expect("object has one entry");
+            let variant = match (info.variant(name), payload) {
+                (Some(VariantInfo::Tuple(variant)), payload) if variant.field_len() == 1 => {
+                    let field = variant.field_at(0).expect("field_len is 1");
+                    let mut tuple = DynamicTuple::default();
+                    tuple.insert_boxed(value_from_json(field.type_path(), payload, registry)?);
+                    DynamicVariant::Tuple(tuple)
+                }
+                (Some(VariantInfo::Tuple(variant)), Value::Array(items))
+                    if items.len() == variant.field_len() =>
+                {
+                    let mut tuple = DynamicTuple::default();
+                    for (field, item) in variant.iter().zip(items) {
+                        tuple.insert_boxed(value_from_json(field.type_path(), item, registry)?);
+                    }
+                    DynamicVariant::Tuple(tuple)
+                }
+                (Some(VariantInfo::Struct(variant)), Value::Object(fields)) => {
+                    let path = format!("{type_path}::{name}");
+                    DynamicVariant::Struct(fields_from_json(
+                        &path,
+                        variant.iter(),
+                        fields,
+                        registry,
+                    )?)
+                }
+                (Some(_), _) => bail!("Variant {type_path}::{name} has an invalid payload"),
+                (None, _) => bail!("Enum {type_path} has no variant {name}"),
+            };
+            DynamicEnum::new(name.clone(), variant)
+        }
+        _ => {
+            let deserializer =
+                bevy_reflect::serde::TypedReflectDeserializer::new(registration, registry);
+            return Ok(deserializer.deserialize(value)?);
+        }
+    };
+
+    variant.set_represented_type(Some(type_info));
+    Ok(Box::new(variant))
+}
+
+/// Reads the named fields of a struct or struct variant from a JSON object
+#[cfg(feature = "serde_json")]
+fn fields_from_json<'a>(
+    type_path: &str,
+    fields: impl Iterator<Item = &'a NamedField>,
+    object: &serde_json::Map<String, serde_json::Value>,
+    registry: &TypeRegistry,
+) -> Result<DynamicStruct> {
+    let mut record = DynamicStruct::default();
+    for field in fields {
+        let value = object.get(field.name()).ok_or_else(|| {
+            anyhow::anyhow!("Record {type_path} is missing the field {}", field.name())
+        })?;
+        record.insert_boxed(
+            field.name(),
+            value_from_json(field.type_path(), value, registry)?,
+        );
+    }
+    Ok(record)
+}
+
+/// Writes a value, with reflected structs and enums in the shape of their WIT types
+#[cfg(feature = "serde_json")]
+fn value_to_json(value: &dyn PartialReflect, registry: &TypeRegistry) -> Result<serde_json::Value> {
+    use serde_json::{Map, Value};
+
+    let fields_to_json = |fields: &mut dyn Iterator<Item = (&str, &dyn PartialReflect)>| {
+        let mut object = Map::new();
+        for (name, field) in fields {
+            object.insert(name.to_string(), value_to_json(field, registry)?);
+        }
+        Ok::<_, anyhow::Error>(Value::Object(object))
+    };
+
+    match value.reflect_ref() {
+        ReflectRef::Tuple(unit) if unit.field_len() == 0 => Ok(Value::Null),
+        ReflectRef::Struct(record) => fields_to_json(&mut record.iter_fields()),
+        ReflectRef::Enum(variant) => {
+            let is_option = variant
+                .get_represented_type_info()
+                .is_some_and(|info| info.type_path_table().ident() == Some("Option"));
+            let name = variant.variant_name();
+            let payload = match variant.variant_type() {
+                VariantType::Unit if is_option => return Ok(Value::Null),
+                VariantType::Unit => return Ok(Value::String(name.to_string())),
+                VariantType::Tuple if is_option => {
+                    let some = variant.field_at(0).expect("Some has one field");
+                    return value_to_json(some, registry);
+                }
+                VariantType::Tuple if variant.field_len() == 1 => {
+                    value_to_json(variant.field_at(0).expect("field_len is 1"), registry)?
+                }
+                VariantType::Tuple => Value::Array(
+                    variant
+                        .iter_fields()
+                        .map(|field| value_to_json(field.value(), registry))
+                        .collect::<Result<_>>()?,
+                ),
+                VariantType::Struct => fields_to_json(&mut variant.iter_fields().map(|field| {
+                    (
+                        field.name().expect("struct variant fields are named"),
+                        field.value(),
+                    )
+                }))?,
+            };
+            Ok(Value::Object(Map::from_iter([(name.to_string(), payload)])))
+        }
+        _ => {
+            let serializer = bevy_reflect::serde::TypedReflectSerializer::new(value, registry);
+            Ok(serde_json::to_value(serializer)?)
+        }
+    }
+}
+
+/// Converts dynamic values, such as the structs read by reflect deserialization, to the concrete
+/// type a method takes
+fn from_reflect_arg(
+    arg: Box<dyn PartialReflect>,
+    type_path: &str,
+    registry: &TypeRegistry,
+) -> Result<Box<dyn PartialReflect>> {
+    if arg.try_as_reflect().is_some() {
+        return Ok(arg);
+    }
+    let from_reflect = registry
+        .get_with_type_path(type_path)
+        .and_then(|registration| registration.data::<ReflectFromReflect>())
+        .ok_or_else(|| anyhow::anyhow!("Type {type_path} must implement FromReflect"))?;
+    let arg = from_reflect
+        .from_reflect(arg.as_ref())
+        .ok_or_else(|| anyhow::anyhow!("Failed to convert the argument to {type_path}"))?;
+    Ok(arg.into_partial_reflect())
+}
+
+/// Checks the arity and shape of JSON params, see [`FunctionIndex::set_strict_params`]
+#[cfg(feature = "serde_json")]
+fn validate_params(entry: &FunctionEntry, params: &[u8], registry: &TypeRegistry) -> Result<()> {
+    use serde_json::Value;
+
+    let FunctionEntry {
+        type_path, method, ..
+    } = entry;
+
+    let value = if params.iter().all(|b| b.is_ascii_whitespace()) {
+        Value::Null
+    } else {
+        serde_json::from_slice(params).context("Invalid JSON params")?
+    };
+    let args = match value {
+        Value::Null => Vec::new(),
+        Value::Array(args) => args,
+        other => bail!(
+            "Method {type_path}::{method} expects a JSON array of params but received {}",
+            json_kind(&other)
+        ),
+    };
+
+    if args.len() != entry.args.len() {
+        bail!(
+            "Method {type_path}::{method} expects {} args but received {}",
+            entry.args.len(),
+            args.len()
+        );
+    }
+
+    for (index, (spec, value)) in entry.args.iter().zip(args.iter()).enumerate() {
+        if let Err(mismatch) = check_shape(&spec.type_path, value, registry) {
+            let field = if mismatch.path.is_empty() {
+                String::new()
+            } else {
+                let path: Vec<_> = mismatch.path.iter().rev().map(String::as_str).collect();
+                format!(" at `{}`", path.join("."))
+            };
+            bail!(
+                "Method {type_path}::{method} argument {index} ({}){field} expects {} but received {}",
+                spec.name,
+                mismatch.expected,
+                mismatch.received
+            );
+        }
+    }
+
+    Ok(())
+}
+
+/// Where a JSON value didn't match the expected type
+#[cfg(feature = "serde_json")]
+struct Mismatch {
+    /// Path to the mismatched value, innermost first
+    path: Vec<String>,
+    expected: String,
+    received: &'static str,
+}
+
+#[cfg(feature = "serde_json")]
+impl Mismatch {
+    fn within(mut self, segment: impl ToString) -> Self {
+        self.path.push(segment.to_string());
+        self
+    }
+}
+
+/// Checks that a JSON value has the shape reflect deserialization expects for a type
+#[cfg(feature = "serde_json")]
+fn check_shape(
+    type_path: &str,
+    value: &serde_json::Value,
+    registry: &TypeRegistry,
+) -> Result<(), Mismatch> {
+    use serde_json::Value;
+
+    // Unregistered types are reported when decoding
+    let Some(registration) = registry.get_with_type_path(type_path) else {
+        return Ok(());
+    };
+    let info = registration.type_info();
+    let mismatch = || Mismatch {
+        path: Vec::new(),
+        expected: info.type_path().to_string(),
+        received: json_kind(value),
+    };
+
+    let check_items = |items: &[Value], item_type_path: &str| {
+        for (index, item) in items.iter().enumerate() {
+            check_shape(item_type_path, item, registry).map_err(|m| m.within(index))?;
+        }
+        Ok(())
+    };
+
+    match (info, value) {
+        (TypeInfo::Opaque(_), value) => match opaque_kind(info.type_path()) {
+            Some(kind) if kind != json_kind(value) => Err(mismatch()),
+            _ => Ok(()),
+        },
+        (TypeInfo::Struct(info), Value::Object(object)) => {
+            for field in info.iter() {
+                if let Some(value) = object.get(field.name()) {
+                    check_shape(field.type_path(), value, registry)
+                        .map_err(|m| m.within(field.name()))?;
+                }
+            }
+            Ok(())
+        }
+        // Newtypes are serialized as their inner value
+        (TypeInfo::TupleStruct(info), value) if info.field_len() == 1 => {
+            let field = info.field_at(0).expect("field_len is 1");
+            check_shape(field.type_path(), value, registry)
+        }
+        (TypeInfo::TupleStruct(info), Value::Array(items)) if items.len() == info.field_len() => {
+            for (field, item) in info.iter().zip(items) {
+                check_shape(field.type_path(), item, registry)
+                    .map_err(|m| m.within(field.index()))?;
+            }
+            Ok(())
+        }
+        (TypeInfo::Tuple(info), Value::Array(items)) if items.len() == info.field_len() => {
+            for (field, item) in info.iter().zip(items) {
+                check_shape(field.type_path(), item, registry)
+                    .map_err(|m| m.within(field.index()))?;
+            }
+            Ok(())
+        }
+        (TypeInfo::List(info), Value::Array(items)) => check_items(items, info.item_ty().path()),
+        (TypeInfo::Set(info), Value::Array(items)) => check_items(items, info.value_ty().path()),
+        (TypeInfo::Array(info), Value::Array(items)) if items.len() == info.capacity() => {
+            check_items(items, info.item_ty().path())
+        }
+        (TypeInfo::Map(_), Value::Object(_)) => Ok(()),
+        (TypeInfo::Enum(info), value) if info.type_path_table().ident() == Some("Option") => {
+            // Options are serialized as null or their inner value
+            match (value, info.variant("Some")) {
+                (Value::Null, _) => Ok(()),
+                (value, Some(VariantInfo::Tuple(some))) => {
+                    let field = some.field_at(0).expect("Some has one field");
+                    check_shape(field.type_path(), value, registry)
+                }
+                _ => Ok(()),
+            }
+        }
+        // Unit variants are strings, others are objects with the variant as the only key
+        (TypeInfo::Enum(info), Value::String(variant)) if info.contains_variant(variant) => Ok(()),
+        (TypeInfo::Enum(_), Value::Object(object)) if object.len() == 1 => Ok(()),
+        _ => Err(mismatch()),
+    }
+}
+
+/// The JSON kind primitive types are serialized as
+#[cfg(feature = "serde_json")]
+fn opaque_kind(type_path: &str) -> Option<&'static str> {
+    match type_path {
+        "bool" => Some("bool"),
+        "u8" | "u16" | "u32" | "u64" | "u128" | "usize" | "i8" | "i16" | "i32" | "i64" | "i128"
+        | "isize" | "f32" | "f64" => Some("number"),
+        "char" | "alloc::string::String" | "&str" | "alloc::borrow::Cow<str>" => Some("string"),
+        _ => None,
+    }
+}
+
+#[cfg(feature = "serde_json")]
+fn json_kind(value: &serde_json::Value) -> &'static str {
+    use serde_json::Value;
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "bool",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
 fn serialize_return(
     result: bevy_reflect::func::Return<'_>,
-    registry: &bevy_reflect::TypeRegistry,
+    registry: &TypeRegistry,
     codec: &CodecResource,
 ) -> Result<Vec<u8>> {
     if result.is_unit() {
         return Ok(b"null".to_vec());
     }
-    match result {
-        bevy_reflect::func::Return::Owned(value) => {
-            Ok(codec.encode_reflect(value.as_ref(), registry)?)
-        }
-        bevy_reflect::func::Return::Ref(value) => Ok(codec.encode_reflect(value, registry)?),
-        bevy_reflect::func::Return::Mut(value) => Ok(codec.encode_reflect(value, registry)?),
+    let value: &dyn PartialReflect = match &result {
+        bevy_reflect::func::Return::Owned(value) => value.as_ref(),
+        bevy_reflect::func::Return::Ref(value) => *value,
+        bevy_reflect::func::Return::Mut(value) => &**value,
+    };
+    encode_value(value, registry, codec)
+}
+
+/// Encodes a value returned to the guest, writing reflected structs and enums as in WIT
+fn encode_value(
+    value: &dyn PartialReflect,
+    registry: &TypeRegistry,
+    codec: &CodecResource,
+) -> Result<Vec<u8>> {
+    #[cfg(feature = "serde_json")]
+    if codec.get_type() == "json" {
+        return Ok(serde_json::to_vec(&value_to_json(value, registry)?)?);
     }
+    codec.encode_reflect(value, registry)
 }
 
 fn method_from_name(name: &str) -> &str {
@@ -332,7 +1181,7 @@ fn method_from_name(name: &str) -> &str {
     segment.rsplit('.').next().unwrap_or(segment)
 }
 
-fn normalize_type_path(path: &str) -> String {
+pub(crate) fn normalize_type_path(path: &str) -> String {
     let trimmed = path.trim();
     let stripped = if let Some(rest) = trimmed.strip_prefix("&mut ") {
         rest
@@ -360,6 +1209,7 @@ mod tests {
     use bevy_ecs::component::Component;
     use bevy_ecs::prelude::ReflectComponent;
     use bevy_ecs::reflect::AppFunctionRegistry;
+    use bevy_math::Vec3;
     use bevy_reflect::{Reflect, TypePath};
     use wasvy_macros::*;
 
@@ -372,6 +1222,10 @@ mod tests {
 
     #[methods]
     impl Health {
+        fn full(max: f32) -> Health {
+            Health { current: max, max }
+        }
+
         fn heal(&mut self, amount: f32) {
             self.current = (self.current + amount).min(self.max);
         }
@@ -424,11 +1278,88 @@ mod tests {
         }
     }
 
+    #[derive(Reflect, Default, Clone, Copy, PartialEq, Debug)]
+    struct Waypoint {
+        position: Vec3,
+        wait: f32,
+    }
+
+    #[derive(Component, Reflect, Default, WasvyComponent)]
+    #[reflect(Component)]
+    struct Steering {
+        target: Vec3,
+        waypoint: Waypoint,
+    }
+
+    #[methods]
+    impl Steering {
+        fn set_target(&mut self, pos: Vec3) {
+            self.target = pos;
+        }
+
+        fn target(&self) -> Vec3 {
+            self.target
+        }
+
+        fn follow(&mut self, waypoint: Waypoint) -> Waypoint {
+            std::mem::replace(&mut self.waypoint, waypoint)
+        }
+    }
+
+    #[derive(Reflect, Default, Clone, PartialEq, Debug)]
+    enum Order {
+        #[default]
+        Hold,
+        MoveTo(Vec3),
+        Patrol {
+            from: Vec3,
+            to: Vec3,
+        },
+        Wait(f32, Option<f32>),
+    }
+
+    #[derive(Component, Reflect, Default, WasvyComponent)]
+    #[reflect(Component)]
+    struct Squad {
+        order: Order,
+    }
+
+    #[methods]
+    impl Squad {
+        fn command(&mut self, order: Order) -> Order {
+            std::mem::replace(&mut self.order, order)
+        }
+    }
+
+    #[derive(Component, Reflect, Default, WasvyComponent)]
+    #[reflect(Component)]
+    struct Wallet {
+        gold: u32,
+    }
+
+    #[methods]
+    impl Wallet {
+        fn spend(&mut self, amount: u32) -> Result<u32, String> {
+            self.gold = self
+                .gold
+                .checked_sub(amount)
+                .ok_or_else(|| format!("Can't spend {amount} of {} gold", self.gold))?;
+            Ok(self.gold)
+        }
+
+        fn clear(&mut self) -> Result<(), String> {
+            self.gold = 0;
+            Ok(())
+        }
+    }
+
     inventory::submit! {
         WasvyMethodMetadata {
             type_path: "build_script_build::methods::tests::BuildScriptHealth",
             method: "heal",
             arg_names: &["amount"],
+            is_static: false,
+            docs: "",
         }
     }
 
@@ -485,6 +1416,339 @@ mod tests {
         assert!((pct_val - 0.7).abs() < 1e-6);
     }
 
+    #[derive(Component, Reflect, Default, WasvyComponent)]
+    #[reflect(Component)]
+    #[wasvy(level = "internal")]
+    struct DebugCounter {
+        hits: u32,
+    }
+
+    #[methods]
+    impl DebugCounter {
+        fn hit(&mut self) {
+            self.hits += 1;
+        }
+    }
+
+    #[test]
+    fn internal_methods_need_an_internal_index() {
+        let app = new_app();
+        let type_registry = app.world().resource::<AppTypeRegistry>();
+        let function_registry = app.world().resource::<AppFunctionRegistry>();
+        let codec = CodecResource::default();
+        let mut counter = DebugCounter::default();
+        let mut hit = |index: &FunctionIndex| {
+            index.invoke(
+                DebugCounter::type_path(),
+                "hit",
+                MethodTarget::Write(&mut counter),
+                b"null",
+                type_registry,
+                &codec,
+            )
+        };
+
+        let index = FunctionIndex::build(type_registry, function_registry);
+        assert_eq!(index.export_level(), ExportLevel::ModdingApi);
+        assert!(hit(&index).is_err());
+
+        let index = FunctionIndex::build_with_level(
+            type_registry,
+            function_registry,
+            ExportLevel::Internal,
+        );
+        assert_eq!(index.export_level(), ExportLevel::Internal);
+        hit(&index).unwrap();
+        assert_eq!(counter.hits, 1);
+    }
+
+    #[test]
+    fn invokes_methods_of_entities() {
+        let mut app = new_app();
+        let world = app.world_mut();
+        let index = FunctionIndex::build(
+            world.resource::<AppTypeRegistry>(),
+            world.resource::<AppFunctionRegistry>(),
+        );
+        world.insert_resource(index);
+        let entity = world
+            .spawn(Health {
+                current: 2.0,
+                max: 10.0,
+            })
+            .id();
+
+        let out = world
+            .invoke_component_method(entity, Health::type_path(), "heal", "[5.0]")
+            .unwrap();
+        assert_eq!(out, "null");
+        assert_eq!(world.get::<Health>(entity).unwrap().current, 7.0);
+
+        let pct = world
+            .invoke_component_method(entity, Health::type_path(), "pct", "[]")
+            .unwrap();
+        let pct: f32 = pct.parse().unwrap();
+        assert!((pct - 0.7).abs() < 1e-6);
+
+        let empty = world.spawn_empty().id();
+        assert!(
+            world
+                .invoke_component_method(empty, Health::type_path(), "pct", "[]")
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn invokes_with_records() {
+        let mut app = new_app();
+        app.register_type::<Vec3>().register_type::<Waypoint>();
+
+        let type_registry = app.world().resource::<AppTypeRegistry>();
+        let function_registry = app.world().resource::<AppFunctionRegistry>();
+        let codec = CodecResource::default();
+        let index = FunctionIndex::build(type_registry, function_registry);
+        let mut steering = Steering::default();
+
+        // Methods are called by their WIT name
+        index
+            .invoke(
+                Steering::type_path(),
+                "set-target",
+                MethodTarget::Write(&mut steering),
+                br#"[{"x": 1.0, "y": 2.0, "z": 3.0}]"#,
+                type_registry,
+                &codec,
+            )
+            .unwrap();
+        assert_eq!(steering.target, Vec3::new(1.0, 2.0, 3.0));
+
+        let target = index
+            .invoke(
+                Steering::type_path(),
+                "target",
+                MethodTarget::Read(&steering),
+                b"null",
+                type_registry,
+                &codec,
+            )
+            .unwrap();
+        let target: serde_json::Value = serde_json::from_slice(&target).unwrap();
+        assert_eq!(target, serde_json::json!({"x": 1.0, "y": 2.0, "z": 3.0}));
+
+        let previous = index
+            .invoke(
+                Steering::type_path(),
+                "follow",
+                MethodTarget::Write(&mut steering),
+                br#"[{"position": {"x": 0.0, "y": 0.0, "z": 5.0}, "wait": 0.5}]"#,
+                type_registry,
+                &codec,
+            )
+            .unwrap();
+        let previous: serde_json::Value = serde_json::from_slice(&previous).unwrap();
+        assert_eq!(
+            previous,
+            serde_json::json!({"position": {"x": 0.0, "y": 0.0, "z": 0.0}, "wait": 0.0})
+        );
+        assert_eq!(steering.waypoint.position, Vec3::Z * 5.0);
+    }
+
+    #[test]
+    fn invokes_with_enums() {
+        let mut app = new_app();
+        app.register_type::<Vec3>()
+            .register_type::<Order>()
+            .register_type::<Option<f32>>();
+
+        let type_registry = app.world().resource::<AppTypeRegistry>();
+        let function_registry = app.world().resource::<AppFunctionRegistry>();
+        let codec = CodecResource::default();
+        let index = FunctionIndex::build(type_registry, function_registry);
+        let mut squad = Squad::default();
+
+        let mut command = |order: &str| {
+            let previous = index
+                .invoke(
+                    Squad::type_path(),
+                    "command",
+                    MethodTarget::Write(&mut squad),
+                    format!("[{order}]").as_bytes(),
+                    type_registry,
+                    &codec,
+                )
+                .unwrap();
+            serde_json::from_slice::<serde_json::Value>(&previous).unwrap()
+        };
+
+        let previous = command(r#"{"MoveTo": {"x": 1.0, "y": 0.0, "z": 0.0}}"#);
+        assert_eq!(previous, serde_json::json!("Hold"));
+
+        let previous = command(
+            r#"{"Patrol": {"from": {"x": 0.0, "y": 0.0, "z": 0.0}, "to": {"x": 0.0, "y": 0.0, "z": 1.0}}}"#,
+        );
+        assert_eq!(
+            previous,
+            serde_json::json!({"MoveTo": {"x": 1.0, "y": 0.0, "z": 0.0}})
+        );
+
+        let previous = command(r#"{"Wait": [2.0, null]}"#);
+        assert_eq!(
+            previous,
+            serde_json::json!({"Patrol": {
+                "from": {"x": 0.0, "y": 0.0, "z": 0.0},
+                "to": {"x": 0.0, "y": 0.0, "z": 1.0}
+            }})
+        );
+
+        let previous = command(r#""Hold""#);
+        assert_eq!(previous, serde_json::json!({"Wait": [2.0, null]}));
+        assert_eq!(squad.order, Order::Hold);
+    }
+
+    #[test]
+    fn invokes_with_results() {
+        let app = new_app();
+        let type_registry = app.world().resource::<AppTypeRegistry>();
+        let function_registry = app.world().resource::<AppFunctionRegistry>();
+        let codec = CodecResource::default();
+        let index = FunctionIndex::build(type_registry, function_registry);
+        let mut wallet = Wallet { gold: 10 };
+
+        let mut invoke = |method: &str, params: &[u8]| {
+            let output = index
+                .invoke(
+                    Wallet::type_path(),
+                    method,
+                    MethodTarget::Write(&mut wallet),
+                    params,
+                    type_registry,
+                    &codec,
+                )
+                .unwrap();
+            serde_json::from_slice::<serde_json::Value>(&output).unwrap()
+        };
+
+        assert_eq!(invoke("spend", b"[4]"), serde_json::json!({"Ok": 6}));
+        assert_eq!(
+            invoke("spend", b"[7]"),
+            serde_json::json!({"Err": "Can't spend 7 of 6 gold"})
+        );
+        assert_eq!(invoke("clear", b"[]"), serde_json::json!({"Ok": null}));
+
+        // Guests decode them as results
+        let output = serde_json::to_vec(&invoke("spend", b"[1]")).unwrap();
+        let result: Result<u32, String> = crate::serialize::wasvy_decode(&output).unwrap();
+        assert_eq!(result, Err("Can't spend 1 of 0 gold".to_string()));
+    }
+
+    #[test]
+    fn invokes_static_functions() {
+        let app = new_app();
+        let type_registry = app.world().resource::<AppTypeRegistry>();
+        let function_registry = app.world().resource::<AppFunctionRegistry>();
+        let codec = CodecResource::default();
+        let index = FunctionIndex::build(type_registry, function_registry);
+
+        let entry = index.get(Health::type_path(), "full").expect("full entry");
+        assert_eq!(entry.access, FunctionAccess::Static);
+        assert_eq!(entry.args[0].name, "max");
+
+        let health = index
+            .invoke_static(
+                Health::type_path(),
+                "full",
+                b"[50.0]",
+                type_registry,
+                &codec,
+            )
+            .unwrap();
+        let health: serde_json::Value = serde_json::from_slice(&health).unwrap();
+        assert_eq!(health, serde_json::json!({"current": 50.0, "max": 50.0}));
+
+        // Static functions have no receiver, and methods need one
+        let mut health = Health::default();
+        assert!(
+            index
+                .invoke(
+                    Health::type_path(),
+                    "full",
+                    MethodTarget::Write(&mut health),
+                    b"[50.0]",
+                    type_registry,
+                    &codec,
+                )
+                .is_err()
+        );
+        assert!(
+            index
+                .invoke_static(Health::type_path(), "heal", b"[5.0]", type_registry, &codec)
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn invokes_field_accessors() {
+        let app = new_app();
+        let type_registry = app.world().resource::<AppTypeRegistry>();
+        let function_registry = app.world().resource::<AppFunctionRegistry>();
+        let codec = CodecResource::default();
+        let index = FunctionIndex::build(type_registry, function_registry);
+
+        let mut health = Health {
+            current: 5.0,
+            max: 10.0,
+        };
+        let current = index
+            .invoke(
+                Health::type_path(),
+                "get-current",
+                MethodTarget::Read(&health),
+                b"[]",
+                type_registry,
+                &codec,
+            )
+            .unwrap();
+        assert_eq!(current, b"5.0");
+
+        index
+            .invoke(
+                Health::type_path(),
+                "set-current",
+                MethodTarget::Write(&mut health),
+                b"[8.0]",
+                type_registry,
+                &codec,
+            )
+            .unwrap();
+        assert_eq!(health.current, 8.0);
+
+        // Setters need mutable access, and unknown fields are unknown methods
+        assert!(
+            index
+                .invoke(
+                    Health::type_path(),
+                    "set-max",
+                    MethodTarget::Read(&health),
+                    b"[1.0]",
+                    type_registry,
+                    &codec,
+                )
+                .is_err()
+        );
+        assert!(
+            index
+                .invoke(
+                    Health::type_path(),
+                    "get-armor",
+                    MethodTarget::Read(&health),
+                    b"[]",
+                    type_registry,
+                    &codec,
+                )
+                .is_err()
+        );
+    }
+
     #[test]
     fn metadata_build_script_path_normalizes() {
         let mut app = App::new();
@@ -1,7 +1,6 @@
 use std::fmt;
 
-use bevy_asset::{AssetPath, AssetServer, Handle};
-use bevy_derive::{Deref, DerefMut};
+use bevy_asset::{AssetPath, AssetServer, Assets, Handle};
 use bevy_ecs::{
     change_detection::MaybeLocation, error::warn, lifecycle::HookContext, prelude::*,
     system::SystemParam, world::DeferredWorld,
@@ -10,13 +9,27 @@ use bevy_log::prelude::*;
 use bevy_platform::collections::HashSet;
 use bevy_reflect::Reflect;
 
-use crate::{access::ModAccess, asset::ModAsset, cleanup::DisableSystemSet};
+use crate::{
+    access::ModAccess,
+    asset::{ModAsset, ModLoaderSettings},
+    cleanup::{
+        DespawnModEntity, DisableSystemSet, TransferModEntities, TransferModEntity,
+        transfer_to_host,
+    },
+    discovery::ModScan,
+    entry_points::ModEntryPoints,
+    failure::ModTraps,
+    manifest::{ModDetails, ModManifest},
+    permissions::{AssetPermissions, ComponentPermissions},
+    setup::ReloadMod,
+};
 
 /// This system param provides an interface to load and manage Wasvy mods
 #[derive(SystemParam)]
 pub struct Mods<'w, 's> {
     commands: Commands<'w, 's>,
-    asset_server: Res<'w, AssetServer>,
+    /// Missing in headless apps, see [ModAsset::from_bytes]
+    asset_server: Option<Res<'w, AssetServer>>,
     mods: Query<'w, 's, Entity, With<Mod>>,
 }
 
@@ -26,16 +39,192 @@ impl Mods<'_, '_> {
     /// This [spawns](Self::spawn) a new instance of the mod and configures it to run in the world.
     ///
     /// The mod will be given access to the entire World. See [docs for Global Sandbox](crate::sandbox::Sandbox)
+    ///
+    /// Logs an error if the mod can't be loaded, see [Self::try_load].
     pub fn load<'a>(&mut self, path: impl Into<AssetPath<'a>>) {
-        let mod_id = self.spawn(path, None);
+        if let Err(err) = self.try_load(path) {
+            error!("{err}");
+        }
+    }
+
+    /// Loads a single wasm file like [Self::load], returning an error if it can't be loaded.
+    pub fn try_load<'a>(&mut self, path: impl Into<AssetPath<'a>>) -> Result<(), ModSpawnError> {
+        let mod_id = self.try_spawn(path, None)?;
         self.enable_access(mod_id, ModAccess::World);
+        Ok(())
+    }
+
+    /// Loads every wasm file in the given directory, like [Self::load].
+    ///
+    /// The directory is scanned in the background (subdirectories are not), and a [ModDiscovered]
+    /// message is sent for each mod found.
+    ///
+    /// ```
+    /// # use bevy_ecs::prelude::*;
+    /// # use wasvy_runtime::prelude::*;
+    /// fn load_mods(mut mods: Mods) {
+    ///     mods.load_dir("mods");
+    /// }
+    /// ```
+    pub fn load_dir<'a>(&mut self, path: impl Into<AssetPath<'a>>) {
+        self.load_dir_filtered(path, |_| true);
+    }
+
+    /// Loads every wasm file in the given directory like [Self::load_dir], returning an error if
+    /// the directory can't be scanned.
+    pub fn try_load_dir<'a>(
+        &mut self,
+        path: impl Into<AssetPath<'a>>,
+    ) -> Result<(), ModSpawnError> {
+        self.try_load_dir_filtered(path, |_| true)
+    }
+
+    /// Like [Self::load_dir], but only loads the mods for which `filter` returns true.
+    ///
+    /// Use this to implement lists of enabled or disabled mods. Skipped mods are still reported
+    /// via [ModDiscovered](crate::discovery::ModDiscovered), without a mod id.
+    ///
+    /// ```
+    /// # use bevy_ecs::prelude::*;
+    /// # use wasvy_runtime::prelude::*;
+    /// fn load_mods(mut mods: Mods) {
+    ///     let disabled = ["broken.wasm"];
+    ///     mods.load_dir_filtered("mods", move |path| {
+    ///         let file_name = path.path().file_name().and_then(|name| name.to_str());
+    ///         !file_name.is_some_and(|name| disabled.contains(&name))
+    ///     });
+    /// }
+    /// ```
+    ///
+    /// Logs an error if the directory can't be scanned, see [Self::try_load_dir_filtered].
+    pub fn load_dir_filtered<'a>(
+        &mut self,
+        path: impl Into<AssetPath<'a>>,
+        filter: impl Fn(&AssetPath) -> bool + Send + Sync + 'static,
+    ) {
+        if let Err(err) = self.try_load_dir_filtered(path, filter) {
+            error!("{err}");
+        }
+    }
+
+    /// Like [Self::load_dir_filtered], returning an error if the directory can't be scanned.
+    ///
+    /// Errors if the app has no `AssetPlugin`.
+    pub fn try_load_dir_filtered<'a>(
+        &mut self,
+        path: impl Into<AssetPath<'a>>,
+        filter: impl Fn(&AssetPath) -> bool + Send + Sync + 'static,
+    ) -> Result<(), ModSpawnError> {
+        let Some(asset_server) = &self.asset_server else {
+            return Err(ModSpawnError::NoAssetServer);
+        };
+        let scan = ModScan::new(
+            AssetServer::clone(asset_server),
+            path.into().into_owned(),
+            Box::new(filter),
+        );
+        self.commands.spawn(scan);
+        Ok(())
     }
 
     /// Spawns a new instance of a mod from the given path. By default this mod will do nothing once loaded.
     ///
     /// Next, you might want to give this mod access via [Self::enable_access].
+    ///
+    /// Logs an error if the mod can't be spawned, in which case the returned entity is not a mod.
+    /// See [Self::try_spawn].
     pub fn spawn<'a>(&mut self, path: impl Into<AssetPath<'a>>, name: Option<String>) -> Entity {
-        let path = path.into();
+        match self.try_spawn(path, name) {
+            Ok(mod_id) => mod_id,
+            Err(err) => {
+                error!("{err}");
+                self.commands.spawn_empty().id()
+            }
+        }
+    }
+
+    /// Spawns a new instance of a mod like [Self::spawn], returning an error if it can't be spawned.
+    ///
+    /// Errors if the app has no `AssetPlugin`, use [Self::spawn_bytes] instead.
+    pub fn try_spawn<'a>(
+        &mut self,
+        path: impl Into<AssetPath<'a>>,
+        name: Option<String>,
+    ) -> Result<Entity, ModSpawnError> {
+        self.spawn_with(path.into(), name, Vec::new())
+    }
+
+    /// Loads a mod made of several wasm files, linked together host-side, like [Self::load].
+    ///
+    /// The first file is the mod, and the others are plugs: components whose exports satisfy
+    /// imports of the mod, or of the plugs listed before them, like `wac plug` composes them ahead
+    /// of time. Each instance of the mod gets its own instances of its plugs, which live as long as
+    /// it does. Plugs can't export resources yet.
+    ///
+    /// ```
+    /// # use bevy_ecs::prelude::*;
+    /// # use wasvy_runtime::prelude::*;
+    /// fn load_mods(mut mods: Mods) {
+    ///     mods.load_composed(["mods/game.wasm", "mods/pathfinding.wasm"]);
+    /// }
+    /// ```
+    ///
+    /// The plugs are [loader settings](ModLoaderSettings) of the first file, so other loads of
+    /// that path share the asset loaded first, with or without plugs.
+    pub fn load_composed<'a>(&mut self, paths: impl IntoIterator<Item = impl Into<AssetPath<'a>>>) {
+        if let Err(err) = self.try_load_composed(paths) {
+            error!("{err}");
+        }
+    }
+
+    /// Loads a mod made of several wasm files like [Self::load_composed], returning an error if it
+    /// can't be loaded.
+    pub fn try_load_composed<'a>(
+        &mut self,
+        paths: impl IntoIterator<Item = impl Into<AssetPath<'a>>>,
+    ) -> Result<(), ModSpawnError> {
+        let mod_id = self.try_spawn_composed(paths, None)?;
+        self.enable_access(mod_id, ModAccess::World);
+        Ok(())
+    }
+
+    /// Spawns a new instance of a mod made of several wasm files, like [Self::spawn]. See
+    /// [Self::load_composed].
+    ///
+    /// Logs an error and returns [None] if the mod can't be spawned, see [Self::try_spawn_composed].
+    pub fn spawn_composed<'a>(
+        &mut self,
+        paths: impl IntoIterator<Item = impl Into<AssetPath<'a>>>,
+        name: Option<String>,
+    ) -> Option<Entity> {
+        self.try_spawn_composed(paths, name)
+            .inspect_err(|err| error!("{err}"))
+            .ok()
+    }
+
+    /// Spawns a new instance of a mod made of several wasm files like [Self::spawn_composed],
+    /// returning an error if it can't be spawned.
+    ///
+    /// Errors if `paths` is empty.
+    pub fn try_spawn_composed<'a>(
+        &mut self,
+        paths: impl IntoIterator<Item = impl Into<AssetPath<'a>>>,
+        name: Option<String>,
+    ) -> Result<Entity, ModSpawnError> {
+        let mut paths = paths.into_iter().map(|path| path.into().into_owned());
+        let path = paths.next().ok_or(ModSpawnError::NoPaths)?;
+        self.spawn_with(path, name, paths.collect())
+    }
+
+    fn spawn_with(
+        &mut self,
+        path: AssetPath,
+        name: Option<String>,
+        plugs: Vec<AssetPath<'static>>,
+    ) -> Result<Entity, ModSpawnError> {
+        let Some(asset_server) = &self.asset_server else {
+            return Err(ModSpawnError::NoAssetServer);
+        };
         let name = name.unwrap_or_else(|| {
             path.path()
                 .file_name()
@@ -43,10 +232,65 @@ impl Mods<'_, '_> {
                 .unwrap_or("unknown")
                 .to_string()
         });
-        let asset = self.asset_server.load(path);
+        let asset = if plugs.is_empty() {
+            asset_server.load(path)
+        } else {
+            asset_server
+                .load_builder()
+                .with_settings(move |settings: &mut ModLoaderSettings| {
+                    settings.plugs = plugs.clone();
+                })
+                .load(path)
+        };
+
+        info!("Loading mod \"{name}\"");
+        Ok(self.commands.spawn((Mod::new(asset), Name::new(name))).id())
+    }
 
+    /// Loads a mod from the bytes of a wasm file, like [Self::load].
+    ///
+    /// Use this for mods embedded in the binary, downloaded at runtime or read from custom storage.
+    ///
+    /// ```
+    /// # use bevy_ecs::prelude::*;
+    /// # use wasvy_runtime::prelude::*;
+    /// fn load_mods(mut mods: Mods, downloads: Res<Downloads>) {
+    ///     for (name, bytes) in &downloads.0 {
+    ///         mods.load_bytes(name, bytes.clone());
+    ///     }
+    /// }
+    /// # #[derive(Resource)]
+    /// # struct Downloads(Vec<(String, Vec<u8>)>);
+    /// ```
+    pub fn load_bytes(&mut self, name: impl Into<String>, bytes: Vec<u8>) {
+        let mod_id = self.spawn_bytes(name, bytes);
+        self.enable_access(mod_id, ModAccess::World);
+    }
+
+    /// Spawns a new instance of a mod from the bytes of a wasm file, like [Self::spawn].
+    ///
+    /// The mod is compiled with [ModAsset::from_bytes] when commands are applied. If that fails,
+    /// the error is logged and the mod is despawned.
+    pub fn spawn_bytes(&mut self, name: impl Into<String>, bytes: Vec<u8>) -> Entity {
+        let name = name.into();
         info!("Loading mod \"{name}\"");
-        self.commands.spawn((Mod::new(asset), Name::new(name))).id()
+        let mod_id = self.commands.spawn(Name::new(name.clone())).id();
+
+        let command = move |world: &mut World| -> Result<(), BevyError> {
+            let asset = match ModAsset::from_bytes(world, &bytes) {
+                Ok(asset) => asset,
+                Err(err) => {
+                    world.despawn(mod_id);
+                    return Err(format!("Failed to compile mod \"{name}\": {err:?}").into());
+                }
+            };
+            let handle = world.resource_mut::<Assets<ModAsset>>().add(asset);
+            world.get_entity_mut(mod_id)?.insert(Mod::new(handle));
+            Ok(())
+        };
+        self.commands.queue_handled(command, warn);
+
+        mod_id
     }
 
     /// Despawns a mod, removing its systems from the sandboxes it was added to.
@@ -147,6 +391,173 @@ impl Mods<'_, '_> {
         self.commands.queue_handled(command, warn);
     }
 
+    /// Sets what happens to the entities a mod spawns when it is despawned (or reloaded),
+    /// overriding the default of the app. See [ModDespawnBehaviour].
+    ///
+    /// ```
+    /// # use bevy_ecs::prelude::*;
+    /// # use wasvy_runtime::prelude::*;
+    /// fn load_level_editor(mut mods: Mods) {
+    ///     let editor = mods.spawn("mods/level_editor.wasm", None);
+    ///     mods.set_despawn_behaviour(editor, ModDespawnBehaviour::Persist);
+    ///     mods.enable_access(editor, ModAccess::World);
+    /// }
+    /// ```
+    ///
+    /// Note: This applies to entities spawned once the mod is next set up, so set it right after
+    /// [spawning](Self::spawn) the mod.
+    #[track_caller]
+    pub fn set_despawn_behaviour(&mut self, mod_id: Entity, behaviour: ModDespawnBehaviour) {
+        let caller = MaybeLocation::caller();
+        let command = move |world: &mut World| -> Result<(), BevyError> {
+            let mut entity = Mod::get_entity_mut(mod_id, world).map_err(|error| {
+                format!("{error}, could not set the despawn behaviour\n{caller}")
+            })?;
+            entity.insert(ModDespawnPolicy(behaviour));
+
+            Ok(())
+        };
+        self.commands.queue_handled(command, warn);
+    }
+
+    /// Stops running a mod's systems, without despawning it. [Reload](Self::reload) the mod to
+    /// run them again.
+    ///
+    /// Note: The effect of this change is not immediate. This change will apply after the setup
+    /// schedule (which defaults to [First](bevy_app::First), see
+    /// [ModRuntimePlugin::set_setup_schedule](crate::plugin::ModRuntimePlugin::set_setup_schedule)) runs.
+    #[track_caller]
+    pub fn disable(&mut self, mod_id: Entity) {
+        let caller = MaybeLocation::caller();
+        let command = move |world: &mut World| -> Result<(), BevyError> {
+            let entity = Mod::get_entity_mut(mod_id, world)
+                .map_err(|error| format!("{error}, could not disable\n{caller}"))?;
+            let name = Mod::get_name(&entity).to_string();
+            let accesses: Vec<_> = entity
+                .get::<Mod>()
+                .expect("checked by get_entity_mut")
+                .access
+                .iter()
+                .copied()
+                .collect();
+
+            info!("Disabling mod \"{name}\"");
+            for access in accesses {
+                let schedules = access.schedules(world);
+                DisableSystemSet {
+                    set: ModSystemSet::Mod(mod_id),
+                    schedules,
+                }
+                .apply(world);
+            }
+            ModState::Disabled.set(mod_id, world);
+
+            Ok(())
+        };
+        self.commands.queue_handled(command, warn);
+    }
+
+    /// Runs the setup of a mod again, without waiting for its file to change.
+    ///
+    /// The systems added by its previous setup are disabled, and its startup systems run again once
+    /// it is set up. This is handy for a "reload mods" button in games that don't watch files.
+    ///
+    /// This also gives [quarantined](ModState::Quarantined) mods another chance, see
+    /// [ModFailurePolicy](crate::failure::ModFailurePolicy), and enables [disabled](Self::disable) mods.
+    ///
+    /// Note: The effect of this change is not immediate. This change will apply after the setup
+    /// schedule (which defaults to [First](bevy_app::First), see
+    /// [ModRuntimePlugin::set_setup_schedule](crate::plugin::ModRuntimePlugin::set_setup_schedule)) runs.
+    #[track_caller]
+    pub fn reload(&mut self, mod_id: Entity) {
+        let caller = MaybeLocation::caller();
+        let command = move |world: &mut World| -> Result<(), BevyError> {
+            let entity = Mod::get_entity_mut(mod_id, world)
+                .map_err(|error| format!("{error}, could not reload\n{caller}"))?;
+            let name = Mod::get_name(&entity).to_string();
+
+            if let Some(traps) = entity.get::<ModTraps>() {
+                traps.reset();
+            }
+
+            let mod_component = entity.get::<Mod>().expect("checked by get_entity_mut");
+            let asset_id = mod_component.asset.id();
+            let accesses: Vec<_> = mod_component.access.iter().copied().collect();
+
+            info!("Reloading mod \"{name}\"");
+            ModState::Loading.set(mod_id, world);
+
+            // The next setup adds the systems again. Disabling the systems of failed mods twice is harmless
+            for access in accesses {
+                let schedules = access.schedules(world);
+                DisableSystemSet {
+                    set: ModSystemSet::Mod(mod_id),
+                    schedules,
+                }
+                .apply(world);
+            }
+
+            let tick = world.change_tick();
+            if let Some(mut asset) = world.resource_mut::<Assets<ModAsset>>().get_mut(asset_id) {
+                asset.bump_version(tick);
+            }
+
+            world.write_message(ReloadMod(mod_id));
+
+            Ok(())
+        };
+        self.commands.queue_handled(command, warn);
+    }
+
+    /// Calls an entry point, such as `on-save`, on every mod that has loaded.
+    ///
+    /// The entry point must be listed in [ModEntryPoints], and mods lacking it are skipped.
+    /// [Failed](ModState::Failed), quarantined and disabled mods are skipped too.
+    #[track_caller]
+    pub fn run_entry_point(&mut self, name: impl Into<String>) {
+        let caller = MaybeLocation::caller();
+        let name = name.into();
+        let command = move |world: &mut World| -> Result<(), BevyError> {
+            let known = world
+                .get_resource::<ModEntryPoints>()
+                .is_some_and(|entry_points| entry_points.contains(&name));
+            if !known {
+                return Err(format!(
+                    "\"{name}\" is not an entry point, see ModEntryPoints\n{caller}"
+                )
+                .into());
+            }
+
+            let mut mods = world.query::<(Entity, &Mod, Option<&Name>, Option<&ModState>)>();
+            let assets = world.resource::<Assets<ModAsset>>();
+            let targets: Vec<_> = mods
+                .iter(world)
+                .filter(|(_, mod_component, _, state)| {
+                    !matches!(
+                        state,
+                        Some(ModState::Failed | ModState::Quarantined | ModState::Disabled)
+                    ) && assets.contains(mod_component.asset.id())
+                })
+                .map(|(mod_id, mod_component, name, _)| {
+                    let name = name.map(|name| name.as_str()).unwrap_or("unknown");
+                    let accesses: Vec<_> = mod_component.access.iter().copied().collect();
+                    (mod_id, mod_component.asset.id(), name.to_string(), accesses)
+                })
+                .collect();
+
+            for (mod_id, asset_id, mod_name, accesses) in targets {
+                if let Err(err) =
+                    ModAsset::run_entry_point(world, &asset_id, mod_id, &mod_name, &accesses, &name)
+                {
+                    error!("Failed to run \"{name}\" of mod \"{mod_name}\": {err:?}");
+                }
+            }
+
+            Ok(())
+        };
+        self.commands.queue_handled(command, warn);
+    }
+
     /// Unload all currently loaded mods.
     pub fn despawn_all(&mut self) {
         for entity in self.mods.iter() {
@@ -155,12 +566,34 @@ impl Mods<'_, '_> {
     }
 }
 
+/// The error returned by the `try_` methods of [Mods] when a mod can't be spawned
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ModSpawnError {
+    /// Loading mods from paths requires the `AssetPlugin`, see [Mods::spawn_bytes]
+    NoAssetServer,
+
+    /// A composed mod was given no paths, see [Mods::try_spawn_composed]
+    NoPaths,
+}
+
+impl std::error::Error for ModSpawnError {}
+
+impl fmt::Display for ModSpawnError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NoAssetServer => write!(f, "Loading mods from paths requires the AssetPlugin"),
+            Self::NoPaths => write!(f, "Composing a mod requires at least one path"),
+        }
+    }
+}
+
 /// A Bevy wasm mod.
 ///
 /// Note: Bevy drops assets if there are no active handles so
 /// this component holds a reference to it in order to keep it alive.
 #[derive(Component, Reflect)]
 #[component(on_despawn = Self::on_despawn)]
+#[require(ModState)]
 pub struct Mod {
     /// A handle to wasm file for this mod
     asset: Handle<ModAsset>,
@@ -236,6 +669,39 @@ impl Mod {
 
     /// [On despawn](bevy_ecs::lifecycle::ComponentHooks::on_despawn) for [Mod]
     fn on_despawn(mut world: DeferredWorld, ctx: HookContext) {
+        let entity = world.entity(ctx.entity);
+        let mod_component = entity.get::<Self>().expect("Mod was removed");
+
+        // Give the mod a chance to clean up before its systems are disabled
+        // The entity will be gone by then, so collect what the mod needs now
+        let asset_id = mod_component.asset.id();
+        let name = entity
+            .get::<Name>()
+            .map(|name| name.as_str())
+            .unwrap_or("unknown")
+            .to_string();
+        let accesses: Vec<_> = mod_component.access.iter().copied().collect();
+        let transfer: Vec<_> = entity
+            .get::<TransferModEntities>()
+            .map(|transfer| transfer.iter().collect())
+            .unwrap_or_default();
+        let permissions = ComponentPermissions::of(ctx.entity, &world);
+        let mod_id = ctx.entity;
+        let previous = entity.get::<ModState>().copied().unwrap_or_default();
+        world.write_message(ModStateChanged {
+            mod_id,
+            previous,
+            state: ModState::Unloading,
+        });
+        world.commands().queue(move |world: &mut World| {
+            if let Err(err) =
+                ModAsset::teardown(world, &asset_id, mod_id, &name, &accesses, &permissions)
+            {
+                error!("Failed to tear down mod \"{name}\": {err:?}");
+            }
+            transfer_to_host(world, transfer);
+        });
+
         let mod_component = world
             .entity(ctx.entity)
             .get::<Self>()
@@ -253,6 +719,138 @@ impl Mod {
     }
 }
 
+/// The state of a [Mod], so games can show a loading screen or react to failures.
+///
+/// Mods are compiled in the background, off the main thread, so loading big mods doesn't freeze
+/// the app. Only their setup runs on the main thread, since it needs access to the world.
+///
+/// A [ModStateChanged] message is sent whenever the state changes.
+///
+/// ```
+/// # use bevy_ecs::prelude::*;
+/// # use wasvy_runtime::prelude::*;
+/// fn loading_progress(mods: Query<&ModState, With<Mod>>) -> f32 {
+///     let ready = mods.iter().filter(|state| **state == ModState::Ready).count();
+///     ready as f32 / mods.iter().len().max(1) as f32
+/// }
+/// ```
+#[derive(Component, Debug, Clone, Copy, Default, PartialEq, Eq, Reflect)]
+pub enum ModState {
+    /// The mod is being compiled, or is waiting for the mods it depends on
+    #[default]
+    Loading,
+
+    /// The mod's setup is running
+    SettingUp,
+
+    /// The mod was set up and its systems are running
+    Ready,
+
+    /// The mod failed to load or to set up. It is set up again when its file changes, or when
+    /// it is [reloaded](Mods::reload).
+    Failed,
+
+    /// The mod was quarantined after trapping too often, so its systems are disabled.
+    ///
+    /// See [ModFailurePolicy](crate::failure::ModFailurePolicy) and [Mods::reload].
+    Quarantined,
+
+    /// The mod's systems were disabled with [Mods::disable]
+    Disabled,
+
+    /// The mod was despawned and is being torn down.
+    ///
+    /// Since the mod's entity is gone, this state is only seen in [ModStateChanged].
+    Unloading,
+}
+
+impl ModState {
+    /// Sets the state of a mod, sending a [ModStateChanged] message if it changed
+    pub(crate) fn set(self, mod_id: Entity, world: &mut World) {
+        let Ok(mut entity) = world.get_entity_mut(mod_id) else {
+            return;
+        };
+        let previous = entity.get::<Self>().copied().unwrap_or_default();
+        if previous == self {
+            return;
+        }
+
+        entity.insert(self);
+        world.write_message(ModStateChanged {
+            mod_id,
+            previous,
+            state: self,
+        });
+    }
+}
+
+/// Sent when the [ModState] of a mod changes
+#[derive(Message, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ModStateChanged {
+    /// The mod's entity
+    pub mod_id: Entity,
+
+    /// The state the mod was in
+    pub previous: ModState,
+
+    /// The state the mod is in now
+    pub state: ModState,
+}
+
+/// Describes how a [Mod] was loaded, so that mods can introspect it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ModInfo {
+    /// The name of the mod
+    pub name: String,
+
+    /// The mod's entity
+    pub entity: Entity,
+
+    /// A display of each [access](ModAccess) the mod was given
+    pub accesses: Vec<String>,
+
+    /// The capabilities the host granted to this mod
+    pub capabilities: Vec<String>,
+
+    /// The version of the mod, if it declared one
+    pub version: Option<String>,
+}
+
+impl ModInfo {
+    /// Collects the info of a mod. Returns [None] if the entity is not a [Mod].
+    pub fn new(mod_id: Entity, world: &World) -> Option<Self> {
+        let entity = world.get_entity(mod_id).ok()?;
+        let mod_component = entity.get::<Mod>()?;
+
+        let name = entity
+            .get::<Name>()
+            .map(|name| name.as_str())
+            .unwrap_or("unknown")
+            .to_string();
+        let mut accesses: Vec<String> = mod_component
+            .accesses()
+            .map(|access| access.display(world))
+            .collect();
+        accesses.sort();
+
+        Some(Self {
+            name,
+            entity: mod_id,
+            accesses,
+            capabilities: ComponentPermissions::of(mod_id, world)
+                .capabilities()
+                .into_iter()
+                .chain(AssetPermissions::of(mod_id, world).capabilities())
+                .collect(),
+            version: entity
+                .get::<ModManifest>()
+                .map(|manifest| &manifest.version)
+                .or_else(|| entity.get::<ModDetails>().map(|details| &details.version))
+                .map(ToString::to_string),
+        })
+    }
+}
+
 /// SystemSets for systems from mod added to the schedule graph by wasvy
 #[derive(SystemSet, Clone, Debug, Hash, PartialEq, Eq, Default)]
 pub enum ModSystemSet {
@@ -302,48 +900,103 @@ impl ModSystemSet {
     }
 }
 
-/// An enum that defines what happens when a mod is despawned (or reloaded)
+/// An enum that defines what happens to the entities a mod spawned when it is despawned (or reloaded)
 ///
-/// Set this value during plugin instantiation via
-/// [ModRuntimePlugin::set_despawn_behaviour](crate::plugin::ModRuntimePlugin::set_despawn_behaviour).
+/// Set the default for all mods during plugin instantiation via
+/// [ModRuntimePlugin::set_despawn_behaviour](crate::plugin::ModRuntimePlugin::set_despawn_behaviour),
+/// override it for a mod with [Mods::set_despawn_behaviour], or for a single entity from the mod
+/// with `entity-commands.set-despawn-behaviour`.
 ///
 /// The default behaviour is to despawn all entities this mod spawned.
-/// See [DespawnEntities](ModDespawnBehaviour::DespawnEntities).
-#[derive(Resource, Debug, Default, PartialEq, Eq)]
+/// See [DespawnWithMod](ModDespawnBehaviour::DespawnWithMod).
+#[derive(Resource, Debug, Default, Clone, Copy, PartialEq, Eq, Reflect)]
 #[component(immutable)]
 pub enum ModDespawnBehaviour {
-    /// Do nothing when a mod is despawned
-    None,
-
     /// The default. Despawn all entities this mod spawned.
     ///
     /// So for example if your mod spawns a cube in the center of the scene,
     /// when this mod is hot reloaded the cube is despawned, and the newest
     /// version of the mod spawns a new cube in its place.
     #[default]
-    DespawnEntities,
+    DespawnWithMod,
+
+    /// Leave the entities in the world as they are, such as for content created by a mod
+    Persist,
+
+    /// Leave the entities in the world, but hand them over to the host: entities of sandboxed mods
+    /// are moved out of the [Sandbox](crate::sandbox::Sandbox), so only the host and mods with
+    /// access to the world can reach them.
+    TransferToHost,
 }
 
 impl ModDespawnBehaviour {
-    pub(crate) fn should_despawn_entities(world: &World) -> bool {
-        match world.get_resource() {
-            None | Some(ModDespawnBehaviour::DespawnEntities) => true,
-            Some(ModDespawnBehaviour::None) => false,
-        }
+    /// The behaviour of a mod, which is its [ModDespawnPolicy] or the default of the app
+    pub fn of(mod_id: Entity, world: &World) -> Self {
+        world
+            .get::<ModDespawnPolicy>(mod_id)
+            .map(|policy| policy.0)
+            .or_else(|| world.get_resource().copied())
+            .unwrap_or_default()
     }
 }
 
-/// Determines whether `DespawnModEntities` should be inserted to entities spawned by mods
-#[derive(Clone, Copy, Deref, DerefMut)]
-pub struct InsertDespawnComponent(Option<Entity>);
+/// Overrides the [ModDespawnBehaviour] of the app for a single mod, see [Mods::set_despawn_behaviour]
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Reflect)]
+pub struct ModDespawnPolicy(pub ModDespawnBehaviour);
+
+/// Ties the entities spawned by a mod to it, according to its [ModDespawnBehaviour]
+#[derive(Clone, Copy)]
+pub struct InsertDespawnComponent {
+    mod_id: Option<Entity>,
+    behaviour: ModDespawnBehaviour,
+}
 
 impl InsertDespawnComponent {
     pub fn new(mod_id: Entity, world: &World) -> Self {
-        Self(if ModDespawnBehaviour::should_despawn_entities(world) {
-            Some(mod_id)
-        } else {
-            None
-        })
+        Self {
+            mod_id: Some(mod_id),
+            behaviour: ModDespawnBehaviour::of(mod_id, world),
+        }
+    }
+
+    /// Never inserts the component, such as for entities spawned after the mod was despawned
+    pub fn disabled() -> Self {
+        Self {
+            mod_id: None,
+            behaviour: ModDespawnBehaviour::Persist,
+        }
+    }
+
+    /// Ties a newly spawned entity to the mod, according to the behaviour of the mod
+    pub fn insert(&self, entity_commands: &mut EntityCommands) {
+        self.insert_with(entity_commands, self.behaviour);
+    }
+
+    /// Ties an entity to the mod according to `behaviour` instead of the behaviour of the mod,
+    /// replacing how it was tied before
+    pub fn insert_with(
+        &self,
+        entity_commands: &mut EntityCommands,
+        behaviour: ModDespawnBehaviour,
+    ) {
+        let Some(mod_id) = self.mod_id else {
+            return;
+        };
+        match behaviour {
+            ModDespawnBehaviour::DespawnWithMod => {
+                entity_commands
+                    .remove::<TransferModEntity>()
+                    .insert(DespawnModEntity::new(mod_id));
+            }
+            ModDespawnBehaviour::Persist => {
+                entity_commands.remove::<(DespawnModEntity, TransferModEntity)>();
+            }
+            ModDespawnBehaviour::TransferToHost => {
+                entity_commands
+                    .remove::<DespawnModEntity>()
+                    .insert(TransferModEntity::new(mod_id));
+            }
+        }
     }
 }
 
@@ -360,3 +1013,128 @@ impl<'a> fmt::Display for ModName<'a> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use bevy_ecs::system::SystemState;
+
+    use super::*;
+    use crate::{
+        asset::{ModBackend, ModCompiler},
+        permissions::ComponentAccess,
+        sandbox::{Sandbox, Sandboxed},
+        schedule::ModSchedules,
+    };
+
+    #[test]
+    fn sends_state_changes() {
+        let mut world = World::new();
+        world.init_resource::<Messages<ModStateChanged>>();
+        let mod_id = world.spawn(ModState::default()).id();
+
+        ModState::SettingUp.set(mod_id, &mut world);
+        ModState::SettingUp.set(mod_id, &mut world);
+        ModState::Ready.set(mod_id, &mut world);
+
+        assert_eq!(world.get::<ModState>(mod_id), Some(&ModState::Ready));
+        let changes: Vec<_> = world
+            .resource_mut::<Messages<ModStateChanged>>()
+            .drain()
+            .map(|change| (change.previous, change.state))
+            .collect();
+        assert_eq!(
+            changes,
+            [
+                (ModState::Loading, ModState::SettingUp),
+                (ModState::SettingUp, ModState::Ready),
+            ]
+        );
+    }
+
+    #[test]
+    fn ties_entities_by_despawn_behaviour() {
+        let mut world = World::new();
+        world.register_component::<Sandboxed>();
+        let sandbox = Sandbox::new(&mut world, ModSchedules::empty());
+        let sandbox = world.spawn(sandbox).id();
+        let mod_id = world
+            .spawn(ModDespawnPolicy(ModDespawnBehaviour::TransferToHost))
+            .id();
+        let insert_despawn_component = InsertDespawnComponent::new(mod_id, &world);
+
+        let mut commands = world.commands();
+        let mut transferred = commands.spawn(ChildOf(sandbox));
+        insert_despawn_component.insert(&mut transferred);
+        let transferred = transferred.id();
+        let mut persisted = commands.spawn(ChildOf(sandbox));
+        insert_despawn_component.insert_with(&mut persisted, ModDespawnBehaviour::DespawnWithMod);
+        insert_despawn_component.insert_with(&mut persisted, ModDespawnBehaviour::Persist);
+        let persisted = persisted.id();
+        world.flush();
+
+        assert_eq!(
+            world.get::<TransferModEntity>(transferred).map(|t| t.0),
+            Some(mod_id)
+        );
+        assert!(world.get::<DespawnModEntity>(persisted).is_none());
+        assert!(world.get::<TransferModEntity>(persisted).is_none());
+
+        transfer_to_host(&mut world, vec![transferred]);
+        assert!(world.get::<TransferModEntity>(transferred).is_none());
+        assert!(world.get::<ChildOf>(transferred).is_none());
+        assert_eq!(
+            world.get::<ChildOf>(persisted).map(ChildOf::parent),
+            Some(sandbox)
+        );
+    }
+
+    #[test]
+    fn spawns_mods_from_bytes() {
+        struct EmptyBackend;
+        impl ModBackend for EmptyBackend {
+            fn setup(
+                &self,
+                _: &mut World,
+                _: Entity,
+                _: &str,
+                _: &[ModAccess],
+            ) -> anyhow::Result<()> {
+                Ok(())
+            }
+        }
+
+        let mut world = World::new();
+        world.init_resource::<Assets<ModAsset>>();
+        world.insert_resource(ModCompiler::new(|_| Ok(ModAsset::new(EmptyBackend))));
+
+        let mut state = SystemState::<Mods>::new(&mut world);
+        let mut mods = state.get_mut(&mut world).unwrap();
+        let compiled = mods.spawn_bytes("compiled", b"\0asm\x0d\0\x01\0".to_vec());
+        let invalid = mods.spawn_bytes("invalid", b"not wasm".to_vec());
+        state.apply(&mut world);
+
+        let asset = world.get::<Mod>(compiled).unwrap().asset();
+        assert!(world.resource::<Assets<ModAsset>>().contains(&asset));
+        assert!(world.get_entity(invalid).is_err());
+    }
+
+    #[test]
+    fn mod_info_lists_granted_capabilities() {
+        let mut world = World::new();
+        let mod_id = world
+            .spawn((
+                Mod::new(Handle::default()),
+                Name::new("my-mod"),
+                ComponentPermissions::default().allow(ComponentAccess::Query, ["game::Health"]),
+                AssetPermissions::default().allow_root("scenes"),
+            ))
+            .id();
+
+        let info = ModInfo::new(mod_id, &world).unwrap();
+        assert_eq!(info.name, "my-mod");
+        assert_eq!(
+            info.capabilities,
+            ["query:game::Health", "insert:*", "mutate:*", "asset:scenes"]
+        );
+    }
+}
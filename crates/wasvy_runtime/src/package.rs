@@ -0,0 +1,275 @@
+use std::{
+    io::{Cursor, Read},
+    path::{Component as PathComponent, Path, PathBuf},
+};
+
+use anyhow::{Context, Result, bail};
+use bevy_asset::{AssetId, AssetPath, Assets, io::embedded::EmbeddedAssetRegistry};
+use bevy_ecs::prelude::*;
+use bevy_log::prelude::*;
+use bevy_platform::collections::HashMap;
+use zip::ZipArchive;
+
+use crate::{asset::ModAsset, manifest::ModManifest};
+
+/// The file extension of [ModPackage]s
+pub const PACKAGE_EXTENSION: &str = "wasvymod";
+
+/// The folder of the `embedded` asset source that packages mount their assets in
+const MOUNT_DIR: &str = "wasvymod";
+
+/// The most bytes the files of a [ModPackage] may take once extracted, so a small archive can't
+/// inflate into more memory than the host has
+pub const MAX_PACKAGE_SIZE: u64 = 256 * 1024 * 1024;
+
+/// A mod bundled into a single `.wasvymod` file, so it ships as one download.
+///
+/// Packages are zip archives holding:
+/// - `mod.wasm`, the mod itself
+/// - `mod.toml`, its [ModManifest] (optional)
+/// - `assets/`, the assets the mod loads with `assets.load` (optional)
+///
+/// Packages are loaded like wasm files, for example with `mods.spawn("mods/dungeon.wasvymod", None)`.
+/// Their assets are mounted under the `embedded` asset source, and become the mod's
+/// [ModAssetRoot](crate::asset_root::ModAssetRoot) unless the host configured another.
+///
+/// Any zip tool can create a package: `zip -r dungeon.wasvymod mod.wasm mod.toml assets`
+#[derive(Debug, Default, Clone)]
+pub struct ModPackage {
+    /// The contents of `mod.wasm`
+    pub wasm: Vec<u8>,
+
+    /// The parsed `mod.toml`
+    pub manifest: Option<ModManifest>,
+
+    /// The files in `assets/`, by their path relative to it
+    pub assets: Vec<(PathBuf, Vec<u8>)>,
+}
+
+impl ModPackage {
+    /// Reads a package from the bytes of a `.wasvymod` file
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        let mut wasm = None;
+        let mut package = Self::default();
+        for (name, contents) in read_zip(bytes, MAX_PACKAGE_SIZE).context("Invalid mod package")? {
+            if name == "mod.wasm" {
+                wasm = Some(contents);
+            } else if name == "mod.toml" {
+                let source =
+                    std::str::from_utf8(&contents).context("mod.toml is not valid UTF-8")?;
+                package.manifest = Some(ModManifest::from_toml(source)?);
+            } else if let Ok(path) = Path::new(&name).strip_prefix("assets") {
+                package.assets.push((path.to_path_buf(), contents));
+            }
+        }
+
+        package.wasm = wasm.context("Mod package has no mod.wasm")?;
+        Ok(package)
+    }
+}
+
+/// The assets of a [ModPackage], mounted when the mod is set up
+pub(crate) struct PackageAssets {
+    name: String,
+    files: Vec<(PathBuf, Vec<u8>)>,
+}
+
+impl PackageAssets {
+    /// Fails if `name` is not a single folder name, since the assets would be mounted outside of
+    /// the folder of the mod
+    pub(crate) fn new(name: String, files: Vec<(PathBuf, Vec<u8>)>) -> Result<Self> {
+        let mut components = Path::new(&name).components();
+        let folder = matches!(components.next(), Some(PathComponent::Normal(_)))
+            && components.next().is_none()
+            && !name.contains(['/', '\\']);
+        if !folder {
+            bail!("Cannot mount the assets of package \"{name}\", its id must be a folder name");
+        }
+        Ok(Self { name, files })
+    }
+
+    /// The folder the assets are mounted in
+    pub(crate) fn root(&self) -> AssetPath<'static> {
+        AssetPath::from(format!("embedded://{MOUNT_DIR}/{}", self.name))
+    }
+
+    /// Makes the assets of `owner` loadable under [Self::root].
+    ///
+    /// Fails if the assets of another package are already mounted there.
+    pub(crate) fn mount(&self, world: &mut World, owner: AssetId<ModAsset>) -> Result<()> {
+        if !world.contains_resource::<EmbeddedAssetRegistry>() {
+            warn!(
+                "Could not mount the assets of package \"{}\", since the AssetPlugin is missing",
+                self.name
+            );
+            return Ok(());
+        }
+
+        world.init_resource::<MountedPackages>();
+        world.resource_scope(|world, mut mounted: Mut<MountedPackages>| {
+            // Packages that were unloaded give up their folder
+            let assets = world.resource::<Assets<ModAsset>>();
+            if let Some(other) = mounted.0.get(&self.name)
+                && *other != owner
+                && assets.contains(*other)
+            {
+                bail!(
+                    "Cannot mount the assets of package \"{}\", another package with the same id is loaded",
+                    self.name
+                );
+            }
+            mounted.0.insert(self.name.clone(), owner);
+
+            let registry = world.resource::<EmbeddedAssetRegistry>();
+            for (path, contents) in &self.files {
+                let asset_path = Path::new(MOUNT_DIR).join(&self.name).join(path);
+                registry.insert_asset(asset_path.clone(), &asset_path, contents.clone());
+            }
+            Ok(())
+        })
+    }
+}
+
+/// The package mounted in each folder of [MOUNT_DIR]
+#[derive(Resource, Default)]
+struct MountedPackages(HashMap<String, AssetId<ModAsset>>);
+
+/// Reads the files of a zip archive, skipping directories.
+///
+/// Fails if the files take more than `max_size` bytes once extracted.
+fn read_zip(bytes: &[u8], max_size: u64) -> Result<Vec<(String, Vec<u8>)>> {
+    let mut archive = ZipArchive::new(Cursor::new(bytes))?;
+    let mut remaining = max_size;
+    let mut files = Vec::new();
+    for index in 0..archive.len() {
+        let mut file = archive.by_index(index)?;
+        if file.is_dir() {
+            continue;
+        }
+
+        let name = file.name().to_string();
+        let relative = Path::new(&name)
+            .components()
+            .all(|component| matches!(component, PathComponent::Normal(_)));
+        if !relative {
+            bail!("File {name} is outside of the archive");
+        }
+
+        // The size in the header is only used to fail early, since the archive may lie about it
+        if file.size() > remaining {
+            bail!("Package is larger than {max_size} bytes");
+        }
+        let mut contents = Vec::new();
+        file.by_ref()
+            .take(remaining + 1)
+            .read_to_end(&mut contents)
+            .with_context(|| format!("Failed to extract file {name}"))?;
+        remaining = remaining
+            .checked_sub(contents.len() as u64)
+            .with_context(|| format!("Package is larger than {max_size} bytes"))?;
+        files.push((name, contents));
+    }
+
+    Ok(files)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use zip::{CompressionMethod, ZipWriter, write::SimpleFileOptions};
+
+    use super::*;
+
+    /// Writes a zip archive, deflating files whose name ends with `.png`
+    fn zip(files: &[(&str, &[u8])]) -> Vec<u8> {
+        let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+        for (name, contents) in files {
+            let method = if name.ends_with(".png") {
+                CompressionMethod::Deflated
+            } else {
+                CompressionMethod::Stored
+            };
+            let options = SimpleFileOptions::default().compression_method(method);
+            writer.start_file(*name, options).unwrap();
+            writer.write_all(contents).unwrap();
+        }
+        writer.finish().unwrap().into_inner()
+    }
+
+    #[test]
+    fn reads_packages() {
+        let bytes = zip(&[
+            ("mod.wasm", b"\0asm"),
+            ("mod.toml", b"id = \"dungeon\"\nversion = \"1.0.0\""),
+            ("assets/textures/wall.png", b"not really a png"),
+            ("README.md", b"ignored"),
+        ]);
+
+        let package = ModPackage::from_bytes(&bytes).unwrap();
+        assert_eq!(package.wasm, b"\0asm");
+        assert_eq!(package.manifest.unwrap().id, "dungeon");
+        assert_eq!(
+            package.assets,
+            vec![(
+                PathBuf::from("textures/wall.png"),
+                b"not really a png".to_vec()
+            )]
+        );
+
+        assert!(ModPackage::from_bytes(&zip(&[("mod.toml", b"")])).is_err());
+        assert!(ModPackage::from_bytes(&zip(&[("../mod.wasm", b"")])).is_err());
+        assert!(ModPackage::from_bytes(b"\0asm").is_err());
+    }
+
+    #[test]
+    fn limits_extracted_size() {
+        let bytes = zip(&[("mod.wasm", b"\0asm"), ("assets/zeros.png", &[0; 4096])]);
+
+        assert_eq!(read_zip(&bytes, 4100).unwrap().len(), 2);
+        assert!(read_zip(&bytes, 4099).is_err());
+    }
+
+    #[test]
+    fn mounts_in_a_single_folder() {
+        assert!(PackageAssets::new("dungeon".into(), Vec::new()).is_ok());
+        for name in ["", ".", "..", "a/b", "../b", "/abs", "a\\b"] {
+            assert!(
+                PackageAssets::new(name.into(), Vec::new()).is_err(),
+                "{name}"
+            );
+        }
+    }
+
+    #[test]
+    fn refuses_to_mount_over_another_package() {
+        struct EmptyBackend;
+        impl crate::asset::ModBackend for EmptyBackend {
+            fn setup(
+                &self,
+                _: &mut World,
+                _: Entity,
+                _: &str,
+                _: &[crate::access::ModAccess],
+            ) -> Result<()> {
+                Ok(())
+            }
+        }
+
+        let mut world = World::new();
+        world.init_resource::<EmbeddedAssetRegistry>();
+        let mut assets = Assets::<ModAsset>::default();
+        let first = assets.add(ModAsset::new(EmptyBackend)).id();
+        let second = assets.add(ModAsset::new(EmptyBackend)).id();
+        world.insert_resource(assets);
+
+        let package = PackageAssets::new("dungeon".into(), Vec::new()).unwrap();
+        package.mount(&mut world, first).unwrap();
+        package.mount(&mut world, first).unwrap();
+        assert!(package.mount(&mut world, second).is_err());
+
+        // Unloaded packages give up their folder
+        world.resource_mut::<Assets<ModAsset>>().remove(first);
+        package.mount(&mut world, second).unwrap();
+    }
+}
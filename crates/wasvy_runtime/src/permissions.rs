@@ -0,0 +1,333 @@
+use std::{
+    fmt,
+    path::{Component as PathComponent, Path, PathBuf},
+};
+
+use bevy_ecs::prelude::*;
+use bevy_platform::collections::HashSet;
+
+use crate::component::TypePath;
+
+/// Restricts which components a [Mod](crate::mods::Mod) may query, insert or mutate.
+///
+/// Insert this component on a mod's entity. Mods without it may access any component.
+///
+/// ```
+/// # use bevy_ecs::prelude::*;
+/// # use wasvy_runtime::prelude::*;
+/// fn setup(mut mods: Mods, mut commands: Commands) {
+///     let mod_id = mods.spawn("mods/untrusted.wasm", None);
+///     commands.entity(mod_id).insert(
+///         ComponentPermissions::default()
+///             .allow(ComponentAccess::Query, ["my_game::Position", "my_game::Velocity"])
+///             .deny(ComponentAccess::Mutate, ["my_game::Position"]),
+///     );
+///     mods.enable_access(mod_id, ModAccess::World);
+/// }
+/// ```
+///
+/// Denied components don't cause the mod to fail: systems with a denied query are skipped, as are
+/// commands inserting a denied component, and an error is logged.
+///
+/// Note: Permissions are read when the mod is set up, so changes apply the next time the mod is
+/// reloaded.
+#[derive(Component, Debug, Clone, Default, PartialEq, Eq)]
+pub struct ComponentPermissions {
+    query: TypeList,
+    insert: TypeList,
+    mutate: TypeList,
+}
+
+/// The ways in which a mod may access a component. See [ComponentPermissions]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ComponentAccess {
+    /// Reading the component in a query, or filtering by it
+    Query,
+
+    /// Inserting the component via commands
+    Insert,
+
+    /// Mutating the component in a query
+    Mutate,
+}
+
+impl fmt::Display for ComponentAccess {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Query => "query",
+            Self::Insert => "insert",
+            Self::Mutate => "mutate",
+        })
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct TypeList {
+    /// When set, only these types are allowed
+    allow: Option<HashSet<TypePath>>,
+
+    /// These types are always denied
+    deny: HashSet<TypePath>,
+}
+
+impl ComponentPermissions {
+    /// Only allows the given components for this kind of access. Can be called multiple times to allow more.
+    pub fn allow<T: Into<TypePath>>(
+        mut self,
+        access: ComponentAccess,
+        type_paths: impl IntoIterator<Item = T>,
+    ) -> Self {
+        self.list_mut(access)
+            .allow
+            .get_or_insert_default()
+            .extend(type_paths.into_iter().map(Into::into));
+        self
+    }
+
+    /// Denies the given components for this kind of access. This takes priority over [Self::allow].
+    pub fn deny<T: Into<TypePath>>(
+        mut self,
+        access: ComponentAccess,
+        type_paths: impl IntoIterator<Item = T>,
+    ) -> Self {
+        self.list_mut(access)
+            .deny
+            .extend(type_paths.into_iter().map(Into::into));
+        self
+    }
+
+    /// Returns whether a component may be accessed
+    pub fn is_allowed(&self, access: ComponentAccess, type_path: &str) -> bool {
+        let list = self.list(access);
+        !list.deny.contains(type_path)
+            && list
+                .allow
+                .as_ref()
+                .is_none_or(|allow| allow.contains(type_path))
+    }
+
+    /// Like [Self::is_allowed], but returns an error describing the denied access
+    pub fn check(&self, access: ComponentAccess, type_path: &str) -> Result<(), PermissionDenied> {
+        if self.is_allowed(access, type_path) {
+            Ok(())
+        } else {
+            Err(PermissionDenied {
+                access,
+                type_path: type_path.to_string(),
+            })
+        }
+    }
+
+    /// Returns the permissions of a mod, which allow everything when the mod has none
+    pub fn of(mod_id: Entity, world: &World) -> Self {
+        world
+            .get::<ComponentPermissions>(mod_id)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// The capabilities these permissions grant, reported to mods by `mod-info`.
+    ///
+    /// Each access is `<access>:*` when any component is allowed, or `<access>:<type path>` for
+    /// each allowed component otherwise. Components denied despite `*` are `!<access>:<type path>`.
+    pub fn capabilities(&self) -> Vec<String> {
+        let mut capabilities = Vec::new();
+        for access in [
+            ComponentAccess::Query,
+            ComponentAccess::Insert,
+            ComponentAccess::Mutate,
+        ] {
+            let list = self.list(access);
+            let mut denied: Vec<&TypePath> = list.deny.iter().collect();
+            denied.sort();
+            match &list.allow {
+                None => {
+                    capabilities.push(format!("{access}:*"));
+                    capabilities.extend(denied.iter().map(|ty| format!("!{access}:{ty}")));
+                }
+                Some(allow) => {
+                    let mut allowed: Vec<&TypePath> =
+                        allow.iter().filter(|ty| !denied.contains(ty)).collect();
+                    allowed.sort();
+                    capabilities.extend(allowed.iter().map(|ty| format!("{access}:{ty}")));
+                }
+            }
+        }
+        capabilities
+    }
+
+    fn list(&self, access: ComponentAccess) -> &TypeList {
+        match access {
+            ComponentAccess::Query => &self.query,
+            ComponentAccess::Insert => &self.insert,
+            ComponentAccess::Mutate => &self.mutate,
+        }
+    }
+
+    fn list_mut(&mut self, access: ComponentAccess) -> &mut TypeList {
+        match access {
+            ComponentAccess::Query => &mut self.query,
+            ComponentAccess::Insert => &mut self.insert,
+            ComponentAccess::Mutate => &mut self.mutate,
+        }
+    }
+}
+
+/// The error produced when a mod accesses a component its [ComponentPermissions] don't allow
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PermissionDenied {
+    pub access: ComponentAccess,
+    pub type_path: TypePath,
+}
+
+impl std::error::Error for PermissionDenied {}
+
+impl fmt::Display for PermissionDenied {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Mod is not permitted to {} component {}",
+            self.access, self.type_path
+        )
+    }
+}
+
+/// Restricts which assets a [Mod](crate::mods::Mod) may load, such as the scenes it spawns with
+/// `commands.spawn-scene`.
+///
+/// Insert this component on a mod's entity. Unlike [ComponentPermissions], mods without it may not
+/// load any assets, since asset paths reach outside the world.
+///
+/// ```
+/// # use bevy_ecs::prelude::*;
+/// # use wasvy_runtime::prelude::*;
+/// fn setup(mut mods: Mods, mut commands: Commands) {
+///     let mod_id = mods.spawn("mods/dungeon.wasm", None);
+///     commands
+///         .entity(mod_id)
+///         .insert(AssetPermissions::default().allow_root("scenes/dungeon"));
+///     mods.enable_access(mod_id, ModAccess::World);
+/// }
+/// ```
+#[derive(Component, Debug, Clone, Default, PartialEq, Eq)]
+pub struct AssetPermissions {
+    roots: Vec<PathBuf>,
+}
+
+impl AssetPermissions {
+    /// Allows loading any asset under `root`. Can be called multiple times to allow more.
+    pub fn allow_root(mut self, root: impl Into<PathBuf>) -> Self {
+        self.roots.push(root.into());
+        self
+    }
+
+    /// Returns whether an asset may be loaded. Paths leaving their root with `..` never are.
+    pub fn is_allowed(&self, path: &str) -> bool {
+        let path = Path::new(path);
+        let normal = path
+            .components()
+            .all(|component| matches!(component, PathComponent::Normal(_) | PathComponent::CurDir));
+        normal && self.roots.iter().any(|root| path.starts_with(root))
+    }
+
+    /// Like [Self::is_allowed], but returns an error describing the denied asset
+    pub fn check(&self, path: &str) -> Result<(), AssetPermissionDenied> {
+        if self.is_allowed(path) {
+            Ok(())
+        } else {
+            Err(AssetPermissionDenied {
+                path: path.to_string(),
+            })
+        }
+    }
+
+    /// The capabilities these permissions grant, as `asset:<root>` for each root
+    pub fn capabilities(&self) -> Vec<String> {
+        self.roots
+            .iter()
+            .map(|root| format!("asset:{}", root.display()))
+            .collect()
+    }
+
+    /// Returns the asset permissions of a mod, which allow nothing when the mod has none
+    pub fn of(mod_id: Entity, world: &World) -> Self {
+        world
+            .get::<AssetPermissions>(mod_id)
+            .cloned()
+            .unwrap_or_default()
+    }
+}
+
+/// The error produced when a mod loads an asset its [AssetPermissions] don't allow
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AssetPermissionDenied {
+    pub path: String,
+}
+
+impl std::error::Error for AssetPermissionDenied {}
+
+impl fmt::Display for AssetPermissionDenied {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Mod is not permitted to load asset {}", self.path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deny_takes_priority() {
+        let permissions = ComponentPermissions::default()
+            .allow(ComponentAccess::Query, ["a", "b"])
+            .deny(ComponentAccess::Query, ["b"])
+            .deny(ComponentAccess::Insert, ["a"]);
+
+        assert!(permissions.is_allowed(ComponentAccess::Query, "a"));
+        assert!(!permissions.is_allowed(ComponentAccess::Query, "b"));
+        assert!(!permissions.is_allowed(ComponentAccess::Query, "c"));
+        assert!(!permissions.is_allowed(ComponentAccess::Insert, "a"));
+        assert!(permissions.is_allowed(ComponentAccess::Insert, "c"));
+        assert!(permissions.is_allowed(ComponentAccess::Mutate, "b"));
+        assert_eq!(
+            permissions.check(ComponentAccess::Query, "c"),
+            Err(PermissionDenied {
+                access: ComponentAccess::Query,
+                type_path: "c".into()
+            })
+        );
+    }
+
+    #[test]
+    fn assets_are_allowed_under_their_roots() {
+        assert!(!AssetPermissions::default().is_allowed("scenes/a.scn.ron"));
+
+        let permissions = AssetPermissions::default().allow_root("scenes/dungeon");
+        assert!(permissions.is_allowed("scenes/dungeon/a.scn.ron"));
+        assert!(permissions.is_allowed("scenes/dungeon/rooms/b.scn.ron"));
+        assert!(!permissions.is_allowed("scenes/dungeon2/a.scn.ron"));
+        assert!(!permissions.is_allowed("scenes/dungeon/../secret.scn.ron"));
+        assert!(!permissions.is_allowed("/scenes/dungeon/a.scn.ron"));
+        assert_eq!(
+            permissions.check("scenes/a.scn.ron"),
+            Err(AssetPermissionDenied {
+                path: "scenes/a.scn.ron".into()
+            })
+        );
+    }
+
+    #[test]
+    fn lists_granted_capabilities() {
+        let permissions = ComponentPermissions::default()
+            .allow(ComponentAccess::Query, ["b", "a"])
+            .deny(ComponentAccess::Query, ["b"])
+            .deny(ComponentAccess::Mutate, ["c"]);
+        assert_eq!(
+            permissions.capabilities(),
+            ["query:a", "insert:*", "mutate:*", "!mutate:c"]
+        );
+
+        let permissions = AssetPermissions::default().allow_root("scenes/dungeon");
+        assert_eq!(permissions.capabilities(), ["asset:scenes/dungeon"]);
+    }
+}
@@ -1,25 +1,37 @@
-use std::sync::Mutex;
+use std::{num::NonZeroUsize, sync::Mutex};
 
 use bevy_app::prelude::*;
+#[cfg(not(feature = "headless"))]
 use bevy_asset::prelude::*;
 use bevy_ecs::reflect::AppFunctionRegistry;
 use bevy_ecs::reflect::AppTypeRegistry;
-use bevy_ecs::{intern::Interned, schedule::ScheduleLabel};
+use bevy_ecs::{
+    intern::Interned,
+    schedule::{IntoScheduleConfigs, ScheduleLabel, SystemSet},
+};
 use bevy_log::prelude::*;
 
+#[cfg(not(feature = "headless"))]
 use crate::app_extend::AppExtend;
 use crate::{
-    asset::ModAsset,
-    authoring::AutoRegistrationPlugin,
-    cleanup::{DespawnModEntities, DisableSystemSet, disable_mod_system_sets},
-    component::WasmComponentRegistry,
+    asset::init_mod_assets,
+    authoring::{AutoRegistrationPlugin, ExportLevel},
+    budget::{ModFrameBudgets, reset_frame_budgets},
+    cleanup::{DespawnModEntities, DisableSystemSet, TransferModEntities, disable_mod_system_sets},
+    component::{TypeAliasRegistry, WasmComponentRegistry},
     devtools,
+    discovery::{ModDiscovered, load_scanned_mods},
+    entry_points::ModEntryPoints,
+    failure::{ModFailurePolicy, ModQuarantined},
+    limits::ModLimits,
     methods::FunctionIndex,
-    mods::{Mod, ModDespawnBehaviour},
-    sandbox::Sandboxed,
-    schedule::{ModSchedule, ModSchedules, ModStartup},
+    mods::{Mod, ModDespawnBehaviour, ModState, ModStateChanged},
+    registry::{RegistryChanged, notify_registry_changed},
+    sandbox::{Sandboxed, reset_sandbox_spawns},
+    schedule::{HostSystemSets, ModConcurrency, ModSchedule, ModSchedules, ModStartup},
     serialize::{CodecResource, WasvyCodec},
-    setup::run_setup,
+    setup::{ReloadMod, run_setup},
+    verify::ModVerifier,
 };
 
 /// Adds Wasvy runtime support to a [`bevy_app::App`].
@@ -94,6 +106,16 @@ struct Inner {
     despawn_behaviour: ModDespawnBehaviour,
     devtools_config: Option<devtools::Devtools>,
     codec: Option<CodecResource>,
+    concurrency: ModConcurrency,
+    limits: ModLimits,
+    failure_policy: ModFailurePolicy,
+    strict_params: Option<bool>,
+    export_level: ExportLevel,
+    entry_points: ModEntryPoints,
+    host_system_sets: HostSystemSets,
+    verifier: Option<ModVerifier>,
+    frame_budgets: ModFrameBudgets,
+    aliases: TypeAliasRegistry,
 }
 
 impl Default for ModRuntimePlugin {
@@ -125,6 +147,16 @@ impl ModRuntimePlugin {
             codec: Some(CodecResource::default()),
             #[cfg(not(feature = "serde_json"))]
             codec: None,
+            concurrency: ModConcurrency::default(),
+            limits: ModLimits::default(),
+            failure_policy: ModFailurePolicy::default(),
+            strict_params: None,
+            export_level: ExportLevel::ModdingApi,
+            entry_points: ModEntryPoints::default(),
+            host_system_sets: HostSystemSets::default(),
+            verifier: None,
+            frame_budgets: ModFrameBudgets::default(),
+            aliases: TypeAliasRegistry::default(),
         };
 
         ModRuntimePlugin(Mutex::new(Some(inner)))
@@ -169,7 +201,8 @@ impl ModRuntimePlugin {
     /// Sets the despawn behaviour for when mods are despawned (or reloaded).
     ///
     /// The default behaviour is to despawn all entities the mod spawned.
-    /// See [DespawnEntities](ModDespawnBehaviour::DespawnEntities).
+    /// See [DespawnWithMod](ModDespawnBehaviour::DespawnWithMod). Override it for a single mod with
+    /// [Mods::set_despawn_behaviour](crate::mods::Mods::set_despawn_behaviour).
     pub fn set_despawn_behaviour(mut self, despawn_behaviour: ModDespawnBehaviour) -> Self {
         let inner = self.inner();
         inner.despawn_behaviour = despawn_behaviour;
@@ -213,6 +246,120 @@ impl ModRuntimePlugin {
         self
     }
 
+    /// Caps how many mod systems may run concurrently in each schedule.
+    ///
+    /// Mod systems are assigned round-robin to `lanes` chains per schedule, so hosts with tight
+    /// frame budgets can bound worst-case contention. By default mod systems are not capped.
+    ///
+    /// See [`ModConcurrency`].
+    pub fn max_concurrent_systems(mut self, lanes: NonZeroUsize) -> Self {
+        let inner = self.inner();
+        inner.concurrency = ModConcurrency::new(lanes);
+        self
+    }
+
+    /// Bounds the number of systems and queries a single mod can register.
+    ///
+    /// Mods that exceed these fail their setup with a [`LimitExceeded`](crate::limits::LimitExceeded)
+    /// error. The defaults are generous, see [`ModLimits`].
+    pub fn limits(mut self, limits: ModLimits) -> Self {
+        let inner = self.inner();
+        inner.limits = limits;
+        self
+    }
+
+    /// Decides when mods that keep trapping are quarantined.
+    ///
+    /// By default a mod is quarantined after trapping 3 runs in a row, see [`ModFailurePolicy`].
+    pub fn failure_policy(mut self, policy: ModFailurePolicy) -> Self {
+        let inner = self.inner();
+        inner.failure_policy = policy;
+        self
+    }
+
+    /// Validates the params mods pass to component methods before invoking them.
+    ///
+    /// Defaults to enabled in debug builds. See [`FunctionIndex::set_strict_params`].
+    pub fn strict_params(mut self, strict: bool) -> Self {
+        let inner = self.inner();
+        inner.strict_params = Some(strict);
+        self
+    }
+
+    /// The least visible [`ExportLevel`] of the components mods can use.
+    ///
+    /// Defaults to [`ExportLevel::ModdingApi`]. Use [`ExportLevel::Internal`] for apps that only
+    /// run first-party tooling mods, along with the same level in
+    /// [`WitGeneratorSettings`](crate::witgen::WitGeneratorSettings).
+    pub fn export_level(mut self, level: ExportLevel) -> Self {
+        let inner = self.inner();
+        inner.export_level = level;
+        self
+    }
+
+    /// Defines the exports the host calls on mods, such as `on-save` and `on-load`.
+    ///
+    /// Mods lacking a required export fail to load. By default mods only need a `setup` export,
+    /// see [`ModEntryPoints`].
+    pub fn entry_points(mut self, entry_points: ModEntryPoints) -> Self {
+        let inner = self.inner();
+        inner.entry_points = entry_points;
+        self
+    }
+
+    /// Lets mods order their systems before or after a host system set, referring to it as `name`.
+    ///
+    /// ```
+    /// # use bevy_ecs::prelude::*;
+    /// # use wasvy_runtime::prelude::*;
+    /// #[derive(SystemSet, Clone, Debug, PartialEq, Eq, Hash)]
+    /// struct PhysicsSet;
+    ///
+    /// // Mods call `system.after-set("physics")`
+    /// let runtime = ModRuntimePlugin::default().host_system_set("physics", PhysicsSet);
+    /// # let _ = runtime;
+    /// ```
+    pub fn host_system_set(mut self, name: impl ToString, set: impl SystemSet) -> Self {
+        let inner = self.inner();
+        inner.host_system_sets.insert(name, set);
+        self
+    }
+
+    /// Checks mods before they are compiled, rejecting those that fail to load. See [ModVerifier].
+    ///
+    /// By default mods are not verified.
+    pub fn set_verifier(mut self, verifier: ModVerifier) -> Self {
+        let inner = self.inner();
+        inner.verifier = Some(verifier);
+        self
+    }
+
+    /// Spreads the systems of mods across frames, running them within a time budget per frame.
+    ///
+    /// See [ModFrameBudgets], which can also be changed at runtime. By default mods have no budget.
+    pub fn frame_budgets(mut self, budgets: ModFrameBudgets) -> Self {
+        let inner = self.inner();
+        inner.frame_budgets = budgets;
+        self
+    }
+
+    /// Lets mods refer to a component or resource by `alias` instead of its full type path.
+    ///
+    /// ```
+    /// # use wasvy_runtime::plugin::ModRuntimePlugin;
+    /// // Mods may query "Transform" instead
+    /// let runtime = ModRuntimePlugin::default()
+    ///     .alias("Transform", "bevy_transform::components::transform::Transform");
+    /// # let _ = runtime;
+    /// ```
+    ///
+    /// See [TypeAliasRegistry].
+    pub fn alias(mut self, alias: impl Into<String>, type_path: impl Into<String>) -> Self {
+        let inner = self.inner();
+        inner.aliases.insert(alias, type_path);
+        self
+    }
+
     fn inner(&mut self) -> &mut Inner {
         self.0
             .get_mut()
@@ -230,6 +377,16 @@ impl Plugin for ModRuntimePlugin {
             despawn_behaviour,
             devtools_config,
             codec,
+            concurrency,
+            limits,
+            failure_policy,
+            strict_params,
+            export_level,
+            entry_points,
+            host_system_sets,
+            verifier,
+            frame_budgets,
+            aliases,
         } = self
             .0
             .lock()
@@ -237,20 +394,49 @@ impl Plugin for ModRuntimePlugin {
             .take()
             .expect("ModRuntimePlugin is not built");
 
-        if despawn_behaviour == ModDespawnBehaviour::DespawnEntities {
-            // Registers a component that tracks mod entities and despawns them when the mod despawns
-            app.register_required_components::<Mod, DespawnModEntities>();
-        }
+        debug!("Mod limits: {limits}");
 
-        app.init_asset::<ModAsset>()
-            .insert_resource(despawn_behaviour)
+        // Registers the components that track mod entities, to despawn or transfer them when the
+        // mod despawns. See ModDespawnBehaviour.
+        app.register_required_components::<Mod, DespawnModEntities>()
+            .register_required_components::<Mod, TransferModEntities>();
+
+        init_mod_assets(app);
+        if let Some(verifier) = verifier {
+            app.insert_resource(verifier);
+        }
+        app.insert_resource(despawn_behaviour)
             .insert_resource(codec.expect("WasvyCodec is necessary"))
             .init_resource::<WasmComponentRegistry>()
             .init_resource::<AppTypeRegistry>()
             .insert_resource(schedules)
+            .insert_resource(concurrency)
+            .insert_resource(limits)
+            .insert_resource(failure_policy)
+            .insert_resource(entry_points)
+            .insert_resource(host_system_sets)
+            .insert_resource(frame_budgets)
+            .insert_resource(aliases)
+            .register_type::<ModState>()
             .add_schedule(ModStartup::new_schedule())
             .add_message::<DisableSystemSet>()
-            .add_systems(setup_schedule, (run_setup, disable_mod_system_sets))
+            .add_message::<RegistryChanged>()
+            .add_message::<ReloadMod>()
+            .add_message::<ModQuarantined>()
+            .add_message::<ModStateChanged>()
+            .add_message::<ModDiscovered>()
+            .add_systems(
+                setup_schedule,
+                // Systems are disabled first, since reloaded mods add them again during setup
+                (
+                    load_scanned_mods,
+                    disable_mod_system_sets,
+                    run_setup,
+                    notify_registry_changed,
+                )
+                    .chain(),
+            )
+            .add_systems(First, (reset_sandbox_spawns, reset_frame_budgets))
             .add_plugins(AutoRegistrationPlugin);
 
         if let Some(config) = devtools_config {
@@ -261,18 +447,25 @@ impl Plugin for ModRuntimePlugin {
             );
         }
 
-        app.insert_resource(FunctionIndex::build(
+        let mut function_index = FunctionIndex::build_with_level(
             app.world()
                 .get_resource::<AppTypeRegistry>()
                 .expect("AppTypeRegistry to be initialized"),
             app.world()
                 .get_resource::<AppFunctionRegistry>()
                 .expect("AppFunctionRegistry to be initialized"),
-        ));
+            export_level,
+        );
+        if let Some(strict) = strict_params {
+            function_index.set_strict_params(strict);
+        }
+        app.insert_resource(function_index);
 
         app.world_mut().register_component::<Sandboxed>();
     }
 
+    // Headless apps don't hot-reload mods, and may not even have an AssetPlugin
+    #[cfg(not(feature = "headless"))]
     fn finish(&self, app: &mut App) {
         let asset_plugin: &AssetPlugin = app.plugin();
 
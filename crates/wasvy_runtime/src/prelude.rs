@@ -1,16 +1,41 @@
 pub use crate::access::ModAccess;
 pub use crate::asset::ModAsset;
+pub use crate::asset_root::ModAssetRoot;
 pub use crate::authoring::{
-    AutoRegistrationPlugin, WasvyComponent, WasvyComponentPlugin, WasvyExport, WasvyMethods,
-    WasvyMethodsPlugin,
+    AutoRegistrationPlugin, ExportComponentExt, ExportLevel, ExportMethodsExt, WasvyComponent,
+    WasvyComponentPlugin, WasvyExport, WasvyMethods, WasvyMethodsPlugin, WasvyResource,
+    WasvyResourcePlugin,
 };
+pub use crate::budget::ModFrameBudgets;
+pub use crate::callbacks::ModCallbacks;
 #[cfg(feature = "devtools")]
 pub use crate::devtools::Devtools;
+pub use crate::discovery::ModDiscovered;
+pub use crate::entry_points::ModEntryPoints;
+pub use crate::failure::{ModFailurePolicy, ModQuarantined};
+#[cfg(feature = "serde_json")]
+pub use crate::guest_witgen::{GuestWitPlugin, GuestWitSettings};
+pub use crate::limits::{ModLimits, ModMemoryLimits};
+pub use crate::manifest::ModManifest;
+pub use crate::messages::{AppModMessageExt, ReflectModMessage};
+#[cfg(feature = "serde_json")]
+pub use crate::methods::WorldMethodExt;
 pub use crate::methods::{FunctionAccess, FunctionIndex};
-pub use crate::mods::{Mod, ModDespawnBehaviour, ModSystemSet, Mods};
+pub use crate::mods::{
+    Mod, ModDespawnBehaviour, ModDespawnPolicy, ModInfo, ModState, ModStateChanged, ModSystemSet,
+    Mods,
+};
+pub use crate::package::ModPackage;
+pub use crate::permissions::{AssetPermissions, ComponentAccess, ComponentPermissions};
 pub use crate::plugin::ModRuntimePlugin;
-pub use crate::sandbox::Sandbox;
-pub use crate::schedule::{ModSchedule, ModSchedules};
+pub use crate::sandbox::{RelationPolicy, Sandbox, SandboxOverrides, SandboxQuota};
+pub use crate::save::ModSaveData;
+pub use crate::schedule::{HostSystemSets, ModConcurrency, ModSchedule, ModSchedules};
+#[cfg(feature = "serde_json")]
+pub use crate::schema::{ComponentSchema, SchemaCheckPlugin, WasmComponentSchemas};
 pub use crate::serialize::WasvyCodec;
+pub use crate::storage::ModStorage;
+pub use crate::sync::ModSync;
+pub use crate::verify::ModVerifier;
 pub use crate::witgen::{WitGeneratorPlugin, WitGeneratorSettings};
-pub use bevy_ecs::schedule::ScheduleLabel;
+pub use bevy_ecs::schedule::{ScheduleLabel, SystemSet};
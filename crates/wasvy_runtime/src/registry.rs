@@ -0,0 +1,152 @@
+//! Notifies mods about components exported after they were setup.
+//!
+//! Plugins added late (such as DLC) may register new [`WasvyExport`] components after mods were
+//! already setup. Wasvy detects these, rebuilds the [`FunctionIndex`], sends a [`RegistryChanged`]
+//! message and lets each mod's backend know so mods can register new systems against them.
+//!
+//! Mods may also list what the host exports at any time with `app.list-components`, see
+//! [`list_components`].
+
+use bevy_asset::AssetId;
+use bevy_ecs::{
+    prelude::*,
+    reflect::{AppFunctionRegistry, AppTypeRegistry},
+};
+use bevy_log::prelude::*;
+use bevy_platform::collections::HashSet;
+use bevy_reflect::{TypeInfo, TypeRegistry};
+
+use crate::{
+    access::ModAccess,
+    asset::ModAsset,
+    authoring::{ExportLevel, WasvyExport},
+    component::TypeAliasRegistry,
+    methods::FunctionIndex,
+    mods::Mod,
+};
+
+/// Sent when new components were exported to mods after the app started.
+#[derive(Message, Debug, Clone)]
+pub struct RegistryChanged {
+    /// Type paths of the newly exported components, sorted
+    pub type_paths: Vec<String>,
+}
+
+/// Describes a component or resource exported to mods, see [`list_components`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ComponentInfo {
+    /// The full type path of the type
+    pub type_path: String,
+
+    /// The alias of the type in the [`TypeAliasRegistry`], or its short type path if it has none
+    pub short_name: String,
+
+    /// Whether the type has methods mods may invoke
+    pub has_methods: bool,
+
+    /// The names of the fields of the type, if it is a struct with named fields
+    pub fields: Option<Vec<String>>,
+}
+
+/// Lists the components and resources in the [`FunctionIndex`], sorted by type path.
+///
+/// This lets generic tooling mods, such as inspectors and debug consoles, discover what the host
+/// exports at runtime instead of hard-coding type paths.
+pub fn list_components(
+    index: &FunctionIndex,
+    type_registry: &TypeRegistry,
+    aliases: &TypeAliasRegistry,
+) -> Vec<ComponentInfo> {
+    index
+        .components()
+        .map(|type_path| {
+            let type_info = type_registry
+                .get_with_type_path(type_path)
+                .map(|registration| registration.type_info());
+            let short_name = aliases
+                .iter()
+                .filter(|(_, aliased)| *aliased == type_path)
+                .map(|(alias, _)| alias)
+                .min()
+                .or_else(|| type_info.map(|info| info.type_path_table().short_path()))
+                .unwrap_or(type_path)
+                .to_string();
+            let fields = match type_info {
+                Some(TypeInfo::Struct(info)) => {
+                    Some(info.iter().map(|field| field.name().to_string()).collect())
+                }
+                _ => None,
+            };
+
+            ComponentInfo {
+                type_path: type_path.to_string(),
+                short_name,
+                has_methods: index.methods_for(type_path).next().is_some(),
+                fields,
+            }
+        })
+        .collect()
+}
+
+pub(crate) fn notify_registry_changed(
+    world: &mut World,
+    mut known: Local<Option<HashSet<String>>>,
+) {
+    let type_registry = world.resource::<AppTypeRegistry>().clone();
+    let level = world
+        .get_resource::<FunctionIndex>()
+        .map_or(ExportLevel::ModdingApi, FunctionIndex::export_level);
+    let exported: HashSet<String> = type_registry
+        .read()
+        .iter_with_data::<WasvyExport>()
+        .filter(|(_, export)| export.level <= level)
+        .map(|(registration, _)| registration.type_info().type_path().to_string())
+        .collect();
+
+    // The first run only takes note of what was already exported
+    let Some(known) = known.as_mut() else {
+        *known = Some(exported);
+        return;
+    };
+
+    let mut type_paths: Vec<String> = exported.difference(known).cloned().collect();
+    if type_paths.is_empty() {
+        return;
+    }
+    type_paths.sort();
+    *known = exported;
+
+    info!("Exported {} new components to mods", type_paths.len());
+
+    let function_registry = world.resource::<AppFunctionRegistry>().clone();
+    let mut index = FunctionIndex::build_with_level(&type_registry, &function_registry, level);
+    if let Some(previous) = world.get_resource::<FunctionIndex>() {
+        index.set_strict_params(previous.strict_params());
+    }
+    world.insert_resource(index);
+    world.write_message(RegistryChanged {
+        type_paths: type_paths.clone(),
+    });
+
+    let mods: Vec<(AssetId<ModAsset>, Entity, String, Vec<ModAccess>)> = world
+        .query::<(Entity, &Mod, Option<&Name>)>()
+        .iter(world)
+        .map(|(mod_id, mod_component, name)| {
+            let name = name
+                .map(|name| name.as_str())
+                .unwrap_or("unknown")
+                .to_string();
+            let accesses = mod_component.accesses().copied().collect();
+            (mod_component.asset().id(), mod_id, name, accesses)
+        })
+        .collect();
+
+    for (asset_id, mod_id, name, accesses) in mods {
+        // Mods that haven't loaded yet will see the new components when they are setup
+        if let Err(err) =
+            ModAsset::registry_changed(world, &asset_id, mod_id, &name, &accesses, &type_paths)
+        {
+            debug!("Mod \"{name}\" was not notified of new components: {err}");
+        }
+    }
+}
@@ -1,16 +1,28 @@
-use std::alloc::Layout;
+use std::{
+    alloc::Layout,
+    fmt,
+    sync::{
+        Arc,
+        atomic::{AtomicU64, AtomicUsize, Ordering},
+    },
+};
 
+use anyhow::{Result, anyhow};
 use bevy_ecs::{
     component::{ComponentCloneBehavior, ComponentDescriptor, ComponentId, StorageType},
     entity::EntityHashSet,
     lifecycle::HookContext,
     prelude::*,
     query::FilteredAccess,
-    relationship::Relationship,
+    relationship::{Relationship, RelationshipAccessor},
     world::{DeferredWorld, WorldId},
 };
 
-use crate::{cleanup::DisableSystemSet, mods::ModSystemSet, schedule::ModSchedules};
+use bevy_log::prelude::*;
+
+use crate::{
+    access::ModAccess, cleanup::DisableSystemSet, mods::ModSystemSet, schedule::ModSchedules,
+};
 
 /// Sandboxes are subsets of entities within a bevy [World] in which [Mods](crate::mods::Mod) can run exclusively.
 ///
@@ -20,16 +32,22 @@ use crate::{cleanup::DisableSystemSet, mods::ModSystemSet, schedule::ModSchedule
 /// - No entities that are in another Sandbox, even if the Sandbox is nested in this one
 ///
 /// A neat feature of sandboxes is that since systems of one sandbox do not conflict with those in another, bevy can run them in parallel.
+/// Each mod system keeps its own wasm instance, and its other params only read shared resources, so mod systems only conflict
+/// over the components and resources they query. This requires Bevy's `multi_threaded` feature, which is enabled by default.
 ///
 /// ## Security and Isolation
 ///
 /// **Loading a mod via a sandbox does not provide additional security!** Mods might have access to dangerous wasi apis (such as file io), and that doesn't change within a sandbox.
 /// The goal of sandboxes is simply to restrict mod access to certain entities in the world. There are no strong security guarantees.
 ///
-/// Note: Sandboxed mods can technically affect entities outside the sandbox via relations!
-/// No guards are in place to prevent mods from creating components that reference entities outside their sandbox. Thus component hooks can mutate a component on an entity within a sandbox when a mod in a different sandbox mutates a component.
+/// Note: Relationships (such as [ChildOf]) between entities in different sandboxes would let component hooks reach across them.
+/// By default such relationships are denied, see [RelationPolicy]. Components that reference entities without being
+/// relationships are not guarded.
 ///
-/// The intention is that an upcoming permissions system will solve this issue, giving fine-tuned access on what components mods can read from or mutate.
+/// ## Quotas
+///
+/// A buggy or malicious mod could spawn entities every frame until the app runs out of memory. Limit how many entities the
+/// mods in a sandbox may spawn with [Sandbox::with_quota], see [SandboxQuota].
 ///
 /// ## Example
 ///
@@ -158,6 +176,12 @@ pub struct Sandbox {
 
     /// The world this Sandbox belongs to
     world_id: WorldId,
+
+    /// Counts the spawns of mods in this sandbox, see [SandboxQuota]
+    spawns: SandboxSpawns,
+
+    /// Guards relationships crossing this sandbox
+    relation_policy: RelationPolicy,
 }
 
 impl Sandbox {
@@ -173,6 +197,7 @@ impl Sandbox {
         // Activate the propagation when the very first Sandbox is added to the world
         if count == 1 {
             world.add_observer(Sandboxed::propagate);
+            world.add_observer(Sandboxed::guard_relations);
         }
 
         let name = format!("Sandbox{count}");
@@ -206,9 +231,75 @@ impl Sandbox {
             access,
             world_id,
             schedules,
+            spawns: SandboxSpawns::default(),
+            relation_policy: RelationPolicy::default(),
         }
     }
 
+    /// Sets what happens to relationships between entities in this sandbox and entities outside of it
+    pub fn with_relation_policy(mut self, policy: RelationPolicy) -> Self {
+        self.relation_policy = policy;
+        self
+    }
+
+    /// Returns the [RelationPolicy] of this sandbox
+    pub fn relation_policy(&self) -> RelationPolicy {
+        self.relation_policy
+    }
+
+    /// Creates a [Sandbox] nested in `parent`, inheriting its settings unless `overrides` sets them.
+    ///
+    /// Inherits the [ModSchedules], [SandboxQuota] and [RelationPolicy] of the parent as they are
+    /// now, since sandboxes can't change once created. The quota is counted separately for each
+    /// sandbox. Fails if `parent` is not a sandbox.
+    ///
+    /// Spawn the new sandbox as a descendant of `parent` to nest its entities.
+    ///
+    /// ```
+    /// # use bevy_ecs::prelude::*;
+    /// # use wasvy_runtime::prelude::*;
+    /// # let mut world = World::new();
+    /// # world.register_component::<wasvy_runtime::sandbox::Sandboxed>();
+    /// let sandbox = Sandbox::new(&mut world, ModSchedules::default());
+    /// let parent = world.spawn(sandbox).id();
+    ///
+    /// let overrides = SandboxOverrides {
+    ///     relation_policy: Some(RelationPolicy::Warn),
+    ///     ..Default::default()
+    /// };
+    /// let nested = Sandbox::new_inherited(&mut world, parent, overrides).unwrap();
+    /// world.spawn((nested, ChildOf(parent)));
+    /// ```
+    pub fn new_inherited(
+        world: &mut World,
+        parent: Entity,
+        overrides: SandboxOverrides,
+    ) -> Result<Self> {
+        let parent = world
+            .get::<Sandbox>(parent)
+            .ok_or_else(|| anyhow!("Entity {parent} is not a sandbox"))?;
+        let schedules = overrides
+            .schedules
+            .unwrap_or_else(|| parent.schedules.clone());
+        let quota = overrides.quota.unwrap_or_else(|| parent.quota());
+        let relation_policy = overrides.relation_policy.unwrap_or(parent.relation_policy);
+
+        Ok(Self::new(world, schedules)
+            .with_quota(quota)
+            .with_relation_policy(relation_policy))
+    }
+
+    /// Limits how many entities the mods in this sandbox may spawn
+    pub fn with_quota(mut self, quota: SandboxQuota) -> Self {
+        self.spawns = SandboxSpawns::new(quota);
+        self
+    }
+
+    /// Returns the quota of this sandbox, see [Sandbox::with_quota]
+    pub fn quota(&self) -> SandboxQuota {
+        self.spawns.0.quota
+    }
+
     /// Returns the Schedules for
     pub fn schedules(&self) -> &ModSchedules {
         &self.schedules
@@ -414,6 +505,41 @@ impl Sandboxed {
         <Self as Relationship>::on_insert(world, ctx);
     }
 
+    /// An observer that applies the [RelationPolicy] to the relationships of entities joining a sandbox
+    fn guard_relations(insert: On<Insert, Sandboxed>, mut world: DeferredWorld) {
+        let entity = world.entity(insert.entity);
+        let mut denied = Vec::new();
+        for &component_id in entity.archetype().components() {
+            let Some(info) = world.components().get_info(component_id) else {
+                continue;
+            };
+            let Some(RelationshipAccessor::Relationship {
+                entity_field_offset,
+                ..
+            }) = info.relationship_accessor()
+            else {
+                continue;
+            };
+            let Ok(ptr) = entity.get_by_id(component_id) else {
+                continue;
+            };
+
+            // Safety: the offset is that of the Entity field of the relationship
+            let target = unsafe { *ptr.byte_add(*entity_field_offset).deref::<Entity>() };
+            let name = info.name().to_string();
+            if !check_relation(&world, insert.entity, target, &name) {
+                denied.push(component_id);
+            }
+        }
+
+        for component_id in denied {
+            world
+                .commands()
+                .entity(insert.entity)
+                .remove_by_id(component_id);
+        }
+    }
+
     /// Recursively sandbox the provided entity and its descendants
     fn add_children(entity: Entity, sandbox: Entity, world: &mut DeferredWorld) {
         // A sandbox should not be sandboxed in itself. Skip and continue with its children
@@ -436,6 +562,217 @@ impl Sandboxed {
     }
 }
 
+/// The settings a nested [Sandbox] sets itself instead of inheriting them, see [Sandbox::new_inherited]
+#[derive(Debug, Default, Clone)]
+pub struct SandboxOverrides {
+    pub schedules: Option<ModSchedules>,
+    pub quota: Option<SandboxQuota>,
+    pub relation_policy: Option<RelationPolicy>,
+}
+
+/// What happens to a relationship between an entity in a [Sandbox] and an entity outside of it.
+///
+/// Applies to relationships that mods insert, and to those of entities joining a sandbox. When a
+/// relationship crosses two sandboxes, the stricter of their policies applies. A relationship to
+/// the sandbox entity itself, such as the [ChildOf] of entities spawned by its mods, is always
+/// allowed.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum RelationPolicy {
+    /// Relationships may cross the sandbox
+    Allow,
+
+    /// Relationships may cross the sandbox, logging a warning
+    Warn,
+
+    /// Relationships crossing the sandbox are rejected or removed, logging an error
+    #[default]
+    Deny,
+}
+
+/// Checks a relationship from `entity` to `target` against the [RelationPolicy] of their
+/// sandboxes, logging according to the policy. Returns whether the relationship is allowed.
+pub fn check_relation(world: &World, entity: Entity, target: Entity, relationship: &str) -> bool {
+    let sandbox = world.get::<Sandboxed>(entity).map(Relationship::get);
+    let target_sandbox = world.get::<Sandboxed>(target).map(Relationship::get);
+    if sandbox == target_sandbox || sandbox == Some(target) {
+        return true;
+    }
+
+    let policy = [sandbox, target_sandbox]
+        .into_iter()
+        .flatten()
+        .filter_map(|sandbox| world.get::<Sandbox>(sandbox))
+        .map(Sandbox::relation_policy)
+        .max()
+        .unwrap_or(RelationPolicy::Allow);
+    match policy {
+        RelationPolicy::Allow => true,
+        RelationPolicy::Warn => {
+            warn!("{relationship} of {entity} relates it to {target} in another sandbox");
+            true
+        }
+        RelationPolicy::Deny => {
+            error!(
+                "{relationship} of {entity} would relate it to {target} in another sandbox, denying it"
+            );
+            false
+        }
+    }
+}
+
+/// Limits how many entities the mods in a [Sandbox] may spawn.
+///
+/// Spawns over the quota are rejected, which ends the mod system that made them until the next
+/// frame, and a warning naming the mod is logged at most every [QUOTA_WARNING_INTERVAL] frames.
+/// Unlike traps, rejected spawns never disable the mod.
+///
+/// ```
+/// # use bevy_ecs::prelude::*;
+/// # use wasvy_runtime::prelude::*;
+/// # let mut world = World::new();
+/// # world.register_component::<wasvy_runtime::sandbox::Sandboxed>();
+/// let sandbox = Sandbox::new(&mut world, ModSchedules::default()).with_quota(SandboxQuota {
+///     max_entities: Some(1000),
+///     max_spawns_per_frame: Some(50),
+/// });
+/// world.spawn(sandbox);
+/// ```
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct SandboxQuota {
+    /// How many entities the sandbox may hold, counting those spawned by the host
+    pub max_entities: Option<usize>,
+
+    /// How many entities the mods in the sandbox may spawn each frame
+    pub max_spawns_per_frame: Option<usize>,
+}
+
+impl fmt::Display for SandboxQuota {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let limit = |limit: Option<usize>| limit.map_or("unlimited".to_string(), |l| l.to_string());
+        write!(
+            f,
+            "{} entities, {} spawns per frame",
+            limit(self.max_entities),
+            limit(self.max_spawns_per_frame)
+        )
+    }
+}
+
+/// How many frames pass at least between two warnings about the same [Sandbox] exceeding its [SandboxQuota]
+pub const QUOTA_WARNING_INTERVAL: u64 = 60;
+
+/// Counts the spawns of the mods in a [Sandbox] to enforce its [SandboxQuota].
+///
+/// The counts are shared by every mod system running in the sandbox, and reset each frame.
+#[derive(Clone, Default)]
+pub struct SandboxSpawns(Arc<SpawnCounts>);
+
+#[derive(Default)]
+struct SpawnCounts {
+    quota: SandboxQuota,
+
+    /// The entities in the sandbox at the start of the frame
+    entities: AtomicUsize,
+
+    /// The entities mods spawned in the sandbox this frame
+    spawned: AtomicUsize,
+
+    /// The frames since the sandbox was created
+    frame: AtomicU64,
+
+    /// The frame from which on rejected spawns are logged again
+    next_warning: AtomicU64,
+}
+
+impl SandboxSpawns {
+    fn new(quota: SandboxQuota) -> Self {
+        Self(Arc::new(SpawnCounts {
+            quota,
+            ..Default::default()
+        }))
+    }
+
+    /// Returns the spawn counts of the sandbox a mod runs in, or [None] for [ModAccess::World]
+    pub fn of(access: &ModAccess, world: &World) -> Option<Self> {
+        match access {
+            ModAccess::Sandbox(sandbox) => world
+                .get::<Sandbox>(*sandbox)
+                .map(|sandbox| sandbox.spawns.clone()),
+            ModAccess::World => None,
+        }
+    }
+
+    /// Counts a spawn by a mod, or rejects it if the sandbox would exceed its [SandboxQuota]
+    pub fn spawn(&self, mod_name: &str, sandbox: Entity) -> Result<(), SandboxQuotaExceeded> {
+        let counts = &self.0;
+        let quota = counts.quota;
+        let entities = counts.entities.load(Ordering::Relaxed);
+        let within_quota = counts
+            .spawned
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |spawned| {
+                let per_frame = quota.max_spawns_per_frame.is_none_or(|max| spawned < max);
+                let total = quota
+                    .max_entities
+                    .is_none_or(|max| entities + spawned < max);
+                (per_frame && total).then_some(spawned + 1)
+            })
+            .is_ok();
+        if within_quota {
+            return Ok(());
+        }
+
+        let frame = counts.frame.load(Ordering::Relaxed);
+        let warn = counts
+            .next_warning
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |next| {
+                (frame >= next).then_some(frame + QUOTA_WARNING_INTERVAL)
+            })
+            .is_ok();
+        if warn {
+            warn!(
+                "Mod \"{mod_name}\" exceeded the quota of sandbox {sandbox} ({quota}), rejecting its spawns"
+            );
+        }
+
+        Err(SandboxQuotaExceeded { sandbox, quota })
+    }
+
+    fn reset(&self, entities: usize) {
+        let counts = &self.0;
+        counts.entities.store(entities, Ordering::Relaxed);
+        counts.spawned.store(0, Ordering::Relaxed);
+        counts.frame.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+/// Resets the spawn counts of every [Sandbox] at the start of each frame, see [SandboxQuota]
+pub(crate) fn reset_sandbox_spawns(sandboxes: Query<(&Sandbox, Option<&SandboxedEntities>)>) {
+    for (sandbox, entities) in sandboxes.iter() {
+        sandbox
+            .spawns
+            .reset(entities.map_or(0, |entities| entities.0.len()));
+    }
+}
+
+/// The error of a spawn rejected by [SandboxSpawns::spawn]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SandboxQuotaExceeded {
+    pub sandbox: Entity,
+    pub quota: SandboxQuota,
+}
+
+impl fmt::Display for SandboxQuotaExceeded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Sandbox {} exceeded its quota of {}",
+            self.sandbox, self.quota
+        )
+    }
+}
+
+impl std::error::Error for SandboxQuotaExceeded {}
+
 /// A hidden custom marker component for [Sandboxed] entities
 struct SandboxedMarker;
 
@@ -448,7 +785,7 @@ mod tests {
     use bevy_ecs::{prelude::*, relationship::RelationshipSourceCollection};
 
     use super::*;
-    use crate::schedule::ModSchedules;
+    use crate::{access::ModAccess, schedule::ModSchedules};
 
     fn setup() -> World {
         let mut world = World::new();
@@ -495,6 +832,27 @@ mod tests {
         );
     }
 
+    #[test]
+    fn access_contains_sandboxed() {
+        let mut world = setup();
+
+        let component = Sandbox::new(&mut world, ModSchedules::empty());
+        let sandbox = world.spawn(component).id();
+        let component = Sandbox::new(&mut world, ModSchedules::empty());
+        let other_sandbox = world.spawn(component).id();
+        let child = world.spawn_empty().insert(ChildOf(sandbox)).id();
+        let outside = world.spawn_empty().id();
+
+        let sandboxed = world.get::<Sandboxed>(child);
+        assert!(ModAccess::Sandbox(sandbox).contains(sandboxed));
+        assert!(!ModAccess::Sandbox(other_sandbox).contains(sandboxed));
+        assert!(!ModAccess::World.contains(sandboxed));
+
+        let sandboxed = world.get::<Sandboxed>(outside);
+        assert!(ModAccess::World.contains(sandboxed));
+        assert!(!ModAccess::Sandbox(sandbox).contains(sandboxed));
+    }
+
     #[test]
     fn reparent_sandboxed() {
         let mut world = setup();
@@ -599,6 +957,129 @@ mod tests {
         );
     }
 
+    #[test]
+    fn quota_rejects_spawns() {
+        let mut world = setup();
+
+        let quota = SandboxQuota {
+            max_entities: Some(3),
+            max_spawns_per_frame: Some(2),
+        };
+        let component = Sandbox::new(&mut world, ModSchedules::empty()).with_quota(quota);
+        let sandbox = world.spawn(component).id();
+        world.spawn(ChildOf(sandbox));
+
+        let mut reset = IntoSystem::into_system(reset_sandbox_spawns);
+        reset.initialize(&mut world);
+        reset.run((), &mut world).unwrap();
+
+        let spawns = SandboxSpawns::of(&ModAccess::Sandbox(sandbox), &world).unwrap();
+        assert!(spawns.spawn("test", sandbox).is_ok());
+        assert!(spawns.spawn("test", sandbox).is_ok());
+        assert_eq!(
+            spawns.spawn("test", sandbox),
+            Err(SandboxQuotaExceeded { sandbox, quota }),
+            "Only 2 spawns are allowed per frame"
+        );
+
+        world.spawn(ChildOf(sandbox));
+        reset.run((), &mut world).unwrap();
+        assert!(spawns.spawn("test", sandbox).is_ok());
+        assert!(
+            spawns.spawn("test", sandbox).is_err(),
+            "The sandbox may only hold 3 entities"
+        );
+
+        assert!(SandboxSpawns::of(&ModAccess::World, &world).is_none());
+    }
+
+    #[test]
+    fn relations_across_sandboxes() {
+        let mut world = setup();
+
+        let component = Sandbox::new(&mut world, ModSchedules::empty());
+        let sandbox = world.spawn(component).id();
+        let component = Sandbox::new(&mut world, ModSchedules::empty())
+            .with_relation_policy(RelationPolicy::Warn);
+        let lenient = world.spawn(component).id();
+        let inside = world.spawn(ChildOf(sandbox)).id();
+        let lenient_inside = world.spawn(ChildOf(lenient)).id();
+        let outside = world.spawn_empty().id();
+
+        assert!(check_relation(&world, inside, sandbox, "ChildOf"));
+        assert!(check_relation(&world, outside, outside, "ChildOf"));
+        assert!(!check_relation(&world, inside, outside, "ChildOf"));
+        assert!(check_relation(&world, lenient_inside, outside, "ChildOf"));
+        assert!(
+            !check_relation(&world, lenient_inside, inside, "ChildOf"),
+            "The stricter policy applies"
+        );
+
+        // Entities joining a sandbox lose relationships to entities outside of it
+        let joining = world.spawn(ChildOf(outside)).id();
+        world.entity_mut(joining).insert(Sandboxed(sandbox));
+        world.flush();
+        assert!(world.get::<ChildOf>(joining).is_none());
+    }
+
+    #[test]
+    fn nested_sandbox_inherits() {
+        let mut world = setup();
+
+        let quota = SandboxQuota {
+            max_entities: Some(10),
+            max_spawns_per_frame: None,
+        };
+        let component = Sandbox::new(&mut world, ModSchedules::empty())
+            .with_quota(quota)
+            .with_relation_policy(RelationPolicy::Allow);
+        let parent = world.spawn(component).id();
+
+        let overrides = SandboxOverrides {
+            relation_policy: Some(RelationPolicy::Warn),
+            ..Default::default()
+        };
+        let nested = Sandbox::new_inherited(&mut world, parent, overrides).unwrap();
+        assert!(nested.schedules().is_empty());
+        assert_eq!(nested.quota(), quota);
+        assert_eq!(nested.relation_policy(), RelationPolicy::Warn);
+
+        let outside = world.spawn_empty().id();
+        assert!(
+            Sandbox::new_inherited(&mut world, outside, SandboxOverrides::default()).is_err(),
+            "Only sandboxes can be inherited from"
+        );
+    }
+
+    #[test]
+    fn panic_sub_app_world_mismatch() {
+        use bevy_app::{App, AppLabel, SubApp};
+
+        #[derive(AppLabel, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+        struct ServerApp;
+
+        let result = std::panic::catch_unwind(move || {
+            let mut app = App::new();
+            app.insert_sub_app(ServerApp, SubApp::new());
+            app.world_mut().register_component::<Sandboxed>();
+            let server = app.sub_app_mut(ServerApp).world_mut();
+            server.register_component::<Sandboxed>();
+
+            let component = Sandbox::new(server, ModSchedules::empty());
+            let sandbox = server.spawn(component).id();
+            assert!(server.get::<Sandbox>(sandbox).is_some());
+
+            let component = Sandbox::new(server, ModSchedules::empty());
+            // Should panic, since the sandbox belongs to the world of the sub-app
+            app.world_mut().spawn(component);
+        });
+
+        assert!(
+            result.is_err(),
+            "Should panic when a Sandbox of a sub-app is spawned in the main world"
+        );
+    }
+
     #[test]
     fn panic_world_mismatch() {
         let result = std::panic::catch_unwind(move || {
@@ -0,0 +1,344 @@
+use std::collections::BTreeMap;
+
+use bevy_asset::Assets;
+use bevy_ecs::{entity::EntityHashMap, prelude::*};
+use bevy_log::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    access::ModAccess,
+    asset::ModAsset,
+    cleanup::{DespawnModEntities, TransferModEntities},
+    component::{WasmComponent, WasmComponentRegistry, insert_wasm_component},
+    manifest::{ModDetails, ModManifest},
+    mods::{InsertDespawnComponent, Mod},
+    sandbox::Sandbox,
+    storage::ModStorage,
+};
+
+/// The state of all mods, for the host to include in its save games.
+///
+/// Holds the [ModStorage] of each mod along with the entities it owns and their guest defined
+/// components ([WasmComponent]s). Components defined by the host are left to the host's own save
+/// system, as are entities mods spawned with the
+/// [Persist](crate::mods::ModDespawnBehaviour::Persist) behaviour.
+///
+/// Mods are keyed by the id of their [ModManifest], or the name in their [ModDetails] or their
+/// [Name] if they have none, so a save can be restored into another session once the same mods are
+/// loaded. Guest components are keyed by type path and registered again on restore, since their
+/// component ids differ between worlds.
+///
+/// Before extracting, mods may write state with `set-data` in an optional `on-save` export. After
+/// restoring, an optional `on-load` export lets them read it back.
+///
+/// ```
+/// # use bevy_ecs::prelude::*;
+/// # use wasvy_runtime::save::ModSaveData;
+/// fn save(world: &mut World) {
+///     let data = ModSaveData::extract(world);
+///     let json = serde_json::to_string(&data).unwrap();
+///     // Write json to a file...
+/// #   let _ = json;
+/// }
+///
+/// fn load(world: &mut World, json: &str) {
+///     let data: ModSaveData = serde_json::from_str(json).unwrap();
+///     data.restore(world);
+/// }
+/// ```
+#[derive(Serialize, Deserialize, Default, Clone, Debug, PartialEq, Eq)]
+pub struct ModSaveData {
+    pub mods: Vec<ModSave>,
+}
+
+/// The saved state of a single mod, see [ModSaveData]
+#[derive(Serialize, Deserialize, Default, Clone, Debug, PartialEq, Eq)]
+pub struct ModSave {
+    /// The id of the mod's [ModManifest], or the name in its [ModDetails] or its [Name] if it has
+    /// none
+    pub id: String,
+
+    /// The version of the mod, if it has a [ModManifest]
+    pub version: Option<String>,
+
+    /// The contents of the mod's [ModStorage]
+    pub storage: BTreeMap<String, Vec<u8>>,
+
+    /// The entities the mod owns
+    pub entities: Vec<SavedEntity>,
+}
+
+/// An entity owned by a mod, see [ModSaveData]
+#[derive(Serialize, Deserialize, Default, Clone, Debug, PartialEq, Eq)]
+pub struct SavedEntity {
+    /// The entity in the saved world, see [ModSaveData::restore]
+    pub entity: u64,
+
+    /// Whether the entity was a child of one of the mod's sandboxes
+    pub sandboxed: bool,
+
+    /// The serialized value of each guest defined component, keyed by type path
+    pub components: BTreeMap<String, Vec<u8>>,
+}
+
+impl ModSaveData {
+    /// Runs the `on-save` hook of every mod, then extracts their state
+    pub fn extract(world: &mut World) -> Self {
+        for mod_id in mod_ids(world) {
+            run_hook(world, mod_id, "on-save");
+        }
+
+        let mut registry: Vec<_> = world
+            .get_resource::<WasmComponentRegistry>()
+            .map(|registry| {
+                registry
+                    .iter()
+                    .map(|(type_path, id)| (type_path.clone(), *id))
+                    .collect()
+            })
+            .unwrap_or_default();
+        registry.sort();
+
+        let mut mods = Vec::new();
+        for mod_id in mod_ids(world) {
+            let entity = world.entity(mod_id);
+            let manifest = entity.get::<ModManifest>();
+            let Some(id) = mod_key(world, mod_id) else {
+                warn!("Mod ({mod_id}) has neither a manifest nor a name, so it was not saved");
+                continue;
+            };
+
+            let entities = owned_entities(world, mod_id)
+                .into_iter()
+                .map(|owned| {
+                    let entity = world.entity(owned);
+                    let components = registry
+                        .iter()
+                        .filter_map(|(type_path, component_id)| {
+                            let ptr = entity.get_by_id(*component_id).ok()?;
+
+                            // Safety: components in the registry are all WasmComponents
+                            let component = unsafe { ptr.deref::<WasmComponent>() };
+                            Some((type_path.clone(), component.serialized_value.clone()))
+                        })
+                        .collect();
+                    let sandboxed = entity
+                        .get::<ChildOf>()
+                        .is_some_and(|child_of| world.get::<Sandbox>(child_of.parent()).is_some());
+
+                    SavedEntity {
+                        entity: owned.to_bits(),
+                        sandboxed,
+                        components,
+                    }
+                })
+                .collect();
+
+            mods.push(ModSave {
+                id,
+                version: manifest.map(|manifest| manifest.version.to_string()),
+                storage: world
+                    .get::<ModStorage>(mod_id)
+                    .map(ModStorage::entries)
+                    .unwrap_or_default(),
+                entities,
+            });
+        }
+
+        Self { mods }
+    }
+
+    /// Restores the state of every mod that is currently spawned, then runs their `on-load` hook.
+    ///
+    /// The entities mods own are despawned and replaced by the saved ones. Returns a map from the
+    /// saved entities to the spawned ones, for the host to remap references it saved itself.
+    pub fn restore(&self, world: &mut World) -> EntityHashMap<Entity> {
+        let mut entity_map = EntityHashMap::default();
+
+        let mods: Vec<_> = mod_ids(world)
+            .into_iter()
+            .filter_map(|mod_id| Some((mod_key(world, mod_id)?, mod_id)))
+            .collect();
+        let mut restored = Vec::new();
+        for save in &self.mods {
+            let Some(&(_, mod_id)) = mods.iter().find(|(id, _)| *id == save.id) else {
+                warn!("Mod \"{}\" is not loaded, so its save was skipped", save.id);
+                continue;
+            };
+
+            let version = world
+                .get::<ModManifest>(mod_id)
+                .map(|manifest| manifest.version.to_string());
+            if save.version.is_some() && version != save.version {
+                warn!(
+                    "Mod \"{}\" was saved at version {:?}, but version {:?} is loaded",
+                    save.id, save.version, version
+                );
+            }
+
+            // Replace the entities the mod currently owns
+            for owned in owned_entities(world, mod_id) {
+                world.despawn(owned);
+            }
+
+            ModStorage::of(mod_id, world).replace(save.storage.clone());
+
+            let sandbox = world
+                .get::<Mod>(mod_id)
+                .and_then(|mod_component| {
+                    mod_component.accesses().find_map(|access| match access {
+                        ModAccess::Sandbox(sandbox) => Some(*sandbox),
+                        ModAccess::World => None,
+                    })
+                })
+                .filter(|sandbox| world.get::<Sandbox>(*sandbox).is_some());
+            let insert_despawn_component = InsertDespawnComponent::new(mod_id, world);
+            for saved in &save.entities {
+                let entity = world.spawn_empty().id();
+                if saved.sandboxed
+                    && let Some(sandbox) = sandbox
+                {
+                    world.entity_mut(entity).insert(ChildOf(sandbox));
+                }
+                insert_despawn_component.insert(&mut world.commands().entity(entity));
+                for (type_path, value) in &saved.components {
+                    insert_wasm_component(world, entity, type_path, value.clone());
+                }
+                world.flush();
+
+                if let Some(saved_entity) = Entity::try_from_bits(saved.entity) {
+                    entity_map.insert(saved_entity, entity);
+                }
+            }
+
+            restored.push(mod_id);
+        }
+
+        for mod_id in restored {
+            run_hook(world, mod_id, "on-load");
+        }
+
+        entity_map
+    }
+}
+
+pub(crate) fn mod_ids(world: &mut World) -> Vec<Entity> {
+    let mut query = world.query_filtered::<Entity, With<Mod>>();
+    let mut mod_ids: Vec<_> = query.iter(world).collect();
+    mod_ids.sort();
+    mod_ids
+}
+
+/// The key of a mod in a [ModSaveData]
+pub(crate) fn mod_key(world: &World, mod_id: Entity) -> Option<String> {
+    let entity = world.entity(mod_id);
+    entity
+        .get::<ModManifest>()
+        .map(|manifest| manifest.id.clone())
+        .or_else(|| {
+            entity
+                .get::<ModDetails>()
+                .map(|details| details.name.clone())
+        })
+        .or_else(|| entity.get::<Name>().map(|name| name.to_string()))
+}
+
+/// The entities a mod owns, sorted. See [ModDespawnBehaviour](crate::mods::ModDespawnBehaviour)
+pub(crate) fn owned_entities(world: &World, mod_id: Entity) -> Vec<Entity> {
+    let entity = world.entity(mod_id);
+    let mut owned: Vec<Entity> = entity
+        .get::<DespawnModEntities>()
+        .into_iter()
+        .flat_map(|entities| entities.iter())
+        .chain(
+            entity
+                .get::<TransferModEntities>()
+                .into_iter()
+                .flat_map(|entities| entities.iter()),
+        )
+        .collect();
+    owned.sort();
+    owned
+}
+
+/// Runs a hook of a mod, logging rather than failing when it can't
+fn run_hook(world: &mut World, mod_id: Entity, name: &str) {
+    let entity = world.entity(mod_id);
+    let mod_component = entity.get::<Mod>().expect("mod_id is a Mod");
+    let asset_id = mod_component.asset().id();
+    let accesses: Vec<_> = mod_component.accesses().copied().collect();
+    let mod_name = entity
+        .get::<Name>()
+        .map(|name| name.as_str())
+        .unwrap_or("unknown")
+        .to_string();
+
+    let loaded = world
+        .get_resource::<Assets<ModAsset>>()
+        .is_some_and(|assets| assets.contains(asset_id));
+    if !loaded {
+        warn!("Mod \"{mod_name}\" has not loaded yet, could not run \"{name}\"");
+        return;
+    }
+
+    if let Err(err) = ModAsset::run_hook(world, &asset_id, mod_id, &mod_name, &accesses, name) {
+        error!("Failed to run \"{name}\" of mod \"{mod_name}\": {err:?}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{cleanup::DespawnModEntity, component::get_wasm_component_id};
+
+    #[test]
+    fn restores_storage_and_guest_components() {
+        let mut world = World::new();
+        world.init_resource::<WasmComponentRegistry>();
+        world.register_component::<crate::sandbox::Sandboxed>();
+        world.init_resource::<Assets<ModAsset>>();
+
+        let mod_id = world
+            .spawn((Mod::new(Default::default()), Name::new("score-mod")))
+            .id();
+        ModStorage::of(mod_id, &mut world).set("score", vec![42]);
+
+        let entity = world.spawn_empty().id();
+        InsertDespawnComponent::new(mod_id, &world).insert(&mut world.commands().entity(entity));
+        insert_wasm_component(&mut world, entity, "guest::Score", b"7".to_vec());
+        world.flush();
+
+        let data = ModSaveData::extract(&mut world);
+        assert_eq!(
+            data,
+            ModSaveData {
+                mods: vec![ModSave {
+                    id: "score-mod".into(),
+                    version: None,
+                    storage: BTreeMap::from([("score".into(), vec![42])]),
+                    entities: vec![SavedEntity {
+                        entity: entity.to_bits(),
+                        sandboxed: false,
+                        components: BTreeMap::from([("guest::Score".into(), b"7".to_vec())]),
+                    }],
+                }],
+            }
+        );
+
+        ModStorage::of(mod_id, &mut world).set("score", vec![0]);
+        let entity_map = data.restore(&mut world);
+
+        assert!(world.get_entity(entity).is_err());
+        let restored = entity_map[&entity];
+        let component_id = get_wasm_component_id("guest::Score", &mut world);
+        let ptr = world.entity(restored).get_by_id(component_id).unwrap();
+        // Safety: guest::Score is a WasmComponent
+        let component = unsafe { ptr.deref::<WasmComponent>() };
+        assert_eq!(component.serialized_value, b"7");
+        assert_eq!(
+            ModStorage::of(mod_id, &mut world).get("score"),
+            Some(vec![42])
+        );
+        assert_eq!(world.get::<DespawnModEntity>(restored).unwrap().0, mod_id);
+    }
+}
@@ -1,7 +1,13 @@
 use bevy_app::prelude::*;
 use bevy_derive::{Deref, DerefMut};
-use bevy_ecs::{intern::Interned, prelude::*, schedule::ScheduleLabel};
-use bevy_platform::collections::HashSet;
+use std::num::NonZeroUsize;
+
+use bevy_ecs::{
+    intern::Interned,
+    prelude::*,
+    schedule::{ScheduleLabel, SystemSet},
+};
+use bevy_platform::collections::{HashMap, HashSet};
 
 /// This is an enum representing schedules in Bevy where mods can also be run.
 ///
@@ -142,3 +148,146 @@ impl ModSchedules {
         Self(HashSet::new())
     }
 }
+
+/// The host system sets mods can order their systems against, by name.
+///
+/// Mods refer to these sets by name via `system.before-set` and `system.after-set`. Unknown names
+/// fail the mod's setup.
+///
+/// Register sets via [ModRuntimePlugin::host_system_set](crate::plugin::ModRuntimePlugin::host_system_set).
+#[derive(Resource, Debug, Clone, Default)]
+pub struct HostSystemSets(HashMap<String, Interned<dyn SystemSet>>);
+
+impl HostSystemSets {
+    /// Lets mods order their systems against `set` by referring to it as `name`
+    pub fn insert(&mut self, name: impl ToString, set: impl SystemSet) {
+        self.0.insert(name.to_string(), set.intern());
+    }
+
+    /// Returns the set registered as `name`
+    pub fn get(&self, name: &str) -> Option<Interned<dyn SystemSet>> {
+        self.0.get(name).copied()
+    }
+
+    /// Returns the names of all registered sets
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.0.keys().map(String::as_str)
+    }
+}
+
+/// Caps how many mod systems can run concurrently in each schedule.
+///
+/// Mod systems are assigned round-robin to a fixed number of lanes per schedule. Systems in the same
+/// lane are chained, so at most `lanes` mod systems ever execute at the same time. This trades
+/// parallelism for predictable worst-case contention.
+///
+/// Each mod's systems are assigned in an order that respects their own ordering. Systems ordered
+/// against a host set (see [HostSystemSets]) aren't assigned to lanes, since chaining them could
+/// contradict the order of the host sets, so they aren't capped.
+///
+/// Adjust this via [ModRuntimePlugin::max_concurrent_systems](crate::plugin::ModRuntimePlugin::max_concurrent_systems).
+/// By default there is no cap.
+#[derive(Resource, Debug, Default)]
+pub struct ModConcurrency {
+    lanes: Option<NonZeroUsize>,
+    schedules: HashMap<Interned<dyn ScheduleLabel>, Lanes>,
+}
+
+#[derive(Debug, Default)]
+struct Lanes {
+    next: usize,
+    tails: Vec<Interned<dyn SystemSet>>,
+}
+
+impl ModConcurrency {
+    /// Creates a cap of `lanes` concurrent mod systems per schedule.
+    pub fn new(lanes: NonZeroUsize) -> Self {
+        Self {
+            lanes: Some(lanes),
+            schedules: HashMap::new(),
+        }
+    }
+
+    /// Returns the maximum number of mod systems that run concurrently per schedule, if capped.
+    pub fn lanes(&self) -> Option<NonZeroUsize> {
+        self.lanes
+    }
+
+    /// Assigns a mod system (identified by a set containing only that system) to the next lane of a schedule.
+    ///
+    /// Returns the set the system must run after, or None if it is the first in its lane (or there is no cap).
+    pub fn assign(
+        &mut self,
+        schedule: Interned<dyn ScheduleLabel>,
+        system: Interned<dyn SystemSet>,
+    ) -> Option<Interned<dyn SystemSet>> {
+        let lanes = self.lanes?.get();
+        let Lanes { next, tails } = self.schedules.entry(schedule).or_default();
+
+        let lane = *next % lanes;
+        *next += 1;
+
+        if let Some(tail) = tails.get_mut(lane) {
+            Some(std::mem::replace(tail, system))
+        } else {
+            tails.push(system);
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(SystemSet, Clone, Debug, PartialEq, Eq, Hash)]
+    struct System(usize);
+
+    #[test]
+    fn assigns_lanes_round_robin() {
+        let mut concurrency = ModConcurrency::new(NonZeroUsize::new(2).unwrap());
+        let update = Update.intern();
+        let after: Vec<_> = (0..5)
+            .map(|i| concurrency.assign(update, System(i).intern()))
+            .collect();
+
+        assert_eq!(
+            after,
+            vec![
+                None,
+                None,
+                Some(System(0).intern()),
+                Some(System(1).intern()),
+                Some(System(2).intern()),
+            ]
+        );
+
+        // Lanes are tracked per schedule
+        assert_eq!(
+            concurrency.assign(PostUpdate.intern(), System(5).intern()),
+            None
+        );
+    }
+
+    #[test]
+    fn looks_up_host_system_sets() {
+        let mut sets = HostSystemSets::default();
+        sets.insert("physics", System(0));
+        assert_eq!(sets.get("physics"), Some(System(0).intern()));
+        assert_eq!(sets.get("audio"), None);
+        assert_eq!(sets.names().collect::<Vec<_>>(), vec!["physics"]);
+    }
+
+    #[test]
+    fn uncapped_by_default() {
+        let mut concurrency = ModConcurrency::default();
+        assert_eq!(
+            concurrency.assign(Update.intern(), System(0).intern()),
+            None
+        );
+        assert_eq!(
+            concurrency.assign(Update.intern(), System(1).intern()),
+            None
+        );
+    }
+}
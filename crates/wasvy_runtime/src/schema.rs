@@ -0,0 +1,512 @@
+//! Schemas for guest-defined components.
+//!
+//! Guest components are stored as serialized JSON in a [`WasmComponent`], so the host has no idea
+//! what shape they should have. Registering a [`ComponentSchema`] in [`WasmComponentSchemas`]
+//! declares it, and the opt-in [`SchemaCheckPlugin`] then validates every guest component at the
+//! end of each frame. This catches partial writes and version skew between the host and mods, which
+//! is especially useful while developing migration logic.
+//!
+//! Mods can also declare the schema of their own components with `app.register-component-schema`.
+//! Schemas also give guest components a [reflected](bevy_reflect) shape, so inspectors and other
+//! host systems can view and edit their fields, see [`WasmComponentSchemas::reflect`].
+//!
+//! ```
+//! # use bevy_app::prelude::*;
+//! # use wasvy_runtime::schema::*;
+//! # let mut app = App::new();
+//! app.add_plugins(SchemaCheckPlugin);
+//! app.world_mut()
+//!     .resource_mut::<WasmComponentSchemas>()
+//!     .register(
+//!         "my_mod::Score",
+//!         ComponentSchema::object([("points", ComponentSchema::Number)]),
+//!     );
+//! ```
+
+use anyhow::{Context, Result, anyhow, bail};
+use bevy_app::{App, Last, Plugin};
+use bevy_ecs::prelude::*;
+use bevy_log::prelude::*;
+use bevy_platform::collections::{HashMap, HashSet};
+use bevy_reflect::{
+    PartialReflect, ReflectRef,
+    enums::{DynamicEnum, DynamicVariant},
+    list::DynamicList,
+    structs::DynamicStruct,
+    tuple::DynamicTuple,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Number, Value};
+
+use crate::component::{TypePath, WasmComponent, WasmComponentRegistry};
+
+/// The expected shape of the JSON stored in a [`WasmComponent`].
+///
+/// Mods send schemas as JSON, where an object lists its fields as pairs:
+///
+/// ```
+/// # use wasvy_runtime::schema::ComponentSchema;
+/// let schema: ComponentSchema =
+///     serde_json::from_str(r#"{"object": [["points", "number"], ["tags", {"list": "string"}]]}"#)
+///         .unwrap();
+/// assert_eq!(
+///     schema,
+///     ComponentSchema::object([
+///         ("points", ComponentSchema::Number),
+///         ("tags", ComponentSchema::List(Box::new(ComponentSchema::String))),
+///     ])
+/// );
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ComponentSchema {
+    /// Accepts any value
+    Any,
+    Bool,
+    Number,
+    String,
+    /// Accepts `null` or a value matching the inner schema
+    Optional(Box<ComponentSchema>),
+    /// An array where every element matches the inner schema
+    List(Box<ComponentSchema>),
+    /// An object with the given fields. Fields that aren't declared are allowed.
+    Object(Vec<(String, ComponentSchema)>),
+}
+
+impl ComponentSchema {
+    /// Creates an [`Object`](Self::Object) schema
+    pub fn object<S: Into<String>>(fields: impl IntoIterator<Item = (S, ComponentSchema)>) -> Self {
+        Self::Object(
+            fields
+                .into_iter()
+                .map(|(name, schema)| (name.into(), schema))
+                .collect(),
+        )
+    }
+
+    /// Parses a schema sent by a mod, see the [type docs](Self)
+    pub fn from_json(json: &str) -> Result<Self> {
+        serde_json::from_str(json).context("invalid component schema")
+    }
+
+    /// Checks a value against this schema.
+    ///
+    /// On failure, returns the path of the offending field (such as `stats.health`, empty for the
+    /// root) along with what went wrong.
+    pub fn validate(&self, value: &Value) -> Result<(), (String, String)> {
+        let mut path = Vec::new();
+        self.validate_at(value, &mut path)
+            .map_err(|message| (path.join("."), message))
+    }
+
+    fn validate_at(&self, value: &Value, path: &mut Vec<String>) -> Result<(), String> {
+        match (self, value) {
+            (Self::Any, _)
+            | (Self::Bool, Value::Bool(_))
+            | (Self::Number, Value::Number(_))
+            | (Self::String, Value::String(_))
+            | (Self::Optional(_), Value::Null) => Ok(()),
+            (Self::Optional(inner), value) => inner.validate_at(value, path),
+            (Self::List(inner), Value::Array(items)) => {
+                for (index, item) in items.iter().enumerate() {
+                    path.push(index.to_string());
+                    inner.validate_at(item, path)?;
+                    path.pop();
+                }
+                Ok(())
+            }
+            (Self::Object(fields), Value::Object(object)) => {
+                for (name, schema) in fields {
+                    path.push(name.clone());
+                    match object.get(name) {
+                        Some(value) => schema.validate_at(value, path)?,
+                        None if matches!(schema, Self::Optional(_) | Self::Any) => {}
+                        None => return Err("missing field".to_string()),
+                    }
+                    path.pop();
+                }
+                Ok(())
+            }
+            (schema, value) => Err(format!("expected {}, found {value}", schema.name())),
+        }
+    }
+
+    /// Converts a value matching this schema into a dynamic [reflected](bevy_reflect) value.
+    ///
+    /// Objects become [structs](DynamicStruct) with the declared fields, lists become
+    /// [lists](DynamicList), optionals become [Option]-like [enums](DynamicEnum), numbers
+    /// become [f64], and [Any](Self::Any) values become their JSON text.
+    pub fn to_reflect(&self, value: &Value) -> Result<Box<dyn PartialReflect>> {
+        self.validate(value)
+            .map_err(|(field, message)| anyhow!("invalid value at \"{field}\": {message}"))?;
+        Ok(self.to_reflect_unchecked(value))
+    }
+
+    fn to_reflect_unchecked(&self, value: &Value) -> Box<dyn PartialReflect> {
+        match (self, value) {
+            (Self::Bool, Value::Bool(value)) => Box::new(*value),
+            (Self::Number, Value::Number(value)) => Box::new(value.as_f64().unwrap_or_default()),
+            (Self::String, Value::String(value)) => Box::new(value.clone()),
+            (Self::Optional(_), Value::Null) => Box::new(DynamicEnum::new("None", ())),
+            (Self::Optional(inner), value) => {
+                let mut some = DynamicTuple::default();
+                some.insert_boxed(inner.to_reflect_unchecked(value));
+                Box::new(DynamicEnum::new("Some", DynamicVariant::Tuple(some)))
+            }
+            (Self::List(inner), Value::Array(items)) => Box::new(DynamicList::from_iter(
+                items.iter().map(|item| inner.to_reflect_unchecked(item)),
+            )),
+            (Self::Object(fields), Value::Object(object)) => {
+                let mut output = DynamicStruct::default();
+                for (name, schema) in fields {
+                    let value = object.get(name).unwrap_or(&Value::Null);
+                    output.insert_boxed(name, schema.to_reflect_unchecked(value));
+                }
+                Box::new(output)
+            }
+            // Any, and missing fields declared as any
+            (_, value) => Box::new(value.to_string()),
+        }
+    }
+
+    /// Converts a reflected value, such as one returned by [Self::to_reflect] and then edited,
+    /// back into JSON matching this schema.
+    ///
+    /// Fields of `original` that the schema doesn't declare are kept.
+    pub fn from_reflect(&self, value: &dyn PartialReflect, original: &Value) -> Result<Value> {
+        Ok(match (self, value.reflect_ref()) {
+            (Self::Bool, _) => Value::Bool(downcast::<bool>(value)?),
+            (Self::Number, _) => {
+                let number = downcast::<f64>(value)?;
+                Value::Number(Number::from_f64(number).context("numbers must be finite")?)
+            }
+            (Self::String, _) => Value::String(downcast::<String>(value)?),
+            (Self::Any, _) => {
+                let json = downcast::<String>(value)?;
+                serde_json::from_str(&json).context("invalid json")?
+            }
+            (Self::Optional(inner), ReflectRef::Enum(value)) => match value.variant_name() {
+                "None" => Value::Null,
+                "Some" => {
+                    let field = value.field_at(0).context("Some must have a value")?;
+                    inner.from_reflect(field, original)?
+                }
+                other => bail!("expected None or Some, found {other}"),
+            },
+            (Self::List(inner), ReflectRef::List(list)) => {
+                let originals = original.as_array();
+                Value::Array(
+                    list.iter()
+                        .enumerate()
+                        .map(|(index, item)| {
+                            let original = originals
+                                .and_then(|originals| originals.get(index))
+                                .unwrap_or(&Value::Null);
+                            inner
+                                .from_reflect(item, original)
+                                .with_context(|| format!("at {index}"))
+                        })
+                        .collect::<Result<_>>()?,
+                )
+            }
+            (Self::Object(fields), ReflectRef::Struct(value)) => {
+                let mut object = original.as_object().cloned().unwrap_or_else(Map::new);
+                for (name, schema) in fields {
+                    let field = value
+                        .field(name)
+                        .with_context(|| format!("missing field {name}"))?;
+                    let original = object.get(name).cloned().unwrap_or(Value::Null);
+                    let field = schema
+                        .from_reflect(field, &original)
+                        .with_context(|| format!("at {name}"))?;
+                    object.insert(name.clone(), field);
+                }
+                Value::Object(object)
+            }
+            (schema, _) => bail!(
+                "expected {}, found {}",
+                schema.name(),
+                value.reflect_type_path()
+            ),
+        })
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            Self::Any => "any",
+            Self::Bool => "a bool",
+            Self::Number => "a number",
+            Self::String => "a string",
+            Self::Optional(_) => "an optional",
+            Self::List(_) => "a list",
+            Self::Object(_) => "an object",
+        }
+    }
+}
+
+/// The declared [`ComponentSchema`]s of guest components, by type path.
+#[derive(Resource, Default, Debug, Clone)]
+pub struct WasmComponentSchemas(HashMap<TypePath, ComponentSchema>);
+
+impl WasmComponentSchemas {
+    /// Declares the schema of a guest component, replacing any previous one
+    pub fn register(&mut self, type_path: impl Into<TypePath>, schema: ComponentSchema) {
+        self.0.insert(type_path.into(), schema);
+    }
+
+    pub fn get(&self, type_path: &str) -> Option<&ComponentSchema> {
+        self.0.get(type_path)
+    }
+
+    /// Returns the schema of each guest component, by type path
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &ComponentSchema)> {
+        self.0
+            .iter()
+            .map(|(type_path, schema)| (type_path.as_str(), schema))
+    }
+
+    /// Reads a guest component of an entity as a [reflected](bevy_reflect) value shaped by its
+    /// schema, see [ComponentSchema::to_reflect].
+    ///
+    /// Fails if the component has no schema, or the entity doesn't have it. Assumes the default
+    /// JSON codec.
+    pub fn reflect(
+        world: &World,
+        entity: Entity,
+        type_path: &str,
+    ) -> Result<Box<dyn PartialReflect>> {
+        let (schema, value) = Self::read(world, entity, type_path)?;
+        schema.to_reflect(&value)
+    }
+
+    /// Writes a guest component of an entity from a [reflected](bevy_reflect) value, such as one
+    /// read with [Self::reflect] and then edited. See [ComponentSchema::from_reflect].
+    pub fn apply_reflect(
+        world: &mut World,
+        entity: Entity,
+        type_path: &str,
+        value: &dyn PartialReflect,
+    ) -> Result<()> {
+        let (schema, original) = Self::read(world, entity, type_path)?;
+        let value = schema.from_reflect(value, &original)?;
+        let component_id = world.resource::<WasmComponentRegistry>()[type_path];
+
+        let mut entity = world.entity_mut(entity);
+        let mut component = entity
+            .get_mut_by_id(component_id)
+            .expect("checked by Self::read");
+        // SAFETY: Components in the WasmComponentRegistry are always WasmComponents
+        let component = unsafe { component.as_mut().deref_mut::<WasmComponent>() };
+        component.serialized_value = serde_json::to_vec(&value)?;
+        Ok(())
+    }
+
+    fn read(world: &World, entity: Entity, type_path: &str) -> Result<(ComponentSchema, Value)> {
+        let schema = world
+            .get_resource::<Self>()
+            .and_then(|schemas| schemas.get(type_path))
+            .with_context(|| format!("{type_path} has no schema"))?
+            .clone();
+        let component_id = world
+            .get_resource::<WasmComponentRegistry>()
+            .and_then(|registry| registry.get(type_path))
+            .with_context(|| format!("{type_path} is not a guest component"))?;
+        let value = world
+            .get_entity(entity)?
+            .get_by_id(*component_id)
+            .with_context(|| format!("{entity} has no {type_path}"))?;
+        // SAFETY: Components in the WasmComponentRegistry are always WasmComponents
+        let value = unsafe { value.deref::<WasmComponent>() };
+        let value = serde_json::from_slice(&value.serialized_value)?;
+        Ok((schema, value))
+    }
+}
+
+/// Reads a reflected value as a concrete type
+fn downcast<T: Clone + 'static>(value: &dyn PartialReflect) -> Result<T> {
+    value.try_downcast_ref::<T>().cloned().with_context(|| {
+        format!(
+            "expected {}, found {}",
+            std::any::type_name::<T>(),
+            value.reflect_type_path()
+        )
+    })
+}
+
+/// Sent by the [`SchemaCheckPlugin`] when a guest component doesn't match its schema.
+#[derive(Message, Debug, Clone, PartialEq, Eq)]
+pub struct SchemaViolation {
+    pub entity: Entity,
+    pub type_path: TypePath,
+    /// Path of the offending field, empty when the component as a whole is invalid
+    pub field: String,
+    pub message: String,
+}
+
+/// Validates every guest component against its registered schema at the end of each frame.
+///
+/// Violations are logged and sent as [`SchemaViolation`] messages. Each violation is only reported
+/// once, until the component becomes valid again.
+///
+/// This visits every guest component every frame, so it's intended as a debugging aid. It also
+/// assumes the default JSON codec.
+pub struct SchemaCheckPlugin;
+
+impl Plugin for SchemaCheckPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<WasmComponentSchemas>()
+            .add_message::<SchemaViolation>()
+            .add_systems(Last, check_schemas);
+    }
+}
+
+fn check_schemas(world: &mut World, mut reported: Local<HashSet<(Entity, TypePath)>>) {
+    let (Some(schemas), Some(registry)) = (
+        world.get_resource::<WasmComponentSchemas>(),
+        world.get_resource::<WasmComponentRegistry>(),
+    ) else {
+        return;
+    };
+
+    let mut violations = Vec::new();
+    let mut valid = Vec::new();
+    for (type_path, component_id) in registry.iter() {
+        let Some(schema) = schemas.get(type_path) else {
+            continue;
+        };
+
+        for archetype in world.archetypes().iter() {
+            if !archetype.contains(*component_id) {
+                continue;
+            }
+
+            for entity in archetype.entities().iter().map(|entity| entity.id()) {
+                let value = world
+                    .entity(entity)
+                    .get_by_id(*component_id)
+                    .expect("archetype contains this component");
+                // SAFETY: Components in the WasmComponentRegistry are always WasmComponents
+                let value = unsafe { value.deref::<WasmComponent>() };
+
+                let result = serde_json::from_slice::<Value>(&value.serialized_value)
+                    .map_err(|err| (String::new(), format!("invalid json: {err}")))
+                    .and_then(|value| schema.validate(&value));
+
+                match result {
+                    Ok(()) => valid.push((entity, type_path.clone())),
+                    Err((field, message)) => violations.push(SchemaViolation {
+                        entity,
+                        type_path: type_path.clone(),
+                        field,
+                        message,
+                    }),
+                }
+            }
+        }
+    }
+
+    for key in valid {
+        reported.remove(&key);
+    }
+    for violation in violations {
+        if !reported.insert((violation.entity, violation.type_path.clone())) {
+            continue;
+        }
+
+        let SchemaViolation {
+            entity,
+            type_path,
+            field,
+            message,
+        } = &violation;
+        error!(
+            "Component {type_path} on {entity} does not match its schema at \"{field}\": {message}"
+        );
+        world.write_message(violation);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy_reflect::ReflectMut;
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn reports_offending_field() {
+        let schema = ComponentSchema::object([
+            ("name", ComponentSchema::String),
+            (
+                "stats",
+                ComponentSchema::object([
+                    ("health", ComponentSchema::Number),
+                    (
+                        "tags",
+                        ComponentSchema::List(Box::new(ComponentSchema::String)),
+                    ),
+                ]),
+            ),
+            (
+                "owner",
+                ComponentSchema::Optional(Box::new(ComponentSchema::Number)),
+            ),
+        ]);
+
+        let valid = json!({ "name": "orc", "stats": { "health": 3, "tags": ["green"] } });
+        assert_eq!(schema.validate(&valid), Ok(()));
+
+        let wrong_type = json!({ "name": "orc", "stats": { "health": "3", "tags": [] } });
+        assert_eq!(
+            schema.validate(&wrong_type),
+            Err((
+                "stats.health".into(),
+                "expected a number, found \"3\"".into()
+            ))
+        );
+
+        let wrong_item = json!({ "name": "orc", "stats": { "health": 3, "tags": ["a", 1] } });
+        assert_eq!(schema.validate(&wrong_item).unwrap_err().0, "stats.tags.1");
+
+        let missing = json!({ "stats": { "health": 3, "tags": [] } });
+        assert_eq!(
+            schema.validate(&missing),
+            Err(("name".into(), "missing field".into()))
+        );
+    }
+
+    #[test]
+    fn edits_components_via_reflection() {
+        let schema = ComponentSchema::from_json(
+            r#"{"object": [["points", "number"], ["tags", {"list": "string"}], ["owner", {"optional": "number"}]]}"#,
+        )
+        .unwrap();
+
+        let original = json!({ "points": 3, "tags": ["a"], "owner": null, "extra": true });
+        let mut value = schema.to_reflect(&original).unwrap();
+
+        let ReflectMut::Struct(fields) = value.reflect_mut() else {
+            panic!("objects are reflected as structs");
+        };
+        *fields
+            .field_mut("points")
+            .unwrap()
+            .try_downcast_mut::<f64>()
+            .unwrap() = 5.0;
+        assert!(
+            fields
+                .field("owner")
+                .unwrap()
+                .reflect_partial_eq(&DynamicEnum::new("None", ()))
+                .unwrap()
+        );
+
+        let edited = schema.from_reflect(value.as_ref(), &original).unwrap();
+        assert_eq!(
+            edited,
+            json!({ "points": 5.0, "tags": ["a"], "owner": null, "extra": true })
+        );
+
+        assert!(schema.to_reflect(&json!({ "points": "3" })).is_err());
+    }
+}
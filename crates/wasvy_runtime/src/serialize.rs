@@ -3,7 +3,7 @@ use bevy_derive::{Deref, DerefMut};
 use bevy_ecs::resource::Resource;
 use bevy_reflect::{PartialReflect, TypeRegistration, TypeRegistry};
 
-#[cfg(feature = "serde_json")]
+#[cfg(any(feature = "serde_json", feature = "postcard"))]
 use serde::de::DeserializeSeed;
 
 #[derive(Resource, Deref, DerefMut)]
@@ -110,6 +110,92 @@ impl WasvyCodec for JsonCodec {
     }
 }
 
+/// A compact binary codec based on [postcard](https://docs.rs/postcard).
+///
+/// This is much faster than JSON for large components, but the format is not self-describing, so
+/// guests must serialize their components with the exact same layout as the host.
+///
+/// Mods opt into it during setup, see [ModCodec].
+#[cfg(feature = "postcard")]
+#[derive(Default, Resource)]
+pub struct PostcardCodec;
+
+#[cfg(feature = "postcard")]
+impl WasvyCodec for PostcardCodec {
+    fn encode_reflect(
+        &self,
+        reflect: &dyn PartialReflect,
+        registry: &TypeRegistry,
+    ) -> Result<Vec<u8>> {
+        let serializer = bevy_reflect::serde::TypedReflectSerializer::new(reflect, registry);
+        Ok(postcard::to_allocvec(&serializer)?)
+    }
+
+    fn decode_reflect(
+        &self,
+        bytes: &[u8],
+        registration: &TypeRegistration,
+        registry: &TypeRegistry,
+    ) -> Result<Box<dyn PartialReflect>> {
+        let mut de = postcard::Deserializer::from_bytes(bytes);
+        let reflect_deserializer =
+            bevy_reflect::serde::TypedReflectDeserializer::new(registration, registry);
+        Ok(reflect_deserializer.deserialize(&mut de)?)
+    }
+
+    /// Args are encoded one after another, like a tuple
+    fn decode_reflect_args(
+        &self,
+        params: &[u8],
+        type_path: &[&str],
+        registry: &TypeRegistry,
+    ) -> Result<Vec<Option<Box<dyn PartialReflect>>>> {
+        let mut de = postcard::Deserializer::from_bytes(params);
+        let mut output = Vec::new();
+        for type_path in type_path {
+            let registration = registry
+                .get_with_type_path(type_path)
+                .ok_or_else(|| anyhow::anyhow!("Type {type_path} is not registered"))?;
+
+            let reflect_de =
+                bevy_reflect::serde::TypedReflectDeserializer::new(registration, registry);
+            output.push(Some(reflect_de.deserialize(&mut de)?));
+        }
+
+        Ok(output)
+    }
+
+    fn get_type(&self) -> String {
+        "postcard".to_string()
+    }
+}
+
+/// The codec a mod asked to use for its own data.
+///
+/// Mods that don't ask use the host's [CodecResource].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ModCodec {
+    #[default]
+    Host,
+    Json,
+    Postcard,
+}
+
+impl ModCodec {
+    /// Creates the codec requested by the mod, or [None] when it uses the host's
+    pub fn codec(&self) -> Result<Option<CodecResource>> {
+        match self {
+            Self::Host => Ok(None),
+            #[cfg(feature = "serde_json")]
+            Self::Json => Ok(Some(CodecResource::new(JsonCodec))),
+            #[cfg(feature = "postcard")]
+            Self::Postcard => Ok(Some(CodecResource::new(PostcardCodec))),
+            #[allow(unreachable_patterns)]
+            codec => bail!("The host was built without support for the {codec:?} codec"),
+        }
+    }
+}
+
 #[cfg(feature = "serde_json")]
 pub fn wasvy_encode<T>(value: &T) -> Result<Vec<u8>>
 where
@@ -1,21 +1,35 @@
-use bevy_asset::prelude::*;
+use bevy_asset::{AssetLoadFailedEvent, prelude::*};
 use bevy_ecs::{
+    entity::EntityHashSet,
     prelude::*,
     system::{SystemParam, SystemState},
 };
 use bevy_log::prelude::*;
 use bevy_platform::collections::HashSet;
 
-use crate::{access::ModAccess, asset::ModAsset, mods::Mod, schedule::ModStartup};
+use crate::{
+    access::ModAccess,
+    asset::ModAsset,
+    dependencies::{Dependencies, Resolution},
+    manifest::ModDetails,
+    mods::{Mod, ModState, ModSystemSet},
+    schedule::ModStartup,
+};
 
 /// Group all the system params we neeed to allow shared access from one &mut world
 #[derive(SystemParam)]
 pub(crate) struct Setup<'w, 's> {
     events: MessageReader<'w, 's, AssetEvent<ModAsset>>,
+    failures: MessageReader<'w, 's, AssetLoadFailedEvent<ModAsset>>,
+    reloads: MessageReader<'w, 's, ReloadMod>,
     assets: Res<'w, Assets<ModAsset>>,
     mods: Query<'w, 's, (Entity, Ref<'static, Mod>, Option<&'static Name>)>,
 }
 
+/// Runs the setup of a mod again, see [Mods::reload](crate::mods::Mods::reload)
+#[derive(Message)]
+pub(crate) struct ReloadMod(pub(crate) Entity);
+
 #[derive(PartialEq, Eq, Hash)]
 pub(crate) struct RanWith {
     mod_id: Entity,
@@ -26,9 +40,13 @@ pub(crate) fn run_setup(
     world: &mut World,
     param: &mut SystemState<Setup>,
     mut ran_with: Local<HashSet<RanWith>>,
+    mut deferred: Local<EntityHashSet>,
+    mut initialized: Local<EntityHashSet>,
 ) {
     let Setup {
         mut events,
+        mut failures,
+        mut reloads,
         assets,
         mods,
     } = param.get_mut(world).expect("valid system parameter");
@@ -61,14 +79,38 @@ pub(crate) fn run_setup(
             ran_with.remove(&RanWith { mod_id, access });
         }
 
+        // Mods depending on this one must wait until it has been setup again
+        initialized.remove(&mod_id);
+        loaded_mods.push(mod_id);
+    }
+
+    // Mods that failed to compile. They may load again once their file changes
+    let failed: Vec<Entity> = failures
+        .read()
+        .flat_map(|failure| {
+            mods.iter()
+                .filter(move |(_, mod_component, _)| mod_component.asset().id() == failure.id)
+                .map(|(mod_id, _, _)| mod_id)
+        })
+        .collect();
+
+    // Reloaded mods are setup again as if their asset had just loaded
+    for ReloadMod(mod_id) in reloads.read() {
+        let Ok((mod_id, mod_component, _)) = mods.get(*mod_id) else {
+            continue;
+        };
+        for access in mod_component.into_inner().accesses().map(Clone::clone) {
+            ran_with.remove(&RanWith { mod_id, access });
+        }
         loaded_mods.push(mod_id);
     }
 
     // We need exclusive world access later in order to setup mods, so store refs to them in a vec while we still have access to the Setup system params
     let mut setup: Vec<(AssetId<ModAsset>, Entity, String, Vec<ModAccess>)> = Vec::new();
     for (mod_id, mod_component, name) in mods.iter().filter(|(mod_id, mod_component, _)| {
-        // We only need to setup mods that have changed (such as sandboxes were added) or those that have loaded
-        mod_component.is_changed() || loaded_mods.contains(mod_id)
+        // We only need to setup mods that have changed (such as sandboxes were added), those that have loaded
+        // or those still waiting for their dependencies
+        mod_component.is_changed() || loaded_mods.contains(mod_id) || deferred.contains(mod_id)
     }) {
         let asset_id = mod_component.asset().id();
 
@@ -102,16 +144,74 @@ pub(crate) fn run_setup(
         }
     }
 
-    // Initiate mods with exclusive world access (runs the mod setup)
+    for mod_id in failed {
+        ModState::Failed.set(mod_id, world);
+    }
+
+    // Initiate mods with exclusive world access (runs the mod setup).
+    // Mods are only setup after the mods they depend on, so keep going until no more progress is made
+    let mut dependencies = Dependencies::new(world);
     let mut run_startup_schedule = false;
-    for (asset_id, mod_id, name, accesses) in setup {
-        if ModAsset::initiate(world, &asset_id, mod_id, &name, &accesses[..]).is_ok() {
-            info!("Successfully initialized mod \"{name}\"");
+    loop {
+        let pending: EntityHashSet = setup.iter().map(|(_, mod_id, _, _)| *mod_id).collect();
+        let mut progress = false;
+        for (asset_id, mod_id, name, accesses) in std::mem::take(&mut setup) {
+            let after = match dependencies.resolve(mod_id, &initialized, &pending) {
+                Resolution::Ready(after) => after,
+                Resolution::Wait => {
+                    setup.push((asset_id, mod_id, name, accesses));
+                    continue;
+                }
+                Resolution::Fail(err) => {
+                    error!("Failed to load mod \"{name}\": {err}");
+                    ModState::Failed.set(mod_id, world);
+                    progress = true;
+                    continue;
+                }
+            };
+
+            progress = true;
+            ModState::SettingUp.set(mod_id, world);
+            if let Err(err) = ModAsset::initiate(world, &asset_id, mod_id, &name, &accesses[..]) {
+                error!("Failed to initialize mod \"{name}\": {err:?}");
+                ModState::Failed.set(mod_id, world);
+                continue;
+            }
+
+            let details = world.get::<ModDetails>(mod_id);
+            match details {
+                Some(details) => info!("Successfully initialized mod \"{details}\""),
+                None => info!("Successfully initialized mod \"{name}\""),
+            }
+            dependencies.declare(mod_id, details);
             run_startup_schedule = true;
-            continue;
-        };
+            initialized.insert(mod_id);
+
+            ModState::Ready.set(mod_id, world);
+
+            // Run the mod's systems after those of its dependencies
+            for access in accesses.iter() {
+                let schedules = access.schedules(world);
+                let mut world_schedules = world.resource_mut::<Schedules>();
+                for schedule in schedules.0.iter() {
+                    for dependency in after.iter() {
+                        world_schedules.configure_sets(
+                            schedule.schedule_label(),
+                            ModSystemSet::Mod(mod_id).after(ModSystemSet::Mod(*dependency)),
+                        );
+                    }
+                }
+            }
+        }
+
+        if !progress {
+            break;
+        }
     }
 
+    // The remaining mods are waiting for dependencies that haven't loaded yet
+    *deferred = setup.iter().map(|(_, mod_id, _, _)| *mod_id).collect();
+
     if run_startup_schedule {
         ModStartup::run(world);
     }
@@ -0,0 +1,85 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::{Arc, Mutex, MutexGuard},
+};
+
+use bevy_ecs::prelude::*;
+
+/// A key-value store where a [Mod](crate::mods::Mod) keeps state between system runs.
+///
+/// Mods may be instantiated again at any time, for example after a hot reload or a trap, and each
+/// of their systems runs in its own instance. So state that must be kept or shared goes through
+/// the `get-data`, `set-data` and `remove-data` functions instead. Values are opaque bytes,
+/// serialized however the mod likes.
+///
+/// It lives on the mod's entity, so it survives hot reloads and is dropped with the mod. The host
+/// may also insert it beforehand, to hand a mod some initial state.
+///
+/// Cloning shares the same store, which allows systems of the same mod to run in parallel.
+#[derive(Component, Clone, Default)]
+pub struct ModStorage(Arc<Mutex<HashMap<String, Vec<u8>>>>);
+
+impl ModStorage {
+    /// Returns the storage of a mod, inserting an empty one if it has none yet
+    pub fn of(mod_id: Entity, world: &mut World) -> Self {
+        let mut entity = world.entity_mut(mod_id);
+        if let Some(storage) = entity.get::<ModStorage>() {
+            return storage.clone();
+        }
+
+        let storage = ModStorage::default();
+        entity.insert(storage.clone());
+        storage
+    }
+
+    pub fn get(&self, key: &str) -> Option<Vec<u8>> {
+        self.lock().get(key).cloned()
+    }
+
+    pub fn set(&self, key: impl Into<String>, value: Vec<u8>) {
+        self.lock().insert(key.into(), value);
+    }
+
+    pub fn remove(&self, key: &str) -> Option<Vec<u8>> {
+        self.lock().remove(key)
+    }
+
+    /// A copy of every entry, such as for [ModSaveData](crate::save::ModSaveData)
+    pub fn entries(&self) -> BTreeMap<String, Vec<u8>> {
+        self.lock()
+            .iter()
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect()
+    }
+
+    /// Replaces every entry with `entries`
+    pub fn replace(&self, entries: impl IntoIterator<Item = (String, Vec<u8>)>) {
+        let mut map = self.lock();
+        map.clear();
+        map.extend(entries);
+    }
+
+    fn lock(&self) -> MutexGuard<'_, HashMap<String, Vec<u8>>> {
+        // The map is never left in an invalid state, so poisoning can be ignored
+        self.0
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn storage_is_shared_per_mod() {
+        let mut world = World::new();
+        let mod_id = world.spawn_empty().id();
+
+        ModStorage::of(mod_id, &mut world).set("score", vec![1, 2]);
+        let storage = ModStorage::of(mod_id, &mut world);
+        assert_eq!(storage.get("score"), Some(vec![1, 2]));
+        assert_eq!(storage.remove("score"), Some(vec![1, 2]));
+        assert_eq!(ModStorage::of(mod_id, &mut world).get("score"), None);
+    }
+}
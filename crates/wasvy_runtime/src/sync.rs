@@ -0,0 +1,340 @@
+use bevy_ecs::{change_detection::Tick, entity::EntityHashMap, prelude::*};
+use bevy_log::prelude::*;
+use bevy_platform::collections::HashSet;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    component::{
+        WasmComponent, WasmComponentRegistry, get_wasm_component_id, insert_wasm_component,
+    },
+    mods::InsertDespawnComponent,
+    save::{mod_ids, mod_key, owned_entities},
+};
+
+/// Replicates the guest defined components ([WasmComponent]s) of mod entities between apps, such
+/// as from a server to its clients.
+///
+/// The server calls [ModSync::collect] to get the changes since its last call, and sends them to
+/// clients, which call [ModSync::apply]. This leaves the transport to the host or a replication
+/// crate such as `bevy_replicon`, which would drive these from its own systems.
+///
+/// Only entities owned by mods are synced, see [ModDespawnBehaviour](crate::mods::ModDespawnBehaviour).
+/// Components are identified by a [ModSyncId], so clients apply changes to the mod with the same
+/// id and skip those of mods they don't have. Components defined by the host are left to the
+/// host's own replication.
+///
+/// ```
+/// # use bevy_ecs::prelude::*;
+/// # use wasvy_runtime::sync::{ModSync, ModSyncDiff};
+/// fn send_changes(world: &mut World) {
+///     let diff = ModSync::collect(world);
+///     if !diff.is_empty() {
+///         let bytes = serde_json::to_vec(&diff).unwrap();
+///         // Send bytes to clients...
+/// #       let _ = bytes;
+///     }
+/// }
+///
+/// fn receive_changes(world: &mut World, bytes: &[u8]) {
+///     let diff: ModSyncDiff = serde_json::from_slice(bytes).unwrap();
+///     ModSync::apply(world, &diff);
+/// }
+/// ```
+#[derive(Resource, Default, Debug)]
+pub struct ModSync {
+    /// When the server last collected changes
+    last_collect: Option<Tick>,
+
+    /// The guest components of each entity the server synced, to detect removals
+    synced: EntityHashMap<Vec<ModSyncId>>,
+
+    /// The entities a client spawned for those of the server
+    entities: EntityHashMap<Entity>,
+}
+
+/// Identifies a guest defined component the same way in every app running the mod
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ModSyncId {
+    /// The id of the [ModManifest](crate::manifest::ModManifest) of the mod owning the entity, or
+    /// the name in its [ModDetails](crate::manifest::ModDetails) or its [Name] if it has none
+    pub mod_id: String,
+
+    /// The type path of the component
+    pub type_path: String,
+}
+
+/// The changes to the guest components of mod entities, see [ModSync]
+#[derive(Serialize, Deserialize, Default, Clone, Debug, PartialEq, Eq)]
+pub struct ModSyncDiff {
+    pub changes: Vec<ComponentDiff>,
+}
+
+/// A single change in a [ModSyncDiff]
+///
+/// Entities are those of the server, see [ModSync::entity].
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub enum ComponentDiff {
+    /// A component was inserted or changed
+    Set {
+        entity: u64,
+        id: ModSyncId,
+        value: Vec<u8>,
+    },
+
+    /// A component was removed
+    Removed { entity: u64, id: ModSyncId },
+
+    /// An entity was despawned, or is no longer owned by a mod
+    Despawned { entity: u64 },
+}
+
+impl ModSyncDiff {
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+}
+
+impl ModSync {
+    /// Collects the changes since the last call, or every synced component on the first call
+    pub fn collect(world: &mut World) -> ModSyncDiff {
+        world.init_resource::<ModSync>();
+        world.resource_scope(|world, mut sync: Mut<ModSync>| sync.collect_changes(world))
+    }
+
+    /// Applies changes collected by the server.
+    ///
+    /// Entities are spawned for those of the server as needed, and owned by the local mod with
+    /// the same id, so they despawn with it.
+    pub fn apply(world: &mut World, diff: &ModSyncDiff) {
+        world.init_resource::<ModSync>();
+        world.resource_scope(|world, mut sync: Mut<ModSync>| sync.apply_changes(world, diff));
+    }
+
+    /// The entity a client spawned for an entity of the server
+    pub fn entity(&self, server_entity: Entity) -> Option<Entity> {
+        self.entities.get(&server_entity).copied()
+    }
+
+    fn collect_changes(&mut self, world: &mut World) -> ModSyncDiff {
+        // Like running a system, so changes made after this in the same tick are collected next time
+        let this_run = world.increment_change_tick();
+        let last_collect = self.last_collect.replace(this_run);
+
+        let mut registry: Vec<_> = world
+            .get_resource::<WasmComponentRegistry>()
+            .map(|registry| {
+                registry
+                    .iter()
+                    .map(|(type_path, id)| (type_path.clone(), *id))
+                    .collect()
+            })
+            .unwrap_or_default();
+        registry.sort();
+
+        let mut changes = Vec::new();
+        let mut synced = EntityHashMap::default();
+        for mod_id in mod_ids(world) {
+            let Some(key) = mod_key(world, mod_id) else {
+                continue;
+            };
+
+            for owned in owned_entities(world, mod_id) {
+                let entity = world.entity(owned);
+                let mut ids = Vec::new();
+                for (type_path, component_id) in &registry {
+                    let Some(ticks) = entity.get_change_ticks_by_id(*component_id) else {
+                        continue;
+                    };
+                    let id = ModSyncId {
+                        mod_id: key.clone(),
+                        type_path: type_path.clone(),
+                    };
+
+                    let changed = last_collect.is_none_or(|last| ticks.is_changed(last, this_run));
+                    if changed {
+                        let ptr = entity.get_by_id(*component_id).expect("has change ticks");
+                        // Safety: components in the registry are all WasmComponents
+                        let component = unsafe { ptr.deref::<WasmComponent>() };
+                        changes.push(ComponentDiff::Set {
+                            entity: owned.to_bits(),
+                            id: id.clone(),
+                            value: component.serialized_value.clone(),
+                        });
+                    }
+                    ids.push(id);
+                }
+
+                for id in self.synced.remove(&owned).unwrap_or_default() {
+                    if !ids.contains(&id) {
+                        changes.push(ComponentDiff::Removed {
+                            entity: owned.to_bits(),
+                            id,
+                        });
+                    }
+                }
+                synced.insert(owned, ids);
+            }
+        }
+
+        let mut despawned: Vec<_> = self.synced.drain().map(|(entity, _)| entity).collect();
+        despawned.sort();
+        changes.extend(
+            despawned
+                .into_iter()
+                .map(|entity| ComponentDiff::Despawned {
+                    entity: entity.to_bits(),
+                }),
+        );
+        self.synced = synced;
+
+        ModSyncDiff { changes }
+    }
+
+    fn apply_changes(&mut self, world: &mut World, diff: &ModSyncDiff) {
+        let mods: Vec<_> = mod_ids(world)
+            .into_iter()
+            .filter_map(|mod_id| Some((mod_key(world, mod_id)?, mod_id)))
+            .collect();
+        let mut missing_mods = HashSet::new();
+
+        for change in &diff.changes {
+            let server_entity = match change {
+                ComponentDiff::Set { entity, .. }
+                | ComponentDiff::Removed { entity, .. }
+                | ComponentDiff::Despawned { entity } => Entity::try_from_bits(*entity),
+            };
+            let Some(server_entity) = server_entity else {
+                warn!("Skipped a synced change of an invalid entity");
+                continue;
+            };
+
+            match change {
+                ComponentDiff::Set { id, value, .. } => {
+                    let Some(&(_, mod_id)) = mods.iter().find(|(key, _)| *key == id.mod_id) else {
+                        if missing_mods.insert(&id.mod_id) {
+                            debug!(
+                                "Mod \"{}\" is not loaded, skipping synced changes",
+                                id.mod_id
+                            );
+                        }
+                        continue;
+                    };
+
+                    let entity = match self.entity(server_entity) {
+                        Some(entity) if world.get_entity(entity).is_ok() => entity,
+                        _ => {
+                            let entity = world.spawn_empty().id();
+                            InsertDespawnComponent::new(mod_id, world)
+                                .insert(&mut world.commands().entity(entity));
+                            self.entities.insert(server_entity, entity);
+                            entity
+                        }
+                    };
+                    insert_wasm_component(world, entity, &id.type_path, value.clone());
+                }
+                ComponentDiff::Removed { id, .. } => {
+                    let Some(entity) = self.entity(server_entity) else {
+                        continue;
+                    };
+                    let component_id = get_wasm_component_id(&id.type_path, world);
+                    if let Ok(mut entity) = world.get_entity_mut(entity) {
+                        entity.remove_by_id(component_id);
+                    }
+                }
+                ComponentDiff::Despawned { .. } => {
+                    if let Some(entity) = self.entities.remove(&server_entity) {
+                        world.despawn(entity);
+                    }
+                }
+            }
+            world.flush();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mods::Mod;
+
+    fn spawn_mod(world: &mut World) -> Entity {
+        world
+            .spawn((Mod::new(Default::default()), Name::new("score-mod")))
+            .id()
+    }
+
+    fn world() -> World {
+        let mut world = World::new();
+        world.init_resource::<WasmComponentRegistry>();
+        world.register_component::<crate::sandbox::Sandboxed>();
+        world
+    }
+
+    #[test]
+    fn syncs_guest_components() {
+        let mut server = world();
+        let server_mod = spawn_mod(&mut server);
+        let entity = server.spawn_empty().id();
+        InsertDespawnComponent::new(server_mod, &server)
+            .insert(&mut server.commands().entity(entity));
+        insert_wasm_component(&mut server, entity, "guest::Score", b"1".to_vec());
+        server.flush();
+
+        let mut client = world();
+        let client_mod = spawn_mod(&mut client);
+
+        let diff = ModSync::collect(&mut server);
+        assert_eq!(diff.changes.len(), 1);
+        ModSync::apply(&mut client, &diff);
+        let synced = client.resource::<ModSync>().entity(entity).unwrap();
+        let component_id = get_wasm_component_id("guest::Score", &mut client);
+        let ptr = client.entity(synced).get_by_id(component_id).unwrap();
+        // Safety: guest::Score is a WasmComponent
+        let component = unsafe { ptr.deref::<WasmComponent>() };
+        assert_eq!(component.serialized_value, b"1");
+        assert!(
+            owned_entities(&client, client_mod).contains(&synced),
+            "synced entities belong to the local mod"
+        );
+
+        // Unchanged components are not synced again
+        assert!(ModSync::collect(&mut server).is_empty());
+
+        let component_id = get_wasm_component_id("guest::Score", &mut server);
+        server.entity_mut(entity).remove_by_id(component_id);
+        let diff = ModSync::collect(&mut server);
+        assert!(matches!(diff.changes[..], [ComponentDiff::Removed { .. }]));
+        ModSync::apply(&mut client, &diff);
+        let component_id = get_wasm_component_id("guest::Score", &mut client);
+        assert!(client.entity(synced).get_by_id(component_id).is_err());
+
+        server.despawn(entity);
+        let diff = ModSync::collect(&mut server);
+        assert!(matches!(
+            diff.changes[..],
+            [ComponentDiff::Despawned { .. }]
+        ));
+        ModSync::apply(&mut client, &diff);
+        assert!(client.get_entity(synced).is_err());
+    }
+
+    #[test]
+    fn syncs_changes_made_right_after_collecting() {
+        let mut server = world();
+        let server_mod = spawn_mod(&mut server);
+        let entity = server.spawn_empty().id();
+        InsertDespawnComponent::new(server_mod, &server)
+            .insert(&mut server.commands().entity(entity));
+        insert_wasm_component(&mut server, entity, "guest::Score", b"1".to_vec());
+        server.flush();
+        assert_eq!(ModSync::collect(&mut server).changes.len(), 1);
+
+        // Changed in the same exclusive context as the collect, without the tick advancing
+        insert_wasm_component(&mut server, entity, "guest::Score", b"2".to_vec());
+        let diff = ModSync::collect(&mut server);
+        assert!(matches!(
+            &diff.changes[..],
+            [ComponentDiff::Set { value, .. }] if value == b"2"
+        ));
+    }
+}
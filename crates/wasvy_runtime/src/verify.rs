@@ -0,0 +1,351 @@
+use std::{fmt, path::PathBuf, sync::Arc};
+
+use anyhow::Result;
+use bevy_ecs::prelude::*;
+
+use crate::manifest::ModManifest;
+#[cfg(feature = "signatures")]
+use crate::manifest::strip_manifest;
+
+/// Checks mods before they are compiled, such as verifying the signature of their publisher.
+///
+/// Verifiers are passed the bytes of the wasm file, the mod's [ModManifest] and the assets of the
+/// [ModPackage](crate::package::ModPackage) it shipped in, if any. Mods without a manifest are rejected as [unsigned](ModVerificationError::Unsigned) without calling the
+/// verifier. Rejected mods fail to load with a [ModVerificationError].
+///
+/// Set one with [ModRuntimePlugin::set_verifier](crate::plugin::ModRuntimePlugin::set_verifier).
+///
+/// ```
+/// # use wasvy_runtime::verify::ModVerifier;
+/// let verifier = ModVerifier::new(|_bytes, manifest, _assets| {
+///     anyhow::ensure!(manifest.author.as_deref() == Some("Jane Doe"), "Unknown author");
+///     Ok(())
+/// });
+/// # let _ = verifier;
+/// ```
+#[derive(Resource, Clone)]
+pub struct ModVerifier(Arc<VerifyFn>);
+
+type VerifyFn = dyn Fn(&[u8], &ModManifest, &[(PathBuf, Vec<u8>)]) -> Result<()> + Send + Sync;
+
+impl ModVerifier {
+    pub fn new(
+        verify: impl Fn(&[u8], &ModManifest, &[(PathBuf, Vec<u8>)]) -> Result<()>
+        + Send
+        + Sync
+        + 'static,
+    ) -> Self {
+        Self(Arc::new(verify))
+    }
+
+    /// Accepts mods signed by one of the `trusted_keys`, which are ed25519 public keys.
+    ///
+    /// Publishers sign their mods with [sign_mod], and put the signature in the `signature` field
+    /// of the manifest. The signature covers the wasm file, without its
+    /// [embedded manifest](crate::manifest::MANIFEST_SECTION), every other field of the
+    /// manifest and the assets of the mod's package, so none can be tampered with.
+    #[cfg(feature = "signatures")]
+    pub fn ed25519(trusted_keys: impl IntoIterator<Item = [u8; 32]>) -> Result<Self> {
+        use ed25519_dalek::{Signature, VerifyingKey};
+
+        let trusted_keys = trusted_keys
+            .into_iter()
+            .map(|key| VerifyingKey::from_bytes(&key))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Self::new(move |bytes, manifest, assets| {
+            let signature = manifest
+                .signature
+                .as_deref()
+                .ok_or(ModVerificationError::Unsigned)?;
+            let signature = from_hex(signature)
+                .and_then(|signature| Signature::from_slice(&signature).ok())
+                .ok_or(ModVerificationError::InvalidSignature)?;
+            let message = signed_message(bytes, manifest, assets)?;
+
+            if !trusted_keys
+                .iter()
+                .any(|key| key.verify_strict(&message, &signature).is_ok())
+            {
+                return Err(ModVerificationError::InvalidSignature.into());
+            }
+            Ok(())
+        }))
+    }
+
+    /// Verifies a mod, given the bytes of its wasm file, its manifest and the assets of its package
+    pub fn verify(
+        &self,
+        bytes: &[u8],
+        manifest: Option<&ModManifest>,
+        assets: &[(PathBuf, Vec<u8>)],
+    ) -> Result<(), ModVerificationError> {
+        let manifest = manifest.ok_or(ModVerificationError::Unsigned)?;
+        (self.0)(bytes, manifest, assets).map_err(|err| {
+            err.downcast::<ModVerificationError>()
+                .unwrap_or_else(ModVerificationError::Rejected)
+        })
+    }
+}
+
+/// Signs a mod, its manifest and the assets of its package with an ed25519 secret key, see
+/// [ModVerifier::ed25519]. Mods that don't ship in a package have no `assets`.
+///
+/// Returns the signature to put in the `signature` field of the mod's manifest. The manifest's
+/// current signature is ignored.
+#[cfg(feature = "signatures")]
+pub fn sign_mod(
+    bytes: &[u8],
+    manifest: &ModManifest,
+    assets: &[(PathBuf, Vec<u8>)],
+    secret_key: &[u8; 32],
+) -> Result<String> {
+    use ed25519_dalek::{Signer, SigningKey};
+
+    let message = signed_message(bytes, manifest, assets)?;
+    let signature = SigningKey::from_bytes(secret_key).sign(&message);
+    Ok(signature
+        .to_bytes()
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect())
+}
+
+/// What a mod's signature covers: its wasm file without the embedded manifest, followed by the
+/// fields of its manifest besides the signature, then the path and SHA-256 hash of each asset of
+/// its package, sorted by path. Each field is prefixed by its length, and optional fields by
+/// whether they are present, so no two manifests sign the same message.
+#[cfg(feature = "signatures")]
+fn signed_message(
+    bytes: &[u8],
+    manifest: &ModManifest,
+    assets: &[(PathBuf, Vec<u8>)],
+) -> Result<Vec<u8>> {
+    use sha2::{Digest, Sha256};
+
+    fn field(message: &mut Vec<u8>, field: &str) {
+        message.extend_from_slice(&(field.len() as u64).to_le_bytes());
+        message.extend_from_slice(field.as_bytes());
+    }
+
+    let mut message = strip_manifest(bytes)?;
+    field(&mut message, &manifest.id);
+    field(&mut message, &manifest.version.to_string());
+    match &manifest.author {
+        Some(author) => {
+            message.push(1);
+            field(&mut message, author);
+        }
+        None => message.push(0),
+    }
+    message.extend_from_slice(&(manifest.dependencies.len() as u64).to_le_bytes());
+    for (id, requirement) in &manifest.dependencies {
+        field(&mut message, id);
+        field(&mut message, &requirement.to_string());
+    }
+
+    let mut assets: Vec<_> = assets
+        .iter()
+        .map(|(path, contents)| (asset_path(path), contents))
+        .collect();
+    assets.sort_by(|(a, _), (b, _)| a.cmp(b));
+    message.extend_from_slice(&(assets.len() as u64).to_le_bytes());
+    for (path, contents) in assets {
+        field(&mut message, &path);
+        message.extend_from_slice(&Sha256::digest(contents));
+    }
+    Ok(message)
+}
+
+/// The path of an asset with `/` separators, so signatures don't depend on the platform
+#[cfg(feature = "signatures")]
+fn asset_path(path: &std::path::Path) -> String {
+    path.components()
+        .map(|component| component.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+#[cfg(feature = "signatures")]
+fn from_hex(hex: &str) -> Option<Vec<u8>> {
+    if !hex.is_ascii() || !hex.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).ok())
+        .collect()
+}
+
+/// The reason a [ModVerifier] rejected a mod
+#[derive(Debug)]
+pub enum ModVerificationError {
+    /// The mod has no manifest, or its manifest has no signature
+    Unsigned,
+
+    /// The signature is malformed, or was not made by a trusted key
+    InvalidSignature,
+
+    /// The verifier rejected the mod for another reason
+    Rejected(anyhow::Error),
+}
+
+impl fmt::Display for ModVerificationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Unsigned => write!(f, "Mod is not signed"),
+            Self::InvalidSignature => write!(f, "Mod has an invalid signature"),
+            Self::Rejected(err) => write!(f, "Mod was rejected: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for ModVerificationError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn manifest(signature: Option<String>) -> ModManifest {
+        ModManifest {
+            id: "my-mod".into(),
+            version: "1.0.0".parse().unwrap(),
+            author: None,
+            dependencies: Default::default(),
+            signature,
+        }
+    }
+
+    #[test]
+    fn rejects_mods_with_a_distinct_error() {
+        let verifier = ModVerifier::new(|bytes, _, _| {
+            anyhow::ensure!(bytes.len() > 8, "Mod is empty");
+            Ok(())
+        });
+        let wasm = b"\0asm\x01\0\0\0";
+
+        assert!(matches!(
+            verifier.verify(wasm, None, &[]),
+            Err(ModVerificationError::Unsigned)
+        ));
+        assert!(matches!(
+            verifier.verify(wasm, Some(&manifest(None)), &[]),
+            Err(ModVerificationError::Rejected(_))
+        ));
+        assert!(
+            verifier
+                .verify(b"\0asm\x01\0\0\0\0", Some(&manifest(None)), &[])
+                .is_ok()
+        );
+    }
+
+    #[cfg(feature = "signatures")]
+    #[test]
+    fn verifies_ed25519_signatures() {
+        let secret_key = [7; 32];
+        let public_key = ed25519_dalek::SigningKey::from_bytes(&secret_key)
+            .verifying_key()
+            .to_bytes();
+        let verifier = ModVerifier::ed25519([public_key]).unwrap();
+        let wasm = b"\0asm\x01\0\0\0";
+        let signature = sign_mod(wasm, &manifest(None), &[], &secret_key).unwrap();
+
+        assert!(
+            verifier
+                .verify(wasm, Some(&manifest(Some(signature.clone()))), &[])
+                .is_ok()
+        );
+        assert!(matches!(
+            verifier.verify(wasm, Some(&manifest(None)), &[]),
+            Err(ModVerificationError::Unsigned)
+        ));
+        assert!(matches!(
+            verifier.verify(b"\0asm\x0d\0\x01\0", Some(&manifest(Some(signature))), &[]),
+            Err(ModVerificationError::InvalidSignature)
+        ));
+        assert!(matches!(
+            verifier.verify(wasm, Some(&manifest(Some("nope".into()))), &[]),
+            Err(ModVerificationError::InvalidSignature)
+        ));
+    }
+
+    #[cfg(feature = "signatures")]
+    #[test]
+    fn signatures_cover_the_manifest() {
+        let secret_key = [7; 32];
+        let public_key = ed25519_dalek::SigningKey::from_bytes(&secret_key)
+            .verifying_key()
+            .to_bytes();
+        let verifier = ModVerifier::ed25519([public_key]).unwrap();
+        let wasm = b"\0asm\x01\0\0\0";
+        let signature = sign_mod(wasm, &manifest(None), &[], &secret_key).unwrap();
+
+        let mut tampered = manifest(Some(signature.clone()));
+        tampered.author = Some("Mallory".into());
+        assert!(matches!(
+            verifier.verify(wasm, Some(&tampered), &[]),
+            Err(ModVerificationError::InvalidSignature)
+        ));
+
+        let mut tampered = manifest(Some(signature.clone()));
+        tampered.author = Some(String::new());
+        assert!(
+            matches!(
+                verifier.verify(wasm, Some(&tampered), &[]),
+                Err(ModVerificationError::InvalidSignature)
+            ),
+            "an empty author is not the same as no author"
+        );
+
+        let mut tampered = manifest(Some(signature));
+        tampered
+            .dependencies
+            .insert("evil-mod".into(), "^1.0".parse().unwrap());
+        assert!(matches!(
+            verifier.verify(wasm, Some(&tampered), &[]),
+            Err(ModVerificationError::InvalidSignature)
+        ));
+    }
+
+    #[cfg(feature = "signatures")]
+    #[test]
+    fn signatures_cover_package_assets() {
+        let secret_key = [7; 32];
+        let public_key = ed25519_dalek::SigningKey::from_bytes(&secret_key)
+            .verifying_key()
+            .to_bytes();
+        let verifier = ModVerifier::ed25519([public_key]).unwrap();
+        let wasm = b"\0asm\x01\0\0\0";
+        let assets = vec![
+            (PathBuf::from("textures/wall.png"), b"wall".to_vec()),
+            (PathBuf::from("sounds/door.ogg"), b"door".to_vec()),
+        ];
+        let signature = sign_mod(wasm, &manifest(None), &assets, &secret_key).unwrap();
+        let signed = manifest(Some(signature));
+
+        assert!(verifier.verify(wasm, Some(&signed), &assets).is_ok());
+        let reordered: Vec<_> = assets.iter().rev().cloned().collect();
+        assert!(verifier.verify(wasm, Some(&signed), &reordered).is_ok());
+
+        let mut tampered = assets.clone();
+        tampered[0].1 = b"evil".to_vec();
+        assert!(matches!(
+            verifier.verify(wasm, Some(&signed), &tampered),
+            Err(ModVerificationError::InvalidSignature)
+        ));
+
+        let mut renamed = assets.clone();
+        renamed[0].0 = PathBuf::from("textures/floor.png");
+        assert!(matches!(
+            verifier.verify(wasm, Some(&signed), &renamed),
+            Err(ModVerificationError::InvalidSignature)
+        ));
+
+        let mut added = assets.clone();
+        added.push((PathBuf::from("extra.txt"), Vec::new()));
+        assert!(matches!(
+            verifier.verify(wasm, Some(&signed), &added),
+            Err(ModVerificationError::InvalidSignature)
+        ));
+    }
+}
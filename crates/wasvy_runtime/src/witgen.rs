@@ -3,6 +3,18 @@
 //! This module inspects the Bevy `TypeRegistry` + `FunctionRegistry` at runtime
 //! and produces a `components.wit` description for guest bindings.
 //! Argument names are sourced from `#[wasvy::methods]` metadata when available.
+//! Reflected structs used in method signatures, like `Vec3`, become WIT records, and reflected
+//! enums become WIT enums, or variants when they carry data. Common `bevy_math` types (`Vec2`,
+//! `Vec3`, `Vec4`, `Quat`, `Mat4`) and `Transform` are records even when they aren't registered,
+//! such as in build scripts, see [`BUILTIN_RECORDS`].
+//!
+//! Resources are named after their component and land in the components interface, unless the
+//! component sets another [name](WasvyExport::name) or [interface](WasvyExport::interface).
+//! Free functions exported with `#[wasvy::host_fn]` are functions of the components interface.
+//! Types exported with [`WasvyResource`](crate::authoring::WasvyResource) become resources
+//! constructed from a `res`, rather than a `component`.
+//! Doc comments of components and of their `#[wasvy::methods]` are written as WIT docs, so
+//! guest bindings show them to mod authors.
 
 use std::{
     collections::{BTreeMap, BTreeSet},
@@ -13,8 +25,16 @@ use std::{
 use bevy_app::{App, Plugin, Startup};
 use bevy_ecs::prelude::*;
 use bevy_ecs::reflect::AppFunctionRegistry;
+use bevy_reflect::{
+    TypeInfo, TypeRegistry,
+    enums::{EnumInfo, VariantInfo},
+    structs::StructInfo,
+};
 
-use crate::methods::FunctionIndex;
+use crate::{
+    authoring::{ExportLevel, WasvyExport},
+    methods::{FunctionAccess, FunctionIndex, normalize_type_path},
+};
 
 /// Settings controlling how `components.wit` is generated.
 ///
@@ -55,6 +75,20 @@ pub struct WitGeneratorSettings {
     ///
     /// Defaults to "wit/game-bingings.wit"
     pub output_path: PathBuf,
+
+    /// The least visible [`ExportLevel`] included in the WIT.
+    ///
+    /// Defaults to [`ExportLevel::ModdingApi`]. Use [`ExportLevel::Internal`] to
+    /// generate an internal WIT for first-party tooling mods.
+    pub export_level: ExportLevel,
+
+    /// Whether resources get a `get-<field>` and `set-<field>` function for each field of their
+    /// component, so mods can access single fields without serializing the whole component.
+    ///
+    /// Fields without a WIT representation and fields with the name of a method are left out.
+    ///
+    /// Defaults to false
+    pub field_accessors: bool,
 }
 
 impl Default for WitGeneratorSettings {
@@ -65,6 +99,8 @@ impl Default for WitGeneratorSettings {
             world: "host".to_string(),
             wasvy_package: "wasvy:ecs@0.0.9".to_string(),
             output_path: PathBuf::from("wit/bingings.wit"),
+            export_level: ExportLevel::ModdingApi,
+            field_accessors: false,
         }
     }
 }
@@ -122,15 +158,22 @@ fn write_wit(
 struct ComponentEntry {
     name: String,
     type_path: String,
+    /// The interface of the resource, if not the components interface
+    interface: Option<String>,
+    docs: Option<String>,
+    /// Whether the resource is constructed from a `res` rather than a `component`
+    resource: bool,
     methods: Vec<MethodEntry>,
 }
 
 #[derive(Clone)]
 struct MethodEntry {
     name: String,
+    is_static: bool,
     arg_names: Vec<String>,
     arg_types: Vec<String>,
     ret: String,
+    docs: Option<String>,
 }
 
 /// Build a WIT document for all exported components and methods.
@@ -142,15 +185,28 @@ pub fn generate_wit(
     type_registry: &AppTypeRegistry,
     function_registry: &AppFunctionRegistry,
 ) -> String {
-    let index = FunctionIndex::build(type_registry, function_registry);
+    let index =
+        FunctionIndex::build_with_level(type_registry, function_registry, settings.export_level);
+    let exports: BTreeMap<String, WasvyExport> = type_registry
+        .read()
+        .iter_with_data::<WasvyExport>()
+        .map(|(registration, export)| {
+            let type_path = normalize_type_path(registration.type_info().type_path());
+            (type_path, export.clone())
+        })
+        .collect();
     let mut components: BTreeMap<String, ComponentEntry> = BTreeMap::new();
 
     for type_path in index.components() {
         let entry = components.entry(type_path.to_string()).or_default();
         entry.type_path = type_path.to_string();
-        if entry.name.is_empty() {
-            entry.name = type_path_to_name(type_path);
-        }
+        let export = exports.get(type_path);
+        entry.name = export
+            .and_then(|export| export.name.clone())
+            .unwrap_or_else(|| type_path_to_name(type_path));
+        entry.interface = export.and_then(|export| export.interface.clone());
+        entry.docs = export.and_then(|export| export.docs.clone());
+        entry.resource = index.is_resource(type_path);
     }
 
     for type_path in index.components() {
@@ -158,6 +214,7 @@ pub fn generate_wit(
             let entry = components.entry(type_path.to_string()).or_default();
             entry.methods.push(MethodEntry {
                 name: method.method.clone(),
+                is_static: method.access == FunctionAccess::Static,
                 arg_names: method.args.iter().map(|arg| arg.name.clone()).collect(),
                 arg_types: method
                     .args
@@ -165,78 +222,300 @@ pub fn generate_wit(
                     .map(|arg| arg.type_path.clone())
                     .collect(),
                 ret: method.ret.clone(),
+                docs: method.docs.clone(),
             });
         }
     }
 
-    render_wit(settings, components)
+    let host_fns = index
+        .host_fns()
+        .map(|function| MethodEntry {
+            name: function.method.clone(),
+            is_static: false,
+            arg_names: function.args.iter().map(|arg| arg.name.clone()).collect(),
+            arg_types: function
+                .args
+                .iter()
+                .map(|arg| arg.type_path.clone())
+                .collect(),
+            ret: function.ret.clone(),
+            docs: function.docs.clone(),
+        })
+        .collect();
+
+    render_wit(settings, components, host_fns, &type_registry.read())
+}
+
+/// The WIT types of the reflected structs and enums in method signatures
+#[derive(Default)]
+struct TypeDefs {
+    /// Type names by type path
+    names: BTreeMap<String, String>,
+    /// Resource names by type path, for components passed by value
+    resources: BTreeMap<String, String>,
+    rendered: Vec<String>,
+}
+
+impl TypeDefs {
+    /// A unique name for the type of `type_path`
+    ///
+    /// Components passed by value (like the return value of a constructor) are named after their
+    /// resource, as `<resource>-data`.
+    fn name(&self, type_path: &str, used_names: &mut BTreeSet<String>) -> String {
+        let name = match self.resources.get(type_path) {
+            Some(resource) => format!("{resource}-data"),
+            None => type_path_to_name(type_path),
+        };
+        to_wit_ident(&name, used_names)
+    }
 }
 
 fn render_wit(
     settings: &WitGeneratorSettings,
     components: BTreeMap<String, ComponentEntry>,
+    host_fns: Vec<MethodEntry>,
+    registry: &TypeRegistry,
 ) -> String {
     let mut out = String::new();
-    out.push_str("/// These bindings are automatically generated by wasvy");
+    out.push_str("/// These bindings are automatically generated by wasvy\n");
     out.push_str(&format!("package {};\n\n", settings.package));
-    out.push_str(&format!("interface {} {{\n", settings.component_interface));
+
+    let mut interfaces: BTreeMap<String, Vec<ComponentEntry>> = BTreeMap::new();
+    for entry in components.into_values() {
+        let interface = entry
+            .interface
+            .clone()
+            .unwrap_or_else(|| settings.component_interface.clone());
+        interfaces.entry(interface).or_default().push(entry);
+    }
+
+    // The components interface comes first, and is rendered even when empty
+    let components = interfaces
+        .remove(&settings.component_interface)
+        .unwrap_or_default();
+    out.push_str(&render_interface(
+        settings,
+        &settings.component_interface,
+        &components,
+        &host_fns,
+        registry,
+    ));
+    for (interface, components) in &interfaces {
+        out.push_str(&render_interface(
+            settings,
+            interface,
+            components,
+            &[],
+            registry,
+        ));
+    }
+
+    out.push_str(&format!("world {} {{\n", settings.world));
+    out.push_str(&format!("  import {};\n", settings.component_interface));
+    for interface in interfaces.keys() {
+        out.push_str(&format!("  import {interface};\n"));
+    }
+    out.push_str("}\n");
+    out
+}
+
+/// Renders an interface with the resources of `components`, the `functions` and the types in
+/// their signatures
+fn render_interface(
+    settings: &WitGeneratorSettings,
+    interface: &str,
+    components: &[ComponentEntry],
+    functions: &[MethodEntry],
+    registry: &TypeRegistry,
+) -> String {
+    let mut out = format!("interface {interface} {{\n");
 
     let (package, version) = settings
         .wasvy_package
         .split_once("@")
         .unwrap_or((&settings.wasvy_package, ""));
     let version_separator = if version.is_empty() { "" } else { "@" };
+    let mut handles = Vec::new();
+    if !components.iter().all(|entry| entry.resource) {
+        handles.push("component");
+    }
+    if components.iter().any(|entry| entry.resource) {
+        handles.push("res");
+    }
     out.push_str(&format!(
-        "  use {package}/app{version_separator}{version}.{{component}};\n\n"
+        "  use {package}/app{version_separator}{version}.{{{}}};\n\n",
+        handles.join(", ")
     ));
 
     let mut used_names = BTreeSet::new();
+    let mut types = TypeDefs::default();
 
-    for (type_path, mut entry) in components {
-        if entry.name.is_empty() {
-            entry.name = type_path_to_name(&type_path);
+    // Resources are named first, so their names don't depend on the types in signatures
+    let components: Vec<(String, &ComponentEntry)> = components
+        .iter()
+        .map(|entry| {
+            let resource_name = to_wit_ident(&entry.name, &mut used_names);
+            types
+                .resources
+                .insert(entry.type_path.clone(), resource_name.clone());
+            (resource_name, entry)
+        })
+        .collect();
+
+    let mut resources = String::new();
+    for (resource_name, entry) in components {
+        if let Some(docs) = &entry.docs {
+            resources.push_str(&render_docs(docs, "  "));
+            resources.push_str("  ///\n");
         }
-        if entry.type_path.is_empty() {
-            entry.type_path = type_path.clone();
+        resources.push_str(&format!("  /// wasvy:type-path={}\n", entry.type_path));
+        resources.push_str(&format!("  resource {} {{\n", resource_name));
+        if entry.resource {
+            resources.push_str("    constructor(res: res);\n");
+        } else {
+            resources.push_str("    constructor(component: component);\n");
         }
 
-        let resource_name = to_wit_ident(&entry.name, &mut used_names);
-        out.push_str(&format!("  /// wasvy:type-path={}\n", entry.type_path));
-        out.push_str(&format!("  resource {} {{\n", resource_name));
-        out.push_str("    constructor(component: component);\n");
+        let mut method_names = BTreeSet::new();
+        for method in &entry.methods {
+            let signature = match render_method(method, registry, &mut types, &mut used_names) {
+                Ok(signature) => signature,
+                Err(err) => {
+                    bevy_log::warn!(
+                        "Skipping method {}::{}: {err}",
+                        entry.type_path,
+                        method.name
+                    );
+                    continue;
+                }
+            };
+            if let Some(docs) = &method.docs {
+                resources.push_str(&render_docs(docs, "    "));
+            }
+            resources.push_str(&format!("    {};\n", signature));
+            method_names.insert(to_wit_ident(&method.name, &mut BTreeSet::new()));
+        }
 
-        for method in entry.methods {
-            let signature = render_method(&method);
-            out.push_str(&format!("    {};\n", signature));
+        if settings.field_accessors {
+            let accessors = render_accessors(
+                &entry.type_path,
+                &method_names,
+                registry,
+                &mut types,
+                &mut used_names,
+            );
+            for accessor in accessors {
+                resources.push_str(&format!("    {accessor};\n"));
+            }
         }
 
-        out.push_str("  }\n");
+        resources.push_str("  }\n");
+    }
+
+    for function in functions {
+        let signature = match render_method(function, registry, &mut types, &mut used_names) {
+            Ok(signature) => signature,
+            Err(err) => {
+                bevy_log::warn!("Skipping function {}: {err}", function.name);
+                continue;
+            }
+        };
+        if let Some(docs) = &function.docs {
+            resources.push_str(&render_docs(docs, "  "));
+        }
+        resources.push_str(&format!("  {signature};\n"));
     }
 
+    for ty in types.rendered {
+        out.push_str(&ty);
+    }
+    out.push_str(&resources);
     out.push_str("}\n\n");
-    out.push_str(&format!("world {} {{\n", settings.world));
-    out.push_str(&format!("  import {};\n", settings.component_interface));
-    out.push_str("}\n");
     out
 }
 
-fn render_method(method: &MethodEntry) -> String {
+/// The WIT signature of a method, or why one of its types has no WIT representation
+fn render_method(
+    method: &MethodEntry,
+    registry: &TypeRegistry,
+    types: &mut TypeDefs,
+    used_names: &mut BTreeSet<String>,
+) -> Result<String, String> {
     let mut args = Vec::new();
+    let mut arg_names = BTreeSet::new();
     for (name, ty) in method.arg_names.iter().zip(method.arg_types.iter()) {
-        let mapped = map_type(ty);
-        args.push(format!("{}: {}", name, mapped));
+        let mapped = map_type(ty, registry, types, used_names)?;
+        args.push(format!(
+            "{}: {}",
+            to_wit_ident(name, &mut arg_names),
+            mapped
+        ));
     }
 
+    let name = to_wit_ident(&method.name, &mut BTreeSet::new());
     let args = args.join(", ");
-    let ret = map_type(&method.ret);
+    let ret = map_type(&method.ret, registry, types, used_names)?;
+    let func = if method.is_static {
+        "static func"
+    } else {
+        "func"
+    };
     if ret == "()" {
-        format!("{}: func({})", method.name, args)
+        Ok(format!("{name}: {func}({args})"))
     } else {
-        format!("{}: func({}) -> {}", method.name, args, ret)
+        Ok(format!("{name}: {func}({args}) -> {ret}"))
     }
 }
 
-fn type_path_to_name(type_path: &str) -> String {
+/// Renders `docs` as `///` comments
+fn render_docs(docs: &str, indent: &str) -> String {
+    docs.lines()
+        .map(|line| match line {
+            "" => format!("{indent}///\n"),
+            line => format!("{indent}/// {line}\n"),
+        })
+        .collect()
+}
+
+/// The `get-<field>` and `set-<field>` functions of a component, see
+/// [`WitGeneratorSettings::field_accessors`]
+fn render_accessors(
+    type_path: &str,
+    method_names: &BTreeSet<String>,
+    registry: &TypeRegistry,
+    types: &mut TypeDefs,
+    used_names: &mut BTreeSet<String>,
+) -> Vec<String> {
+    let Some(TypeInfo::Struct(info)) = registry
+        .get_with_type_path(type_path)
+        .map(|r| r.type_info())
+    else {
+        return Vec::new();
+    };
+
+    let mut accessors = Vec::new();
+    for field in info.iter() {
+        let field_name = to_wit_ident(field.name(), &mut BTreeSet::new());
+        let field_name = field_name.trim_start_matches('%');
+        let getter = format!("get-{field_name}");
+        let setter = format!("set-{field_name}");
+        if method_names.contains(&getter) || method_names.contains(&setter) {
+            continue;
+        }
+        let ty = match map_type(field.type_path(), registry, types, used_names) {
+            Ok(ty) => ty,
+            Err(err) => {
+                bevy_log::debug!("Skipping accessors of {type_path}::{}: {err}", field.name());
+                continue;
+            }
+        };
+        accessors.push(format!("{getter}: func() -> {ty}"));
+        accessors.push(format!("{setter}: func(value: {ty})"));
+    }
+    accessors
+}
+
+pub(crate) fn type_path_to_name(type_path: &str) -> String {
     type_path
         .rsplit("::")
         .next()
@@ -244,7 +523,57 @@ fn type_path_to_name(type_path: &str) -> String {
         .to_string()
 }
 
-fn to_wit_ident(name: &str, used: &mut BTreeSet<String>) -> String {
+/// The keywords of WIT, which identifiers escape with a `%`
+const WIT_KEYWORDS: &[&str] = &[
+    "as",
+    "async",
+    "bool",
+    "borrow",
+    "char",
+    "constructor",
+    "enum",
+    "error-context",
+    "export",
+    "f32",
+    "f64",
+    "flags",
+    "from",
+    "func",
+    "future",
+    "import",
+    "include",
+    "interface",
+    "list",
+    "map",
+    "own",
+    "package",
+    "record",
+    "resource",
+    "result",
+    "s16",
+    "s32",
+    "s64",
+    "s8",
+    "static",
+    "stream",
+    "string",
+    "option",
+    "tuple",
+    "type",
+    "u16",
+    "u32",
+    "u64",
+    "u8",
+    "use",
+    "variant",
+    "with",
+    "world",
+];
+
+/// The kebab-case WIT identifier of `name`, unique among `used`
+pub(crate) fn to_wit_ident(name: &str, used: &mut BTreeSet<String>) -> String {
+    // Names built from escaped identifiers are escaped again once kebab-cased
+    let name = name.trim_start_matches('%');
     let mut out = String::new();
     let mut prev_lower = false;
 
@@ -279,39 +608,267 @@ fn to_wit_ident(name: &str, used: &mut BTreeSet<String>) -> String {
     }
 
     used.insert(candidate.clone());
+    if WIT_KEYWORDS.contains(&candidate.as_str()) {
+        return format!("%{candidate}");
+    }
     candidate
 }
 
-fn map_type(ty: &str) -> String {
+/// The WIT type of a Rust type path, or why it has none
+fn map_type(
+    ty: &str,
+    registry: &TypeRegistry,
+    types: &mut TypeDefs,
+    used_names: &mut BTreeSet<String>,
+) -> Result<String, String> {
     let ty = ty.trim();
     if ty == "()" {
-        return "()".to_string();
+        return Ok("()".to_string());
     }
 
-    let ty = ty.replace(' ', "");
+    let compact = ty.replace(' ', "");
 
-    if let Some(inner) = strip_generic(&ty, "Option") {
-        return format!("option<{}>", map_type(inner));
+    if let Some(inner) = strip_generic(&compact, "Option") {
+        let inner = map_type(inner, registry, types, used_names)?;
+        return Ok(format!("option<{inner}>"));
+    }
+    if let Some(inner) = strip_generic(&compact, "Vec") {
+        let inner = map_type(inner, registry, types, used_names)?;
+        return Ok(format!("list<{inner}>"));
     }
-    if let Some(inner) = strip_generic(&ty, "Vec") {
-        return format!("list<{}>", map_type(inner));
+    if let Some(inner) = strip_generic(&compact, "Result") {
+        let (ok, err) = split_generic_args(inner)
+            .ok_or_else(|| format!("Type '{ty}' is not a Result with two generic params"))?;
+        let ok = map_type(ok, registry, types, used_names)?;
+        let err = map_type(err, registry, types, used_names)?;
+        // Unit types are left out of WIT results
+        return Ok(match (ok.as_str(), err.as_str()) {
+            ("()", "()") => "result".to_string(),
+            ("()", err) => format!("result<_, {err}>"),
+            (ok, "()") => format!("result<{ok}>"),
+            (ok, err) => format!("result<{ok}, {err}>"),
+        });
     }
 
-    match strip_path(&ty) {
-        "bool" => "bool".to_string(),
-        "u8" => "u8".to_string(),
-        "u16" => "u16".to_string(),
-        "u32" => "u32".to_string(),
-        "u64" => "u64".to_string(),
-        "i8" => "s8".to_string(),
-        "i16" => "s16".to_string(),
-        "i32" => "s32".to_string(),
-        "i64" => "s64".to_string(),
-        "f32" => "f32".to_string(),
-        "f64" => "f64".to_string(),
-        "String" | "str" => "string".to_string(),
-        other => unimplemented!("Type '{other}' has no known representation in wit"),
+    let primitive = match strip_path(&compact) {
+        "bool" => "bool",
+        "u8" => "u8",
+        "u16" => "u16",
+        "u32" => "u32",
+        "u64" => "u64",
+        "i8" => "s8",
+        "i16" => "s16",
+        "i32" => "s32",
+        "i64" => "s64",
+        "f32" => "f32",
+        "f64" => "f64",
+        "String" | "str" => "string",
+        other => {
+            return match registry.get_with_type_path(ty).map(|r| r.type_info()) {
+                Some(TypeInfo::Struct(info)) => map_struct(ty, info, registry, types, used_names),
+                Some(TypeInfo::Enum(info)) => map_enum(ty, info, registry, types, used_names),
+                _ => match builtin_record(ty) {
+                    Some(fields) => map_builtin(ty, fields, registry, types, used_names),
+                    None => Err(format!("Type '{other}' has no known representation in wit")),
+                },
+            };
+        }
+    };
+    Ok(primitive.to_string())
+}
+
+/// Names the record of a reflected struct, rendering it the first time it is used
+fn map_struct(
+    type_path: &str,
+    info: &StructInfo,
+    registry: &TypeRegistry,
+    types: &mut TypeDefs,
+    used_names: &mut BTreeSet<String>,
+) -> Result<String, String> {
+    if let Some(name) = types.names.get(type_path) {
+        return Ok(name.clone());
+    }
+    if info.field_len() == 0 {
+        return Err(format!(
+            "Struct '{type_path}' has no fields, which wit records don't support"
+        ));
+    }
+
+    let name = types.name(type_path, used_names);
+    types.names.insert(type_path.to_string(), name.clone());
+
+    let docs = format!("  /// wasvy:type-path={type_path}\n");
+    let fields = info.iter().map(|field| (field.name(), field.type_path()));
+    let rendered = render_record(&name, &docs, fields, registry, types, used_names);
+    if rendered.is_err() {
+        types.names.remove(type_path);
+    }
+    rendered.map(|()| name)
+}
+
+/// The fields of the types with a built-in record, by type path
+///
+/// These match the reflected fields of the types, so their records are the same whether or not
+/// the types are registered.
+pub const BUILTIN_RECORDS: &[(&str, &[(&str, &str)])] = &[
+    ("glam::Vec2", &[("x", "f32"), ("y", "f32")]),
+    ("glam::Vec3", &[("x", "f32"), ("y", "f32"), ("z", "f32")]),
+    ("glam::Vec3A", &[("x", "f32"), ("y", "f32"), ("z", "f32")]),
+    (
+        "glam::Vec4",
+        &[("x", "f32"), ("y", "f32"), ("z", "f32"), ("w", "f32")],
+    ),
+    (
+        "glam::Quat",
+        &[("x", "f32"), ("y", "f32"), ("z", "f32"), ("w", "f32")],
+    ),
+    (
+        "glam::Mat4",
+        &[
+            ("x_axis", "glam::Vec4"),
+            ("y_axis", "glam::Vec4"),
+            ("z_axis", "glam::Vec4"),
+            ("w_axis", "glam::Vec4"),
+        ],
+    ),
+    (
+        "bevy_transform::components::transform::Transform",
+        &[
+            ("translation", "glam::Vec3"),
+            ("rotation", "glam::Quat"),
+            ("scale", "glam::Vec3"),
+        ],
+    ),
+];
+
+/// The fields of a type with a built-in record, see [`BUILTIN_RECORDS`]
+fn builtin_record(type_path: &str) -> Option<&'static [(&'static str, &'static str)]> {
+    BUILTIN_RECORDS
+        .iter()
+        .find(|(path, _)| *path == type_path)
+        .map(|(_, fields)| *fields)
+}
+
+/// Names the record of a type that isn't registered but has a built-in record, rendering it the
+/// first time it is used
+fn map_builtin(
+    type_path: &str,
+    fields: &[(&str, &str)],
+    registry: &TypeRegistry,
+    types: &mut TypeDefs,
+    used_names: &mut BTreeSet<String>,
+) -> Result<String, String> {
+    if let Some(name) = types.names.get(type_path) {
+        return Ok(name.clone());
+    }
+
+    let name = types.name(type_path, used_names);
+    types.names.insert(type_path.to_string(), name.clone());
+
+    let docs = format!("  /// wasvy:type-path={type_path}\n");
+    let fields = fields.iter().copied();
+    render_record(&name, &docs, fields, registry, types, used_names).map(|()| name)
+}
+
+/// Renders a record after the types of its fields, given their names and type paths
+fn render_record<'a>(
+    name: &str,
+    docs: &str,
+    fields: impl Iterator<Item = (&'a str, &'a str)>,
+    registry: &TypeRegistry,
+    types: &mut TypeDefs,
+    used_names: &mut BTreeSet<String>,
+) -> Result<(), String> {
+    let mut field_names = BTreeSet::new();
+    let mut out = format!("{docs}  record {name} {{\n");
+    for (field_name, type_path) in fields {
+        let field_name = to_wit_ident(field_name, &mut field_names);
+        let ty = map_type(type_path, registry, types, used_names)?;
+        out.push_str(&format!("    {field_name}: {ty},\n"));
     }
+    out.push_str("  }\n");
+    types.rendered.push(out);
+    Ok(())
+}
+
+/// Names the enum or variant of a reflected enum, rendering it the first time it is used
+///
+/// Enums with only unit variants are WIT enums. Otherwise they are WIT variants, where tuple
+/// variants carry their field (or a tuple of them) and struct variants carry a record named
+/// `<enum>-<variant>`.
+fn map_enum(
+    type_path: &str,
+    info: &EnumInfo,
+    registry: &TypeRegistry,
+    types: &mut TypeDefs,
+    used_names: &mut BTreeSet<String>,
+) -> Result<String, String> {
+    if let Some(name) = types.names.get(type_path) {
+        return Ok(name.clone());
+    }
+    if info.variant_len() == 0 {
+        return Err(format!(
+            "Enum '{type_path}' has no variants, which wit doesn't support"
+        ));
+    }
+
+    let name = types.name(type_path, used_names);
+    types.names.insert(type_path.to_string(), name.clone());
+    let rendered = render_enum(&name, type_path, info, registry, types, used_names);
+    if rendered.is_err() {
+        types.names.remove(type_path);
+    }
+    rendered.map(|()| name)
+}
+
+fn render_enum(
+    name: &str,
+    type_path: &str,
+    info: &EnumInfo,
+    registry: &TypeRegistry,
+    types: &mut TypeDefs,
+    used_names: &mut BTreeSet<String>,
+) -> Result<(), String> {
+    let is_unit = info
+        .iter()
+        .all(|variant| matches!(variant, VariantInfo::Unit(_)));
+    let kind = if is_unit { "enum" } else { "variant" };
+
+    let mut case_names = BTreeSet::new();
+    let mut out = format!("  /// wasvy:type-path={type_path}\n  {kind} {name} {{\n");
+    for variant in info.iter() {
+        let case = to_wit_ident(variant.name(), &mut case_names);
+        let payload = match variant {
+            VariantInfo::Unit(_) => None,
+            VariantInfo::Tuple(variant) => {
+                let fields = variant
+                    .iter()
+                    .map(|field| map_type(field.type_path(), registry, types, used_names))
+                    .collect::<Result<Vec<_>, _>>()?;
+                match fields.as_slice() {
+                    [] => None,
+                    [field] => Some(field.clone()),
+                    fields => Some(format!("tuple<{}>", fields.join(", "))),
+                }
+            }
+            VariantInfo::Struct(variant) if variant.field_len() == 0 => None,
+            VariantInfo::Struct(variant) => {
+                let record = to_wit_ident(&format!("{name}-{case}"), used_names);
+                let fields = variant
+                    .iter()
+                    .map(|field| (field.name(), field.type_path()));
+                render_record(&record, "", fields, registry, types, used_names)?;
+                Some(record)
+            }
+        };
+        match payload {
+            Some(payload) => out.push_str(&format!("    {case}({payload}),\n")),
+            None => out.push_str(&format!("    {case},\n")),
+        }
+    }
+    out.push_str("  }\n");
+    types.rendered.push(out);
+    Ok(())
 }
 
 fn strip_path(ty: &str) -> &str {
@@ -319,16 +876,27 @@ fn strip_path(ty: &str) -> &str {
 }
 
 fn strip_generic<'a>(ty: &'a str, name: &str) -> Option<&'a str> {
-    let simple = strip_path(ty);
-    if !simple.starts_with(name) {
+    // Only the outer type is stripped of its path, the generic params are full type paths too
+    let start = ty.find('<')?;
+    let end = ty.rfind('>')?;
+    if strip_path(&ty[..start]) != name || end <= start + 1 {
         return None;
     }
-    let start = simple.find('<')?;
-    let end = simple.rfind('>')?;
-    if end <= start + 1 {
-        return None;
+    Some(&ty[start + 1..end])
+}
+
+/// Splits the generic params of a type with two of them, such as `Result<T, E>`
+fn split_generic_args(args: &str) -> Option<(&str, &str)> {
+    let mut depth = 0;
+    for (index, ch) in args.char_indices() {
+        match ch {
+            '<' | '(' | '[' => depth += 1,
+            '>' | ')' | ']' => depth -= 1,
+            ',' if depth == 0 => return Some((&args[..index], &args[index + 1..])),
+            _ => {}
+        }
     }
-    Some(&simple[start + 1..end])
+    None
 }
 
 #[cfg(test)]
@@ -383,4 +951,177 @@ mod tests {
         assert!(output.contains("pct: func() -> f32"));
         assert!(output.contains("world host"));
     }
+
+    #[derive(Resource, Reflect, Default)]
+    struct DayNightCycle {
+        hour: f32,
+    }
+
+    impl DayNightCycle {
+        fn advance(&mut self, hours: f32) {
+            self.hour = (self.hour + hours) % 24.0;
+        }
+    }
+
+    #[test]
+    fn resources_are_constructed_from_res() {
+        let mut app = App::new();
+        app.register_type::<Health>();
+        app.register_type_data::<Health, crate::authoring::WasvyExport>();
+        app.register_type::<DayNightCycle>();
+        app.world()
+            .resource::<AppTypeRegistry>()
+            .write()
+            .get_mut(std::any::TypeId::of::<DayNightCycle>())
+            .unwrap()
+            .insert(crate::authoring::WasvyExport {
+                resource: true,
+                ..Default::default()
+            });
+        app.register_function(DayNightCycle::advance);
+
+        let type_registry = app.world().resource::<AppTypeRegistry>();
+        let function_registry = app.world().resource::<AppFunctionRegistry>();
+        let output = generate_wit(&Default::default(), type_registry, function_registry);
+
+        assert!(output.contains("use wasvy:ecs/app@0.0.9.{component, res}"));
+        assert!(output.contains("resource day-night-cycle {\n    constructor(res: res);"));
+        assert!(output.contains("advance: func(arg0: f32)"));
+        assert!(output.contains("resource health {\n    constructor(component: component);"));
+    }
+
+    #[derive(Component, Reflect, Default)]
+    struct Camera;
+
+    impl Camera {
+        fn look_at(&self, transform: bevy_transform::components::Transform) -> bevy_math::Mat4 {
+            transform.to_matrix()
+        }
+    }
+
+    #[test]
+    fn maps_math_types_without_registering_them() {
+        let mut app = App::new();
+        app.register_type::<Camera>();
+        app.register_type_data::<Camera, crate::authoring::WasvyExport>();
+        app.register_function(Camera::look_at);
+
+        let type_registry = app.world().resource::<AppTypeRegistry>();
+        let function_registry = app.world().resource::<AppFunctionRegistry>();
+        let output = generate_wit(&Default::default(), type_registry, function_registry);
+
+        assert!(output.contains("record vec3 {\n    x: f32,\n    y: f32,\n    z: f32,\n  }"));
+        assert!(output.contains("record quat {"));
+        assert!(output.contains("    x-axis: vec4,"));
+        assert!(output.contains("record transform {\n    translation: vec3,\n    rotation: quat,"));
+        assert!(output.contains("look-at: func(arg0: transform) -> mat4"));
+    }
+
+    #[test]
+    fn builtin_records_match_reflection() {
+        use bevy_math::{Mat4, Quat, Vec2, Vec3, Vec3A, Vec4};
+        use bevy_transform::components::Transform;
+
+        let mut registry = TypeRegistry::new();
+        registry.register::<Vec2>();
+        registry.register::<Vec3>();
+        registry.register::<Vec3A>();
+        registry.register::<Vec4>();
+        registry.register::<Quat>();
+        registry.register::<Mat4>();
+        registry.register::<Transform>();
+
+        for (type_path, fields) in BUILTIN_RECORDS {
+            let Some(TypeInfo::Struct(info)) = registry
+                .get_with_type_path(type_path)
+                .map(|registration| registration.type_info())
+            else {
+                panic!("{type_path} is not a reflected struct");
+            };
+            let reflected: Vec<_> = info
+                .iter()
+                .map(|field| (field.name(), field.type_path()))
+                .collect();
+            assert_eq!(reflected, fields.to_vec(), "{type_path}");
+        }
+    }
+
+    #[derive(Component, Reflect, Default)]
+    struct DebugOverlay;
+
+    #[test]
+    fn internal_components_are_opt_in() {
+        let mut app = App::new();
+        app.register_type::<Health>();
+        app.register_type_data::<Health, crate::authoring::WasvyExport>();
+        app.register_type::<DebugOverlay>();
+        app.world()
+            .resource::<AppTypeRegistry>()
+            .write()
+            .get_mut(std::any::TypeId::of::<DebugOverlay>())
+            .unwrap()
+            .insert(crate::authoring::WasvyExport::new(ExportLevel::Internal));
+
+        let type_registry = app.world().resource::<AppTypeRegistry>();
+        let function_registry = app.world().resource::<AppFunctionRegistry>();
+
+        let settings = WitGeneratorSettings::default();
+        let output = generate_wit(&settings, type_registry, function_registry);
+        assert!(output.contains("resource health"));
+        assert!(!output.contains("resource debug-overlay"));
+
+        let settings = WitGeneratorSettings {
+            export_level: ExportLevel::Internal,
+            ..Default::default()
+        };
+        let output = generate_wit(&settings, type_registry, function_registry);
+        assert!(output.contains("resource health"));
+        assert!(output.contains("resource debug-overlay"));
+    }
+
+    #[derive(Reflect)]
+    struct Opaque(u32);
+
+    #[derive(Reflect, Default)]
+    struct Marker;
+
+    #[derive(Component, Reflect, Default)]
+    struct Inventory {
+        slots: u32,
+    }
+
+    impl Inventory {
+        fn store(&mut self, item: Opaque) {
+            self.slots += item.0;
+        }
+
+        fn mark(&self, _marker: Marker) {}
+
+        fn slots(&self) -> u32 {
+            self.slots
+        }
+    }
+
+    #[test]
+    fn skips_methods_without_wit_types() {
+        let mut app = App::new();
+        app.register_type::<Inventory>();
+        app.register_type_data::<Inventory, crate::authoring::WasvyExport>();
+        app.register_type::<Marker>();
+        app.register_function(Inventory::store);
+        app.register_function(Inventory::mark);
+        app.register_function(Inventory::slots);
+
+        let type_registry = app.world().resource::<AppTypeRegistry>();
+        let function_registry = app.world().resource::<AppFunctionRegistry>();
+        let output = generate_wit(&Default::default(), type_registry, function_registry);
+
+        assert!(output.contains("resource inventory"));
+        assert!(output.contains("slots: func() -> u32"));
+        // Tuple structs that aren't registered have no representation
+        assert!(!output.contains("store:"));
+        // And wit records need at least one field
+        assert!(!output.contains("mark:"));
+        assert!(!output.contains("record marker"));
+    }
 }
@@ -0,0 +1,232 @@
+//! An on-disk cache of compiled mods.
+//!
+//! Compiling a mod with Wasmtime is slow for big mods, and happens every time the app starts.
+//! With a [`ModCache`], the [`ModAssetLoader`](crate::wasm_asset::ModAssetLoader) stores the
+//! compiled mod (a `.cwasm` file) and loads it on the next launch instead of compiling it again.
+//!
+//! Cached mods are keyed by a hash of the mod's bytes and of the engine's settings, so changing a
+//! mod, upgrading Wasmtime or changing settings like
+//! [`fuel_per_frame`](crate::WasmBackendPlugin::fuel_per_frame) compiles the mod again.
+//!
+//! Compiled mods are native code, loaded without the checks wasm goes through. Each cached mod
+//! is stored with its SHA-256 digest, so truncated or corrupted files are compiled again, but the
+//! digest can't stop anyone able to write to the cache directory from replacing both. The cache
+//! directory must be trusted: keep it out of folders that users or mods can write to, such as a
+//! shared downloads or mods folder.
+
+use std::{
+    fs,
+    hash::{DefaultHasher, Hash, Hasher},
+    io,
+    path::{Path, PathBuf},
+};
+
+use anyhow::Result;
+use bevy_log::prelude::*;
+use sha2::{Digest, Sha256};
+use wasmtime::component::Component;
+
+/// The extension of cached mods
+const EXTENSION: &str = "cwasm";
+
+/// Cached mods start with the SHA-256 digest of the compiled mod that follows it
+const DIGEST_LEN: usize = 32;
+
+/// An on-disk cache of compiled mods, see [`crate::cache`].
+///
+/// Enable it with [`WasmBackendPlugin::cache`](crate::WasmBackendPlugin::cache).
+///
+/// ```
+/// # use wasvy_wasm::cache::ModCache;
+/// let cache = ModCache::new("target/wasvy-cache").clear_on_startup(true);
+/// # let _ = cache;
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ModCache {
+    dir: PathBuf,
+    clear_on_startup: bool,
+}
+
+impl ModCache {
+    /// Caches compiled mods in `dir`, which is created when needed.
+    ///
+    /// `dir` must only be writable by the app, see [`crate::cache`].
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: dir.into(),
+            clear_on_startup: false,
+        }
+    }
+
+    /// Removes every cached mod when the app starts, so mods are always compiled once per launch.
+    ///
+    /// Stale entries are never loaded, but they are only removed by clearing the cache.
+    pub fn clear_on_startup(mut self, clear: bool) -> Self {
+        self.clear_on_startup = clear;
+        self
+    }
+
+    /// The directory mods are cached in
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Removes every cached mod
+    pub fn clear(&self) -> io::Result<()> {
+        let entries = match fs::read_dir(&self.dir) {
+            Ok(entries) => entries,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(()),
+            Err(err) => return Err(err),
+        };
+        for entry in entries {
+            let path = entry?.path();
+            if path
+                .extension()
+                .is_some_and(|extension| extension == EXTENSION)
+            {
+                fs::remove_file(path)?;
+            }
+        }
+        Ok(())
+    }
+
+    pub(crate) fn startup(&self) {
+        if self.clear_on_startup
+            && let Err(err) = self.clear()
+        {
+            warn!(
+                "Failed to clear the mod cache at {}: {err}",
+                self.dir.display()
+            );
+        }
+    }
+
+    /// Loads the compiled mod from the cache, or compiles it and caches it
+    pub(crate) fn load(&self, engine: &wasmtime::Engine, bytes: &[u8]) -> Result<Component> {
+        let path = self.path(engine, bytes);
+        if let Some(compiled) = self.read(&path) {
+            // SAFETY: the digest matches, so this is the file `precompile_component` below wrote,
+            // as long as the cache directory is trusted (see the module docs). Wasmtime still
+            // checks the file was compiled by a compatible engine.
+            match unsafe { Component::deserialize(engine, &compiled) } {
+                Ok(component) => return Ok(component),
+                Err(err) => warn!(
+                    "Failed to load the cached mod {}, compiling it again: {err}",
+                    path.display()
+                ),
+            }
+        }
+
+        let compiled = engine.precompile_component(bytes)?;
+        if let Err(err) = self.write(&path, &compiled) {
+            warn!("Failed to cache the mod at {}: {err}", path.display());
+        }
+
+        // SAFETY: the bytes were just compiled by this engine
+        let component = unsafe { Component::deserialize(engine, &compiled) }?;
+        Ok(component)
+    }
+
+    /// The file a mod is cached in, keyed by the mod's bytes and the engine's settings
+    fn path(&self, engine: &wasmtime::Engine, bytes: &[u8]) -> PathBuf {
+        let mut hasher = DefaultHasher::new();
+        engine.precompile_compatibility_hash().hash(&mut hasher);
+        let engine = hasher.finish();
+
+        let content: String = Sha256::digest(bytes)
+            .iter()
+            .map(|byte| format!("{byte:02x}"))
+            .collect();
+
+        self.dir
+            .join(format!("{content}-{engine:016x}.{EXTENSION}"))
+    }
+
+    /// Reads a cached mod, if its digest matches
+    fn read(&self, path: &Path) -> Option<Vec<u8>> {
+        let mut file = match fs::read(path) {
+            Ok(file) => file,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return None,
+            Err(err) => {
+                warn!("Failed to read the cached mod {}: {err}", path.display());
+                return None;
+            }
+        };
+
+        let verified = file
+            .split_at_checked(DIGEST_LEN)
+            .is_some_and(|(digest, compiled)| Sha256::digest(compiled)[..] == *digest);
+        if !verified {
+            warn!(
+                "The cached mod {} is corrupt, compiling it again",
+                path.display()
+            );
+            return None;
+        }
+        Some(file.split_off(DIGEST_LEN))
+    }
+
+    /// Writes to a temporary file first, so a crash never leaves a partially written mod behind
+    fn write(&self, path: &Path, compiled: &[u8]) -> io::Result<()> {
+        fs::create_dir_all(&self.dir)?;
+        let temporary = path.with_extension("tmp");
+        let mut file = Sha256::digest(compiled).to_vec();
+        file.extend_from_slice(compiled);
+        fs::write(&temporary, file)?;
+        fs::rename(&temporary, path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The smallest valid component
+    const COMPONENT: &[u8] = b"\0asm\x0d\0\x01\0";
+
+    #[test]
+    fn caches_compiled_mods() {
+        let dir = std::env::temp_dir().join(format!("wasvy-cache-test-{}", std::process::id()));
+        let cache = ModCache::new(&dir);
+        let engine = wasmtime::Engine::default();
+
+        cache.load(&engine, COMPONENT).unwrap();
+        let path = cache.path(&engine, COMPONENT);
+        assert!(path.exists());
+
+        // Loaded from the cache
+        cache.load(&engine, COMPONENT).unwrap();
+
+        // Engines with other settings don't share compiled mods
+        let mut config = wasmtime::Config::new();
+        config.consume_fuel(true);
+        let other = wasmtime::Engine::new(&config).unwrap();
+        assert_ne!(cache.path(&other, COMPONENT), path);
+
+        cache.clear().unwrap();
+        assert!(!path.exists());
+        fs::remove_dir_all(dir).unwrap();
+    }
+    #[test]
+    fn compiles_corrupt_mods_again() {
+        let dir =
+            std::env::temp_dir().join(format!("wasvy-cache-corrupt-test-{}", std::process::id()));
+        let cache = ModCache::new(&dir);
+        let engine = wasmtime::Engine::default();
+
+        cache.load(&engine, COMPONENT).unwrap();
+        let path = cache.path(&engine, COMPONENT);
+        let written = fs::read(&path).unwrap();
+
+        let mut corrupt = written.clone();
+        *corrupt.last_mut().unwrap() ^= 1;
+        fs::write(&path, corrupt).unwrap();
+        assert!(cache.read(&path).is_none());
+
+        // The corrupt file is replaced
+        cache.load(&engine, COMPONENT).unwrap();
+        assert_eq!(fs::read(&path).unwrap(), written);
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
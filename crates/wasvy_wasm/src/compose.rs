@@ -0,0 +1,148 @@
+//! Links mods with the components they import from, see
+//! [`Mods::load_composed`](wasvy_runtime::mods::Mods::load_composed).
+
+use std::collections::HashSet;
+
+use anyhow::{Result, bail};
+use wasmtime::{
+    component::{
+        Component, ComponentExportIndex, InstancePre, LinkerInstance, types::ComponentItem,
+    },
+    error::Context,
+};
+
+use crate::{engine::Linker, host::WasmHost, version::check_imports};
+
+/// Returns a copy of `linker` defining the exports of `plugs` that `component` or the plugs import.
+///
+/// Each plug is linked with the host and the plugs listed after it, so its exports satisfy imports
+/// of the mod and of the plugs listed before it. A plug is instantiated in the store of the mod
+/// the first time one of its exports is called, and its instance is kept with the store.
+pub(crate) fn link_plugs(
+    linker: &Linker,
+    component: &Component,
+    plugs: &[Component],
+) -> Result<Linker> {
+    let engine = linker.engine().clone();
+    let mut imports = HashSet::new();
+    for component in std::iter::once(component).chain(plugs) {
+        let component_type = component.component_type();
+        let names = component_type.imports(&engine).map(|(name, _)| name);
+        imports.extend(names.map(str::to_string));
+    }
+
+    let mut linker = linker.clone();
+    for (index, plug) in plugs.iter().enumerate().rev() {
+        let component_type = plug.component_type();
+        check_imports(component_type.imports(&engine).map(|(name, _)| name))?;
+        let instance_pre = linker
+            .instantiate_pre(plug)
+            .with_context(|| format!("Failed to link plug {index}"))?;
+
+        for (name, export) in component_type.exports(&engine) {
+            if !imports.contains(name) {
+                continue;
+            }
+            let export_index = plug
+                .get_export_index(None, name)
+                .expect("the plug exports it");
+            match export.ty {
+                ComponentItem::ComponentFunc(_) => {
+                    forward(&mut linker.root(), name, index, &instance_pre, export_index)?;
+                }
+                ComponentItem::ComponentInstance(instance) => {
+                    let mut linker = linker.instance(name)?;
+                    for (func, export) in instance.exports(&engine) {
+                        match export.ty {
+                            ComponentItem::ComponentFunc(_) => {
+                                let func_index = plug
+                                    .get_export_index(Some(&export_index), func)
+                                    .expect("the plug exports it");
+                                forward(&mut linker, func, index, &instance_pre, func_index)?;
+                            }
+                            ComponentItem::Resource(_) => bail!(
+                                "Plug {index} exports the resource {func} of {name}, which can't be linked host-side"
+                            ),
+                            _ => {}
+                        }
+                    }
+                }
+                ComponentItem::Resource(_) => {
+                    bail!(
+                        "Plug {index} exports the resource {name}, which can't be linked host-side"
+                    )
+                }
+                _ => {}
+            }
+        }
+    }
+
+    Ok(linker)
+}
+
+/// Defines `name` as a function calling an export of the plug at `index`
+fn forward(
+    linker: &mut LinkerInstance<WasmHost>,
+    name: &str,
+    index: usize,
+    instance_pre: &InstancePre<WasmHost>,
+    export_index: ComponentExportIndex,
+) -> Result<()> {
+    let instance_pre = instance_pre.clone();
+    linker.func_new(name, move |mut store, _, params, results| {
+        let instance = match store.data().plug_instance(index) {
+            Some(instance) => instance,
+            None => {
+                let instance = instance_pre
+                    .instantiate(&mut store)
+                    .with_context(|| format!("Failed to instantiate plug {index}"))?;
+                store.data_mut().keep_plug_instance(index, instance);
+                instance
+            }
+        };
+
+        let func = instance
+            .get_func(&mut store, export_index)
+            .ok_or_else(|| wasmtime::Error::msg(format!("Plug {index} is missing an export")))?;
+        func.call(&mut store, params, results)
+    })?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use wasmtime::component::Val;
+
+    use super::*;
+    use crate::{
+        engine::{Engine, create_linker},
+        runner::{Config, Runner},
+        test_utils::{counter_plug, counter_user},
+    };
+
+    #[test]
+    fn plugs_live_as_long_as_the_mod() {
+        let engine = Engine::default();
+        let component = counter_user(engine.inner());
+        let plug = counter_plug(engine.inner());
+        let linker = create_linker(&engine);
+        assert!(linker.instantiate_pre(&component).is_err());
+        let linker = link_plugs(&linker, &component, &[plug]).unwrap();
+        let instance_pre = linker.instantiate_pre(&component).unwrap();
+
+        let run = |runner: &mut Runner| {
+            let mut results = [Val::U32(0)];
+            runner
+                .call_reused(&instance_pre, Config::Call, "run", &[], &mut results)
+                .unwrap();
+            results[0].clone()
+        };
+        let mut runner = Runner::new(&engine, "my-mod", None);
+        assert_eq!(run(&mut runner), Val::U32(1));
+        assert_eq!(run(&mut runner), Val::U32(2));
+
+        // Each instance of the mod has its own instance of the plug
+        let mut runner = Runner::new(&engine, "my-mod", None);
+        assert_eq!(run(&mut runner), Val::U32(1));
+    }
+}
@@ -0,0 +1,135 @@
+use anyhow::{Context, Result};
+use bevy_ecs::{
+    prelude::*,
+    schedule::ScheduleConfigs,
+    system::{BoxedSystem, FilteredResourcesParamBuilder, ParamBuilder, SystemState},
+    world::FilteredResources,
+};
+use bevy_reflect::PartialReflect;
+use wasvy_runtime::{
+    component::{ResourceRef, TypePath, resource_equals},
+    serialize::{CodecResource, ModCodec},
+};
+
+/// A condition a mod system must meet to run, see `system.run-if-resource-equals`
+pub(crate) enum RunCondition {
+    ResourceEquals { type_path: TypePath, value: Vec<u8> },
+    EveryNFrames(u32),
+}
+
+impl RunCondition {
+    /// Translates the condition into a Bevy run condition of the system
+    pub(crate) fn apply(
+        &self,
+        config: ScheduleConfigs<BoxedSystem>,
+        codec: ModCodec,
+        world: &mut World,
+    ) -> Result<ScheduleConfigs<BoxedSystem>> {
+        Ok(match self {
+            Self::ResourceEquals { type_path, value } => {
+                let resource = ResourceRef::new(type_path, world)?;
+                let expected = decode(resource.type_path(), value, codec, world)?;
+                let component_id = resource.component_id();
+                let state: SystemState<(FilteredResources, Res<AppTypeRegistry>)> = (
+                    FilteredResourcesParamBuilder::new(move |builder| {
+                        builder.add_read_by_id(component_id);
+                    }),
+                    ParamBuilder,
+                )
+                    .build_state(world);
+                let condition = state.build_system(
+                    move |resources: FilteredResources, type_registry: Res<AppTypeRegistry>| {
+                        resource_equals(&resources, &resource, expected.as_ref(), &type_registry)
+                    },
+                );
+                config.run_if(condition)
+            }
+            Self::EveryNFrames(n) => {
+                let n = *n;
+                config.run_if(move |mut frame: Local<u32>| {
+                    let run = *frame == 0;
+                    *frame = (*frame + 1) % n;
+                    run
+                })
+            }
+        })
+    }
+}
+
+/// Decodes the value a resource is compared with, using the mod's codec
+fn decode(
+    type_path: &str,
+    value: &[u8],
+    codec: ModCodec,
+    world: &World,
+) -> Result<Box<dyn PartialReflect>> {
+    let type_registry = world.resource::<AppTypeRegistry>().read();
+    let registration = type_registry
+        .get_with_type_path(type_path)
+        .expect("ResourceRef type_path be registered");
+    let value = match codec.codec()? {
+        Some(codec) => codec.decode_reflect(value, registration, &type_registry),
+        None => {
+            world
+                .resource::<CodecResource>()
+                .decode_reflect(value, registration, &type_registry)
+        }
+    };
+    value.with_context(|| format!("Invalid value to compare {type_path} with"))
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy_reflect::Reflect;
+
+    use super::*;
+
+    #[derive(Resource, Reflect, PartialEq)]
+    #[reflect(Resource)]
+    enum GameState {
+        Menu,
+        Playing,
+    }
+
+    #[derive(Resource, Default)]
+    struct Runs(u32);
+
+    fn run(world: &mut World, conditions: &[RunCondition], frames: u32) -> u32 {
+        world.insert_resource(Runs::default());
+        let mut config = IntoScheduleConfigs::into_configs(Box::new(IntoSystem::into_system(
+            |mut runs: ResMut<Runs>| runs.0 += 1,
+        )) as BoxedSystem);
+        for condition in conditions {
+            config = condition.apply(config, ModCodec::Json, world).unwrap();
+        }
+
+        let mut schedule = Schedule::default();
+        schedule.add_systems(config);
+        for _ in 0..frames {
+            schedule.run(world);
+        }
+        world.resource::<Runs>().0
+    }
+
+    #[test]
+    fn applies_run_conditions() {
+        let mut world = World::new();
+        world.init_resource::<AppTypeRegistry>();
+        world
+            .resource::<AppTypeRegistry>()
+            .write()
+            .register::<GameState>();
+        world.insert_resource(GameState::Menu);
+
+        assert_eq!(run(&mut world, &[RunCondition::EveryNFrames(3)], 7), 3);
+
+        let playing = RunCondition::ResourceEquals {
+            type_path: <GameState as bevy_reflect::TypePath>::type_path().to_string(),
+            value: br#""Playing""#.to_vec(),
+        };
+        assert_eq!(run(&mut world, std::slice::from_ref(&playing), 2), 0);
+
+        *world.resource_mut::<GameState>() = GameState::Playing;
+        assert_eq!(run(&mut world, &[playing], 2), 2);
+    }
+}
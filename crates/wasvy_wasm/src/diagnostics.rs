@@ -0,0 +1,150 @@
+//! Per-system [diagnostics](bevy_diagnostic) of mods.
+//!
+//! Enable them with [`WasmBackendPlugin::diagnostics`](crate::WasmBackendPlugin::diagnostics).
+//! Each mod system then records every [`ModMetric`] in the [`DiagnosticsStore`], so they show up
+//! in overlays and inspectors like any other diagnostic.
+
+use std::time::Duration;
+
+use bevy_diagnostic::{Diagnostic, DiagnosticPath, Diagnostics, DiagnosticsStore};
+use bevy_ecs::prelude::*;
+
+/// The first component of the path of every mod diagnostic
+pub const PREFIX: &str = "wasvy";
+
+/// A metric recorded for each mod system, see [`ModMetric::path`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ModMetric {
+    /// How long each run of the system took, in milliseconds
+    RunTime,
+
+    /// How many times the system ran
+    Runs,
+
+    /// How many times the system trapped
+    Traps,
+
+    /// The size of the linear memory of the system's instance, in bytes
+    Memory,
+}
+
+impl ModMetric {
+    /// Every metric, in the order they are recorded
+    pub const ALL: [Self; 4] = [Self::RunTime, Self::Runs, Self::Traps, Self::Memory];
+
+    /// The last component of the metric's path
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::RunTime => "run_time",
+            Self::Runs => "runs",
+            Self::Traps => "traps",
+            Self::Memory => "memory",
+        }
+    }
+
+    /// The path the metric of a mod system is recorded under: `wasvy/<mod>/<system>/<metric>`.
+    ///
+    /// Slashes in the mod and system names are replaced with underscores.
+    ///
+    /// ```
+    /// # use wasvy_wasm::diagnostics::ModMetric;
+    /// let path = ModMetric::RunTime.path("mods/physics.wasm", "gravity");
+    /// assert_eq!(path.as_str(), "wasvy/mods_physics.wasm/gravity/run_time");
+    /// ```
+    pub fn path(self, mod_name: &str, system_name: &str) -> DiagnosticPath {
+        DiagnosticPath::from_components([
+            PREFIX,
+            &component(mod_name),
+            &component(system_name),
+            self.name(),
+        ])
+    }
+
+    fn suffix(self) -> &'static str {
+        match self {
+            Self::RunTime => "ms",
+            Self::Memory => "B",
+            Self::Runs | Self::Traps => "",
+        }
+    }
+}
+
+/// Makes a name usable as a single component of a [`DiagnosticPath`]
+fn component(name: &str) -> String {
+    if name.is_empty() {
+        return "_".to_string();
+    }
+    name.replace('/', "_")
+}
+
+/// The diagnostics of a single mod system
+pub(crate) struct SystemDiagnostics {
+    paths: [DiagnosticPath; 4],
+    runs: u64,
+    traps: u64,
+}
+
+impl SystemDiagnostics {
+    /// Registers the diagnostics of a mod system, if the app has a [`DiagnosticsStore`]
+    pub(crate) fn new(mod_name: &str, system_name: &str, world: &mut World) -> Self {
+        let paths = ModMetric::ALL.map(|metric| metric.path(mod_name, system_name));
+        if let Some(mut store) = world.get_resource_mut::<DiagnosticsStore>() {
+            for (metric, path) in ModMetric::ALL.into_iter().zip(paths.iter()) {
+                // Systems of reloaded mods keep the history of their previous instance
+                if store.get(path).is_none() {
+                    store.add(Diagnostic::new(path.clone()).with_suffix(metric.suffix()));
+                }
+            }
+        }
+
+        Self {
+            paths,
+            runs: 0,
+            traps: 0,
+        }
+    }
+
+    /// Records a run of the system
+    pub(crate) fn record(
+        &mut self,
+        diagnostics: &mut Diagnostics,
+        run_time: Duration,
+        trapped: bool,
+        memory: usize,
+    ) {
+        self.runs += 1;
+        if trapped {
+            self.traps += 1;
+        }
+
+        let [run_time_path, runs_path, traps_path, memory_path] = &self.paths;
+        diagnostics.add_measurement(run_time_path, || run_time.as_secs_f64() * 1000.0);
+        diagnostics.add_measurement(runs_path, || self.runs as f64);
+        diagnostics.add_measurement(traps_path, || self.traps as f64);
+        diagnostics.add_measurement(memory_path, || memory as f64);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn registers_system_diagnostics() {
+        let mut world = World::new();
+        world.init_resource::<DiagnosticsStore>();
+
+        SystemDiagnostics::new("my/mod", "", &mut world);
+
+        let store = world.resource::<DiagnosticsStore>();
+        let paths: Vec<_> = store
+            .iter()
+            .map(|d| d.path().as_str().to_string())
+            .collect();
+        assert_eq!(paths.len(), 4);
+        for metric in ModMetric::ALL {
+            let path = format!("wasvy/my_mod/_/{}", metric.name());
+            assert!(paths.contains(&path), "missing {path}");
+        }
+    }
+}
@@ -1,26 +1,160 @@
+use std::time::Duration;
+
 use bevy_derive::Deref;
 use bevy_ecs::resource::Resource;
+use wasvy_runtime::limits::ModMemoryLimits;
 
 use crate::host::WasmHost;
 
+/// How often the epoch of the [`Engine`] is incremented when mods have a timeout
+pub(crate) const EPOCH_TICK: Duration = Duration::from_millis(1);
+
+/// The number of nested calls into a mod engines allow by default, see [`Engine::max_call_depth`]
+pub(crate) const DEFAULT_MAX_CALL_DEPTH: usize = 4;
+
 /// Cross engine instatiation of WASM components is not supported.
 /// This resources is the global [`Engine`](wasmtime::Engine) that is used for instatiation.
 ///
 /// Check the wasmtime [`Engine`](wasmtime::Engine) docs for more information.
 #[derive(Resource, Clone, Deref)]
 #[component(immutable)]
-pub(crate) struct Engine(wasmtime::Engine);
+pub(crate) struct Engine {
+    #[deref]
+    engine: wasmtime::Engine,
+
+    /// How long a single call into a mod may run before it is interrupted
+    timeout: Option<Duration>,
+
+    /// How much fuel each mod may use per frame. Fuel is only metered when this is set.
+    fuel: Option<u64>,
+
+    /// The limits of mods without their own [`ModMemoryLimits`]
+    memory_limits: ModMemoryLimits,
+
+    /// Whether the stdout and stderr of mods go to Bevy's log instead of the host's
+    capture_output: bool,
+
+    /// Whether mod systems record [diagnostics](crate::diagnostics)
+    diagnostics: bool,
+
+    /// How many calls into a mod may be on the stack at once, see [`call_reentrant`](crate::runner::call_reentrant)
+    max_call_depth: usize,
+}
 
 impl Default for Engine {
     fn default() -> Self {
-        let engine = wasmtime::Engine::default();
-        Self(engine)
+        Self::new(None)
     }
 }
 
 impl Engine {
+    fn new(fuel: Option<u64>) -> Self {
+        let mut config = wasmtime::Config::new();
+        config.epoch_interruption(true);
+        config.consume_fuel(fuel.is_some());
+        let engine = wasmtime::Engine::new(&config).expect("valid engine config");
+
+        Self {
+            engine,
+            timeout: None,
+            fuel,
+            memory_limits: ModMemoryLimits::default(),
+            capture_output: false,
+            diagnostics: false,
+            max_call_depth: DEFAULT_MAX_CALL_DEPTH,
+        }
+    }
+
+    /// Creates a new engine that meters fuel, keeping the other settings of this one.
+    ///
+    /// Fuel metering can't be enabled on an existing engine.
+    pub(crate) fn with_fuel(&self, fuel: u64) -> Self {
+        Self {
+            timeout: self.timeout,
+            memory_limits: self.memory_limits,
+            capture_output: self.capture_output,
+            diagnostics: self.diagnostics,
+            max_call_depth: self.max_call_depth,
+            ..Self::new(Some(fuel))
+        }
+    }
+
     pub(crate) fn inner(&self) -> &wasmtime::Engine {
-        &self.0
+        &self.engine
+    }
+
+    pub(crate) fn fuel(&self) -> Option<u64> {
+        self.fuel
+    }
+
+    pub(crate) fn timeout(&self) -> Option<Duration> {
+        self.timeout
+    }
+
+    pub(crate) fn set_timeout(&mut self, timeout: Duration) {
+        self.timeout = Some(timeout);
+    }
+
+    pub(crate) fn memory_limits(&self) -> ModMemoryLimits {
+        self.memory_limits
+    }
+
+    pub(crate) fn set_memory_limits(&mut self, memory_limits: ModMemoryLimits) {
+        self.memory_limits = memory_limits;
+    }
+
+    pub(crate) fn capture_output(&self) -> bool {
+        self.capture_output
+    }
+
+    pub(crate) fn set_capture_output(&mut self, capture_output: bool) {
+        self.capture_output = capture_output;
+    }
+
+    pub(crate) fn diagnostics(&self) -> bool {
+        self.diagnostics
+    }
+
+    pub(crate) fn set_diagnostics(&mut self, diagnostics: bool) {
+        self.diagnostics = diagnostics;
+    }
+
+    pub(crate) fn max_call_depth(&self) -> usize {
+        self.max_call_depth
+    }
+
+    pub(crate) fn set_max_call_depth(&mut self, max_call_depth: usize) {
+        self.max_call_depth = max_call_depth;
+    }
+
+    /// The number of epoch ticks a call into a mod may take, see [`EPOCH_TICK`]
+    pub(crate) fn epoch_deadline(&self) -> u64 {
+        match self.timeout {
+            Some(timeout) => (timeout.as_micros() / EPOCH_TICK.as_micros()).max(1) as u64,
+            // The epoch is never incremented without a timeout
+            None => u64::MAX,
+        }
+    }
+
+    /// Increments the epoch on a background thread, for as long as the engine lives.
+    ///
+    /// This is a no-op without a timeout.
+    pub(crate) fn start_epoch_ticker(&self) {
+        if self.timeout.is_none() {
+            return;
+        }
+
+        let engine = self.engine.weak();
+        std::thread::Builder::new()
+            .name("wasvy epoch ticker".into())
+            .spawn(move || {
+                while let Some(engine) = engine.upgrade() {
+                    engine.increment_epoch();
+                    drop(engine);
+                    std::thread::sleep(EPOCH_TICK);
+                }
+            })
+            .expect("failed to spawn the epoch ticker thread");
     }
 }
 
@@ -6,8 +6,9 @@ use wasmtime::component::Resource;
 use wasmtime_wasi::ResourceTable;
 use wasvy_runtime::{
     access::ModAccess,
-    cleanup::DespawnModEntity,
     component::{insert_component, remove_component},
+    permissions::ComponentAccess,
+    sandbox::Sandboxed,
 };
 
 use crate::{
@@ -16,6 +17,11 @@ use crate::{
     runner::State,
 };
 
+/// Used to check which sandbox entities passed in by mods belong to (see [check_access]), and to
+/// read their children
+pub(crate) type HierarchyQuery<'w, 's> =
+    Query<'w, 's, (Option<&'static Sandboxed>, Option<&'static Children>)>;
+
 /// A helper to ingest one host resource and create another with the same entity
 pub(crate) fn map_entity<I, F>(
     host: &mut WasmHost,
@@ -25,7 +31,10 @@ where
     for<'a> &'a I: Into<Entity>,
     F: From<Entity> + Send,
 {
-    let State::RunSystem { table, .. } = host.access() else {
+    let State::RunSystem {
+        table, commands, ..
+    } = host.access()
+    else {
         let ty = type_name::<I>();
         return Err(wasmtime::Error::msg(format!(
             "{ty} resource is only accessible when running systems"
@@ -34,17 +43,68 @@ where
 
     let input = table.get(&input)?;
     let entity = input.into();
+    check_entity(commands, entity)?;
     entity_resource(entity, table)
 }
 
+/// Checks that the entity with these bits (see [Entity::to_bits]) exists
+pub(crate) fn entity_from_bits(host: &mut WasmHost, bits: u64) -> Result<Entity, wasmtime::Error> {
+    let State::RunSystem { commands, .. } = host.access() else {
+        return Err(wasmtime::Error::msg(
+            "Entities can only be created when running systems",
+        ));
+    };
+
+    let entity = Entity::try_from_bits(bits)
+        .ok_or_else(|| wasmtime::Error::msg(format!("{bits} is not a valid entity")))?;
+    check_entity(commands, entity)?;
+    Ok(entity)
+}
+
+/// Entities may be despawned while a mod still holds them, and their slot reused by another entity.
+///
+/// Since the generation is part of the [Entity], this makes sure a stale entity errors instead of
+/// touching whichever entity took its place.
+pub(crate) fn check_entity(commands: &mut Commands, entity: Entity) -> Result<(), wasmtime::Error> {
+    commands.get_entity(entity).map(|_| ()).map_err(|_| {
+        wasmtime::Error::msg(format!(
+            "Entity {entity} does not exist, it may have been despawned"
+        ))
+    })
+}
+
+/// Mods can hold on to entities from anywhere, for example by storing their bits.
+///
+/// This makes sure a mod only gets commands for entities within its [ModAccess].
+pub(crate) fn check_access(
+    hierarchy: &HierarchyQuery,
+    access: &ModAccess,
+    entity: Entity,
+) -> Result<(), wasmtime::Error> {
+    // Otherwise the entity was spawned by commands that haven't been applied yet
+    let Ok((sandboxed, _)) = hierarchy.get(entity) else {
+        return Ok(());
+    };
+
+    if access.contains(sandboxed) {
+        Ok(())
+    } else {
+        Err(wasmtime::Error::msg(format!(
+            "Entity {entity} is outside of the mod's access"
+        )))
+    }
+}
+
 pub(crate) fn spawn_empty<F>(host: &mut WasmHost) -> Result<Resource<F>, wasmtime::Error>
 where
     F: From<Entity> + Send,
 {
+    let mod_name = host.mod_name().to_string();
     let State::RunSystem {
         commands,
         table,
         insert_despawn_component,
+        spawns,
         access,
         ..
     } = host.access()
@@ -54,20 +114,21 @@ where
         ));
     };
 
+    // Reject spawns over the quota of the sandbox, see [SandboxQuota](wasvy_runtime::sandbox::SandboxQuota)
+    if let (Some(spawns), ModAccess::Sandbox(sandbox)) = (spawns, access) {
+        spawns.spawn(&mod_name, *sandbox)?;
+    }
+
     let mut entity_commands = commands.spawn_empty();
 
     // Make sure the entity is not spawned outside the sandbox
-    // The mod can still override the ChildOf with its own value
-    // Note: We can't currently prevent a mod from creating a component that has a relation to a component outside the sandbox
-    // TODO: Restrict what entities a mod can reference via permissions
+    // The mod can still override the ChildOf with its own value, as allowed by the RelationPolicy
     if let ModAccess::Sandbox(entity) = access {
         entity_commands.insert(ChildOf(*entity));
     };
 
-    // Make sure this entity is despawned when the mod is despawned. See [ModDespawnBehaviour]
-    if let Some(mod_id) = insert_despawn_component.as_ref() {
-        entity_commands.insert(DespawnModEntity::new(*mod_id));
-    }
+    // Make sure this entity is despawned when the mod is despawned. See [ModDespawnBehaviour](wasvy_runtime::mods::ModDespawnBehaviour)
+    insert_despawn_component.insert(&mut entity_commands);
 
     let entity = entity_commands.id();
     trace!("Spawn empty ({entity})");
@@ -87,11 +148,14 @@ where
         return Ok(());
     }
 
+    let command_errors = host.command_errors().clone();
     let State::RunSystem {
         commands,
         table,
         type_registry,
         codec,
+        aliases,
+        permissions,
         ..
     } = host.access()
     else {
@@ -103,8 +167,11 @@ where
 
     let input = table.get(input)?;
     let entity = input.into();
+    check_entity(commands, entity)?;
     trace!("Insert components to ({entity})");
+    let mut failed = Vec::new();
     for (type_path, serialized_component) in bundle {
+        let type_path = aliases.resolve(&type_path).to_string();
         #[cfg(feature = "serde_json")]
         trace!(
             "- {type_path}: {}",
@@ -113,16 +180,33 @@ where
         #[cfg(not(feature = "serde_json"))]
         trace!("- {type_path}: {:?}", serialized_component);
 
-        // TODO: collect instead of emitting immediately
-        insert_component(
+        // Failed inserts are reported to the mod, see `commands.take-errors`
+        if let Err(denied) = permissions.check(ComponentAccess::Insert, &type_path) {
+            failed.push((type_path, denied.to_string()));
+            continue;
+        }
+        // Relationships are checked once the commands are applied, so denied ones are reported then
+        let command_errors = command_errors.clone();
+        let relation = type_path.clone();
+        let relation_denied = move |message| {
+            command_errors.push("insert", Some(&relation), Some(entity), message);
+        };
+        if let Err(err) = insert_component(
             commands,
             type_registry,
             codec,
+            permissions,
             entity,
-            type_path,
+            type_path.clone(),
             serialized_component,
-        )
-        .map_err(wasmtime::Error::msg)?;
+            relation_denied,
+        ) {
+            failed.push((type_path, err.to_string()));
+        }
+    }
+
+    for (type_path, message) in failed {
+        host.command_failed("insert", Some(&type_path), Some(entity), message);
     }
 
     Ok(())
@@ -144,6 +228,8 @@ where
         commands,
         table,
         wasm_registry,
+        aliases,
+        permissions,
         ..
     } = host.access()
     else {
@@ -155,16 +241,31 @@ where
 
     let input = table.get(&input)?;
     let entity = input.into();
+    check_entity(commands, entity)?;
     trace!("Remove components from ({entity})");
+    let mut failed = Vec::new();
     for type_path in bundle {
         trace!("- {type_path}");
+        let type_path = aliases.resolve(&type_path).to_string();
+
+        // Removing a component changes it, so mods that may mutate or insert it may remove it too
+        if !permissions.is_allowed(ComponentAccess::Mutate, &type_path)
+            && let Err(denied) = permissions.check(ComponentAccess::Insert, &type_path)
+        {
+            failed.push((type_path, denied.to_string()));
+            continue;
+        }
         remove_component(commands, wasm_registry, entity, type_path);
     }
 
+    for (type_path, message) in failed {
+        host.command_failed("remove", Some(&type_path), Some(entity), message);
+    }
+
     Ok(())
 }
 
-fn entity_resource<T>(
+pub(crate) fn entity_resource<T>(
     entity: Entity,
     table: &mut ResourceTable,
 ) -> Result<Resource<T>, wasmtime::Error>
@@ -175,3 +276,121 @@ where
     let output = table.push(output)?;
     Ok(output)
 }
+
+#[cfg(test)]
+mod tests {
+    use bevy_ecs::reflect::AppTypeRegistry;
+    use bevy_reflect::{Reflect, TypePath as _};
+    use wasvy_runtime::{
+        permissions::ComponentPermissions, sandbox::Sandbox, schedule::ModSchedules,
+    };
+
+    use super::*;
+    use crate::{
+        bindings::wasvy::ecs::app::HostCommands,
+        host::{CommandErrors, WasmEntityCommands},
+        test_utils::{run_system, run_system_with_errors, world},
+    };
+
+    #[derive(Component, Reflect)]
+    #[reflect(Component)]
+    struct Health(u32);
+
+    #[derive(Component, Reflect)]
+    #[reflect(Component)]
+    struct Armor(u32);
+
+    #[derive(Component, Reflect)]
+    #[reflect(Component)]
+    struct Shield(u32);
+
+    #[test]
+    fn removing_needs_permission_to_mutate_or_insert() {
+        let mut world = world();
+        {
+            let mut type_registry = world.resource::<AppTypeRegistry>().write();
+            type_registry.register::<Health>();
+            type_registry.register::<Armor>();
+            type_registry.register::<Shield>();
+        }
+        let entity = world.spawn((Health(1), Armor(2), Shield(3))).id();
+        let permissions = ComponentPermissions::default()
+            .deny(
+                ComponentAccess::Mutate,
+                [Health::type_path(), Shield::type_path()],
+            )
+            .deny(
+                ComponentAccess::Insert,
+                [Health::type_path(), Armor::type_path()],
+            );
+
+        let errors = run_system(&mut world, &[], &permissions, |host| {
+            let entity_commands = host.table().push(WasmEntityCommands(entity)).unwrap();
+            let bundle = [Health::type_path(), Armor::type_path(), Shield::type_path()]
+                .map(str::to_string)
+                .to_vec();
+            remove(host, entity_commands, bundle).unwrap();
+            host.take_errors(Resource::new_own(0)).unwrap()
+        });
+
+        let entity = world.entity(entity);
+        assert!(entity.contains::<Health>());
+        assert!(!entity.contains::<Armor>());
+        assert!(!entity.contains::<Shield>());
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].command, "remove");
+        assert_eq!(errors[0].type_path.as_deref(), Some(Health::type_path()));
+    }
+
+    #[test]
+    fn stale_entities_are_rejected() {
+        let mut world = world();
+        let stale = world.spawn_empty().id();
+        // Reuse the slot of the despawned entity right away, as the allocator eventually does
+        let reused = world.despawn_no_free(stale).unwrap();
+        world.spawn_empty_at(reused).unwrap();
+        assert_eq!(reused.index(), stale.index());
+
+        run_system(&mut world, &[], &Default::default(), |host| {
+            assert!(entity_from_bits(host, stale.to_bits()).is_err());
+            assert_eq!(entity_from_bits(host, reused.to_bits()).unwrap(), reused);
+
+            let entity_commands = host.table().push(WasmEntityCommands(stale)).unwrap();
+            let mapped = map_entity::<_, WasmEntityCommands>(host, entity_commands);
+            assert!(mapped.is_err());
+        });
+    }
+
+    #[test]
+    fn denied_relations_are_reported() {
+        let mut world = world();
+        world
+            .resource::<AppTypeRegistry>()
+            .write()
+            .register::<ChildOf>();
+        let sandbox = Sandbox::new(&mut world, ModSchedules::empty());
+        let sandbox = world.spawn(sandbox).id();
+        let inside = world.spawn(ChildOf(sandbox)).id();
+        let outside = world.spawn_empty().id();
+
+        // The relationship is only checked once the commands are applied, after the system's run
+        let permissions = ComponentPermissions::default();
+        let command_errors = CommandErrors::default();
+        run_system_with_errors(&mut world, &[], &permissions, &command_errors, |host| {
+            let entity_commands = host.table().push(WasmEntityCommands(inside)).unwrap();
+            let child_of = outside.to_bits().to_string().into_bytes();
+            let bundle = vec![(ChildOf::type_path().to_string(), child_of)];
+            insert(host, &entity_commands, bundle).unwrap();
+            assert!(host.take_errors(Resource::new_own(0)).unwrap().is_empty());
+        });
+        assert_eq!(world.get::<ChildOf>(inside), Some(&ChildOf(sandbox)));
+
+        let errors =
+            run_system_with_errors(&mut world, &[], &permissions, &command_errors, |host| {
+                host.take_errors(Resource::new_own(0)).unwrap()
+            });
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].command, "insert");
+        assert_eq!(errors[0].type_path.as_deref(), Some(ChildOf::type_path()));
+    }
+}
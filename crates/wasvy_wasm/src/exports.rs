@@ -0,0 +1,102 @@
+use std::collections::{HashMap, HashSet};
+
+use anyhow::{Result, anyhow, bail};
+use bevy_ecs::prelude::*;
+use wasmtime::component::{InstancePre, Val};
+use wasvy_runtime::{
+    access::ModAccess, limits::ModMemoryLimits, mods::ModInfo, storage::ModStorage,
+};
+
+use crate::{
+    engine::Engine,
+    host::WasmHost,
+    runner::{Config, Runner},
+    wasm_asset::call,
+};
+
+/// The functions that mods expose to other mods, see `app.export-function`.
+///
+/// Functions are looked up by the name of the mod that exported them. If several mods share a
+/// name, the last one to be set up wins.
+#[derive(Resource, Default)]
+pub(crate) struct ModExports(HashMap<(String, String), ModExport>);
+
+/// The exports a mod registered as callbacks, see `app.add-callback`
+#[derive(Component, Default)]
+pub(crate) struct ModCallbackNames(pub(crate) HashSet<String>);
+
+/// A function exported by a mod
+pub(crate) struct ModExport {
+    pub(crate) mod_id: Entity,
+    pub(crate) mod_info: Option<ModInfo>,
+    pub(crate) storage: ModStorage,
+    pub(crate) memory_limits: Option<ModMemoryLimits>,
+    pub(crate) accesses: Vec<ModAccess>,
+    pub(crate) engine: Engine,
+    pub(crate) instance_pre: InstancePre<WasmHost>,
+}
+
+impl ModExports {
+    pub(crate) fn insert(&mut self, mod_name: &str, function: &str, export: ModExport) {
+        self.0
+            .insert((mod_name.to_string(), function.to_string()), export);
+    }
+
+    /// Forgets all the functions exported by a mod
+    pub(crate) fn remove_mod(&mut self, mod_id: Entity) {
+        self.0.retain(|_, export| export.mod_id != mod_id);
+    }
+
+    /// Calls a function exported by a mod on behalf of a system running in `access`.
+    ///
+    /// The function runs in a fresh store, without access to the world, so it can only compute a
+    /// result from its params. Mods may only call mods that are enabled in the same access, so
+    /// that sandboxed mods can't reach out of their sandbox.
+    pub(crate) fn call(
+        &self,
+        access: ModAccess,
+        mod_name: &str,
+        function: &str,
+        params: Vec<u8>,
+    ) -> Result<Vec<u8>> {
+        let export = self
+            .0
+            .get(&(mod_name.to_string(), function.to_string()))
+            .ok_or_else(|| {
+                anyhow!("Mod \"{mod_name}\" does not export a function \"{function}\"")
+            })?;
+        if !export.accesses.contains(&access) {
+            bail!(
+                "Mod \"{mod_name}\" is not enabled in {access:?}, so its functions can't be called"
+            );
+        }
+
+        let mut runner = Runner::new(&export.engine, mod_name, export.memory_limits);
+        runner.set_mod_info(export.mod_info.clone());
+        runner.set_storage(export.storage.clone());
+
+        let params = [Val::List(params.into_iter().map(Val::U8).collect())];
+        let mut results = [Val::Bool(false)];
+        call(
+            &mut runner,
+            &export.instance_pre,
+            Config::Call,
+            function,
+            &params,
+            &mut results,
+        )?;
+
+        let [Val::List(result)] = results else {
+            bail!("Function \"{function}\" of mod \"{mod_name}\" must return a list<u8>");
+        };
+        result
+            .into_iter()
+            .map(|val| match val {
+                Val::U8(byte) => Ok(byte),
+                _ => Err(anyhow!(
+                    "Function \"{function}\" of mod \"{mod_name}\" must return a list<u8>"
+                )),
+            })
+            .collect()
+    }
+}
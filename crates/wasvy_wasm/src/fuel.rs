@@ -0,0 +1,72 @@
+use std::sync::{
+    Arc,
+    atomic::{AtomicU64, Ordering},
+};
+
+use bevy_ecs::prelude::*;
+
+use crate::engine::Engine;
+
+/// Sent when a mod used up its fuel for this frame, see
+/// [`WasmBackendPlugin::fuel_per_frame`](crate::WasmBackendPlugin::fuel_per_frame).
+///
+/// The mod's remaining systems are skipped until its fuel is refilled next frame.
+#[derive(Message, Debug, Clone, PartialEq, Eq)]
+pub struct ModFuelExhausted {
+    /// The mod's entity
+    pub mod_id: Entity,
+
+    /// The name of the mod
+    pub mod_name: String,
+
+    /// The system that was running when the mod ran out of fuel
+    pub system_name: String,
+}
+
+/// The fuel a mod has left this frame, shared by all of its systems
+#[derive(Component, Clone, Default)]
+pub(crate) struct ModFuel(Arc<AtomicU64>);
+
+impl ModFuel {
+    /// Returns the fuel of a mod, inserting a full tank if it has none yet
+    pub(crate) fn of(mod_id: Entity, world: &mut World, budget: u64) -> Self {
+        let mut entity = world.entity_mut(mod_id);
+        if let Some(fuel) = entity.get::<ModFuel>() {
+            return fuel.clone();
+        }
+
+        let fuel = ModFuel(Arc::new(AtomicU64::new(budget)));
+        entity.insert(fuel.clone());
+        fuel
+    }
+
+    pub(crate) fn remaining(&self) -> u64 {
+        self.0.load(Ordering::Relaxed)
+    }
+
+    /// Uses up fuel, returning whether any is left
+    pub(crate) fn consume(&self, amount: u64) -> bool {
+        let previous = self
+            .0
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |fuel| {
+                Some(fuel.saturating_sub(amount))
+            })
+            .expect("the update never fails");
+        previous > amount
+    }
+
+    fn refill(&self, budget: u64) {
+        self.0.store(budget, Ordering::Relaxed);
+    }
+}
+
+/// Refills the fuel of every mod at the start of each frame
+pub(crate) fn refill_fuel(engine: Res<Engine>, fuel: Query<&ModFuel>) {
+    let Some(budget) = engine.fuel() else {
+        return;
+    };
+
+    for fuel in fuel.iter() {
+        fuel.refill(budget);
+    }
+}
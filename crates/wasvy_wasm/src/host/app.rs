@@ -1,7 +1,13 @@
+use bevy_ecs::reflect::AppTypeRegistry;
 use wasmtime::component::Resource;
+use wasvy_runtime::{
+    limits::ModLimits,
+    schema::{ComponentSchema, WasmComponentSchemas},
+    serialize::ModCodec,
+};
 
 use crate::{
-    bindings::wasvy::ecs::app::{HostApp, Schedule},
+    bindings::wasvy::ecs::app::{Codec, HostApp, Schedule},
     host::{WasmHost, WasmSystem},
     runner::State,
 };
@@ -19,17 +25,107 @@ impl HostApp for WasmHost {
             return Ok(());
         }
 
-        let State::Setup { add_systems, .. } = self.access() else {
+        let State::Setup {
+            add_systems, world, ..
+        } = self.access()
+        else {
             return Err(wasmtime::Error::msg(
                 "App can only be modified in a setup function",
             ));
         };
 
+        let limits = world
+            .get_resource::<ModLimits>()
+            .copied()
+            .unwrap_or_default();
+        limits
+            .check_systems(add_systems.len() + systems.len())
+            .map_err(wasmtime::Error::new)?;
+
         add_systems.push(schedule, systems);
 
         Ok(())
     }
 
+    fn use_codec(&mut self, _: Resource<WasmApp>, codec: Codec) -> Result<(), wasmtime::Error> {
+        let State::Setup { add_systems, .. } = self.access() else {
+            return Err(wasmtime::Error::msg(
+                "App can only be modified in a setup function",
+            ));
+        };
+
+        let codec = match codec {
+            Codec::Json => ModCodec::Json,
+            Codec::Postcard => ModCodec::Postcard,
+        };
+
+        // Fail early, rather than when the systems are added
+        codec.codec().map_err(wasmtime::Error::msg)?;
+        add_systems.set_codec(codec);
+
+        Ok(())
+    }
+
+    fn export_function(
+        &mut self,
+        _: Resource<WasmApp>,
+        name: String,
+    ) -> Result<(), wasmtime::Error> {
+        let State::Setup { add_systems, .. } = self.access() else {
+            return Err(wasmtime::Error::msg(
+                "App can only be modified in a setup function",
+            ));
+        };
+
+        add_systems.export_function(name);
+
+        Ok(())
+    }
+
+    fn add_callback(&mut self, _: Resource<WasmApp>, name: String) -> Result<(), wasmtime::Error> {
+        let State::Setup { add_systems, .. } = self.access() else {
+            return Err(wasmtime::Error::msg(
+                "App can only be modified in a setup function",
+            ));
+        };
+
+        add_systems.add_callback(name);
+
+        Ok(())
+    }
+
+    fn register_component_schema(
+        &mut self,
+        _: Resource<WasmApp>,
+        component: String,
+        schema: String,
+    ) -> Result<(), wasmtime::Error> {
+        let State::Setup { world, .. } = self.access() else {
+            return Err(wasmtime::Error::msg(
+                "App can only be modified in a setup function",
+            ));
+        };
+
+        let is_host_type = world
+            .resource::<AppTypeRegistry>()
+            .read()
+            .get_with_type_path(&component)
+            .is_some();
+        if is_host_type {
+            return Err(wasmtime::Error::msg(format!(
+                "{component} is a host component, so it already has a schema"
+            )));
+        }
+
+        let schema = ComponentSchema::from_json(&schema)
+            .map_err(|err| wasmtime::Error::msg(format!("{component}: {err:#}")))?;
+        world
+            .get_resource_or_init::<WasmComponentSchemas>()
+            .register(component, schema);
+
+        Ok(())
+    }
+
     // Note: this is never guaranteed to be called by the wasi binary
     fn drop(&mut self, app: Resource<WasmApp>) -> Result<(), wasmtime::Error> {
         let _ = self.table().delete(app)?;
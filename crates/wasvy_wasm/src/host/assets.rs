@@ -0,0 +1,49 @@
+use wasmtime::component::Resource;
+use wasvy_runtime::asset_root::check_asset_path;
+
+use crate::{
+    bindings::wasvy::ecs::app::{HostAssetHandle, HostAssets},
+    host::WasmHost,
+};
+
+/// Host-side type for the WIT `assets` resource, which only has static functions
+pub struct WasmAssets;
+
+/// Host-side handle for a WIT `asset-handle` resource, see `assets.load`
+pub struct WasmAssetHandle {
+    path: String,
+}
+
+impl WasmAssetHandle {
+    pub(crate) fn path(&self) -> &str {
+        &self.path
+    }
+}
+
+impl HostAssets for WasmHost {
+    fn load(&mut self, path: String) -> Result<Resource<WasmAssetHandle>, wasmtime::Error> {
+        check_asset_path(&path).map_err(wasmtime::Error::msg)?;
+
+        Ok(self.table().push(WasmAssetHandle { path })?)
+    }
+
+    // Note: this is never guaranteed to be called by the wasi binary
+    fn drop(&mut self, assets: Resource<WasmAssets>) -> Result<(), wasmtime::Error> {
+        let _ = self.table().delete(assets)?;
+
+        Ok(())
+    }
+}
+
+impl HostAssetHandle for WasmHost {
+    fn path(&mut self, handle: Resource<WasmAssetHandle>) -> Result<String, wasmtime::Error> {
+        Ok(self.table().get(&handle)?.path.clone())
+    }
+
+    // Note: this is never guaranteed to be called by the wasi binary
+    fn drop(&mut self, handle: Resource<WasmAssetHandle>) -> Result<(), wasmtime::Error> {
+        let _ = self.table().delete(handle)?;
+
+        Ok(())
+    }
+}
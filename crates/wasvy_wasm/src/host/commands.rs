@@ -1,19 +1,95 @@
+use std::{
+    collections::VecDeque,
+    sync::{Arc, Mutex},
+};
+
+use bevy_ecs::{entity::Entity, name::Name};
+use bevy_log::prelude::*;
+use bevy_reflect::TypePath;
 use wasmtime::component::Resource;
+use wasvy_runtime::{
+    component::{insert_resource, remove_resource},
+    messages::send_message,
+    permissions::ComponentAccess,
+};
 
 use crate::{
-    bindings::wasvy::ecs::app::{Bundle, HostCommands},
-    entity::{insert, map_entity, spawn_empty},
+    bindings::wasvy::ecs::app::{Bundle, CommandError, HostCommands, SerializedComponent},
+    entity::{check_access, check_entity, entity_resource, insert, spawn_empty},
     host::{WasmEntity, WasmEntityCommands, WasmHost},
+    record::{Boundary, encode_bundle, encode_entities},
+    runner::State,
+    scene::spawn_scene,
 };
 
 pub struct WasmCommands;
 
+/// How many failed commands a system keeps until it takes them, see `commands.take-errors`
+const MAX_COMMAND_ERRORS: usize = 64;
+
+/// The commands of a system that failed since the mod last took them, see `commands.take-errors`.
+///
+/// Each system keeps its own, and lends it to the host it runs in for the length of its run (see
+/// [Runner::set_command_errors](crate::runner::Runner::set_command_errors)), so systems never take
+/// the errors of another. Clones share the same errors, so commands failing once they are applied
+/// can still report them.
+#[derive(Clone, Default)]
+pub(crate) struct CommandErrors(Arc<Mutex<VecDeque<CommandError>>>);
+
+impl CommandErrors {
+    /// Reports a command that failed, dropping the oldest error once [MAX_COMMAND_ERRORS] are kept
+    pub(crate) fn push(
+        &self,
+        command: &str,
+        type_path: Option<&str>,
+        entity: Option<Entity>,
+        message: String,
+    ) {
+        let mut errors = self.0.lock().unwrap_or_else(|err| err.into_inner());
+        if errors.len() == MAX_COMMAND_ERRORS {
+            errors.pop_front();
+        }
+        errors.push_back(CommandError {
+            command: command.to_string(),
+            type_path: type_path.map(str::to_string),
+            entity_bits: entity.map(Entity::to_bits),
+            message,
+        });
+    }
+
+    /// Takes the errors reported so far, oldest first
+    pub(crate) fn take(&self) -> Vec<CommandError> {
+        let mut errors = self.0.lock().unwrap_or_else(|err| err.into_inner());
+        errors.drain(..).collect()
+    }
+}
+
+impl WasmHost {
+    /// Reports a command that failed, so the mod can take it with `commands.take-errors`
+    pub(crate) fn command_failed(
+        &mut self,
+        command: &str,
+        type_path: Option<&str>,
+        entity: Option<Entity>,
+        message: String,
+    ) {
+        warn!("Mod \"{}\" failed to {command}: {message}", self.mod_name());
+        self.command_errors()
+            .push(command, type_path, entity, message);
+    }
+}
+
 impl HostCommands for WasmHost {
     fn spawn_empty(
         &mut self,
         _: Resource<WasmCommands>,
     ) -> Result<Resource<WasmEntityCommands>, wasmtime::Error> {
-        spawn_empty(self)
+        let mut spawned = None;
+        self.tape("commands.spawn-empty", Boundary::Write, Vec::new, |host| {
+            spawned = Some(spawn_empty(host)?);
+            Ok(())
+        })?;
+        Ok(spawned.expect("write calls always run"))
     }
 
     fn spawn(
@@ -21,9 +97,22 @@ impl HostCommands for WasmHost {
         _: Resource<WasmCommands>,
         bundle: Bundle,
     ) -> Result<Resource<WasmEntityCommands>, wasmtime::Error> {
-        let entity_commands = spawn_empty(self)?;
-        insert(self, &entity_commands, bundle)?;
-        Ok(entity_commands)
+        let params = self.encode_params(|| {
+            encode_bundle(bundle.iter().map(|(ty, value)| (ty.as_str(), &value[..])))
+        });
+        let mut spawned = None;
+        self.tape(
+            "commands.spawn",
+            Boundary::Write,
+            || params,
+            |host| {
+                let entity_commands = spawn_empty(host)?;
+                insert(host, &entity_commands, bundle)?;
+                spawned = Some(entity_commands);
+                Ok(())
+            },
+        )?;
+        Ok(spawned.expect("write calls always run"))
     }
 
     fn entity(
@@ -31,7 +120,198 @@ impl HostCommands for WasmHost {
         _: Resource<WasmCommands>,
         entity: Resource<WasmEntity>,
     ) -> Result<Resource<WasmEntityCommands>, wasmtime::Error> {
-        map_entity(self, entity)
+        let entity: Entity = self.table().get(&entity)?.into();
+        let params = || encode_entities([entity]);
+        self.tape("commands.entity", Boundary::Read, params, |host| {
+            let State::RunSystem {
+                commands,
+                hierarchy,
+                access,
+                ..
+            } = host.access()
+            else {
+                return Err(wasmtime::Error::msg(
+                    "Commands resource is only accessible when running systems",
+                ));
+            };
+
+            check_entity(commands, entity)?;
+            check_access(hierarchy, access, entity)?;
+            Ok(())
+        })?;
+
+        entity_resource(entity, self.table())
+    }
+
+    fn find_by_name(
+        &mut self,
+        _: Resource<WasmCommands>,
+        name: String,
+    ) -> Result<Option<Resource<WasmEntity>>, wasmtime::Error> {
+        let entity = self.tape(
+            "commands.find-by-name",
+            Boundary::Read,
+            || name.clone().into_bytes(),
+            |host| {
+                let State::RunSystem {
+                    queries,
+                    query_resolver,
+                    permissions,
+                    ..
+                } = host.access()
+                else {
+                    return Err(wasmtime::Error::msg(
+                        "Commands resource is only accessible when running systems",
+                    ));
+                };
+
+                permissions
+                    .check(ComponentAccess::Query, Name::type_path())
+                    .map_err(wasmtime::Error::msg)?;
+                Ok(query_resolver.find_by_name(&name, queries))
+            },
+        )?;
+
+        entity
+            .map(|entity| entity_resource(entity, self.table()))
+            .transpose()
+    }
+
+    fn send_event(
+        &mut self,
+        _: Resource<WasmCommands>,
+        event: String,
+        value: SerializedComponent,
+    ) -> Result<(), wasmtime::Error> {
+        let params = || encode_bundle([(event.as_str(), &value[..])]);
+        self.tape("commands.send-event", Boundary::Write, params, |host| {
+            let State::RunSystem {
+                commands,
+                type_registry,
+                codec,
+                ..
+            } = host.access()
+            else {
+                return Err(wasmtime::Error::msg("Commands can only be used in systems"));
+            };
+
+            send_message(commands, type_registry, codec, &event, &value)
+                .map_err(wasmtime::Error::msg)
+        })
+    }
+
+    fn insert_resource(
+        &mut self,
+        _: Resource<WasmCommands>,
+        resource_type: String,
+        value: SerializedComponent,
+    ) -> Result<(), wasmtime::Error> {
+        let params = self.encode_params(|| encode_bundle([(resource_type.as_str(), &value[..])]));
+        self.tape(
+            "commands.insert-resource",
+            Boundary::Write,
+            || params,
+            |host| {
+                let State::RunSystem {
+                    commands,
+                    type_registry,
+                    codec,
+                    permissions,
+                    ..
+                } = host.access()
+                else {
+                    return Err(wasmtime::Error::msg("Commands can only be used in systems"));
+                };
+
+                let result = match permissions.check(ComponentAccess::Insert, &resource_type) {
+                    Ok(()) => insert_resource(
+                        commands,
+                        type_registry,
+                        codec,
+                        permissions,
+                        resource_type.clone(),
+                        value,
+                    ),
+                    Err(denied) => Err(denied.into()),
+                };
+                if let Err(err) = result {
+                    host.command_failed(
+                        "insert-resource",
+                        Some(&resource_type),
+                        None,
+                        err.to_string(),
+                    );
+                }
+                Ok(())
+            },
+        )
+    }
+
+    fn remove_resource(
+        &mut self,
+        _: Resource<WasmCommands>,
+        resource_type: String,
+    ) -> Result<(), wasmtime::Error> {
+        let params = || encode_bundle([(resource_type.as_str(), &[][..])]);
+        self.tape(
+            "commands.remove-resource",
+            Boundary::Write,
+            params,
+            |host| {
+                let State::RunSystem {
+                    commands,
+                    type_registry,
+                    permissions,
+                    ..
+                } = host.access()
+                else {
+                    return Err(wasmtime::Error::msg("Commands can only be used in systems"));
+                };
+
+                let result = match permissions.check(ComponentAccess::Insert, &resource_type) {
+                    Ok(()) => {
+                        remove_resource(commands, type_registry, permissions, resource_type.clone())
+                    }
+                    Err(denied) => Err(denied.into()),
+                };
+                if let Err(err) = result {
+                    host.command_failed(
+                        "remove-resource",
+                        Some(&resource_type),
+                        None,
+                        err.to_string(),
+                    );
+                }
+                Ok(())
+            },
+        )
+    }
+
+    fn spawn_scene(
+        &mut self,
+        _: Resource<WasmCommands>,
+        path: String,
+    ) -> Result<Resource<WasmEntityCommands>, wasmtime::Error> {
+        let params = self.encode_params(|| path.clone().into_bytes());
+        let mut spawned = None;
+        self.tape(
+            "commands.spawn-scene",
+            Boundary::Write,
+            || params,
+            |host| {
+                spawned = Some(spawn_scene(host, path)?);
+                Ok(())
+            },
+        )?;
+        Ok(spawned.expect("write calls always run"))
+    }
+
+    // Note: this doesn't go through the tape, since replaying the failed commands reports them again
+    fn take_errors(
+        &mut self,
+        _: Resource<WasmCommands>,
+    ) -> Result<Vec<CommandError>, wasmtime::Error> {
+        Ok(self.command_errors().take())
     }
 
     // Note: this is never guaranteed to be called by the wasi binary
@@ -41,3 +321,99 @@ impl HostCommands for WasmHost {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use bevy_ecs::{
+        component::Component,
+        hierarchy::ChildOf,
+        reflect::{AppTypeRegistry, ReflectComponent},
+        world::World,
+    };
+    use bevy_reflect::Reflect;
+    use wasvy_runtime::{
+        permissions::ComponentPermissions, sandbox::Sandbox, schedule::ModSchedules,
+    };
+
+    use super::*;
+    use crate::{
+        bindings::wasvy::ecs::app::HostEntityCommands,
+        host::WasmEntity,
+        test_utils::{run_system, run_system_with_errors, world},
+    };
+
+    #[derive(Component, Reflect)]
+    #[reflect(Component)]
+    struct Health(u32);
+
+    #[test]
+    fn keeps_the_last_command_errors() {
+        let mut host = WasmHost::new("my-mod", false);
+        for i in 0..MAX_COMMAND_ERRORS + 2 {
+            let entity = Entity::from_raw_u32(i as u32 + 1);
+            host.command_failed(
+                "insert",
+                Some("game::Health"),
+                entity,
+                "Not a component".into(),
+            );
+        }
+
+        let errors = host.take_errors(Resource::new_own(0)).unwrap();
+        assert_eq!(errors.len(), MAX_COMMAND_ERRORS);
+        assert_eq!(
+            errors[0].entity_bits,
+            Entity::from_raw_u32(3).map(Entity::to_bits)
+        );
+        assert!(host.take_errors(Resource::new_own(0)).unwrap().is_empty());
+    }
+
+    #[test]
+    fn systems_only_take_their_own_errors() {
+        let mut world = world();
+        world
+            .resource::<AppTypeRegistry>()
+            .write()
+            .register::<Health>();
+        let permissions =
+            ComponentPermissions::default().deny(ComponentAccess::Insert, [Health::type_path()]);
+
+        // Two systems of the same mod, the first of which fails to insert a component
+        let first = CommandErrors::default();
+        let second = CommandErrors::default();
+        run_system_with_errors(&mut world, &[], &permissions, &first, |host| {
+            let entity_commands = host.spawn_empty(Resource::new_own(0)).unwrap();
+            let bundle = vec![(Health::type_path().to_string(), b"[1]".to_vec())];
+            host.insert(entity_commands, bundle).unwrap();
+        });
+
+        let take_errors = |world: &mut World, errors: &CommandErrors| {
+            run_system_with_errors(world, &[], &permissions, errors, |host| {
+                host.take_errors(Resource::new_own(0)).unwrap()
+            })
+        };
+        assert!(take_errors(&mut world, &second).is_empty());
+        let errors = take_errors(&mut world, &first);
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].command, "insert");
+        assert_eq!(errors[0].type_path.as_deref(), Some(Health::type_path()));
+    }
+
+    #[test]
+    fn only_gets_entity_commands_within_the_access() {
+        let mut world = world();
+        let sandbox = Sandbox::new(&mut world, ModSchedules::empty());
+        let sandbox = world.spawn(sandbox).id();
+        let inside = world.spawn_empty().id();
+        let sandboxed = world.spawn(ChildOf(sandbox)).id();
+
+        run_system(&mut world, &[], &Default::default(), |host| {
+            let mut entity = |entity| {
+                let entity = host.table().push(WasmEntity(entity)).unwrap();
+                host.entity(Resource::new_own(0), entity)
+            };
+            assert!(entity(inside).is_ok());
+            assert!(entity(sandboxed).is_err());
+        });
+    }
+}
@@ -7,9 +7,10 @@ use wasvy_runtime::{
 };
 
 use crate::{
-    bindings::wasvy::ecs::app::{ComponentIndex, HostComponent, SerializedComponent},
+    bindings::wasvy::ecs::app::{ComponentIndex, HostComponent, SerializedComponent, TypePath},
     host::WasmHost,
     query::QueryId,
+    record::{Boundary, encode_entities, encode_fields},
     runner::State,
 };
 
@@ -27,6 +28,12 @@ impl WasmComponent {
     pub(crate) fn new(index: ComponentIndex, id: QueryId, entity: Entity) -> Self {
         Self { index, id, entity }
     }
+
+    /// Identifies the component in the params of a recorded host call
+    fn params(&self) -> impl Fn() -> Vec<u8> + use<> {
+        let Self { index, id, entity } = *self;
+        move || encode_fields([&id.to_le_bytes()[..], &encode_entities([entity]), &[index]])
+    }
 }
 
 impl HostComponent for WasmHost {
@@ -34,31 +41,34 @@ impl HostComponent for WasmHost {
         &mut self,
         component: Resource<WasmComponent>,
     ) -> Result<SerializedComponent, wasmtime::Error> {
-        let State::RunSystem {
-            table,
-            queries,
-            query_resolver,
-            type_registry,
-            codec,
-            ..
-        } = self.access()
-        else {
-            return Err(wasmtime::Error::msg(
-                "Component can only be accessed in systems",
-            ));
-        };
-
-        let component = table.get(&component)?;
-        query_resolver
-            .get(
-                component.id,
-                component.entity,
-                component.index,
+        let params = self.table().get(&component)?.params();
+        self.tape("component.get", Boundary::Read, params, |host| {
+            let State::RunSystem {
+                table,
                 queries,
+                query_resolver,
                 type_registry,
                 codec,
-            )
-            .map_err(wasmtime::Error::msg)
+                ..
+            } = host.access()
+            else {
+                return Err(wasmtime::Error::msg(
+                    "Component can only be accessed in systems",
+                ));
+            };
+
+            let component = table.get(&component)?;
+            query_resolver
+                .get(
+                    component.id,
+                    component.entity,
+                    component.index,
+                    queries,
+                    type_registry,
+                    codec,
+                )
+                .map_err(wasmtime::Error::msg)
+        })
     }
 
     fn set(
@@ -66,32 +76,41 @@ impl HostComponent for WasmHost {
         component: Resource<WasmComponent>,
         value: SerializedComponent,
     ) -> Result<(), wasmtime::Error> {
-        let State::RunSystem {
-            table,
-            queries,
-            query_resolver,
-            type_registry,
-            codec,
-            ..
-        } = self.access()
-        else {
-            return Err(wasmtime::Error::msg(
-                "Component can only be accessed in systems",
-            ));
-        };
-
-        let component = table.get(&component)?;
-        query_resolver
-            .set(
-                component.id,
-                component.entity,
-                component.index,
-                value,
-                queries,
-                type_registry,
-                codec,
-            )
-            .map_err(wasmtime::Error::msg)
+        let target = self.table().get(&component)?.params();
+        let params = self.encode_params(|| encode_fields([&target()[..], &value[..]]));
+        self.tape(
+            "component.set",
+            Boundary::Write,
+            || params,
+            |host| {
+                let State::RunSystem {
+                    table,
+                    queries,
+                    query_resolver,
+                    type_registry,
+                    codec,
+                    ..
+                } = host.access()
+                else {
+                    return Err(wasmtime::Error::msg(
+                        "Component can only be accessed in systems",
+                    ));
+                };
+
+                let component = table.get(&component)?;
+                query_resolver
+                    .set(
+                        component.id,
+                        component.entity,
+                        component.index,
+                        value,
+                        queries,
+                        type_registry,
+                        codec,
+                    )
+                    .map_err(wasmtime::Error::msg)
+            },
+        )
     }
 
     // Note: this is never guaranteed to be called by the wasi binary
@@ -107,9 +126,47 @@ impl HostComponent for WasmHost {
         method: String,
         params: SerializedComponent,
     ) -> Result<SerializedComponent, wasmtime::Error> {
-        invoke_component_method(self, component, &method, &params)
-            // TODO: collect instead of emitting immediately
-            .map_err(wasmtime::Error::msg)
+        let target = self.table().get(&component)?.params();
+        let fields = || encode_fields([&target()[..], method.as_bytes(), &params[..]]);
+        self.tape("component.invoke", Boundary::Write, fields, |host| {
+            invoke_component_method(host, component, &method, &params)
+                // TODO: collect instead of emitting immediately
+                .map_err(wasmtime::Error::msg)
+        })
+    }
+
+    fn try_invoke(
+        &mut self,
+        component: Resource<WasmComponent>,
+        method: String,
+        params: String,
+    ) -> Result<Result<String, String>, wasmtime::Error> {
+        let target = self.table().get(&component)?.params();
+        let fields = || encode_fields([&target()[..], method.as_bytes(), params.as_bytes()]);
+        let output = self.tape("component.try-invoke", Boundary::Write, fields, |host| {
+            Ok(
+                invoke_component_method(host, component, &method, params.as_bytes())
+                    .map_err(|err| err.to_string()),
+            )
+        })?;
+
+        Ok(match output {
+            Ok(output) => Ok(String::from_utf8(output)?),
+            Err(err) => Err(err),
+        })
+    }
+
+    fn invoke_static(
+        &mut self,
+        type_path: TypePath,
+        method: String,
+        params: SerializedComponent,
+    ) -> Result<SerializedComponent, wasmtime::Error> {
+        let fields = || encode_fields([type_path.as_bytes(), method.as_bytes(), &params[..]]);
+        self.tape("component.invoke-static", Boundary::Write, fields, |host| {
+            invoke_component_static(host, &type_path, &method, &params)
+                .map_err(wasmtime::Error::msg)
+        })
     }
 }
 
@@ -167,3 +224,110 @@ pub fn invoke_component_method(
 
     Ok(output)
 }
+
+/// Invoke a reflected associated function of a component type using JSON-encoded arguments.
+///
+/// This is used by the auto-generated host bindings to implement static WIT functions.
+pub fn invoke_component_static(
+    host: &mut WasmHost,
+    type_path: &str,
+    method: &str,
+    params: &[u8],
+) -> Result<SerializedComponent> {
+    let State::RunSystem {
+        type_registry,
+        codec,
+        function_index,
+        ..
+    } = host.access()
+    else {
+        bail!("Components can only be constructed in systems")
+    };
+
+    function_index.invoke_static(type_path, method, params, type_registry, codec)
+}
+
+/// Invoke a free function exported with `#[wasvy::host_fn]` using JSON-encoded arguments.
+///
+/// This is used by the auto-generated host bindings to implement the WIT functions of an
+/// interface.
+pub fn invoke_host_fn(
+    host: &mut WasmHost,
+    name: &str,
+    params: &[u8],
+) -> Result<SerializedComponent> {
+    let State::RunSystem {
+        type_registry,
+        codec,
+        function_index,
+        ..
+    } = host.access()
+    else {
+        bail!("Host functions can only be called in systems")
+    };
+
+    function_index.invoke_host_fn(name, params, type_registry, codec)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::any::TypeId;
+
+    use bevy_ecs::reflect::{AppFunctionRegistry, AppTypeRegistry, ReflectComponent};
+    use bevy_reflect::{Reflect, TypePath};
+    use wasvy_runtime::{authoring::WasvyExport, methods::FunctionIndex};
+
+    use super::*;
+    use crate::{
+        bindings::wasvy::ecs::app::{HostQuery, HostQueryResult, QueryFor},
+        host::WasmQuery,
+        query::QueryIdGenerator,
+        system::Param,
+        test_utils::{run_system, world},
+    };
+
+    #[derive(Component, Reflect)]
+    #[reflect(Component)]
+    struct Health {
+        current: u32,
+    }
+
+    #[test]
+    fn try_invoke_returns_errors() {
+        let mut world = world();
+        world.register_component::<Health>();
+        let type_registry = world.resource::<AppTypeRegistry>().clone();
+        {
+            let mut registry = type_registry.write();
+            registry.register::<Health>();
+            registry
+                .get_mut(TypeId::of::<Health>())
+                .unwrap()
+                .insert(WasvyExport::default());
+        }
+        let function_index = FunctionIndex::build(&type_registry, &AppFunctionRegistry::default());
+        world.insert_resource(function_index);
+        world.spawn(Health { current: 3 });
+
+        let params = [Param::Query(vec![QueryFor::Ref(
+            Health::type_path().to_string(),
+        )])];
+        run_system(&mut world, &params, &Default::default(), |host| {
+            let id = QueryIdGenerator::default().generate();
+            let query = host.table().push(WasmQuery::new(id)).unwrap();
+            let result = host.iter(query).unwrap().unwrap();
+            let component = host.component(result, 0).unwrap();
+            let mut try_invoke = |method: &str, params: &str| {
+                let component = Resource::<WasmComponent>::new_borrow(component.rep());
+                host.try_invoke(component, method.to_string(), params.to_string())
+                    .unwrap()
+            };
+
+            assert_eq!(try_invoke("get-current", "[]"), Ok("3".to_string()));
+            let err = try_invoke("set-current", "[5]").unwrap_err();
+            assert!(err.contains("requires mutable access"), "{err}");
+            let err = try_invoke("heal", "[]").unwrap_err();
+            assert!(err.contains("Unknown method"), "{err}");
+        });
+    }
+}
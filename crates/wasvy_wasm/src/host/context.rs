@@ -0,0 +1,65 @@
+use bevy_ecs::prelude::*;
+use wasmtime::{AsContextMut, StoreContextMut, component::Val};
+
+use crate::{
+    host::WasmHost,
+    runner::{State, call_reentrant},
+};
+
+/// What a host function defined with `wasvy::host_interface!` may access of the mod calling it.
+///
+/// During setup the function has the whole [World], while from systems it may only queue
+/// [Commands], like the mod itself.
+///
+/// Functions added to the linker by hand, see `ModLoaderPlugin::add_functionality`, can create
+/// one from the store they are given.
+pub struct HostContext<'a> {
+    store: StoreContextMut<'a, WasmHost>,
+}
+
+impl<'a> HostContext<'a> {
+    pub fn new(store: StoreContextMut<'a, WasmHost>) -> Self {
+        Self { store }
+    }
+
+    /// The name of the calling mod
+    pub fn mod_name(&self) -> &str {
+        self.store.data().mod_name()
+    }
+
+    /// The entity of the calling mod, if known
+    pub fn mod_id(&self) -> Option<Entity> {
+        self.store.data().mod_id()
+    }
+
+    /// The world, when called during the setup of the mod
+    pub fn world(&mut self) -> Option<&mut World> {
+        match self.store.data_mut().access() {
+            State::Setup { world, .. } => Some(world),
+            _ => None,
+        }
+    }
+
+    /// The commands of the running system, when called from a system of the mod
+    pub fn commands(&mut self) -> Option<Commands<'_, '_>> {
+        match self.store.data_mut().access() {
+            State::RunSystem { commands, .. } => Some(commands.reborrow()),
+            _ => None,
+        }
+    }
+
+    /// Calls an export of the calling mod, such as a callback it passed the name of.
+    ///
+    /// The export runs in a new instance of the mod, with the same access to the app as the call
+    /// this is made from. Fails when too many calls into the mod are on the stack, see
+    /// [`WasmBackendPlugin::max_call_depth`](crate::plugin::WasmBackendPlugin::max_call_depth).
+    pub fn call(
+        &mut self,
+        export: &str,
+        params: &[Val],
+        results: &mut [Val],
+    ) -> Result<(), wasmtime::Error> {
+        call_reentrant(self.store.as_context_mut(), export, params, results)
+            .map_err(wasmtime::Error::from_anyhow)
+    }
+}
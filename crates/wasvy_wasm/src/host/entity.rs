@@ -1,7 +1,13 @@
 use bevy_ecs::prelude::*;
 use wasmtime::component::Resource;
 
-use crate::{bindings::wasvy::ecs::app::HostEntity, host::WasmHost};
+use crate::{
+    bindings::wasvy::ecs::app::HostEntity,
+    entity::{entity_from_bits, entity_resource},
+    host::WasmHost,
+    record::Boundary,
+    runner::State,
+};
 
 pub struct WasmEntity(pub(crate) Entity);
 
@@ -18,6 +24,28 @@ impl From<Entity> for WasmEntity {
 }
 
 impl HostEntity for WasmHost {
+    fn to_bits(&mut self, entity: Resource<WasmEntity>) -> Result<u64, wasmtime::Error> {
+        let State::RunSystem { table, .. } = self.access() else {
+            return Err(wasmtime::Error::msg(
+                "Entity resource is only accessible when running systems",
+            ));
+        };
+
+        Ok(table.get(&entity)?.0.to_bits())
+    }
+
+    fn from_bits(&mut self, bits: u64) -> Result<Resource<WasmEntity>, wasmtime::Error> {
+        let entity = self.tape(
+            "entity.from-bits",
+            Boundary::Read,
+            || bits.to_le_bytes().to_vec(),
+            |host| Ok(Some(entity_from_bits(host, bits)?)),
+        )?;
+
+        let entity = entity.ok_or_else(|| wasmtime::Error::msg("Invalid recorded entity"))?;
+        entity_resource(entity, self.table())
+    }
+
     // Note: this is never guaranteed to be called by the wasi binary
     fn drop(&mut self, commands: Resource<WasmEntity>) -> Result<(), wasmtime::Error> {
         let _ = self.table().delete(commands)?;
@@ -25,3 +53,26 @@ impl HostEntity for WasmHost {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{run_system, world};
+
+    #[test]
+    fn entity_bits_round_trip() {
+        let mut world = world();
+        let entity = world.spawn_empty().id();
+        let despawned = world.spawn_empty().id();
+        world.despawn(despawned);
+
+        run_system(&mut world, &[], &Default::default(), |host| {
+            let resource = host.from_bits(entity.to_bits()).unwrap();
+            assert_eq!(host.to_bits(resource).unwrap(), entity.to_bits());
+
+            assert!(host.from_bits(despawned.to_bits()).is_err());
+            // The index is stored inverted in the lower bits, so these can't be zero
+            assert!(host.from_bits(0).is_err());
+        });
+    }
+}
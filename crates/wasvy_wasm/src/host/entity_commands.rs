@@ -1,10 +1,12 @@
 use bevy_ecs::prelude::*;
 use wasmtime::component::Resource;
+use wasvy_runtime::{access::ModAccess, asset_root::insert_asset, mods::ModDespawnBehaviour};
 
 use crate::{
-    bindings::wasvy::ecs::app::{Bundle, BundleTypes, HostEntityCommands},
-    entity::{insert, map_entity, remove},
-    host::{WasmEntity, WasmHost},
+    bindings::wasvy::ecs::app::{Bundle, BundleTypes, DespawnBehaviour, HostEntityCommands},
+    entity::{check_access, check_entity, entity_resource, insert, map_entity, remove},
+    host::{WasmAssetHandle, WasmEntity, WasmHost},
+    record::{Boundary, encode_bundle, encode_entities, encode_fields},
     runner::State,
 };
 
@@ -27,7 +29,18 @@ impl HostEntityCommands for WasmHost {
         &mut self,
         entity_commands: Resource<WasmEntityCommands>,
     ) -> Result<Resource<WasmEntity>, wasmtime::Error> {
-        map_entity(self, entity_commands)
+        let entity = self.table().get(&entity_commands)?.0;
+        self.tape(
+            "entity-commands.id",
+            Boundary::Read,
+            || encode_entities([entity]),
+            |host| {
+                map_entity::<_, WasmEntity>(host, entity_commands)?;
+                Ok(())
+            },
+        )?;
+
+        entity_resource(entity, self.table())
     }
 
     fn insert(
@@ -35,7 +48,22 @@ impl HostEntityCommands for WasmHost {
         entity_commands: Resource<WasmEntityCommands>,
         bundle: Bundle,
     ) -> Result<(), wasmtime::Error> {
-        insert(self, &entity_commands, bundle)
+        let entity = self.table().get(&entity_commands)?.0;
+        let params = self.encode_params(|| {
+            encode_fields([
+                &encode_entities([entity])[..],
+                &encode_bundle(bundle.iter().map(|(ty, value)| (ty.as_str(), &value[..]))),
+            ])
+        });
+        self.tape(
+            "entity-commands.insert",
+            Boundary::Write,
+            || params,
+            |host| {
+                insert(host, &entity_commands, bundle)?;
+                Ok(())
+            },
+        )
     }
 
     fn remove(
@@ -43,27 +71,211 @@ impl HostEntityCommands for WasmHost {
         entity_commands: Resource<WasmEntityCommands>,
         bundle: BundleTypes,
     ) -> Result<(), wasmtime::Error> {
-        remove(self, entity_commands, bundle)
+        let entity = self.table().get(&entity_commands)?.0;
+        let params = self.encode_params(|| {
+            encode_fields([
+                &encode_entities([entity])[..],
+                &encode_bundle(bundle.iter().map(|ty| (ty.as_str(), &[][..]))),
+            ])
+        });
+        self.tape(
+            "entity-commands.remove",
+            Boundary::Write,
+            || params,
+            |host| {
+                remove(host, entity_commands, bundle)?;
+                Ok(())
+            },
+        )
     }
 
     fn despawn(
         &mut self,
         entity_commands: Resource<WasmEntityCommands>,
     ) -> Result<(), wasmtime::Error> {
-        let mut entity_commands = access(self, entity_commands)?;
-        entity_commands.despawn();
-
-        Ok(())
+        let entity = self.table().get(&entity_commands)?.0;
+        let params = || encode_entities([entity]);
+        self.tape("entity-commands.despawn", Boundary::Write, params, |host| {
+            access(host, entity_commands)?.despawn();
+            Ok(())
+        })
     }
 
     fn try_despawn(
         &mut self,
         entity_commands: Resource<WasmEntityCommands>,
     ) -> std::result::Result<(), wasmtime::Error> {
-        let mut entity_commands = access(self, entity_commands)?;
-        entity_commands.try_despawn();
+        let entity = self.table().get(&entity_commands)?.0;
+        let params = || encode_entities([entity]);
+        self.tape(
+            "entity-commands.try-despawn",
+            Boundary::Write,
+            params,
+            |host| {
+                access(host, entity_commands)?.try_despawn();
+                Ok(())
+            },
+        )
+    }
 
-        Ok(())
+    fn set_parent(
+        &mut self,
+        entity_commands: Resource<WasmEntityCommands>,
+        parent: Resource<WasmEntity>,
+    ) -> Result<(), wasmtime::Error> {
+        let entities = [
+            self.table().get(&entity_commands)?.0,
+            self.table().get(&parent)?.0,
+        ];
+        let params = || encode_entities(entities);
+        self.tape(
+            "entity-commands.set-parent",
+            Boundary::Write,
+            params,
+            |host| {
+                let (mut entity_commands, parent) = related(host, entity_commands, parent, true)?;
+                entity_commands.insert(ChildOf(parent));
+                Ok(())
+            },
+        )
+    }
+
+    fn remove_parent(
+        &mut self,
+        entity_commands: Resource<WasmEntityCommands>,
+    ) -> Result<(), wasmtime::Error> {
+        let entity = self.table().get(&entity_commands)?.0;
+        let params = || encode_entities([entity]);
+        self.tape(
+            "entity-commands.remove-parent",
+            Boundary::Write,
+            params,
+            |host| {
+                let State::RunSystem { access, .. } = host.access() else {
+                    return Err(wasmtime::Error::msg(
+                        "EntityCommands resource is only accessible when running systems",
+                    ));
+                };
+                let access = *access;
+
+                let mut entity_commands = self::access(host, entity_commands)?;
+                match access {
+                    // Keep the entity in the sandbox, see [spawn_empty](crate::entity::spawn_empty)
+                    ModAccess::Sandbox(sandbox) => entity_commands.insert(ChildOf(sandbox)),
+                    ModAccess::World => entity_commands.remove::<ChildOf>(),
+                };
+                Ok(())
+            },
+        )
+    }
+
+    fn add_child(
+        &mut self,
+        entity_commands: Resource<WasmEntityCommands>,
+        child: Resource<WasmEntity>,
+    ) -> Result<(), wasmtime::Error> {
+        let entities = [
+            self.table().get(&entity_commands)?.0,
+            self.table().get(&child)?.0,
+        ];
+        let params = || encode_entities(entities);
+        self.tape(
+            "entity-commands.add-child",
+            Boundary::Write,
+            params,
+            |host| {
+                let (mut entity_commands, child) = related(host, entity_commands, child, false)?;
+                entity_commands.add_child(child);
+                Ok(())
+            },
+        )
+    }
+
+    fn set_despawn_behaviour(
+        &mut self,
+        entity_commands: Resource<WasmEntityCommands>,
+        behaviour: DespawnBehaviour,
+    ) -> Result<(), wasmtime::Error> {
+        let behaviour = match behaviour {
+            DespawnBehaviour::DespawnWithMod => ModDespawnBehaviour::DespawnWithMod,
+            DespawnBehaviour::Persist => ModDespawnBehaviour::Persist,
+            DespawnBehaviour::TransferToHost => ModDespawnBehaviour::TransferToHost,
+        };
+
+        let entity = self.table().get(&entity_commands)?.0;
+        let params = || encode_fields([&encode_entities([entity])[..], &[behaviour as u8]]);
+        self.tape(
+            "entity-commands.set-despawn-behaviour",
+            Boundary::Write,
+            params,
+            |host| {
+                let State::RunSystem {
+                    insert_despawn_component,
+                    ..
+                } = host.access()
+                else {
+                    return Err(wasmtime::Error::msg(
+                        "EntityCommands resource is only accessible when running systems",
+                    ));
+                };
+                let insert_despawn_component = *insert_despawn_component;
+
+                let mut entity_commands = access(host, entity_commands)?;
+                insert_despawn_component.insert_with(&mut entity_commands, behaviour);
+                Ok(())
+            },
+        )
+    }
+
+    fn insert_asset(
+        &mut self,
+        entity_commands: Resource<WasmEntityCommands>,
+        component_type: String,
+        handle: Resource<WasmAssetHandle>,
+    ) -> Result<(), wasmtime::Error> {
+        let Some(mod_id) = self.mod_id() else {
+            return Err(wasmtime::Error::msg("Assets can only be loaded by mods"));
+        };
+        let path = self.table().get(&handle)?.path().to_string();
+
+        let entity = self.table().get(&entity_commands)?.0;
+        let params = self.encode_params(|| {
+            encode_fields([
+                &encode_entities([entity])[..],
+                &encode_bundle([(component_type.as_str(), path.as_bytes())]),
+            ])
+        });
+        self.tape(
+            "entity-commands.insert-asset",
+            Boundary::Write,
+            || params,
+            |host| {
+                let State::RunSystem {
+                    commands,
+                    type_registry,
+                    permissions,
+                    ..
+                } = host.access()
+                else {
+                    return Err(wasmtime::Error::msg(
+                        "EntityCommands resource is only accessible when running systems",
+                    ));
+                };
+
+                check_entity(commands, entity)?;
+                insert_asset(
+                    commands,
+                    type_registry,
+                    permissions,
+                    mod_id,
+                    entity,
+                    component_type,
+                    path,
+                )
+                .map_err(wasmtime::Error::msg)?;
+                Ok(())
+            },
+        )
     }
 
     // Note: this is never guaranteed to be called by the wasi binary
@@ -90,6 +302,77 @@ fn access(
         ));
     };
 
-    let entity_commands = table.get(&entity_commands)?;
-    Ok(commands.entity(entity_commands.0))
+    let entity = table.get(&entity_commands)?.0;
+    check_entity(commands, entity)?;
+    Ok(commands.entity(entity))
+}
+
+/// Like [access], but also returns another entity the mod wants to relate this one to.
+///
+/// Relations may not reach outside of the mod's access, except that a sandboxed entity may be
+/// parented to the sandbox itself when `allow_sandbox` is set.
+fn related(
+    host: &mut WasmHost,
+    entity_commands: Resource<WasmEntityCommands>,
+    other: Resource<WasmEntity>,
+    allow_sandbox: bool,
+) -> Result<(EntityCommands<'_>, Entity), wasmtime::Error> {
+    let State::RunSystem {
+        table,
+        commands,
+        hierarchy,
+        access,
+        ..
+    } = host.access()
+    else {
+        return Err(wasmtime::Error::msg(
+            "EntityCommands resource is only accessible when running systems",
+        ));
+    };
+
+    let entity = table.get(&entity_commands)?.0;
+    let other: Entity = table.get(&other)?.into();
+    check_entity(commands, entity)?;
+    check_entity(commands, other)?;
+    if !(allow_sandbox && *access == ModAccess::Sandbox(other)) {
+        check_access(hierarchy, access, other)?;
+    }
+
+    Ok((commands.entity(entity), other))
+}
+
+#[cfg(test)]
+mod tests {
+    use wasvy_runtime::{sandbox::Sandbox, schedule::ModSchedules};
+
+    use super::*;
+    use crate::test_utils::{run_system, world};
+
+    #[test]
+    fn relations_stay_within_the_access() {
+        let mut world = world();
+        let sandbox = Sandbox::new(&mut world, ModSchedules::empty());
+        let sandbox = world.spawn(sandbox).id();
+        let entity = world.spawn_empty().id();
+        let inside = world.spawn_empty().id();
+        let sandboxed = world.spawn(ChildOf(sandbox)).id();
+
+        run_system(&mut world, &[], &Default::default(), |host| {
+            let mut relate = |other, parent: bool| {
+                let entity_commands = host.table().push(WasmEntityCommands(entity)).unwrap();
+                let other = host.table().push(WasmEntity(other)).unwrap();
+                if parent {
+                    host.set_parent(entity_commands, other)
+                } else {
+                    host.add_child(entity_commands, other)
+                }
+            };
+            assert!(relate(sandboxed, true).is_err());
+            assert!(relate(sandboxed, false).is_err());
+            assert!(relate(inside, true).is_ok());
+        });
+
+        assert_eq!(world.get::<ChildOf>(entity), Some(&ChildOf(inside)));
+        assert_eq!(world.get::<ChildOf>(sandboxed), Some(&ChildOf(sandbox)));
+    }
 }
@@ -0,0 +1,40 @@
+use wasmtime::component::Resource;
+
+use crate::{
+    bindings::wasvy::ecs::app::{HostEventReader, SerializedComponent},
+    host::WasmHost,
+    record::Boundary,
+};
+
+/// Host-side handle for a WIT `event-reader` resource.
+///
+/// Events are read (and serialized) right before the system runs, so this only holds the
+/// events that the system hasn't seen yet.
+pub struct WasmEventReader {
+    events: Vec<SerializedComponent>,
+}
+
+impl WasmEventReader {
+    pub(crate) fn new(events: Vec<SerializedComponent>) -> Self {
+        Self { events }
+    }
+}
+
+impl HostEventReader for WasmHost {
+    fn read(
+        &mut self,
+        reader: Resource<WasmEventReader>,
+    ) -> Result<Vec<SerializedComponent>, wasmtime::Error> {
+        self.tape("event-reader.read", Boundary::Read, Vec::new, |host| {
+            let reader = host.table().get_mut(&reader)?;
+            Ok(std::mem::take(&mut reader.events))
+        })
+    }
+
+    // Note: this is never guaranteed to be called by the wasi binary
+    fn drop(&mut self, reader: Resource<WasmEventReader>) -> Result<(), wasmtime::Error> {
+        let _ = self.table().delete(reader)?;
+
+        Ok(())
+    }
+}
@@ -2,31 +2,51 @@
 //!
 //! These types back the `wasvy:ecs` resources exposed to guest mods.
 
+use std::{collections::HashMap, sync::Arc};
+
+use wasmtime::component::{Instance, InstancePre};
+
+use bevy_ecs::{entity::Entity, reflect::AppTypeRegistry};
 use wasmtime_wasi::{ResourceTable, WasiCtx, WasiCtxBuilder, WasiCtxView, WasiView};
+use wasvy_runtime::{
+    component::TypeAliasRegistry, methods::FunctionIndex, mods::ModInfo, registry,
+    storage::ModStorage,
+};
 
 use crate::{
     bindings::wasvy::ecs::app::*,
-    runner::{Data, State},
+    engine::DEFAULT_MAX_CALL_DEPTH,
+    log::{ModOutput, log},
+    record::{Boundary, CallResponse, Tape, encode_fields},
+    runner::{Config, Data, ModLimiter, State},
 };
 
 mod app;
+mod assets;
 mod commands;
 mod component;
+mod context;
 mod entity;
 mod entity_commands;
+mod event_reader;
 mod query;
 mod query_result;
+mod res;
 mod schedule;
 mod serialize;
 mod system;
 
 pub use app::*;
+pub use assets::*;
 pub use commands::*;
 pub use component::*;
+pub use context::*;
 pub use entity::*;
 pub use entity_commands::*;
+pub use event_reader::*;
 pub use query::*;
 pub use query_result::*;
+pub use res::*;
 pub use serialize::*;
 pub use system::*;
 
@@ -37,41 +57,355 @@ pub struct WasmHost {
     data: Data,
     table: ResourceTable,
     ctx: WasiCtx,
+    tape: Option<Arc<Tape>>,
+    mod_name: String,
+    mod_info: Option<ModInfo>,
+    storage: Option<ModStorage>,
+    time: Option<TimeInfo>,
+    limits: ModLimiter,
+
+    /// The mod being run, so host functions can call back into it, see [`call_reentrant`](crate::runner::call_reentrant)
+    instance_pre: Option<InstancePre<WasmHost>>,
+    max_call_depth: usize,
+
+    /// The instances kept by [`call_reentrant`](crate::runner::call_reentrant), one per call depth
+    reentrant_instances: Vec<Instance>,
+
+    /// The failed commands of the system being run, see [`CommandErrors`]
+    command_errors: CommandErrors,
+
+    /// The instances of the mod's plugs created in this store, see [`link_plugs`](crate::compose::link_plugs)
+    plug_instances: HashMap<usize, Instance>,
 }
 
 impl WasmHost {
-    pub(crate) fn new() -> Self {
-        let data = Data::uninitialized();
+    pub(crate) fn new(mod_name: &str, capture_output: bool) -> Self {
+        let data = Data::default();
         let table = ResourceTable::new();
-        let ctx = WasiCtxBuilder::new()
-            .inherit_stdio()
+        let mut ctx = WasiCtxBuilder::new();
+        ctx.inherit_stdio()
             .inherit_network()
-            .allow_ip_name_lookup(true)
-            .build();
+            .allow_ip_name_lookup(true);
+        if capture_output {
+            ctx.stdout(ModOutput::stdout(mod_name))
+                .stderr(ModOutput::stderr(mod_name));
+        }
+        let ctx = ctx.build();
 
-        Self { data, table, ctx }
+        Self {
+            data,
+            table,
+            ctx,
+            tape: None,
+            mod_name: mod_name.to_string(),
+            mod_info: None,
+            storage: None,
+            time: None,
+            limits: ModLimiter::default(),
+            instance_pre: None,
+            max_call_depth: DEFAULT_MAX_CALL_DEPTH,
+            reentrant_instances: Vec::new(),
+            command_errors: CommandErrors::default(),
+            plug_instances: HashMap::new(),
+        }
     }
 
-    pub(crate) fn set_data(&mut self, data: Data) {
-        self.data = data;
+    pub(crate) fn set_tape(&mut self, tape: Option<Arc<Tape>>) {
+        self.tape = tape;
     }
 
-    pub(crate) fn table(&mut self) -> &mut ResourceTable {
-        &mut self.table
+    pub(crate) fn set_mod_info(&mut self, mod_info: Option<ModInfo>) {
+        self.mod_info = mod_info;
     }
 
-    pub(crate) fn clear(&mut self) {
-        self.set_data(Data::uninitialized());
+    pub(crate) fn mod_name(&self) -> &str {
+        &self.mod_name
     }
 
-    /// Access to the data contained in the [`WasmHost`]
+    /// The entity of the mod this host runs, if known
+    pub(crate) fn mod_id(&self) -> Option<Entity> {
+        self.mod_info.as_ref().map(|info| info.entity)
+    }
+
+    pub(crate) fn set_command_errors(&mut self, command_errors: CommandErrors) {
+        self.command_errors = command_errors;
+    }
+
+    pub(crate) fn command_errors(&self) -> &CommandErrors {
+        &self.command_errors
+    }
+
+    pub(crate) fn set_storage(&mut self, storage: ModStorage) {
+        self.storage = Some(storage);
+    }
+
+    pub(crate) fn set_time(&mut self, time: Option<TimeInfo>) {
+        self.time = time;
+    }
+
+    /// The limits enforced by the store's [limiter](wasmtime::Store::limiter)
+    pub(crate) fn limits(&mut self) -> &mut ModLimiter {
+        &mut self.limits
+    }
+
+    pub(crate) fn set_limits(&mut self, limits: ModLimiter) {
+        self.limits = limits;
+    }
+
+    /// The size of the mod's linear memories, in bytes
+    pub(crate) fn memory_size(&self) -> usize {
+        self.limits.memory_size()
+    }
+
+    fn storage(&self) -> Result<&ModStorage, wasmtime::Error> {
+        self.storage
+            .as_ref()
+            .ok_or_else(|| wasmtime::Error::msg("Mod data is not available"))
+    }
+
+    pub(crate) fn set_instance_pre(&mut self, instance_pre: InstancePre<WasmHost>) {
+        self.instance_pre = Some(instance_pre);
+    }
+
+    pub(crate) fn instance_pre(&self) -> Option<&InstancePre<WasmHost>> {
+        self.instance_pre.as_ref()
+    }
+
+    /// The instance that reentrant calls made at `depth` reuse, if one was created
+    pub(crate) fn reentrant_instance(&self, depth: usize) -> Option<Instance> {
+        self.reentrant_instances.get(depth).copied()
+    }
+
+    /// Keeps `instance` for the reentrant calls made at `depth`
+    pub(crate) fn keep_reentrant_instance(&mut self, depth: usize, instance: Instance) {
+        self.reentrant_instances.truncate(depth);
+        if self.reentrant_instances.len() == depth {
+            self.reentrant_instances.push(instance);
+        }
+    }
+
+    /// Forgets the instances of reentrant calls made at `depth` or deeper, so the next ones
+    /// instantiate the mod again
+    pub(crate) fn forget_reentrant_instances(&mut self, depth: usize) {
+        self.reentrant_instances.truncate(depth);
+    }
+
+    /// The instance of the plug at `index`, if the mod called it in this store already
+    pub(crate) fn plug_instance(&self, index: usize) -> Option<Instance> {
+        self.plug_instances.get(&index).copied()
+    }
+
+    /// Keeps `instance` for the next calls into the plug at `index`
+    pub(crate) fn keep_plug_instance(&mut self, index: usize, instance: Instance) {
+        self.plug_instances.insert(index, instance);
+    }
+
+    pub(crate) fn set_max_call_depth(&mut self, max_call_depth: usize) {
+        self.max_call_depth = max_call_depth;
+    }
+
+    pub(crate) fn max_call_depth(&self) -> usize {
+        self.max_call_depth
+    }
+
+    /// The number of calls into the mod on the stack
+    pub(crate) fn call_depth(&self) -> usize {
+        self.data.depth()
+    }
+
+    /// Enters a copy of the innermost scope, for a call made while it's on the stack
+    pub(crate) fn reenter_scope(&mut self) {
+        self.data.reenter();
+    }
+
+    /// Enters a new scope, see [`scoped`](crate::runner::scoped)
+    pub(crate) fn push_scope(&mut self, config: Config) {
+        self.data.push(config);
+    }
+
+    /// Leaves the innermost scope, restoring the one before it
+    pub(crate) fn pop_scope(&mut self) {
+        self.data.pop();
+    }
+
+    pub(crate) fn table(&mut self) -> &mut ResourceTable {
+        &mut self.table
+    }
+
+    /// Access to the innermost scope of the [`WasmHost`].
+    ///
+    /// Outside of any scope the mod has no access to the app, just like when another mod calls it.
     pub(crate) fn access(&mut self) -> State<'_> {
         let table = &mut self.table;
-        self.data.access(table).expect("WasmHost to be initialized")
+        self.data.access(table).unwrap_or(State::Call)
+    }
+
+    /// Runs a host call, recording or replaying it when a [`ModRecorder`](crate::record::ModRecorder)
+    /// or [`ModReplay`](crate::record::ModReplay) is present.
+    ///
+    /// The params are only encoded, and the response only passed through its [`CallResponse`]
+    /// encoding, when there is a tape.
+    pub(crate) fn tape<T, P, F>(
+        &mut self,
+        call: &str,
+        boundary: Boundary,
+        params: P,
+        f: F,
+    ) -> Result<T, wasmtime::Error>
+    where
+        T: CallResponse,
+        P: FnOnce() -> Vec<u8>,
+        F: FnOnce(&mut Self) -> Result<T, wasmtime::Error>,
+    {
+        match self.tape.clone() {
+            Some(tape) => tape.call(call, boundary, &params(), || f(self)),
+            None => f(self),
+        }
+    }
+
+    /// Encodes the params of a host call only when there is a tape, for calls that move the
+    /// values they encode into the call, see [`tape`](Self::tape)
+    pub(crate) fn encode_params(&self, encode: impl FnOnce() -> Vec<u8>) -> Vec<u8> {
+        match self.tape {
+            Some(_) => encode(),
+            None => Vec::new(),
+        }
     }
 }
 
-impl Host for WasmHost {}
+impl Host for WasmHost {
+    fn mod_info(&mut self) -> Result<ModMetadata, wasmtime::Error> {
+        let Some(info) = &self.mod_info else {
+            return Err(wasmtime::Error::msg("Mod info is not available"));
+        };
+
+        Ok(ModMetadata {
+            name: info.name.clone(),
+            entity_bits: info.entity.to_bits(),
+            accesses: info.accesses.clone(),
+            capabilities_granted: info.capabilities.clone(),
+            version: info.version.clone(),
+        })
+    }
+
+    fn get_data(&mut self, key: String) -> Result<Option<Vec<u8>>, wasmtime::Error> {
+        self.tape(
+            "get-data",
+            Boundary::Read,
+            || key.clone().into_bytes(),
+            |host| Ok(host.storage()?.get(&key)),
+        )
+    }
+
+    fn set_data(&mut self, key: String, value: Vec<u8>) -> Result<(), wasmtime::Error> {
+        let params = self.encode_params(|| encode_fields([key.as_bytes(), &value[..]]));
+        self.tape(
+            "set-data",
+            Boundary::Write,
+            || params,
+            |host| {
+                host.storage()?.set(key, value);
+                Ok(())
+            },
+        )
+    }
+
+    fn remove_data(&mut self, key: String) -> Result<(), wasmtime::Error> {
+        self.tape(
+            "remove-data",
+            Boundary::Write,
+            || key.clone().into_bytes(),
+            |host| {
+                host.storage()?.remove(&key);
+                Ok(())
+            },
+        )
+    }
+
+    // Note: this doesn't go through the tape, since the called function can't access the world
+    fn log(&mut self, level: LogLevel, message: String) -> Result<(), wasmtime::Error> {
+        log(level.into(), &self.mod_name, &message);
+        Ok(())
+    }
+
+    fn get_time(&mut self) -> Result<TimeInfo, wasmtime::Error> {
+        self.tape("get-time", Boundary::Read, Vec::new, |host| {
+            host.time.ok_or_else(|| {
+                wasmtime::Error::msg(
+                    "Time is not available, it can only be read from systems of hosts that use Bevy's TimePlugin",
+                )
+            })
+        })
+    }
+
+    // Note: this doesn't go through the tape, since it only reads the type registry of the app
+    fn list_components(&mut self) -> Result<Vec<ComponentInfo>, wasmtime::Error> {
+        let components = match self.access() {
+            State::Setup { world, .. } => {
+                let aliases = world.get_resource::<TypeAliasRegistry>().cloned();
+                registry::list_components(
+                    world.resource::<FunctionIndex>(),
+                    &world.resource::<AppTypeRegistry>().read(),
+                    &aliases.unwrap_or_default(),
+                )
+            }
+            State::RunSystem {
+                function_index,
+                type_registry,
+                aliases,
+                ..
+            } => registry::list_components(function_index, &type_registry.read(), aliases),
+            State::Call => {
+                return Err(wasmtime::Error::msg(
+                    "Components can only be listed during setup or from systems",
+                ));
+            }
+        };
+
+        Ok(components
+            .into_iter()
+            .map(|info| ComponentInfo {
+                type_path: info.type_path,
+                short_name: info.short_name,
+                has_methods: info.has_methods,
+                fields: info.fields,
+            })
+            .collect())
+    }
+
+    fn call_mod(
+        &mut self,
+        mod_name: String,
+        function: String,
+        params: SerializedComponent,
+    ) -> Result<Result<SerializedComponent, String>, wasmtime::Error> {
+        // The called mod can't access the world, so its output is only read on replay
+        let fields = self.encode_params(|| {
+            encode_fields([mod_name.as_bytes(), function.as_bytes(), &params[..]])
+        });
+        self.tape(
+            "call-mod",
+            Boundary::Read,
+            || fields,
+            |host| {
+                let State::RunSystem {
+                    mod_exports,
+                    access,
+                    ..
+                } = host.access()
+                else {
+                    return Err(wasmtime::Error::msg(
+                        "Mod functions can only be called from systems",
+                    ));
+                };
+
+                Ok(mod_exports
+                    .call(*access, &mod_name, &function, params)
+                    .map_err(|err| format!("{err:#}")))
+            },
+        )
+    }
+}
 
 impl WasiView for WasmHost {
     fn ctx(&mut self) -> WasiCtxView<'_> {
@@ -1,10 +1,12 @@
-use anyhow::{Result, bail};
+use anyhow::{Result, anyhow};
+use bevy_ecs::prelude::*;
 use wasmtime::component::Resource;
 
 use crate::{
-    bindings::wasvy::ecs::app::HostQuery,
-    host::{WasmHost, WasmQueryResult},
+    bindings::wasvy::ecs::app::{ComponentIndex, EntityRow, HostQuery, SerializedComponent},
+    host::{WasmEntity, WasmHost, WasmQueryResult},
     query::{QueryCursor, QueryId},
+    record::{Boundary, CallResponse, decode_fields, encode_entities, encode_fields},
     runner::State,
 };
 
@@ -27,23 +29,279 @@ impl HostQuery for WasmHost {
         &mut self,
         query: Resource<WasmQuery>,
     ) -> std::result::Result<Option<Resource<WasmQueryResult>>, wasmtime::Error> {
-        (|| -> Result<_> {
-            let State::RunSystem { table, queries, .. } = self.access() else {
-                bail!("Query can only be accessed in systems")
+        let id = self.table().get(&query)?.id;
+        let params = || id.to_le_bytes().to_vec();
+        let entity = self.tape("query.iter", Boundary::Read, params, |host| {
+            let State::RunSystem {
+                table,
+                queries,
+                query_resolver,
+                change_tick,
+                ..
+            } = host.access()
+            else {
+                return Err(wasmtime::Error::msg(
+                    "Query can only be accessed in systems",
+                ));
             };
 
+            // Empty once we've reached the end of the results
             let query = table.get_mut(&query)?;
-            let cursor = query.cursor.increment();
-            let Some(entity) = cursor.entity(queries, query.id) else {
-                // We've reached the end of the results
-                return Ok(None);
+            Ok(query.cursor.next(queries, id, query_resolver, *change_tick))
+        })?;
+
+        Ok(self.query_results(id, entity)?.pop())
+    }
+
+    fn next_batch(
+        &mut self,
+        query: Resource<WasmQuery>,
+        max: u32,
+    ) -> std::result::Result<Vec<Resource<WasmQueryResult>>, wasmtime::Error> {
+        let id = self.table().get(&query)?.id;
+        let params = || encode_fields([&id.to_le_bytes()[..], &max.to_le_bytes()]);
+        let entities = self.tape("query.next-batch", Boundary::Read, params, |host| {
+            let State::RunSystem {
+                table,
+                queries,
+                query_resolver,
+                change_tick,
+                ..
+            } = host.access()
+            else {
+                return Err(wasmtime::Error::msg(
+                    "Query can only be accessed in systems",
+                ));
             };
 
-            let result = WasmQueryResult::new(query.id, entity);
-            let result = table.push(result)?;
-            Ok(Some(result))
-        })()
-        .map_err(|err| wasmtime::Error::msg(err.to_string()))
+            let query = table.get_mut(&query)?;
+            let entities =
+                query
+                    .cursor
+                    .next_batch(max as usize, queries, id, query_resolver, *change_tick);
+            Ok(entities.to_vec())
+        })?;
+
+        self.query_results(id, entities)
+    }
+
+    fn get(
+        &mut self,
+        query: Resource<WasmQuery>,
+        entity: Resource<WasmEntity>,
+    ) -> std::result::Result<Option<Resource<WasmQueryResult>>, wasmtime::Error> {
+        let id = self.table().get(&query)?.id;
+        let entity: Entity = self.table().get(&entity)?.into();
+        let params = || encode_fields([&id.to_le_bytes()[..], &encode_entities([entity])]);
+        let entity = self.tape("query.get", Boundary::Read, params, |host| {
+            let State::RunSystem {
+                queries,
+                query_resolver,
+                change_tick,
+                ..
+            } = host.access()
+            else {
+                return Err(wasmtime::Error::msg(
+                    "Query can only be accessed in systems",
+                ));
+            };
+
+            let contains = query_resolver.contains(id, entity, queries, *change_tick);
+            Ok(contains.then_some(entity))
+        })?;
+
+        Ok(self.query_results(id, entity)?.pop())
+    }
+
+    fn count(&mut self, query: Resource<WasmQuery>) -> std::result::Result<u64, wasmtime::Error> {
+        let id = self.table().get(&query)?.id;
+        let params = || id.to_le_bytes().to_vec();
+        self.tape("query.count", Boundary::Read, params, |host| {
+            let State::RunSystem {
+                table,
+                queries,
+                query_resolver,
+                change_tick,
+                ..
+            } = host.access()
+            else {
+                return Err(wasmtime::Error::msg(
+                    "Query can only be accessed in systems",
+                ));
+            };
+
+            let query = table.get(&query)?;
+            let count = query
+                .cursor
+                .count(queries, id, query_resolver, *change_tick);
+            Ok(count as u64)
+        })
+    }
+
+    fn is_empty(
+        &mut self,
+        query: Resource<WasmQuery>,
+    ) -> std::result::Result<bool, wasmtime::Error> {
+        let id = self.table().get(&query)?.id;
+        let params = || id.to_le_bytes().to_vec();
+        self.tape("query.is-empty", Boundary::Read, params, |host| {
+            let State::RunSystem {
+                table,
+                queries,
+                query_resolver,
+                change_tick,
+                ..
+            } = host.access()
+            else {
+                return Err(wasmtime::Error::msg(
+                    "Query can only be accessed in systems",
+                ));
+            };
+
+            let query = table.get(&query)?;
+            Ok(query
+                .cursor
+                .is_empty(queries, id, query_resolver, *change_tick))
+        })
+    }
+
+    fn fetch_all(
+        &mut self,
+        query: Resource<WasmQuery>,
+    ) -> std::result::Result<Vec<EntityRow>, wasmtime::Error> {
+        let id = self.table().get(&query)?.id;
+        let params = || id.to_le_bytes().to_vec();
+        let rows = self.tape("query.fetch-all", Boundary::Read, params, |host| {
+            let State::RunSystem {
+                queries,
+                query_resolver,
+                type_registry,
+                codec,
+                change_tick,
+                ..
+            } = host.access()
+            else {
+                return Err(wasmtime::Error::msg(
+                    "Query can only be accessed in systems",
+                ));
+            };
+
+            query_resolver
+                .fetch_all(id, queries, *change_tick, type_registry, codec)
+                .map_err(wasmtime::Error::msg)
+        })?;
+
+        Ok(rows
+            .into_iter()
+            .map(|(entity, components)| EntityRow {
+                entity_bits: entity.to_bits(),
+                components,
+            })
+            .collect())
+    }
+
+    fn write_all(
+        &mut self,
+        query: Resource<WasmQuery>,
+        rows: Vec<EntityRow>,
+    ) -> std::result::Result<(), wasmtime::Error> {
+        let rows = rows
+            .into_iter()
+            .map(|row| {
+                let entity = Entity::try_from_bits(row.entity_bits).ok_or_else(|| {
+                    wasmtime::Error::msg(format!("{} is not a valid entity", row.entity_bits))
+                })?;
+                Ok((entity, row.components))
+            })
+            .collect::<std::result::Result<Vec<_>, wasmtime::Error>>()?;
+
+        let id = self.table().get(&query)?.id;
+        let params =
+            self.encode_params(|| encode_fields([&id.to_le_bytes()[..], &encode_rows(&rows)]));
+        self.tape(
+            "query.write-all",
+            Boundary::Write,
+            || params,
+            |host| {
+                let State::RunSystem {
+                    queries,
+                    query_resolver,
+                    type_registry,
+                    codec,
+                    ..
+                } = host.access()
+                else {
+                    return Err(wasmtime::Error::msg(
+                        "Query can only be accessed in systems",
+                    ));
+                };
+
+                query_resolver
+                    .write_all(id, rows, queries, type_registry, codec)
+                    .map_err(wasmtime::Error::msg)
+            },
+        )
+    }
+
+    fn get_resource(
+        &mut self,
+        query: Resource<WasmQuery>,
+        index: ComponentIndex,
+    ) -> std::result::Result<SerializedComponent, wasmtime::Error> {
+        let id = self.table().get(&query)?.id;
+        let params = || encode_fields([&id.to_le_bytes()[..], &[index]]);
+        self.tape("query.get-resource", Boundary::Read, params, |host| {
+            let State::RunSystem {
+                resources,
+                query_resolver,
+                type_registry,
+                codec,
+                ..
+            } = host.access()
+            else {
+                return Err(wasmtime::Error::msg(
+                    "Resources can only be accessed in systems",
+                ));
+            };
+
+            query_resolver
+                .get_resource(id, index, resources, type_registry, codec)
+                .map_err(wasmtime::Error::msg)
+        })
+    }
+
+    fn set_resource(
+        &mut self,
+        query: Resource<WasmQuery>,
+        index: ComponentIndex,
+        value: SerializedComponent,
+    ) -> std::result::Result<(), wasmtime::Error> {
+        let id = self.table().get(&query)?.id;
+        let params =
+            self.encode_params(|| encode_fields([&id.to_le_bytes()[..], &[index], &value[..]]));
+        self.tape(
+            "query.set-resource",
+            Boundary::Write,
+            || params,
+            |host| {
+                let State::RunSystem {
+                    resources,
+                    query_resolver,
+                    type_registry,
+                    codec,
+                    ..
+                } = host.access()
+                else {
+                    return Err(wasmtime::Error::msg(
+                        "Resources can only be accessed in systems",
+                    ));
+                };
+
+                query_resolver
+                    .set_resource(id, index, value, resources, type_registry, codec)
+                    .map_err(wasmtime::Error::msg)
+            },
+        )
     }
 
     // Note: this is never guaranteed to be called by the wasi binary
@@ -56,3 +314,165 @@ impl HostQuery for WasmHost {
         .map_err(|err| wasmtime::Error::msg(err.to_string()))
     }
 }
+
+impl WasmHost {
+    /// Creates the results of a query call from the entities it responded with
+    fn query_results(
+        &mut self,
+        id: QueryId,
+        entities: impl IntoIterator<Item = Entity>,
+    ) -> std::result::Result<Vec<Resource<WasmQueryResult>>, wasmtime::Error> {
+        entities
+            .into_iter()
+            .map(|entity| Ok(self.table().push(WasmQueryResult::new(id, entity))?))
+            .collect()
+    }
+}
+
+/// Encodes the rows of `fetch-all` and `write-all` so they can be recorded and replayed
+fn encode_rows(rows: &[(Entity, Vec<Vec<u8>>)]) -> Vec<u8> {
+    let rows: Vec<_> = rows
+        .iter()
+        .map(|(entity, components)| {
+            let bits = entity.to_bits().to_le_bytes();
+            encode_fields(std::iter::once(&bits[..]).chain(components.iter().map(Vec::as_slice)))
+        })
+        .collect();
+    encode_fields(rows.iter().map(Vec::as_slice))
+}
+
+impl CallResponse for Vec<(Entity, Vec<Vec<u8>>)> {
+    fn encode(&self) -> Vec<u8> {
+        encode_rows(self)
+    }
+
+    fn decode(bytes: Vec<u8>) -> Result<Self> {
+        decode_rows(&bytes)
+    }
+}
+
+fn decode_rows(bytes: &[u8]) -> Result<Vec<(Entity, Vec<Vec<u8>>)>> {
+    let invalid = || anyhow!("Invalid recorded query rows");
+    decode_fields(bytes)?
+        .iter()
+        .map(|row| {
+            let mut fields = decode_fields(row)?.into_iter();
+            let bits = fields.next().ok_or_else(invalid)?;
+            let bits = u64::from_le_bytes(bits.try_into().map_err(|_| invalid())?);
+            let entity = Entity::try_from_bits(bits).ok_or_else(invalid)?;
+            Ok((entity, fields.collect()))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy_ecs::reflect::AppTypeRegistry;
+    use bevy_reflect::{Reflect, TypePath};
+    use wasvy_runtime::{sandbox::Sandbox, schedule::ModSchedules};
+
+    use super::*;
+    use crate::{
+        bindings::wasvy::ecs::app::{HostQueryResult, QueryFor},
+        query::QueryIdGenerator,
+        system::Param,
+        test_utils::{run_system, world},
+    };
+
+    #[derive(Component, Reflect)]
+    #[reflect(Component)]
+    struct Health(u32);
+
+    /// Runs `f` in a system querying [Health], passing it a function creating the query resource
+    fn with_query<R>(
+        world: &mut World,
+        f: impl FnOnce(&mut WasmHost, &dyn Fn() -> Resource<WasmQuery>) -> R,
+    ) -> R {
+        world.register_component::<Health>();
+        world
+            .resource::<AppTypeRegistry>()
+            .write()
+            .register::<Health>();
+        let params = [Param::Query(vec![QueryFor::Ref(
+            Health::type_path().to_string(),
+        )])];
+        run_system(world, &params, &Default::default(), |host| {
+            let id = QueryIdGenerator::default().generate();
+            let query = host.table().push(WasmQuery::new(id)).unwrap();
+            let query = || Resource::<WasmQuery>::new_borrow(query.rep());
+            f(host, &query)
+        })
+    }
+
+    #[test]
+    fn only_gets_entities_within_the_access() {
+        let mut world = world();
+        let sandbox = Sandbox::new(&mut world, ModSchedules::empty());
+        let sandbox = world.spawn(sandbox).id();
+        let inside = world.spawn(Health(1)).id();
+        let sandboxed = world.spawn((Health(2), ChildOf(sandbox))).id();
+
+        with_query(&mut world, |host, query| {
+            let mut get = |entity| {
+                let entity = host.table().push(WasmEntity(entity)).unwrap();
+                host.get(query(), entity).unwrap().is_some()
+            };
+            assert!(get(inside));
+            assert!(!get(sandboxed));
+
+            assert_eq!(host.count(query()).unwrap(), 1);
+            assert!(!host.is_empty(query()).unwrap());
+        });
+    }
+
+    #[test]
+    fn only_gets_children_within_the_access() {
+        let mut world = world();
+        let sandbox = Sandbox::new(&mut world, ModSchedules::empty());
+        let sandbox = world.spawn((sandbox, Health(1))).id();
+        world.spawn(ChildOf(sandbox));
+        let parent = world.spawn(Health(2)).id();
+        let child = world.spawn(ChildOf(parent)).id();
+
+        with_query(&mut world, |host, query| {
+            let mut children = |entity| {
+                let entity = host.table().push(WasmEntity(entity)).unwrap();
+                let result = host.get(query(), entity).unwrap().unwrap();
+                host.children(result)
+                    .unwrap()
+                    .into_iter()
+                    .map(|child| host.table().get(&child).unwrap().0)
+                    .collect::<Vec<_>>()
+            };
+            assert_eq!(children(parent), [child]);
+            assert!(children(sandbox).is_empty());
+        });
+    }
+
+    #[test]
+    fn empty_queries() {
+        let mut world = world();
+        let without_health = world.spawn_empty().id();
+
+        with_query(&mut world, |host, query| {
+            assert_eq!(host.count(query()).unwrap(), 0);
+            assert!(host.is_empty(query()).unwrap());
+            let entity = host.table().push(WasmEntity(without_health)).unwrap();
+            assert!(host.get(query(), entity).unwrap().is_none());
+            assert!(host.iter(query()).unwrap().is_none());
+        });
+    }
+
+    #[test]
+    fn encodes_rows() {
+        let rows = vec![
+            (
+                Entity::from_raw_u32(1).unwrap(),
+                vec![b"{}".to_vec(), Vec::new()],
+            ),
+            (Entity::from_raw_u32(7).unwrap(), Vec::new()),
+        ];
+        assert_eq!(decode_rows(&encode_rows(&rows)).unwrap(), rows);
+        assert!(decode_rows(&encode_fields([&b"x"[..]])).is_err());
+    }
+}
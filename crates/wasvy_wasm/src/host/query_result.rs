@@ -3,9 +3,10 @@ use wasmtime::component::Resource;
 
 use crate::{
     bindings::wasvy::ecs::app::{ComponentIndex, HostQueryResult},
-    entity::map_entity,
+    entity::{entity_resource, map_entity},
     host::{WasmComponent, WasmEntity, WasmHost},
     query::QueryId,
+    record::{Boundary, encode_entities},
     runner::State,
 };
 
@@ -32,7 +33,18 @@ impl HostQueryResult for WasmHost {
         &mut self,
         query_result: Resource<WasmQueryResult>,
     ) -> Result<Resource<WasmEntity>, wasmtime::Error> {
-        map_entity(self, query_result)
+        let entity = self.table().get(&query_result)?.entity;
+        self.tape(
+            "query-result.entity",
+            Boundary::Read,
+            || encode_entities([entity]),
+            |host| {
+                map_entity::<_, WasmEntity>(host, query_result)?;
+                Ok(())
+            },
+        )?;
+
+        entity_resource(entity, self.table())
     }
 
     fn component(
@@ -52,6 +64,41 @@ impl HostQueryResult for WasmHost {
         Ok(component)
     }
 
+    fn children(
+        &mut self,
+        query_result: Resource<WasmQueryResult>,
+    ) -> Result<Vec<Resource<WasmEntity>>, wasmtime::Error> {
+        let entity = self.table().get(&query_result)?.entity;
+        let params = || encode_entities([entity]);
+        let children = self.tape("query-result.children", Boundary::Read, params, |host| {
+            let State::RunSystem {
+                hierarchy, access, ..
+            } = host.access()
+            else {
+                return Err(wasmtime::Error::msg(
+                    "QueryResult can only be accessed in systems",
+                ));
+            };
+
+            let Ok((_, Some(children))) = hierarchy.get(entity) else {
+                return Ok(Vec::new());
+            };
+            Ok(children
+                .iter()
+                .filter(|child| {
+                    hierarchy
+                        .get(*child)
+                        .is_ok_and(|(sandboxed, _)| access.contains(sandboxed))
+                })
+                .collect::<Vec<_>>())
+        })?;
+
+        children
+            .into_iter()
+            .map(|child| entity_resource(child, self.table()))
+            .collect()
+    }
+
     // Note: this is never guaranteed to be called by the wasi binary
     fn drop(
         &mut self,
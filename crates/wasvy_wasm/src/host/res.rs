@@ -0,0 +1,167 @@
+use anyhow::{Result, bail};
+use wasmtime::component::Resource;
+use wasvy_runtime::{
+    component::{ResourceRef, get_resource, set_resource, with_resource_mut, with_resource_ref},
+    methods::MethodTarget,
+};
+
+use crate::{
+    bindings::wasvy::ecs::app::{HostRes, SerializedComponent},
+    host::WasmHost,
+    record::{Boundary, encode_fields},
+    runner::State,
+};
+
+/// Host-side handle for a WIT `res` resource, see `system.add-resource`
+pub struct WasmRes {
+    resource: ResourceRef,
+    mutable: bool,
+}
+
+impl WasmRes {
+    pub(crate) fn new(resource: ResourceRef, mutable: bool) -> Self {
+        Self { resource, mutable }
+    }
+}
+
+impl HostRes for WasmHost {
+    fn get(&mut self, res: Resource<WasmRes>) -> Result<SerializedComponent, wasmtime::Error> {
+        let type_path = self.table().get(&res)?.resource.type_path().to_string();
+        let params = || type_path.into_bytes();
+        self.tape("res.get", Boundary::Read, params, |host| {
+            let State::RunSystem {
+                table,
+                resources,
+                type_registry,
+                codec,
+                ..
+            } = host.access()
+            else {
+                return Err(wasmtime::Error::msg(
+                    "Resources can only be accessed in systems",
+                ));
+            };
+
+            let res = table.get(&res)?;
+            get_resource(
+                &resources.as_readonly(),
+                &res.resource,
+                type_registry,
+                codec,
+            )
+            .map_err(wasmtime::Error::msg)
+        })
+    }
+
+    fn set(
+        &mut self,
+        res: Resource<WasmRes>,
+        value: SerializedComponent,
+    ) -> Result<(), wasmtime::Error> {
+        let type_path = self.table().get(&res)?.resource.type_path().to_string();
+        let params = self.encode_params(|| encode_fields([type_path.as_bytes(), &value[..]]));
+        self.tape(
+            "res.set",
+            Boundary::Write,
+            || params,
+            |host| {
+                let State::RunSystem {
+                    table,
+                    resources,
+                    type_registry,
+                    codec,
+                    ..
+                } = host.access()
+                else {
+                    return Err(wasmtime::Error::msg(
+                        "Resources can only be accessed in systems",
+                    ));
+                };
+
+                let res = table.get(&res)?;
+                if !res.mutable {
+                    return Err(wasmtime::Error::msg(format!(
+                        "Resource {} was not added as mutable",
+                        res.resource.type_path()
+                    )));
+                }
+                set_resource(resources, &res.resource, value, type_registry, codec)
+                    .map_err(wasmtime::Error::msg)
+            },
+        )
+    }
+
+    fn invoke(
+        &mut self,
+        res: Resource<WasmRes>,
+        method: String,
+        params: SerializedComponent,
+    ) -> Result<SerializedComponent, wasmtime::Error> {
+        let type_path = self.table().get(&res)?.resource.type_path().to_string();
+        let fields = || encode_fields([type_path.as_bytes(), method.as_bytes(), &params[..]]);
+        self.tape("res.invoke", Boundary::Write, fields, |host| {
+            invoke_resource_method(host, res, &method, &params).map_err(wasmtime::Error::msg)
+        })
+    }
+
+    // Note: this is never guaranteed to be called by the wasi binary
+    fn drop(&mut self, res: Resource<WasmRes>) -> Result<(), wasmtime::Error> {
+        let _ = self.table().delete(res)?;
+
+        Ok(())
+    }
+}
+
+/// Invoke a reflected method of a resource using JSON-encoded arguments.
+///
+/// This is used by the auto-generated host bindings to implement WIT methods of resources
+/// exported with `WasvyResource`.
+pub fn invoke_resource_method(
+    host: &mut WasmHost,
+    res: Resource<WasmRes>,
+    method: &str,
+    params: &[u8],
+) -> Result<SerializedComponent> {
+    let State::RunSystem {
+        table,
+        resources,
+        type_registry,
+        codec,
+        function_index,
+        ..
+    } = host.access()
+    else {
+        bail!("Resources can only be accessed in systems")
+    };
+
+    let res = table.get(&res)?;
+    let type_path = res.resource.type_path();
+    if res.mutable {
+        with_resource_mut(resources, &res.resource, type_registry, |reflect| {
+            function_index.invoke(
+                type_path,
+                method,
+                MethodTarget::Write(reflect),
+                params,
+                type_registry,
+                codec,
+            )
+        })
+    } else {
+        with_resource_ref(
+            &resources.as_readonly(),
+            &res.resource,
+            type_registry,
+            |reflect| {
+                function_index.invoke(
+                    type_path,
+                    method,
+                    MethodTarget::Read(reflect),
+                    params,
+                    type_registry,
+                    codec,
+                )
+            },
+        )
+    }
+}
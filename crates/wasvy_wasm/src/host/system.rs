@@ -1,8 +1,10 @@
-use bevy_ecs::prelude::*;
+use bevy_ecs::{intern::Interned, prelude::*};
 use wasmtime::component::Resource;
+use wasvy_runtime::{limits::ModLimits, schedule::HostSystemSets};
 
 use crate::{
-    bindings::wasvy::ecs::app::{HostSystem, QueryFor},
+    bindings::wasvy::ecs::app::{HostSystem, LifecycleEvent, Mutability, QueryFor},
+    condition::RunCondition,
     host::WasmHost,
     runner::State,
     system::{DynamicSystemId, Param},
@@ -13,15 +15,31 @@ pub struct WasmSystem {
     pub(crate) name: String,
     pub(crate) params: Vec<Param>,
     pub(crate) after: Vec<DynamicSystemId>,
+
+    /// The host system sets this system runs after, see `system.after-set`
+    pub(crate) after_sets: Vec<Interned<dyn SystemSet>>,
+
+    /// The host system sets this system runs before, see `system.before-set`
+    pub(crate) before_sets: Vec<Interned<dyn SystemSet>>,
+
+    /// The components and events this system observes, see `system.observe`
+    pub(crate) observers: Vec<(String, LifecycleEvent)>,
+
+    /// See `system.run-if-resource-equals` and `system.run-every-n-frames`
+    pub(crate) conditions: Vec<RunCondition>,
 }
 
 impl WasmSystem {
-    fn new(name: String, world: &mut World) -> Self {
+    pub(crate) fn new(name: String, world: &mut World) -> Self {
         Self {
             id: DynamicSystemId::new(world),
             name,
             params: Vec::new(),
             after: Vec::new(),
+            after_sets: Vec::new(),
+            before_sets: Vec::new(),
+            observers: Vec::new(),
+            conditions: Vec::new(),
         }
     }
 
@@ -30,17 +48,77 @@ impl WasmSystem {
         system: Resource<WasmSystem>,
         param: Param,
     ) -> Result<(), wasmtime::Error> {
-        let State::Setup { table, .. } = host.access() else {
+        let State::Setup { table, world, .. } = host.access() else {
             return Err(wasmtime::Error::msg(
                 "Systems can only be modified in a setup function",
             ));
         };
 
         let system = table.get_mut(&system)?;
+        if let Param::Query(items) = &param {
+            let queries = system.params.iter().filter_map(Param::filter_query).count() + 1;
+            let limits = world
+                .get_resource::<ModLimits>()
+                .copied()
+                .unwrap_or_default();
+            limits
+                .check_query(&system.name, queries, items.len())
+                .map_err(wasmtime::Error::new)?;
+        }
         system.params.push(param);
 
         Ok(())
     }
+
+    fn add_condition(
+        host: &mut WasmHost,
+        system: Resource<WasmSystem>,
+        condition: RunCondition,
+    ) -> Result<(), wasmtime::Error> {
+        let State::Setup { table, .. } = host.access() else {
+            return Err(wasmtime::Error::msg(
+                "Systems can only be modified in a setup function",
+            ));
+        };
+
+        let system = table.get_mut(&system)?;
+        system.conditions.push(condition);
+
+        Ok(())
+    }
+
+    /// Orders a system against a host system set, see [HostSystemSets]
+    fn order_against_set(
+        host: &mut WasmHost,
+        system: Resource<WasmSystem>,
+        name: &str,
+        before: bool,
+    ) -> Result<(), wasmtime::Error> {
+        let State::Setup { table, world, .. } = host.access() else {
+            return Err(wasmtime::Error::msg(
+                "Systems can only be modified in a setup function",
+            ));
+        };
+
+        let system = table.get_mut(&system)?;
+        let Some(set) = world
+            .get_resource::<HostSystemSets>()
+            .and_then(|sets| sets.get(name))
+        else {
+            return Err(wasmtime::Error::msg(format!(
+                "System \"{}\" can't be ordered against the unknown host system set \"{name}\"",
+                system.name
+            )));
+        };
+
+        if before {
+            system.before_sets.push(set);
+        } else {
+            system.after_sets.push(set);
+        }
+
+        Ok(())
+    }
 }
 
 impl HostSystem for WasmHost {
@@ -70,6 +148,73 @@ impl HostSystem for WasmHost {
         WasmSystem::add_param(self, system, Param::Query(query))
     }
 
+    fn add_event_reader(
+        &mut self,
+        system: Resource<WasmSystem>,
+        event: String,
+    ) -> std::result::Result<(), wasmtime::Error> {
+        WasmSystem::add_param(self, system, Param::Events(event))
+    }
+
+    fn add_trigger(
+        &mut self,
+        system: Resource<WasmSystem>,
+    ) -> std::result::Result<(), wasmtime::Error> {
+        WasmSystem::add_param(self, system, Param::Trigger)
+    }
+
+    fn add_resource(
+        &mut self,
+        system: Resource<WasmSystem>,
+        resource_type: String,
+        mutability: Mutability,
+    ) -> std::result::Result<(), wasmtime::Error> {
+        let mutable = matches!(mutability, Mutability::Mutable);
+        WasmSystem::add_param(self, system, Param::Resource(resource_type, mutable))
+    }
+
+    fn observe(
+        &mut self,
+        system: Resource<WasmSystem>,
+        component: String,
+        event: LifecycleEvent,
+    ) -> std::result::Result<(), wasmtime::Error> {
+        let State::Setup { table, .. } = self.access() else {
+            return Err(wasmtime::Error::msg(
+                "Systems can only be modified in a setup function",
+            ));
+        };
+
+        let system = table.get_mut(&system)?;
+        system.observers.push((component, event));
+
+        Ok(())
+    }
+
+    fn run_if_resource_equals(
+        &mut self,
+        system: Resource<WasmSystem>,
+        resource_type: String,
+        value: Vec<u8>,
+    ) -> std::result::Result<(), wasmtime::Error> {
+        let condition = RunCondition::ResourceEquals {
+            type_path: resource_type,
+            value,
+        };
+        WasmSystem::add_condition(self, system, condition)
+    }
+
+    fn run_every_n_frames(
+        &mut self,
+        system: Resource<WasmSystem>,
+        n: u32,
+    ) -> std::result::Result<(), wasmtime::Error> {
+        if n == 0 {
+            return Err(wasmtime::Error::msg("Systems can't run every 0 frames"));
+        }
+        WasmSystem::add_condition(self, system, RunCondition::EveryNFrames(n))
+    }
+
     fn after(
         &mut self,
         system: Resource<WasmSystem>,
@@ -109,6 +254,22 @@ impl HostSystem for WasmHost {
         Ok(())
     }
 
+    fn after_set(
+        &mut self,
+        system: Resource<WasmSystem>,
+        name: String,
+    ) -> std::result::Result<(), wasmtime::Error> {
+        WasmSystem::order_against_set(self, system, &name, false)
+    }
+
+    fn before_set(
+        &mut self,
+        system: Resource<WasmSystem>,
+        name: String,
+    ) -> std::result::Result<(), wasmtime::Error> {
+        WasmSystem::order_against_set(self, system, &name, true)
+    }
+
     // Note: this is never guaranteed to be called by the wasi binary
     fn drop(&mut self, _: Resource<WasmSystem>) -> std::result::Result<(), wasmtime::Error> {
         // Don't drop! After running setup, wasvy will find and register all
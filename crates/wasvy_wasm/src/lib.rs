@@ -1,16 +1,55 @@
 #![doc = "WASM backend for wasvy_runtime, powered by Wasmtime and WASI."]
 
-pub mod engine;
-pub mod host;
+#[cfg(not(any(feature = "wasmtime", feature = "web")))]
+compile_error!("wasvy_wasm needs a runtime: enable the `wasmtime` or `web` feature.");
+
 pub mod plugin;
+pub mod runtime;
 pub mod wasm_asset;
 
+#[cfg(feature = "wasmtime")]
+pub mod cache;
+#[cfg(feature = "wasmtime")]
+pub mod diagnostics;
+#[cfg(feature = "wasmtime")]
+pub mod engine;
+#[cfg(feature = "wasmtime")]
+pub mod fuel;
+#[cfg(feature = "wasmtime")]
+pub mod host;
+#[cfg(feature = "wasmtime")]
+pub mod record;
+#[cfg(feature = "wasmtime")]
+pub mod version;
+#[cfg(feature = "web")]
+pub mod web;
+
+#[cfg(feature = "wasmtime")]
+pub(crate) mod compose;
+#[cfg(feature = "wasmtime")]
+pub(crate) mod condition;
+#[cfg(feature = "wasmtime")]
 pub(crate) mod entity;
+#[cfg(feature = "wasmtime")]
+pub(crate) mod exports;
+#[cfg(feature = "wasmtime")]
+pub(crate) mod log;
+#[cfg(feature = "wasmtime")]
 pub(crate) mod query;
+#[cfg(feature = "wasmtime")]
 pub(crate) mod runner;
+#[cfg(feature = "wasmtime")]
+pub(crate) mod scene;
+#[cfg(feature = "wasmtime")]
 pub(crate) mod send_sync_ptr;
+#[cfg(feature = "wasmtime")]
 pub(crate) mod system;
+#[cfg(all(test, feature = "wasmtime"))]
+pub(crate) mod test_utils;
+#[cfg(feature = "wasmtime")]
+pub(crate) mod time;
 
+#[cfg(feature = "wasmtime")]
 pub(crate) mod bindings {
     wasmtime::component::bindgen!({
         path: "wit/wasvy-ecs.wit",
@@ -23,13 +62,27 @@ pub(crate) mod bindings {
             "wasvy:ecs/app.commands": crate::host::WasmCommands,
             "wasvy:ecs/app.entity": crate::host::WasmEntity,
             "wasvy:ecs/app.entity-commands": crate::host::WasmEntityCommands,
+            "wasvy:ecs/app.event-reader": crate::host::WasmEventReader,
             "wasvy:ecs/app.query": crate::host::WasmQuery,
             "wasvy:ecs/app.query-result": crate::host::WasmQueryResult,
+            "wasvy:ecs/app.res": crate::host::WasmRes,
+            "wasvy:ecs/app.assets": crate::host::WasmAssets,
+            "wasvy:ecs/app.asset-handle": crate::host::WasmAssetHandle,
             "wasvy:ecs/app.component": crate::host::WasmComponent,
         },
     });
 }
 
+#[cfg(feature = "wasmtime")]
 pub use engine::Linker;
+#[cfg(feature = "wasmtime")]
+pub use fuel::ModFuelExhausted;
 pub use plugin::WasmBackendPlugin;
-pub use wasm_asset::{ModAssetLoader, WasmModBackend};
+pub use runtime::WasmRuntime;
+#[cfg(feature = "wasmtime")]
+pub use runtime::WasmtimeRuntime;
+pub use wasm_asset::ModAssetLoader;
+#[cfg(feature = "wasmtime")]
+pub use wasm_asset::WasmModBackend;
+#[cfg(feature = "web")]
+pub use web::WebRuntime;
@@ -0,0 +1,139 @@
+use std::{
+    io,
+    pin::Pin,
+    sync::{Arc, Mutex},
+    task::{Context, Poll},
+};
+
+use bevy_log::{Level, prelude::*};
+use bytes::Bytes;
+use tokio::io::AsyncWrite;
+use wasmtime_wasi::{
+    async_trait,
+    cli::{IsTerminal, StdoutStream},
+    p2::{OutputStream, Pollable, StreamError},
+};
+
+use crate::bindings::wasvy::ecs::app::LogLevel;
+
+impl From<LogLevel> for Level {
+    fn from(level: LogLevel) -> Self {
+        match level {
+            LogLevel::Trace => Level::TRACE,
+            LogLevel::Debug => Level::DEBUG,
+            LogLevel::Info => Level::INFO,
+            LogLevel::Warn => Level::WARN,
+            LogLevel::Error => Level::ERROR,
+        }
+    }
+}
+
+/// Logs a message of a mod with Bevy's logger, in a span naming the mod
+pub(crate) fn log(level: Level, mod_name: &str, message: &str) {
+    let _span = info_span!("mod", name = mod_name).entered();
+    match level {
+        Level::TRACE => trace!("{message}"),
+        Level::DEBUG => debug!("{message}"),
+        Level::INFO => info!("{message}"),
+        Level::WARN => warn!("{message}"),
+        Level::ERROR => error!("{message}"),
+    }
+}
+
+/// Routes a mod's stdout or stderr into Bevy's log, one line at a time.
+///
+/// See [`WasmBackendPlugin::capture_output`](crate::plugin::WasmBackendPlugin::capture_output).
+#[derive(Clone)]
+pub(crate) struct ModOutput {
+    mod_name: Arc<str>,
+    level: Level,
+
+    /// The start of a line that hasn't ended yet
+    line: Arc<Mutex<Vec<u8>>>,
+}
+
+impl ModOutput {
+    pub(crate) fn stdout(mod_name: &str) -> Self {
+        Self::new(mod_name, Level::INFO)
+    }
+
+    pub(crate) fn stderr(mod_name: &str) -> Self {
+        Self::new(mod_name, Level::WARN)
+    }
+
+    fn new(mod_name: &str, level: Level) -> Self {
+        Self {
+            mod_name: mod_name.into(),
+            level,
+            line: Default::default(),
+        }
+    }
+
+    fn write_bytes(&self, bytes: &[u8]) {
+        let mut line = self.line.lock().unwrap_or_else(|err| err.into_inner());
+        line.extend_from_slice(bytes);
+
+        while let Some(end) = line.iter().position(|byte| *byte == b'\n') {
+            let rest = line.split_off(end + 1);
+            let text = String::from_utf8_lossy(&line[..end]);
+            log(self.level, &self.mod_name, text.trim_end_matches('\r'));
+            *line = rest;
+        }
+    }
+}
+
+impl IsTerminal for ModOutput {
+    fn is_terminal(&self) -> bool {
+        false
+    }
+}
+
+impl StdoutStream for ModOutput {
+    fn p2_stream(&self) -> Box<dyn OutputStream> {
+        Box::new(self.clone())
+    }
+
+    fn async_stream(&self) -> Box<dyn AsyncWrite + Send + Sync> {
+        Box::new(self.clone())
+    }
+}
+
+#[async_trait]
+impl OutputStream for ModOutput {
+    fn write(&mut self, bytes: Bytes) -> Result<(), StreamError> {
+        self.write_bytes(&bytes);
+        Ok(())
+    }
+
+    fn flush(&mut self) -> Result<(), StreamError> {
+        Ok(())
+    }
+
+    fn check_write(&mut self) -> Result<usize, StreamError> {
+        Ok(usize::MAX)
+    }
+}
+
+#[async_trait]
+impl Pollable for ModOutput {
+    async fn ready(&mut self) {}
+}
+
+impl AsyncWrite for ModOutput {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        _: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        self.write_bytes(buf);
+        Poll::Ready(Ok(buf.len()))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+}
@@ -1,42 +1,242 @@
-use std::sync::Mutex;
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
 
 use bevy_app::Plugin;
+#[cfg(feature = "wasmtime")]
+use bevy_app::{First, Last};
 use bevy_asset::prelude::*;
-use wasvy_runtime::asset::ModAsset;
+use wasvy_runtime::{
+    asset::{ModCompiler, init_mod_assets},
+    limits::ModMemoryLimits,
+    verify::ModVerifier,
+};
 
+#[cfg(feature = "web")]
+use crate::web::WebRuntime;
+#[cfg(feature = "wasmtime")]
 use crate::{
+    cache::ModCache,
     engine::{Engine, Linker, create_linker},
-    wasm_asset::ModAssetLoader,
+    exports::ModExports,
+    fuel::{ModFuelExhausted, refill_fuel},
+    record::save_recording,
+    runtime::WasmtimeRuntime,
 };
+use crate::{runtime::WasmRuntime, wasm_asset::ModAssetLoader};
 
-/// Adds the Wasmtime backend for [`wasvy_runtime`].
+/// Adds the WASM backend for [`wasvy_runtime`].
+///
+/// Mods run with Wasmtime by default. Browser builds can disable the `wasmtime` feature and use
+/// [`WasmBackendPlugin::web`] instead.
 pub struct WasmBackendPlugin(Mutex<Option<Inner>>);
 
-struct Inner {
-    engine: Engine,
-    linker: Linker,
+// The plugin holds a single one, until it is built
+#[allow(clippy::large_enum_variant)]
+enum Inner {
+    #[cfg(feature = "wasmtime")]
+    Wasmtime {
+        engine: Engine,
+        linker: Linker,
+        cache: Option<ModCache>,
+    },
+    #[cfg(feature = "web")]
+    Web,
 }
 
 impl Default for WasmBackendPlugin {
     fn default() -> Self {
-        Self::new()
+        #[cfg(feature = "wasmtime")]
+        return Self::new();
+
+        #[cfg(not(feature = "wasmtime"))]
+        return Self::web();
     }
 }
 
 impl WasmBackendPlugin {
+    /// Creates a backend that runs mods with Wasmtime.
+    #[cfg(feature = "wasmtime")]
     pub fn new() -> Self {
         let engine = Engine::default();
         let linker = create_linker(&engine);
-        Self(Mutex::new(Some(Inner { engine, linker })))
+        Self(Mutex::new(Some(Inner::Wasmtime {
+            engine,
+            linker,
+            cache: None,
+        })))
+    }
+
+    /// Creates a backend that runs mods with the browser's WebAssembly API.
+    ///
+    /// See [`WebRuntime`] for its limitations.
+    #[cfg(feature = "web")]
+    pub fn web() -> Self {
+        Self(Mutex::new(Some(Inner::Web)))
     }
 
     /// Use this function to add custom functionality that will be passed to WASM modules.
+    ///
+    /// Only the Wasmtime runtime supports custom functionality.
+    #[cfg(feature = "wasmtime")]
     pub fn add_functionality<F>(mut self, mut f: F) -> Self
     where
         F: FnMut(&mut Linker),
     {
-        let inner = self.inner();
-        f(&mut inner.linker);
+        match self.inner() {
+            Inner::Wasmtime { linker, .. } => f(linker),
+            #[cfg(feature = "web")]
+            Inner::Web => {
+                bevy_log::warn!("The web runtime does not support custom functionality")
+            }
+        }
+        self
+    }
+
+    /// Interrupts mods that run for longer than `timeout` in a single call, such as a system run.
+    ///
+    /// Without a timeout, a mod stuck in an infinite loop freezes the app. Interrupted systems
+    /// return an error naming the mod and system. By default mods are not interrupted.
+    ///
+    /// Only the Wasmtime runtime supports timeouts.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        match self.inner() {
+            #[cfg(feature = "wasmtime")]
+            Inner::Wasmtime { engine, .. } => engine.set_timeout(timeout),
+            #[cfg(feature = "web")]
+            Inner::Web => {
+                let _ = timeout;
+                bevy_log::warn!("The web runtime does not support timeouts")
+            }
+        }
+        self
+    }
+
+    /// Limits the memory, tables and instances of every mod, so a single mod can't exhaust host
+    /// memory. Insert [`ModMemoryLimits`] on a mod's entity to override these for that mod.
+    ///
+    /// By default mods only have Wasmtime's own limits.
+    ///
+    /// Only the Wasmtime runtime supports memory limits.
+    pub fn memory_limits(mut self, limits: ModMemoryLimits) -> Self {
+        match self.inner() {
+            #[cfg(feature = "wasmtime")]
+            Inner::Wasmtime { engine, .. } => engine.set_memory_limits(limits),
+            #[cfg(feature = "web")]
+            Inner::Web => {
+                let _ = limits;
+                bevy_log::warn!("The web runtime does not support memory limits")
+            }
+        }
+        self
+    }
+
+    /// Limits how many calls into a mod may be on the stack at once, counting the outermost call.
+    ///
+    /// Host functions may call back into the mod that called them (see
+    /// [`HostContext::call`](crate::host::HostContext::call)), which fails once `depth` calls are
+    /// on the stack, so mods and host functions calling each other can't recurse forever. Defaults
+    /// to 4.
+    ///
+    /// Only the Wasmtime runtime supports calling back into mods.
+    pub fn max_call_depth(mut self, depth: usize) -> Self {
+        match self.inner() {
+            #[cfg(feature = "wasmtime")]
+            Inner::Wasmtime { engine, .. } => engine.set_max_call_depth(depth),
+            #[cfg(feature = "web")]
+            Inner::Web => {
+                let _ = depth;
+                bevy_log::warn!("The web runtime does not support calling back into mods")
+            }
+        }
+        self
+    }
+
+    /// Routes what mods print to stdout and stderr into Bevy's log, attributed to the mod.
+    ///
+    /// Lines printed to stdout are logged as info, and lines printed to stderr as warnings. Mods
+    /// can also log with a specific level via the `log` host function. By default mods print
+    /// straight to the host's stdout and stderr.
+    ///
+    /// Only the Wasmtime runtime supports capturing output.
+    pub fn capture_output(mut self, capture: bool) -> Self {
+        match self.inner() {
+            #[cfg(feature = "wasmtime")]
+            Inner::Wasmtime { engine, .. } => engine.set_capture_output(capture),
+            #[cfg(feature = "web")]
+            Inner::Web => {
+                let _ = capture;
+                bevy_log::warn!("The web runtime does not support capturing output")
+            }
+        }
+        self
+    }
+
+    /// Records how long each mod system runs, how often it runs and traps, and how much memory
+    /// it uses, as Bevy [diagnostics](bevy_diagnostic).
+    ///
+    /// The app needs a [`DiagnosticsStore`](bevy_diagnostic::DiagnosticsStore), which Bevy's
+    /// `DiagnosticsPlugin` adds. See [`crate::diagnostics`] for the paths of the metrics. By
+    /// default no diagnostics are recorded.
+    ///
+    /// Only the Wasmtime runtime supports diagnostics.
+    pub fn diagnostics(mut self, enabled: bool) -> Self {
+        match self.inner() {
+            #[cfg(feature = "wasmtime")]
+            Inner::Wasmtime { engine, .. } => engine.set_diagnostics(enabled),
+            #[cfg(feature = "web")]
+            Inner::Web => {
+                let _ = enabled;
+                bevy_log::warn!("The web runtime does not support diagnostics")
+            }
+        }
+        self
+    }
+
+    /// Limits how much each mod may compute per frame, using Wasmtime's fuel metering.
+    ///
+    /// Fuel roughly corresponds to the number of wasm instructions executed. A mod that runs out
+    /// has its remaining systems skipped until the next frame, and a [`ModFuelExhausted`] message
+    /// is sent. By default fuel is not metered, since metering slows mods down.
+    ///
+    /// This recreates the engine, so call it before [`Self::add_functionality`].
+    ///
+    /// Only the Wasmtime runtime supports fuel.
+    ///
+    /// [`ModFuelExhausted`]: crate::fuel::ModFuelExhausted
+    pub fn fuel_per_frame(mut self, fuel: u64) -> Self {
+        match self.inner() {
+            #[cfg(feature = "wasmtime")]
+            Inner::Wasmtime { engine, linker, .. } => {
+                *engine = engine.with_fuel(fuel);
+                *linker = create_linker(engine);
+            }
+            #[cfg(feature = "web")]
+            Inner::Web => {
+                let _ = fuel;
+                bevy_log::warn!("The web runtime does not support fuel")
+            }
+        }
+        self
+    }
+
+    /// Caches compiled mods on disk, so they load faster the next time the app starts.
+    ///
+    /// See [`crate::cache`] for how cached mods are invalidated. By default mods are compiled
+    /// every time they load.
+    ///
+    /// Only the Wasmtime runtime supports caching, since browsers compile mods themselves.
+    #[cfg(feature = "wasmtime")]
+    pub fn cache(mut self, cache: ModCache) -> Self {
+        match self.inner() {
+            Inner::Wasmtime { cache: current, .. } => *current = Some(cache),
+            #[cfg(feature = "web")]
+            Inner::Web => {
+                let _ = cache;
+                bevy_log::warn!("The web runtime does not support caching compiled mods")
+            }
+        }
         self
     }
 
@@ -51,15 +251,87 @@ impl WasmBackendPlugin {
 
 impl Plugin for WasmBackendPlugin {
     fn build(&self, app: &mut bevy_app::App) {
-        let Inner { engine, linker } = self
+        let inner = self
             .0
             .lock()
             .expect("WasmBackendPlugin is not locked")
             .take()
             .expect("WasmBackendPlugin is not built");
 
-        app.init_asset::<ModAsset>()
-            .register_asset_loader(ModAssetLoader::new(linker))
-            .insert_resource(engine);
+        init_mod_assets(app);
+        let runtime: Arc<dyn WasmRuntime> = match inner {
+            #[cfg(feature = "wasmtime")]
+            Inner::Wasmtime {
+                engine,
+                linker,
+                cache,
+            } => {
+                engine.start_epoch_ticker();
+                let mut runtime = WasmtimeRuntime::new(linker);
+                if let Some(cache) = cache {
+                    cache.startup();
+                    runtime = runtime.with_cache(cache);
+                }
+                app.insert_resource(engine)
+                    .init_resource::<ModExports>()
+                    .add_message::<ModFuelExhausted>()
+                    .add_systems(First, refill_fuel)
+                    .add_systems(Last, save_recording);
+                Arc::new(runtime)
+            }
+            #[cfg(feature = "web")]
+            Inner::Web => Arc::new(WebRuntime),
+        };
+
+        // Headless apps may have no asset server, and only load mods with ModAsset::from_bytes
+        if app.world().contains_resource::<AssetServer>() {
+            let mut loader = ModAssetLoader::from_runtime(runtime.clone());
+            if let Some(verifier) = app.world().get_resource::<ModVerifier>() {
+                loader.set_verifier(verifier.clone());
+            }
+            app.register_asset_loader(loader);
+        }
+        app.insert_resource(ModCompiler::new(move |bytes| runtime.compile(bytes)));
+    }
+}
+
+#[cfg(all(test, feature = "wasmtime"))]
+mod tests {
+    use bevy_app::{App, TaskPoolPlugin};
+    use bevy_ecs::system::SystemState;
+    use wasvy_runtime::{
+        asset::ModAsset,
+        mods::{Mod, ModSpawnError, Mods},
+        plugin::ModRuntimePlugin,
+    };
+
+    use super::*;
+    use crate::test_utils::{component_bytes, noop};
+
+    #[test]
+    fn loads_mods_from_bytes_without_asset_plugin() {
+        let mut app = App::new();
+        app.add_plugins((
+            TaskPoolPlugin::default(),
+            ModRuntimePlugin::default(),
+            WasmBackendPlugin::default(),
+        ));
+        assert!(!app.world().contains_resource::<AssetServer>());
+
+        let bytes = component_bytes(&[("setup", noop())]);
+        let mut state = SystemState::<Mods>::new(app.world_mut());
+        let mut mods = state.get_mut(app.world_mut()).unwrap();
+        let mod_id = mods.spawn_bytes("my-mod", bytes);
+        assert_eq!(
+            mods.try_spawn("mods/my-mod.wasm", None),
+            Err(ModSpawnError::NoAssetServer)
+        );
+        assert_eq!(mods.try_load_dir("mods"), Err(ModSpawnError::NoAssetServer));
+        let not_a_mod = mods.spawn("mods/my-mod.wasm", None);
+        state.apply(app.world_mut());
+        assert!(app.world().get::<Mod>(not_a_mod).is_none());
+
+        let asset = app.world().get::<Mod>(mod_id).unwrap().asset();
+        assert!(app.world().resource::<Assets<ModAsset>>().contains(&asset));
     }
 }
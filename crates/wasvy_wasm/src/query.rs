@@ -1,10 +1,17 @@
 use anyhow::{Result, anyhow, bail};
 use bevy_ecs::{
-    component::ComponentId, prelude::*, query::FilteredAccess, system::QueryParamBuilder,
-    world::FilteredEntityMut,
+    component::ComponentId,
+    prelude::*,
+    query::FilteredAccess,
+    system::{QueryParamBuilder, SystemChangeTick},
+    world::{FilteredEntityMut, FilteredEntityRef, FilteredResourcesMut},
 };
 use wasvy_runtime::{
-    component::{ComponentRef, get_component, get_component_id_for_filter, set_component},
+    component::{
+        ComponentRef, ResourceRef, TypeAliasRegistry, get_component, get_component_id_for_filter,
+        get_resource, set_component, set_resource,
+    },
+    permissions::{ComponentAccess, ComponentPermissions},
     serialize::CodecResource,
 };
 
@@ -18,24 +25,156 @@ pub(crate) type Queries<'w, 's> =
 
 /// A helper struct that stores a static lookup for queries.
 ///
+/// For both components and resources:
 /// - The first dimension is the ParamSet index (QueryId).
-/// - The second is the component index
-pub(crate) struct QueryResolver(Vec<Vec<QueryForComponent>>);
+/// - The second is the component (or resource) index
+///
+/// Change filters are only indexed by query, since they aren't accessible to mods.
+pub(crate) struct QueryResolver {
+    components: Vec<Vec<QueryForComponent>>,
+    resources: Vec<Vec<QueryForResource>>,
+    change_filters: Vec<Vec<ChangeFilter>>,
+}
 
 impl QueryResolver {
-    pub(crate) fn new(params: &[Param], world: &mut World) -> Result<Self> {
+    pub(crate) fn new(
+        params: &[Param],
+        permissions: &ComponentPermissions,
+        world: &mut World,
+    ) -> Result<Self> {
         let mut result = Vec::new();
+        let mut result_resources = Vec::new();
+        let mut result_change_filters = Vec::new();
         for component in params.iter().filter_map(|param| Param::filter_query(param)) {
             let mut components = Vec::new();
+            let mut resources = Vec::new();
+            let mut change_filters = Vec::new();
             for original in component {
-                if let Some(component) = QueryForComponent::new(original, world)? {
+                if let Some(component) = QueryForComponent::new(original, permissions, world)? {
                     components.push(component);
                 }
+                if let Some(resource) = QueryForResource::new(original, permissions, world)? {
+                    resources.push(resource);
+                }
+                if let Some(filter) = ChangeFilter::new(original, permissions, world)? {
+                    change_filters.push(filter);
+                }
             }
             result.push(components);
+            result_resources.push(resources);
+            result_change_filters.push(change_filters);
+        }
+
+        Ok(Self {
+            components: result,
+            resources: result_resources,
+            change_filters: result_change_filters,
+        })
+    }
+
+    /// Whether an entity passes the change filters of a query
+    pub(crate) fn matches(
+        &self,
+        id: QueryId,
+        entity: &FilteredEntityRef<'_, '_>,
+        change_tick: SystemChangeTick,
+    ) -> bool {
+        self.change_filters
+            .get(id.0)
+            .expect("Valid query index")
+            .iter()
+            .all(|filter| filter.matches(entity, change_tick))
+    }
+
+    /// Whether an entity matches a query, including its change filters
+    pub(crate) fn contains(
+        &self,
+        id: QueryId,
+        entity: Entity,
+        queries: &mut Queries<'_, '_>,
+        change_tick: SystemChangeTick,
+    ) -> bool {
+        queries
+            .get_mut(id.0)
+            .get(entity)
+            .is_ok_and(|entity| self.matches(id, &entity, change_tick))
+    }
+
+    /// The query reading the names of entities, which comes after those of the mod, see
+    /// [names_query_builder]. Only systems taking commands have it.
+    pub(crate) fn names(&self) -> QueryId {
+        QueryId(self.components.len())
+    }
+
+    /// Finds an entity within the mod's access by its [Name]
+    pub(crate) fn find_by_name(&self, name: &str, queries: &mut Queries<'_, '_>) -> Option<Entity> {
+        queries
+            .get_mut(self.names().0)
+            .iter()
+            .find(|entity| {
+                entity
+                    .get::<Name>()
+                    .is_some_and(|other| other.as_str() == name)
+            })
+            .map(|entity| entity.id())
+    }
+
+    /// Every resource declared by the queries, and whether it is mutable
+    pub(crate) fn resource_access(&self) -> impl Iterator<Item = (ComponentId, bool)> {
+        self.resources
+            .iter()
+            .flatten()
+            .map(|resource| (resource.resource.component_id(), resource.mutable))
+    }
+
+    pub(crate) fn get_resource(
+        &self,
+        id: QueryId,
+        index: ComponentIndex,
+        resources: &FilteredResourcesMut<'_, '_>,
+        type_registry: &AppTypeRegistry,
+        codec: &CodecResource,
+    ) -> Result<Vec<u8>> {
+        let query_for = self.query_for_resource(id, index)?;
+
+        get_resource(
+            &resources.as_readonly(),
+            &query_for.resource,
+            type_registry,
+            codec,
+        )
+    }
+
+    pub(crate) fn set_resource(
+        &self,
+        id: QueryId,
+        index: ComponentIndex,
+        serialized_value: Vec<u8>,
+        resources: &mut FilteredResourcesMut<'_, '_>,
+        type_registry: &AppTypeRegistry,
+        codec: &CodecResource,
+    ) -> Result<()> {
+        let query_for = self.query_for_resource(id, index)?;
+        if !query_for.mutable {
+            bail!("Resource is not mutable!")
         }
 
-        Ok(Self(result))
+        set_resource(
+            resources,
+            &query_for.resource,
+            serialized_value,
+            type_registry,
+            codec,
+        )
+    }
+
+    fn query_for_resource(&self, id: QueryId, index: ComponentIndex) -> Result<&QueryForResource> {
+        let id = id.0;
+        self.resources
+            .get(id)
+            .expect("Valid query index")
+            .get(index as usize)
+            .ok_or_else(|| anyhow!("Query index {id} does not have resource index {index}"))
     }
 
     pub(crate) fn get(
@@ -82,13 +221,69 @@ impl QueryResolver {
         )
     }
 
+    /// Reads every component of every entity matching a query, see `query.fetch-all`
+    pub(crate) fn fetch_all(
+        &self,
+        id: QueryId,
+        queries: &mut Queries<'_, '_>,
+        change_tick: SystemChangeTick,
+        type_registry: &AppTypeRegistry,
+        codec: &CodecResource,
+    ) -> Result<Vec<(Entity, Vec<Vec<u8>>)>> {
+        let components = self.components.get(id.0).expect("Valid query index");
+
+        let query = queries.get_mut(id.0);
+        query
+            .iter()
+            .filter(|entity| self.matches(id, entity, change_tick))
+            .map(|entity| {
+                let values = components
+                    .iter()
+                    .map(|query_for| {
+                        get_component(&entity, &query_for.component, type_registry, codec)
+                    })
+                    .collect::<Result<_>>()?;
+                Ok((entity.id(), values))
+            })
+            .collect()
+    }
+
+    /// Sets the mutable components of many entities of a query, see `query.write-all`
+    pub(crate) fn write_all(
+        &self,
+        id: QueryId,
+        rows: Vec<(Entity, Vec<Vec<u8>>)>,
+        queries: &mut Queries<'_, '_>,
+        type_registry: &AppTypeRegistry,
+        codec: &CodecResource,
+    ) -> Result<()> {
+        let components = self.components.get(id.0).expect("Valid query index");
+
+        let mut query = queries.get_mut(id.0);
+        for (entity, values) in rows {
+            let mut entity = query.get_mut(entity)?;
+            for (query_for, value) in components.iter().zip(values) {
+                if query_for.mutable {
+                    set_component(
+                        &mut entity,
+                        &query_for.component,
+                        value,
+                        type_registry,
+                        codec,
+                    )?;
+                }
+            }
+        }
+        Ok(())
+    }
+
     pub(crate) fn query_for(
         &self,
         id: QueryId,
         index: ComponentIndex,
     ) -> Result<&QueryForComponent> {
         let id = id.0;
-        self.0
+        self.components
             .get(id)
             .expect("Valid query index")
             .get(index as usize)
@@ -115,28 +310,96 @@ impl QueryId {
     pub(crate) fn index(self) -> usize {
         self.0
     }
+
+    /// Identifies the query in the params of a recorded host call, see [record](crate::record)
+    pub(crate) fn to_le_bytes(self) -> [u8; 8] {
+        (self.0 as u64).to_le_bytes()
+    }
 }
 
 /// A cursor so we can resume iterating the query from the last position.
-#[derive(Default, Clone, Copy)]
-pub(crate) struct QueryCursor(usize);
+///
+/// The matching entities are collected on first use, and kept until the query resource is dropped
+/// at the end of the run (see `Runner::reset`). This makes iterating linear in the number of
+/// results. Entities can't be spawned or despawned while a system runs, since commands are applied
+/// afterwards, so the snapshot stays valid.
+#[derive(Default)]
+pub(crate) struct QueryCursor {
+    entities: Option<Vec<Entity>>,
+    position: usize,
+}
 
 impl QueryCursor {
-    /// Increments the cursor, returning the old one
-    pub(crate) fn increment(&mut self) -> Self {
-        let original = *self;
-        self.0 += 1;
-        original
+    /// Retrieves the entity at the cursor, moving the cursor past it
+    pub(crate) fn next(
+        &mut self,
+        queries: &mut Queries<'_, '_>,
+        id: QueryId,
+        query_resolver: &QueryResolver,
+        change_tick: SystemChangeTick,
+    ) -> Option<Entity> {
+        self.next_batch(1, queries, id, query_resolver, change_tick)
+            .first()
+            .copied()
     }
 
-    /// Retrieves the entity at the cursor
-    pub(crate) fn entity(&self, queries: &mut Queries<'_, '_>, id: QueryId) -> Option<Entity> {
-        let query = queries.get_mut(id.0);
+    /// Retrieves up to `max` entities from the cursor on, moving the cursor past them
+    pub(crate) fn next_batch(
+        &mut self,
+        max: usize,
+        queries: &mut Queries<'_, '_>,
+        id: QueryId,
+        query_resolver: &QueryResolver,
+        change_tick: SystemChangeTick,
+    ) -> &[Entity] {
+        let entities = self.entities.get_or_insert_with(|| {
+            queries
+                .get_mut(id.0)
+                .iter()
+                .filter(|entity| query_resolver.matches(id, entity, change_tick))
+                .map(|entity| entity.id())
+                .collect()
+        });
 
-        // This is not the most efficient. Ideally we wouldn't need to walk
-        // to the nth iter each time, but this allows to avoid unsafe.
-        // TODO: Store an actual proper cursor.
-        query.iter().nth(self.0).map(|a| a.id())
+        let start = self.position.min(entities.len());
+        let end = start.saturating_add(max).min(entities.len());
+        self.position = end;
+        &entities[start..end]
+    }
+
+    /// The number of entities matching the query, regardless of the cursor
+    pub(crate) fn count(
+        &self,
+        queries: &mut Queries<'_, '_>,
+        id: QueryId,
+        query_resolver: &QueryResolver,
+        change_tick: SystemChangeTick,
+    ) -> usize {
+        if let Some(entities) = &self.entities {
+            return entities.len();
+        }
+        queries
+            .get_mut(id.0)
+            .iter()
+            .filter(|entity| query_resolver.matches(id, entity, change_tick))
+            .count()
+    }
+
+    /// Whether no entities match the query, regardless of the cursor
+    pub(crate) fn is_empty(
+        &self,
+        queries: &mut Queries<'_, '_>,
+        id: QueryId,
+        query_resolver: &QueryResolver,
+        change_tick: SystemChangeTick,
+    ) -> bool {
+        if let Some(entities) = &self.entities {
+            return entities.is_empty();
+        }
+        !queries
+            .get_mut(id.0)
+            .iter()
+            .any(|entity| query_resolver.matches(id, &entity, change_tick))
     }
 }
 
@@ -150,18 +413,36 @@ pub(crate) struct QueryForComponent {
 }
 
 impl QueryForComponent {
-    fn new(original: &QueryFor, world: &mut World) -> Result<Option<Self>> {
+    fn new(
+        original: &QueryFor,
+        permissions: &ComponentPermissions,
+        world: &mut World,
+    ) -> Result<Option<Self>> {
         Ok(match original {
             QueryFor::Ref(type_path) => Some(Self {
-                component: ComponentRef::new(type_path, world)?,
+                component: ComponentRef::new(
+                    type_path,
+                    ComponentAccess::Query,
+                    permissions,
+                    world,
+                )?,
                 mutable: false,
             }),
             QueryFor::Mut(type_path) => Some(Self {
-                component: ComponentRef::new(type_path, world)?,
+                component: ComponentRef::new(
+                    type_path,
+                    ComponentAccess::Mutate,
+                    permissions,
+                    world,
+                )?,
                 mutable: true,
             }),
             QueryFor::With(_) => None,
             QueryFor::Without(_) => None,
+            QueryFor::Changed(_) => None,
+            QueryFor::Added(_) => None,
+            QueryFor::Resource(_) => None,
+            QueryFor::ResourceMut(_) => None,
         })
     }
 
@@ -174,8 +455,93 @@ impl QueryForComponent {
     }
 }
 
+/// Needed at runtime to access the resources declared by a query
+#[derive(Clone)]
+struct QueryForResource {
+    resource: ResourceRef,
+    mutable: bool,
+}
+
+impl QueryForResource {
+    fn new(
+        original: &QueryFor,
+        permissions: &ComponentPermissions,
+        world: &mut World,
+    ) -> Result<Option<Self>> {
+        Ok(match original {
+            QueryFor::Resource(type_path) => Some(Self {
+                resource: resource_ref(type_path, false, permissions, world)?,
+                mutable: false,
+            }),
+            QueryFor::ResourceMut(type_path) => Some(Self {
+                resource: resource_ref(type_path, true, permissions, world)?,
+                mutable: true,
+            }),
+            _ => None,
+        })
+    }
+}
+
+/// Looks up a resource a mod asked for, if its permissions allow accessing it.
+///
+/// Like components, mutable access requires query access too.
+pub(crate) fn resource_ref(
+    type_path: &str,
+    mutable: bool,
+    permissions: &ComponentPermissions,
+    world: &mut World,
+) -> Result<ResourceRef> {
+    let resolved = TypeAliasRegistry::resolve_in(world, type_path);
+    permissions.check(ComponentAccess::Query, &resolved)?;
+    if mutable {
+        permissions.check(ComponentAccess::Mutate, &resolved)?;
+    }
+    ResourceRef::new(type_path, world)
+}
+
+/// Bevy's [QueryBuilder] can't filter by change ticks dynamically, so these are checked while
+/// iterating instead. The query reads the component, so its ticks are accessible.
+#[derive(Clone, Copy)]
+enum ChangeFilter {
+    Changed(ComponentId),
+    Added(ComponentId),
+}
+
+impl ChangeFilter {
+    fn new(
+        original: &QueryFor,
+        permissions: &ComponentPermissions,
+        world: &mut World,
+    ) -> Result<Option<Self>> {
+        Ok(match original {
+            QueryFor::Changed(type_path) => Some(Self::Changed(
+                ComponentRef::new(type_path, ComponentAccess::Query, permissions, world)?
+                    .component_id(),
+            )),
+            QueryFor::Added(type_path) => Some(Self::Added(
+                ComponentRef::new(type_path, ComponentAccess::Query, permissions, world)?
+                    .component_id(),
+            )),
+            _ => None,
+        })
+    }
+
+    fn matches(&self, entity: &FilteredEntityRef<'_, '_>, change_tick: SystemChangeTick) -> bool {
+        let (last_run, this_run) = (change_tick.last_run(), change_tick.this_run());
+        match *self {
+            Self::Changed(component_id) => entity
+                .get_change_ticks_by_id(component_id)
+                .is_some_and(|ticks| ticks.is_changed(last_run, this_run)),
+            Self::Added(component_id) => entity
+                .get_change_ticks_by_id(component_id)
+                .is_some_and(|ticks| ticks.is_added(last_run, this_run)),
+        }
+    }
+}
+
 pub(crate) fn create_query_builder(
     original_items: &[QueryFor],
+    permissions: &ComponentPermissions,
     world: &mut World,
     access: FilteredAccess,
 ) -> Result<
@@ -183,7 +549,7 @@ pub(crate) fn create_query_builder(
 > {
     let mut items = Vec::with_capacity(original_items.len());
     for original in original_items {
-        if let Some(item) = QueryForId::new(original, world)? {
+        if let Some(item) = QueryForId::new(original, permissions, world)? {
             items.push(item);
         }
     }
@@ -192,7 +558,8 @@ pub(crate) fn create_query_builder(
         builder.extend_access(access);
         for item in items {
             match item {
-                QueryForId::Ref(component_id) => {
+                // Change filters need to read the component's ticks
+                QueryForId::Ref(component_id) | QueryForId::ChangeFilter(component_id) => {
                     builder.ref_id(component_id);
                 }
                 QueryForId::Mut(component_id) => {
@@ -209,29 +576,137 @@ pub(crate) fn create_query_builder(
     }))
 }
 
+/// Reads the [Name] of every entity within the mod's access, for `commands.find-by-name`.
+///
+/// It's part of the ParamSet of queries, after those of the mod, so it doesn't conflict with mod
+/// queries that change names.
+pub(crate) fn names_query_builder(
+    world: &mut World,
+    access: FilteredAccess,
+) -> QueryParamBuilder<Box<dyn FnOnce(&mut QueryBuilder<FilteredEntityMut<'static, 'static>>)>> {
+    let name = world.register_component::<Name>();
+    QueryParamBuilder::new_box(move |builder| {
+        builder.extend_access(access);
+        builder.ref_id(name);
+    })
+}
+
 enum QueryForId {
     Ref(ComponentId),
     Mut(ComponentId),
     With(ComponentId),
     Without(ComponentId),
+    ChangeFilter(ComponentId),
 }
 
 impl QueryForId {
-    fn new(original: &QueryFor, world: &mut World) -> Result<Option<Self>> {
+    fn new(
+        original: &QueryFor,
+        permissions: &ComponentPermissions,
+        world: &mut World,
+    ) -> Result<Option<Self>> {
+        // Mutable access requires query access too, which QueryForComponent doesn't check
+        let mut query = |type_path| {
+            ComponentRef::new(type_path, ComponentAccess::Query, permissions, world)
+                .map(|component| component.component_id())
+        };
         Ok(match original {
-            QueryFor::Ref(type_path) => Some(Self::Ref(
-                ComponentRef::new(type_path, world)?.component_id(),
-            )),
-            QueryFor::Mut(type_path) => Some(Self::Mut(
-                ComponentRef::new(type_path, world)?.component_id(),
-            )),
-            QueryFor::With(type_path) => Some(Self::With(
-                get_component_id_for_filter(type_path, world)
-                    .ok_or_else(|| anyhow!("{type_path} is not a component"))?,
-            )),
+            QueryFor::Ref(type_path) => Some(Self::Ref(query(type_path)?)),
+            QueryFor::Mut(type_path) => Some(Self::Mut(query(type_path)?)),
+            QueryFor::With(type_path) => {
+                permissions.check(
+                    ComponentAccess::Query,
+                    &TypeAliasRegistry::resolve_in(world, type_path),
+                )?;
+                Some(Self::With(
+                    get_component_id_for_filter(type_path, world)
+                        .ok_or_else(|| anyhow!("{type_path} is not a component"))?,
+                ))
+            }
             QueryFor::Without(type_path) => {
+                permissions.check(
+                    ComponentAccess::Query,
+                    &TypeAliasRegistry::resolve_in(world, type_path),
+                )?;
                 get_component_id_for_filter(type_path, world).map(Self::Without)
             }
+            QueryFor::Changed(type_path) | QueryFor::Added(type_path) => {
+                Some(Self::ChangeFilter(query(type_path)?))
+            }
+            // Resources are accessed through FilteredResourcesMut instead
+            QueryFor::Resource(_) | QueryFor::ResourceMut(_) => None,
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use bevy_ecs::reflect::{AppTypeRegistry, ReflectComponent, ReflectResource};
+    use bevy_reflect::{Reflect, TypePath as _};
+    use wasvy_runtime::component::WasmComponentRegistry;
+
+    use super::*;
+    use crate::test_utils::world;
+
+    #[derive(Component, Reflect)]
+    #[reflect(Component)]
+    struct Health(u32);
+
+    #[derive(Resource, Reflect, Default)]
+    #[reflect(Resource)]
+    struct Score(u32);
+
+    /// A world where `Health` and `Score` are aliases of the types above
+    fn world_with_aliases() -> World {
+        let mut world = world();
+        world.register_component::<Health>();
+        world.init_resource::<Score>();
+        let mut type_registry = world.resource::<AppTypeRegistry>().write();
+        type_registry.register::<Health>();
+        type_registry.register::<Score>();
+        drop(type_registry);
+        let mut aliases = world.resource_mut::<TypeAliasRegistry>();
+        aliases.insert("Health", Health::type_path());
+        aliases.insert("Score", Score::type_path());
+        world
+    }
+
+    #[test]
+    fn filters_resolve_aliases() {
+        let mut world = world_with_aliases();
+        let health = world.component_id::<Health>().unwrap();
+        let permissions = ComponentPermissions::default();
+
+        let with = QueryForId::new(&QueryFor::With("Health".into()), &permissions, &mut world);
+        assert!(matches!(with, Ok(Some(QueryForId::With(id))) if id == health));
+        let without = QueryForId::new(
+            &QueryFor::Without("Health".into()),
+            &permissions,
+            &mut world,
+        );
+        assert!(matches!(without, Ok(Some(QueryForId::Without(id))) if id == health));
+        assert!(
+            !world
+                .resource::<WasmComponentRegistry>()
+                .contains_key("Health")
+        );
+
+        let denied =
+            ComponentPermissions::default().deny(ComponentAccess::Query, [Health::type_path()]);
+        assert!(QueryForId::new(&QueryFor::With("Health".into()), &denied, &mut world).is_err());
+        assert!(QueryForId::new(&QueryFor::Without("Health".into()), &denied, &mut world).is_err());
+    }
+
+    #[test]
+    fn resources_resolve_aliases() {
+        let mut world = world_with_aliases();
+
+        let resource = resource_ref("Score", true, &Default::default(), &mut world).unwrap();
+        assert_eq!(resource.type_path(), Score::type_path());
+
+        let denied =
+            ComponentPermissions::default().deny(ComponentAccess::Mutate, [Score::type_path()]);
+        assert!(resource_ref("Score", false, &denied, &mut world).is_ok());
+        assert!(resource_ref("Score", true, &denied, &mut world).is_err());
+    }
+}
@@ -0,0 +1,867 @@
+//! Record and replay of the calls mods make to the host.
+//!
+//! When a user reports a bug in a mod, the interaction between the mod and the host is usually
+//! hard to reproduce. Inserting a [`ModRecorder`] logs every host call mod systems make (with the
+//! serialized params and the world's response, or the error the call failed with) to a compact
+//! file, which is written to as the app runs.
+//!
+//! That file can then be replayed headlessly by inserting a [`ModReplay`] into a fresh app running
+//! the same mods. Reads are answered from the trace instead of the world, and every call the mod
+//! makes is checked against the trace so that the first divergence is reported as an error.
+//!
+//! ```no_run
+//! # use bevy_app::prelude::*;
+//! use wasvy_wasm::record::{ModRecorder, ModReplay, Recording};
+//!
+//! // In the user's build
+//! # let mut app = App::new();
+//! app.insert_resource(ModRecorder::new("bug-report.wasvyrec"));
+//!
+//! // In the maintainer's headless repro
+//! # let mut app = App::new();
+//! let recording = Recording::load("bug-report.wasvyrec").unwrap();
+//! app.insert_resource(ModReplay::new(recording));
+//! ```
+
+use std::{
+    collections::{HashMap, VecDeque},
+    fs::File,
+    io::{BufReader, BufWriter, ErrorKind, Read, Write},
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+};
+
+use anyhow::{Context, Result, anyhow, bail};
+use bevy_app::AppExit;
+use bevy_ecs::prelude::*;
+use bevy_log::prelude::*;
+use wasvy_runtime::access::ModAccess;
+
+const MAGIC: &[u8; 8] = b"WASVYREC";
+const VERSION: u32 = 2;
+
+/// A single call a mod system made to the host.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HostCall {
+    /// The name of the mod that made the call
+    pub mod_name: String,
+    /// The name of the mod system that made the call
+    pub system: String,
+    /// The access the system ran in. Sandboxes are identified by their entity, so a replay must
+    /// spawn them in the same order as the recorded app.
+    pub access: ModAccess,
+    /// The host function that was called, such as `component.get`
+    pub call: String,
+    /// The params of the call, serialized
+    pub params: Vec<u8>,
+    /// What the host responded with, serialized, or the error the call failed with
+    pub response: Result<Vec<u8>, String>,
+}
+
+/// An ordered trace of [`HostCall`]s.
+///
+/// The file format is a short header followed by length-prefixed fields for each call, so
+/// recordings stay compact even for mods that make many calls every frame, and calls can be
+/// appended as they are made. A call cut short, such as by a crash, ends the recording.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Recording(pub Vec<HostCall>);
+
+impl Recording {
+    /// Reads a recording from a file
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let file = File::open(path).with_context(|| format!("Opening recording {path:?}"))?;
+        Self::read_from(BufReader::new(file))
+    }
+
+    /// Writes the recording to a file
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        let file = File::create(path).with_context(|| format!("Creating recording {path:?}"))?;
+        let mut writer = BufWriter::new(file);
+        self.write_to(&mut writer)?;
+        writer.flush()?;
+        Ok(())
+    }
+
+    pub fn read_from(mut reader: impl Read) -> Result<Self> {
+        let mut magic = [0; 8];
+        reader.read_exact(&mut magic)?;
+        if &magic != MAGIC {
+            bail!("Not a wasvy recording");
+        }
+        let version = read_u32(&mut reader)?;
+        if version != VERSION {
+            bail!("Unsupported recording version {version}");
+        }
+
+        let mut calls = Vec::new();
+        loop {
+            match read_call(&mut reader) {
+                Ok(Some(call)) => calls.push(call),
+                Ok(None) => break,
+                Err(err)
+                    if err
+                        .downcast_ref::<std::io::Error>()
+                        .is_some_and(|err| err.kind() == ErrorKind::UnexpectedEof) =>
+                {
+                    warn!("The recording ends with a call cut short, ignoring it");
+                    break;
+                }
+                Err(err) => return Err(err),
+            }
+        }
+
+        Ok(Self(calls))
+    }
+
+    pub fn write_to(&self, mut writer: impl Write) -> Result<()> {
+        write_header(&mut writer)?;
+        for call in self.0.iter() {
+            write_call(&mut writer, call)?;
+        }
+        Ok(())
+    }
+}
+
+/// Insert this resource to record every call mod systems make to the host.
+///
+/// The calls are appended to the file at the end of every frame, and when the app panics, so a
+/// crash loses at most the calls of the frame it happened in. Only the calls of the current frame
+/// are kept in memory. See the [module docs](self).
+#[derive(Resource, Clone)]
+pub struct ModRecorder(Arc<Mutex<RecorderState>>);
+
+struct RecorderState {
+    path: PathBuf,
+
+    /// The calls recorded since the last save
+    unsaved: Vec<HostCall>,
+
+    /// The file the calls are appended to, created by the first save
+    file: Option<BufWriter<File>>,
+}
+
+impl ModRecorder {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self(Arc::new(Mutex::new(RecorderState {
+            path: path.into(),
+            unsaved: Vec::new(),
+            file: None,
+        })))
+    }
+
+    /// Returns everything recorded so far, reading the calls that were already saved back from
+    /// the file
+    pub fn recording(&self) -> Result<Recording> {
+        let mut state = self.state();
+        let state = &mut *state;
+        let mut recording = match &mut state.file {
+            Some(file) => {
+                file.flush()?;
+                Recording::load(&state.path)?
+            }
+            None => Recording::default(),
+        };
+        recording.0.extend(state.unsaved.iter().cloned());
+        Ok(recording)
+    }
+
+    /// Writes the calls recorded since the last save to disk
+    pub fn save(&self) -> Result<()> {
+        self.state().save()
+    }
+
+    fn push(&self, call: HostCall) {
+        self.state().unsaved.push(call);
+    }
+
+    fn state(&self) -> std::sync::MutexGuard<'_, RecorderState> {
+        // Recording a panicking app is the point, so a poisoned lock is still used
+        self.0.lock().unwrap_or_else(|err| err.into_inner())
+    }
+}
+
+impl RecorderState {
+    fn save(&mut self) -> Result<()> {
+        let file = match &mut self.file {
+            Some(file) => file,
+            None => {
+                let path = &self.path;
+                let file =
+                    File::create(path).with_context(|| format!("Creating recording {path:?}"))?;
+                let mut file = BufWriter::new(file);
+                write_header(&mut file)?;
+                self.file.insert(file)
+            }
+        };
+        for call in &self.unsaved {
+            write_call(&mut *file, call)?;
+        }
+        file.flush()?;
+        self.unsaved.clear();
+        Ok(())
+    }
+}
+
+impl Drop for RecorderState {
+    fn drop(&mut self) {
+        // The app is going down, likely because of the crash being recorded
+        if std::thread::panicking()
+            && let Err(err) = self.save()
+        {
+            error!("Failed to save mod recording: {err:?}");
+        }
+    }
+}
+
+/// Insert this resource to replay a [`Recording`] against a fresh world.
+///
+/// See the [module docs](self).
+#[derive(Resource, Clone)]
+pub struct ModReplay(Arc<Mutex<ReplayState>>);
+
+/// Identifies the calls of a mod system running in an access
+type ReplayKey = (String, String, ModAccess);
+
+struct ReplayState {
+    /// Mod systems run in parallel, so each system replays its own calls in order, along with
+    /// their index in the recording
+    calls: HashMap<ReplayKey, VecDeque<(usize, HostCall)>>,
+}
+
+impl ModReplay {
+    pub fn new(recording: Recording) -> Self {
+        let mut calls = HashMap::<_, VecDeque<_>>::new();
+        for (index, call) in recording.0.into_iter().enumerate() {
+            let key = (call.mod_name.clone(), call.system.clone(), call.access);
+            calls.entry(key).or_default().push_back((index, call));
+        }
+
+        Self(Arc::new(Mutex::new(ReplayState { calls })))
+    }
+
+    /// Returns true once every recorded call was replayed
+    pub fn is_finished(&self) -> bool {
+        self.state().calls.values().all(VecDeque::is_empty)
+    }
+
+    /// Takes the next call this system made in the recording, checking it matches what the mod is calling now
+    fn next(
+        &self,
+        (mod_name, system, access): &ReplayKey,
+        call: &str,
+        params: &[u8],
+    ) -> Result<Result<Vec<u8>, String>> {
+        let mut state = self.state();
+        let calls = state
+            .calls
+            .get_mut(&(mod_name.clone(), system.clone(), *access));
+        let Some((index, recorded)) = calls.and_then(VecDeque::pop_front) else {
+            bail!(
+                "Replay diverged: \"{mod_name}\" system \"{system}\" called {call} but the recording has no more calls"
+            );
+        };
+
+        if recorded.call != call || recorded.params != params {
+            bail!(
+                "Replay diverged at call #{index}: \"{mod_name}\" system \"{system}\" called {call} but the recording expected {}",
+                recorded.call
+            );
+        }
+
+        Ok(recorded.response)
+    }
+
+    fn state(&self) -> std::sync::MutexGuard<'_, ReplayState> {
+        // Replaying the crash of a recorded app may panic too, so a poisoned lock is still used
+        self.0.lock().unwrap_or_else(|err| err.into_inner())
+    }
+}
+
+/// Whether a host call reads from the world or writes to it.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Boundary {
+    Read,
+    Write,
+}
+
+/// Records or replays the host calls of a single mod system.
+pub(crate) struct Tape {
+    mode: TapeMode,
+    key: ReplayKey,
+}
+
+enum TapeMode {
+    Record(ModRecorder),
+    Replay(ModReplay),
+}
+
+impl Tape {
+    pub(crate) fn new(
+        recorder: Option<&ModRecorder>,
+        replay: Option<&ModReplay>,
+        mod_name: &str,
+        system: &str,
+        access: ModAccess,
+    ) -> Option<Arc<Self>> {
+        // Replaying takes priority, there's no use in recording a replay
+        let mode = match (recorder, replay) {
+            (_, Some(replay)) => TapeMode::Replay(replay.clone()),
+            (Some(recorder), None) => TapeMode::Record(recorder.clone()),
+            (None, None) => return None,
+        };
+
+        Some(Arc::new(Self {
+            mode,
+            key: (mod_name.to_string(), system.to_string(), access),
+        }))
+    }
+
+    /// Passes a host call through the tape.
+    ///
+    /// When replaying, reads are answered from the recording without touching the world. Calls
+    /// that failed fail again with the recorded error, without calling the host.
+    pub(crate) fn call<T, F>(
+        &self,
+        call: &str,
+        boundary: Boundary,
+        params: &[u8],
+        f: F,
+    ) -> Result<T, wasmtime::Error>
+    where
+        T: CallResponse,
+        F: FnOnce() -> Result<T, wasmtime::Error>,
+    {
+        match &self.mode {
+            TapeMode::Record(recorder) => {
+                let response = f();
+                let (mod_name, system, access) = &self.key;
+                recorder.push(HostCall {
+                    mod_name: mod_name.clone(),
+                    system: system.clone(),
+                    access: *access,
+                    call: call.to_string(),
+                    params: params.to_vec(),
+                    response: match &response {
+                        Ok(response) => Ok(response.encode()),
+                        Err(err) => Err(format!("{err:#}")),
+                    },
+                });
+                response
+            }
+            TapeMode::Replay(replay) => {
+                let response = replay
+                    .next(&self.key, call, params)
+                    .map_err(wasmtime::Error::msg)?
+                    .map_err(wasmtime::Error::msg)?;
+                if boundary == Boundary::Write {
+                    f()?;
+                }
+                T::decode(response).map_err(wasmtime::Error::msg)
+            }
+        }
+    }
+}
+
+/// The response of a host call, which is only encoded when the call is recorded or replayed
+pub(crate) trait CallResponse: Sized {
+    fn encode(&self) -> Vec<u8>;
+
+    fn decode(bytes: Vec<u8>) -> Result<Self>;
+}
+
+impl CallResponse for () {
+    fn encode(&self) -> Vec<u8> {
+        Vec::new()
+    }
+
+    fn decode(_: Vec<u8>) -> Result<Self> {
+        Ok(())
+    }
+}
+
+impl CallResponse for Vec<u8> {
+    fn encode(&self) -> Vec<u8> {
+        self.clone()
+    }
+
+    fn decode(bytes: Vec<u8>) -> Result<Self> {
+        Ok(bytes)
+    }
+}
+
+impl CallResponse for bool {
+    fn encode(&self) -> Vec<u8> {
+        vec![*self as u8]
+    }
+
+    fn decode(bytes: Vec<u8>) -> Result<Self> {
+        Ok(bytes == [1])
+    }
+}
+
+impl CallResponse for u64 {
+    fn encode(&self) -> Vec<u8> {
+        self.to_le_bytes().to_vec()
+    }
+
+    fn decode(bytes: Vec<u8>) -> Result<Self> {
+        let bytes = bytes
+            .try_into()
+            .map_err(|_| anyhow!("Invalid recorded number"))?;
+        Ok(u64::from_le_bytes(bytes))
+    }
+}
+
+/// Encoded as zero fields when missing, or a single field when present
+impl CallResponse for Option<Vec<u8>> {
+    fn encode(&self) -> Vec<u8> {
+        encode_fields(self.as_deref())
+    }
+
+    fn decode(bytes: Vec<u8>) -> Result<Self> {
+        Ok(decode_fields(&bytes)?.pop())
+    }
+}
+
+impl CallResponse for Vec<Vec<u8>> {
+    fn encode(&self) -> Vec<u8> {
+        encode_fields(self.iter().map(Vec::as_slice))
+    }
+
+    fn decode(bytes: Vec<u8>) -> Result<Self> {
+        decode_fields(&bytes)
+    }
+}
+
+/// Encoded as the output and an empty field on success, or the error alone
+impl CallResponse for Result<Vec<u8>, String> {
+    fn encode(&self) -> Vec<u8> {
+        match self {
+            Ok(output) => encode_fields([&output[..], &[]]),
+            Err(err) => encode_fields([err.as_bytes()]),
+        }
+    }
+
+    fn decode(bytes: Vec<u8>) -> Result<Self> {
+        let mut fields = decode_fields(&bytes)?.into_iter();
+        let is_ok = fields.len() == 2;
+        let output = fields.next().unwrap_or_default();
+        Ok(if is_ok {
+            Ok(output)
+        } else {
+            Err(String::from_utf8(output)?)
+        })
+    }
+}
+
+impl CallResponse for Vec<Entity> {
+    fn encode(&self) -> Vec<u8> {
+        encode_entities(self.iter().copied())
+    }
+
+    fn decode(bytes: Vec<u8>) -> Result<Self> {
+        decode_entities(&bytes)
+    }
+}
+
+impl CallResponse for Option<Entity> {
+    fn encode(&self) -> Vec<u8> {
+        encode_entities(*self)
+    }
+
+    fn decode(bytes: Vec<u8>) -> Result<Self> {
+        Ok(decode_entities(&bytes)?.pop())
+    }
+}
+
+/// Encodes a list of fields into the params of a [`HostCall`]
+pub(crate) fn encode_fields<'a>(fields: impl IntoIterator<Item = &'a [u8]>) -> Vec<u8> {
+    let mut buffer = Vec::new();
+    for field in fields {
+        write_bytes(&mut buffer, field).expect("writing to a vec can't fail");
+    }
+    buffer
+}
+
+/// Decodes fields encoded with [`encode_fields`]
+pub(crate) fn decode_fields(mut bytes: &[u8]) -> Result<Vec<Vec<u8>>> {
+    let mut fields = Vec::new();
+    while !bytes.is_empty() {
+        fields.push(read_bytes(&mut bytes)?);
+    }
+    Ok(fields)
+}
+
+/// Encodes a bundle (or bundle types when values are empty) into the params of a [`HostCall`]
+pub(crate) fn encode_bundle<'a>(bundle: impl IntoIterator<Item = (&'a str, &'a [u8])>) -> Vec<u8> {
+    encode_fields(
+        bundle
+            .into_iter()
+            .flat_map(|(type_path, value)| [type_path.as_bytes(), value]),
+    )
+}
+
+/// Encodes entities into the params or response of a [`HostCall`]
+pub(crate) fn encode_entities(entities: impl IntoIterator<Item = Entity>) -> Vec<u8> {
+    entities
+        .into_iter()
+        .flat_map(|entity| entity.to_bits().to_le_bytes())
+        .collect()
+}
+
+/// Decodes entities encoded with [`encode_entities`]
+pub(crate) fn decode_entities(bytes: &[u8]) -> Result<Vec<Entity>> {
+    let invalid = || anyhow!("Invalid recorded entities");
+    if !bytes.len().is_multiple_of(8) {
+        return Err(invalid());
+    }
+    bytes
+        .chunks_exact(8)
+        .map(|bits| {
+            let bits = u64::from_le_bytes(bits.try_into().map_err(|_| invalid())?);
+            Entity::try_from_bits(bits).ok_or_else(invalid)
+        })
+        .collect()
+}
+
+/// Saves the calls the [`ModRecorder`] recorded during the frame
+pub(crate) fn save_recording(mut exit: MessageReader<AppExit>, recorder: Option<Res<ModRecorder>>) {
+    let exiting = exit.read().count() > 0;
+    let Some(recorder) = recorder else {
+        return;
+    };
+
+    match recorder.save() {
+        Ok(()) if exiting => info!("Saved mod recording to {:?}", recorder.state().path),
+        Ok(()) => {}
+        Err(err) => error!("Failed to save mod recording: {err:?}"),
+    }
+}
+
+fn write_header(writer: &mut impl Write) -> Result<()> {
+    writer.write_all(MAGIC)?;
+    writer.write_all(&VERSION.to_le_bytes())?;
+    Ok(())
+}
+
+fn write_call(writer: &mut impl Write, call: &HostCall) -> Result<()> {
+    write_bytes(writer, call.mod_name.as_bytes())?;
+    write_bytes(writer, call.system.as_bytes())?;
+    match call.access {
+        ModAccess::World => writer.write_all(&[0])?,
+        ModAccess::Sandbox(sandbox) => {
+            writer.write_all(&[1])?;
+            writer.write_all(&sandbox.to_bits().to_le_bytes())?;
+        }
+    }
+    write_bytes(writer, call.call.as_bytes())?;
+    write_bytes(writer, &call.params)?;
+    match &call.response {
+        Ok(response) => {
+            writer.write_all(&[0])?;
+            write_bytes(writer, response)?;
+        }
+        Err(err) => {
+            writer.write_all(&[1])?;
+            write_bytes(writer, err.as_bytes())?;
+        }
+    }
+    Ok(())
+}
+
+/// Reads the next call, or none at the end of the recording
+fn read_call(reader: &mut impl Read) -> Result<Option<HostCall>> {
+    // The recording ends where a call would start
+    let mut len = [0; 4];
+    let mut read = 0;
+    while read < len.len() {
+        match reader.read(&mut len[read..])? {
+            0 if read == 0 => return Ok(None),
+            0 => return Err(std::io::Error::from(ErrorKind::UnexpectedEof).into()),
+            n => read += n,
+        }
+    }
+    let mut mod_name = vec![0; u32::from_le_bytes(len) as usize];
+    reader.read_exact(&mut mod_name)?;
+
+    let system = read_string(reader)?;
+    let access = match read_u8(reader)? {
+        0 => ModAccess::World,
+        1 => {
+            let mut bits = [0; 8];
+            reader.read_exact(&mut bits)?;
+            let sandbox = Entity::try_from_bits(u64::from_le_bytes(bits))
+                .ok_or_else(|| anyhow!("Invalid recorded sandbox"))?;
+            ModAccess::Sandbox(sandbox)
+        }
+        tag => bail!("Invalid recorded access {tag}"),
+    };
+    let call = read_string(reader)?;
+    let params = read_bytes(reader)?;
+    let response = match read_u8(reader)? {
+        0 => Ok(read_bytes(reader)?),
+        1 => Err(read_string(reader)?),
+        tag => bail!("Invalid recorded response {tag}"),
+    };
+
+    Ok(Some(HostCall {
+        mod_name: String::from_utf8(mod_name)?,
+        system,
+        access,
+        call,
+        params,
+        response,
+    }))
+}
+
+fn read_u8(reader: &mut impl Read) -> Result<u8> {
+    let mut byte = [0; 1];
+    reader.read_exact(&mut byte)?;
+    Ok(byte[0])
+}
+
+fn read_u32(reader: &mut impl Read) -> Result<u32> {
+    let mut bytes = [0; 4];
+    reader.read_exact(&mut bytes)?;
+    Ok(u32::from_le_bytes(bytes))
+}
+
+fn read_bytes(reader: &mut impl Read) -> Result<Vec<u8>> {
+    let len = read_u32(reader)?;
+    let mut bytes = vec![0; len as usize];
+    reader.read_exact(&mut bytes)?;
+    Ok(bytes)
+}
+
+fn read_string(reader: &mut impl Read) -> Result<String> {
+    Ok(String::from_utf8(read_bytes(reader)?)?)
+}
+
+fn write_bytes(writer: &mut impl Write, bytes: &[u8]) -> std::io::Result<()> {
+    writer.write_all(&(bytes.len() as u32).to_le_bytes())?;
+    writer.write_all(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy_ecs::reflect::AppTypeRegistry;
+    use bevy_reflect::{Reflect, TypePath};
+    use wasmtime::component::Resource;
+
+    use super::*;
+    use crate::{
+        bindings::wasvy::ecs::app::{HostComponent, HostQuery, HostQueryResult, QueryFor},
+        host::{WasmHost, WasmQuery},
+        query::QueryIdGenerator,
+        system::Param,
+        test_utils::{run_system, world},
+    };
+
+    #[derive(Component, Reflect)]
+    #[reflect(Component)]
+    struct Health(u32);
+
+    fn spawn(health: [u32; 3]) -> (World, Vec<Entity>) {
+        let mut world = world();
+        world
+            .resource::<AppTypeRegistry>()
+            .write()
+            .register::<Health>();
+        let entities = health.map(|health| world.spawn(Health(health)).id());
+        (world, entities.to_vec())
+    }
+
+    /// Copies the health of the first entity to the others, going through several host calls
+    fn copy_health(world: &mut World, tape: Option<Arc<Tape>>) {
+        let params = [Param::Query(vec![QueryFor::Mut(
+            Health::type_path().to_string(),
+        )])];
+        run_system(
+            world,
+            &params,
+            &Default::default(),
+            |host: &mut WasmHost| {
+                host.set_tape(tape);
+                let id = QueryIdGenerator::default().generate();
+                let query = host.table().push(WasmQuery::new(id)).unwrap();
+                let query = || Resource::<WasmQuery>::new_borrow(query.rep());
+
+                assert_eq!(host.count(query()).unwrap(), 3);
+                let first = host.iter(query()).unwrap().unwrap();
+                let component = HostQueryResult::component(host, first, 0).unwrap();
+                let health = HostComponent::get(host, component).unwrap();
+                for result in host.next_batch(query(), 8).unwrap() {
+                    let component = HostQueryResult::component(host, result, 0).unwrap();
+                    HostComponent::set(host, component, health.clone()).unwrap();
+                }
+                assert!(host.iter(query()).unwrap().is_none());
+            },
+        );
+    }
+
+    fn health(world: &World, entities: &[Entity]) -> Vec<u32> {
+        entities
+            .iter()
+            .map(|entity| world.get::<Health>(*entity).unwrap().0)
+            .collect()
+    }
+
+    #[test]
+    fn replays_a_recording() {
+        let recorder = ModRecorder::new("unused.wasvyrec");
+        let (mut world, entities) = spawn([1, 2, 3]);
+        let tape = Tape::new(
+            Some(&recorder),
+            None,
+            "my-mod",
+            "copy-health",
+            ModAccess::World,
+        );
+        copy_health(&mut world, tape);
+        assert_eq!(health(&world, &entities), [1, 1, 1]);
+
+        let recording = recorder.recording().unwrap();
+        let calls: Vec<_> = recording.0.iter().map(|call| call.call.as_str()).collect();
+        assert_eq!(
+            calls,
+            [
+                "query.count",
+                "query.iter",
+                "component.get",
+                "query.next-batch",
+                "component.set",
+                "component.set",
+                "query.iter",
+            ]
+        );
+
+        // Reads are answered from the recording, so the health of the first entity is copied
+        // even though this world has another one
+        let mut buffer = Vec::new();
+        recording.write_to(&mut buffer).unwrap();
+        let replay = ModReplay::new(Recording::read_from(&buffer[..]).unwrap());
+        let (mut world, entities) = spawn([7, 8, 9]);
+        let tape = Tape::new(
+            None,
+            Some(&replay),
+            "my-mod",
+            "copy-health",
+            ModAccess::World,
+        );
+        copy_health(&mut world, tape);
+        assert_eq!(health(&world, &entities), [7, 1, 1]);
+        assert!(replay.is_finished());
+    }
+
+    #[test]
+    fn replays_diverge_on_other_params() {
+        let recorder = ModRecorder::new("unused.wasvyrec");
+        let (mut world, _) = spawn([1, 2, 3]);
+        copy_health(
+            &mut world,
+            Tape::new(
+                Some(&recorder),
+                None,
+                "my-mod",
+                "copy-health",
+                ModAccess::World,
+            ),
+        );
+
+        let replay = ModReplay::new(recorder.recording().unwrap());
+        let tape = Tape::new(
+            None,
+            Some(&replay),
+            "my-mod",
+            "copy-health",
+            ModAccess::World,
+        )
+        .unwrap();
+        let err = tape
+            .call("query.count", Boundary::Read, b"other", || Ok(0u64))
+            .unwrap_err();
+        assert!(err.to_string().contains("Replay diverged at call #0"));
+    }
+
+    #[test]
+    fn replays_failed_calls() {
+        let recorder = ModRecorder::new("unused.wasvyrec");
+        let tape = Tape::new(Some(&recorder), None, "my-mod", "update", ModAccess::World).unwrap();
+        let err = tape
+            .call("query.count", Boundary::Read, &[], || {
+                Err::<u64, _>(wasmtime::Error::msg("Query is not available"))
+            })
+            .unwrap_err();
+        assert_eq!(err.to_string(), "Query is not available");
+
+        // The call fails again, even though the host would answer it now
+        let replay = ModReplay::new(recorder.recording().unwrap());
+        let tape = Tape::new(None, Some(&replay), "my-mod", "update", ModAccess::World).unwrap();
+        let err = tape
+            .call("query.count", Boundary::Read, &[], || Ok(0u64))
+            .unwrap_err();
+        assert_eq!(err.to_string(), "Query is not available");
+        assert!(replay.is_finished());
+    }
+
+    #[test]
+    fn replays_each_access_in_order() {
+        let recorder = ModRecorder::new("unused.wasvyrec");
+        let sandbox = ModAccess::Sandbox(Entity::from_raw_u32(1).unwrap());
+        let record = |access, response: &[u8]| {
+            let tape = Tape::new(Some(&recorder), None, "my-mod", "update", access).unwrap();
+            let response = response.to_vec();
+            tape.call("get-time", Boundary::Read, &[], || Ok(response))
+                .unwrap();
+        };
+        record(ModAccess::World, b"world");
+        record(sandbox, b"sandbox");
+
+        // The same system running in both accesses doesn't see the calls of the other
+        let replay = ModReplay::new(recorder.recording().unwrap());
+        let response = |access| {
+            let tape = Tape::new(None, Some(&replay), "my-mod", "update", access).unwrap();
+            tape.call("get-time", Boundary::Read, &[], || Ok(Vec::<u8>::new()))
+                .unwrap()
+        };
+        assert_eq!(response(sandbox), b"sandbox");
+        assert_eq!(response(ModAccess::World), b"world");
+    }
+
+    #[test]
+    fn saves_calls_as_they_are_recorded() {
+        let path =
+            std::env::temp_dir().join(format!("wasvy-record-{}.wasvyrec", std::process::id()));
+        let recorder = ModRecorder::new(&path);
+        let tape = Tape::new(Some(&recorder), None, "my-mod", "update", ModAccess::World).unwrap();
+        let record = |response: Result<Vec<u8>, wasmtime::Error>| {
+            let _ = tape.call("get-time", Boundary::Read, &[], || response);
+            recorder.save().unwrap();
+            Recording::load(&path).unwrap()
+        };
+
+        assert_eq!(record(Ok(b"first".to_vec())).0.len(), 1);
+        let recording = record(Err(wasmtime::Error::msg("Time is not available")));
+        assert!(recorder.state().unsaved.is_empty());
+        assert_eq!(recording, recorder.recording().unwrap());
+        assert_eq!(
+            recording.0[1].response,
+            Err("Time is not available".to_string())
+        );
+
+        // Saved calls are only kept in the file, and read back along with the unsaved ones
+        let _ = tape.call("get-time", Boundary::Read, &[], || Ok(b"third".to_vec()));
+        let recording = recorder.recording().unwrap();
+        assert_eq!(recording.0.len(), 3);
+        assert_eq!(recording.0[2].response, Ok(b"third".to_vec()));
+        assert_eq!(Recording::load(&path).unwrap().0.len(), 2);
+
+        // A call cut short by a crash ends the recording
+        let mut bytes = std::fs::read(&path).unwrap();
+        bytes.truncate(bytes.len() - 1);
+        assert_eq!(Recording::read_from(&bytes[..]).unwrap().0.len(), 1);
+        std::fs::remove_file(&path).unwrap();
+    }
+}
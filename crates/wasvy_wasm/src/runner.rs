@@ -1,18 +1,43 @@
-use std::ptr::NonNull;
+use std::{
+    collections::HashMap,
+    ptr::NonNull,
+    sync::{Arc, Mutex},
+};
 
-use anyhow::Result;
-use bevy_ecs::{prelude::*, reflect::AppTypeRegistry, world::FilteredEntityMut};
-use wasmtime::component::ResourceAny;
+use anyhow::{Result, anyhow, bail};
+use bevy_ecs::{
+    prelude::*,
+    reflect::AppTypeRegistry,
+    system::SystemChangeTick,
+    world::{FilteredEntityMut, FilteredResourcesMut},
+};
+use bevy_log::prelude::*;
+use wasmtime::{
+    AsContextMut, ResourceLimiter, StoreContextMut, StoreLimits, StoreLimitsBuilder, Trap,
+    component::{Instance, InstancePre, ResourceAny, Val},
+    error::Context,
+};
 use wasmtime_wasi::ResourceTable;
 use wasvy_runtime::{
-    access::ModAccess, component::WasmComponentRegistry, mods::InsertDespawnComponent,
-    prelude::FunctionIndex, serialize::CodecResource,
+    access::ModAccess,
+    component::{TypeAliasRegistry, WasmComponentRegistry},
+    limits::ModMemoryLimits,
+    mods::{InsertDespawnComponent, ModInfo},
+    permissions::ComponentPermissions,
+    prelude::FunctionIndex,
+    sandbox::{SandboxQuotaExceeded, SandboxSpawns},
+    serialize::CodecResource,
+    storage::ModStorage,
 };
 
 use crate::{
+    bindings::wasvy::ecs::app::TimeInfo,
     engine::Engine,
-    host::WasmHost,
+    entity::HierarchyQuery,
+    exports::ModExports,
+    host::{CommandErrors, WasmHost},
     query::{Queries, QueryResolver},
+    record::Tape,
     send_sync_ptr::SendSyncPtr,
     system::AddSystems,
 };
@@ -22,14 +47,136 @@ pub(crate) type Store = wasmtime::Store<WasmHost>;
 /// Used to contruct a [`Store`] in order to run mods
 pub(crate) struct Runner {
     store: Store,
+
+    /// See [`Engine::epoch_deadline`]
+    epoch_deadline: u64,
+
+    /// The fuel given to each call, when fuel is metered. See [`Engine::fuel`]
+    fuel: Option<u64>,
+
+    /// The instance kept by [`Runner::call_reused`]
+    instance: Option<Instance>,
 }
 
 impl Runner {
-    pub(crate) fn new(engine: &Engine) -> Self {
-        let host = WasmHost::new();
-        let store = Store::new(engine.inner(), host);
+    /// Creates a runner for a mod, enforcing its memory limits or the engine's when it has none
+    pub(crate) fn new(
+        engine: &Engine,
+        mod_name: &str,
+        memory_limits: Option<ModMemoryLimits>,
+    ) -> Self {
+        let mut host = WasmHost::new(mod_name, engine.capture_output());
+        host.set_max_call_depth(engine.max_call_depth());
+        host.set_limits(ModLimiter::new(store_limits(
+            memory_limits.unwrap_or_else(|| engine.memory_limits()),
+        )));
+        let mut store = Store::new(engine.inner(), host);
+        store.limiter(|host| host.limits());
+        store.epoch_deadline_trap();
+
+        Self {
+            store,
+            epoch_deadline: engine.epoch_deadline(),
+            fuel: engine.fuel(),
+            instance: None,
+        }
+    }
+
+    /// Limits the fuel of the next calls. Has no effect if fuel is not metered.
+    pub(crate) fn set_fuel(&mut self, fuel: u64) {
+        if self.fuel.is_some() {
+            self.fuel = Some(fuel);
+        }
+    }
 
-        Self { store }
+    /// The fuel used by the last call, if fuel is metered
+    pub(crate) fn fuel_consumed(&self) -> Option<u64> {
+        let fuel = self.fuel?;
+        let remaining = self.store.get_fuel().ok()?;
+        Some(fuel.saturating_sub(remaining))
+    }
+
+    /// The size of the linear memories of the instance, in bytes
+    pub(crate) fn memory_size(&self) -> usize {
+        self.store.data().memory_size()
+    }
+
+    /// Whether the mod dropped every handle it was given, so its instance can be called again.
+    ///
+    /// Handles the mod keeps point into the table of this store. Clearing the table would let
+    /// them point at the resources of a later run instead, so a runner whose mod kept handles
+    /// must be dropped, see [`ModRunners::give_back`].
+    pub(crate) fn holds_no_handles(&mut self) -> bool {
+        self.table().is_empty()
+    }
+
+    /// Calls an export of the mod, reusing the instance created by previous calls.
+    ///
+    /// Instantiating a mod is costly, so systems keep their runner between runs instead, see
+    /// [`ModRunners`]. The guest's memory and globals outlive a run, but none of its handles do.
+    ///
+    /// An instance that trapped can't be entered again, so drop the runner when this errors.
+    pub(crate) fn call_reused(
+        &mut self,
+        instance_pre: &InstancePre<WasmHost>,
+        config: Config,
+        name: &str,
+        params: &[Val],
+        results: &mut [Val],
+    ) -> Result<()> {
+        self.set_instance_pre(instance_pre);
+        let instance = self.instance;
+        let instance = self.use_store(config, move |mut store| {
+            let instance = match instance {
+                Some(instance) => instance,
+                None => instance_pre
+                    .instantiate(&mut store)
+                    .context("Failed to instantiate component")?,
+            };
+
+            let func = instance
+                .get_func(&mut store, name)
+                .ok_or(anyhow!("Missing {name} function"))?;
+
+            func.call(&mut store, params, results)
+                .context("Failed to run the desired wasm function")?;
+
+            Ok::<_, anyhow::Error>(instance)
+        })?;
+        self.instance = Some(instance);
+
+        Ok(())
+    }
+
+    /// Sets the mod that host functions call back into, see [`call_reentrant`]
+    pub(crate) fn set_instance_pre(&mut self, instance_pre: &InstancePre<WasmHost>) {
+        self.store.data_mut().set_instance_pre(instance_pre.clone());
+    }
+
+    /// Attaches a tape that records or replays the host calls made with this runner
+    pub(crate) fn set_tape(&mut self, tape: Option<Arc<Tape>>) {
+        self.store.data_mut().set_tape(tape);
+    }
+
+    /// Sets what the mod is told when it calls `mod-info`
+    pub(crate) fn set_mod_info(&mut self, mod_info: Option<ModInfo>) {
+        self.store.data_mut().set_mod_info(mod_info);
+    }
+
+    /// Sets what the mod is told when it calls `get-time`
+    pub(crate) fn set_time(&mut self, time: Option<TimeInfo>) {
+        self.store.data_mut().set_time(time);
+    }
+
+    /// Lends the failed commands of the system about to run, which the mod takes with
+    /// `commands.take-errors`
+    pub(crate) fn set_command_errors(&mut self, command_errors: CommandErrors) {
+        self.store.data_mut().set_command_errors(command_errors);
+    }
+
+    /// Sets the store backing `get-data`, `set-data` and `remove-data`
+    pub(crate) fn set_storage(&mut self, storage: ModStorage) {
+        self.store.data_mut().set_storage(storage);
     }
 
     pub fn table(&mut self) -> &mut ResourceTable {
@@ -44,15 +191,303 @@ impl Runner {
         Ok(resource.try_into_resource_any(&mut self.store)?)
     }
 
-    pub(crate) fn use_store<'a, 'b, 'c, 'd, 'e, 'f, 'g, F, R>(
+    pub(crate) fn use_store<'a, 'b, 'c, 'd, 'e, 'f, 'g, 'h, 'i, 'j, 'k, F, R>(
         &mut self,
-        config: Config<'a, 'b, 'c, 'd, 'e, 'f, 'g>,
+        config: Config<'a, 'b, 'c, 'd, 'e, 'f, 'g, 'h, 'i, 'j, 'k>,
         mut f: F,
     ) -> R
     where
         F: FnMut(&mut Store) -> R,
     {
-        self.store.data_mut().set_data(Data(match config {
+        // The deadline is relative to the epoch when it's set, so each call gets the full budget
+        self.store.set_epoch_deadline(self.epoch_deadline);
+        if let Some(fuel) = self.fuel {
+            self.store.set_fuel(fuel).expect("fuel to be metered");
+        }
+
+        scoped(&mut self.store, config, |store| f(store))
+    }
+}
+
+/// The runners of a mod kept between system runs, one for each of its systems and accesses.
+///
+/// A system takes its runner out for the duration of its run, so systems running on different
+/// threads never share a store. Since a system always gets its own runner back, the guest's
+/// memory and globals persist between the runs of a system, like a [Local], but are never seen
+/// by its other systems or by the same system running in another access. Setting the mod up again
+/// replaces its runners, since they hold instances of the previous version.
+#[derive(Component, Clone, Default)]
+pub(crate) struct ModRunners(Arc<Mutex<HashMap<(String, ModAccess), Runner>>>);
+
+impl ModRunners {
+    /// Returns the runners of a mod, inserting an empty pool if it has none yet
+    pub(crate) fn of(mod_id: Entity, world: &mut World) -> Self {
+        let mut entity = world.entity_mut(mod_id);
+        if let Some(runners) = entity.get::<ModRunners>() {
+            return runners.clone();
+        }
+
+        let runners = ModRunners::default();
+        entity.insert(runners.clone());
+        runners
+    }
+
+    /// Takes the runner that `system` finished its last run in `access` with, if there is one
+    pub(crate) fn take(&self, system: &str, access: ModAccess) -> Option<Runner> {
+        self.0
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .remove(&(system.to_string(), access))
+    }
+
+    /// Keeps the runner of `system` in `access` for its next run, unless its mod kept handles
+    /// (see [`Runner::holds_no_handles`])
+    pub(crate) fn give_back(&self, system: &str, access: ModAccess, mut runner: Runner) {
+        if !runner.holds_no_handles() {
+            trace!(
+                "Mod \"{}\" kept handles after its run, it will run in a new instance",
+                runner.store.data().mod_name()
+            );
+            return;
+        }
+        self.0
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .insert((system.to_string(), access), runner);
+    }
+}
+
+/// Runs `f` with the mod entered in a new scope, see [`Data`].
+///
+/// The scope is popped once `f` returns, restoring the one the store was in before. This is what
+/// lets a host function enter its store again, without clearing the scope of the outer call.
+pub(crate) fn scoped<S, F, R>(mut store: S, config: Config, f: F) -> R
+where
+    S: AsContextMut<Data = WasmHost>,
+    F: FnOnce(&mut S) -> R,
+{
+    store.as_context_mut().data_mut().push_scope(config);
+    let ret = f(&mut store);
+
+    // Avoid storing invalid pointers in WasmHost data (such as ConfigSetup::world), which only
+    // live as long as the config. If we didn't pop the scope, Data::access could access an invalid ref
+    store.as_context_mut().data_mut().pop_scope();
+
+    ret
+}
+
+/// Calls an export of the mod while a call into it is on the stack, such as from a host function
+/// that runs a callback of the mod.
+///
+/// A component can't be entered again while it runs, so this calls another instance of the mod in
+/// the same store, with the same access to the app as the call it's made from. Each call depth
+/// keeps its instance, so a mod calling back every frame doesn't pile up instances in the store.
+/// Fails once [`max_call_depth`](crate::plugin::WasmBackendPlugin::max_call_depth) calls are on
+/// the stack.
+pub(crate) fn call_reentrant(
+    mut store: StoreContextMut<WasmHost>,
+    name: &str,
+    params: &[Val],
+    results: &mut [Val],
+) -> Result<()> {
+    let host = store.data_mut();
+    let max_call_depth = host.max_call_depth();
+    if host.call_depth() >= max_call_depth {
+        bail!(
+            "Mod \"{}\" can't be called back, it already has {max_call_depth} calls on the stack",
+            host.mod_name()
+        );
+    }
+    let instance_pre = host
+        .instance_pre()
+        .cloned()
+        .ok_or_else(|| anyhow!("Mod \"{}\" is not running", host.mod_name()))?;
+
+    let depth = host.call_depth().saturating_sub(1);
+    let instance = host.reentrant_instance(depth);
+    host.reenter_scope();
+    let result = (|| {
+        let instance = match instance {
+            Some(instance) => instance,
+            None => {
+                let instance = instance_pre
+                    .instantiate(&mut store)
+                    .context("Failed to instantiate component")?;
+                store.data_mut().keep_reentrant_instance(depth, instance);
+                instance
+            }
+        };
+
+        let func = instance
+            .get_func(&mut store, name)
+            .ok_or(anyhow!("Missing {name} function"))?;
+
+        func.call(&mut store, params, results)
+            .context("Failed to run the desired wasm function")?;
+
+        Ok(())
+    })();
+    let host = store.data_mut();
+    host.pop_scope();
+    if result.is_err() {
+        // An instance that trapped can't be entered again
+        host.forget_reentrant_instances(depth);
+    }
+
+    result
+}
+
+fn store_limits(limits: ModMemoryLimits) -> StoreLimits {
+    let mut builder = StoreLimitsBuilder::new();
+    if let Some(bytes) = limits.max_memory {
+        builder = builder.memory_size(bytes);
+    }
+    if let Some(tables) = limits.max_tables {
+        builder = builder.tables(tables);
+    }
+    if let Some(instances) = limits.max_instances {
+        builder = builder.instances(instances);
+    }
+    builder.build()
+}
+
+/// Enforces the memory limits of a mod, while keeping track of how much memory it uses
+pub(crate) struct ModLimiter {
+    limits: StoreLimits,
+
+    /// The total size of the linear memories, in bytes
+    memory: usize,
+
+    /// The growth of the last allowed [`ResourceLimiter::memory_growing`], undone if it fails
+    growing: usize,
+}
+
+impl ModLimiter {
+    pub(crate) fn new(limits: StoreLimits) -> Self {
+        Self {
+            limits,
+            memory: 0,
+            growing: 0,
+        }
+    }
+
+    pub(crate) fn memory_size(&self) -> usize {
+        self.memory
+    }
+}
+
+impl Default for ModLimiter {
+    fn default() -> Self {
+        Self::new(StoreLimits::default())
+    }
+}
+
+impl ResourceLimiter for ModLimiter {
+    fn memory_growing(
+        &mut self,
+        current: usize,
+        desired: usize,
+        maximum: Option<usize>,
+    ) -> wasmtime::Result<bool> {
+        let allow = self.limits.memory_growing(current, desired, maximum)?;
+        if allow {
+            self.growing = desired.saturating_sub(current);
+            self.memory += self.growing;
+        }
+        Ok(allow)
+    }
+
+    fn memory_grow_failed(&mut self, error: wasmtime::Error) -> wasmtime::Result<()> {
+        self.memory -= std::mem::take(&mut self.growing);
+        self.limits.memory_grow_failed(error)
+    }
+
+    fn table_growing(
+        &mut self,
+        current: usize,
+        desired: usize,
+        maximum: Option<usize>,
+    ) -> wasmtime::Result<bool> {
+        self.limits.table_growing(current, desired, maximum)
+    }
+
+    fn table_grow_failed(&mut self, error: wasmtime::Error) -> wasmtime::Result<()> {
+        self.limits.table_grow_failed(error)
+    }
+
+    fn instances(&self) -> usize {
+        self.limits.instances()
+    }
+
+    fn tables(&self) -> usize {
+        self.limits.tables()
+    }
+
+    fn memories(&self) -> usize {
+        self.limits.memories()
+    }
+}
+
+/// Whether a call into a mod failed because it ran out of time, see [`Engine::timeout`]
+pub(crate) fn is_interrupted(err: &anyhow::Error) -> bool {
+    is_trap(err, Trap::Interrupt)
+}
+
+/// Whether a call into a mod failed because it ran out of fuel, see [`Engine::fuel`]
+pub(crate) fn is_out_of_fuel(err: &anyhow::Error) -> bool {
+    is_trap(err, Trap::OutOfFuel)
+}
+
+/// Whether a call into a mod failed because it spawned more entities than its sandbox allows,
+/// see [SandboxQuota](wasvy_runtime::sandbox::SandboxQuota)
+pub(crate) fn is_quota_exceeded(err: &anyhow::Error) -> bool {
+    err.chain()
+        .any(|err| err.downcast_ref::<SandboxQuotaExceeded>().is_some())
+}
+
+fn is_trap(err: &anyhow::Error, trap: Trap) -> bool {
+    err.chain()
+        .any(|err| err.downcast_ref::<Trap>() == Some(&trap))
+}
+
+/// Data stored in [`WasmHost`]: the scopes the mod was entered in, innermost last.
+///
+/// Each call into the mod pushes a scope holding what its host functions may access, see
+/// [`scoped`]. Nested calls get their own scope, so they don't clear or overwrite the state of the
+/// call they happen in.
+#[derive(Default)]
+pub(crate) struct Data(Vec<Inner>);
+
+#[derive(Clone)]
+enum Inner {
+    Setup {
+        world: SendSyncPtr<World>,
+        add_systems: SendSyncPtr<AddSystems>,
+    },
+    Call,
+    RunSystem {
+        commands: SendSyncPtr<Commands<'static, 'static>>,
+        type_registry: SendSyncPtr<AppTypeRegistry>,
+        codec: SendSyncPtr<CodecResource>,
+        wasm_registry: SendSyncPtr<WasmComponentRegistry>,
+        aliases: SendSyncPtr<TypeAliasRegistry>,
+        function_index: SendSyncPtr<FunctionIndex>,
+        queries: SendSyncPtr<Queries<'static, 'static>>,
+        resources: SendSyncPtr<FilteredResourcesMut<'static, 'static>>,
+        query_resolver: SendSyncPtr<QueryResolver>,
+        access: ModAccess,
+        insert_despawn_component: InsertDespawnComponent,
+        spawns: Option<SandboxSpawns>,
+        permissions: SendSyncPtr<ComponentPermissions>,
+        change_tick: SystemChangeTick,
+        mod_exports: SendSyncPtr<ModExports>,
+        hierarchy: SendSyncPtr<HierarchyQuery<'static, 'static>>,
+    },
+}
+
+impl Data {
+    pub(crate) fn push(&mut self, config: Config) {
+        self.0.push(match config {
             Config::Setup(ConfigSetup {
                 world,
                 add_systems: systems,
@@ -60,71 +495,64 @@ impl Runner {
                 world: SendSyncPtr::new(world.into()),
                 add_systems: SendSyncPtr::new(systems.into()),
             },
+            Config::Call => Inner::Call,
             Config::RunSystem(ConfigRunSystem {
                 commands,
                 type_registry,
                 codec,
                 wasm_registry,
+                aliases,
                 function_index,
                 queries,
+                resources,
                 query_resolver,
                 access,
                 insert_despawn_component,
+                spawns,
+                permissions,
+                change_tick,
+                mod_exports,
+                hierarchy,
             }) => Inner::RunSystem {
                 commands: SendSyncPtr::new(NonNull::from_mut(commands).cast()),
                 type_registry: SendSyncPtr::new(NonNull::from_ref(type_registry)),
                 codec: SendSyncPtr::new(NonNull::from_ref(codec)),
                 wasm_registry: SendSyncPtr::new(NonNull::from_ref(wasm_registry)),
+                aliases: SendSyncPtr::new(NonNull::from_ref(aliases)),
                 function_index: SendSyncPtr::new(NonNull::from_ref(function_index)),
                 queries: SendSyncPtr::new(NonNull::from_ref(queries).cast()),
+                resources: SendSyncPtr::new(NonNull::from_mut(resources).cast()),
                 query_resolver: SendSyncPtr::new(NonNull::from_ref(query_resolver)),
                 access,
                 insert_despawn_component,
+                spawns,
+                permissions: SendSyncPtr::new(NonNull::from_ref(permissions)),
+                change_tick,
+                mod_exports: SendSyncPtr::new(NonNull::from_ref(mod_exports)),
+                hierarchy: SendSyncPtr::new(NonNull::from_ref(hierarchy).cast()),
             },
-        }));
-
-        let ret = f(&mut self.store);
-
-        // Avoid storing invalid pointers in WasmHost data (such as ConfigSetup::schedules) which have a lifetime of 'a
-        // If we didn't reset the data before this function returns, Data::access could access an invalid ref
-        self.store.data_mut().clear();
-
-        ret
+        });
     }
-}
 
-/// Data stored in [`WasmHost`]
-pub(crate) struct Data(Inner);
+    pub(crate) fn pop(&mut self) {
+        self.0.pop();
+    }
 
-enum Inner {
-    Uninitialized,
-    Setup {
-        world: SendSyncPtr<World>,
-        add_systems: SendSyncPtr<AddSystems>,
-    },
-    RunSystem {
-        commands: SendSyncPtr<Commands<'static, 'static>>,
-        type_registry: SendSyncPtr<AppTypeRegistry>,
-        codec: SendSyncPtr<CodecResource>,
-        wasm_registry: SendSyncPtr<WasmComponentRegistry>,
-        function_index: SendSyncPtr<FunctionIndex>,
-        queries: SendSyncPtr<Queries<'static, 'static>>,
-        query_resolver: SendSyncPtr<QueryResolver>,
-        access: ModAccess,
-        insert_despawn_component: InsertDespawnComponent,
-    },
-}
+    /// Pushes a copy of the innermost scope, or a [`Config::Call`] scope outside of any
+    pub(crate) fn reenter(&mut self) {
+        let scope = self.0.last().cloned().unwrap_or(Inner::Call);
+        self.0.push(scope);
+    }
 
-impl Data {
-    pub(crate) fn uninitialized() -> Self {
-        Self(Inner::Uninitialized)
+    pub(crate) fn depth(&self) -> usize {
+        self.0.len()
     }
 
     /// A helper so [`WasmHost`] can expose access to the [`Data`] it stores
     ///
     /// The resource table from the host is passed through this for convenience
     pub(crate) fn access<'a>(&'a mut self, table: &'a mut ResourceTable) -> Option<State<'a>> {
-        match &mut self.0 {
+        match self.0.last_mut()? {
             Inner::Setup {
                 world,
                 add_systems: systems,
@@ -140,11 +568,18 @@ impl Data {
                 type_registry,
                 codec,
                 wasm_registry,
+                aliases,
                 function_index,
                 queries,
+                resources,
                 query_resolver,
                 access,
                 insert_despawn_component,
+                spawns,
+                permissions,
+                change_tick,
+                mod_exports,
+                hierarchy,
             } =>
             // Safety: Runner::use_store ensures that this always contains a valid reference
             // See the rules here: https://doc.rust-lang.org/stable/core/ptr/index.html#pointer-to-reference-conversion
@@ -154,15 +589,22 @@ impl Data {
                     type_registry: type_registry.as_ref(),
                     codec: codec.as_ref(),
                     wasm_registry: wasm_registry.as_ref(),
+                    aliases: aliases.as_ref(),
                     function_index: function_index.as_ref(),
                     queries: queries.cast().as_mut(),
+                    resources: resources.cast().as_mut(),
                     query_resolver: query_resolver.as_ref(),
                     insert_despawn_component,
+                    spawns: spawns.as_ref(),
                     access,
+                    permissions: permissions.as_ref(),
+                    change_tick,
+                    mod_exports: mod_exports.as_ref(),
+                    hierarchy: hierarchy.cast().as_ref(),
                     table,
                 })
             },
-            Inner::Uninitialized => None,
+            Inner::Call => Some(State::Call),
         }
     }
 }
@@ -173,23 +615,34 @@ pub(crate) enum State<'a> {
         table: &'a mut ResourceTable,
         add_systems: &'a mut AddSystems,
     },
+    /// A function called by another mod, see [ModExports]
+    Call,
     RunSystem {
         table: &'a mut ResourceTable,
         commands: &'a mut Commands<'a, 'a>,
         type_registry: &'a AppTypeRegistry,
         codec: &'a CodecResource,
         wasm_registry: &'a WasmComponentRegistry,
+        aliases: &'a TypeAliasRegistry,
         function_index: &'a FunctionIndex,
         queries: &'a mut Queries<'a, 'a>,
+        resources: &'a mut FilteredResourcesMut<'a, 'a>,
         query_resolver: &'a QueryResolver,
         access: &'a ModAccess,
         insert_despawn_component: &'a InsertDespawnComponent,
+        /// Set when the mod runs in a sandbox, see [SandboxQuota](wasvy_runtime::sandbox::SandboxQuota)
+        spawns: Option<&'a SandboxSpawns>,
+        permissions: &'a ComponentPermissions,
+        change_tick: &'a SystemChangeTick,
+        mod_exports: &'a ModExports,
+        hierarchy: &'a HierarchyQuery<'a, 'a>,
     },
 }
 
-pub(crate) enum Config<'a, 'b, 'c, 'd, 'e, 'f, 'g> {
+pub(crate) enum Config<'a, 'b, 'c, 'd, 'e, 'f, 'g, 'h, 'i, 'j, 'k> {
     Setup(ConfigSetup<'a>),
-    RunSystem(ConfigRunSystem<'a, 'b, 'c, 'd, 'e, 'f, 'g>),
+    Call,
+    RunSystem(ConfigRunSystem<'a, 'b, 'c, 'd, 'e, 'f, 'g, 'h, 'i, 'j, 'k>),
 }
 
 pub(crate) struct ConfigSetup<'a> {
@@ -197,15 +650,158 @@ pub(crate) struct ConfigSetup<'a> {
     pub(crate) add_systems: &'a mut AddSystems,
 }
 
-pub(crate) struct ConfigRunSystem<'a, 'b, 'c, 'd, 'e, 'f, 'g> {
+pub(crate) struct ConfigRunSystem<'a, 'b, 'c, 'd, 'e, 'f, 'g, 'h, 'i, 'j, 'k> {
     pub(crate) commands: &'a mut Commands<'b, 'c>,
     pub(crate) type_registry: &'a AppTypeRegistry,
     pub(crate) codec: &'a CodecResource,
     pub(crate) wasm_registry: &'a WasmComponentRegistry,
+    pub(crate) aliases: &'a TypeAliasRegistry,
     pub(crate) function_index: &'a FunctionIndex,
     pub(crate) queries:
         &'a mut ParamSet<'d, 'e, Vec<Query<'f, 'g, FilteredEntityMut<'static, 'static>>>>,
+    pub(crate) resources: &'a mut FilteredResourcesMut<'h, 'i>,
     pub(crate) query_resolver: &'a QueryResolver,
     pub(crate) access: ModAccess,
     pub(crate) insert_despawn_component: InsertDespawnComponent,
+    pub(crate) spawns: Option<SandboxSpawns>,
+    pub(crate) permissions: &'a ComponentPermissions,
+    pub(crate) change_tick: SystemChangeTick,
+    pub(crate) mod_exports: &'a ModExports,
+    pub(crate) hierarchy: &'a HierarchyQuery<'j, 'k>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        host::WasmCommands,
+        test_utils::{component, noop, set_global, trap_if_global_set},
+    };
+
+    #[test]
+    fn nested_scopes_restore_the_outer_one() {
+        let mut runner = Runner::new(&Engine::default(), "my-mod", None);
+        let mut world = World::new();
+        let mut add_systems = AddSystems::default();
+        let config = Config::Setup(ConfigSetup {
+            world: &mut world,
+            add_systems: &mut add_systems,
+        });
+
+        runner.use_store(config, |store| {
+            scoped(&mut *store, Config::Call, |store| {
+                assert!(matches!(store.data_mut().access(), State::Call));
+            });
+            assert!(matches!(store.data_mut().access(), State::Setup { .. }));
+        });
+        assert!(matches!(runner.store.data_mut().access(), State::Call));
+    }
+
+    #[test]
+    fn limits_reentrant_calls() {
+        let mut runner = Runner::new(&Engine::default(), "my-mod", None);
+        runner.store.data_mut().set_max_call_depth(2);
+
+        runner.use_store(Config::Call, |store| {
+            // The mod isn't actually running, so the first reentrant call has nothing to call
+            let err = call_reentrant(store.as_context_mut(), "callback", &[], &mut []).unwrap_err();
+            assert!(err.to_string().contains("is not running"), "{err}");
+
+            scoped(&mut *store, Config::Call, |store| {
+                let err =
+                    call_reentrant(store.as_context_mut(), "callback", &[], &mut []).unwrap_err();
+                assert!(err.to_string().contains("2 calls on the stack"), "{err}");
+            });
+        });
+    }
+
+    #[test]
+    fn reentrant_calls_reuse_their_instance() {
+        let engine = Engine::default();
+        let component = component(engine.inner(), &[("callback", noop())]);
+        let instance_pre = wasmtime::component::Linker::new(engine.inner())
+            .instantiate_pre(&component)
+            .unwrap();
+
+        // The calls would run out of instances if each of them instantiated the mod
+        let limits = ModMemoryLimits::default().max_instances(2);
+        let mut runner = Runner::new(&engine, "my-mod", Some(limits));
+        runner.set_instance_pre(&instance_pre);
+        runner.use_store(Config::Call, |store| {
+            for _ in 0..100 {
+                call_reentrant(store.as_context_mut(), "callback", &[], &mut []).unwrap();
+            }
+        });
+    }
+
+    #[test]
+    fn runners_keep_their_instance() {
+        let engine = Engine::default();
+        let component = component(engine.inner(), &[("update", noop())]);
+        let instance_pre = wasmtime::component::Linker::new(engine.inner())
+            .instantiate_pre(&component)
+            .unwrap();
+
+        // Systems instantiating the mod on every run would run out of instances
+        let limits = ModMemoryLimits::default().max_instances(1);
+        let runners = ModRunners::default();
+        for _ in 0..10 {
+            let mut runner = runners
+                .take("update", ModAccess::World)
+                .unwrap_or_else(|| Runner::new(&engine, "my-mod", Some(limits)));
+            assert!(
+                runners.take("update", ModAccess::World).is_none(),
+                "one runner is enough"
+            );
+            runner
+                .call_reused(&instance_pre, Config::Call, "update", &[], &mut [])
+                .unwrap();
+            runners.give_back("update", ModAccess::World, runner);
+        }
+    }
+
+    #[test]
+    fn systems_never_see_the_state_of_other_systems() {
+        let engine = Engine::default();
+        let component = component(
+            engine.inner(),
+            &[("write", set_global()), ("check", trap_if_global_set())],
+        );
+        let instance_pre = wasmtime::component::Linker::new(engine.inner())
+            .instantiate_pre(&component)
+            .unwrap();
+        let runners = ModRunners::default();
+        let sandbox = Entity::from_raw_u32(1).unwrap();
+        let run = |system: &str, export: &str, access: ModAccess| {
+            let mut runner = runners
+                .take(system, access)
+                .unwrap_or_else(|| Runner::new(&engine, "my-mod", None));
+            runner
+                .call_reused(&instance_pre, Config::Call, export, &[], &mut [])
+                .map(|()| runners.give_back(system, access, runner))
+        };
+
+        run("write", "write", ModAccess::World).unwrap();
+        run("check", "check", ModAccess::World).expect("another system doesn't see the global");
+        run("write", "check", ModAccess::Sandbox(sandbox))
+            .expect("the same system in another access doesn't see the global");
+
+        // The system itself keeps its state between runs
+        assert!(run("write", "check", ModAccess::World).is_err());
+    }
+
+    #[test]
+    fn runners_of_mods_keeping_handles_are_dropped() {
+        let runners = ModRunners::default();
+        let mut runner = Runner::new(&Engine::default(), "my-mod", None);
+
+        // The mod kept a handle, which must not point at a resource of a later run
+        runner.new_resource(WasmCommands).unwrap();
+        runners.give_back("update", ModAccess::World, runner);
+        assert!(runners.take("update", ModAccess::World).is_none());
+
+        let runner = Runner::new(&Engine::default(), "my-mod", None);
+        runners.give_back("update", ModAccess::World, runner);
+        assert!(runners.take("update", ModAccess::World).is_some());
+    }
 }
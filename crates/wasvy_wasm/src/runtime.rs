@@ -0,0 +1,98 @@
+use anyhow::{Result, bail};
+use bevy_tasks::BoxedFuture;
+use wasvy_runtime::asset::ModAsset;
+
+#[cfg(feature = "wasmtime")]
+use {
+    crate::{cache::ModCache, engine::Linker, wasm_asset::WasmModBackend},
+    wasmtime::component::Component,
+};
+
+/// A WebAssembly runtime able to compile mods.
+///
+/// The [`ModAssetLoader`](crate::wasm_asset::ModAssetLoader) reads the bytes of `.wasm` assets and
+/// hands them to the runtime, which returns a [`ModAsset`] backed by its own
+/// [`ModBackend`](wasvy_runtime::asset::ModBackend).
+///
+/// Native builds use [`WasmtimeRuntime`] (the `wasmtime` feature). Browser builds can't run
+/// wasmtime and use [`WebRuntime`](crate::web::WebRuntime) instead (the `web` feature).
+pub trait WasmRuntime: Send + Sync + 'static {
+    /// Compiles the bytes of a wasm file into a mod asset.
+    fn compile(&self, bytes: &[u8]) -> Result<ModAsset>;
+
+    /// Compiles the bytes of a wasm file into a mod asset linked with `plugs`, see
+    /// [`Mods::load_composed`](wasvy_runtime::mods::Mods::load_composed).
+    ///
+    /// Runtimes that can't link components host-side fail if there are plugs.
+    fn compile_composed(&self, bytes: &[u8], plugs: &[Vec<u8>]) -> Result<ModAsset> {
+        if !plugs.is_empty() {
+            bail!("This runtime can't link mods with plugs");
+        }
+        self.compile(bytes)
+    }
+
+    /// Compiles a mod without blocking, for runtimes that wait on the host to compile it.
+    ///
+    /// Returns [None] by default, in which case the loader calls
+    /// [compile_composed](Self::compile_composed) on the compute task pool.
+    fn compile_async(
+        &self,
+        _bytes: &[u8],
+        _plugs: &[Vec<u8>],
+    ) -> Option<BoxedFuture<'static, Result<ModAsset>>> {
+        None
+    }
+}
+
+/// Runs mods with [Wasmtime](https://wasmtime.dev/) and WASI.
+///
+/// This is the default runtime, supporting the full `wasvy:ecs` component interface.
+#[cfg(feature = "wasmtime")]
+pub struct WasmtimeRuntime {
+    linker: Linker,
+    cache: Option<ModCache>,
+}
+
+#[cfg(feature = "wasmtime")]
+impl WasmtimeRuntime {
+    pub fn new(linker: Linker) -> Self {
+        Self {
+            linker,
+            cache: None,
+        }
+    }
+
+    /// Caches compiled mods on disk, see [`crate::cache`]
+    pub fn with_cache(mut self, cache: ModCache) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+}
+
+#[cfg(feature = "wasmtime")]
+impl WasmRuntime for WasmtimeRuntime {
+    fn compile(&self, bytes: &[u8]) -> Result<ModAsset> {
+        self.compile_composed(bytes, &[])
+    }
+
+    fn compile_composed(&self, bytes: &[u8], plugs: &[Vec<u8>]) -> Result<ModAsset> {
+        let component = self.component(bytes)?;
+        let plugs = plugs
+            .iter()
+            .map(|plug| self.component(plug))
+            .collect::<Result<Vec<_>>>()?;
+        let backend = WasmModBackend::from_components(&self.linker, component, &plugs)?;
+        Ok(ModAsset::new(backend))
+    }
+}
+
+#[cfg(feature = "wasmtime")]
+impl WasmtimeRuntime {
+    /// Compiles a component, or loads it from the cache
+    fn component(&self, bytes: &[u8]) -> Result<Component> {
+        match &self.cache {
+            Some(cache) => cache.load(self.linker.engine(), bytes),
+            None => Ok(Component::from_binary(self.linker.engine(), bytes)?),
+        }
+    }
+}
@@ -0,0 +1,94 @@
+#[cfg(feature = "scenes")]
+use bevy_asset::AssetServer;
+#[cfg(feature = "scenes")]
+use bevy_ecs::{error::warn, prelude::*};
+#[cfg(feature = "scenes")]
+use bevy_world_serialization::DynamicWorldRoot;
+use wasmtime::component::Resource;
+#[cfg(feature = "scenes")]
+use wasvy_runtime::permissions::AssetPermissions;
+
+use crate::host::{WasmEntityCommands, WasmHost};
+#[cfg(feature = "scenes")]
+use crate::{entity::spawn_empty, runner::State};
+
+/// Spawns the root of a scene and loads the scene under it, see `commands.spawn-scene`.
+///
+/// The root is spawned like any other entity of the mod, so it is in the mod's sandbox and
+/// despawned with the mod. The scene itself is spawned as its children once it has loaded.
+#[cfg(feature = "scenes")]
+pub(crate) fn spawn_scene(
+    host: &mut WasmHost,
+    path: String,
+) -> Result<Resource<WasmEntityCommands>, wasmtime::Error> {
+    let Some(mod_id) = host.mod_id() else {
+        return Err(wasmtime::Error::msg("Scenes can only be spawned by mods"));
+    };
+
+    let entity_commands: Resource<WasmEntityCommands> = spawn_empty(host)?;
+    let State::RunSystem {
+        commands, table, ..
+    } = host.access()
+    else {
+        return Err(wasmtime::Error::msg("Commands can only be used in systems"));
+    };
+
+    let root: Entity = table.get(&entity_commands)?.into();
+    commands.queue_handled(load_scene(mod_id, root, path), warn);
+
+    Ok(entity_commands)
+}
+
+#[cfg(not(feature = "scenes"))]
+pub(crate) fn spawn_scene(
+    _: &mut WasmHost,
+    _: String,
+) -> Result<Resource<WasmEntityCommands>, wasmtime::Error> {
+    Err(wasmtime::Error::msg(
+        "Spawning scenes requires the scenes feature of wasvy",
+    ))
+}
+
+/// A command that loads the scene at `path` under `root`, if the mod may load it.
+///
+/// The root is despawned if the mod is not permitted to, see [AssetPermissions].
+#[cfg(feature = "scenes")]
+fn load_scene(
+    mod_id: Entity,
+    root: Entity,
+    path: String,
+) -> impl FnOnce(&mut World) -> Result<(), BevyError> {
+    move |world| {
+        if let Err(err) = AssetPermissions::of(mod_id, world).check(&path) {
+            if let Ok(root) = world.get_entity_mut(root) {
+                root.despawn();
+            }
+            return Err(err.into());
+        }
+
+        let handle = world
+            .get_resource::<AssetServer>()
+            .ok_or("Spawning scenes requires the AssetPlugin")?
+            .load(path);
+        if let Ok(mut root) = world.get_entity_mut(root) {
+            root.insert(DynamicWorldRoot(handle));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(all(test, feature = "scenes"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn denied_scenes_despawn_their_root() {
+        let mut world = World::new();
+        let mod_id = world.spawn_empty().id();
+        let root = world.spawn_empty().id();
+
+        let result = load_scene(mod_id, root, "scenes/a.scn.ron".into())(&mut world);
+        assert!(result.is_err());
+        assert!(world.get_entity(root).is_err());
+    }
+}
@@ -1,29 +1,60 @@
-use anyhow::Result;
+use std::{collections::HashMap, time::Instant};
+
+use anyhow::{Result, anyhow, bail};
+use bevy_diagnostic::Diagnostics;
 use bevy_ecs::{
+    component::ComponentId,
     error::Result as BevyResult,
+    lifecycle::{Add, Discard, Insert, Remove},
     prelude::*,
     resource::Resource as BevyResource,
     schedule::{ScheduleConfigs, ScheduleLabel},
-    system::{BoxedSystem, Commands, LocalBuilder, ParamBuilder, ParamSetBuilder, Query},
-    world::FilteredEntityMut,
+    system::{
+        BoxedSystem, Commands, FilteredResourcesMutParamBuilder, LocalBuilder, ParamBuilder,
+        ParamSetBuilder, Query, SystemChangeTick, SystemParam, SystemState,
+    },
+    world::{FilteredEntityMut, FilteredResourcesMut, FilteredResourcesMutBuilder},
 };
 use bevy_log::prelude::*;
 use wasmtime::component::{InstancePre, Resource, Val};
 use wasmtime_wasi::ResourceTable;
 use wasvy_runtime::{
     access::ModAccess,
-    component::WasmComponentRegistry,
-    mods::{InsertDespawnComponent, ModSystemSet},
+    budget::FrameBudget,
+    component::{ComponentRef, ResourceRef, TypeAliasRegistry, TypePath, WasmComponentRegistry},
+    failure::{ModTrapped, ModTraps},
+    limits::ModMemoryLimits,
+    messages::{ModMessageCursor, ReflectModMessage},
+    mods::{InsertDespawnComponent, ModInfo, ModSystemSet},
+    permissions::{ComponentAccess, ComponentPermissions, PermissionDenied},
     prelude::FunctionIndex,
-    serialize::CodecResource,
+    sandbox::SandboxSpawns,
+    schedule::ModConcurrency,
+    serialize::{CodecResource, ModCodec},
+    storage::ModStorage,
 };
 
 use crate::{
-    bindings::wasvy::ecs::app::{QueryFor, Schedule},
+    bindings::wasvy::ecs::app::{LifecycleEvent, QueryFor, Schedule},
+    diagnostics::SystemDiagnostics,
     engine::Engine,
-    host::{WasmCommands, WasmHost, WasmQuery, WasmSystem},
-    query::{QueryId, QueryIdGenerator, QueryResolver, create_query_builder},
-    runner::{Config, ConfigRunSystem, Runner},
+    entity::{HierarchyQuery, check_access},
+    exports::ModExports,
+    fuel::{ModFuel, ModFuelExhausted},
+    host::{
+        CommandErrors, WasmCommands, WasmEntity, WasmEventReader, WasmHost, WasmQuery, WasmRes,
+        WasmSystem,
+    },
+    query::{
+        Queries, QueryId, QueryIdGenerator, QueryResolver, create_query_builder,
+        names_query_builder, resource_ref,
+    },
+    record::{ModRecorder, ModReplay, Tape},
+    runner::{
+        Config, ConfigRunSystem, ModRunners, Runner, is_interrupted, is_out_of_fuel,
+        is_quota_exceeded,
+    },
+    time::ModTime,
     wasm_asset::call,
 };
 
@@ -31,11 +62,49 @@ use crate::{
 ///
 /// Wasvy only registers systems after mod's setup method has successfully run.
 #[derive(Default)]
-pub(crate) struct AddSystems(Vec<(Schedule, Vec<Resource<WasmSystem>>)>);
+pub(crate) struct AddSystems {
+    systems: Vec<(Schedule, Vec<Resource<WasmSystem>>)>,
+
+    /// The codec the mod asked to use for its systems
+    codec: ModCodec,
+
+    /// The functions the mod exposes to other mods
+    exports: Vec<String>,
+
+    /// The exports the host may invoke on demand
+    callbacks: Vec<String>,
+}
 
 impl AddSystems {
     pub(crate) fn push(&mut self, schedule: Schedule, systems: Vec<Resource<WasmSystem>>) {
-        self.0.push((schedule, systems));
+        self.systems.push((schedule, systems));
+    }
+
+    /// The number of systems added so far
+    pub(crate) fn len(&self) -> usize {
+        self.systems.iter().map(|(_, systems)| systems.len()).sum()
+    }
+
+    pub(crate) fn set_codec(&mut self, codec: ModCodec) {
+        self.codec = codec;
+    }
+
+    pub(crate) fn export_function(&mut self, name: String) {
+        self.exports.push(name);
+    }
+
+    /// The functions the mod exposes to other mods
+    pub(crate) fn exports(&self) -> &[String] {
+        &self.exports
+    }
+
+    pub(crate) fn add_callback(&mut self, name: String) {
+        self.callbacks.push(name);
+    }
+
+    /// The exports the host may invoke on demand
+    pub(crate) fn callbacks(&self) -> &[String] {
+        &self.callbacks
     }
 
     pub(crate) fn add_systems(
@@ -47,10 +116,18 @@ impl AddSystems {
         mod_name: &str,
         instance_pre: &InstancePre<WasmHost>,
     ) -> Result<()> {
+        let systems = self.systems.iter().flat_map(|(schedule, systems)| {
+            systems.iter().map(move |system| {
+                let system = table.get(system).expect("Resource not be dropped");
+                (schedule, system)
+            })
+        });
+        let systems = lane_order(systems.collect());
+
         // Each access needs dedicated systems that run inside it
         for access in accesses {
             let mod_schedules = access.schedules(world);
-            for (schedule, systems) in self.0.iter() {
+            for &(schedule, system, lane) in systems.iter() {
                 // Validate that the schedule requested by the mod is enabled
                 let Some(schedule) = mod_schedules
                     .iter()
@@ -58,25 +135,35 @@ impl AddSystems {
                     .map(|s| s.schedule_label())
                 else {
                     warn!(
-                        "Mod tried adding systems to schedule {schedule:?}, but that schedule is not enabled. See ModSchedules docs."
+                        "Mod tried adding system \"{}\" to schedule {schedule:?}, but that schedule is not enabled. See ModSchedules docs.",
+                        system.name
                     );
                     continue;
                 };
 
-                for system in systems
-                    .iter()
-                    .map(|system| table.get(system).expect("Resource not be dropped"))
+                let result = Self::add_system(
+                    schedule,
+                    system,
+                    lane,
+                    world,
+                    mod_id,
+                    mod_name,
+                    access,
+                    self.codec,
+                    instance_pre,
+                );
+
+                // Skip systems that query denied components instead of failing the whole mod
+                if let Err(err) = &result
+                    && let Some(denied) = err.downcast_ref::<PermissionDenied>()
                 {
-                    Self::add_system(
-                        schedule,
-                        system,
-                        world,
-                        mod_id,
-                        mod_name,
-                        access,
-                        instance_pre,
-                    )?;
+                    error!(
+                        "{denied}, skipping system \"{}\" of mod \"{mod_name}\"",
+                        system.name
+                    );
+                    continue;
                 }
+                result?;
             }
         }
 
@@ -86,24 +173,60 @@ impl AddSystems {
     fn add_system(
         schedule: impl ScheduleLabel,
         system: &WasmSystem,
+        lane: bool,
         world: &mut World,
         mod_id: Entity,
         mod_name: &str,
         access: &ModAccess,
+        codec: ModCodec,
         instance_pre: &InstancePre<WasmHost>,
     ) -> Result<()> {
-        let schedule_config = Self::schedule(
+        if !system.observers.is_empty() {
+            if !system.conditions.is_empty() {
+                bail!("Observer \"{}\" can't have run conditions", system.name);
+            }
+            return Self::add_observers(
+                system,
+                world,
+                mod_id,
+                mod_name,
+                access,
+                codec,
+                instance_pre,
+            );
+        }
+        if system
+            .params
+            .iter()
+            .any(|param| matches!(param, Param::Trigger))
+        {
+            bail!(
+                "System \"{}\" has a trigger param, but doesn't observe anything",
+                system.name
+            );
+        }
+
+        let mut schedule_config = Self::schedule(
             system,
             world,
             mod_id,
             mod_name,
             access,
+            codec,
             instance_pre.clone(),
         )?
         .in_set(ModSystemSet::All)
         .in_set(ModSystemSet::Mod(mod_id))
         .in_set(ModSystemSet::Access(*access));
 
+        // Chain the system into a lane when the number of concurrent mod systems is capped
+        if lane
+            && let Some(mut concurrency) = world.get_resource_mut::<ModConcurrency>()
+            && let Some(tail) = concurrency.assign(schedule.intern(), system.id.intern())
+        {
+            schedule_config = schedule_config.after(tail);
+        }
+
         world
             .get_resource_mut::<Schedules>()
             .expect("running in an App")
@@ -112,74 +235,247 @@ impl AddSystems {
         Ok(())
     }
 
+    /// Spawns an [Observer] for each kind of event the system observes, see `system.observe`
+    fn add_observers(
+        sys: &WasmSystem,
+        world: &mut World,
+        mod_id: Entity,
+        mod_name: &str,
+        access: &ModAccess,
+        codec: ModCodec,
+        instance_pre: &InstancePre<WasmHost>,
+    ) -> Result<()> {
+        let permissions = ComponentPermissions::of(mod_id, world);
+        let events = [
+            LifecycleEvent::Add,
+            LifecycleEvent::Insert,
+            LifecycleEvent::Discard,
+            LifecycleEvent::Remove,
+        ];
+        for event in events {
+            let mut components = Vec::new();
+            for (type_path, _) in sys.observers.iter().filter(|(_, e)| *e == event) {
+                let component =
+                    ComponentRef::new(type_path, ComponentAccess::Query, &permissions, world)?;
+                components.push(component.component_id());
+            }
+            if components.is_empty() {
+                continue;
+            }
+
+            let state = Self::state(
+                sys,
+                world,
+                mod_id,
+                mod_name,
+                access,
+                codec,
+                instance_pre.clone(),
+            )?;
+            let name = format!("wasvy[{mod_name}]::{}", sys.name);
+            let observer = match event {
+                LifecycleEvent::Add => Observer::new(
+                    state
+                        .build_system_with_input(observer_system::<Add>)
+                        .with_name(name),
+                ),
+                LifecycleEvent::Insert => Observer::new(
+                    state
+                        .build_system_with_input(observer_system::<Insert>)
+                        .with_name(name),
+                ),
+                LifecycleEvent::Discard => Observer::new(
+                    state
+                        .build_system_with_input(observer_system::<Discard>)
+                        .with_name(name),
+                ),
+                LifecycleEvent::Remove => Observer::new(
+                    state
+                        .build_system_with_input(observer_system::<Remove>)
+                        .with_name(name),
+                ),
+            };
+
+            world.spawn((
+                observer.with_components(components),
+                ModObserver {
+                    mod_id,
+                    access: *access,
+                },
+            ));
+        }
+
+        Ok(())
+    }
+
     pub(crate) fn schedule(
         sys: &WasmSystem,
         world: &mut World,
         mod_id: Entity,
         mod_name: &str,
         access: &ModAccess,
+        codec: ModCodec,
         instance_pre: InstancePre<WasmHost>,
     ) -> Result<ScheduleConfigs<BoxedSystem>> {
+        let system = Self::state(sys, world, mod_id, mod_name, access, codec, instance_pre)?
+            .build_system(dynamic_system)
+            .with_name(format!("wasvy[{mod_name}]::{}", sys.name));
+
+        let boxed_system = Box::new(IntoSystem::into_system(system));
+
+        let mut schedule_config = boxed_system
+            // See docs for [SystemIdentifier]
+            .in_set(sys.id);
+
+        // Implement system ordering
+        for after in sys.after.iter() {
+            schedule_config = schedule_config.after(*after);
+        }
+        for set in sys.after_sets.iter() {
+            schedule_config = schedule_config.after(*set);
+        }
+        for set in sys.before_sets.iter() {
+            schedule_config = schedule_config.before(*set);
+        }
+
+        for condition in sys.conditions.iter() {
+            schedule_config = condition.apply(schedule_config, codec, world)?;
+        }
+
+        Ok(schedule_config)
+    }
+
+    /// Builds the params of a mod system, see [DynamicParams]
+    fn state(
+        sys: &WasmSystem,
+        world: &mut World,
+        mod_id: Entity,
+        mod_name: &str,
+        access: &ModAccess,
+        codec: ModCodec,
+        instance_pre: InstancePre<WasmHost>,
+    ) -> Result<SystemState<(DynamicParams<'static, 'static>,)>> {
         // The input struct contains various data used at runtime
-        let built_params = BuiltParam::new_vec(&sys.params);
-        let query_resolver = QueryResolver::new(&sys.params, world)?;
+        let permissions = ComponentPermissions::of(mod_id, world);
+        let built_params = BuiltParam::new_vec(&sys.params, &permissions, world)?;
+        let query_resolver = QueryResolver::new(&sys.params, &permissions, world)?;
+        let resources = resources_builder(&built_params, &query_resolver);
         let insert_despawn_component = InsertDespawnComponent::new(mod_id, world);
+        let spawns = SandboxSpawns::of(access, world);
+        // Observers always run, so only scheduled systems take turns within the budget
+        let budget = sys.observers.is_empty().then(|| {
+            let budget = FrameBudget::of(mod_id, access, world);
+            let index = budget.register_system();
+            (budget, index)
+        });
+        let fuel = world
+            .resource::<Engine>()
+            .fuel()
+            .map(|budget| ModFuel::of(mod_id, world, budget));
+        let diagnostics = world
+            .resource::<Engine>()
+            .diagnostics()
+            .then(|| SystemDiagnostics::new(mod_name, &sys.name, world));
         let input = Input {
+            mod_id,
             mod_name: mod_name.to_string(),
+            mod_info: ModInfo::new(mod_id, world),
+            storage: ModStorage::of(mod_id, world),
+            memory_limits: world.get::<ModMemoryLimits>(mod_id).copied(),
+            traps: ModTraps::of(mod_id, world),
             system_name: sys.name.clone(),
             built_params,
             query_resolver,
             access: *access,
             insert_despawn_component,
+            spawns,
+            permissions,
+            codec: codec.codec()?,
+            fuel,
+            budget,
+            diagnostics,
             instance_pre,
+            runners: ModRunners::of(mod_id, world),
+            command_errors: CommandErrors::default(),
         };
 
         // Generate the queries necessary to run this system
         let filtered_access = access.filtered_access(world);
         let mut queries = Vec::with_capacity(sys.params.len());
         for items in sys.params.iter().filter_map(Param::filter_query) {
-            queries.push(create_query_builder(items, world, filtered_access.clone())?);
+            queries.push(create_query_builder(
+                items,
+                &input.permissions,
+                world,
+                filtered_access.clone(),
+            )?);
         }
-
-        let system = (
-            LocalBuilder(input),
-            LocalBuilder(Vec::with_capacity(queries.len())),
-            ParamBuilder,
-            ParamBuilder,
-            ParamBuilder,
-            ParamBuilder,
-            ParamBuilder,
-            ParamBuilder,
-            // TODO: FilteredResourcesMutParamBuilder::new(|builder| {}),
-            ParamSetBuilder(queries),
-        )
-            .build_state(world)
-            .build_system(dynamic_system)
-            .with_name(format!("wasvy[{mod_name}]::{}", sys.name));
-
-        let boxed_system = Box::new(IntoSystem::into_system(system));
-
-        let mut schedule_config = boxed_system
-            // See docs for [SystemIdentifier]
-            .in_set(sys.id);
-
-        // Implement system ordering
-        for after in sys.after.iter() {
-            schedule_config = schedule_config.after(*after);
+        // Only commands look entities up by name
+        if sys
+            .params
+            .iter()
+            .any(|param| matches!(param, Param::Commands))
+        {
+            queries.push(names_query_builder(world, filtered_access));
         }
 
-        Ok(schedule_config)
+        let builder = DynamicParamsBuilder {
+            params: LocalBuilder(Vec::with_capacity(sys.params.len())),
+            input: LocalBuilder(input),
+            engine: ParamBuilder,
+            type_registry: ParamBuilder,
+            codec: ParamBuilder,
+            wasm_registry: ParamBuilder,
+            aliases: ParamBuilder,
+            function_index: ParamBuilder,
+            mod_exports: ParamBuilder,
+            tapes: ParamBuilder,
+            diagnostics: ParamBuilder,
+            commands: ParamBuilder,
+            change_tick: ParamBuilder,
+            hierarchy: ParamBuilder,
+            time: ParamBuilder,
+            resources,
+            queries: ParamSetBuilder(queries),
+        };
+
+        Ok((builder,).build_state(world))
     }
 }
 
 struct Input {
+    mod_id: Entity,
     mod_name: String,
+    mod_info: Option<ModInfo>,
+    storage: ModStorage,
+    memory_limits: Option<ModMemoryLimits>,
+    traps: ModTraps,
     system_name: String,
     built_params: Vec<BuiltParam>,
     query_resolver: QueryResolver,
     access: ModAccess,
     insert_despawn_component: InsertDespawnComponent,
+    spawns: Option<SandboxSpawns>,
+    permissions: ComponentPermissions,
+
+    /// Overrides the host's codec when the mod asked for a different one
+    codec: Option<CodecResource>,
+
+    /// Set when fuel is metered, see [Engine::fuel]
+    fuel: Option<ModFuel>,
+
+    /// The mod's frame budget and the number of this system in it, see [ModFrameBudgets](wasvy_runtime::budget::ModFrameBudgets)
+    budget: Option<(FrameBudget, usize)>,
+
+    /// Set when diagnostics are enabled, see [Engine::diagnostics]
+    diagnostics: Option<SystemDiagnostics>,
     instance_pre: InstancePre<WasmHost>,
+
+    /// Keeps the runner of this system between its runs, see [Runner::call_reused]
+    runners: ModRunners,
+
+    /// The failed commands of this system, lent to its runner for each run
+    command_errors: CommandErrors,
 }
 
 impl FromWorld for Input {
@@ -188,23 +484,120 @@ impl FromWorld for Input {
     }
 }
 
+/// The params of every mod system.
+///
 /// Since mod systems are by their very nature dynamic, they require a
 /// flexible dynamic equivalent at runtime that can adjust to access
 /// just what that mod system needs.
-fn dynamic_system(
-    input: Local<Input>,
-    mut params: Local<Vec<Val>>,
-    engine: Res<Engine>,
-    type_registry: Res<AppTypeRegistry>,
-    codec: Res<CodecResource>,
-    wasm_registry: Res<WasmComponentRegistry>,
-    function_index: Res<FunctionIndex>,
-    mut commands: Commands,
-    // TODO: mut resources: FilteredResourcesMut,
-    mut queries: ParamSet<Vec<Query<FilteredEntityMut>>>,
-) -> BevyResult {
-    let mut runner = Runner::new(&engine);
-    initialize_params(&mut params, &input.built_params, &mut runner)?;
+///
+/// Only `resources` and `queries` write to the world, and queries are limited to the entities of
+/// the system's [ModAccess]. Everything else is read-only or local to the system, so systems of
+/// different accesses never conflict and run in parallel.
+#[derive(SystemParam)]
+#[system_param(builder)]
+struct DynamicParams<'w, 's> {
+    input: Local<'s, Input>,
+    params: Local<'s, Vec<Val>>,
+    engine: Res<'w, Engine>,
+    type_registry: Res<'w, AppTypeRegistry>,
+    codec: Res<'w, CodecResource>,
+    wasm_registry: Res<'w, WasmComponentRegistry>,
+    aliases: Res<'w, TypeAliasRegistry>,
+    function_index: Res<'w, FunctionIndex>,
+    mod_exports: Res<'w, ModExports>,
+    tapes: (Option<Res<'w, ModRecorder>>, Option<Res<'w, ModReplay>>),
+    diagnostics: Option<Diagnostics<'w, 's>>,
+    commands: Commands<'w, 's>,
+    change_tick: SystemChangeTick,
+    hierarchy: HierarchyQuery<'w, 's>,
+    time: ModTime<'w>,
+    resources: FilteredResourcesMut<'w, 's>,
+    queries: Queries<'w, 's>,
+}
+
+/// Runs a mod system scheduled via `app.add-systems`
+fn dynamic_system(params: DynamicParams) -> BevyResult {
+    run_system(params, None)
+}
+
+/// Runs a mod system observing a component, see `system.observe`
+fn observer_system<E: EntityEvent>(on: On<E>, params: DynamicParams) -> BevyResult {
+    let entity = on.event_target();
+
+    // Observers see every entity, so skip those outside of the mod's access
+    if check_access(&params.hierarchy, &params.input.access, entity).is_err() {
+        return Ok(());
+    }
+
+    run_system(params, Some(entity))
+}
+
+fn run_system(params: DynamicParams, trigger: Option<Entity>) -> BevyResult {
+    let DynamicParams {
+        mut input,
+        mut params,
+        engine,
+        type_registry,
+        codec,
+        wasm_registry,
+        aliases,
+        function_index,
+        mod_exports,
+        tapes: (recorder, replay),
+        mut diagnostics,
+        mut commands,
+        change_tick,
+        hierarchy,
+        time,
+        mut resources,
+        mut queries,
+    } = params;
+    let input = &mut *input;
+
+    // The mod used up its fuel earlier this frame
+    if input
+        .fuel
+        .as_ref()
+        .is_some_and(|fuel| fuel.remaining() == 0)
+    {
+        return Ok(());
+    }
+
+    // The system's turn was deferred to a later frame
+    if let Some((budget, index)) = &input.budget
+        && !budget.should_run(*index)
+    {
+        return Ok(());
+    }
+
+    let mut runner = input
+        .runners
+        .take(&input.system_name, input.access)
+        .unwrap_or_else(|| Runner::new(&engine, &input.mod_name, input.memory_limits));
+    if let Some(fuel) = &input.fuel {
+        runner.set_fuel(fuel.remaining());
+    }
+    runner.set_tape(Tape::new(
+        recorder.as_deref(),
+        replay.as_deref(),
+        &input.mod_name,
+        &input.system_name,
+        input.access,
+    ));
+    runner.set_mod_info(input.mod_info.clone());
+    runner.set_storage(input.storage.clone());
+    runner.set_command_errors(input.command_errors.clone());
+    runner.set_time(time.info());
+    let codec = input.codec.as_ref().unwrap_or(&*codec);
+    initialize_params(
+        &mut params,
+        &mut input.built_params,
+        &resources,
+        &type_registry,
+        codec,
+        trigger,
+        &mut runner,
+    )?;
 
     trace!(
         "Running system \"{}\" from \"{}\"",
@@ -214,30 +607,298 @@ fn dynamic_system(
     let config = ConfigRunSystem {
         commands: &mut commands,
         type_registry: &type_registry,
-        codec: &codec,
+        codec,
         wasm_registry: &wasm_registry,
+        aliases: &aliases,
         function_index: &function_index,
         queries: &mut queries,
+        resources: &mut resources,
         query_resolver: &input.query_resolver,
         access: input.access,
         insert_despawn_component: input.insert_despawn_component,
+        spawns: input.spawns.clone(),
+        permissions: &input.permissions,
+        change_tick,
+        mod_exports: &mod_exports,
+        hierarchy: &hierarchy,
     };
-    call(
-        &mut runner,
+    let start = Instant::now();
+    let result = runner.call_reused(
         &input.instance_pre,
         Config::RunSystem(config),
         &input.system_name,
         &params[..],
         &mut [],
-    )?;
+    );
+    if let Some((budget, index)) = &input.budget {
+        budget.spend(*index, start.elapsed());
+    }
+
+    if let Some(system_diagnostics) = &mut input.diagnostics
+        && let Some(diagnostics) = &mut diagnostics
+    {
+        let trapped = result.as_ref().is_err_and(|err| !is_out_of_fuel(err));
+        system_diagnostics.record(diagnostics, start.elapsed(), trapped, runner.memory_size());
+    }
+
+    let exhausted = input.fuel.as_ref().is_some_and(|fuel| {
+        let consumed = runner.fuel_consumed().unwrap_or_default();
+        !fuel.consume(consumed)
+    });
+    if exhausted {
+        warn!(
+            "Mod \"{}\" ran out of fuel in system \"{}\", skipping its systems until next frame",
+            input.mod_name, input.system_name
+        );
+        commands.write_message(ModFuelExhausted {
+            mod_id: input.mod_id,
+            mod_name: input.mod_name.clone(),
+            system_name: input.system_name.clone(),
+        });
+    }
+
+    if let Err(err) = result {
+        // Running out of fuel or exceeding the sandbox quota is not an error, the mod just has to
+        // wait for the next frame
+        if is_out_of_fuel(&err) || is_quota_exceeded(&err) {
+            return Ok(());
+        }
+        let err = if is_interrupted(&err) {
+            anyhow!(
+                "System \"{}\" of mod \"{}\" was interrupted after exceeding its time budget of {:?}",
+                input.system_name,
+                input.mod_name,
+                engine.timeout().unwrap_or_default(),
+            )
+        } else {
+            err
+        };
+        commands.queue(ModTrapped {
+            mod_id: input.mod_id,
+            consecutive: input.traps.record(),
+            error: format!("{err:?}"),
+        });
+        return Err(err.into());
+    }
+    input.traps.reset();
+
+    // Keep the instance for the next run. On errors the runner is dropped instead, since
+    // an instance that trapped can't be entered again.
+    input
+        .runners
+        .give_back(&input.system_name, input.access, runner);
 
     Ok(())
 }
 
+/// Marks the observers of a mod, which are despawned when it is set up again or despawned
+#[derive(Component)]
+pub(crate) struct ModObserver {
+    mod_id: Entity,
+    access: ModAccess,
+}
+
+/// Despawns the observers a mod added for `accesses`, or for all of its accesses
+pub(crate) fn despawn_observers(world: &mut World, mod_id: Entity, accesses: Option<&[ModAccess]>) {
+    let mut observers = world.query::<(Entity, &ModObserver)>();
+    let despawn: Vec<_> = observers
+        .iter(world)
+        .filter(|(_, observer)| {
+            observer.mod_id == mod_id
+                && accesses.is_none_or(|accesses| accesses.contains(&observer.access))
+        })
+        .map(|(entity, _)| entity)
+        .collect();
+    for entity in despawn {
+        world.despawn(entity);
+    }
+}
+
+/// The params needed to run a mod's `teardown` export, see [with_system_config]
+type TeardownParams = (
+    Commands<'static, 'static>,
+    Res<'static, AppTypeRegistry>,
+    Res<'static, CodecResource>,
+    Res<'static, WasmComponentRegistry>,
+    Res<'static, TypeAliasRegistry>,
+    Res<'static, FunctionIndex>,
+    Res<'static, ModExports>,
+    ParamSet<'static, 'static, Vec<Query<'static, 'static, FilteredEntityMut<'static, 'static>>>>,
+    FilteredResourcesMut<'static, 'static>,
+    SystemChangeTick,
+    HierarchyQuery<'static, 'static>,
+);
+
+/// Calls a mod's `teardown` export, passing it commands that are applied right after.
+///
+/// Unlike regular systems this runs once, right when the mod is despawned.
+///
+/// Mods without any access still get to clean up, but can't use the commands they are passed.
+pub(crate) fn teardown(
+    world: &mut World,
+    mod_name: &str,
+    access: Option<ModAccess>,
+    permissions: &ComponentPermissions,
+    instance_pre: &InstancePre<WasmHost>,
+) -> Result<()> {
+    let engine = world
+        .get_resource::<Engine>()
+        .expect("Engine should never be removed from world");
+    // The mod is gone, so its own limits are too
+    let mut runner = Runner::new(engine, mod_name, None);
+
+    trace!("Tearing down \"{mod_name}\"");
+
+    let Some(access) = access else {
+        let commands = Val::Resource(runner.new_resource(WasmCommands)?);
+        return call(
+            &mut runner,
+            instance_pre,
+            Config::Call,
+            "teardown",
+            &[commands],
+            &mut [],
+        );
+    };
+
+    run_once(
+        world,
+        &mut runner,
+        "teardown",
+        Vec::new(),
+        access,
+        permissions,
+        // The mod is gone, so there's nothing to tie spawned entities to
+        InsertDespawnComponent::disabled(),
+        instance_pre,
+    )
+}
+
+/// Calls an export of a mod taking commands followed by `params`, applying the commands right
+/// after. See [teardown] and [ModCallbacks](wasvy_runtime::callbacks::ModCallbacks).
+pub(crate) fn run_once(
+    world: &mut World,
+    runner: &mut Runner,
+    function: &str,
+    params: Vec<Val>,
+    access: ModAccess,
+    permissions: &ComponentPermissions,
+    insert_despawn_component: InsertDespawnComponent,
+    instance_pre: &InstancePre<WasmHost>,
+) -> Result<()> {
+    let params: Vec<_> = std::iter::once(Val::Resource(runner.new_resource(WasmCommands)?))
+        .chain(params)
+        .collect();
+    with_system_config(
+        world,
+        &[],
+        access,
+        permissions,
+        insert_despawn_component,
+        |config| {
+            call(
+                runner,
+                instance_pre,
+                Config::RunSystem(config),
+                function,
+                &params,
+                &mut [],
+            )
+        },
+    )
+}
+
+/// Runs `f` with what a mod system with `system_params` may access, applying its commands right
+/// after. See [run_once].
+pub(crate) fn with_system_config<F, R>(
+    world: &mut World,
+    system_params: &[Param],
+    access: ModAccess,
+    permissions: &ComponentPermissions,
+    insert_despawn_component: InsertDespawnComponent,
+    f: F,
+) -> Result<R>
+where
+    F: FnOnce(ConfigRunSystem) -> Result<R>,
+{
+    let query_resolver = QueryResolver::new(system_params, permissions, world)?;
+    let spawns = SandboxSpawns::of(&access, world);
+    let filtered_access = access.filtered_access(world);
+
+    let mut queries = Vec::new();
+    for items in system_params.iter().filter_map(Param::filter_query) {
+        queries.push(create_query_builder(
+            items,
+            permissions,
+            world,
+            filtered_access.clone(),
+        )?);
+    }
+    queries.push(names_query_builder(world, filtered_access));
+
+    let mut state: SystemState<TeardownParams> = (
+        ParamBuilder,
+        ParamBuilder,
+        ParamBuilder,
+        ParamBuilder,
+        ParamBuilder,
+        ParamBuilder,
+        ParamBuilder,
+        ParamSetBuilder(queries),
+        resources_builder(&[], &query_resolver),
+        ParamBuilder,
+        ParamBuilder,
+    )
+        .build_state(world);
+    let (
+        mut commands,
+        type_registry,
+        codec,
+        wasm_registry,
+        aliases,
+        function_index,
+        mod_exports,
+        mut queries,
+        mut resources,
+        change_tick,
+        hierarchy,
+    ) = state.get_mut(world).expect("valid system parameter");
+
+    let config = ConfigRunSystem {
+        commands: &mut commands,
+        type_registry: &type_registry,
+        codec: &codec,
+        wasm_registry: &wasm_registry,
+        aliases: &aliases,
+        function_index: &function_index,
+        queries: &mut queries,
+        resources: &mut resources,
+        query_resolver: &query_resolver,
+        access,
+        insert_despawn_component,
+        spawns,
+        permissions,
+        change_tick,
+        mod_exports: &mod_exports,
+        hierarchy: &hierarchy,
+    };
+    let result = f(config)?;
+
+    state.apply(world);
+    Ok(result)
+}
+
 /// A system param (what a mod system requests as parameters)
 pub(crate) enum Param {
     Commands,
     Query(Vec<QueryFor>),
+    Events(TypePath),
+
+    /// The entity an observer was triggered for, see `system.observe`
+    Trigger,
+
+    /// A resource of the host and whether it is mutable, see `system.add-resource`
+    Resource(TypePath, bool),
 }
 
 impl Param {
@@ -253,31 +914,212 @@ impl Param {
 enum BuiltParam {
     Commands,
     Query(QueryId),
+    Events(EventReaderState),
+    Trigger,
+    Resource(ResourceRef, bool),
 }
 
 impl BuiltParam {
-    fn new_vec(params: &[Param]) -> Vec<Self> {
+    fn new_vec(
+        params: &[Param],
+        permissions: &ComponentPermissions,
+        world: &mut World,
+    ) -> Result<Vec<Self>> {
         let mut ids = QueryIdGenerator::default();
         params
             .iter()
-            .map(|param| match param {
-                Param::Commands => BuiltParam::Commands,
-                Param::Query(_) => BuiltParam::Query(ids.generate()),
+            .map(|param| {
+                Ok(match param {
+                    Param::Commands => BuiltParam::Commands,
+                    Param::Query(_) => BuiltParam::Query(ids.generate()),
+                    Param::Events(type_path) => {
+                        BuiltParam::Events(EventReaderState::new(type_path, world)?)
+                    }
+                    Param::Trigger => BuiltParam::Trigger,
+                    Param::Resource(type_path, mutable) => BuiltParam::Resource(
+                        resource_ref(type_path, *mutable, permissions, world)?,
+                        *mutable,
+                    ),
+                })
             })
             .collect()
     }
 }
 
+/// The events a mod system reads, along with the cursor of the events it has already seen
+struct EventReaderState {
+    reflect: ReflectModMessage,
+    messages_id: ComponentId,
+    cursor: ModMessageCursor,
+}
+
+impl EventReaderState {
+    fn new(type_path: &str, world: &World) -> Result<Self> {
+        let type_registry = world
+            .get_resource::<AppTypeRegistry>()
+            .expect("there to be an AppTypeRegistry")
+            .read();
+
+        let reflect = type_registry
+            .get_with_type_path(type_path)
+            .and_then(|registration| registration.data::<ReflectModMessage>())
+            .ok_or_else(|| anyhow!("{type_path} is not an event that mods can read. See AppModMessageExt::add_mod_message"))?
+            .clone();
+        let messages_id = reflect
+            .messages_id(world)
+            .ok_or_else(|| anyhow!("Event {type_path} has not been added to the app"))?;
+        let cursor = reflect.new_cursor();
+
+        Ok(Self {
+            reflect,
+            messages_id,
+            cursor,
+        })
+    }
+
+    /// Serializes the events that have not been read yet
+    fn read(
+        &mut self,
+        resources: &FilteredResourcesMut,
+        type_registry: &AppTypeRegistry,
+        codec: &CodecResource,
+    ) -> Result<Vec<Vec<u8>>> {
+        let messages = resources.get_by_id(self.messages_id)?;
+        let type_registry = type_registry.read();
+
+        let mut events = Vec::new();
+        let mut result = Ok(());
+        // SAFETY: messages_id and the cursor both come from self.reflect
+        unsafe {
+            self.reflect
+                .read(messages, &mut self.cursor, &mut |event| match codec
+                    .encode_reflect(event.as_partial_reflect(), &type_registry)
+                {
+                    Ok(event) => events.push(event),
+                    Err(err) => result = Err(err),
+                });
+        }
+
+        result.map(|_| events)
+    }
+}
+
+/// Orders the systems of a mod for [ModConcurrency], along with whether each may join a lane.
+///
+/// Lanes chain each system after the one assigned before it, so systems are returned in an order
+/// that respects their `after` constraints. Systems ordered against a host set, directly or through
+/// the mod's own ordering, stay out of lanes, since chaining them after the system of another mod
+/// could contradict the order of the host sets.
+fn lane_order<S>(systems: Vec<(S, &WasmSystem)>) -> Vec<(S, &WasmSystem, bool)> {
+    let index: HashMap<_, _> = systems
+        .iter()
+        .enumerate()
+        .map(|(i, (_, system))| (system.id, i))
+        .collect();
+    let before = |i: usize| {
+        systems[i]
+            .1
+            .after
+            .iter()
+            .filter_map(|id| index.get(id).copied())
+    };
+
+    // Kahn's algorithm, keeping the order systems were added in where they're unrelated. Systems
+    // in a cycle are left in that order, building the schedule reports the cycle
+    let mut pending: Vec<_> = (0..systems.len()).map(|i| before(i).count()).collect();
+    let mut after = vec![Vec::new(); systems.len()];
+    for i in 0..systems.len() {
+        for j in before(i) {
+            after[j].push(i);
+        }
+    }
+    let mut order = Vec::with_capacity(systems.len());
+    let mut placed = vec![false; systems.len()];
+    while order.len() < systems.len() {
+        let next = (0..systems.len())
+            .find(|&i| !placed[i] && pending[i] == 0)
+            .or_else(|| (0..systems.len()).find(|&i| !placed[i]))
+            .expect("a system is left");
+        placed[next] = true;
+        order.push(next);
+        for &j in &after[next] {
+            pending[j] = pending[j].saturating_sub(1);
+        }
+    }
+
+    let mut after_sets = vec![false; systems.len()];
+    for &i in &order {
+        after_sets[i] = !systems[i].1.after_sets.is_empty() || before(i).any(|j| after_sets[j]);
+    }
+    let mut before_sets = vec![false; systems.len()];
+    for &i in order.iter().rev() {
+        before_sets[i] =
+            !systems[i].1.before_sets.is_empty() || after[i].iter().any(|&j| before_sets[j]);
+    }
+
+    let mut systems: Vec<_> = systems.into_iter().map(Some).collect();
+    order
+        .into_iter()
+        .map(|i| {
+            let (schedule, system) = systems[i].take().expect("each system is placed once");
+            (schedule, system, !after_sets[i] && !before_sets[i])
+        })
+        .collect()
+}
+
+/// The resources system param, which grants access to the resources declared in queries and
+/// `res` params, and to the events read by the system
+fn resources_builder(
+    built_params: &[BuiltParam],
+    query_resolver: &QueryResolver,
+) -> FilteredResourcesMutParamBuilder<Box<dyn FnOnce(&mut FilteredResourcesMutBuilder)>> {
+    let mut access: Vec<_> = query_resolver.resource_access().collect();
+    for param in built_params {
+        match param {
+            BuiltParam::Events(state) => access.push((state.messages_id, false)),
+            BuiltParam::Resource(resource, mutable) => {
+                access.push((resource.component_id(), *mutable))
+            }
+            _ => {}
+        }
+    }
+
+    FilteredResourcesMutParamBuilder::new_box(move |builder| {
+        for (component_id, mutable) in access {
+            if mutable {
+                builder.add_write_by_id(component_id);
+            } else {
+                builder.add_read_by_id(component_id);
+            }
+        }
+    })
+}
+
 fn initialize_params(
     params: &mut Vec<Val>,
-    source: &[BuiltParam],
+    source: &mut [BuiltParam],
+    resources: &FilteredResourcesMut,
+    type_registry: &AppTypeRegistry,
+    codec: &CodecResource,
+    trigger: Option<Entity>,
     runner: &mut Runner,
 ) -> Result<()> {
     params.clear();
-    for param in source.iter() {
+    for param in source.iter_mut() {
         let resource = match param {
             BuiltParam::Commands => runner.new_resource(WasmCommands),
             BuiltParam::Query(id) => runner.new_resource(WasmQuery::new(*id)),
+            BuiltParam::Events(state) => {
+                let events = state.read(resources, type_registry, codec)?;
+                runner.new_resource(WasmEventReader::new(events))
+            }
+            BuiltParam::Trigger => {
+                let entity = trigger.ok_or_else(|| anyhow!("Only observers have a trigger"))?;
+                runner.new_resource(WasmEntity::from(entity))
+            }
+            BuiltParam::Resource(resource, mutable) => {
+                runner.new_resource(WasmRes::new(resource.clone(), *mutable))
+            }
         }?;
         params.push(Val::Resource(resource));
     }
@@ -321,3 +1163,302 @@ impl SystemSet for DynamicSystemId {
 /// An tracker to ensure unique [DynamicSystemId]s in the world
 #[derive(Default, BevyResource)]
 struct DynamicSystemSetCount(usize);
+
+#[cfg(test)]
+mod tests {
+    use bevy_ecs::schedule::{LogLevel, ScheduleBuildSettings};
+    use bevy_reflect::{Reflect, TypePath as _};
+    use wasmtime::component::Component as WasmtimeComponent;
+    use wasvy_runtime::{sandbox::Sandbox, schedule::ModSchedules};
+
+    use std::num::NonZeroUsize;
+
+    use bevy_app::Update;
+
+    use super::*;
+    use crate::{engine::Linker, test_utils};
+
+    #[derive(Component, Reflect)]
+    #[reflect(Component)]
+    struct Health(u32);
+
+    #[derive(Resource, Reflect, Default)]
+    #[reflect(Resource)]
+    struct Score(u32);
+
+    /// The world of [test_utils::world] with [Health] and [Score]
+    fn world() -> World {
+        let mut world = test_utils::world();
+        world.register_component::<Health>();
+        world.init_resource::<Score>();
+        let mut type_registry = world.resource::<AppTypeRegistry>().write();
+        type_registry.register::<Health>();
+        type_registry.register::<Score>();
+        drop(type_registry);
+        world
+    }
+
+    /// A system of an empty mod, querying [Health] mutably
+    fn system(world: &mut World, access: ModAccess) -> ScheduleConfigs<BoxedSystem> {
+        let params = vec![
+            Param::Commands,
+            Param::Query(vec![QueryFor::Mut(Health::type_path().to_string())]),
+        ];
+        system_with_params(world, access, params)
+    }
+
+    fn system_with_params(
+        world: &mut World,
+        access: ModAccess,
+        params: Vec<Param>,
+    ) -> ScheduleConfigs<BoxedSystem> {
+        let mod_id = world.spawn_empty().id();
+        schedule(world, mod_id, access, params).unwrap()
+    }
+
+    fn schedule(
+        world: &mut World,
+        mod_id: Entity,
+        access: ModAccess,
+        params: Vec<Param>,
+    ) -> Result<ScheduleConfigs<BoxedSystem>> {
+        let instance_pre = instance_pre(world);
+        let mut system = WasmSystem::new("update".to_string(), world);
+        system.params = params;
+        AddSystems::schedule(
+            &system,
+            world,
+            mod_id,
+            "mod",
+            &access,
+            ModCodec::Host,
+            instance_pre,
+        )
+    }
+
+    /// The smallest valid component
+    fn instance_pre(world: &World) -> InstancePre<WasmHost> {
+        let engine = world.resource::<Engine>().inner().clone();
+        let component = WasmtimeComponent::new(&engine, b"\0asm\x0d\0\x01\0").unwrap();
+        Linker::new(&engine).instantiate_pre(&component).unwrap()
+    }
+
+    #[test]
+    fn systems_of_different_accesses_run_in_parallel() {
+        let mut world = world();
+        let sandbox = Sandbox::new(&mut world, ModSchedules::empty());
+        let sandbox = world.spawn(sandbox).id();
+        let other_sandbox = Sandbox::new(&mut world, ModSchedules::empty());
+        let other_sandbox = world.spawn(other_sandbox).id();
+
+        let mut schedule = bevy_ecs::schedule::Schedule::default();
+        schedule.set_build_settings(ScheduleBuildSettings {
+            ambiguity_detection: LogLevel::Error,
+            ..Default::default()
+        });
+        for access in [
+            ModAccess::World,
+            ModAccess::Sandbox(sandbox),
+            ModAccess::Sandbox(other_sandbox),
+        ] {
+            let system = system(&mut world, access);
+            schedule.add_systems(system);
+        }
+
+        // Fails if any of the systems conflict
+        schedule.initialize(&mut world).unwrap();
+    }
+
+    #[test]
+    fn lanes_follow_the_order_of_mod_systems() {
+        let mut world = world();
+        world.init_resource::<Schedules>();
+        world.insert_resource(ModConcurrency::new(NonZeroUsize::MIN));
+        let mod_id = world.spawn_empty().id();
+        let instance_pre = instance_pre(&world);
+
+        // Added first, but runs after b
+        let mut a = WasmSystem::new("a".to_string(), &mut world);
+        let b = WasmSystem::new("b".to_string(), &mut world);
+        a.after.push(b.id);
+
+        let systems = lane_order(vec![((), &a), ((), &b)]);
+        let names: Vec<_> = systems.iter().map(|(_, system, _)| &system.name).collect();
+        assert_eq!(names, ["b", "a"]);
+        for (_, system, lane) in systems {
+            AddSystems::add_system(
+                Update,
+                system,
+                lane,
+                &mut world,
+                mod_id,
+                "mod",
+                &ModAccess::World,
+                ModCodec::Host,
+                &instance_pre,
+            )
+            .unwrap();
+        }
+
+        // Fails if chaining the lane contradicts the order of the systems
+        let mut schedule = world.resource_mut::<Schedules>().remove(Update).unwrap();
+        schedule.initialize(&mut world).unwrap();
+    }
+
+    #[test]
+    fn systems_ordered_against_host_sets_stay_out_of_lanes() {
+        #[derive(SystemSet, Clone, Debug, PartialEq, Eq, Hash)]
+        struct Physics;
+
+        let mut world = world();
+        let mut system = |name: &str| WasmSystem::new(name.to_string(), &mut world);
+        let (mut before, mut after_before, mut after) = (system("1"), system("2"), system("3"));
+        let (mut after_after, ahead_of_before) = (system("4"), system("5"));
+        before.before_sets.push(Physics.intern());
+        before.after.push(ahead_of_before.id);
+        after_before.after.push(before.id);
+        after.after_sets.push(Physics.intern());
+        after_after.after.push(after.id);
+
+        let systems = [
+            &before,
+            &after_before,
+            &after,
+            &after_after,
+            &ahead_of_before,
+        ];
+        let lanes: Vec<_> = lane_order(systems.map(|system| ((), system)).to_vec())
+            .into_iter()
+            .map(|(_, system, lane)| (system.name.as_str(), lane))
+            .collect();
+        assert_eq!(
+            lanes,
+            [
+                ("3", false),
+                ("4", false),
+                ("5", false),
+                ("1", false),
+                ("2", true),
+            ]
+        );
+    }
+
+    #[test]
+    fn systems_of_the_same_access_conflict() {
+        let mut world = world();
+
+        let mut schedule = bevy_ecs::schedule::Schedule::default();
+        schedule.set_build_settings(ScheduleBuildSettings {
+            ambiguity_detection: LogLevel::Error,
+            ..Default::default()
+        });
+        for _ in 0..2 {
+            let system = system(&mut world, ModAccess::World);
+            schedule.add_systems(system);
+        }
+
+        assert!(schedule.initialize(&mut world).is_err());
+    }
+
+    /// Two systems with a `res` param for [Score], whose schedule only initializes if they don't conflict
+    fn initialize_resource_systems(mutable: bool) -> bool {
+        let mut world = world();
+
+        let mut schedule = bevy_ecs::schedule::Schedule::default();
+        schedule.set_build_settings(ScheduleBuildSettings {
+            ambiguity_detection: LogLevel::Error,
+            ..Default::default()
+        });
+        for _ in 0..2 {
+            let params = vec![Param::Resource(Score::type_path().to_string(), mutable)];
+            let system = system_with_params(&mut world, ModAccess::World, params);
+            schedule.add_systems(system);
+        }
+
+        schedule.initialize(&mut world).is_ok()
+    }
+
+    #[test]
+    fn systems_may_change_names() {
+        let mut world = world();
+        world.register_component::<Name>();
+        world
+            .resource::<AppTypeRegistry>()
+            .write()
+            .register::<Name>();
+
+        // The query of commands.find-by-name must not conflict with the mod's own queries
+        let params = vec![
+            Param::Commands,
+            Param::Query(vec![QueryFor::Mut(Name::type_path().to_string())]),
+        ];
+        let mut schedule = bevy_ecs::schedule::Schedule::default();
+        schedule.add_systems(system_with_params(&mut world, ModAccess::World, params));
+        schedule.initialize(&mut world).unwrap();
+    }
+
+    #[test]
+    fn only_systems_with_commands_read_names() {
+        fn rename(mut names: Query<&mut Name>) {
+            for mut name in &mut names {
+                name.set("renamed");
+            }
+        }
+
+        let conflicts = |params: Vec<Param>| {
+            let mut world = world();
+            let mut schedule = bevy_ecs::schedule::Schedule::default();
+            schedule.set_build_settings(ScheduleBuildSettings {
+                ambiguity_detection: LogLevel::Error,
+                ..Default::default()
+            });
+            schedule.add_systems(system_with_params(&mut world, ModAccess::World, params));
+            schedule.add_systems(rename);
+            schedule.initialize(&mut world).is_err()
+        };
+
+        assert!(conflicts(vec![Param::Commands]));
+        assert!(!conflicts(vec![Param::Resource(
+            Score::type_path().to_string(),
+            false
+        )]));
+    }
+
+    #[test]
+    fn systems_reading_a_resource_run_in_parallel() {
+        assert!(initialize_resource_systems(false));
+    }
+
+    #[test]
+    fn systems_writing_a_resource_conflict() {
+        assert!(!initialize_resource_systems(true));
+    }
+
+    #[test]
+    fn resources_need_permission() {
+        let mut world = world();
+        let permissions =
+            ComponentPermissions::default().deny(ComponentAccess::Mutate, [Score::type_path()]);
+        let mod_id = world.spawn(permissions).id();
+        let score = || Score::type_path().to_string();
+
+        let allowed = [
+            Param::Resource(score(), false),
+            Param::Query(vec![QueryFor::Resource(score())]),
+        ];
+        for param in allowed {
+            assert!(schedule(&mut world, mod_id, ModAccess::World, vec![param]).is_ok());
+        }
+
+        let denied = [
+            Param::Resource(score(), true),
+            Param::Query(vec![QueryFor::ResourceMut(score())]),
+        ];
+        for param in denied {
+            let Err(err) = schedule(&mut world, mod_id, ModAccess::World, vec![param]) else {
+                panic!("mutating the resource must be denied");
+            };
+            assert!(err.to_string().contains("not permitted to mutate"), "{err}");
+        }
+    }
+}
@@ -0,0 +1,402 @@
+//! Helpers for tests that need a real mod, or a host in the middle of running one.
+
+use bevy_ecs::{
+    prelude::*,
+    reflect::{AppFunctionRegistry, AppTypeRegistry},
+};
+use wasm_encoder::{
+    BlockType, CodeSection, ComponentBuilder, ComponentExportKind, ComponentTypeRef,
+    ComponentValType, ConstExpr, EntityType, ExportKind, ExportSection, Function, FunctionSection,
+    GlobalSection, GlobalType, ImportSection, InstanceType, Module, ModuleArg, PrimitiveValType,
+    TypeBounds, TypeSection, ValType,
+};
+use wasmtime::component::Component;
+use wasvy_runtime::{
+    access::ModAccess,
+    component::{TypeAliasRegistry, WasmComponentRegistry},
+    methods::FunctionIndex,
+    mods::InsertDespawnComponent,
+    permissions::ComponentPermissions,
+    sandbox::Sandboxed,
+    serialize::CodecResource,
+};
+
+use crate::{
+    engine::Engine,
+    exports::ModExports,
+    host::{CommandErrors, WasmHost},
+    runner::{Config, Runner},
+    system::{Param, with_system_config},
+    version::{WIT_PACKAGE, WIT_VERSION},
+};
+
+/// A world with the resources mod systems need. Call [FunctionIndex::build] again after
+/// registering types with methods.
+pub(crate) fn world() -> World {
+    let mut world = World::new();
+    world.register_component::<Sandboxed>();
+    world.init_resource::<AppTypeRegistry>();
+    let function_index = FunctionIndex::build(
+        world.resource::<AppTypeRegistry>(),
+        &AppFunctionRegistry::default(),
+    );
+    world.insert_resource(function_index);
+    world.init_resource::<Engine>();
+    world.init_resource::<CodecResource>();
+    world.init_resource::<WasmComponentRegistry>();
+    world.init_resource::<TypeAliasRegistry>();
+    world.init_resource::<ModExports>();
+    world
+}
+
+/// Runs `f` with the host of a mod in the middle of running a system with `system_params`, then
+/// applies the commands it queued
+pub(crate) fn run_system<F, R>(
+    world: &mut World,
+    system_params: &[Param],
+    permissions: &ComponentPermissions,
+    f: F,
+) -> R
+where
+    F: FnOnce(&mut WasmHost) -> R,
+{
+    let command_errors = CommandErrors::default();
+    run_system_with_errors(world, system_params, permissions, &command_errors, f)
+}
+
+/// Like [run_system], for a system whose failed commands are kept in `command_errors`
+pub(crate) fn run_system_with_errors<F, R>(
+    world: &mut World,
+    system_params: &[Param],
+    permissions: &ComponentPermissions,
+    command_errors: &CommandErrors,
+    f: F,
+) -> R
+where
+    F: FnOnce(&mut WasmHost) -> R,
+{
+    let mut runner = Runner::new(world.resource::<Engine>(), "my-mod", None);
+    runner.set_command_errors(command_errors.clone());
+    let mut f = Some(f);
+    with_system_config(
+        world,
+        system_params,
+        ModAccess::World,
+        permissions,
+        InsertDespawnComponent::disabled(),
+        |config| {
+            Ok(runner.use_store(Config::RunSystem(config), |store| {
+                f.take().expect("a single call")(store.data_mut())
+            }))
+        },
+    )
+    .expect("valid system params")
+}
+
+/// Builds a component exporting functions without params or results, each running the body of a
+/// core function. The bodies share a mutable `i32` global, which starts at 0, see [set_global].
+pub(crate) fn component(engine: &wasmtime::Engine, exports: &[(&str, Function)]) -> Component {
+    Component::new(engine, component_bytes(exports)).expect("a valid component")
+}
+
+/// The wasm file of a [component]
+pub(crate) fn component_bytes(exports: &[(&str, Function)]) -> Vec<u8> {
+    let mut types = TypeSection::new();
+    types.ty().function([], []);
+    let mut globals = GlobalSection::new();
+    globals.global(
+        GlobalType {
+            val_type: ValType::I32,
+            mutable: true,
+            shared: false,
+        },
+        &ConstExpr::i32_const(0),
+    );
+    let mut functions = FunctionSection::new();
+    let mut export_section = ExportSection::new();
+    let mut code = CodeSection::new();
+    for (index, (name, body)) in exports.iter().enumerate() {
+        functions.function(0);
+        export_section.export(name, ExportKind::Func, index as u32);
+        code.function(body);
+    }
+    let mut module = Module::new();
+    module
+        .section(&types)
+        .section(&functions)
+        .section(&globals)
+        .section(&export_section)
+        .section(&code);
+
+    let mut builder = ComponentBuilder::default();
+    let module = builder.core_module(None, &module);
+    let instance = builder.core_instantiate(None, module, []);
+    let (ty, mut func_type) = builder.type_function(None);
+    func_type
+        .params([] as [(&str, ComponentValType); 0])
+        .result(None);
+    for (name, _) in exports {
+        let core_func = builder.core_alias_export(None, instance, name, ExportKind::Func);
+        let func = builder.lift_func(None, core_func, ty, []);
+        builder.export(*name, ComponentExportKind::Func, func, None);
+    }
+
+    builder.finish()
+}
+
+/// The body of a function that returns right away
+pub(crate) fn noop() -> Function {
+    let mut body = Function::new([]);
+    body.instructions().end();
+    body
+}
+
+/// The body of a function that sets the global of its [component] to 1
+pub(crate) fn set_global() -> Function {
+    let mut body = Function::new([]);
+    body.instructions().i32_const(1).global_set(0).end();
+    body
+}
+
+/// The body of a function that traps if the global of its [component] was set
+pub(crate) fn trap_if_global_set() -> Function {
+    let mut body = Function::new([]);
+    body.instructions()
+        .global_get(0)
+        .if_(BlockType::Empty)
+        .unreachable()
+        .end()
+        .end();
+    body
+}
+
+/// Builds a component exporting `name: func(commands: commands)`, which spawns an empty entity with
+/// the commands each time it is called.
+pub(crate) fn spawning_component(engine: &wasmtime::Engine, name: &str) -> Component {
+    let mut builder = ComponentBuilder::default();
+
+    // The part of the app interface the component imports
+    let mut app = InstanceType::new();
+    app.export("commands", ComponentTypeRef::Type(TypeBounds::SubResource));
+    app.export(
+        "entity-commands",
+        ComponentTypeRef::Type(TypeBounds::SubResource),
+    );
+    app.ty().defined_type().borrow(0);
+    app.ty().defined_type().own(1);
+    app.ty()
+        .function()
+        .params([("self", ComponentValType::Type(2))])
+        .result(Some(ComponentValType::Type(3)));
+    app.export("[method]commands.spawn-empty", ComponentTypeRef::Func(4));
+    let app_type = builder.type_instance(None, &app);
+    let app = builder.import(
+        format!("{WIT_PACKAGE}/app@{WIT_VERSION}").as_str(),
+        ComponentTypeRef::Instance(app_type),
+    );
+    let commands = builder.alias_export(app, "commands", ComponentExportKind::Type);
+    let spawn_empty = builder.alias_export(
+        app,
+        "[method]commands.spawn-empty",
+        ComponentExportKind::Func,
+    );
+    let spawn_empty = builder.lower_func(None, spawn_empty, []);
+    let host =
+        builder.core_instantiate_exports(None, [("spawn-empty", ExportKind::Func, spawn_empty)]);
+
+    // Calls spawn-empty with the commands it is passed, dropping the entity commands
+    let mut types = TypeSection::new();
+    types.ty().function([ValType::I32], [ValType::I32]);
+    types.ty().function([ValType::I32], []);
+    let mut imports = ImportSection::new();
+    imports.import("host", "spawn-empty", EntityType::Function(0));
+    let mut functions = FunctionSection::new();
+    functions.function(1);
+    let mut exports = ExportSection::new();
+    exports.export(name, ExportKind::Func, 1);
+    let mut body = Function::new([]);
+    body.instructions().local_get(0).call(0).drop().end();
+    let mut code = CodeSection::new();
+    code.function(&body);
+    let mut module = Module::new();
+    module
+        .section(&types)
+        .section(&imports)
+        .section(&functions)
+        .section(&exports)
+        .section(&code);
+    let module = builder.core_module(None, &module);
+    let instance = builder.core_instantiate(None, module, [("host", ModuleArg::Instance(host))]);
+
+    let (own_commands, own) = builder.type_defined(None);
+    own.own(commands);
+    let (ty, mut func_type) = builder.type_function(None);
+    func_type
+        .params([("commands", ComponentValType::Type(own_commands))])
+        .result(None);
+    let core_func = builder.core_alias_export(None, instance, name, ExportKind::Func);
+    let func = builder.lift_func(None, core_func, ty, []);
+    builder.export(name, ComponentExportKind::Func, func, None);
+
+    Component::new(engine, builder.finish()).expect("a valid component")
+}
+
+/// The wasm file of a mod whose `setup: func(app: app)` export does nothing
+pub(crate) fn empty_mod() -> Vec<u8> {
+    let mut builder = ComponentBuilder::default();
+
+    let mut app = InstanceType::new();
+    app.export("app", ComponentTypeRef::Type(TypeBounds::SubResource));
+    let app_type = builder.type_instance(None, &app);
+    let app = builder.import(
+        format!("{WIT_PACKAGE}/app@{WIT_VERSION}").as_str(),
+        ComponentTypeRef::Instance(app_type),
+    );
+    let app = builder.alias_export(app, "app", ComponentExportKind::Type);
+
+    let mut types = TypeSection::new();
+    types.ty().function([ValType::I32], []);
+    let mut functions = FunctionSection::new();
+    functions.function(0);
+    let mut exports = ExportSection::new();
+    exports.export("setup", ExportKind::Func, 0);
+    let mut body = Function::new([]);
+    body.instructions().end();
+    let mut code = CodeSection::new();
+    code.function(&body);
+    let mut module = Module::new();
+    module
+        .section(&types)
+        .section(&functions)
+        .section(&exports)
+        .section(&code);
+    let module = builder.core_module(None, &module);
+    let instance = builder.core_instantiate(None, module, []);
+
+    let (own_app, own) = builder.type_defined(None);
+    own.own(app);
+    let (ty, mut func_type) = builder.type_function(None);
+    func_type
+        .params([("app", ComponentValType::Type(own_app))])
+        .result(None);
+    let core_func = builder.core_alias_export(None, instance, "setup", ExportKind::Func);
+    let func = builder.lift_func(None, core_func, ty, []);
+    builder.export("setup", ComponentExportKind::Func, func, None);
+
+    builder.finish()
+}
+
+/// Builds a component exporting `counter: func() -> u32` from the `test:plug/counter` interface,
+/// which returns how many times it was called. See [counter_user].
+pub(crate) fn counter_plug(engine: &wasmtime::Engine) -> Component {
+    let mut types = TypeSection::new();
+    types.ty().function([], [ValType::I32]);
+    let mut globals = GlobalSection::new();
+    globals.global(
+        GlobalType {
+            val_type: ValType::I32,
+            mutable: true,
+            shared: false,
+        },
+        &ConstExpr::i32_const(0),
+    );
+    let mut functions = FunctionSection::new();
+    functions.function(0);
+    let mut exports = ExportSection::new();
+    exports.export("counter", ExportKind::Func, 0);
+    let mut body = Function::new([]);
+    body.instructions()
+        .global_get(0)
+        .i32_const(1)
+        .i32_add()
+        .global_set(0)
+        .global_get(0)
+        .end();
+    let mut code = CodeSection::new();
+    code.function(&body);
+    let mut module = Module::new();
+    module
+        .section(&types)
+        .section(&functions)
+        .section(&globals)
+        .section(&exports)
+        .section(&code);
+
+    // The interface is the instance of a nested component, as composed components have them
+    let mut inner = ComponentBuilder::default();
+    let module = inner.core_module(None, &module);
+    let instance = inner.core_instantiate(None, module, []);
+    let (ty, mut func_type) = inner.type_function(None);
+    func_type
+        .params([] as [(&str, ComponentValType); 0])
+        .result(Some(ComponentValType::Primitive(PrimitiveValType::U32)));
+    let core_func = inner.core_alias_export(None, instance, "counter", ExportKind::Func);
+    let func = inner.lift_func(None, core_func, ty, []);
+    inner.export("counter", ComponentExportKind::Func, func, None);
+
+    let mut builder = ComponentBuilder::default();
+    let inner = builder.component(None, inner);
+    let instance = builder.instantiate(None, inner, [] as [(&str, ComponentExportKind, u32); 0]);
+    builder.export(
+        "test:plug/counter",
+        ComponentExportKind::Instance,
+        instance,
+        None,
+    );
+
+    Component::new(engine, builder.finish()).expect("a valid component")
+}
+
+/// Builds a component importing the interface exported by [counter_plug], and exporting
+/// `run: func() -> u32`, which returns the result of calling `counter`.
+pub(crate) fn counter_user(engine: &wasmtime::Engine) -> Component {
+    let mut builder = ComponentBuilder::default();
+
+    let mut counter = InstanceType::new();
+    counter
+        .ty()
+        .function()
+        .params([] as [(&str, ComponentValType); 0])
+        .result(Some(ComponentValType::Primitive(PrimitiveValType::U32)));
+    counter.export("counter", ComponentTypeRef::Func(0));
+    let counter_type = builder.type_instance(None, &counter);
+    let counter = builder.import(
+        "test:plug/counter",
+        ComponentTypeRef::Instance(counter_type),
+    );
+    let func = builder.alias_export(counter, "counter", ComponentExportKind::Func);
+    let func = builder.lower_func(None, func, []);
+    let plug = builder.core_instantiate_exports(None, [("counter", ExportKind::Func, func)]);
+
+    let mut types = TypeSection::new();
+    types.ty().function([], [ValType::I32]);
+    let mut imports = ImportSection::new();
+    imports.import("plug", "counter", EntityType::Function(0));
+    let mut functions = FunctionSection::new();
+    functions.function(0);
+    let mut exports = ExportSection::new();
+    exports.export("run", ExportKind::Func, 1);
+    let mut body = Function::new([]);
+    body.instructions().call(0).end();
+    let mut code = CodeSection::new();
+    code.function(&body);
+    let mut module = Module::new();
+    module
+        .section(&types)
+        .section(&imports)
+        .section(&functions)
+        .section(&exports)
+        .section(&code);
+    let module = builder.core_module(None, &module);
+    let instance = builder.core_instantiate(None, module, [("plug", ModuleArg::Instance(plug))]);
+
+    let (ty, mut func_type) = builder.type_function(None);
+    func_type
+        .params([] as [(&str, ComponentValType); 0])
+        .result(Some(ComponentValType::Primitive(PrimitiveValType::U32)));
+    let core_func = builder.core_alias_export(None, instance, "run", ExportKind::Func);
+    let func = builder.lift_func(None, core_func, ty, []);
+    builder.export("run", ComponentExportKind::Func, func, None);
+
+    Component::new(engine, builder.finish()).expect("a valid component")
+}
@@ -0,0 +1,79 @@
+use bevy_diagnostic::FrameCount;
+use bevy_ecs::{prelude::*, system::SystemParam};
+use bevy_time::Time;
+
+use crate::{
+    bindings::wasvy::ecs::app::TimeInfo,
+    record::{CallResponse, decode_fields, encode_fields},
+};
+
+/// The time mod systems see via `get-time`
+#[derive(SystemParam)]
+pub(crate) struct ModTime<'w> {
+    time: Option<Res<'w, Time>>,
+    frame_count: Option<Res<'w, FrameCount>>,
+}
+
+impl ModTime<'_> {
+    /// Returns [None] when the host doesn't track time
+    pub(crate) fn info(&self) -> Option<TimeInfo> {
+        let time = self.time.as_ref()?;
+        Some(TimeInfo {
+            delta_secs: time.delta_secs(),
+            elapsed_secs: time.elapsed_secs_f64(),
+            frame_count: self.frame_count.as_ref().map_or(0, |frames| frames.0),
+        })
+    }
+}
+
+/// Encodes the time so it can be recorded and replayed, see [`ModRecorder`](crate::record::ModRecorder)
+pub(crate) fn encode_time(time: &TimeInfo) -> Vec<u8> {
+    encode_fields([
+        &time.delta_secs.to_le_bytes()[..],
+        &time.elapsed_secs.to_le_bytes()[..],
+        &time.frame_count.to_le_bytes()[..],
+    ])
+}
+
+pub(crate) fn decode_time(bytes: &[u8]) -> anyhow::Result<TimeInfo> {
+    let invalid = || anyhow::anyhow!("Invalid recorded time");
+    let fields = decode_fields(bytes)?;
+    let [delta_secs, elapsed_secs, frame_count] = &fields[..] else {
+        return Err(invalid());
+    };
+
+    Ok(TimeInfo {
+        delta_secs: f32::from_le_bytes(delta_secs[..].try_into().map_err(|_| invalid())?),
+        elapsed_secs: f64::from_le_bytes(elapsed_secs[..].try_into().map_err(|_| invalid())?),
+        frame_count: u32::from_le_bytes(frame_count[..].try_into().map_err(|_| invalid())?),
+    })
+}
+
+impl CallResponse for TimeInfo {
+    fn encode(&self) -> Vec<u8> {
+        encode_time(self)
+    }
+
+    fn decode(bytes: Vec<u8>) -> anyhow::Result<Self> {
+        decode_time(&bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn time_round_trips() {
+        let time = TimeInfo {
+            delta_secs: 0.016,
+            elapsed_secs: 12.5,
+            frame_count: 750,
+        };
+        let decoded = decode_time(&encode_time(&time)).unwrap();
+        assert_eq!(decoded.delta_secs, time.delta_secs);
+        assert_eq!(decoded.elapsed_secs, time.elapsed_secs);
+        assert_eq!(decoded.frame_count, time.frame_count);
+        assert!(decode_time(&encode_fields([&[1u8][..]])).is_err());
+    }
+}
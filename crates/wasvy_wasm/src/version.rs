@@ -0,0 +1,125 @@
+//! Versioning of the `wasvy:ecs` WIT package mods are built against.
+//!
+//! Mods import the interfaces of the package with its version, such as `wasvy:ecs/app@0.0.9`.
+//! Before a mod is instantiated its imports are checked against [`WIT_VERSION`], so a mod built
+//! for another version fails to load with a precise error instead of an opaque linker error.
+//!
+//! Versions are compatible following semver: same major version from `1.0.0` on, same minor
+//! version for `0.x` and the exact same version for `0.0.x`. Wasmtime adapts the imports of mods
+//! built against an older compatible version to the host's interfaces.
+
+use anyhow::{Result, bail};
+use semver::Version;
+
+/// The WIT package providing the ECS to mods
+pub const WIT_PACKAGE: &str = "wasvy:ecs";
+
+/// The version of [`WIT_PACKAGE`] this host provides
+pub const WIT_VERSION: &str = "0.0.9";
+
+/// Checks that the versions of [`WIT_PACKAGE`] imported by a mod are supported by the host.
+///
+/// `imports` are the names of the mod's imports, such as `wasvy:ecs/app@0.0.9`. Imports of other
+/// packages and unversioned imports are ignored.
+pub fn check_imports<'a>(imports: impl IntoIterator<Item = &'a str>) -> Result<()> {
+    let host = Version::parse(WIT_VERSION).expect("WIT_VERSION is a valid version");
+    for import in imports {
+        let Some(version) = imported_version(import) else {
+            continue;
+        };
+        let Ok(version) = Version::parse(version) else {
+            bail!("Mod imports {import}, which has an invalid version");
+        };
+        check_version(&version, &host)?;
+    }
+    Ok(())
+}
+
+/// Checks a version of [`WIT_PACKAGE`] a mod declared it requires, see `ModDetails::wasvy_version`
+pub fn check_required(version: &str) -> Result<()> {
+    let host = Version::parse(WIT_VERSION).expect("WIT_VERSION is a valid version");
+    let Ok(version) = Version::parse(version) else {
+        bail!("Mod requires {WIT_PACKAGE}@{version}, which is an invalid version");
+    };
+    check_version(&version, &host)
+}
+
+/// The version of [`WIT_PACKAGE`] in an import name
+fn imported_version(import: &str) -> Option<&str> {
+    let interface = import.strip_prefix(WIT_PACKAGE)?.strip_prefix('/')?;
+    interface.split_once('@').map(|(_, version)| version)
+}
+
+fn check_version(version: &Version, host: &Version) -> Result<()> {
+    if compatibility(version) != compatibility(host) {
+        bail!(
+            "Mod was built against {WIT_PACKAGE}@{}, but the host provides {WIT_PACKAGE}@{}",
+            short(version),
+            short(host)
+        );
+    }
+    if version > host {
+        bail!(
+            "Mod was built against {WIT_PACKAGE}@{version}, which is newer than the host's {WIT_PACKAGE}@{host}"
+        );
+    }
+    Ok(())
+}
+
+/// The part of a version that must match for versions to be compatible
+fn compatibility(version: &Version) -> (u64, u64, u64) {
+    match (version.major, version.minor) {
+        (0, 0) => (0, 0, version.patch),
+        (0, minor) => (0, minor, 0),
+        (major, _) => (major, 0, 0),
+    }
+}
+
+/// Formats a version as precisely as needed to tell incompatible versions apart
+fn short(version: &Version) -> String {
+    match (version.major, version.minor) {
+        (0, 0) => version.to_string(),
+        (0, minor) => format!("0.{minor}"),
+        (major, _) => major.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check(version: &str, host: &str) -> Result<()> {
+        check_version(&version.parse().unwrap(), &host.parse().unwrap())
+    }
+
+    #[test]
+    fn wit_version_matches_wit_file() {
+        let wit = include_str!("../wit/wasvy-ecs.wit");
+        assert!(wit.contains(&format!("package {WIT_PACKAGE}@{WIT_VERSION};")));
+    }
+
+    #[test]
+    fn checks_compatibility() {
+        assert!(check("0.2.0", "0.2.3").is_ok());
+        assert!(check("1.0.0", "1.4.0").is_ok());
+        assert!(check("0.0.9", "0.0.9").is_ok());
+        assert!(check("0.2.4", "0.2.3").is_err());
+        assert!(check("0.0.8", "0.0.9").is_err());
+        assert_eq!(
+            check("0.2.1", "0.3.0").unwrap_err().to_string(),
+            "Mod was built against wasvy:ecs@0.2, but the host provides wasvy:ecs@0.3"
+        );
+
+        assert!(
+            check_imports([
+                "wasvy:ecs/app@0.0.9",
+                "wasi:cli/stdout@0.2.0",
+                "wasvy:ecs/app"
+            ])
+            .is_ok()
+        );
+        assert!(check_imports(["wasvy:ecs/app@0.1.0"]).is_err());
+        assert!(check_required(WIT_VERSION).is_ok());
+        assert!(check_required("0.1.0").is_err());
+    }
+}
@@ -1,53 +1,161 @@
-use anyhow::{Result, anyhow};
-use bevy_asset::{AssetLoader, LoadContext, io::Reader};
-use bevy_ecs::prelude::*;
-use bevy_reflect::TypePath;
-use wasmtime::{
-    component::{Component, InstancePre, Val},
-    error::Context,
+use std::{path::PathBuf, sync::Arc};
+
+use anyhow::Result;
+use bevy_asset::{
+    AssetLoader, LoadContext, ReadAssetBytesError,
+    io::{AssetReaderError, Reader},
 };
+use bevy_reflect::TypePath;
+use bevy_tasks::AsyncComputeTaskPool;
 use wasvy_runtime::{
-    access::ModAccess,
-    asset::{ModAsset, ModBackend},
+    asset::{ModAsset, ModLoaderSettings},
+    manifest::ModManifest,
+    package::{ModPackage, PACKAGE_EXTENSION},
+    verify::ModVerifier,
+};
+
+use crate::runtime::WasmRuntime;
+
+#[cfg(feature = "wasmtime")]
+use {
+    anyhow::{anyhow, bail},
+    bevy_ecs::prelude::*,
+    bevy_log::prelude::*,
+    wasmtime::{
+        component::{Component, InstancePre, Val},
+        error::Context,
+    },
+    wasvy_runtime::{
+        access::ModAccess,
+        asset::ModBackend,
+        entry_points::ModEntryPoints,
+        limits::ModMemoryLimits,
+        manifest::ModDetails,
+        mods::{InsertDespawnComponent, ModInfo},
+        permissions::ComponentPermissions,
+        storage::ModStorage,
+    },
 };
 
+#[cfg(feature = "wasmtime")]
 use crate::{
+    compose::link_plugs,
     engine::{Engine, Linker},
+    exports::{ModCallbackNames, ModExport, ModExports},
     host::{WasmApp, WasmHost},
-    runner::{Config, ConfigSetup, Runner},
-    system::AddSystems,
+    runner::{Config, ConfigSetup, ModRunners, Runner},
+    system::{AddSystems, despawn_observers, run_once, teardown},
+    version::{check_imports, check_required},
 };
 
 /// Wasmtime-backed implementation for a loaded WebAssembly mod.
+#[cfg(feature = "wasmtime")]
 pub struct WasmModBackend {
     instance_pre: InstancePre<WasmHost>,
 }
 
+#[cfg(feature = "wasmtime")]
 impl WasmModBackend {
-    pub async fn new(loader: &ModAssetLoader, reader: &mut dyn Reader) -> Result<Self> {
-        let mut bytes = vec![];
-        reader.read_to_end(&mut bytes).await?;
+    pub fn new(linker: &Linker, bytes: &[u8]) -> Result<Self> {
+        let component = Component::from_binary(linker.engine(), bytes)?;
+        Self::from_component(linker, component)
+    }
 
-        let component = Component::from_binary(loader.linker.engine(), &bytes)?;
-        let instance_pre = loader.linker.instantiate_pre(&component)?;
+    /// Creates the backend of an already compiled mod, such as one loaded from a [`ModCache`]
+    ///
+    /// [`ModCache`]: crate::cache::ModCache
+    pub(crate) fn from_component(linker: &Linker, component: Component) -> Result<Self> {
+        let component_type = component.component_type();
+        check_imports(
+            component_type
+                .imports(linker.engine())
+                .map(|(name, _)| name),
+        )?;
+        let instance_pre = linker.instantiate_pre(&component)?;
 
         Ok(Self { instance_pre })
     }
+
+    /// Creates the backend of a mod linked with plugs, see [`Mods::load_composed`]
+    ///
+    /// [`Mods::load_composed`]: wasvy_runtime::mods::Mods::load_composed
+    pub fn composed(linker: &Linker, bytes: &[u8], plugs: &[Vec<u8>]) -> Result<Self> {
+        let component = Component::from_binary(linker.engine(), bytes)?;
+        let plugs = plugs
+            .iter()
+            .map(|plug| Component::from_binary(linker.engine(), plug))
+            .collect::<Result<Vec<_>, _>>()?;
+        Self::from_components(linker, component, &plugs)
+    }
+
+    /// Like [`Self::from_component`], linking the mod with `plugs`
+    pub(crate) fn from_components(
+        linker: &Linker,
+        component: Component,
+        plugs: &[Component],
+    ) -> Result<Self> {
+        if plugs.is_empty() {
+            return Self::from_component(linker, component);
+        }
+        let linker = link_plugs(linker, &component, plugs)?;
+        Self::from_component(&linker, component)
+    }
 }
 
-impl ModBackend for WasmModBackend {
-    fn setup(
+#[cfg(feature = "wasmtime")]
+impl WasmModBackend {
+    /// Runs an export once per access, with `commands` followed by `params`
+    fn run_with_commands(
         &self,
         world: &mut World,
         mod_id: Entity,
         mod_name: &str,
         accesses: &[ModAccess],
+        name: &str,
+        params: Vec<Val>,
+    ) -> Result<()> {
+        let permissions = ComponentPermissions::of(mod_id, world);
+        let memory_limits = world.get::<ModMemoryLimits>(mod_id).copied();
+        for access in accesses {
+            let engine = world
+                .get_resource::<Engine>()
+                .expect("Engine should never be removed from world");
+            let mut runner = Runner::new(engine, mod_name, memory_limits);
+            runner.set_mod_info(ModInfo::new(mod_id, world));
+            runner.set_storage(ModStorage::of(mod_id, world));
+
+            run_once(
+                world,
+                &mut runner,
+                name,
+                params.clone(),
+                *access,
+                &permissions,
+                InsertDespawnComponent::new(mod_id, world),
+                &self.instance_pre,
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Calls an export taking the [`WasmApp`] followed by `params`, adding the systems it registers
+    fn add_systems_with(
+        &self,
+        world: &mut World,
+        mod_id: Entity,
+        mod_name: &str,
+        accesses: &[ModAccess],
+        name: &str,
+        params: Vec<Val>,
     ) -> Result<()> {
         let engine = world
             .get_resource::<Engine>()
             .expect("Engine should never be removed from world");
 
-        let mut runner = Runner::new(engine);
+        let memory_limits = world.get::<ModMemoryLimits>(mod_id).copied();
+        let mut runner = Runner::new(engine, mod_name, memory_limits);
+        runner.set_mod_info(ModInfo::new(mod_id, world));
+        runner.set_storage(ModStorage::of(mod_id, world));
 
         let mut systems = AddSystems::default();
         let config = Config::Setup(ConfigSetup {
@@ -56,15 +164,53 @@ impl ModBackend for WasmModBackend {
         });
 
         let app = runner.new_resource(WasmApp).expect("Table has space left");
+        let params: Vec<Val> = std::iter::once(Val::Resource(app)).chain(params).collect();
         call(
             &mut runner,
             &self.instance_pre,
             config,
-            "setup",
-            &[Val::Resource(app)],
+            name,
+            &params,
             &mut [],
         )?;
 
+        for function in systems.exports() {
+            if !self.has_export(function) {
+                warn!(
+                    "Mod \"{mod_name}\" tried exporting function \"{function}\", but has no such export"
+                );
+                continue;
+            }
+
+            let export = ModExport {
+                mod_id,
+                mod_info: ModInfo::new(mod_id, world),
+                storage: ModStorage::of(mod_id, world),
+                memory_limits,
+                accesses: accesses.to_vec(),
+                engine: world.resource::<Engine>().clone(),
+                instance_pre: self.instance_pre.clone(),
+            };
+            world
+                .resource_mut::<ModExports>()
+                .insert(mod_name, function, export);
+        }
+
+        let mut callbacks = world
+            .entity_mut(mod_id)
+            .take::<ModCallbackNames>()
+            .unwrap_or_default();
+        for callback in systems.callbacks() {
+            if !self.has_export(callback) {
+                warn!(
+                    "Mod \"{mod_name}\" tried adding callback \"{callback}\", but has no such export"
+                );
+                continue;
+            }
+            callbacks.0.insert(callback.clone());
+        }
+        world.entity_mut(mod_id).insert(callbacks);
+
         systems.add_systems(
             world,
             accesses,
@@ -76,6 +222,208 @@ impl ModBackend for WasmModBackend {
     }
 }
 
+#[cfg(feature = "wasmtime")]
+impl ModBackend for WasmModBackend {
+    fn setup(
+        &self,
+        world: &mut World,
+        mod_id: Entity,
+        mod_name: &str,
+        accesses: &[ModAccess],
+    ) -> Result<()> {
+        // Functions, callbacks and observers are added again by setup
+        world.resource_mut::<ModExports>().remove_mod(mod_id);
+        world.entity_mut(mod_id).remove::<ModCallbackNames>();
+        // The systems kept instances of the previous version of the mod
+        world.entity_mut(mod_id).remove::<ModRunners>();
+        despawn_observers(world, mod_id, Some(accesses));
+
+        let setup = ModEntryPoints::setup_of(world);
+        self.add_systems_with(world, mod_id, mod_name, accesses, &setup, Vec::new())
+    }
+
+    fn info(
+        &self,
+        world: &mut World,
+        mod_id: Entity,
+        mod_name: &str,
+    ) -> Result<Option<ModDetails>> {
+        // Mods opt into describing themselves by exporting this
+        if !self.has_export("info") {
+            return Ok(None);
+        }
+
+        let engine = world
+            .get_resource::<Engine>()
+            .expect("Engine should never be removed from world");
+        let memory_limits = world.get::<ModMemoryLimits>(mod_id).copied();
+        let mut runner = Runner::new(engine, mod_name, memory_limits);
+        runner.set_mod_info(ModInfo::new(mod_id, world));
+
+        let mut results = [Val::Bool(false)];
+        call(
+            &mut runner,
+            &self.instance_pre,
+            Config::Call,
+            "info",
+            &[],
+            &mut results,
+        )?;
+        let [result] = results;
+        let details = mod_details(result).map_err(|err| {
+            anyhow!("Export \"info\" of mod \"{mod_name}\" must return a mod-details: {err}")
+        })?;
+
+        if let Some(version) = &details.wasvy_version {
+            check_required(version)?;
+        }
+        Ok(Some(details))
+    }
+
+    fn has_export(&self, name: &str) -> bool {
+        self.instance_pre
+            .component()
+            .get_export_index(None, name)
+            .is_some()
+    }
+
+    fn run_entry_point(
+        &self,
+        world: &mut World,
+        mod_id: Entity,
+        mod_name: &str,
+        accesses: &[ModAccess],
+        name: &str,
+    ) -> Result<()> {
+        if !self.has_export(name) {
+            return Ok(());
+        }
+
+        self.add_systems_with(world, mod_id, mod_name, accesses, name, Vec::new())
+    }
+
+    fn run_callback(
+        &self,
+        world: &mut World,
+        mod_id: Entity,
+        mod_name: &str,
+        accesses: &[ModAccess],
+        name: &str,
+        args: &[u8],
+    ) -> Result<()> {
+        let registered = world
+            .get::<ModCallbackNames>(mod_id)
+            .is_some_and(|callbacks| callbacks.0.contains(name));
+        if !registered {
+            bail!("Mod \"{mod_name}\" has no callback \"{name}\"");
+        }
+
+        let args = Val::List(args.iter().copied().map(Val::U8).collect());
+        self.run_with_commands(world, mod_id, mod_name, accesses, name, vec![args])
+    }
+
+    fn run_hook(
+        &self,
+        world: &mut World,
+        mod_id: Entity,
+        mod_name: &str,
+        accesses: &[ModAccess],
+        name: &str,
+    ) -> Result<()> {
+        // Mods opt into hooks by exporting them
+        if !self.has_export(name) {
+            return Ok(());
+        }
+
+        self.run_with_commands(world, mod_id, mod_name, accesses, name, Vec::new())
+    }
+
+    fn registry_changed(
+        &self,
+        world: &mut World,
+        mod_id: Entity,
+        mod_name: &str,
+        accesses: &[ModAccess],
+        type_paths: &[String],
+    ) -> Result<()> {
+        // Mods opt into this callback by exporting it
+        if !self.has_export("registry-changed") {
+            return Ok(());
+        }
+
+        let type_paths = type_paths
+            .iter()
+            .map(|type_path| Val::String(type_path.clone()))
+            .collect();
+        self.add_systems_with(
+            world,
+            mod_id,
+            mod_name,
+            accesses,
+            "registry-changed",
+            vec![Val::List(type_paths)],
+        )
+    }
+
+    fn teardown(
+        &self,
+        world: &mut World,
+        mod_id: Entity,
+        mod_name: &str,
+        accesses: &[ModAccess],
+        permissions: &ComponentPermissions,
+    ) -> Result<()> {
+        world.resource_mut::<ModExports>().remove_mod(mod_id);
+        despawn_observers(world, mod_id, None);
+
+        // Mods opt into this callback by exporting it. It runs once per mod, with its first access
+        if !self.has_export("teardown") {
+            return Ok(());
+        }
+
+        let access = accesses.first().copied();
+        teardown(world, mod_name, access, permissions, &self.instance_pre)
+    }
+}
+
+/// Reads the `mod-details` record returned by the `info` export of a mod
+#[cfg(feature = "wasmtime")]
+fn mod_details(value: Val) -> Result<ModDetails> {
+    let Val::Record(fields) = value else {
+        bail!("expected a record");
+    };
+
+    let mut name = None;
+    let mut version = None;
+    let mut wasvy_version = None;
+    let mut description = None;
+    for (field, value) in fields {
+        match (field.as_str(), value) {
+            ("name", Val::String(value)) => name = Some(value),
+            ("version", Val::String(value)) => version = Some(value),
+            ("wasvy-version", Val::Option(value)) => wasvy_version = value,
+            ("description", Val::Option(value)) => description = value,
+            (field, _) => bail!("unexpected field {field}"),
+        }
+    }
+    let optional = |value: Option<Box<Val>>| match value.map(|value| *value) {
+        None => Ok(None),
+        Some(Val::String(value)) => Ok(Some(value)),
+        Some(_) => Err(anyhow!("expected an option<string>")),
+    };
+
+    let version = version.ok_or(anyhow!("missing field version"))?;
+    Ok(ModDetails {
+        name: name.ok_or(anyhow!("missing field name"))?,
+        version: version
+            .parse()
+            .map_err(|err| anyhow!("invalid version {version}: {err}"))?,
+        wasvy_version: optional(wasvy_version)?,
+        description: optional(description)?,
+    })
+}
+
+#[cfg(feature = "wasmtime")]
 pub(crate) fn call(
     runner: &mut Runner,
     instance_pre: &InstancePre<WasmHost>,
@@ -84,6 +432,7 @@ pub(crate) fn call(
     params: &[Val],
     results: &mut [Val],
 ) -> Result<()> {
+    runner.set_instance_pre(instance_pre);
     runner.use_store(config, move |mut store| {
         let instance = instance_pre
             .instantiate(&mut store)
@@ -101,32 +450,282 @@ pub(crate) fn call(
 }
 
 /// The Bevy [`AssetLoader`] for WebAssembly-backed [`ModAsset`] values.
+///
+/// Compilation is delegated to a [`WasmRuntime`], and runs on the [`AsyncComputeTaskPool`] so
+/// compiling big mods neither freezes the app nor blocks other assets from loading.
+///
+/// Loads both plain `.wasm` files and [`ModPackage`]s.
 #[derive(TypePath)]
 pub struct ModAssetLoader {
-    pub(crate) linker: Linker,
+    runtime: Arc<dyn WasmRuntime>,
+    verifier: Option<ModVerifier>,
 }
 
 impl ModAssetLoader {
-    pub fn new(linker: Linker) -> Self {
-        Self { linker }
+    pub fn new(runtime: impl WasmRuntime) -> Self {
+        Self::from_runtime(Arc::new(runtime))
+    }
+
+    /// Creates a loader sharing its runtime, such as with a [`ModCompiler`](wasvy_runtime::asset::ModCompiler)
+    pub fn from_runtime(runtime: Arc<dyn WasmRuntime>) -> Self {
+        Self {
+            runtime,
+            verifier: None,
+        }
+    }
+
+    /// Checks mods before they are compiled, failing to load those it rejects. See [`ModVerifier`].
+    pub fn set_verifier(&mut self, verifier: ModVerifier) {
+        self.verifier = Some(verifier);
+    }
+
+    fn verify(
+        &self,
+        bytes: &[u8],
+        manifest: Option<&ModManifest>,
+        assets: &[(PathBuf, Vec<u8>)],
+    ) -> Result<()> {
+        if let Some(verifier) = &self.verifier {
+            verifier.verify(bytes, manifest, assets)?;
+        }
+        Ok(())
+    }
+
+    /// Compiles the mod on the compute task pool, or right away if the app has none. Runtimes that
+    /// compile asynchronously are awaited instead.
+    ///
+    /// Setting up the mod stays on the main thread, since it adds the mod's systems to the world.
+    async fn compile(&self, bytes: Vec<u8>, plugs: Vec<Vec<u8>>) -> Result<ModAsset> {
+        if let Some(compiling) = self.runtime.compile_async(&bytes, &plugs) {
+            return compiling.await;
+        }
+        let Some(pool) = AsyncComputeTaskPool::try_get() else {
+            return self.runtime.compile_composed(&bytes, &plugs);
+        };
+        let runtime = Arc::clone(&self.runtime);
+        pool.spawn(async move { runtime.compile_composed(&bytes, &plugs) })
+            .await
+    }
+
+    /// Reads and verifies the plugs the mod is linked with, see [`ModLoaderSettings`]
+    async fn read_plugs(
+        &self,
+        settings: &ModLoaderSettings,
+        load_context: &mut LoadContext<'_>,
+    ) -> Result<Vec<Vec<u8>>> {
+        let mut plugs = Vec::new();
+        for path in &settings.plugs {
+            let bytes = load_context
+                .read_asset_bytes(path)
+                .await
+                .map_err(|err| anyhow::anyhow!("Failed to read plug {path}: {err}"))?;
+            self.verify(&bytes, ModManifest::from_wasm(&bytes)?.as_ref(), &[])?;
+            plugs.push(bytes);
+        }
+        Ok(plugs)
     }
 }
 
 impl AssetLoader for ModAssetLoader {
     type Asset = ModAsset;
-    type Settings = ();
+    type Settings = ModLoaderSettings;
     type Error = anyhow::Error;
 
     async fn load(
         &self,
         reader: &mut dyn Reader,
-        _settings: &Self::Settings,
-        _load_context: &mut LoadContext<'_>,
+        settings: &Self::Settings,
+        load_context: &mut LoadContext<'_>,
     ) -> Result<Self::Asset> {
-        Ok(ModAsset::new(WasmModBackend::new(self, reader).await?))
+        let mut bytes = vec![];
+        reader.read_to_end(&mut bytes).await?;
+        let plugs = self.read_plugs(settings, load_context).await?;
+
+        let path = load_context.path().path();
+        if path.extension().is_some_and(|ext| ext == PACKAGE_EXTENSION) {
+            let package = ModPackage::from_bytes(&bytes)?;
+            let manifest = match package.manifest {
+                Some(manifest) => Some(manifest),
+                None => ModManifest::from_wasm(&package.wasm)?,
+            };
+
+            // Mount the assets by mod id, so they keep their paths when the package is renamed
+            let name = match &manifest {
+                Some(manifest) => manifest.id.clone(),
+                None => path
+                    .file_stem()
+                    .map(|stem| stem.to_string_lossy().into_owned())
+                    .unwrap_or_default(),
+            };
+
+            self.verify(&package.wasm, manifest.as_ref(), &package.assets)?;
+            return self
+                .compile(package.wasm, plugs)
+                .await?
+                .with_manifest(manifest)
+                .with_package_assets(name, package.assets);
+        }
+
+        let manifest = match read_side_car_manifest(load_context).await? {
+            Some(manifest) => Some(manifest),
+            None => ModManifest::from_wasm(&bytes)?,
+        };
+
+        self.verify(&bytes, manifest.as_ref(), &[])?;
+        Ok(self.compile(bytes, plugs).await?.with_manifest(manifest))
     }
 
     fn extensions(&self) -> &[&str] {
-        &["wasm"]
+        &["wasm", PACKAGE_EXTENSION]
+    }
+}
+
+/// Reads the manifest next to a mod, see [`ModManifest`]
+///
+/// Only `{stem}.toml` is read, since mods loaded from the same folder would otherwise share a
+/// `mod.toml`, and with it their id. Packages hold their `mod.toml` inside instead.
+async fn read_side_car_manifest(load_context: &mut LoadContext<'_>) -> Result<Option<ModManifest>> {
+    let path = load_context.path().clone();
+    let Some(stem) = path.path().file_stem().and_then(|stem| stem.to_str()) else {
+        return Ok(None);
+    };
+
+    let side_car = path.resolve_embed_str(&format!("{stem}.toml"))?;
+    match load_context.read_asset_bytes(&side_car).await {
+        Ok(bytes) => {
+            let source = std::str::from_utf8(&bytes)?;
+            ModManifest::from_toml(source)
+                .map_err(|err| err.context(format!("Failed to read manifest {side_car}")))
+                .map(Some)
+        }
+        Err(ReadAssetBytesError::AssetReaderError(AssetReaderError::NotFound(_))) => Ok(None),
+        Err(err) => Err(err.into()),
+    }
+}
+
+#[cfg(all(test, feature = "wasmtime"))]
+mod tests {
+    use std::{fs, path::Path, thread, time::Duration};
+
+    use bevy_app::{App, TaskPoolPlugin};
+    use bevy_asset::{AssetPlugin, AssetServer, Assets};
+    use bevy_ecs::system::SystemState;
+    use wasvy_runtime::{
+        mods::{ModState, Mods},
+        plugin::ModRuntimePlugin,
+        sandbox::Sandbox,
+        schedule::ModSchedules,
+    };
+
+    use super::*;
+    use crate::{
+        engine::create_linker,
+        plugin::WasmBackendPlugin,
+        test_utils::{empty_mod, spawning_component, world},
+    };
+
+    #[test]
+    fn tears_down_once_per_mod() {
+        let mut world = world();
+        let engine = world.resource::<Engine>().clone();
+        let component = spawning_component(engine.inner(), "teardown");
+        let backend = WasmModBackend::from_component(&create_linker(&engine), component).unwrap();
+        let mod_id = world.spawn_empty().id();
+        let sandbox = Sandbox::new(&mut world, ModSchedules::empty());
+        let sandbox = world.spawn(sandbox).id();
+        let permissions = ComponentPermissions::default();
+
+        // The export spawns an entity each time it's called
+        let mut entities = world.query::<Entity>();
+        let spawned = entities.iter(&world).count();
+        let accesses = [ModAccess::World, ModAccess::Sandbox(sandbox)];
+        backend
+            .teardown(&mut world, mod_id, "my-mod", &accesses, &permissions)
+            .unwrap();
+        assert_eq!(entities.iter(&world).count(), spawned + 1);
+
+        // Mods without access are called too, but can't use their commands
+        let err = backend
+            .teardown(&mut world, mod_id, "my-mod", &[], &permissions)
+            .unwrap_err();
+        assert!(
+            format!("{err:?}").contains("only accessible when running systems"),
+            "{err:?}"
+        );
+    }
+
+    /// An app loading mods from the files in `dir`
+    fn app_loading_from(dir: &Path) -> App {
+        let mut app = App::new();
+        app.add_plugins((
+            TaskPoolPlugin::default(),
+            AssetPlugin {
+                file_path: dir.to_string_lossy().into_owned(),
+                ..Default::default()
+            },
+            ModRuntimePlugin::default(),
+            WasmBackendPlugin::default(),
+        ));
+        app
+    }
+
+    #[test]
+    fn mods_are_ready_once_compiled_and_set_up() {
+        let dir = std::env::temp_dir().join(format!("wasvy-asset-test-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("my-mod.wasm"), empty_mod()).unwrap();
+
+        let mut app = app_loading_from(&dir);
+        let mut state = SystemState::<Mods>::new(app.world_mut());
+        let mut mods = state.get_mut(app.world_mut()).unwrap();
+        let mod_id = mods.spawn("my-mod.wasm", None);
+        mods.enable_access(mod_id, ModAccess::World);
+        state.apply(app.world_mut());
+
+        let mod_state = |app: &App| *app.world().get::<ModState>(mod_id).unwrap();
+        assert_eq!(mod_state(&app), ModState::Loading);
+
+        // The mod is compiled in the background, and set up once it has loaded
+        for _ in 0..500 {
+            app.update();
+            if mod_state(&app) != ModState::Loading {
+                break;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+        fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(mod_state(&app), ModState::Ready);
+    }
+
+    #[test]
+    fn side_car_manifests_are_named_after_the_mod() {
+        let dir = std::env::temp_dir().join(format!("wasvy-side-car-test-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("first.wasm"), empty_mod()).unwrap();
+        fs::write(
+            dir.join("first.toml"),
+            "id = \"first\"\nversion = \"1.0.0\"",
+        )
+        .unwrap();
+        fs::write(dir.join("second.wasm"), empty_mod()).unwrap();
+        fs::write(dir.join("mod.toml"), "id = \"shared\"\nversion = \"1.0.0\"").unwrap();
+
+        let mut app = app_loading_from(&dir);
+        let asset_server = app.world().resource::<AssetServer>().clone();
+        let first = asset_server.load::<ModAsset>("first.wasm");
+        let second = asset_server.load::<ModAsset>("second.wasm");
+        for _ in 0..500 {
+            app.update();
+            if asset_server.is_loaded(&first) && asset_server.is_loaded(&second) {
+                break;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+        fs::remove_dir_all(&dir).unwrap();
+
+        let assets = app.world().resource::<Assets<ModAsset>>();
+        let id = |handle| assets.get(handle).unwrap().manifest().map(|m| m.id.clone());
+        assert_eq!(id(&first), Some("first".to_string()));
+        assert_eq!(id(&second), None);
     }
 }
@@ -0,0 +1,142 @@
+//! A [`WasmRuntime`] for browser builds, where wasmtime is unavailable.
+//!
+//! Browsers only run core WebAssembly modules, so the component model (and with it the
+//! `wasvy:ecs` interface) is not supported yet. Simple mods that export a `setup` function
+//! taking no arguments can still be loaded and run.
+
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+use anyhow::{Result, anyhow, bail};
+use bevy_ecs::prelude::*;
+use js_sys::{
+    Function, Object, Reflect, Uint8Array, WebAssembly,
+    wasm_bindgen::{JsCast, JsValue},
+};
+use wasvy_runtime::{
+    access::ModAccess,
+    asset::{ModAsset, ModBackend},
+    entry_points::ModEntryPoints,
+};
+
+use crate::runtime::WasmRuntime;
+
+#[cfg(target_arch = "wasm32")]
+use {bevy_tasks::BoxedFuture, wasm_bindgen_futures::JsFuture};
+
+/// Runs mods with the browser's `WebAssembly` API.
+///
+/// Mods loaded as assets are compiled asynchronously, since browsers refuse to compile modules
+/// over 4KB synchronously on the main thread. Mods loaded from bytes have to be compiled right
+/// away, which only works for small modules.
+pub struct WebRuntime;
+
+impl WasmRuntime for WebRuntime {
+    fn compile(&self, bytes: &[u8]) -> Result<ModAsset> {
+        let module = WebAssembly::Module::new(&Uint8Array::from(bytes).into()).map_err(js_error)?;
+        let instance = WebAssembly::Instance::new(&module, &Object::new()).map_err(js_error)?;
+        Ok(ModAsset::new(WebModBackend::new(instance)))
+    }
+
+    // Javascript futures can't be sent between threads, which native builds require
+    #[cfg(target_arch = "wasm32")]
+    fn compile_async(
+        &self,
+        bytes: &[u8],
+        plugs: &[Vec<u8>],
+    ) -> Option<BoxedFuture<'static, Result<ModAsset>>> {
+        let has_plugs = !plugs.is_empty();
+        let bytes = bytes.to_vec();
+        Some(Box::pin(async move {
+            if has_plugs {
+                bail!("This runtime can't link mods with plugs");
+            }
+            let instantiating = WebAssembly::instantiate_buffer(&bytes, &Object::new());
+            let result = JsFuture::from(instantiating).await.map_err(js_error)?;
+            let instance = Reflect::get(&result, &JsValue::from_str("instance"))
+                .map_err(js_error)?
+                .dyn_into::<WebAssembly::Instance>()
+                .map_err(js_error)?;
+            Ok(ModAsset::new(WebModBackend::new(instance)))
+        }))
+    }
+}
+
+thread_local! {
+    /// The instances of the loaded mods, by the id of their backend
+    static INSTANCES: RefCell<HashMap<u64, WebAssembly::Instance>> = RefCell::default();
+}
+
+/// A mod loaded by the [`WebRuntime`].
+///
+/// Javascript values can't be shared between threads, so the mod's instance is kept in a thread
+/// local. Browser builds run on a single thread, where the mod is both loaded and run.
+struct WebModBackend {
+    id: u64,
+}
+
+impl WebModBackend {
+    fn new(instance: WebAssembly::Instance) -> Self {
+        static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+        let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+        INSTANCES.with_borrow_mut(|instances| instances.insert(id, instance));
+        Self { id }
+    }
+}
+
+impl Drop for WebModBackend {
+    fn drop(&mut self) {
+        INSTANCES.with_borrow_mut(|instances| instances.remove(&self.id));
+    }
+}
+
+impl ModBackend for WebModBackend {
+    fn setup(
+        &self,
+        world: &mut World,
+        _mod_id: Entity,
+        _mod_name: &str,
+        _accesses: &[ModAccess],
+    ) -> Result<()> {
+        self.call(&ModEntryPoints::setup_of(world), true)
+    }
+
+    fn run_entry_point(
+        &self,
+        _world: &mut World,
+        _mod_id: Entity,
+        _mod_name: &str,
+        _accesses: &[ModAccess],
+        name: &str,
+    ) -> Result<()> {
+        self.call(name, false)
+    }
+}
+
+impl WebModBackend {
+    /// Calls one of the exports of the mod's instance, which must exist if `required`
+    fn call(&self, name: &str, required: bool) -> Result<()> {
+        let exports = INSTANCES
+            .with_borrow(|instances| instances.get(&self.id).map(WebAssembly::Instance::exports))
+            .ok_or_else(|| anyhow!("The mod was loaded on another thread"))?;
+
+        let export = Reflect::get(&exports, &JsValue::from_str(name))
+            .map_err(js_error)?
+            .dyn_into::<Function>();
+        let function = match export {
+            Ok(function) => function,
+            Err(_) if required => bail!("Missing {name} function"),
+            Err(_) => return Ok(()),
+        };
+        function.call0(&JsValue::UNDEFINED).map_err(js_error)?;
+
+        Ok(())
+    }
+}
+
+fn js_error(value: impl Into<JsValue>) -> anyhow::Error {
+    anyhow!("{:?}", value.into())
+}
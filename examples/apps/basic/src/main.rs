@@ -6,7 +6,7 @@ use bevy_egui::EguiPlugin;
 use bevy_inspector_egui::quick::WorldInspectorPlugin;
 
 // Get started by importing the prelude
-use wasvy::prelude::*;
+use wasvy::{inspector::ModInspectorPlugin, prelude::*};
 
 fn main() {
     App::new()
@@ -19,11 +19,15 @@ fn main() {
                 ..Default::default()
             }),
             // Next, add the [`ModLoaderPlugin`] ;)
-            ModLoaderPlugin::default().devtools("wasvy basic example"),
+            ModLoaderPlugin::default()
+                .devtools("wasvy basic example")
+                .diagnostics(true),
             // Plus some helpers for the example
             FpsOverlayPlugin::default(),
             EguiPlugin::default(),
             WorldInspectorPlugin::new(),
+            // Lists the mods, with buttons to reload or disable them
+            ModInspectorPlugin,
         ))
         .add_systems(Startup, (load_mods, setup))
         .add_systems(Update, hide_disclaimer)
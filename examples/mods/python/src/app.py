@@ -55,16 +55,8 @@ class WitWorld(wit_world.WitWorld):
     def my_system(self, commands: Commands, query: Query):
         codec = get_codec()
 
-        # Count how many entities we've spawned
-        count = 0
-        while True:
-            components = query.iter()
-            if components is None:
-                break
-            count += 1
-
         # Avoid spawning more than 10
-        if count >= 10:
+        if query.count() >= 10:
             return
 
         @dataclass
@@ -15,15 +15,14 @@ use bindings::*;
 
 struct GuestComponent;
 
+#[wasvy_macros::guest_systems]
 impl Guest for GuestComponent {
     fn setup(app: App) {
-        let heal_system = System::new("heal-system");
-        heal_system.add_query(&[QueryFor::Mut(Health::type_path())]);
+        let heal_system = systems::heal_system(&[QueryFor::Mut(Health::type_path())]);
         println!("Type path: {:?}", Health::type_path());
         app.add_systems(&Schedule::Update, &[&heal_system]);
 
-        let pct_system = System::new("pct-system");
-        pct_system.add_query(&[QueryFor::Ref(Health::type_path())]);
+        let pct_system = systems::pct_system(&[QueryFor::Ref(Health::type_path())]);
         app.add_systems(&Schedule::Update, &[&pct_system]);
     }
 
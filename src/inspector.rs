@@ -0,0 +1,263 @@
+//! An [egui](bevy_egui) window to inspect and manage mods.
+//!
+//! Enable the `inspector` feature and add the [`ModInspectorPlugin`] along with bevy_egui's
+//! `EguiPlugin`. The window lists every mod with its [state](ModState), the sandboxes it runs in
+//! and the systems it added to each schedule, with buttons to [reload](Mods::reload) or
+//! [disable](Mods::disable) it.
+//!
+//! Systems show how long they take to run when
+//! [diagnostics](crate::plugin::ModLoaderPlugin::diagnostics) are enabled.
+//!
+//! ```no_run
+//! # use bevy_app::App;
+//! # use wasvy::{inspector::ModInspectorPlugin, prelude::*};
+//! App::new().add_plugins((ModLoaderPlugin::default(), ModInspectorPlugin));
+//! ```
+
+use bevy_app::{App, Plugin};
+use bevy_diagnostic::DiagnosticsStore;
+use bevy_ecs::{prelude::*, system::SystemState};
+use bevy_egui::{
+    EguiContext, EguiPrimaryContextPass, PrimaryEguiContext,
+    egui::{self, Color32},
+};
+
+use crate::prelude::*;
+
+/// Adds the mod inspector window, see [`crate::inspector`]
+pub struct ModInspectorPlugin;
+
+impl Plugin for ModInspectorPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ModInspector>().add_systems(
+            EguiPrimaryContextPass,
+            draw_inspector.run_if(|inspector: Res<ModInspector>| inspector.open),
+        );
+    }
+}
+
+/// Controls the mod inspector window
+#[derive(Resource, Debug, Clone, PartialEq, Eq)]
+pub struct ModInspector {
+    /// Whether the window is shown. Defaults to true
+    pub open: bool,
+}
+
+impl Default for ModInspector {
+    fn default() -> Self {
+        Self { open: true }
+    }
+}
+
+/// What the inspector shows of a mod, collected before drawing it
+struct ModRow {
+    info: ModInfo,
+    state: ModState,
+    schedules: Vec<ScheduleRow>,
+}
+
+struct ScheduleRow {
+    label: String,
+    systems: Vec<SystemRow>,
+}
+
+struct SystemRow {
+    name: String,
+
+    /// The average run time in milliseconds, if diagnostics are enabled
+    run_time: Option<f64>,
+}
+
+enum Action {
+    Reload(Entity),
+    Disable(Entity),
+}
+
+fn draw_inspector(world: &mut World, mods: &mut SystemState<Mods>) {
+    let Ok(mut context) = world
+        .query_filtered::<&mut EguiContext, With<PrimaryEguiContext>>()
+        .single_mut(world)
+    else {
+        return;
+    };
+    let context = context.get_mut().clone();
+
+    let rows = collect_rows(world);
+    let mut open = true;
+    let mut actions = Vec::new();
+    egui::Window::new("Mods")
+        .open(&mut open)
+        .show(&context, |ui| {
+            if rows.is_empty() {
+                ui.label("No mods are loaded");
+            }
+            egui::ScrollArea::vertical().show(ui, |ui| {
+                for row in &rows {
+                    draw_mod(ui, row, &mut actions);
+                }
+            });
+        });
+
+    world.resource_mut::<ModInspector>().open = open;
+
+    let mut mods_param = mods.get_mut(world).expect("valid system parameter");
+    for action in actions {
+        match action {
+            Action::Reload(mod_id) => mods_param.reload(mod_id),
+            Action::Disable(mod_id) => mods_param.disable(mod_id),
+        }
+    }
+    mods.apply(world);
+}
+
+fn draw_mod(ui: &mut egui::Ui, row: &ModRow, actions: &mut Vec<Action>) {
+    let ModRow {
+        info,
+        state,
+        schedules,
+    } = row;
+    let title = match &info.version {
+        Some(version) => format!("{} v{version}", info.name),
+        None => info.name.clone(),
+    };
+
+    egui::CollapsingHeader::new(title)
+        .id_salt(info.entity)
+        .show(ui, |ui| {
+            ui.horizontal(|ui| {
+                ui.label("State:");
+                ui.colored_label(state_color(*state), format!("{state:?}"));
+            });
+            ui.label(format!("Entity: {}", info.entity));
+            ui.label(format!("Access: {}", info.accesses.join(", ")));
+            if !info.capabilities.is_empty() {
+                ui.label(format!("Capabilities: {}", info.capabilities.join(", ")));
+            }
+
+            for schedule in schedules {
+                ui.collapsing(&schedule.label, |ui| {
+                    egui::Grid::new((info.entity, &schedule.label))
+                        .striped(true)
+                        .show(ui, |ui| {
+                            for system in &schedule.systems {
+                                ui.label(&system.name);
+                                match system.run_time {
+                                    Some(run_time) => ui.label(format!("{run_time:.3} ms")),
+                                    None => ui.label("-"),
+                                };
+                                ui.end_row();
+                            }
+                        });
+                });
+            }
+
+            ui.horizontal(|ui| {
+                if ui.button("Reload").clicked() {
+                    actions.push(Action::Reload(info.entity));
+                }
+                let enabled = !matches!(state, ModState::Disabled | ModState::Quarantined);
+                if ui
+                    .add_enabled(enabled, egui::Button::new("Disable"))
+                    .clicked()
+                {
+                    actions.push(Action::Disable(info.entity));
+                }
+            });
+        });
+}
+
+fn state_color(state: ModState) -> Color32 {
+    match state {
+        ModState::Ready => Color32::GREEN,
+        ModState::Loading | ModState::SettingUp => Color32::YELLOW,
+        ModState::Failed | ModState::Quarantined => Color32::RED,
+        ModState::Disabled | ModState::Unloading => Color32::GRAY,
+    }
+}
+
+fn collect_rows(world: &mut World) -> Vec<ModRow> {
+    let mods: Vec<(Entity, ModState)> = world
+        .query_filtered::<(Entity, &ModState), With<Mod>>()
+        .iter(world)
+        .map(|(mod_id, state)| (mod_id, *state))
+        .collect();
+
+    let mut rows: Vec<ModRow> = mods
+        .into_iter()
+        .filter_map(|(mod_id, state)| {
+            let info = ModInfo::new(mod_id, world)?;
+            let schedules = collect_schedules(mod_id, &info.name, world);
+            Some(ModRow {
+                info,
+                state,
+                schedules,
+            })
+        })
+        .collect();
+    rows.sort_by(|a, b| a.info.name.cmp(&b.info.name));
+    rows
+}
+
+/// Finds the systems a mod added to each of its schedules
+fn collect_schedules(mod_id: Entity, mod_name: &str, world: &World) -> Vec<ScheduleRow> {
+    let mod_component = world.get::<Mod>(mod_id).expect("Mod entity exists");
+    let schedules = world.resource::<Schedules>();
+    let diagnostics = world.get_resource::<DiagnosticsStore>();
+    let set = ModSystemSet::Mod(mod_id).intern();
+
+    let mut rows = Vec::new();
+    for access in mod_component.accesses() {
+        for mod_schedule in access.schedules(world).0 {
+            let label = mod_schedule.schedule_label();
+            let Some(schedule) = schedules.get(label) else {
+                continue;
+            };
+            // Schedules list their systems once they have run
+            let (Ok(keys), Ok(systems)) =
+                (schedule.graph().systems_in_set(set), schedule.systems())
+            else {
+                continue;
+            };
+
+            let mut systems: Vec<SystemRow> = systems
+                .filter(|(key, _)| keys.contains(key))
+                .map(|(_, system)| {
+                    let name = system.name().to_string();
+                    let run_time = diagnostics.and_then(|store| run_time(store, mod_name, &name));
+                    SystemRow { name, run_time }
+                })
+                .collect();
+            if systems.is_empty() {
+                continue;
+            }
+            systems.sort_by(|a, b| a.name.cmp(&b.name));
+
+            let label = match access {
+                ModAccess::World => format!("{label:?}"),
+                ModAccess::Sandbox(_) => format!("{label:?} ({})", access.display(world)),
+            };
+            rows.push(ScheduleRow { label, systems });
+        }
+    }
+    rows.sort_by(|a, b| a.label.cmp(&b.label));
+    rows
+}
+
+/// The average run time of a mod system, see [`crate::wasm::diagnostics`]
+#[cfg(feature = "wasm")]
+fn run_time(store: &DiagnosticsStore, mod_name: &str, system_name: &str) -> Option<f64> {
+    use crate::wasm::diagnostics::ModMetric;
+
+    // Mod systems are named `wasvy[<mod>]::<system>`
+    let system_name = system_name
+        .strip_prefix(&format!("wasvy[{mod_name}]::"))
+        .unwrap_or(system_name);
+    store
+        .get(&ModMetric::RunTime.path(mod_name, system_name))?
+        .average()
+}
+
+#[cfg(not(feature = "wasm"))]
+fn run_time(_: &DiagnosticsStore, _: &str, _: &str) -> Option<f64> {
+    None
+}
@@ -4,11 +4,13 @@
     html_favicon_url = "https://github.com/wasvy-org/wasvy/raw/main/assets/logo.png"
 )]
 
+#[cfg(feature = "inspector")]
+pub mod inspector;
 pub mod plugin;
 
 pub mod prelude {
     pub use crate::plugin::ModLoaderPlugin;
-    pub use wasvy_macros::WasvyComponent;
+    pub use wasvy_macros::{WasvyComponent, WasvyResource};
     pub use wasvy_runtime::prelude::*;
 }
 
@@ -16,12 +18,13 @@ pub mod runtime {
     pub use wasvy_runtime::*;
 }
 
-#[cfg(feature = "wasm")]
+#[cfg(any(feature = "wasm", feature = "web"))]
 pub mod wasm {
     pub use wasvy_wasm::*;
 }
 
 pub use wasvy_macros::{
-    WasvyComponent, auto_host_components, component, guest_bindings, guest_type_paths,
+    WasvyComponent, WasvyResource, auto_host_components, component, export_fn, guest_bindings,
+    guest_query, guest_systems, guest_type_paths, host_fn, host_interface,
     include_wasvy_components, methods, skip,
 };
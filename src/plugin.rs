@@ -1,16 +1,19 @@
-use std::sync::Mutex;
+use std::{num::NonZeroUsize, sync::Mutex};
+
+use bevy_app::{AppLabel, InternedAppLabel};
 use wasvy_runtime::app_extend::AppExtend;
 use wasvy_runtime::devtools;
 
 pub use wasvy_macros::WasvyComponent;
 pub use wasvy_runtime::prelude::*;
-#[cfg(feature = "wasm")]
+#[cfg(any(feature = "wasm", feature = "web"))]
 pub use wasvy_wasm::WasmBackendPlugin;
 
 /// This plugin adds Wasvy modding support to the [`bevy_app::App`].
 ///
 /// The high-level loader installs the runtime plugin and, when the `wasm`
-/// feature is enabled, the WASM backend.
+/// feature is enabled, the WASM backend. Browser builds can enable the `web`
+/// feature instead, which runs simple mods with the browser's WebAssembly API.
 ///
 /// ```no_run
 /// # use bevy_app::prelude::*;
@@ -72,16 +75,19 @@ pub use wasvy_wasm::WasmBackendPlugin;
 pub struct ModLoaderPlugin(Mutex<Option<Inner>>);
 
 struct Inner {
+    /// Installs the modloader into this sub-app instead, see [`ModLoaderPlugin::in_sub_app`]
+    sub_app: Option<InternedAppLabel>,
     runtime: ModRuntimePlugin,
-    #[cfg(feature = "wasm")]
+    #[cfg(any(feature = "wasm", feature = "web"))]
     wasm: WasmBackendPlugin,
 }
 
 impl Default for ModLoaderPlugin {
     fn default() -> Self {
         Self(Mutex::new(Some(Inner {
+            sub_app: None,
             runtime: Default::default(),
-            #[cfg(feature = "wasm")]
+            #[cfg(any(feature = "wasm", feature = "web"))]
             wasm: Default::default(),
         })))
     }
@@ -91,8 +97,9 @@ impl ModLoaderPlugin {
     /// Creates a new mod loader that runs mods during the provided schedules.
     pub fn new(schedules: ModSchedules) -> Self {
         Self(Mutex::new(Some(Inner {
+            sub_app: None,
             runtime: ModRuntimePlugin::new(schedules),
-            #[cfg(feature = "wasm")]
+            #[cfg(any(feature = "wasm", feature = "web"))]
             wasm: Default::default(),
         })))
     }
@@ -106,8 +113,9 @@ impl ModLoaderPlugin {
     /// [`ModLoaderPlugin::default`] or [`ModLoaderPlugin::new`].
     pub fn unscheduled() -> Self {
         Self(Mutex::new(Some(Inner {
+            sub_app: None,
             runtime: ModRuntimePlugin::unscheduled(),
-            #[cfg(feature = "wasm")]
+            #[cfg(any(feature = "wasm", feature = "web"))]
             wasm: Default::default(),
         })))
     }
@@ -137,10 +145,34 @@ impl ModLoaderPlugin {
         self
     }
 
+    /// Installs the modloader into a sub-app, such as a server world, instead of the main app.
+    ///
+    /// The sub-app must already exist when this plugin is added, and have its own
+    /// `AssetPlugin` to load mods with, as well as the schedules mods run in. Mods loaded in a
+    /// sub-app only see the entities of its world, and the resources Wasvy uses (such as the
+    /// wasm engine) are separate for each world. Add one modloader per world mods should run in.
+    ///
+    /// ```no_run
+    /// # use bevy_app::{prelude::*, AppLabel};
+    /// use wasvy::prelude::*;
+    ///
+    /// #[derive(AppLabel, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+    /// struct ServerApp;
+    ///
+    /// # let mut app = App::new();
+    /// # app.insert_sub_app(ServerApp, SubApp::new());
+    /// app.add_plugins(ModLoaderPlugin::default())
+    ///     .add_plugins(ModLoaderPlugin::default().in_sub_app(ServerApp));
+    /// ```
+    pub fn in_sub_app(mut self, label: impl AppLabel) -> Self {
+        self.inner().sub_app = Some(label.intern());
+        self
+    }
+
     /// Sets the despawn behaviour for when mods are despawned (or reloaded).
     ///
     /// The default behaviour is to despawn all entities the mod spawned.
-    /// See [`ModDespawnBehaviour::DespawnEntities`].
+    /// See [`ModDespawnBehaviour::DespawnWithMod`].
     pub fn set_despawn_behaviour(mut self, despawn_behaviour: ModDespawnBehaviour) -> Self {
         let inner = self.inner();
         inner.runtime = std::mem::take(&mut inner.runtime).set_despawn_behaviour(despawn_behaviour);
@@ -184,6 +216,195 @@ impl ModLoaderPlugin {
         self
     }
 
+    /// Caps how many mod systems may run concurrently in each schedule.
+    ///
+    /// Mod systems are assigned round-robin to `lanes` chains per schedule,
+    /// trading parallelism for predictable frame times. By default mod systems
+    /// are not capped.
+    ///
+    /// ```
+    /// # use std::num::NonZeroUsize;
+    /// # use wasvy::prelude::*;
+    /// let modloader = ModLoaderPlugin::default()
+    ///     .max_concurrent_systems(NonZeroUsize::new(2).unwrap());
+    /// # let _ = modloader;
+    /// ```
+    pub fn max_concurrent_systems(mut self, lanes: NonZeroUsize) -> Self {
+        let inner = self.inner();
+        inner.runtime = std::mem::take(&mut inner.runtime).max_concurrent_systems(lanes);
+        self
+    }
+
+    /// Bounds the number of systems and queries a single mod can register.
+    ///
+    /// ```
+    /// # use wasvy::prelude::*;
+    /// let modloader = ModLoaderPlugin::default().limits(ModLimits {
+    ///     max_systems_per_mod: 64,
+    ///     ..Default::default()
+    /// });
+    /// # let _ = modloader;
+    /// ```
+    pub fn limits(mut self, limits: ModLimits) -> Self {
+        let inner = self.inner();
+        inner.runtime = std::mem::take(&mut inner.runtime).limits(limits);
+        self
+    }
+
+    /// Decides when mods that keep trapping are quarantined.
+    ///
+    /// ```
+    /// # use wasvy::prelude::*;
+    /// let modloader = ModLoaderPlugin::default().failure_policy(ModFailurePolicy {
+    ///     max_consecutive_traps: Some(10),
+    /// });
+    /// # let _ = modloader;
+    /// ```
+    pub fn failure_policy(mut self, policy: ModFailurePolicy) -> Self {
+        let inner = self.inner();
+        inner.runtime = std::mem::take(&mut inner.runtime).failure_policy(policy);
+        self
+    }
+
+    /// Validates the params mods pass to component methods before invoking them, so mods get
+    /// precise errors about wrong-shaped params.
+    ///
+    /// Defaults to enabled in debug builds.
+    pub fn strict_params(mut self, strict: bool) -> Self {
+        let inner = self.inner();
+        inner.runtime = std::mem::take(&mut inner.runtime).strict_params(strict);
+        self
+    }
+
+    /// Defines the exports the host calls on mods, such as `on-save` and `on-load`.
+    ///
+    /// ```
+    /// # use wasvy::prelude::*;
+    /// let modloader = ModLoaderPlugin::default()
+    ///     .entry_points(ModEntryPoints::default().optional("on-save"));
+    /// # let _ = modloader;
+    /// ```
+    pub fn entry_points(mut self, entry_points: ModEntryPoints) -> Self {
+        let inner = self.inner();
+        inner.runtime = std::mem::take(&mut inner.runtime).entry_points(entry_points);
+        self
+    }
+
+    /// Lets mods order their systems before or after a host system set, referring to it as `name`.
+    ///
+    /// See [`ModRuntimePlugin::host_system_set`].
+    pub fn host_system_set(mut self, name: impl ToString, set: impl SystemSet) -> Self {
+        let inner = self.inner();
+        inner.runtime = std::mem::take(&mut inner.runtime).host_system_set(name, set);
+        self
+    }
+
+    /// Spreads the systems of mods across frames, running them within a time budget per frame.
+    ///
+    /// ```
+    /// # use std::time::Duration;
+    /// # use wasvy::prelude::*;
+    /// let modloader = ModLoaderPlugin::default()
+    ///     .frame_budgets(ModFrameBudgets::default().with_default(Duration::from_millis(4)));
+    /// # let _ = modloader;
+    /// ```
+    ///
+    /// See [`ModFrameBudgets`].
+    pub fn frame_budgets(mut self, budgets: ModFrameBudgets) -> Self {
+        let inner = self.inner();
+        inner.runtime = std::mem::take(&mut inner.runtime).frame_budgets(budgets);
+        self
+    }
+
+    /// Lets mods refer to a component or resource by `alias` instead of its full type path.
+    ///
+    /// See [`ModRuntimePlugin::alias`].
+    pub fn alias(mut self, alias: impl Into<String>, type_path: impl Into<String>) -> Self {
+        let inner = self.inner();
+        inner.runtime = std::mem::take(&mut inner.runtime).alias(alias, type_path);
+        self
+    }
+
+    /// Checks mods before they are compiled, such as verifying the signature of their publisher.
+    ///
+    /// See [`ModRuntimePlugin::set_verifier`].
+    pub fn set_verifier(mut self, verifier: ModVerifier) -> Self {
+        let inner = self.inner();
+        inner.runtime = std::mem::take(&mut inner.runtime).set_verifier(verifier);
+        self
+    }
+
+    /// Interrupts mods that run for longer than `timeout` in a single call, such as a system run.
+    ///
+    /// See [`WasmBackendPlugin::timeout`].
+    #[cfg(any(feature = "wasm", feature = "web"))]
+    pub fn timeout(mut self, timeout: std::time::Duration) -> Self {
+        let inner = self.inner();
+        inner.wasm = std::mem::take(&mut inner.wasm).timeout(timeout);
+        self
+    }
+
+    /// Limits the memory, tables and instances of every mod.
+    ///
+    /// See [`WasmBackendPlugin::memory_limits`].
+    #[cfg(any(feature = "wasm", feature = "web"))]
+    pub fn memory_limits(mut self, limits: wasvy_runtime::limits::ModMemoryLimits) -> Self {
+        let inner = self.inner();
+        inner.wasm = std::mem::take(&mut inner.wasm).memory_limits(limits);
+        self
+    }
+
+    /// Limits how many calls into a mod may be on the stack at once.
+    ///
+    /// See [`WasmBackendPlugin::max_call_depth`].
+    #[cfg(any(feature = "wasm", feature = "web"))]
+    pub fn max_call_depth(mut self, depth: usize) -> Self {
+        let inner = self.inner();
+        inner.wasm = std::mem::take(&mut inner.wasm).max_call_depth(depth);
+        self
+    }
+
+    /// Routes what mods print to stdout and stderr into Bevy's log.
+    ///
+    /// See [`WasmBackendPlugin::capture_output`].
+    #[cfg(any(feature = "wasm", feature = "web"))]
+    pub fn capture_output(mut self, capture: bool) -> Self {
+        let inner = self.inner();
+        inner.wasm = std::mem::take(&mut inner.wasm).capture_output(capture);
+        self
+    }
+
+    /// Records per-system diagnostics of mods, such as their run time and memory.
+    ///
+    /// See [`WasmBackendPlugin::diagnostics`].
+    #[cfg(any(feature = "wasm", feature = "web"))]
+    pub fn diagnostics(mut self, enabled: bool) -> Self {
+        let inner = self.inner();
+        inner.wasm = std::mem::take(&mut inner.wasm).diagnostics(enabled);
+        self
+    }
+
+    /// Limits how much each mod may compute per frame.
+    ///
+    /// This recreates the engine, so call it before [`Self::add_functionality`].
+    /// See [`WasmBackendPlugin::fuel_per_frame`].
+    #[cfg(any(feature = "wasm", feature = "web"))]
+    pub fn fuel_per_frame(mut self, fuel: u64) -> Self {
+        let inner = self.inner();
+        inner.wasm = std::mem::take(&mut inner.wasm).fuel_per_frame(fuel);
+        self
+    }
+
+    /// Caches compiled mods on disk, so they load faster the next time the app starts.
+    ///
+    /// See [`WasmBackendPlugin::cache`].
+    #[cfg(feature = "wasm")]
+    pub fn cache(mut self, cache: wasvy_wasm::cache::ModCache) -> Self {
+        let inner = self.inner();
+        inner.wasm = std::mem::take(&mut inner.wasm).cache(cache);
+        self
+    }
+
     /// Use this function to add custom functionality that will be passed to WASM modules.
     ///
     /// This is only available when the `wasm` feature is enabled.
@@ -208,19 +429,37 @@ impl ModLoaderPlugin {
 
 impl bevy_app::Plugin for ModLoaderPlugin {
     fn build(&self, app: &mut bevy_app::App) {
-        let Inner {
-            runtime,
-            #[cfg(feature = "wasm")]
-            wasm,
-        } = self
+        let mut inner = self
             .0
             .lock()
             .expect("ModLoaderPlugin is not locked")
             .take()
             .expect("ModLoaderPlugin is not built");
 
+        if let Some(label) = inner.sub_app.take() {
+            app.get_sub_app_mut(label)
+                .unwrap_or_else(|| panic!("Sub-app {label:?} must exist to install the modloader"))
+                .add_plugins(Self(Mutex::new(Some(inner))));
+            return;
+        }
+
+        let Inner {
+            runtime,
+            #[cfg(any(feature = "wasm", feature = "web"))]
+            wasm,
+            ..
+        } = inner;
         app.try_add_plugin(runtime);
-        #[cfg(feature = "wasm")]
+        #[cfg(any(feature = "wasm", feature = "web"))]
         app.try_add_plugin(wasm);
     }
+
+    /// One modloader may be added for the main app, and one for each sub-app
+    fn is_unique(&self) -> bool {
+        self.0
+            .lock()
+            .ok()
+            .and_then(|inner| inner.as_ref().map(|inner| inner.sub_app.is_none()))
+            .unwrap_or(true)
+    }
 }
@@ -1,6 +1,6 @@
 use bevy_app::App;
 use bevy_ecs::component::Component;
-use bevy_ecs::prelude::{AppTypeRegistry, ReflectComponent};
+use bevy_ecs::prelude::{AppTypeRegistry, ReflectComponent, ReflectResource, Resource};
 use bevy_ecs::reflect::AppFunctionRegistry;
 use bevy_reflect::{Reflect, TypePath};
 
@@ -33,6 +33,11 @@ impl Health {
     }
 }
 
+#[wasvy::export_fn(Health)]
+fn drain(health: &mut Health, amount: f32) {
+    health.current = (health.current - amount).max(0.0);
+}
+
 fn new_app() -> App {
     let mut app = App::new();
     app.add_plugins(AutoRegistrationPlugin);
@@ -366,3 +371,315 @@ fn invoke_errors_on_bad_json() {
         "unexpected error: {err}"
     );
 }
+
+#[test]
+fn invoke_errors_name_the_wrong_param() {
+    let app = new_app();
+
+    let mut health = Health {
+        current: 1.0,
+        max: 2.0,
+    };
+
+    let type_registry = app
+        .world()
+        .get_resource::<AppTypeRegistry>()
+        .expect("AppTypeRegistry to exist");
+    let codec = app
+        .world()
+        .get_resource::<CodecResource>()
+        .expect("CodecResource to exist");
+    let function_registry = app
+        .world()
+        .get_resource::<AppFunctionRegistry>()
+        .expect("AppFunctionRegistry to exist");
+    let mut index = FunctionIndex::build(type_registry, function_registry);
+    index.set_strict_params(true);
+
+    let err = index
+        .invoke(
+            Health::type_path(),
+            "heal",
+            MethodTarget::Write(&mut health),
+            br#"["five"]"#,
+            type_registry,
+            codec,
+        )
+        .unwrap_err();
+
+    assert_eq!(
+        err.to_string(),
+        format!(
+            "Method {}::heal argument 0 (amount) expects f32 but received string",
+            Health::type_path()
+        )
+    );
+
+    let err = index
+        .invoke(
+            Health::type_path(),
+            "heal",
+            MethodTarget::Write(&mut health),
+            b"[1.0, 2.0]",
+            type_registry,
+            codec,
+        )
+        .unwrap_err();
+
+    assert!(
+        err.to_string().contains("expects 1 args but received 2"),
+        "unexpected error: {err}"
+    );
+}
+
+#[derive(Component, Reflect, Default, WasvyComponent)]
+#[reflect(Component)]
+#[wasvy(level = "internal")]
+struct DebugOverlay;
+
+#[test]
+fn export_level_attribute_hides_internal_components() {
+    let app = new_app();
+
+    let type_registry = app
+        .world()
+        .get_resource::<AppTypeRegistry>()
+        .expect("AppTypeRegistry to exist");
+    let function_registry = app
+        .world()
+        .get_resource::<AppFunctionRegistry>()
+        .expect("AppFunctionRegistry to exist");
+
+    let index = FunctionIndex::build(type_registry, function_registry);
+    assert!(index.components().any(|c| c == Health::type_path()));
+    assert!(!index.components().any(|c| c == DebugOverlay::type_path()));
+
+    let index =
+        FunctionIndex::build_with_level(type_registry, function_registry, ExportLevel::Internal);
+    assert!(index.components().any(|c| c == DebugOverlay::type_path()));
+}
+
+#[test]
+fn export_fn_registers_free_functions() {
+    let app = new_app();
+
+    let type_registry = app
+        .world()
+        .get_resource::<AppTypeRegistry>()
+        .expect("AppTypeRegistry to exist");
+    let codec = app
+        .world()
+        .get_resource::<CodecResource>()
+        .expect("CodecResource to exist");
+    let function_registry = app
+        .world()
+        .get_resource::<AppFunctionRegistry>()
+        .expect("AppFunctionRegistry to exist");
+    let index = FunctionIndex::build(type_registry, function_registry);
+
+    let mut health = Health {
+        current: 5.0,
+        max: 10.0,
+    };
+    index
+        .invoke(
+            Health::type_path(),
+            "drain",
+            MethodTarget::Write(&mut health),
+            b"[2.0]",
+            type_registry,
+            codec,
+        )
+        .unwrap();
+    assert_eq!(health.current, 3.0);
+
+    let settings = WitGeneratorSettings::default();
+    let output = generate_wit(&settings, type_registry, function_registry);
+    assert!(output.contains("drain: func(amount: f32)"), "{output}");
+}
+
+#[derive(Resource, Reflect, Default, WasvyResource)]
+#[reflect(Resource)]
+struct Score {
+    total: u32,
+}
+
+#[wasvy::methods]
+impl Score {
+    fn add(&mut self, points: u32) {
+        self.total += points;
+    }
+}
+
+#[test]
+fn resources_export_methods() {
+    let app = new_app();
+
+    let type_registry = app.world().resource::<AppTypeRegistry>();
+    let codec = app.world().resource::<CodecResource>();
+    let function_registry = app.world().resource::<AppFunctionRegistry>();
+    let index = FunctionIndex::build(type_registry, function_registry);
+    assert!(index.is_resource(Score::type_path()));
+    assert!(!index.is_resource(Health::type_path()));
+
+    let mut score = Score { total: 1 };
+    index
+        .invoke(
+            Score::type_path(),
+            "add",
+            MethodTarget::Write(&mut score),
+            b"[2]",
+            type_registry,
+            codec,
+        )
+        .unwrap();
+    assert_eq!(score.total, 3);
+
+    let settings = WitGeneratorSettings::default();
+    let output = generate_wit(&settings, type_registry, function_registry);
+    assert!(
+        output.contains("resource score {\n    constructor(res: res);"),
+        "{output}"
+    );
+    assert!(output.contains("add: func(points: u32)"), "{output}");
+}
+
+static GOLD: std::sync::atomic::AtomicU32 = std::sync::atomic::AtomicU32::new(0);
+
+/// Gives the player gold
+#[wasvy::host_fn]
+fn give_gold(amount: u32) -> u32 {
+    GOLD.fetch_add(amount, std::sync::atomic::Ordering::Relaxed) + amount
+}
+
+#[test]
+fn host_fns_are_invoked_without_a_receiver() {
+    let app = new_app();
+
+    let type_registry = app.world().resource::<AppTypeRegistry>();
+    let codec = app.world().resource::<CodecResource>();
+    let function_registry = app.world().resource::<AppFunctionRegistry>();
+    let index = FunctionIndex::build(type_registry, function_registry);
+
+    let output = index
+        .invoke_host_fn("give-gold", b"[5]", type_registry, codec)
+        .unwrap();
+    assert_eq!(output, b"5");
+    assert!(index.get_host_fn("give_gold").is_some());
+
+    let settings = WitGeneratorSettings::default();
+    let output = generate_wit(&settings, type_registry, function_registry);
+    assert!(
+        output.contains("  /// Gives the player gold\n  give-gold: func(amount: u32) -> u32;"),
+        "{output}"
+    );
+}
+
+#[test]
+fn export_methods_of_external_types() {
+    use bevy_math::Vec3;
+    use bevy_transform::components::Transform;
+
+    let mut app = new_app();
+    app.export_methods::<Transform>(|methods| {
+        methods
+            .method("translation", |transform: &Transform| transform.translation)
+            .method("translate", |transform: &mut Transform, offset: Vec3| {
+                transform.translation += offset;
+            })
+            .args(&["offset"])
+            .static_method("from_xyz", Transform::from_xyz)
+            .args(&["x", "y", "z"])
+            .docs("Creates a transform at a position");
+    });
+
+    let type_registry = app.world().resource::<AppTypeRegistry>();
+    let codec = app.world().resource::<CodecResource>();
+    let function_registry = app.world().resource::<AppFunctionRegistry>();
+    let index = FunctionIndex::build(type_registry, function_registry);
+
+    let mut transform = Transform::from_xyz(1.0, 0.0, 0.0);
+    index
+        .invoke(
+            Transform::type_path(),
+            "translate",
+            MethodTarget::Write(&mut transform),
+            br#"[{"x": 1.0, "y": 2.0, "z": 0.0}]"#,
+            type_registry,
+            codec,
+        )
+        .unwrap();
+    assert_eq!(transform.translation, Vec3::new(2.0, 2.0, 0.0));
+
+    let output = index
+        .invoke_static(
+            Transform::type_path(),
+            "from_xyz",
+            b"[1.0, 2.0, 3.0]",
+            type_registry,
+            codec,
+        )
+        .unwrap();
+    assert!(
+        String::from_utf8(output)
+            .unwrap()
+            .contains("\"translation\"")
+    );
+
+    let settings = WitGeneratorSettings::default();
+    let output = generate_wit(&settings, type_registry, function_registry);
+    assert!(output.contains("resource transform {"), "{output}");
+    assert!(output.contains("translate: func(offset: vec3)"), "{output}");
+    assert!(output.contains("    /// Creates a transform at a position\n    from-xyz: static func(x: f32, y: f32, z: f32) -> transform-data"), "{output}");
+}
+
+#[test]
+fn export_components_of_other_crates() {
+    use bevy_transform::components::{GlobalTransform, Transform};
+
+    let mut app = new_app();
+    app.export_component::<Transform>()
+        .export_component_with::<GlobalTransform>(WasvyExport {
+            name: Some("world-transform".into()),
+            ..Default::default()
+        });
+    assert!(
+        app.export_component_by_path("unknown::Type", WasvyExport::default())
+            .is_err()
+    );
+
+    let type_registry = app.world().resource::<AppTypeRegistry>();
+    let function_registry = app.world().resource::<AppFunctionRegistry>();
+    let index = FunctionIndex::build(type_registry, function_registry);
+    assert!(index.get(Transform::type_path(), "translation").is_none());
+
+    let settings = WitGeneratorSettings::default();
+    let output = generate_wit(&settings, type_registry, function_registry);
+    assert!(output.contains("resource transform {"), "{output}");
+    assert!(output.contains("resource world-transform {"), "{output}");
+}
+
+#[test]
+fn lists_exported_components() {
+    use wasvy_runtime::component::TypeAliasRegistry;
+    use wasvy_runtime::registry::list_components;
+
+    let app = new_app();
+    let type_registry = app.world().resource::<AppTypeRegistry>();
+    let function_registry = app.world().resource::<AppFunctionRegistry>();
+    let index = FunctionIndex::build(type_registry, function_registry);
+    let mut aliases = TypeAliasRegistry::default();
+    aliases.insert("Hp", Health::type_path());
+
+    let components = list_components(&index, &type_registry.read(), &aliases);
+    let health = components
+        .iter()
+        .find(|info| info.type_path == Health::type_path())
+        .unwrap();
+    assert_eq!(health.short_name, "Hp");
+    assert!(health.has_methods);
+    assert_eq!(
+        health.fields.as_deref(),
+        Some(&["current".to_string(), "max".to_string()][..])
+    );
+}
@@ -250,7 +250,7 @@ mod dev {
         );
         assert!(
             !has_example_name(&mut world),
-            "ModDespawnBehaviour::DespawnEntities cleanup"
+            "ModDespawnBehaviour::DespawnWithMod cleanup"
         );
         assert!(dev.is_finished(), "`wasvy dev` stops after 1 update")
     }
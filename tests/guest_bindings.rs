@@ -18,3 +18,21 @@ fn guest_bindings_includes_type_path_helpers() {
     );
     assert_eq!(Health::type_path(), Health::type_path_str().to_string());
 }
+
+#[test]
+fn guest_bindings_convert_math_types() {
+    use bevy_math::{Mat4, Quat, Vec3};
+    use bevy_transform::components::Transform;
+    use bindings::game::components::components as wit;
+
+    let translation: wit::Vec3 = [1.0, 2.0, 3.0].into();
+    assert_eq!(Vec3::from(translation), Vec3::new(1.0, 2.0, 3.0));
+    assert_eq!(<[f32; 3]>::from(wit::Vec3::from(Vec3::X)), [1.0, 0.0, 0.0]);
+
+    let transform = Transform::from_xyz(1.0, 2.0, 3.0).with_rotation(Quat::from_rotation_y(1.0));
+    let record = wit::Transform::from(transform);
+    assert_eq!(Transform::from(record), transform);
+
+    let matrix = transform.to_matrix();
+    assert_eq!(Mat4::from(wit::Mat4::from(matrix)), matrix);
+}
@@ -0,0 +1,72 @@
+mod bindings {
+    ::wasvy::guest_bindings!({
+        path: ["wit/wasvy-ecs.wit", "tests/fixtures/guest_systems"],
+        world: "test:systems/example",
+        with: {
+            "wasvy:ecs/app@0.0.9": generate,
+        }
+    });
+}
+
+use bindings::wasvy::ecs::app::{Component, Query, QueryFor};
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize)]
+struct Health {
+    current: f32,
+}
+
+impl Health {
+    const TYPE_PATH: &str = "my_game::Health";
+}
+
+#[derive(Serialize, Deserialize)]
+struct Transform;
+
+struct Marker;
+
+impl Marker {
+    const TYPE_PATH: &str = "my_game::Marker";
+}
+
+wasvy::guest_query! {
+    /// A typed query
+    struct Heal {
+        health: mut Health,
+        transform: Transform = "bevy_transform::components::transform::Transform",
+        with Marker,
+        without "my_game::Dead",
+    }
+}
+
+#[test]
+fn guest_query_params() {
+    let params: Vec<String> = Heal::query_for()
+        .into_iter()
+        .map(|param| match param {
+            QueryFor::Ref(path) => format!("ref {path}"),
+            QueryFor::Mut(path) => format!("mut {path}"),
+            QueryFor::With(path) => format!("with {path}"),
+            QueryFor::Without(path) => format!("without {path}"),
+            _ => unreachable!(),
+        })
+        .collect();
+    assert_eq!(
+        params,
+        [
+            "mut my_game::Health",
+            "ref bevy_transform::components::transform::Transform",
+            "with my_game::Marker",
+            "without my_game::Dead",
+        ]
+    );
+}
+
+// Results are resources of the host, so systems can only be compiled natively
+#[allow(dead_code)]
+fn heal_system(query: Query) {
+    for mut result in Heal::iter(&query) {
+        result.health.current += 1.0;
+        result.set();
+    }
+}
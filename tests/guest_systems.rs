@@ -0,0 +1,39 @@
+mod bindings {
+    ::wasvy::guest_bindings!({
+        path: ["wit/wasvy-ecs.wit", "tests/fixtures/guest_systems"],
+        world: "test:systems/example",
+        with: {
+            "wasvy:ecs/app@0.0.9": generate,
+        }
+    });
+}
+
+use bindings::Guest;
+use bindings::wasvy::ecs::app::{
+    App, Commands, Entity, EventReader, Mutability, Query, QueryFor, Res, System,
+};
+
+#[allow(dead_code)]
+struct GuestComponent;
+
+#[wasvy::guest_systems]
+impl Guest for GuestComponent {
+    fn setup(_app: App) {}
+
+    fn spawn_enemies(_commands: Commands) {}
+
+    fn heal_system(_query: Query, _events: EventReader) {}
+
+    fn on_hit(_entity: Entity, _query: Query) {}
+
+    fn tick_score(_score: Res) {}
+}
+
+#[test]
+fn guest_systems_match_exports() {
+    // Systems are resources of the host, so only their signatures can be checked natively
+    let _: fn() -> System = systems::spawn_enemies;
+    let _: fn(&[QueryFor], &str) -> System = systems::heal_system;
+    let _: fn(&[QueryFor]) -> System = systems::on_hit;
+    let _: fn(&str, Mutability) -> System = systems::tick_score;
+}
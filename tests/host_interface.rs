@@ -0,0 +1,36 @@
+use wasvy_wasm::engine::Linker;
+
+wasvy::host_interface! {
+    path = "tests/fixtures/host_interface",
+    world = "game:api/host",
+    interface = "economy",
+    module = economy_bindings,
+    functions = EconomyFunctions,
+}
+
+use economy_bindings::game::api::economy::{Currency, Purchase};
+
+#[test]
+fn host_interface_registers_linker() {
+    let economy = EconomyFunctions::default()
+        .give_gold(|ctx, amount| {
+            let _ = (ctx.mod_name(), amount);
+            Ok(())
+        })
+        .balance(|_ctx, currency| {
+            Ok(match currency {
+                Currency::Gold => 100,
+                Currency::Gems => 5,
+            })
+        })
+        .buy(|_ctx, Purchase { item, price }| {
+            if price > 100 {
+                return Ok(Err(format!("Can't afford {item}")));
+            }
+            Ok(Ok(Some(item)))
+        });
+
+    let engine = wasmtime::Engine::default();
+    let mut linker: Linker = Linker::new(&engine);
+    economy.add_to_linker(&mut linker).unwrap();
+}
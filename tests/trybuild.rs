@@ -3,3 +3,9 @@ fn wasvy_methods_ui() {
     let t = trybuild::TestCases::new();
     t.compile_fail("tests/ui/wasvy_methods/*.rs");
 }
+
+#[test]
+fn guest_systems_ui() {
+    let t = trybuild::TestCases::new();
+    t.compile_fail("tests/ui/guest_systems/*.rs");
+}
@@ -0,0 +1,12 @@
+trait Guest {
+    fn heal_system(amount: f32);
+}
+
+struct GuestComponent;
+
+#[wasvy::guest_systems]
+impl Guest for GuestComponent {
+    fn heal_system(amount: f32) {}
+}
+
+fn main() {}
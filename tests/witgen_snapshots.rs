@@ -0,0 +1,384 @@
+//! Snapshot tests for the generated WIT.
+//!
+//! Each test renders the WIT for the fixtures below, checks that it parses along with the
+//! `wasvy:ecs` package, and compares it against a file in `tests/snapshots`. When the generator changes on purpose, re-run the tests with
+//! `WASVY_UPDATE_SNAPSHOTS=1` and review the snapshot diff like any other change.
+
+use std::{env, fs, path::PathBuf};
+
+use bevy_app::App;
+use bevy_ecs::component::Component;
+use bevy_ecs::prelude::{AppFunctionRegistry, AppTypeRegistry, ReflectComponent};
+use bevy_math::Vec3;
+use bevy_reflect::Reflect;
+
+use wasvy::prelude::*;
+use wasvy_runtime::witgen::generate_wit;
+use wit_parser::Resolve;
+
+/// A component with plain scalar methods
+#[derive(Component, Reflect, Default, WasvyComponent)]
+#[reflect(Component)]
+struct Health {
+    current: f32,
+    max: f32,
+}
+
+#[wasvy::methods]
+impl Health {
+    fn full(max: f32) -> Health {
+        Health { current: max, max }
+    }
+
+    /// Heals by `amount`, up to the max health.
+    ///
+    /// Dead entities can be healed too.
+    fn heal(&mut self, amount: f32) {
+        self.current = (self.current + amount).min(self.max);
+    }
+
+    fn pct(&self) -> f32 {
+        self.current / self.max
+    }
+
+    fn is_dead(&self) -> bool {
+        self.current <= 0.0
+    }
+}
+
+/// A component with the same name as [Health], so its resource name must be disambiguated
+mod alt {
+    use super::*;
+
+    #[derive(Component, Reflect, Default, WasvyComponent)]
+    #[reflect(Component)]
+    pub struct Health {
+        pub shield: u32,
+    }
+
+    #[wasvy::methods]
+    impl Health {
+        fn absorb(&mut self, damage: u32) -> u32 {
+            let absorbed = damage.min(self.shield);
+            self.shield -= absorbed;
+            damage - absorbed
+        }
+    }
+}
+
+/// A component with generic options and vecs in its signatures
+#[derive(Component, Reflect, Default, WasvyComponent)]
+#[reflect(Component)]
+struct Inventory {
+    items: Vec<String>,
+    equipped: Option<u8>,
+}
+
+#[wasvy::methods]
+impl Inventory {
+    fn items(&self) -> Vec<String> {
+        self.items.clone()
+    }
+
+    fn equipped(&self) -> Option<u8> {
+        self.equipped
+    }
+
+    fn equip(&mut self, slot: Option<u8>) {
+        self.equipped = slot;
+    }
+
+    fn add_all(&mut self, items: Vec<String>, counts: Vec<Option<i64>>) -> u32 {
+        for (item, count) in items.into_iter().zip(counts) {
+            for _ in 0..count.unwrap_or(1) {
+                self.items.push(item.clone());
+            }
+        }
+        self.items.len() as u32
+    }
+}
+
+/// An enum component
+#[derive(Component, Reflect, Default, WasvyComponent)]
+#[reflect(Component)]
+enum Stance {
+    #[default]
+    Idle,
+    Running {
+        speed: f64,
+    },
+}
+
+#[wasvy::methods]
+impl Stance {
+    fn is_moving(&self) -> bool {
+        matches!(self, Stance::Running { .. })
+    }
+
+    fn run(&mut self, speed: f64) {
+        *self = Stance::Running { speed };
+    }
+}
+
+/// A struct passed to methods as a record
+#[derive(Reflect, Default)]
+struct Waypoint {
+    position: Vec3,
+    wait_secs: f32,
+}
+
+/// A component with records in its signatures
+#[derive(Component, Reflect, Default, WasvyComponent)]
+#[reflect(Component)]
+struct Steering {
+    target: Vec3,
+    waypoints: Vec<Waypoint>,
+}
+
+#[wasvy::methods]
+impl Steering {
+    fn set_target(&mut self, pos: Vec3) {
+        self.target = pos;
+    }
+
+    fn add_waypoint(&mut self, waypoint: Waypoint) -> u32 {
+        self.waypoints.push(waypoint);
+        self.waypoints.len() as u32
+    }
+}
+
+/// A unit enum passed to methods as a WIT enum
+#[derive(Reflect, Default)]
+enum Formation {
+    #[default]
+    Line,
+    Wedge,
+}
+
+/// A data-carrying enum passed to methods as a WIT variant
+#[derive(Reflect, Default)]
+enum Order {
+    #[default]
+    Hold,
+    MoveTo(Vec3),
+    Patrol {
+        from: Vec3,
+        to: Vec3,
+    },
+    Wait(f32, f32),
+}
+
+/// A component with enums in its signatures
+#[derive(Component, Reflect, Default, WasvyComponent)]
+#[reflect(Component)]
+struct Squad {
+    formation: Formation,
+    order: Order,
+}
+
+#[wasvy::methods]
+impl Squad {
+    fn formation(&self) -> Formation {
+        match self.formation {
+            Formation::Line => Formation::Line,
+            Formation::Wedge => Formation::Wedge,
+        }
+    }
+
+    fn command(&mut self, order: Order) {
+        self.order = order;
+    }
+}
+
+/// A component with fallible methods
+#[derive(Component, Reflect, Default, WasvyComponent)]
+#[reflect(Component)]
+struct Wallet {
+    gold: u32,
+}
+
+#[wasvy::methods]
+impl Wallet {
+    fn spend(&mut self, amount: u32) -> Result<u32, String> {
+        self.gold = self.gold.checked_sub(amount).ok_or("Not enough gold")?;
+        Ok(self.gold)
+    }
+
+    fn clear(&mut self) -> Result<(), String> {
+        self.gold = 0;
+        Ok(())
+    }
+}
+
+/// A component without methods
+#[derive(Component, Reflect, Default, WasvyComponent)]
+#[reflect(Component)]
+struct PlayerMarker;
+
+/// A component that only appears in internal WIT
+#[derive(Component, Reflect, Default, WasvyComponent)]
+#[reflect(Component)]
+#[wasvy(level = "internal")]
+struct DebugOverlay {
+    visible: bool,
+}
+
+#[wasvy::methods]
+impl DebugOverlay {
+    fn toggle(&mut self) {
+        self.visible = !self.visible;
+    }
+}
+
+/// A component exported under another name, in its own interface
+#[derive(Component, Reflect, Default, WasvyComponent)]
+#[reflect(Component)]
+#[wasvy(rename = "plating", interface = "combat")]
+struct Armor {
+    rating: u32,
+}
+
+#[wasvy::methods]
+impl Armor {
+    fn rating(&self) -> u32 {
+        self.rating
+    }
+}
+
+/// A component that is left out of the WIT
+#[derive(Component, Reflect, Default, WasvyComponent)]
+#[reflect(Component)]
+#[wasvy(skip_export)]
+struct Scratch;
+
+/// Renders the WIT for `settings`, checking that it parses
+fn render(name: &str, settings: &WitGeneratorSettings) -> String {
+    let mut app = App::new();
+    app.add_plugins(AutoRegistrationPlugin)
+        .register_type::<Vec3>()
+        .register_type::<Waypoint>()
+        .register_type::<Formation>()
+        .register_type::<Order>();
+
+    let type_registry = app.world().resource::<AppTypeRegistry>();
+    let function_registry = app.world().resource::<AppFunctionRegistry>();
+    let wit = generate_wit(settings, type_registry, function_registry);
+    assert_valid(name, settings, &wit);
+    wit
+}
+
+/// Checks that the WIT generated with `settings` parses, with `wasvy:ecs` as the package it uses
+fn assert_valid(name: &str, settings: &WitGeneratorSettings, wit: &str) {
+    let wasvy_ecs = include_str!("../wit/wasvy-ecs.wit")
+        .lines()
+        .map(|line| {
+            if line.starts_with("package ") {
+                format!("package {};", settings.wasvy_package)
+            } else {
+                line.to_string()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+    let mut resolve = Resolve::new();
+    resolve
+        .push_str("wasvy-ecs.wit", &wasvy_ecs)
+        .expect("wasvy:ecs parses");
+    if let Err(err) = resolve.push_str(format!("{name}.wit"), wit) {
+        panic!("Generated WIT for {name} is invalid: {err:?}\n{wit}");
+    }
+}
+
+/// Compares `actual` against the snapshot called `name`.
+///
+/// Missing snapshots fail the test, `WASVY_UPDATE_SNAPSHOTS=1` writes or overwrites them.
+fn assert_snapshot(name: &str, actual: &str) {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/snapshots")
+        .join(format!("{name}.wit"));
+
+    if env::var_os("WASVY_UPDATE_SNAPSHOTS").is_some() {
+        fs::create_dir_all(path.parent().unwrap()).expect("create snapshot dir");
+        fs::write(&path, actual).expect("write snapshot");
+        return;
+    }
+
+    let Ok(expected) = fs::read_to_string(&path) else {
+        panic!(
+            "Missing snapshot {path:?}. Generate it with WASVY_UPDATE_SNAPSHOTS=1 and commit it"
+        );
+    };
+    if expected != actual {
+        panic!(
+            "Snapshot {name} does not match the generated WIT:\n{}\nRe-run with WASVY_UPDATE_SNAPSHOTS=1 to accept the changes",
+            diff(&expected, actual)
+        );
+    }
+}
+
+/// A minimal line diff, enough to review snapshot changes in the test output
+fn diff(expected: &str, actual: &str) -> String {
+    let expected: Vec<&str> = expected.lines().collect();
+    let actual: Vec<&str> = actual.lines().collect();
+
+    let mut out = String::new();
+    for i in 0..expected.len().max(actual.len()) {
+        match (expected.get(i), actual.get(i)) {
+            (Some(a), Some(b)) if a == b => out.push_str(&format!("  {a}\n")),
+            (a, b) => {
+                if let Some(a) = a {
+                    out.push_str(&format!("- {a}\n"));
+                }
+                if let Some(b) = b {
+                    out.push_str(&format!("+ {b}\n"));
+                }
+            }
+        }
+    }
+    out
+}
+
+#[test]
+fn default_settings() {
+    let output = render("default_settings", &WitGeneratorSettings::default());
+    assert_snapshot("default_settings", &output);
+}
+
+#[test]
+fn internal_export_level() {
+    let output = render(
+        "internal_export_level",
+        &WitGeneratorSettings {
+            export_level: ExportLevel::Internal,
+            ..Default::default()
+        },
+    );
+    assert_snapshot("internal_export_level", &output);
+}
+
+#[test]
+fn custom_package() {
+    let output = render(
+        "custom_package",
+        &WitGeneratorSettings {
+            package: "acme:dungeon".to_string(),
+            component_interface: "dungeon-components".to_string(),
+            world: "dungeon".to_string(),
+            wasvy_package: "wasvy:ecs".to_string(),
+            ..Default::default()
+        },
+    );
+    assert_snapshot("custom_package", &output);
+}
+
+#[test]
+fn field_accessors() {
+    let output = render(
+        "field_accessors",
+        &WitGeneratorSettings {
+            field_accessors: true,
+            ..Default::default()
+        },
+    );
+    assert_snapshot("field_accessors", &output);
+}